@@ -0,0 +1,632 @@
+//! 保存データの暗号化設定
+//!
+//! 暗号化モードと鍵の取得方法を SQLite に保存し、トークンの暗号化・鍵の切り替えを行う。
+//! 会話履歴の再暗号化は `chat_history::ConversationManager::reencrypt_all` が担当する。
+
+use crate::{default_db_path, get_default_data_dir, open_database};
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::{params, Connection};
+use secret_store::{EncryptionKey, FileKeyStore, KeyStore};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// パスフレーズモードで参照する環境変数
+pub const PASSPHRASE_ENV: &str = "NEKO_ASSISTANT_PASSPHRASE";
+
+/// OS キーリングに保存するときのサービス名・ユーザー名
+const KEYRING_SERVICE: &str = "neko-assistant";
+const KEYRING_USER: &str = "storage-key";
+/// 切り替え途中の新しい鍵を置くキーリングのユーザー名
+const KEYRING_PENDING_USER: &str = "storage-key-pending";
+
+/// 確定済みの設定と、切り替え途中の設定を置くテーブル
+const SETTINGS_TABLE: &str = "encryption_settings";
+const PENDING_TABLE: &str = "encryption_pending";
+
+/// 鍵の正しさを確認するための既知の平文
+const VERIFIER_PLAINTEXT: &str = "neko-assistant";
+
+/// 暗号化モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncryptionMode {
+    /// 暗号化しない
+    #[default]
+    Disabled,
+    /// OS のキーリングに保存した鍵を使う
+    Keyring,
+    /// パスフレーズから鍵を導出する
+    Passphrase,
+    /// 鍵ファイルを使う（キーリングが無い環境向け）
+    File,
+}
+
+impl EncryptionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Keyring => "keyring",
+            Self::Passphrase => "passphrase",
+            Self::File => "file",
+        }
+    }
+}
+
+impl fmt::Display for EncryptionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EncryptionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "disabled" | "none" | "off" => Ok(Self::Disabled),
+            "keyring" => Ok(Self::Keyring),
+            "passphrase" => Ok(Self::Passphrase),
+            "file" => Ok(Self::File),
+            other => Err(anyhow!("Unknown encryption mode: {}", other)),
+        }
+    }
+}
+
+/// 暗号化設定（鍵そのものは含まない）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncryptionSettings {
+    pub mode: EncryptionMode,
+    /// パスフレーズモードの鍵導出用ソルト
+    pub salt: Option<Vec<u8>>,
+    /// ファイルモードの鍵ファイル（None なら既定パス）
+    pub key_file: Option<PathBuf>,
+    /// 既知の平文を暗号化した値。誤った鍵・パスフレーズの検出に使う
+    pub verifier: Option<String>,
+}
+
+impl EncryptionSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.mode != EncryptionMode::Disabled
+    }
+
+    /// 鍵の保管先（キーリング・ファイルモードのみ）
+    fn key_store(&self) -> Result<Option<Box<dyn KeyStore>>> {
+        self.key_store_for(false)
+    }
+
+    /// 切り替え途中の新しい鍵の保管先（確定済みの鍵とは別の場所）
+    fn pending_key_store(&self) -> Result<Option<Box<dyn KeyStore>>> {
+        self.key_store_for(true)
+    }
+
+    fn key_store_for(&self, pending: bool) -> Result<Option<Box<dyn KeyStore>>> {
+        match self.mode {
+            EncryptionMode::Keyring => keyring_store(if pending {
+                KEYRING_PENDING_USER
+            } else {
+                KEYRING_USER
+            })
+            .map(Some),
+            EncryptionMode::File => {
+                let path = self.key_file.clone().unwrap_or_else(default_key_file);
+                let path = if pending {
+                    path.with_extension("pending.key")
+                } else {
+                    path
+                };
+                Ok(Some(Box::new(FileKeyStore::new(path))))
+            }
+            EncryptionMode::Disabled | EncryptionMode::Passphrase => Ok(None),
+        }
+    }
+
+    fn verify(&self, key: &EncryptionKey) -> Result<()> {
+        if let Some(verifier) = &self.verifier {
+            let plaintext = secret_store::decrypt_string(key, verifier)
+                .map_err(|_| anyhow!("Encryption key does not match (wrong passphrase?)"))?;
+            if plaintext != VERIFIER_PLAINTEXT {
+                bail!("Encryption key does not match (wrong passphrase?)");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 既定の鍵ファイルパス
+pub fn default_key_file() -> PathBuf {
+    get_default_data_dir().join("keys").join("storage.key")
+}
+
+#[cfg(feature = "os-keyring")]
+fn keyring_store(user: &str) -> Result<Box<dyn KeyStore>> {
+    Ok(Box::new(secret_store::KeyringKeyStore::new(
+        KEYRING_SERVICE,
+        user,
+    )))
+}
+
+#[cfg(not(feature = "os-keyring"))]
+fn keyring_store(user: &str) -> Result<Box<dyn KeyStore>> {
+    bail!(
+        "OS keyring support ({}/{}) is not enabled in this build; use the file or passphrase mode",
+        KEYRING_SERVICE,
+        user
+    )
+}
+
+/// 暗号化設定を読み込み（未設定なら Disabled）
+pub fn load_encryption_settings_from_db(path: &Path) -> Result<EncryptionSettings> {
+    let conn = open_database(path)?;
+    load_settings(&conn)
+}
+
+pub fn load_encryption_settings() -> Result<EncryptionSettings> {
+    load_encryption_settings_from_db(&default_db_path()?)
+}
+
+/// 切り替え途中の暗号化設定を読み込み（中断した切り替えが無ければ None）
+pub fn load_pending_encryption_from_db(path: &Path) -> Result<Option<EncryptionSettings>> {
+    let conn = open_database(path)?;
+    read_settings(&conn, PENDING_TABLE)
+}
+
+pub fn load_pending_encryption() -> Result<Option<EncryptionSettings>> {
+    load_pending_encryption_from_db(&default_db_path()?)
+}
+
+fn load_settings(conn: &Connection) -> Result<EncryptionSettings> {
+    Ok(read_settings(conn, SETTINGS_TABLE)?.unwrap_or_default())
+}
+
+fn read_settings(conn: &Connection, table: &str) -> Result<Option<EncryptionSettings>> {
+    let result = conn.query_row(
+        &format!(
+            "SELECT mode, salt, key_file, verifier FROM {} WHERE id = 1",
+            table
+        ),
+        [],
+        |row| {
+            let mode: String = row.get(0)?;
+            let key_file: Option<String> = row.get(2)?;
+            Ok((mode, row.get(1)?, key_file.map(PathBuf::from), row.get(3)?))
+        },
+    );
+
+    match result {
+        Ok((mode, salt, key_file, verifier)) => Ok(Some(EncryptionSettings {
+            mode: mode.parse()?,
+            salt,
+            key_file,
+            verifier,
+        })),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_settings(conn: &Connection, table: &str, settings: &EncryptionSettings) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (id, mode, salt, key_file, verifier)
+             VALUES (1, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                 mode = excluded.mode,
+                 salt = excluded.salt,
+                 key_file = excluded.key_file,
+                 verifier = excluded.verifier",
+            table
+        ),
+        params![
+            settings.mode.as_str(),
+            settings.salt,
+            settings
+                .key_file
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
+            settings.verifier
+        ],
+    )
+    .context("Failed to persist encryption settings")?;
+    Ok(())
+}
+
+/// 設定に従って鍵を取得（Disabled なら None）
+///
+/// パスフレーズモードで `passphrase` が None の場合は環境変数 `PASSPHRASE_ENV` を参照する。
+pub fn resolve_encryption_key(
+    settings: &EncryptionSettings,
+    passphrase: Option<&str>,
+) -> Result<Option<EncryptionKey>> {
+    resolve_key(settings, false, passphrase)
+}
+
+/// 切り替え途中の設定の鍵を取得（`stage_encryption_in_db` で保存した鍵）
+///
+/// パスフレーズモードでは新しいパスフレーズを `passphrase` で渡す。
+pub fn resolve_pending_encryption_key(
+    settings: &EncryptionSettings,
+    passphrase: Option<&str>,
+) -> Result<Option<EncryptionKey>> {
+    resolve_key(settings, true, passphrase)
+}
+
+fn resolve_key(
+    settings: &EncryptionSettings,
+    pending: bool,
+    passphrase: Option<&str>,
+) -> Result<Option<EncryptionKey>> {
+    let key = match settings.mode {
+        EncryptionMode::Disabled => return Ok(None),
+        EncryptionMode::Keyring | EncryptionMode::File => settings
+            .key_store_for(pending)?
+            .ok_or_else(|| anyhow!("No key store for mode {}", settings.mode))?
+            .load()
+            .context("Failed to read encryption key")?
+            .ok_or_else(|| anyhow!("Encryption key not found for mode {}", settings.mode))?,
+        EncryptionMode::Passphrase => {
+            let passphrase = match passphrase {
+                Some(passphrase) => passphrase.to_string(),
+                None => std::env::var(PASSPHRASE_ENV).map_err(|_| {
+                    anyhow!(
+                        "Passphrase required: set {} to unlock storage",
+                        PASSPHRASE_ENV
+                    )
+                })?,
+            };
+            let salt = settings
+                .salt
+                .as_deref()
+                .ok_or_else(|| anyhow!("Passphrase salt is missing"))?;
+            EncryptionKey::from_passphrase(&passphrase, salt)?
+        }
+    };
+
+    settings.verify(&key)?;
+    Ok(Some(key))
+}
+
+/// 保存済み設定から鍵を取得
+///
+/// 鍵の切り替えが中断したままなら、会話履歴に新旧の鍵が混ざっているためエラーにする。
+pub fn resolve_encryption_key_from_db(path: &Path) -> Result<Option<EncryptionKey>> {
+    let conn = open_database(path)?;
+    if read_settings(&conn, PENDING_TABLE)?.is_some() {
+        bail!(
+            "An encryption change was interrupted; run `neko-assistant encryption resume` \
+             or `neko-assistant encryption abort` first"
+        );
+    }
+    resolve_encryption_key(&load_settings(&conn)?, None)
+}
+
+/// デフォルト DB の設定から鍵を取得
+pub fn resolve_default_encryption_key() -> Result<Option<EncryptionKey>> {
+    resolve_encryption_key_from_db(&default_db_path()?)
+}
+
+/// 新しい設定と鍵を作成（まだ保存しない）
+///
+/// キーリング・ファイルモードでは乱数鍵、パスフレーズモードでは新しいソルトから鍵を導出する。
+pub fn prepare_encryption(
+    mode: EncryptionMode,
+    passphrase: Option<&str>,
+    key_file: Option<PathBuf>,
+) -> Result<(EncryptionSettings, Option<EncryptionKey>)> {
+    let mut settings = EncryptionSettings {
+        mode,
+        key_file: if mode == EncryptionMode::File {
+            key_file
+        } else {
+            None
+        },
+        ..Default::default()
+    };
+
+    let key = match mode {
+        EncryptionMode::Disabled => return Ok((settings, None)),
+        EncryptionMode::Keyring | EncryptionMode::File => EncryptionKey::generate(),
+        EncryptionMode::Passphrase => {
+            let passphrase = passphrase
+                .filter(|p| !p.is_empty())
+                .ok_or_else(|| anyhow!("Passphrase mode requires a non-empty passphrase"))?;
+            let salt = secret_store::generate_salt().to_vec();
+            let key = EncryptionKey::from_passphrase(passphrase, &salt)?;
+            settings.salt = Some(salt);
+            key
+        }
+    };
+
+    settings.verifier = Some(secret_store::encrypt_string(&key, VERIFIER_PLAINTEXT)?);
+    Ok((settings, Some(key)))
+}
+
+/// 切り替え先の設定と鍵を保留中として保存する
+///
+/// 会話履歴を書き直す前に呼び、途中で止まっても新しい鍵を失わないようにする。
+/// パスフレーズモードの鍵は保存せず、再開時にパスフレーズから導出し直す。
+pub fn stage_encryption_in_db(
+    path: &Path,
+    settings: &EncryptionSettings,
+    key: Option<&EncryptionKey>,
+) -> Result<()> {
+    if let (Some(store), Some(key)) = (settings.pending_key_store()?, key) {
+        store.store(key).context("Failed to stage encryption key")?;
+    }
+    write_settings(&open_database(path)?, PENDING_TABLE, settings)
+}
+
+/// デフォルト DB に対して `stage_encryption_in_db` を実行
+pub fn stage_encryption(settings: &EncryptionSettings, key: Option<&EncryptionKey>) -> Result<()> {
+    stage_encryption_in_db(&default_db_path()?, settings, key)
+}
+
+/// 保留中の設定と鍵を削除（切り替えの完了・巻き戻し後）
+pub fn discard_pending_encryption_in_db(path: &Path) -> Result<()> {
+    let conn = open_database(path)?;
+    if let Some(pending) = read_settings(&conn, PENDING_TABLE)? {
+        if let Some(store) = pending.pending_key_store()? {
+            store
+                .delete()
+                .context("Failed to delete staged encryption key")?;
+        }
+        conn.execute(&format!("DELETE FROM {}", PENDING_TABLE), [])
+            .context("Failed to clear pending encryption settings")?;
+    }
+    Ok(())
+}
+
+pub fn discard_pending_encryption() -> Result<()> {
+    discard_pending_encryption_in_db(&default_db_path()?)
+}
+
+/// トークンを新しい鍵で書き直し、暗号化設定を保存する
+///
+/// トークンの書き換えと設定の保存は 1 トランザクションで行い、コミット後に新しい鍵を保管先へ書き込む。
+/// 鍵の保存に失敗した場合は DB を元の鍵・設定へ書き戻してからエラーを返す。
+/// 成功したら保留中の設定と鍵を削除する。戻り値は書き直したトークン数。
+pub fn apply_encryption_in_db(
+    path: &Path,
+    old_key: Option<&EncryptionKey>,
+    settings: &EncryptionSettings,
+    new_key: Option<&EncryptionKey>,
+) -> Result<usize> {
+    let previous = load_settings(&open_database(path)?)?;
+    let count = rewrite_tokens_in_db(path, old_key, settings, new_key)?;
+
+    if let (Some(store), Some(key)) = (settings.key_store()?, new_key) {
+        if let Err(err) = store.store(key) {
+            rewrite_tokens_in_db(path, new_key, &previous, old_key)
+                .context("Failed to restore tokens after the encryption key could not be stored")?;
+            return Err(anyhow!(err).context("Failed to store encryption key"));
+        }
+    }
+
+    // 使わなくなった鍵を削除（同じ保管先なら上書き済み）
+    let new_store_kind = (settings.mode, settings.key_file.clone());
+    if (previous.mode, previous.key_file.clone()) != new_store_kind {
+        if let Ok(Some(old_store)) = previous.key_store() {
+            let _ = old_store.delete();
+        }
+    }
+
+    discard_pending_encryption_in_db(path)?;
+    Ok(count)
+}
+
+/// トークンを `from_key` から `to_key` へ書き直し、設定と合わせて 1 トランザクションで保存
+fn rewrite_tokens_in_db(
+    path: &Path,
+    from_key: Option<&EncryptionKey>,
+    settings: &EncryptionSettings,
+    to_key: Option<&EncryptionKey>,
+) -> Result<usize> {
+    let mut conn = open_database(path)?;
+    let tx = conn
+        .transaction()
+        .context("Failed to begin encryption transaction")?;
+
+    let tokens = {
+        let mut stmt = tx
+            .prepare("SELECT id, value FROM tokens")
+            .context("Failed to prepare token scan")?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+        rows
+    };
+    for (id, stored) in &tokens {
+        let plaintext = decode_token(stored, from_key)?;
+        let value = encode_token(&plaintext, to_key)?;
+        tx.execute(
+            "UPDATE tokens SET value = ?, updated_at = strftime('%s','now') WHERE id = ?",
+            params![value, id],
+        )
+        .context("Failed to re-encrypt token")?;
+    }
+
+    write_settings(&tx, SETTINGS_TABLE, settings)?;
+    tx.commit()
+        .context("Failed to commit encryption settings")?;
+    Ok(tokens.len())
+}
+
+/// デフォルト DB に対して `apply_encryption_in_db` を実行
+pub fn apply_encryption(
+    old_key: Option<&EncryptionKey>,
+    settings: &EncryptionSettings,
+    new_key: Option<&EncryptionKey>,
+) -> Result<usize> {
+    apply_encryption_in_db(&default_db_path()?, old_key, settings, new_key)
+}
+
+/// 現在の設定の鍵でトークン値を暗号化（無効なら平文）
+pub(crate) fn encrypt_token_value(conn: &Connection, value: &str) -> Result<String> {
+    let settings = load_settings(conn)?;
+    let key = resolve_encryption_key(&settings, None)?;
+    encode_token(value, key.as_ref())
+}
+
+/// 保存値を復号（暗号化前に保存された平文もそのまま返す）
+pub(crate) fn decrypt_token_value(conn: &Connection, stored: &str) -> Result<String> {
+    if !secret_store::is_encrypted_string(stored) {
+        return Ok(stored.to_string());
+    }
+    let settings = load_settings(conn)?;
+    let key = resolve_encryption_key(&settings, None)?;
+    decode_token(stored, key.as_ref())
+}
+
+fn encode_token(value: &str, key: Option<&EncryptionKey>) -> Result<String> {
+    match key {
+        Some(key) => Ok(secret_store::encrypt_string(key, value)?),
+        None => Ok(value.to_string()),
+    }
+}
+
+fn decode_token(stored: &str, key: Option<&EncryptionKey>) -> Result<String> {
+    if !secret_store::is_encrypted_string(stored) {
+        return Ok(stored.to_string());
+    }
+    let key = key.ok_or_else(|| anyhow!("Token is encrypted but no key is available"))?;
+    secret_store::decrypt_string(key, stored).context("Failed to decrypt token")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_token_from_db, set_token_in_db};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_mode_encrypts_tokens_and_rotates() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("settings.db");
+        let key_file = temp_dir.path().join("storage.key");
+
+        // 暗号化前に保存した平文トークン
+        set_token_in_db(&db_path, "openai", "default", "sk-plain").unwrap();
+
+        let (settings, key) =
+            prepare_encryption(EncryptionMode::File, None, Some(key_file.clone())).unwrap();
+        let count = apply_encryption_in_db(&db_path, None, &settings, key.as_ref()).unwrap();
+        assert_eq!(count, 1);
+        assert!(key_file.exists());
+
+        let conn = open_database(&db_path).unwrap();
+        let raw: String = conn
+            .query_row("SELECT value FROM tokens", [], |r| r.get(0))
+            .unwrap();
+        assert!(secret_store::is_encrypted_string(&raw));
+        assert_eq!(
+            get_token_from_db(&db_path, "openai", "default").unwrap(),
+            Some("sk-plain".to_string())
+        );
+
+        // 鍵の切り替え
+        let old_key = resolve_encryption_key_from_db(&db_path).unwrap();
+        assert_eq!(old_key, key);
+        let (settings, new_key) =
+            prepare_encryption(EncryptionMode::File, None, Some(key_file.clone())).unwrap();
+        apply_encryption_in_db(&db_path, old_key.as_ref(), &settings, new_key.as_ref()).unwrap();
+        assert_eq!(resolve_encryption_key_from_db(&db_path).unwrap(), new_key);
+        assert_eq!(
+            get_token_from_db(&db_path, "openai", "default").unwrap(),
+            Some("sk-plain".to_string())
+        );
+
+        // 無効化すると平文に戻り、鍵ファイルは削除される
+        let (settings, _) = prepare_encryption(EncryptionMode::Disabled, None, None).unwrap();
+        apply_encryption_in_db(&db_path, new_key.as_ref(), &settings, None).unwrap();
+        let raw: String = conn
+            .query_row("SELECT value FROM tokens", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(raw, "sk-plain");
+        assert!(!key_file.exists());
+    }
+
+    #[test]
+    fn test_failed_key_store_restores_tokens() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("settings.db");
+        // 親がファイルなので鍵ファイルを作れない
+        let blocker = temp_dir.path().join("blocker");
+        std::fs::write(&blocker, b"").unwrap();
+        let key_file = blocker.join("storage.key");
+
+        set_token_in_db(&db_path, "openai", "default", "sk-plain").unwrap();
+
+        let (settings, key) =
+            prepare_encryption(EncryptionMode::File, None, Some(key_file)).unwrap();
+        assert!(apply_encryption_in_db(&db_path, None, &settings, key.as_ref()).is_err());
+
+        let conn = open_database(&db_path).unwrap();
+        let raw: String = conn
+            .query_row("SELECT value FROM tokens", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(raw, "sk-plain");
+        assert_eq!(
+            load_encryption_settings_from_db(&db_path).unwrap().mode,
+            EncryptionMode::Disabled
+        );
+    }
+
+    #[test]
+    fn test_staged_key_survives_until_applied() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("settings.db");
+        let key_file = temp_dir.path().join("storage.key");
+        let pending_file = temp_dir.path().join("storage.pending.key");
+
+        let (settings, key) =
+            prepare_encryption(EncryptionMode::File, None, Some(key_file.clone())).unwrap();
+        stage_encryption_in_db(&db_path, &settings, key.as_ref()).unwrap();
+
+        // 会話の書き直し中に止まっても、新しい鍵は保留中の設定から取り出せる
+        assert!(pending_file.exists());
+        assert!(!key_file.exists());
+        let pending = load_pending_encryption_from_db(&db_path).unwrap().unwrap();
+        assert_eq!(pending, settings);
+        assert_eq!(resolve_pending_encryption_key(&pending, None).unwrap(), key);
+        assert!(resolve_encryption_key_from_db(&db_path).is_err());
+
+        apply_encryption_in_db(&db_path, None, &settings, key.as_ref()).unwrap();
+        assert!(load_pending_encryption_from_db(&db_path).unwrap().is_none());
+        assert!(!pending_file.exists());
+        assert_eq!(resolve_encryption_key_from_db(&db_path).unwrap(), key);
+
+        // 巻き戻すときは保留中の設定と鍵だけを消す
+        let (settings, key) =
+            prepare_encryption(EncryptionMode::File, None, Some(key_file.clone())).unwrap();
+        stage_encryption_in_db(&db_path, &settings, key.as_ref()).unwrap();
+        discard_pending_encryption_in_db(&db_path).unwrap();
+        assert!(load_pending_encryption_from_db(&db_path).unwrap().is_none());
+        assert!(!pending_file.exists());
+        assert!(key_file.exists());
+    }
+
+    #[test]
+    fn test_passphrase_mode_rejects_wrong_passphrase() {
+        let (settings, key) =
+            prepare_encryption(EncryptionMode::Passphrase, Some("meow"), None).unwrap();
+
+        assert_eq!(
+            resolve_encryption_key(&settings, Some("meow")).unwrap(),
+            key
+        );
+        assert!(resolve_encryption_key(&settings, Some("woof")).is_err());
+        assert!(prepare_encryption(EncryptionMode::Passphrase, Some(""), None).is_err());
+    }
+
+    #[test]
+    fn test_mode_parsing() {
+        assert_eq!(
+            "Keyring".parse::<EncryptionMode>().unwrap(),
+            EncryptionMode::Keyring
+        );
+        assert_eq!(
+            "off".parse::<EncryptionMode>().unwrap(),
+            EncryptionMode::Disabled
+        );
+        assert!("rot13".parse::<EncryptionMode>().is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! キーボードショートカットの設定（データディレクトリの `keymap.toml`）
+//!
+//! ```toml
+//! [bindings]
+//! new_chat = ["ctrl-n"]
+//! toggle_console = []   # 空にすると割り当てを外す
+//! ```
+//!
+//! ファイルに書いたアクションだけが既定の割り当てを置き換える。
+
+use crate::{get_default_data_dir, write_file_atomically};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 既定の割り当て（アクション名とキー。キーの書式は gpui の `ctrl-shift-p` 形式）
+pub const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("command_palette", "ctrl-shift-p"),
+    ("new_chat", "ctrl-n"),
+    ("next_conversation", "ctrl-tab"),
+    ("previous_conversation", "ctrl-shift-tab"),
+    ("new_tab", "ctrl-t"),
+    ("close_tab", "ctrl-w"),
+    ("next_tab", "ctrl-pagedown"),
+    ("previous_tab", "ctrl-pageup"),
+    ("new_window", "ctrl-shift-n"),
+    ("next_model", "ctrl-m"),
+    ("open_settings", "ctrl-,"),
+    ("open_mcp_manager", "ctrl-shift-m"),
+    ("toggle_console", "ctrl-`"),
+    ("toggle_scratchpad", "ctrl-shift-s"),
+    ("toggle_sidebar", "ctrl-b"),
+    ("toggle_mcp_status", "ctrl-shift-t"),
+];
+
+/// アクション名ごとのキーの割り当て
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keymap {
+    #[serde(default)]
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|(action, key)| (action.to_string(), vec![key.to_string()]))
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// ファイルを読み込み、書かれたアクションだけ既定の割り当てを置き換える
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keymap file: {}", path.display()))?;
+        let overrides: Keymap = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse keymap file: {}", path.display()))?;
+
+        let mut keymap = Self::default();
+        keymap.bindings.extend(overrides.bindings);
+        Ok(keymap)
+    }
+
+    /// 既定のパスから読み込み（無い・壊れている場合は既定の割り当て）
+    pub fn load_or_default() -> Self {
+        let path = default_keymap_path();
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load_from_file(&path).unwrap_or_else(|e| {
+            eprintln!("Warning: {:#}. Using default key bindings.", e);
+            Self::default()
+        })
+    }
+
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self).context("Failed to serialize keymap")?;
+        write_file_atomically(path, &content)
+    }
+
+    /// アクションに割り当てられたキー
+    pub fn keys_for(&self, action: &str) -> &[String] {
+        self.bindings.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// 既定の一覧に無いアクション名（綴り間違いの警告用）
+    pub fn unknown_actions(&self) -> Vec<&str> {
+        self.bindings
+            .keys()
+            .map(String::as_str)
+            .filter(|action| !DEFAULT_BINDINGS.iter().any(|(known, _)| known == action))
+            .collect()
+    }
+}
+
+/// キーマップファイルのパス
+pub fn default_keymap_path() -> PathBuf {
+    get_default_data_dir().join("keymap.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn overrides_replace_only_listed_actions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("keymap.toml");
+        std::fs::write(
+            &path,
+            "[bindings]\nnew_chat = [\"ctrl-t\", \"alt-n\"]\ntoggle_console = []\nnew_cht = [\"f1\"]\n",
+        )
+        .unwrap();
+
+        let keymap = Keymap::load_from_file(&path).unwrap();
+        assert_eq!(keymap.keys_for("new_chat"), ["ctrl-t", "alt-n"]);
+        assert!(keymap.keys_for("toggle_console").is_empty());
+        assert_eq!(keymap.keys_for("command_palette"), ["ctrl-shift-p"]);
+        assert_eq!(keymap.unknown_actions(), ["new_cht"]);
+
+        keymap.save_to_file(&path).unwrap();
+        assert_eq!(Keymap::load_from_file(&path).unwrap(), keymap);
+    }
+}
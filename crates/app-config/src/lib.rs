@@ -4,13 +4,31 @@
 //! - デフォルト設定の提供
 //! - TOML ファイルからの読み込み
 //! - 設定の保存
+//! - トークン・会話履歴の暗号化設定
+//! - キーボードショートカット（`keymap.toml`）
+
+mod encryption;
+mod keymap;
+
+pub use encryption::{
+    apply_encryption, apply_encryption_in_db, default_key_file, discard_pending_encryption,
+    discard_pending_encryption_in_db, load_encryption_settings, load_encryption_settings_from_db,
+    load_pending_encryption, load_pending_encryption_from_db, prepare_encryption,
+    resolve_default_encryption_key, resolve_encryption_key, resolve_encryption_key_from_db,
+    resolve_pending_encryption_key, stage_encryption, stage_encryption_in_db, EncryptionMode,
+    EncryptionSettings, PASSPHRASE_ENV,
+};
+pub use keymap::{default_keymap_path, Keymap, DEFAULT_BINDINGS};
+pub use secret_store::EncryptionKey;
 
 use anyhow::{anyhow, Context, Result};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 const DB_FILE_NAME: &str = "neko_assistant_settings.db";
@@ -30,7 +48,8 @@ pub struct AppConfig {
     #[serde(default = "default_max_history")]
     pub max_history_messages: usize,
 
-    /// セッション保存ディレクトリ
+    /// 旧形式（chat-engine の ChatSession）のセッション保存ディレクトリ。
+    /// 起動時に会話ストアへ取り込む対象として参照する。
     #[serde(default = "default_session_dir")]
     pub session_dir: PathBuf,
 
@@ -41,6 +60,14 @@ pub struct AppConfig {
     /// LangChain を使用するかどうか
     #[serde(default = "default_use_langchain")]
     pub use_langchain: bool,
+
+    /// 配色テーマ（"system" / "dark" / "light" / テーマファイルの名前）
+    #[serde(default = "default_theme")]
+    pub theme: String,
+
+    /// コンソールに残すログの件数（超えたら古いものから捨てる）
+    #[serde(default = "default_console_log_capacity")]
+    pub console_log_capacity: usize,
 }
 
 fn default_send_key() -> String {
@@ -51,6 +78,14 @@ fn default_use_langchain() -> bool {
     false // デフォルトは既存の実装を使用
 }
 
+fn default_theme() -> String {
+    "system".to_string()
+}
+
+fn default_console_log_capacity() -> usize {
+    1000
+}
+
 fn default_ollama_url() -> String {
     "http://localhost:11434/".to_string()
 }
@@ -76,6 +111,8 @@ impl Default for AppConfig {
             session_dir: default_session_dir(),
             send_key: default_send_key(),
             use_langchain: default_use_langchain(),
+            theme: default_theme(),
+            console_log_capacity: default_console_log_capacity(),
         }
     }
 }
@@ -102,7 +139,7 @@ impl AppConfig {
         let conn = open_database(path)?;
         let mut stmt = conn
             .prepare(
-                "SELECT ollama_base_url, default_model, max_history_messages, session_dir, send_key, use_langchain
+                "SELECT ollama_base_url, default_model, max_history_messages, session_dir, send_key, use_langchain, theme, console_log_capacity
                  FROM app_config
                  WHERE id = 1",
             )
@@ -112,6 +149,7 @@ impl AppConfig {
             let max_history: i64 = row.get(2)?;
             let session_dir: String = row.get(3)?;
             let use_langchain_raw: i64 = row.get(5)?;
+            let console_log_capacity: i64 = row.get(7)?;
             let max_history_messages = max_history.try_into().unwrap_or(0);
 
             Ok(AppConfig {
@@ -121,6 +159,10 @@ impl AppConfig {
                 session_dir: PathBuf::from(session_dir),
                 send_key: row.get(4)?,
                 use_langchain: use_langchain_raw != 0,
+                theme: row.get(6)?,
+                console_log_capacity: console_log_capacity
+                    .try_into()
+                    .unwrap_or_else(|_| default_console_log_capacity()),
             })
         });
 
@@ -155,50 +197,52 @@ impl AppConfig {
 
     /// 設定をファイルに保存
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
-        // 親ディレクトリを作成
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).with_context(|| {
-                format!("Failed to create config directory: {}", parent.display())
-            })?;
-        }
-
         let content = toml::to_string_pretty(self).with_context(|| "Failed to serialize config")?;
-
-        std::fs::write(path, content)
-            .with_context(|| format!("Failed to write config file: {}", path.display()))?;
-
-        Ok(())
+        write_file_atomically(path, &content)
     }
 
     /// SQLite データベースに保存
     pub fn save_to_database(&self, path: &Path) -> Result<()> {
-        let conn = open_database(path)?;
+        let mut conn = open_database(path)?;
         let session_dir = self.session_dir.to_string_lossy().to_string();
         let max_history: i64 = self
             .max_history_messages
             .try_into()
             .map_err(|_| anyhow!("max_history_messages exceeds supported range"))?;
 
-        conn.execute(
-            "INSERT INTO app_config (id, ollama_base_url, default_model, max_history_messages, session_dir, send_key, use_langchain)
-             VALUES (1, ?, ?, ?, ?, ?, ?)
+        let console_log_capacity: i64 = self
+            .console_log_capacity
+            .try_into()
+            .map_err(|_| anyhow!("console_log_capacity exceeds supported range"))?;
+
+        let tx = conn
+            .transaction()
+            .context("Failed to begin app_config transaction")?;
+        tx.execute(
+            "INSERT INTO app_config (id, ollama_base_url, default_model, max_history_messages, session_dir, send_key, use_langchain, theme, console_log_capacity)
+             VALUES (1, ?, ?, ?, ?, ?, ?, ?, ?)
              ON CONFLICT(id) DO UPDATE SET
                  ollama_base_url = excluded.ollama_base_url,
                  default_model = excluded.default_model,
                  max_history_messages = excluded.max_history_messages,
                  session_dir = excluded.session_dir,
                  send_key = excluded.send_key,
-                 use_langchain = excluded.use_langchain",
+                 use_langchain = excluded.use_langchain,
+                 theme = excluded.theme,
+                 console_log_capacity = excluded.console_log_capacity",
             params![
                 &self.ollama_base_url,
                 &self.default_model,
                 max_history,
                 session_dir,
                 &self.send_key,
-                if self.use_langchain { 1 } else { 0 }
+                if self.use_langchain { 1 } else { 0 },
+                &self.theme,
+                console_log_capacity
             ],
         )
         .context("Failed to persist app_config row")?;
+        tx.commit().context("Failed to commit app_config row")?;
 
         Ok(())
     }
@@ -233,7 +277,7 @@ fn load_legacy_config_file() -> Option<AppConfig> {
     }
 }
 
-fn default_db_path() -> Result<PathBuf> {
+pub(crate) fn default_db_path() -> Result<PathBuf> {
     let exe = env::current_exe().context("Failed to determine executable path")?;
     let dir = exe
         .parent()
@@ -241,7 +285,7 @@ fn default_db_path() -> Result<PathBuf> {
     Ok(dir.join(DB_FILE_NAME))
 }
 
-fn open_database(path: &Path) -> Result<Connection> {
+pub(crate) fn open_database(path: &Path) -> Result<Connection> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).with_context(|| {
             format!("Failed to create database directory: {}", parent.display())
@@ -255,7 +299,12 @@ fn open_database(path: &Path) -> Result<Connection> {
 }
 
 fn ensure_schema(conn: &Connection) -> Result<()> {
-    conn.execute(
+    // 関連テーブルはまとめて作成し、途中で失敗した場合は何も残さない
+    let tx = conn
+        .unchecked_transaction()
+        .context("Failed to begin schema transaction")?;
+
+    tx.execute(
         "CREATE TABLE IF NOT EXISTS app_config (
             id INTEGER PRIMARY KEY CHECK (id = 1),
             ollama_base_url TEXT NOT NULL,
@@ -263,14 +312,23 @@ fn ensure_schema(conn: &Connection) -> Result<()> {
             max_history_messages INTEGER NOT NULL,
             session_dir TEXT NOT NULL,
             send_key TEXT NOT NULL,
-            use_langchain INTEGER NOT NULL
+            use_langchain INTEGER NOT NULL,
+            theme TEXT NOT NULL DEFAULT 'system',
+            console_log_capacity INTEGER NOT NULL DEFAULT 1000
         )",
         [],
     )
     .context("Failed to create app_config table")?;
+    add_column_if_missing(&tx, "app_config", "theme", "TEXT NOT NULL DEFAULT 'system'")?;
+    add_column_if_missing(
+        &tx,
+        "app_config",
+        "console_log_capacity",
+        "INTEGER NOT NULL DEFAULT 1000",
+    )?;
 
     // tokens table for storing API keys / secret tokens
-    conn.execute(
+    tx.execute(
         "CREATE TABLE IF NOT EXISTS tokens (
             id INTEGER PRIMARY KEY,
             service TEXT NOT NULL,
@@ -284,12 +342,78 @@ fn ensure_schema(conn: &Connection) -> Result<()> {
     )
     .context("Failed to create tokens table")?;
 
+    // 暗号化設定（鍵そのものは保存しない）
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS encryption_settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            mode TEXT NOT NULL,
+            salt BLOB,
+            key_file TEXT,
+            verifier TEXT
+        )",
+        [],
+    )
+    .context("Failed to create encryption_settings table")?;
+
+    // 切り替え途中の暗号化設定（完了するまで残し、中断後の再開・巻き戻しに使う）
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS encryption_pending (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            mode TEXT NOT NULL,
+            salt BLOB,
+            key_file TEXT,
+            verifier TEXT
+        )",
+        [],
+    )
+    .context("Failed to create encryption_pending table")?;
+
+    // プラグインごとの設定値（値は JSON 文字列、スキーマは plugin.toml 側）
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS plugin_settings (
+            plugin TEXT NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            updated_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+            PRIMARY KEY(plugin, key)
+        )",
+        [],
+    )
+    .context("Failed to create plugin_settings table")?;
+
+    tx.commit().context("Failed to commit schema")?;
+    Ok(())
+}
+
+/// 古い DB に後から増えた列を追加する
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .with_context(|| format!("Failed to inspect {} table", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .with_context(|| format!("Failed to add {}.{} column", table, column))?;
+    }
     Ok(())
 }
 
 /// Store a token (service+name) into the given database path.
+/// The value is encrypted when storage encryption is enabled.
 pub fn set_token_in_db(path: &Path, service: &str, name: &str, value: &str) -> Result<()> {
     let conn = open_database(path)?;
+    let value = encryption::encrypt_token_value(&conn, value)?;
     conn.execute(
         "INSERT INTO tokens (service, name, value, created_at, updated_at)
          VALUES (?, ?, ?, strftime('%s','now'), strftime('%s','now'))
@@ -312,9 +436,9 @@ pub fn get_token_from_db(path: &Path, service: &str, name: &str) -> Result<Optio
     let mut stmt = conn
         .prepare("SELECT value FROM tokens WHERE service = ? AND name = ?")
         .context("Failed to prepare token select")?;
-    let result = stmt.query_row(params![service, name], |row| row.get(0));
+    let result = stmt.query_row(params![service, name], |row| row.get::<_, String>(0));
     match result {
-        Ok(v) => Ok(Some(v)),
+        Ok(v) => encryption::decrypt_token_value(&conn, &v).map(Some),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(e.into()),
     }
@@ -357,6 +481,78 @@ pub fn list_tokens() -> Result<Vec<(String, String, i64, i64)>> {
     list_tokens_in_db(&path)
 }
 
+/// プラグインの設定値（key → JSON 文字列）を読み込む
+pub fn load_plugin_settings_from_db(path: &Path, plugin: &str) -> Result<BTreeMap<String, String>> {
+    let conn = open_database(path)?;
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM plugin_settings WHERE plugin = ?")
+        .context("Failed to prepare plugin settings query")?;
+    let rows = stmt
+        .query_map(params![plugin], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<Result<BTreeMap<String, String>, rusqlite::Error>>()?;
+    Ok(rows)
+}
+
+pub fn load_plugin_settings(plugin: &str) -> Result<BTreeMap<String, String>> {
+    let path = default_db_path()?;
+    load_plugin_settings_from_db(&path, plugin)
+}
+
+/// プラグインの設定値をまとめて置き換える
+pub fn save_plugin_settings_in_db(
+    path: &Path,
+    plugin: &str,
+    values: &BTreeMap<String, String>,
+) -> Result<()> {
+    let mut conn = open_database(path)?;
+    let tx = conn
+        .transaction()
+        .context("Failed to begin plugin_settings transaction")?;
+    tx.execute(
+        "DELETE FROM plugin_settings WHERE plugin = ?",
+        params![plugin],
+    )
+    .context("Failed to clear plugin settings")?;
+    for (key, value) in values {
+        tx.execute(
+            "INSERT INTO plugin_settings (plugin, key, value, updated_at)
+             VALUES (?, ?, ?, strftime('%s','now'))",
+            params![plugin, key, value],
+        )
+        .context("Failed to insert plugin setting")?;
+    }
+    tx.commit().context("Failed to commit plugin settings")?;
+    Ok(())
+}
+
+pub fn save_plugin_settings(plugin: &str, values: &BTreeMap<String, String>) -> Result<()> {
+    let path = default_db_path()?;
+    save_plugin_settings_in_db(&path, plugin, values)
+}
+
+/// 一時ファイルに書いてから rename し、書き込み途中のクラッシュで壊れないようにする
+pub(crate) fn write_file_atomically(path: &Path, content: &str) -> Result<()> {
+    // 親ディレクトリを作成
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
+    }
+
+    let tmp_path = path.with_extension("toml.tmp");
+    {
+        let mut file = std::fs::File::create(&tmp_path).with_context(|| {
+            format!("Failed to create temp config file: {}", tmp_path.display())
+        })?;
+        file.write_all(content.as_bytes())
+            .and_then(|_| file.sync_all())
+            .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+    }
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace config file: {}", path.display()))?;
+
+    Ok(())
+}
+
 /// デフォルトのデータディレクトリを取得
 /// Windows: %USERPROFILE%\.neko-assistant
 /// Unix: ~/.neko-assistant
@@ -398,6 +594,8 @@ mod tests {
             session_dir: PathBuf::from("/tmp/sessions"),
             send_key: "ctrl_enter".to_string(),
             use_langchain: false,
+            theme: "light".to_string(),
+            console_log_capacity: 250,
         };
 
         // 保存
@@ -410,6 +608,37 @@ mod tests {
         assert_eq!(loaded.ollama_base_url, config.ollama_base_url);
         assert_eq!(loaded.default_model, config.default_model);
         assert_eq!(loaded.max_history_messages, config.max_history_messages);
+        assert_eq!(loaded.theme, "light");
+        assert_eq!(loaded.console_log_capacity, 250);
+    }
+
+    #[test]
+    fn test_adds_theme_column_to_old_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("settings.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE app_config (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    ollama_base_url TEXT NOT NULL,
+                    default_model TEXT NOT NULL,
+                    max_history_messages INTEGER NOT NULL,
+                    session_dir TEXT NOT NULL,
+                    send_key TEXT NOT NULL,
+                    use_langchain INTEGER NOT NULL
+                );
+                INSERT INTO app_config VALUES (1, 'http://old:11434/', 'old-model', 10, '/tmp', 'enter', 0);",
+            )
+            .unwrap();
+        }
+
+        let loaded = AppConfig::load_from_database(&db_path)
+            .unwrap()
+            .expect("config row should exist");
+        assert_eq!(loaded.default_model, "old-model");
+        assert_eq!(loaded.theme, "system");
+        assert_eq!(loaded.console_log_capacity, 1000);
     }
 
     #[test]
@@ -446,4 +675,40 @@ mod tests {
         assert!(delete_token_in_db(&db_path, "openai", "default").unwrap());
         assert!(get_token_from_db(&db_path, "openai", "default").unwrap().is_none());
     }
+
+    #[test]
+    fn test_save_to_file_replaces_atomically() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        let mut config = AppConfig::default();
+        config.save_to_file(&path).unwrap();
+        config.default_model = "updated-model".to_string();
+        config.save_to_file(&path).unwrap();
+
+        let loaded = AppConfig::load_from_file(&path).unwrap();
+        assert_eq!(loaded.default_model, "updated-model");
+        assert!(!path.with_extension("toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_plugin_settings_are_replaced_per_plugin() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("settings.db");
+        let stored = load_plugin_settings_from_db(&db_path, "phi4").unwrap();
+        assert!(stored.is_empty());
+
+        let mut values = BTreeMap::new();
+        values.insert("system_prompt".to_string(), "\"Be brief.\"".to_string());
+        values.insert("temperature".to_string(), "0.2".to_string());
+        save_plugin_settings_in_db(&db_path, "phi4", &values).unwrap();
+        save_plugin_settings_in_db(&db_path, "other", &values).unwrap();
+
+        values.remove("temperature");
+        save_plugin_settings_in_db(&db_path, "phi4", &values).unwrap();
+        let stored = load_plugin_settings_from_db(&db_path, "phi4").unwrap();
+        assert_eq!(stored, values);
+        let other = load_plugin_settings_from_db(&db_path, "other").unwrap();
+        assert_eq!(other.len(), 2);
+    }
 }
@@ -1,14 +1,25 @@
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 
-use chat_history::{Conversation, ConversationMetadata, Message, MessageRole};
+use chat_history::{
+    collect_folders, collect_tags, BackupInfo, Conversation, ConversationFilter,
+    ConversationMetadata, ConversationSettings, GenerationOptions, Message, MessageRole,
+    SkippedConversation,
+};
 use ollama_client::{OllamaClient, OllamaListedModel};
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{mpsc, oneshot, watch};
 
+use crate::hot_reload::{build_plugin_registries, HotReloadWatcher, PluginChanges, PluginSnapshot};
+use crate::slash_commands::{
+    default_export_path, ExportFormat, SlashCommand, SlashCommandRegistry,
+};
 use crate::{
-    console_log::ConsoleLogRecord, ConversationService, McpManager, McpServerConfig,
-    MessageHandler, PromptBuilderRegistry,
+    console_log::{ConsoleLogBuffer, ConsoleLogRecord, ConsoleLogSource},
+    discover_plugins, AdapterRegistry, BuilderPreview, ConsoleLogKind, ConversationService,
+    McpManager, McpServerConfig, MessageHandler, NoteStore, PluginEntry, PromptBuilderRegistry,
+    ToolRegistry,
 };
 
 const PRIMARY_MODEL_ID: &str = "phi4-mini:3.8b";
@@ -27,6 +38,12 @@ pub struct ChatControllerConfig {
     pub mcp_manager: Option<Arc<McpManager>>,
     pub mcp_configs: Vec<McpServerConfig>,
     pub prompt_registry: Option<Arc<PromptBuilderRegistry>>,
+    pub adapter_registry: Option<Arc<AdapterRegistry>>,
+    pub tool_registry: Option<Arc<ToolRegistry>>,
+    /// スクラッチパッドのノート（None ならノートをプロンプトに含めない）
+    pub note_store: Option<NoteStore>,
+    /// コンソールに残すログの件数（超えたら古いものから捨てる）
+    pub console_log_capacity: usize,
     pub welcome_message: String,
 }
 
@@ -80,13 +97,29 @@ pub struct AvailableModel {
 #[derive(Clone, Debug)]
 pub struct ChatState {
     pub conversation_id: Option<String>,
+    /// 現在の会話のタイトル（タブの見出しに使う）
+    pub conversation_title: String,
+    /// 現在の会話で使うモデル（会話に指定が無ければ既定モデル）
     pub active_model: String,
+    /// 現在の会話の設定（モデル・システムプロンプト・ツール・生成オプション）
+    pub conversation_settings: ConversationSettings,
     pub messages: Vec<Message>,
+    /// フィルタ適用後の会話一覧（ピン留めが先頭）
     pub conversations: Vec<ConversationMetadata>,
+    pub conversation_filter: ConversationFilter,
+    /// 全会話に現れるフォルダ名
+    pub conversation_folders: Vec<String>,
+    /// 全会話に現れるタグ
+    pub conversation_tags: Vec<String>,
+    /// 読み込めずに一覧から外した会話（鍵違い・I/O エラーなど）
+    pub unreadable_conversations: Vec<SkippedConversation>,
     pub mcp_servers: Vec<McpServerMetadata>,
     pub mcp_tools: Vec<McpToolMetadata>,
-    pub console_logs: Vec<ConsoleLogRecord>,
+    /// 件数に上限のあるコンソールのログ（古い順）
+    pub console_logs: ConsoleLogBuffer,
     pub available_models: Vec<AvailableModel>,
+    /// 入力欄で使えるスラッシュコマンド（組み込み・プラグイン・MCP プロンプト）
+    pub slash_commands: SlashCommandRegistry,
 }
 
 /// コントローラーが発火するイベント
@@ -98,18 +131,83 @@ pub enum ChatEvent {
     McpMetadataUpdated,
     ConsoleLogUpdated,
     ModelsUpdated,
+    /// ホットリロードでプラグインのレジストリが差し替わった
+    PluginsReloaded,
     Error(String),
 }
 
 /// UI から発行されるコマンド
 #[derive(Clone, Debug)]
 pub enum ChatCommand {
+    /// `/` で始まればスラッシュコマンドとして実行する
     SendUserMessage(String),
+    /// 現在の会話のモデルを切り替える（会話に保存される）
     SwitchModel(String),
+    /// 現在の会話のシステムプロンプト（None なら既定の指示文）
+    SetSystemPrompt(Option<String>),
+    /// 現在の会話で使う MCP ツール（`tool@server`。None なら全ツール）
+    SetEnabledTools(Option<Vec<String>>),
+    /// 現在の会話の生成オプション
+    SetGenerationOptions(GenerationOptions),
+    /// 現在の会話で使うプロンプトビルダー（`PromptBuilderSource::id`。None ならモデルの既定）
+    SetPromptBuilder(Option<String>),
+    /// 現在の会話でプロンプトに含めるスクラッチパッドのノート名
+    SetContextNotes(Vec<String>),
+    /// 現在の会話からメッセージを削除する
+    DeleteMessage(String),
+    /// 指定メッセージ以前で最後のユーザーメッセージから応答を生成し直す（それより後ろは削除）
+    RetryFromMessage(String),
+    SetMessagePinned {
+        id: String,
+        pinned: bool,
+    },
+    /// ツール呼び出しのメッセージを同じ引数でもう一度実行する
+    RerunToolCall(String),
+    /// ツールを直接呼び出し、結果を現在の会話に記録する
+    CallTool {
+        tool: String,
+        server: String,
+        arguments: serde_json::Value,
+    },
+    /// 現在の会話のメッセージをすべて削除する
+    ClearConversation,
+    /// 現在の会話をファイルに書き出す
+    ExportConversation {
+        format: ExportFormat,
+        path: PathBuf,
+    },
+    /// コンソールのログを JSON Lines で書き出す
+    ExportConsoleLog(PathBuf),
     CreateConversation,
     SwitchConversation(String),
     DeleteConversation(String),
     RefreshConversations,
+    SetConversationFolder {
+        id: String,
+        folder: Option<String>,
+    },
+    AddConversationTag {
+        id: String,
+        tag: String,
+    },
+    RemoveConversationTag {
+        id: String,
+        tag: String,
+    },
+    SetConversationPinned {
+        id: String,
+        pinned: bool,
+    },
+    SetConversationArchived {
+        id: String,
+        archived: bool,
+    },
+    SetConversationFilter(ConversationFilter),
+    /// バックアップから復元（backup が None なら最新世代）
+    RestoreConversationBackup {
+        id: String,
+        backup: Option<PathBuf>,
+    },
     RefreshState,
     RefreshMcpMetadata,
     RefreshModels,
@@ -137,51 +235,45 @@ type ControllerResult<T> = Result<T, ControllerError>;
 
 type EventCallback = Arc<dyn Fn(ChatEvent) + Send + Sync>;
 
+/// 全セッションで共有する部分（会話一覧・MCP・モデル一覧・コンソール・プラグイン）
 struct ChatControllerInner {
-    conversation_service: ConversationService,
-    message_handler: Arc<MessageHandler>,
-    state: Arc<RwLock<ChatState>>,
-    state_tx: watch::Sender<ChatState>,
-    callbacks: Mutex<HashMap<usize, EventCallback>>,
-    next_callback_id: AtomicUsize,
+    /// 会話の保存先。一覧・削除・開いていない会話の更新に使う（現在の会話は使わない）
+    store: ConversationService,
+    /// 共有部分の最新の状態。各セッションはこれに自分の会話を重ねて公開する
+    shared: RwLock<ChatState>,
+    sessions: Mutex<Vec<Arc<ChatSession>>>,
+    /// 閉じたが応答を保存し終えていないセッション。終わるまで同じ会話は開き直せない
+    closing: Mutex<Vec<Arc<ChatSession>>>,
+    next_id: AtomicUsize,
+    use_langchain: bool,
     welcome_message: String,
     mcp_manager: Option<Arc<McpManager>>,
-    mcp_configs: Vec<McpServerConfig>,
+    mcp_configs: RwLock<Vec<McpServerConfig>>,
     ollama_url: String,
+    /// 会話にモデル指定が無いときに使うモデル
+    default_model: RwLock<String>,
+    /// ホットリロードで読み込んだプラグインと、変更検出用のスナップショット
+    plugins: RwLock<Vec<PluginEntry>>,
+    plugin_snapshot: Mutex<PluginSnapshot>,
+    note_store: Option<NoteStore>,
 }
 
-impl ChatControllerInner {
-    fn publish_state(&self) {
-        if let Ok(state) = self.state.read() {
-            let _ = self.state_tx.send(state.clone());
-        }
-    }
-
-    fn refresh_state(&self) -> ControllerResult<ChatState> {
-        let mut guard = self
-            .state
-            .write()
-            .map_err(|_| ControllerError::new("State lock poisoned"))?;
-        guard.conversation_id = self.conversation_service.current_conversation_id();
-        guard.messages = self.conversation_service.current_messages();
-        let snapshot = guard.clone();
-        drop(guard);
-        self.publish_state();
-        Ok(snapshot)
-    }
+/// ビュー（タブやウィンドウ）ごとに開いている会話。生成中の応答とモデルはセッションごとに持つ
+struct ChatSession {
+    id: usize,
+    conversation_service: ConversationService,
+    message_handler: Arc<MessageHandler>,
+    active_model: RwLock<String>,
+    state_tx: watch::Sender<ChatState>,
+    callbacks: Mutex<HashMap<usize, EventCallback>>,
+}
 
-    fn refresh_conversation_list(&self) -> ControllerResult<()> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|_| ControllerError::new("State lock poisoned"))?;
-        state.conversations = self
-            .conversation_service
-            .list_conversations()
-            .map_err(|e| ControllerError::new(e.to_string()))?;
-        drop(state);
-        self.publish_state();
-        Ok(())
+impl ChatSession {
+    fn active_model(&self) -> String {
+        self.active_model
+            .read()
+            .map(|model| model.clone())
+            .unwrap_or_default()
     }
 
     fn emit_event(&self, event: ChatEvent) {
@@ -201,44 +293,233 @@ impl ChatControllerInner {
         self.emit_event(ChatEvent::Error(message.into()));
     }
 
-    fn append_console_log(&self, record: ConsoleLogRecord) {
-        let mut should_emit = true;
-        if let Ok(mut guard) = self.state.write() {
-            guard.console_logs.push(record);
-            drop(guard);
-            self.publish_state();
+    fn remove_callback(&self, id: usize) {
+        self.callbacks
+            .lock()
+            .expect("Callback lock should never be poisoned")
+            .remove(&id);
+    }
+}
+
+impl ChatControllerInner {
+    /// 会話を開いたセッションを登録し、そのセッションのハンドルを返す
+    fn attach_session(
+        self: &Arc<Self>,
+        conversation_service: ConversationService,
+        message_handler: MessageHandler,
+        ui_rx: mpsc::UnboundedReceiver<()>,
+        active_model: String,
+    ) -> ChatController {
+        let logs_inner = Arc::downgrade(self);
+        message_handler.set_console_logger(Some(Arc::new(move |record| {
+            if let Some(inner) = logs_inner.upgrade() {
+                inner.append_console_log(record);
+            }
+        })));
+        if self.use_langchain {
+            let refresh_inner = Arc::downgrade(self);
+            message_handler.set_mcp_refresh_callback(Some(Arc::new(move || {
+                if let Some(inner) = refresh_inner.upgrade() {
+                    if let Err(err) = inner.refresh_mcp_metadata() {
+                        inner.emit_error(err.message());
+                    }
+                }
+            })));
+        }
+
+        let state = self.compose_state(&conversation_service, &active_model);
+        let (state_tx, state_rx) = watch::channel(state);
+        let session = Arc::new(ChatSession {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            conversation_service,
+            message_handler: Arc::new(message_handler),
+            active_model: RwLock::new(active_model),
+            state_tx,
+            callbacks: Mutex::new(HashMap::new()),
+        });
+        self.sessions
+            .lock()
+            .expect("Session lock should never be poisoned")
+            .push(Arc::clone(&session));
+        Self::spawn_ui_listener(self, &session, ui_rx);
+
+        ChatController {
+            inner: Arc::clone(self),
+            session,
+            state_rx,
+        }
+    }
+
+    fn sessions(&self) -> Vec<Arc<ChatSession>> {
+        self.sessions
+            .lock()
+            .expect("Session lock should never be poisoned")
+            .clone()
+    }
+
+    /// 閉じたあとも応答を生成中のセッション（保存し終えたものは取り除く）
+    fn closing_sessions(&self) -> Vec<Arc<ChatSession>> {
+        let mut closing = self
+            .closing
+            .lock()
+            .expect("Session lock should never be poisoned");
+        closing.retain(|session| session.message_handler.has_pending_turn());
+        closing.clone()
+    }
+
+    /// 指定した会話を開いているセッション（閉じたが応答を生成中のものを含む）
+    fn session_for(&self, conversation_id: &str) -> Option<Arc<ChatSession>> {
+        let mut sessions = self.sessions();
+        sessions.extend(self.closing_sessions());
+        sessions.into_iter().find(|session| {
+            session
+                .conversation_service
+                .current_conversation_id()
+                .as_deref()
+                == Some(conversation_id)
+        })
+    }
+
+    /// 他のセッション（`current` 以外）が開いている会話、
+    /// または閉じたセッションが応答を保存し終えていない会話なら開けない
+    fn ensure_not_open_elsewhere(
+        &self,
+        conversation_id: &str,
+        current: Option<usize>,
+    ) -> ControllerResult<()> {
+        let Some(session) = self.session_for(conversation_id) else {
+            return Ok(());
+        };
+        if Some(session.id) == current {
+            Ok(())
+        } else if self.sessions().iter().any(|open| open.id == session.id) {
+            Err(ControllerError::new(format!(
+                "Conversation is already open in another tab or window: {}",
+                conversation_id
+            )))
         } else {
-            eprintln!("Failed to record console log due to poisoned state lock");
-            should_emit = false;
+            Err(ControllerError::new(format!(
+                "Conversation is still saving a response from a closed tab or window: {}",
+                conversation_id
+            )))
+        }
+    }
+
+    /// 共有部分の状態にセッションの会話を重ねたスナップショット
+    fn compose_state(&self, service: &ConversationService, active_model: &str) -> ChatState {
+        let mut state = self
+            .shared
+            .read()
+            .map(|shared| shared.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone());
+        state.active_model = active_model.to_string();
+        if let Ok(conversation) = service.snapshot() {
+            state.conversation_id = Some(conversation.id);
+            state.conversation_title = conversation.title;
+            state.conversation_settings = conversation.settings;
+            state.messages = conversation.messages;
+        }
+        state
+    }
+
+    fn publish_state(&self, session: &ChatSession) {
+        let state = self.compose_state(&session.conversation_service, &session.active_model());
+        let _ = session.state_tx.send(state);
+    }
+
+    fn publish_all(&self) {
+        for session in self.sessions() {
+            self.publish_state(&session);
         }
+    }
+
+    /// 共有部分を更新し、すべてのセッションへ公開する
+    fn update_shared<F>(&self, mutator: F) -> ControllerResult<()>
+    where
+        F: FnOnce(&mut ChatState),
+    {
+        let mut guard = self
+            .shared
+            .write()
+            .map_err(|_| ControllerError::new("State lock poisoned"))?;
+        mutator(&mut guard);
+        drop(guard);
+        self.publish_all();
+        Ok(())
+    }
+
+    fn emit_state_event(&self, session: &ChatSession) {
+        self.publish_state(session);
+        session.emit_event(ChatEvent::StateChanged);
+    }
 
-        if should_emit {
-            self.emit_event(ChatEvent::ConsoleLogUpdated);
+    /// すべてのセッションにイベントを送る
+    fn emit_event(&self, event: ChatEvent) {
+        for session in self.sessions() {
+            session.emit_event(event.clone());
         }
     }
 
-    fn emit_state_event(&self) -> ControllerResult<()> {
-        self.refresh_state()?;
-        self.emit_event(ChatEvent::StateChanged);
+    fn emit_error(&self, message: impl Into<String>) {
+        self.emit_event(ChatEvent::Error(message.into()));
+    }
+
+    fn refresh_conversation_list(&self) -> ControllerResult<()> {
+        let (all, skipped) = self
+            .store
+            .list_conversations_with_skipped()
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        let mut newly_skipped = Vec::new();
+        self.update_shared(|state| {
+            state.conversations = state.conversation_filter.apply(&all);
+            state.conversation_folders = collect_folders(&all);
+            state.conversation_tags = collect_tags(&all);
+            newly_skipped = skipped
+                .iter()
+                .filter(|s| !state.unreadable_conversations.contains(s))
+                .cloned()
+                .collect();
+            state.unreadable_conversations = skipped;
+        })?;
+        // 同じ会話を一覧の更新のたびに報告しない
+        for skipped in &newly_skipped {
+            self.append_console_log(unreadable_conversation_record(skipped));
+        }
         Ok(())
     }
 
+    fn append_console_log(&self, record: ConsoleLogRecord) {
+        if self
+            .update_shared(|state| state.console_logs.push(record))
+            .is_err()
+        {
+            eprintln!("Failed to record console log due to poisoned state lock");
+            return;
+        }
+        self.emit_event(ChatEvent::ConsoleLogUpdated);
+    }
+
     fn emit_conversation_list(&self) -> ControllerResult<()> {
         self.refresh_conversation_list()?;
         self.emit_event(ChatEvent::ConversationsUpdated);
         Ok(())
     }
 
+    fn mcp_configs(&self) -> Vec<McpServerConfig> {
+        self.mcp_configs
+            .read()
+            .map(|configs| configs.clone())
+            .unwrap_or_default()
+    }
+
     fn refresh_mcp_metadata(self: &Arc<Self>) -> ControllerResult<()> {
-        if self.mcp_configs.is_empty() {
-            let mut guard = self
-                .state
-                .write()
-                .map_err(|_| ControllerError::new("State lock poisoned"))?;
-            guard.mcp_servers.clear();
-            guard.mcp_tools.clear();
-            drop(guard);
-            self.publish_state();
+        let configs = self.mcp_configs();
+        if configs.is_empty() {
+            self.update_shared(|state| {
+                state.mcp_servers.clear();
+                state.mcp_tools.clear();
+                state.slash_commands.set_mcp_prompts(&[]);
+            })?;
             self.emit_event(ChatEvent::McpMetadataUpdated);
             return Ok(());
         }
@@ -246,25 +527,19 @@ impl ChatControllerInner {
         let manager = match &self.mcp_manager {
             Some(manager) => Arc::clone(manager),
             None => {
-                let mut guard = self
-                    .state
-                    .write()
-                    .map_err(|_| ControllerError::new("State lock poisoned"))?;
-                guard.mcp_servers = self
-                    .mcp_configs
-                    .iter()
-                    .map(|cfg| McpServerMetadata::unknown(cfg.name.clone()))
-                    .collect();
-                guard.mcp_tools.clear();
-                drop(guard);
-                self.publish_state();
+                self.update_shared(|state| {
+                    state.mcp_servers = configs
+                        .iter()
+                        .map(|cfg| McpServerMetadata::unknown(cfg.name.clone()))
+                        .collect();
+                    state.mcp_tools.clear();
+                    state.slash_commands.set_mcp_prompts(&[]);
+                })?;
                 self.emit_event(ChatEvent::McpMetadataUpdated);
                 return Ok(());
             }
         };
 
-        let state = Arc::clone(&self.state);
-        let configs = self.mcp_configs.clone();
         let controller = Arc::clone(self);
 
         tokio::spawn(async move {
@@ -294,43 +569,42 @@ impl ChatControllerInner {
                         });
                     }
 
-                    let mut should_emit = true;
-                    if let Ok(mut guard) = state.write() {
+                    let prompts = manager.get_all_prompts().await.unwrap_or_default();
+
+                    let updated = controller.update_shared(|state| {
                         let mut servers: Vec<_> = server_map.into_values().collect();
                         servers.sort_by(|a, b| a.name.cmp(&b.name));
-                        guard.mcp_servers = servers;
-                        guard.mcp_tools = tool_metadata;
-                        drop(guard);
-                        controller.publish_state();
-                    } else {
-                        eprintln!("Failed to acquire chat state lock for MCP metadata update");
-                        should_emit = false;
-                    }
-
-                    if should_emit {
-                        controller.emit_event(ChatEvent::McpMetadataUpdated);
+                        state.mcp_servers = servers;
+                        state.mcp_tools = tool_metadata;
+                        state.slash_commands.set_mcp_prompts(&prompts);
+                    });
+                    match updated {
+                        Ok(()) => controller.emit_event(ChatEvent::McpMetadataUpdated),
+                        Err(_) => {
+                            eprintln!("Failed to acquire chat state lock for MCP metadata update")
+                        }
                     }
                 }
                 Err(err) => {
-                    let mut should_emit = true;
-                    if let Ok(mut guard) = state.write() {
-                        guard.mcp_servers = configs
+                    let updated = controller.update_shared(|state| {
+                        state.mcp_servers = configs
                             .iter()
                             .map(|cfg| McpServerMetadata::error(cfg.name.clone(), err.clone()))
                             .collect();
-                        guard.mcp_tools.clear();
-                        drop(guard);
-                        controller.publish_state();
-                    } else {
-                        eprintln!("Failed to acquire chat state lock for MCP metadata error");
-                        should_emit = false;
-                    }
-
-                    if should_emit {
-                        controller.emit_event(ChatEvent::McpMetadataUpdated);
-                        controller.emit_error(format!("Failed to refresh MCP metadata: {}", err));
-                    } else {
-                        eprintln!("Skipped emitting MCP metadata error due to poisoned state lock");
+                        state.mcp_tools.clear();
+                        state.slash_commands.set_mcp_prompts(&[]);
+                    });
+                    match updated {
+                        Ok(()) => {
+                            controller.emit_event(ChatEvent::McpMetadataUpdated);
+                            controller
+                                .emit_error(format!("Failed to refresh MCP metadata: {}", err));
+                        }
+                        Err(_) => {
+                            eprintln!(
+                                "Skipped emitting MCP metadata error due to poisoned state lock"
+                            )
+                        }
                     }
                 }
             }
@@ -341,7 +615,6 @@ impl ChatControllerInner {
 
     fn refresh_available_models(self: &Arc<Self>) -> ControllerResult<()> {
         let base_url = self.ollama_url.clone();
-        let state = Arc::clone(&self.state);
         let controller = Arc::clone(self);
 
         tokio::spawn(async move {
@@ -358,39 +631,36 @@ impl ChatControllerInner {
             match result {
                 Ok(models) => {
                     let presets = build_available_models(models);
-                    let active_model = state
-                        .read()
-                        .map(|guard| guard.active_model.clone())
-                        .unwrap_or_default();
-                    let contains_active = presets.iter().any(|preset| preset.id == active_model);
-                    let fallback_model = if contains_active {
-                        None
-                    } else {
-                        presets
-                            .iter()
-                            .find(|preset| preset.id == PRIMARY_MODEL_ID)
-                            .or_else(|| presets.first())
-                            .map(|preset| preset.id.clone())
-                    };
-
-                    let mut should_emit = true;
-                    if let Ok(mut guard) = state.write() {
-                        guard.available_models = presets.clone();
-                        drop(guard);
-                        controller.publish_state();
-                    } else {
-                        eprintln!("Failed to update available models due to poisoned state lock");
-                        should_emit = false;
+                    let fallback_model = presets
+                        .iter()
+                        .find(|preset| preset.id == PRIMARY_MODEL_ID)
+                        .or_else(|| presets.first())
+                        .map(|preset| preset.id.clone());
+                    let contains = |model: &str| presets.iter().any(|preset| preset.id == model);
+
+                    let stored = presets.clone();
+                    match controller.update_shared(move |state| state.available_models = stored) {
+                        Ok(()) => controller.emit_event(ChatEvent::ModelsUpdated),
+                        Err(_) => {
+                            eprintln!(
+                                "Failed to update available models due to poisoned state lock"
+                            )
+                        }
                     }
 
-                    if should_emit {
-                        controller.emit_event(ChatEvent::ModelsUpdated);
+                    let Some(fallback) = fallback_model else {
+                        return;
+                    };
+                    if let Ok(mut default_model) = controller.default_model.write() {
+                        if !contains(&default_model) {
+                            *default_model = fallback.clone();
+                        }
                     }
-
-                    if let Some(fallback) = fallback_model {
-                        if fallback != active_model {
-                            if let Err(err) = controller.switch_model(fallback.clone()) {
-                                controller.emit_error(err.message());
+                    // 一覧に無いモデルを使っているセッションは代わりのモデルに切り替える
+                    for session in controller.sessions() {
+                        if !contains(&session.active_model()) {
+                            if let Err(err) = controller.apply_model(&session, fallback.clone()) {
+                                session.emit_error(err.message());
                             }
                         }
                     }
@@ -404,91 +674,124 @@ impl ChatControllerInner {
         Ok(())
     }
 
-    fn add_callback(self: &Arc<Self>, callback: EventCallback) -> ControllerSubscription {
-        let id = self.next_callback_id.fetch_add(1, Ordering::Relaxed);
-        self.callbacks
-            .lock()
-            .expect("Callback lock should never be poisoned")
-            .insert(id, callback);
-        ControllerSubscription {
-            inner: Arc::clone(self),
-            id,
-            active: true,
+    fn delete_conversation(&self, conversation_id: &str) -> ControllerResult<()> {
+        if self.session_for(conversation_id).is_some() {
+            return Err(ControllerError::new(
+                "Cannot delete a conversation that is open in a tab or window",
+            ));
         }
+        self.store
+            .delete_conversation(conversation_id)
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        self.emit_conversation_list()?;
+        Ok(())
     }
 
-    fn remove_callback(&self, id: usize) {
-        self.callbacks
-            .lock()
-            .expect("Callback lock should never be poisoned")
-            .remove(&id);
+    /// 会話の属性を変更する。開いているセッションがあればその会話を更新する
+    fn update_conversation<F>(&self, conversation_id: &str, mutator: F) -> ControllerResult<()>
+    where
+        F: FnOnce(&mut Conversation),
+    {
+        match self.session_for(conversation_id) {
+            Some(session) => {
+                session
+                    .conversation_service
+                    .mutate_and_save(mutator)
+                    .map_err(|e| ControllerError::new(e.to_string()))?;
+                self.publish_state(&session);
+            }
+            None => self
+                .store
+                .update_conversation(conversation_id, mutator)
+                .map_err(|e| ControllerError::new(e.to_string()))?,
+        }
+        self.emit_conversation_list()
     }
 
-    fn create_conversation(&self) -> ControllerResult<()> {
-        let mut conversation = Conversation::new("New Chat");
-        conversation.add_message(Message::new(
-            MessageRole::System,
-            self.welcome_message.clone(),
-        ));
-        self.conversation_service
-            .replace_conversation(conversation)
+    fn restore_conversation_backup(
+        &self,
+        conversation_id: &str,
+        backup: Option<PathBuf>,
+    ) -> ControllerResult<()> {
+        let session = self.session_for(conversation_id);
+        let service = session
+            .as_ref()
+            .map(|session| &session.conversation_service)
+            .unwrap_or(&self.store);
+        service
+            .restore_backup(conversation_id, backup.as_deref())
             .map_err(|e| ControllerError::new(e.to_string()))?;
-        self.emit_state_event()?;
-        self.emit_conversation_list()?;
-        Ok(())
+        if let Some(session) = session {
+            self.emit_state_event(&session);
+            self.apply_conversation_model(&session)?;
+        }
+        self.emit_conversation_list()
     }
 
-    fn switch_conversation(&self, conversation_id: &str) -> ControllerResult<()> {
-        self.conversation_service
-            .save_current()
-            .map_err(|e| ControllerError::new(e.to_string()))?;
-        self.conversation_service
-            .load_conversation(conversation_id)
-            .map_err(|e| ControllerError::new(e.to_string()))?;
-        self.emit_state_event()?;
-        Ok(())
+    fn set_conversation_filter(&self, filter: ConversationFilter) -> ControllerResult<()> {
+        self.update_shared(|state| state.conversation_filter = filter)?;
+        self.emit_conversation_list()
     }
 
-    fn delete_conversation(&self, conversation_id: &str) -> ControllerResult<()> {
-        if self
-            .conversation_service
-            .current_conversation_id()
-            .map(|id| id == conversation_id)
-            .unwrap_or(false)
-        {
-            return Err(ControllerError::new(
-                "Cannot delete the currently active conversation",
-            ));
-        }
-        self.conversation_service
-            .delete_conversation(conversation_id)
-            .map_err(|e| ControllerError::new(e.to_string()))?;
-        self.emit_conversation_list()?;
-        Ok(())
+    /// 有効なプラグインの `[commands]` をスラッシュコマンドに反映する
+    fn update_plugin_commands(&self, plugins: &[PluginEntry]) {
+        let _ = self.update_shared(|state| state.slash_commands.set_plugin_commands(plugins));
     }
 
-    fn switch_model(&self, model: String) -> ControllerResult<()> {
-        let mut state = self
-            .state
+    /// セッションの会話のモデル（未指定なら既定モデル）をハンドラーへ反映
+    fn apply_conversation_model(&self, session: &ChatSession) -> ControllerResult<()> {
+        let model = match session.conversation_service.current_settings().model {
+            Some(model) => model,
+            None => self
+                .default_model
+                .read()
+                .map_err(|_| ControllerError::new("Default model lock poisoned"))?
+                .clone(),
+        };
+        self.apply_model(session, model)
+    }
+
+    fn apply_model(&self, session: &ChatSession, model: String) -> ControllerResult<()> {
+        let mut active_model = session
+            .active_model
             .write()
             .map_err(|_| ControllerError::new("State lock poisoned"))?;
-        if state.active_model == model {
+        if *active_model == model {
             return Ok(());
         }
-        self.message_handler
+        session
+            .message_handler
             .set_model(model.clone())
             .map_err(ControllerError::new)?;
-        state.active_model = model.clone();
-        drop(state);
-        self.publish_state();
-        self.emit_event(ChatEvent::ModelChanged);
+        *active_model = model;
+        drop(active_model);
+        self.publish_state(session);
+        session.emit_event(ChatEvent::ModelChanged);
         Ok(())
     }
+
+    /// ハンドラーが会話を更新したらセッションの状態を公開する。セッションが閉じられたら終わる
+    fn spawn_ui_listener(
+        inner: &Arc<Self>,
+        session: &Arc<ChatSession>,
+        mut rx: mpsc::UnboundedReceiver<()>,
+    ) {
+        let inner = Arc::downgrade(inner);
+        let session = Arc::downgrade(session);
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                let (Some(inner), Some(session)) = (inner.upgrade(), session.upgrade()) else {
+                    break;
+                };
+                inner.emit_state_event(&session);
+            }
+        });
+    }
 }
 
 /// UI 側で保持するサブスクリプションハンドル
 pub struct ControllerSubscription {
-    inner: Arc<ChatControllerInner>,
+    session: Arc<ChatSession>,
     id: usize,
     active: bool,
 }
@@ -496,7 +799,7 @@ pub struct ControllerSubscription {
 impl ControllerSubscription {
     pub fn cancel(mut self) {
         if self.active {
-            self.inner.remove_callback(self.id);
+            self.session.remove_callback(self.id);
             self.active = false;
         }
     }
@@ -505,15 +808,17 @@ impl ControllerSubscription {
 impl Drop for ControllerSubscription {
     fn drop(&mut self) {
         if self.active {
-            self.inner.remove_callback(self.id);
+            self.session.remove_callback(self.id);
             self.active = false;
         }
     }
 }
 
-/// コントローラー外部公開ラッパー
+/// コントローラー外部公開ラッパー。ビュー（タブやウィンドウ）ごとのセッションのハンドルで、
+/// 会話一覧・MCP・プラグインなどは `open_session` で開いた他のセッションと共有する
 pub struct ChatController {
     inner: Arc<ChatControllerInner>,
+    session: Arc<ChatSession>,
     state_rx: watch::Receiver<ChatState>,
 }
 
@@ -521,6 +826,7 @@ impl Clone for ChatController {
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            session: Arc::clone(&self.session),
             state_rx: self.state_rx.clone(),
         }
     }
@@ -536,11 +842,21 @@ impl ChatController {
             mcp_manager,
             mcp_configs,
             prompt_registry,
+            adapter_registry,
+            tool_registry,
+            note_store,
+            console_log_capacity,
             welcome_message,
         } = config;
 
+        let default_model = active_model;
+        let active_model = conversation_service
+            .current_settings()
+            .model
+            .unwrap_or_else(|| default_model.clone());
+
         let (ui_tx, ui_rx) = mpsc::unbounded_channel();
-        let message_handler = Arc::new(MessageHandler::new(
+        let message_handler = MessageHandler::new(
             conversation_service.clone(),
             ui_tx,
             use_langchain,
@@ -548,72 +864,142 @@ impl ChatController {
             active_model.clone(),
             mcp_manager.clone(),
             prompt_registry.clone(),
-        ));
-        let handler_for_callback = Arc::clone(&message_handler);
+            adapter_registry,
+            tool_registry,
+        );
+        message_handler.set_note_store(note_store.clone());
 
-        let conversations = conversation_service
-            .list_conversations()
+        let (all_conversations, unreadable_conversations) = conversation_service
+            .list_conversations_with_skipped()
             .unwrap_or_else(|err| {
                 eprintln!("Failed to load conversations: {}", err);
-                Vec::new()
+                (Vec::new(), Vec::new())
             });
+        let conversation_filter = ConversationFilter::default();
+        let mut console_logs = ConsoleLogBuffer::new(console_log_capacity);
+        for skipped in &unreadable_conversations {
+            console_logs.push(unreadable_conversation_record(skipped));
+        }
 
-        let state = ChatState {
-            conversation_id: conversation_service.current_conversation_id(),
-            active_model: active_model.clone(),
-            messages: conversation_service.current_messages(),
-            conversations,
+        let shared = ChatState {
+            conversation_id: None,
+            conversation_title: String::new(),
+            active_model: String::new(),
+            conversation_settings: ConversationSettings::default(),
+            messages: Vec::new(),
+            conversations: conversation_filter.apply(&all_conversations),
+            conversation_filter,
+            conversation_folders: collect_folders(&all_conversations),
+            conversation_tags: collect_tags(&all_conversations),
+            unreadable_conversations,
             mcp_servers: mcp_configs
                 .iter()
                 .map(|cfg| McpServerMetadata::unknown(cfg.name.clone()))
                 .collect(),
             mcp_tools: Vec::new(),
-            console_logs: Vec::new(),
+            console_logs,
             available_models: curated_model_list(),
+            slash_commands: SlashCommandRegistry::new(),
         };
 
-        let (state_tx, state_rx) = watch::channel(state.clone());
-        let state_store = Arc::new(RwLock::new(state.clone()));
-
         let inner = Arc::new(ChatControllerInner {
-            conversation_service,
-            message_handler,
-            state: Arc::clone(&state_store),
-            state_tx,
-            callbacks: Mutex::new(HashMap::new()),
-            next_callback_id: AtomicUsize::new(1),
+            store: conversation_service.open(Conversation::new("")),
+            shared: RwLock::new(shared),
+            sessions: Mutex::new(Vec::new()),
+            closing: Mutex::new(Vec::new()),
+            next_id: AtomicUsize::new(1),
+            use_langchain,
             welcome_message,
             mcp_manager,
-            mcp_configs,
+            mcp_configs: RwLock::new(mcp_configs),
             ollama_url,
+            default_model: RwLock::new(default_model),
+            plugins: RwLock::new(Vec::new()),
+            plugin_snapshot: Mutex::new(PluginSnapshot::default()),
+            note_store,
         });
 
-        let logs_inner = Arc::downgrade(&inner);
-        handler_for_callback.set_console_logger(Some(Arc::new(move |record| {
-            if let Some(inner) = logs_inner.upgrade() {
-                inner.append_console_log(record);
-            }
-        })));
+        inner.attach_session(conversation_service, message_handler, ui_rx, active_model)
+    }
 
-        if use_langchain {
-            let controller_for_refresh = Arc::clone(&inner);
-            handler_for_callback.set_mcp_refresh_callback(Some(Arc::new(move || {
-                if let Err(err) = controller_for_refresh.refresh_mcp_metadata() {
-                    controller_for_refresh.emit_error(err.message());
-                }
-            })));
+    /// 別のタブやウィンドウ用のセッションを開く（None なら新しい会話）。
+    /// 会話一覧・MCP・プラグインは共有し、生成中の応答・モデル・会話はセッションごとに持つ
+    pub fn open_session(&self, conversation_id: Option<&str>) -> ControllerResult<ChatController> {
+        let conversation = match conversation_id {
+            Some(id) => {
+                self.inner.ensure_not_open_elsewhere(id, None)?;
+                self.inner
+                    .store
+                    .load(id)
+                    .map_err(|e| ControllerError::new(e.to_string()))?
+            }
+            None => self.new_conversation(),
+        };
+        let conversation_service = self.inner.store.open(conversation);
+        if conversation_id.is_none() {
+            conversation_service
+                .save_current()
+                .map_err(|e| ControllerError::new(e.to_string()))?;
         }
 
-        ChatController::spawn_ui_listener(&inner, ui_rx);
+        let active_model = match conversation_service.current_settings().model {
+            Some(model) => model,
+            None => self
+                .inner
+                .default_model
+                .read()
+                .map_err(|_| ControllerError::new("Default model lock poisoned"))?
+                .clone(),
+        };
+        let (ui_tx, ui_rx) = mpsc::unbounded_channel();
+        let message_handler = self.session.message_handler.for_conversation(
+            conversation_service.clone(),
+            ui_tx,
+            active_model.clone(),
+        );
+        let session =
+            self.inner
+                .attach_session(conversation_service, message_handler, ui_rx, active_model);
+        self.inner.emit_conversation_list()?;
+        Ok(session)
+    }
 
-        Self { inner, state_rx }
+    /// セッションを閉じる。以後は共有のイベントを受け取らない。
+    /// 生成中の応答はこのセッションの会話に保存され、それまで同じ会話は開き直せない
+    pub fn close_session(&self) -> ControllerResult<()> {
+        self.inner
+            .sessions
+            .lock()
+            .map_err(|_| ControllerError::new("Session lock poisoned"))?
+            .retain(|session| session.id != self.session.id);
+        if self.session.message_handler.has_pending_turn() {
+            self.inner
+                .closing
+                .lock()
+                .map_err(|_| ControllerError::new("Session lock poisoned"))?
+                .push(Arc::clone(&self.session));
+        }
+        self.session
+            .conversation_service
+            .save_current()
+            .map_err(|e| ControllerError::new(e.to_string()))
     }
 
     pub fn subscribe<F>(&self, callback: F) -> ControllerSubscription
     where
         F: Fn(ChatEvent) + Send + Sync + 'static,
     {
-        self.inner.add_callback(Arc::new(callback))
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        self.session
+            .callbacks
+            .lock()
+            .expect("Callback lock should never be poisoned")
+            .insert(id, Arc::new(callback));
+        ControllerSubscription {
+            session: Arc::clone(&self.session),
+            id,
+            active: true,
+        }
     }
 
     pub fn handle_command(&self, command: ChatCommand) -> ControllerResult<()> {
@@ -622,15 +1008,88 @@ impl ChatController {
                 if text.trim().is_empty() {
                     return Ok(());
                 }
-                self.inner.message_handler.handle_user_message(text);
+                let parsed = self.state_rx.borrow().slash_commands.parse(&text);
+                let result = match parsed {
+                    None => {
+                        self.session.message_handler.handle_user_message(text);
+                        return Ok(());
+                    }
+                    Some(Ok(command)) => self.run_slash_command(command),
+                    Some(Err(message)) => Err(ControllerError::new(message)),
+                };
+                if let Err(err) = &result {
+                    self.append_console_log(ConsoleLogKind::Error, err.message());
+                }
+                result
+            }
+            ChatCommand::SwitchModel(model) => self.switch_model(model),
+            ChatCommand::SetSystemPrompt(prompt) => {
+                self.update_settings(move |settings| settings.set_system_prompt(prompt))
+            }
+            ChatCommand::SetEnabledTools(tools) => {
+                self.update_settings(move |settings| settings.enabled_tools = tools)
+            }
+            ChatCommand::SetGenerationOptions(options) => {
+                self.update_settings(move |settings| settings.generation = options)
+            }
+            ChatCommand::SetPromptBuilder(builder) => {
+                self.update_settings(move |settings| settings.prompt_builder = builder)
+            }
+            ChatCommand::SetContextNotes(notes) => {
+                self.update_settings(move |settings| settings.context_notes = notes)
+            }
+            ChatCommand::DeleteMessage(id) => self.delete_message(&id),
+            ChatCommand::RetryFromMessage(id) => self.retry_from_message(&id),
+            ChatCommand::SetMessagePinned { id, pinned } => self.set_message_pinned(&id, pinned),
+            ChatCommand::RerunToolCall(id) => self.rerun_tool_call(&id),
+            ChatCommand::CallTool {
+                tool,
+                server,
+                arguments,
+            } => {
+                self.session
+                    .message_handler
+                    .run_tool_call(server, tool, arguments);
                 Ok(())
             }
-            ChatCommand::SwitchModel(model) => self.inner.switch_model(model),
-            ChatCommand::CreateConversation => self.inner.create_conversation(),
-            ChatCommand::SwitchConversation(id) => self.inner.switch_conversation(&id),
+            ChatCommand::ClearConversation => self.clear_conversation(),
+            ChatCommand::ExportConversation { format, path } => {
+                self.export_conversation(format, &path)
+            }
+            ChatCommand::ExportConsoleLog(path) => self.export_console_log(&path),
+            ChatCommand::CreateConversation => self.create_conversation(),
+            ChatCommand::SwitchConversation(id) => self.switch_conversation(&id),
             ChatCommand::DeleteConversation(id) => self.inner.delete_conversation(&id),
             ChatCommand::RefreshConversations => self.inner.emit_conversation_list(),
-            ChatCommand::RefreshState => self.inner.emit_state_event(),
+            ChatCommand::SetConversationFolder { id, folder } => self
+                .inner
+                .update_conversation(&id, move |conv| conv.set_folder(folder)),
+            ChatCommand::AddConversationTag { id, tag } => {
+                self.inner.update_conversation(&id, move |conv| {
+                    conv.add_tag(tag);
+                })
+            }
+            ChatCommand::RemoveConversationTag { id, tag } => {
+                self.inner.update_conversation(&id, move |conv| {
+                    conv.remove_tag(&tag);
+                })
+            }
+            ChatCommand::SetConversationPinned { id, pinned } => self
+                .inner
+                .update_conversation(&id, move |conv| conv.pinned = pinned),
+            ChatCommand::SetConversationArchived { id, archived } => self
+                .inner
+                .update_conversation(&id, move |conv| conv.archived = archived),
+            ChatCommand::SetConversationFilter(filter) => {
+                self.inner.set_conversation_filter(filter)
+            }
+            ChatCommand::RestoreConversationBackup { id, backup } => {
+                self.inner.restore_conversation_backup(&id, backup)
+            }
+            ChatCommand::RefreshState => {
+                self.inner.emit_state_event(&self.session);
+                Ok(())
+            }
             ChatCommand::RefreshMcpMetadata => self.inner.refresh_mcp_metadata(),
             ChatCommand::RefreshModels => self.inner.refresh_available_models(),
         }
@@ -640,28 +1099,487 @@ impl ChatController {
         self.state_rx.borrow().clone()
     }
 
+    fn emit_state_event(&self) {
+        self.inner.emit_state_event(&self.session);
+    }
+
+    /// 既定の挨拶と、このセッションの会話の設定を引き継いだ新しい会話
+    fn new_conversation(&self) -> Conversation {
+        let mut conversation = Conversation::new("New Chat");
+        conversation.settings = self.session.conversation_service.current_settings();
+        conversation.add_message(Message::new(
+            MessageRole::System,
+            self.inner.welcome_message.clone(),
+        ));
+        conversation
+    }
+
+    /// 生成中の応答はこのセッションの現在の会話に保存されるため、それまで会話を入れ替えない
+    fn ensure_no_pending_turn(&self) -> ControllerResult<()> {
+        if self.session.message_handler.has_pending_turn() {
+            return Err(ControllerError::new(
+                "A response is still being generated; wait for it or open another tab",
+            ));
+        }
+        Ok(())
+    }
+
+    fn create_conversation(&self) -> ControllerResult<()> {
+        self.ensure_no_pending_turn()?;
+        self.session
+            .conversation_service
+            .replace_conversation(self.new_conversation())
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        self.emit_state_event();
+        self.inner.emit_conversation_list()?;
+        Ok(())
+    }
+
+    fn switch_conversation(&self, conversation_id: &str) -> ControllerResult<()> {
+        self.inner
+            .ensure_not_open_elsewhere(conversation_id, Some(self.session.id))?;
+        self.ensure_no_pending_turn()?;
+        let service = &self.session.conversation_service;
+        service
+            .save_current()
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        service
+            .load_conversation(conversation_id)
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        self.emit_state_event();
+        self.inner.apply_conversation_model(&self.session)
+    }
+
+    /// 現在の会話の設定を変更して保存
+    fn update_settings<F>(&self, mutator: F) -> ControllerResult<()>
+    where
+        F: FnOnce(&mut ConversationSettings),
+    {
+        self.session
+            .conversation_service
+            .mutate_and_save(move |conv| mutator(&mut conv.settings))
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        self.emit_state_event();
+        Ok(())
+    }
+
+    fn delete_message(&self, message_id: &str) -> ControllerResult<()> {
+        self.ensure_no_pending_turn()?;
+        let mut removed = false;
+        self.session
+            .conversation_service
+            .mutate_and_save(|conv| removed = conv.remove_message(message_id))
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        if !removed {
+            return Err(ControllerError::new(format!(
+                "Message not found: {}",
+                message_id
+            )));
+        }
+        self.emit_state_event();
+        Ok(())
+    }
+
+    fn set_message_pinned(&self, message_id: &str, pinned: bool) -> ControllerResult<()> {
+        let mut found = false;
+        self.session
+            .conversation_service
+            .mutate_and_save(|conv| found = conv.set_message_pinned(message_id, pinned))
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        if !found {
+            return Err(ControllerError::new(format!(
+                "Message not found: {}",
+                message_id
+            )));
+        }
+        self.emit_state_event();
+        Ok(())
+    }
+
+    fn retry_from_message(&self, message_id: &str) -> ControllerResult<()> {
+        self.ensure_no_pending_turn()?;
+        let mut user_input = None;
+        self.session
+            .conversation_service
+            .mutate_and_save(|conv| user_input = conv.rewind_to_user_message(message_id))
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        let user_input =
+            user_input.ok_or_else(|| ControllerError::new("No user message to retry from"))?;
+        self.emit_state_event();
+        self.session.message_handler.respond_to(user_input);
+        Ok(())
+    }
+
+    fn rerun_tool_call(&self, message_id: &str) -> ControllerResult<()> {
+        let record = self
+            .session
+            .conversation_service
+            .current_messages()
+            .into_iter()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| ControllerError::new(format!("Message not found: {}", message_id)))?
+            .tool_call_record()
+            .ok_or_else(|| ControllerError::new("Message is not a tool call"))?;
+        self.session.message_handler.rerun_tool_call(record);
+        Ok(())
+    }
+
+    /// ツール名から提供元（MCP サーバーまたはツールプラグイン）を探す。複数あればエラー
+    fn find_tool_server(&self, tool: &str) -> ControllerResult<String> {
+        let mut servers = self.session.message_handler.plugin_tool_servers(tool);
+        if let Ok(state) = self.inner.shared.read() {
+            servers.extend(
+                state
+                    .mcp_tools
+                    .iter()
+                    .filter(|metadata| metadata.tool_name == tool)
+                    .map(|metadata| metadata.server_name.clone()),
+            );
+        }
+        servers.sort();
+        servers.dedup();
+        match servers.as_slice() {
+            [server] => Ok(server.clone()),
+            [] => Err(ControllerError::new(format!("Tool not found: {}", tool))),
+            _ => Err(ControllerError::new(format!(
+                "Tool '{}' is provided by {}; use /tool {}@<server>",
+                tool,
+                servers.join(", "),
+                tool
+            ))),
+        }
+    }
+
+    fn clear_conversation(&self) -> ControllerResult<()> {
+        self.ensure_no_pending_turn()?;
+        self.session
+            .conversation_service
+            .mutate_and_save(|conv| {
+                conv.clear_messages();
+            })
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        self.emit_state_event();
+        self.inner.emit_conversation_list()
+    }
+
+    fn export_conversation(&self, format: ExportFormat, path: &Path) -> ControllerResult<()> {
+        let conversation = self
+            .session
+            .conversation_service
+            .snapshot()
+            .map_err(|e| ControllerError::new(e.to_string()))?;
+        write_export_file(path, &format.render(&conversation))?;
+        self.append_console_log(
+            ConsoleLogKind::Output,
+            format!("Exported conversation to {}", path.display()),
+        );
+        Ok(())
+    }
+
+    fn export_console_log(&self, path: &Path) -> ControllerResult<()> {
+        let logs = self.state_snapshot().console_logs;
+        write_export_file(path, &logs.to_jsonl())?;
+        self.append_console_log(
+            ConsoleLogKind::Output,
+            format!(
+                "Exported {} console records to {}",
+                logs.len(),
+                path.display()
+            ),
+        );
+        Ok(())
+    }
+
+    /// MCP サーバーのプロンプトを展開し、ユーザーメッセージとして送る
+    fn send_mcp_prompt(
+        &self,
+        server: String,
+        prompt: String,
+        arguments: serde_json::Map<String, serde_json::Value>,
+    ) -> ControllerResult<()> {
+        let manager = self
+            .inner
+            .mcp_manager
+            .clone()
+            .ok_or_else(|| ControllerError::new("MCP servers are not available"))?;
+        let controller = self.clone();
+        tokio::spawn(async move {
+            let message = match manager.get_prompt(&server, &prompt, arguments).await {
+                Ok(text) if !text.trim().is_empty() => {
+                    controller.session.message_handler.handle_user_message(text);
+                    return;
+                }
+                Ok(_) => format!("Prompt /{} returned no text", prompt),
+                Err(err) => format!("Failed to get prompt /{}: {}", prompt, err),
+            };
+            controller.append_console_record(
+                ConsoleLogRecord::new(ConsoleLogKind::Error, message)
+                    .with_source(ConsoleLogSource::Mcp(server)),
+            );
+        });
+        Ok(())
+    }
+
+    fn switch_model(&self, model: String) -> ControllerResult<()> {
+        let service = &self.session.conversation_service;
+        if service.current_settings().model.as_deref() != Some(model.as_str()) {
+            let stored = model.clone();
+            service
+                .mutate_and_save(move |conv| conv.settings.model = Some(stored))
+                .map_err(|e| ControllerError::new(e.to_string()))?;
+            self.inner.publish_state(&self.session);
+        }
+        self.inner.apply_model(&self.session, model)
+    }
+
+    /// 解析済みのスラッシュコマンドを対応する ChatCommand で実行する
+    fn run_slash_command(&self, command: SlashCommand) -> ControllerResult<()> {
+        match command {
+            SlashCommand::SwitchModel(model) => {
+                self.handle_command(ChatCommand::SwitchModel(model))
+            }
+            SlashCommand::NewConversation => self.handle_command(ChatCommand::CreateConversation),
+            SlashCommand::ClearConversation => self.handle_command(ChatCommand::ClearConversation),
+            SlashCommand::SetSystemPrompt(prompt) => {
+                self.handle_command(ChatCommand::SetSystemPrompt(prompt))
+            }
+            SlashCommand::CallTool {
+                tool,
+                server,
+                arguments,
+            } => {
+                let server = match server {
+                    Some(server) => server,
+                    None => self.find_tool_server(&tool)?,
+                };
+                self.handle_command(ChatCommand::CallTool {
+                    tool,
+                    server,
+                    arguments,
+                })
+            }
+            SlashCommand::Export { format, path } => {
+                let path = match path {
+                    Some(path) => path,
+                    None => {
+                        let conversation = self
+                            .session
+                            .conversation_service
+                            .snapshot()
+                            .map_err(|e| ControllerError::new(e.to_string()))?;
+                        default_export_path(&conversation, format)
+                    }
+                };
+                self.handle_command(ChatCommand::ExportConversation { format, path })
+            }
+            SlashCommand::Retry => {
+                let last = self
+                    .session
+                    .conversation_service
+                    .current_messages()
+                    .last()
+                    .map(|message| message.id.clone())
+                    .ok_or_else(|| ControllerError::new("No message to retry"))?;
+                self.handle_command(ChatCommand::RetryFromMessage(last))
+            }
+            SlashCommand::SendMessage(text) => {
+                self.session.message_handler.handle_user_message(text);
+                Ok(())
+            }
+            SlashCommand::McpPrompt {
+                server,
+                prompt,
+                arguments,
+            } => self.send_mcp_prompt(server, prompt, arguments),
+        }
+    }
+
     pub fn state_stream(&self) -> watch::Receiver<ChatState> {
         self.state_rx.clone()
     }
 
     /// Append a record to the console logs visible in the UI.
     pub fn append_console_log(&self, kind: crate::ConsoleLogKind, content: impl Into<String>) {
-        let record = ConsoleLogRecord::new(kind, content.into());
+        self.append_console_record(ConsoleLogRecord::new(kind, content.into()));
+    }
+
+    /// 記録をそのままコンソールに追加する（会話 ID が無ければこのセッションの会話を付ける）
+    pub fn append_console_record(&self, mut record: ConsoleLogRecord) {
+        if record.conversation_id.is_none() {
+            record.conversation_id = self.session.conversation_service.current_conversation_id();
+        }
         self.inner.append_console_log(record);
     }
 
-    fn spawn_ui_listener(inner: &Arc<ChatControllerInner>, mut rx: mpsc::UnboundedReceiver<()>) {
-        let controller = Arc::clone(inner);
+    /// 会話のバックアップ一覧（新しい順）。復元する世代を選ぶのに使う
+    pub fn conversation_backups(&self, conversation_id: &str) -> ControllerResult<Vec<BackupInfo>> {
+        self.inner
+            .store
+            .list_backups(conversation_id)
+            .map_err(|e| ControllerError::new(e.to_string()))
+    }
+
+    /// 直近に読み込んだプラグイン（ホットリロード前は空）
+    pub fn plugins(&self) -> Vec<PluginEntry> {
+        self.inner
+            .plugins
+            .read()
+            .map(|plugins| plugins.clone())
+            .unwrap_or_default()
+    }
+
+    /// このセッションで応答を生成中かどうか（その間は会話を切り替えられない）
+    pub fn has_pending_turn(&self) -> bool {
+        self.session.message_handler.has_pending_turn()
+    }
+
+    /// スクラッチパッドのノートの保存先
+    pub fn note_store(&self) -> Option<NoteStore> {
+        self.inner.note_store.clone()
+    }
+
+    /// 現在のプロンプトビルダーレジストリ
+    pub fn prompt_registry(&self) -> Option<Arc<PromptBuilderRegistry>> {
+        self.session.message_handler.prompt_registry()
+    }
+
+    /// 現在の会話の文脈をモデルに一致するすべてのプロンプトビルダーで組み立てる。
+    /// ツール一覧の取得に tokio ランタイムを使うため、結果はチャネルで返す
+    pub fn preview_prompt_builders(
+        &self,
+    ) -> oneshot::Receiver<Result<Vec<BuilderPreview>, String>> {
+        let handler = Arc::clone(&self.session.message_handler);
+        let (tx, rx) = oneshot::channel();
         tokio::spawn(async move {
-            while rx.recv().await.is_some() {
-                if let Err(err) = controller.emit_state_event() {
-                    controller.emit_error(err.message());
-                }
+            let _ = tx.send(handler.preview_prompt_builders().await);
+        });
+        rx
+    }
+
+    /// `plugins/` と `mcp_servers.json` の監視を始める。返したハンドルを破棄すると止まる
+    pub fn watch_for_changes(&self, repo_root: &Path) -> Result<HotReloadWatcher, String> {
+        let mcp_config = crate::mcp_client::mcp_config_path().ok();
+        HotReloadWatcher::spawn(self.clone(), repo_root.to_path_buf(), mcp_config)
+            .map_err(|e| format!("Failed to watch for plugin changes: {}", e))
+    }
+
+    /// 現在のプラグイン構成を変更検出の基準として記録する
+    pub(crate) fn track_plugins(&self, repo_root: &Path) {
+        let entries = discover_plugins(repo_root).unwrap_or_default();
+        if let Ok(mut snapshot) = self.inner.plugin_snapshot.lock() {
+            *snapshot = PluginSnapshot::capture(&entries);
+        }
+        self.inner.update_plugin_commands(&entries);
+        if let Ok(mut plugins) = self.inner.plugins.write() {
+            *plugins = entries;
+        }
+    }
+
+    /// 変更のあったプラグインがあればレジストリを作り直し、すべてのセッションで差し替える。
+    /// 古いライブラリは実行中のセッションが使い終わった時点でアンロードされる
+    pub fn reload_plugins(&self, repo_root: &Path) -> ControllerResult<PluginChanges> {
+        let entries = discover_plugins(repo_root)
+            .map_err(|e| ControllerError::new(format!("Failed to discover plugins: {}", e)))?;
+        let snapshot = PluginSnapshot::capture(&entries);
+        let changes = {
+            let mut guard = self
+                .inner
+                .plugin_snapshot
+                .lock()
+                .map_err(|_| ControllerError::new("Plugin snapshot lock poisoned"))?;
+            let changes = guard.diff(&snapshot);
+            *guard = snapshot;
+            changes
+        };
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        let (prompt_registry, adapter_registry, tool_registry) = build_plugin_registries(&entries);
+        let prompt_registry = Arc::new(prompt_registry);
+        let adapter_registry = Arc::new(adapter_registry);
+        let tool_registry = Arc::new(tool_registry);
+        for session in self.inner.sessions() {
+            session.message_handler.set_plugin_registries(
+                Some(Arc::clone(&prompt_registry)),
+                Some(Arc::clone(&adapter_registry)),
+                Some(Arc::clone(&tool_registry)),
+            );
+        }
+        self.inner.update_plugin_commands(&entries);
+        if let Ok(mut plugins) = self.inner.plugins.write() {
+            *plugins = entries;
+        }
+
+        self.append_console_log(
+            ConsoleLogKind::Output,
+            format!("Plugins reloaded: {}", changes),
+        );
+        self.inner.emit_event(ChatEvent::PluginsReloaded);
+        Ok(changes)
+    }
+
+    /// MCP 設定を差し替え、追加・変更・削除されたサーバーだけを起動し直す
+    pub fn reload_mcp_config(&self, configs: Vec<McpServerConfig>) {
+        let inner = Arc::clone(&self.inner);
+        let Some(manager) = inner.mcp_manager.clone() else {
+            if !configs.is_empty() && configs != inner.mcp_configs() {
+                self.append_console_log(
+                    ConsoleLogKind::Error,
+                    "MCP servers were not enabled at startup; restart to apply mcp_servers.json",
+                );
+            }
+            return;
+        };
+
+        tokio::spawn(async move {
+            let summary = manager.reload(configs.clone()).await;
+            if summary.is_empty() {
+                return;
+            }
+            if let Ok(mut guard) = inner.mcp_configs.write() {
+                *guard = configs;
+            }
+
+            inner.append_console_log(ConsoleLogRecord::new(
+                ConsoleLogKind::Output,
+                format!("MCP servers reloaded: {}", summary),
+            ));
+            for session in inner.sessions() {
+                session.message_handler.reload_tools();
+            }
+            if let Err(err) = inner.refresh_mcp_metadata() {
+                inner.emit_error(err.message());
             }
         });
     }
 }
 
+/// 書き出し先のディレクトリを作ってからファイルに書く
+fn write_export_file(path: &Path, contents: &str) -> ControllerResult<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| ControllerError::new(format!("Failed to create {:?}: {}", parent, e)))?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|e| ControllerError::new(format!("Failed to write {:?}: {}", path, e)))
+}
+
+/// 読み込めなかった会話をコンソールに出す記録
+fn unreadable_conversation_record(skipped: &SkippedConversation) -> ConsoleLogRecord {
+    ConsoleLogRecord::new(
+        ConsoleLogKind::Error,
+        format!(
+            "Could not read conversation {}: {}",
+            skipped.path.display(),
+            skipped.error
+        ),
+    )
+}
+
 impl From<&str> for ControllerError {
     fn from(value: &str) -> Self {
         ControllerError::new(value)
@@ -1,21 +1,393 @@
-#[derive(Clone, Debug, PartialEq, Eq)]
+//! コンソールに表示する構造化ログ（時刻・レベル・発生元・リクエスト ID・所要時間）
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 既定で保持するログの件数
+pub const DEFAULT_CONSOLE_LOG_CAPACITY: usize = 1000;
+
+/// モデルへの入力か、モデルの出力か、エラーか
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ConsoleLogKind {
     Input,
     Output,
     Error,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl ConsoleLogKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConsoleLogKind::Input => "Input",
+            ConsoleLogKind::Output => "Output",
+            ConsoleLogKind::Error => "Error",
+        }
+    }
+
+    fn default_level(self) -> ConsoleLogLevel {
+        match self {
+            ConsoleLogKind::Input | ConsoleLogKind::Output => ConsoleLogLevel::Info,
+            ConsoleLogKind::Error => ConsoleLogLevel::Error,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleLogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl ConsoleLogLevel {
+    pub const ALL: [ConsoleLogLevel; 4] = [
+        ConsoleLogLevel::Debug,
+        ConsoleLogLevel::Info,
+        ConsoleLogLevel::Warn,
+        ConsoleLogLevel::Error,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConsoleLogLevel::Debug => "debug",
+            ConsoleLogLevel::Info => "info",
+            ConsoleLogLevel::Warn => "warn",
+            ConsoleLogLevel::Error => "error",
+        }
+    }
+}
+
+/// ログの発生元（`prompt_builder` / `langchain` / `mcp:<サーバー>` などの文字列で書き出す）
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
+pub enum ConsoleLogSource {
+    /// コントローラー自身（プラグインの再読み込み・書き出しなど）
+    #[default]
+    App,
+    PromptBuilder,
+    LangChain,
+    /// プロンプトビルダーの出力を Ollama へ直接送る経路
+    DirectProvider,
+    /// LangChain もプロンプトビルダーも使わずに Ollama へ送る経路
+    Ollama,
+    Adapter(String),
+    Mcp(String),
+}
+
+impl fmt::Display for ConsoleLogSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsoleLogSource::App => f.write_str("app"),
+            ConsoleLogSource::PromptBuilder => f.write_str("prompt_builder"),
+            ConsoleLogSource::LangChain => f.write_str("langchain"),
+            ConsoleLogSource::DirectProvider => f.write_str("direct_provider"),
+            ConsoleLogSource::Ollama => f.write_str("ollama"),
+            ConsoleLogSource::Adapter(name) => write!(f, "adapter:{}", name),
+            ConsoleLogSource::Mcp(server) => write!(f, "mcp:{}", server),
+        }
+    }
+}
+
+impl From<ConsoleLogSource> for String {
+    fn from(source: ConsoleLogSource) -> Self {
+        source.to_string()
+    }
+}
+
+impl From<String> for ConsoleLogSource {
+    /// 知らない発生元は `App` として読む
+    fn from(value: String) -> Self {
+        if let Some(name) = value.strip_prefix("adapter:") {
+            return ConsoleLogSource::Adapter(name.to_string());
+        }
+        if let Some(server) = value.strip_prefix("mcp:") {
+            return ConsoleLogSource::Mcp(server.to_string());
+        }
+        match value.as_str() {
+            "prompt_builder" => ConsoleLogSource::PromptBuilder,
+            "langchain" => ConsoleLogSource::LangChain,
+            "direct_provider" => ConsoleLogSource::DirectProvider,
+            "ollama" => ConsoleLogSource::Ollama,
+            _ => ConsoleLogSource::App,
+        }
+    }
+}
+
+/// コンソールの 1 行。JSONL で書き出すときはこのまま 1 行の JSON になる
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ConsoleLogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: ConsoleLogLevel,
     pub kind: ConsoleLogKind,
+    pub source: ConsoleLogSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    /// 同じ応答の生成に属する記録に共通の ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// 生成やツール呼び出しにかかった時間
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
     pub content: String,
 }
 
 impl ConsoleLogRecord {
+    /// 今の時刻の記録（レベルは `kind` から決め、発生元は `App`）
     pub fn new(kind: ConsoleLogKind, content: impl Into<String>) -> Self {
         Self {
+            timestamp: Utc::now(),
+            level: kind.default_level(),
             kind,
+            source: ConsoleLogSource::App,
+            conversation_id: None,
+            request_id: None,
+            duration_ms: None,
             content: content.into(),
         }
     }
+
+    pub fn with_level(mut self, level: ConsoleLogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_source(mut self, source: ConsoleLogSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_conversation(mut self, conversation_id: Option<String>) -> Self {
+        self.conversation_id = conversation_id;
+        self
+    }
+
+    pub fn with_request(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration_ms = Some(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX));
+        self
+    }
+}
+
+/// 件数に上限のあるログ。上限を超えたら古い記録から捨てる
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleLogBuffer {
+    records: VecDeque<ConsoleLogRecord>,
+    capacity: usize,
+}
+
+impl Default for ConsoleLogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONSOLE_LOG_CAPACITY)
+    }
+}
+
+impl ConsoleLogBuffer {
+    /// `capacity` が 0 なら 1 件だけ保持する
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.trim();
+    }
+
+    pub fn push(&mut self, record: ConsoleLogRecord) {
+        self.records.push_back(record);
+        self.trim();
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// 古い順
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ConsoleLogRecord> + '_ {
+        self.records.iter()
+    }
+
+    /// 1 行に 1 記録の JSON（JSON Lines）
+    pub fn to_jsonl(&self) -> String {
+        to_jsonl(self.records.iter())
+    }
+
+    fn trim(&mut self) {
+        let overflow = self.records.len().saturating_sub(self.capacity);
+        self.records.drain(..overflow);
+    }
+}
+
+/// 既定の書き出し先（データディレクトリの `exports/console-<日時>.jsonl`）
+pub fn default_console_log_path() -> PathBuf {
+    app_config::get_default_data_dir()
+        .join("exports")
+        .join(format!(
+            "console-{}.jsonl",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ))
+}
+
+/// 記録を 1 行に 1 つの JSON として並べる
+pub fn to_jsonl<'a>(records: impl IntoIterator<Item = &'a ConsoleLogRecord>) -> String {
+    records
+        .into_iter()
+        .filter_map(|record| serde_json::to_string(record).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// コンソールへ記録を送るコールバック
+pub(crate) type ConsoleLogger = Arc<dyn Fn(ConsoleLogRecord) + Send + Sync>;
+
+/// 1 回の応答の生成で出す記録に、共通のリクエスト ID・会話 ID・発生元を付ける
+#[derive(Clone)]
+pub(crate) struct RequestLogger {
+    sink: Option<ConsoleLogger>,
+    request_id: String,
+    conversation_id: Option<String>,
+    started: Instant,
+}
+
+impl RequestLogger {
+    pub(crate) fn new(sink: Option<ConsoleLogger>, conversation_id: Option<String>) -> Self {
+        Self {
+            sink,
+            request_id: new_request_id(),
+            conversation_id,
+            started: Instant::now(),
+        }
+    }
+
+    /// モデルへの入力（所要時間は付けない）
+    pub(crate) fn input(&self, source: ConsoleLogSource, content: impl Into<String>) {
+        self.emit(self.record(ConsoleLogKind::Input, source, content));
+    }
+
+    /// モデルの出力（リクエストの開始からの時間を付ける）
+    pub(crate) fn output(&self, source: ConsoleLogSource, content: impl Into<String>) {
+        let record = self
+            .record(ConsoleLogKind::Output, source, content)
+            .with_duration(self.started.elapsed());
+        self.emit(record);
+    }
+
+    pub(crate) fn error(&self, source: ConsoleLogSource, content: impl Into<String>) {
+        let record = self
+            .record(ConsoleLogKind::Error, source, content)
+            .with_duration(self.started.elapsed());
+        self.emit(record);
+    }
+
+    /// リクエストの ID などを付けたまま記録を出す
+    pub(crate) fn emit(&self, record: ConsoleLogRecord) {
+        if let Some(sink) = &self.sink {
+            sink(record);
+        }
+    }
+
+    pub(crate) fn record(
+        &self,
+        kind: ConsoleLogKind,
+        source: ConsoleLogSource,
+        content: impl Into<String>,
+    ) -> ConsoleLogRecord {
+        ConsoleLogRecord::new(kind, content)
+            .with_source(source)
+            .with_conversation(self.conversation_id.clone())
+            .with_request(self.request_id.clone())
+    }
+}
+
+/// 短いリクエスト ID（UUID の先頭 8 文字）
+fn new_request_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn buffer_drops_the_oldest_records() {
+        let mut buffer = ConsoleLogBuffer::new(2);
+        for content in ["a", "b", "c"] {
+            buffer.push(ConsoleLogRecord::new(ConsoleLogKind::Output, content));
+        }
+        let contents: Vec<&str> = buffer.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["b", "c"]);
+
+        buffer.set_capacity(1);
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer.iter().next().unwrap().content, "c");
+        assert_eq!(ConsoleLogBuffer::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn records_round_trip_through_jsonl() {
+        let mut buffer = ConsoleLogBuffer::default();
+        buffer.push(
+            ConsoleLogRecord::new(ConsoleLogKind::Output, "line 1\nline 2")
+                .with_source(ConsoleLogSource::Mcp("weather".into()))
+                .with_request("abcd1234")
+                .with_duration(Duration::from_millis(42)),
+        );
+        buffer.push(ConsoleLogRecord::new(ConsoleLogKind::Error, "boom"));
+
+        let jsonl = buffer.to_jsonl();
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(r#""source":"mcp:weather""#));
+        assert!(lines[0].contains(r#""duration_ms":42"#));
+        assert!(lines[1].contains(r#""level":"error""#));
+        assert!(!lines[1].contains("request_id"));
+
+        let parsed: ConsoleLogRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(&parsed, buffer.iter().next().unwrap());
+    }
+
+    #[test]
+    fn request_logger_tags_records_of_one_request() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink_records = records.clone();
+        let logger = RequestLogger::new(
+            Some(Arc::new(move |record| {
+                sink_records.lock().unwrap().push(record)
+            })),
+            Some("conv-1".into()),
+        );
+        logger.input(ConsoleLogSource::PromptBuilder, "prompt");
+        logger.output(ConsoleLogSource::DirectProvider, "answer");
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].request_id, records[1].request_id);
+        assert_eq!(records[0].request_id.as_deref().map(str::len), Some(8));
+        assert_eq!(records[1].conversation_id.as_deref(), Some("conv-1"));
+        assert!(records[0].duration_ms.is_none());
+        assert!(records[1].duration_ms.is_some());
+        assert_eq!(records[1].source.to_string(), "direct_provider");
+    }
 }
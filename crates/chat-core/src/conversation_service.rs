@@ -1,10 +1,12 @@
 use std::sync::{Arc, Mutex, MutexGuard};
 
 use chat_history::{
-    Conversation, ConversationManager, ConversationMetadata, HistoryError, Message, MessageRole,
-    Result as HistoryResult,
+    BackupInfo, Conversation, ConversationFilter, ConversationManager, ConversationMetadata,
+    ConversationSettings, HistoryError, Message, MessageRole, Result as HistoryResult,
+    SkippedConversation,
 };
 use serde_json::Value;
+use std::path::Path;
 
 /// 会話データと永続化を仲介するサービス層。
 /// UI やハンドラーが直接 Mutex を触らずに済むよう共通処理をまとめる。
@@ -25,6 +27,20 @@ impl ConversationService {
         }
     }
 
+    /// 同じ保存先で別の会話を扱うサービスを作る（タブやウィンドウごとの会話）。
+    pub fn open(&self, conversation: Conversation) -> Self {
+        Self::new(
+            Arc::new(Mutex::new(conversation)),
+            Arc::clone(&self.manager),
+        )
+    }
+
+    /// 保存済みの会話を読み込む（現在の会話は変えない）。
+    pub fn load(&self, conversation_id: &str) -> HistoryResult<Conversation> {
+        let manager = self.manager_guard()?;
+        manager.load(conversation_id)
+    }
+
     /// 現在の会話スナップショットを取得。
     pub fn snapshot(&self) -> HistoryResult<Conversation> {
         let conv = self.conversation_guard()?;
@@ -36,6 +52,13 @@ impl ConversationService {
         self.snapshot().map(|conv| conv.id).ok()
     }
 
+    /// 現在の会話の設定（ロックが取得できない場合は既定値）。
+    pub fn current_settings(&self) -> ConversationSettings {
+        self.conversation_guard()
+            .map(|conv| conv.settings.clone())
+            .unwrap_or_default()
+    }
+
     /// メッセージ一覧（失敗時は空）。
     pub fn current_messages(&self) -> Vec<Message> {
         self.snapshot()
@@ -49,6 +72,29 @@ impl ConversationService {
         manager.list_metadata()
     }
 
+    /// 会話メタデータ一覧と、読み込めずに一覧から外した会話。
+    pub fn list_conversations_with_skipped(
+        &self,
+    ) -> HistoryResult<(Vec<ConversationMetadata>, Vec<SkippedConversation>)> {
+        let manager = self.manager_guard()?;
+        manager.list_metadata_with_skipped()
+    }
+
+    /// 会話のバックアップ一覧（新しい順）。
+    pub fn list_backups(&self, conversation_id: &str) -> HistoryResult<Vec<BackupInfo>> {
+        let manager = self.manager_guard()?;
+        manager.list_backups(conversation_id)
+    }
+
+    /// 条件に一致する会話メタデータ一覧。
+    pub fn list_filtered(
+        &self,
+        filter: &ConversationFilter,
+    ) -> HistoryResult<Vec<ConversationMetadata>> {
+        let manager = self.manager_guard()?;
+        manager.list_filtered(filter)
+    }
+
     /// 会話を永続化。
     pub fn save_current(&self) -> HistoryResult<()> {
         let snapshot = self.snapshot()?;
@@ -82,6 +128,25 @@ impl ConversationService {
         manager.save(&conversation)
     }
 
+    /// バックアップから会話を復元。現在の会話なら表示中の内容も置き換える。
+    pub fn restore_backup(
+        &self,
+        conversation_id: &str,
+        backup: Option<&Path>,
+    ) -> HistoryResult<Conversation> {
+        let restored = {
+            let manager = self.manager_guard()?;
+            manager.restore_backup(conversation_id, backup)?
+        };
+
+        if self.current_conversation_id().as_deref() == Some(conversation_id) {
+            let mut conv = self.conversation_guard_mut()?;
+            *conv = restored.clone();
+        }
+
+        Ok(restored)
+    }
+
     /// 会話を削除。
     pub fn delete_conversation(&self, conversation_id: &str) -> HistoryResult<()> {
         let manager = self.manager_guard()?;
@@ -100,6 +165,22 @@ impl ConversationService {
         self.save_current()
     }
 
+    /// 指定 ID の会話（現在の会話を含む）を更新し保存。
+    /// フォルダ・タグ・ピン留めなど一覧用の属性変更に使う。
+    pub fn update_conversation<F>(&self, conversation_id: &str, mutator: F) -> HistoryResult<()>
+    where
+        F: FnOnce(&mut Conversation),
+    {
+        if self.current_conversation_id().as_deref() == Some(conversation_id) {
+            return self.mutate_and_save(mutator);
+        }
+
+        let manager = self.manager_guard()?;
+        let mut conversation = manager.load(conversation_id)?;
+        mutator(&mut conversation);
+        manager.save(&conversation)
+    }
+
     /// メッセージを追加して保存。
     pub fn append_message(
         &self,
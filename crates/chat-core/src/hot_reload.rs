@@ -0,0 +1,329 @@
+//! プラグインディレクトリと MCP 設定の変更を監視し、再起動せずに反映する
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::plugins::approval_status;
+use crate::plugins::discovery::plugins_dir;
+use crate::plugins::{AdapterRegistry, PluginEntry, PromptBuilderRegistry, ToolRegistry};
+use crate::{load_mcp_config, register_builtin_prompt_builders, ChatController};
+
+/// 連続したファイル更新（ビルド中の書き込みなど）をまとめる待ち時間
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 起動時と同じ手順でプラグインのレジストリを作る（組み込みビルダーを含む）
+pub fn build_plugin_registries(
+    entries: &[PluginEntry],
+) -> (PromptBuilderRegistry, AdapterRegistry, ToolRegistry) {
+    let mut prompt_registry = PromptBuilderRegistry::from_plugins(entries);
+    register_builtin_prompt_builders(&mut prompt_registry);
+    (
+        prompt_registry,
+        AdapterRegistry::from_plugins(entries),
+        ToolRegistry::from_plugins(entries),
+    )
+}
+
+/// プラグインディレクトリごとのファイル構成のフィンガープリント
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginSnapshot {
+    plugins: BTreeMap<String, u64>,
+}
+
+/// 前回のスナップショットからの変更
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+impl PluginSnapshot {
+    pub fn capture(entries: &[PluginEntry]) -> Self {
+        let plugins = entries
+            .iter()
+            .map(|entry| {
+                // 承認・取り消しや有効・無効の切り替えでも読み込み可否が変わるので再読み込みの対象にする
+                let mut hasher = DefaultHasher::new();
+                fingerprint_dir(&entry.path).hash(&mut hasher);
+                approval_status(entry).hash(&mut hasher);
+                entry.enabled.hash(&mut hasher);
+                (entry.dir_name.clone(), hasher.finish())
+            })
+            .collect();
+        Self { plugins }
+    }
+
+    pub fn diff(&self, newer: &PluginSnapshot) -> PluginChanges {
+        let mut changes = PluginChanges::default();
+        for (name, hash) in &newer.plugins {
+            match self.plugins.get(name) {
+                None => changes.added.push(name.clone()),
+                Some(previous) if previous != hash => changes.updated.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        for name in self.plugins.keys() {
+            if !newer.plugins.contains_key(name) {
+                changes.removed.push(name.clone());
+            }
+        }
+        changes
+    }
+}
+
+impl PluginChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+impl fmt::Display for PluginChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_changes(
+            f,
+            [
+                ("added", &self.added),
+                ("updated", &self.updated),
+                ("removed", &self.removed),
+            ],
+        )
+    }
+}
+
+/// 変更の種類ごとの名前を「added a, b; removed c」の形で書く（無ければ "no changes"）
+///
+/// プラグインと MCP サーバーの再読み込みの要約で共通に使う。
+pub(crate) fn write_changes(
+    f: &mut fmt::Formatter<'_>,
+    groups: [(&str, &Vec<String>); 3],
+) -> fmt::Result {
+    let parts: Vec<String> = groups
+        .iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(label, names)| format!("{} {}", label, names.join(", ")))
+        .collect();
+    if parts.is_empty() {
+        write!(f, "no changes")
+    } else {
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// ディレクトリ内のファイルのパス・サイズ・更新時刻からハッシュを作る
+fn fingerprint_dir(dir: &Path) -> u64 {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files);
+    files.sort();
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, u64, u128)>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(root, &path, files);
+            continue;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        files.push((relative, metadata.len(), modified));
+    }
+}
+
+/// 変更のあった監視対象
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ReloadTargets {
+    plugins: bool,
+    mcp: bool,
+}
+
+struct WatchedPaths {
+    plugins_dir: PathBuf,
+    mcp_config: Option<PathBuf>,
+}
+
+impl WatchedPaths {
+    fn classify(&self, event: &Event, targets: &mut ReloadTargets) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in &event.paths {
+            if self.mcp_config.as_deref() == Some(path.as_path()) {
+                targets.mcp = true;
+            } else if let Ok(relative) = path.strip_prefix(&self.plugins_dir) {
+                // インストーラーの作業ディレクトリ（.staging-* など）は無視する
+                let hidden = relative
+                    .components()
+                    .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+                if !hidden {
+                    targets.plugins = true;
+                }
+            }
+        }
+    }
+}
+
+/// ファイル監視のハンドル。破棄すると監視を止める
+pub struct HotReloadWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl Drop for HotReloadWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl HotReloadWatcher {
+    /// `plugins/` と `mcp_config`（指定時）を監視し、変更をコントローラーへ反映する。
+    /// tokio ランタイム内で呼び出すこと
+    pub fn spawn(
+        controller: ChatController,
+        repo_root: PathBuf,
+        mcp_config: Option<PathBuf>,
+    ) -> notify::Result<Self> {
+        let paths = WatchedPaths {
+            plugins_dir: plugins_dir(&repo_root),
+            mcp_config,
+        };
+        fs::create_dir_all(&paths.plugins_dir).map_err(notify::Error::io)?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            if let Ok(event) = result {
+                let _ = tx.send(event);
+            }
+        })?;
+        watcher.watch(&paths.plugins_dir, RecursiveMode::Recursive)?;
+        if let Some(parent) = paths.mcp_config.as_deref().and_then(Path::parent) {
+            watcher.watch(parent, RecursiveMode::NonRecursive)?;
+        }
+
+        controller.track_plugins(&repo_root);
+        let task = tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let mut targets = ReloadTargets::default();
+                paths.classify(&event, &mut targets);
+                loop {
+                    match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                        Ok(Some(event)) => paths.classify(&event, &mut targets),
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+
+                if targets.plugins {
+                    let controller = controller.clone();
+                    let root = repo_root.clone();
+                    // ライブラリの読み込みや WASM のコンパイルはブロッキング処理
+                    let _ = tokio::task::spawn_blocking(move || {
+                        if let Err(err) = controller.reload_plugins(&root) {
+                            controller.append_console_log(
+                                crate::ConsoleLogKind::Error,
+                                format!("Plugin reload failed: {}", err.message()),
+                            );
+                        }
+                    })
+                    .await;
+                }
+                if targets.mcp {
+                    match load_mcp_config() {
+                        Ok(configs) => controller.reload_mcp_config(configs),
+                        Err(err) => controller.append_console_log(
+                            crate::ConsoleLogKind::Error,
+                            format!("MCP config reload failed: {}", err),
+                        ),
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            task,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dir: &Path, name: &str) -> PluginEntry {
+        PluginEntry {
+            dir_name: name.into(),
+            path: dir.join(name),
+            enabled: true,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn detects_added_updated_and_removed_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["alpha", "beta"] {
+            fs::create_dir_all(dir.path().join(name)).unwrap();
+            fs::write(dir.path().join(name).join("plugin.toml"), "name = \"x\"").unwrap();
+        }
+        let before =
+            PluginSnapshot::capture(&[entry(dir.path(), "alpha"), entry(dir.path(), "beta")]);
+        assert!(before.diff(&before).is_empty());
+
+        fs::write(dir.path().join("alpha").join("lib.wasm"), b"\0asm").unwrap();
+        fs::create_dir_all(dir.path().join("gamma")).unwrap();
+        let after =
+            PluginSnapshot::capture(&[entry(dir.path(), "alpha"), entry(dir.path(), "gamma")]);
+
+        let changes = before.diff(&after);
+        assert_eq!(changes.added, vec!["gamma".to_string()]);
+        assert_eq!(changes.updated, vec!["alpha".to_string()]);
+        assert_eq!(changes.removed, vec!["beta".to_string()]);
+        assert_eq!(
+            changes.to_string(),
+            "added gamma; updated alpha; removed beta"
+        );
+    }
+
+    #[test]
+    fn ignores_installer_directories_and_other_files() {
+        let paths = WatchedPaths {
+            plugins_dir: PathBuf::from("/app/plugins"),
+            mcp_config: Some(PathBuf::from("/app/mcp_servers.json")),
+        };
+        let classify = |path: &str| {
+            let mut targets = ReloadTargets::default();
+            let event = Event::new(EventKind::Any).add_path(PathBuf::from(path));
+            paths.classify(&event, &mut targets);
+            targets
+        };
+
+        assert!(classify("/app/plugins/sample/plugin.toml").plugins);
+        assert!(!classify("/app/plugins/.staging-1/plugin.toml").plugins);
+        assert!(classify("/app/mcp_servers.json").mcp);
+        assert_eq!(classify("/app/other.json"), ReloadTargets::default());
+    }
+}
@@ -2,28 +2,72 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use chat_history::ConversationSettings;
 use langchain_rust::tools::Tool;
 use serde_json::{json, Value};
 
+use super::plugin::PluginLangChainTool;
 use crate::mcp_client::McpTool;
 use crate::mcp_manager::McpManager;
+use crate::plugins::ToolRegistry;
+use crate::tool_calls::{call_with_log, ToolCallLog};
 
-/// LangChain 用の MCP ツール一覧を構築
+/// LangChain 用の MCP ツール一覧を構築（ツールプラグインのツールも含める）
 pub async fn build_mcp_tools(
-    manager: Arc<McpManager>,
+    manager: Option<Arc<McpManager>>,
+    plugin_tools: Option<Arc<ToolRegistry>>,
     on_tool_used: Option<Arc<dyn Fn() + Send + Sync>>,
 ) -> Result<Vec<Arc<dyn Tool>>, String> {
-    let specs = manager.get_all_tools().await?;
+    build_mcp_tools_filtered(
+        manager,
+        plugin_tools,
+        on_tool_used,
+        &ConversationSettings::default(),
+        None,
+    )
+    .await
+}
+
+/// 会話設定で有効なツールだけを LangChain 用に構築。`call_log` があれば呼び出しを記録する
+pub async fn build_mcp_tools_filtered(
+    manager: Option<Arc<McpManager>>,
+    plugin_tools: Option<Arc<ToolRegistry>>,
+    on_tool_used: Option<Arc<dyn Fn() + Send + Sync>>,
+    settings: &ConversationSettings,
+    call_log: Option<ToolCallLog>,
+) -> Result<Vec<Arc<dyn Tool>>, String> {
     let mut tools: Vec<Arc<dyn Tool>> = Vec::new();
 
-    for (server_name, spec) in specs {
-        let tool = McpLangChainTool::new(
-            manager.clone(),
-            server_name,
-            spec,
-            on_tool_used.as_ref().map(Arc::clone),
-        );
-        tools.push(Arc::new(tool) as Arc<dyn Tool>);
+    if let Some(manager) = manager {
+        for (server_name, spec) in manager.get_all_tools().await? {
+            if !settings.is_tool_enabled(&server_name, &spec.name) {
+                continue;
+            }
+            let tool = McpLangChainTool::new(
+                manager.clone(),
+                server_name,
+                spec,
+                on_tool_used.as_ref().map(Arc::clone),
+            )
+            .with_call_log(call_log.clone());
+            tools.push(Arc::new(tool) as Arc<dyn Tool>);
+        }
+    }
+
+    if let Some(registry) = plugin_tools {
+        for (plugin, spec) in registry.tools() {
+            if !settings.is_tool_enabled(&plugin, &spec.name) {
+                continue;
+            }
+            let tool = PluginLangChainTool::new(
+                registry.clone(),
+                plugin,
+                spec,
+                on_tool_used.as_ref().map(Arc::clone),
+            )
+            .with_call_log(call_log.clone());
+            tools.push(Arc::new(tool) as Arc<dyn Tool>);
+        }
     }
 
     Ok(tools)
@@ -38,6 +82,7 @@ pub struct McpLangChainTool {
     description: String,
     input_schema: Value,
     on_tool_used: Option<Arc<dyn Fn() + Send + Sync>>,
+    call_log: Option<ToolCallLog>,
 }
 
 impl McpLangChainTool {
@@ -58,8 +103,15 @@ impl McpLangChainTool {
             description,
             input_schema: spec.input_schema,
             on_tool_used,
+            call_log: None,
         }
     }
+
+    /// 呼び出しを `log` に記録する
+    pub fn with_call_log(mut self, log: Option<ToolCallLog>) -> Self {
+        self.call_log = log;
+        self
+    }
 }
 
 #[async_trait]
@@ -91,10 +143,17 @@ impl Tool for McpLangChainTool {
 
     async fn run(&self, input: Value) -> Result<String, Box<dyn std::error::Error>> {
         let arguments = normalize_arguments(input, &self.input_schema);
-        let result = self
-            .manager
-            .call_tool(&self.server_name, &self.tool_name, arguments)
-            .await;
+        let result = call_with_log(
+            self.call_log.as_ref(),
+            &self.server_name,
+            &self.tool_name,
+            arguments,
+            |arguments| {
+                self.manager
+                    .call_tool(&self.server_name, &self.tool_name, arguments)
+            },
+        )
+        .await;
 
         if let Some(callback) = self.on_tool_used.as_ref().map(Arc::clone) {
             callback();
@@ -106,7 +165,7 @@ impl Tool for McpLangChainTool {
     }
 }
 
-fn normalize_arguments(input: Value, schema: &Value) -> Value {
+pub(super) fn normalize_arguments(input: Value, schema: &Value) -> Value {
     match input {
         Value::String(text) => {
             if let Ok(parsed) = serde_json::from_str(&text) {
@@ -167,7 +226,7 @@ fn infer_primary_property(schema: &Value) -> Option<String> {
     None
 }
 
-fn render_result(value: Value) -> String {
+pub(super) fn render_result(value: Value) -> String {
     match value {
         Value::String(s) => s,
         Value::Number(n) => n.to_string(),
@@ -1,3 +1,5 @@
 pub mod mcp;
+pub mod plugin;
 
-pub use mcp::build_mcp_tools;
+pub use mcp::{build_mcp_tools, build_mcp_tools_filtered};
+pub use plugin::PluginLangChainTool;
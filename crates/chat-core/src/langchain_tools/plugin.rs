@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use langchain_rust::tools::Tool;
+use prompt_spi::ToolSpec;
+use serde_json::{json, Value};
+
+use super::mcp::{normalize_arguments, render_result};
+use crate::plugins::ToolRegistry;
+use crate::tool_calls::{call_with_log, ToolCallLog};
+
+/// LangChain の Tool トレイトへツールプラグインのツールをブリッジ
+pub struct PluginLangChainTool {
+    registry: Arc<ToolRegistry>,
+    plugin: String,
+    tool_name: String,
+    description: String,
+    input_schema: Value,
+    on_tool_used: Option<Arc<dyn Fn() + Send + Sync>>,
+    call_log: Option<ToolCallLog>,
+}
+
+impl PluginLangChainTool {
+    pub fn new(
+        registry: Arc<ToolRegistry>,
+        plugin: String,
+        spec: ToolSpec,
+        on_tool_used: Option<Arc<dyn Fn() + Send + Sync>>,
+    ) -> Self {
+        let description = format!(
+            "{} (plugin: {})",
+            spec.description.unwrap_or_default(),
+            plugin
+        );
+
+        Self {
+            registry,
+            plugin,
+            tool_name: spec.name,
+            description,
+            input_schema: spec.input_schema,
+            on_tool_used,
+            call_log: None,
+        }
+    }
+
+    /// 呼び出しを `log` に記録する
+    pub fn with_call_log(mut self, log: Option<ToolCallLog>) -> Self {
+        self.call_log = log;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for PluginLangChainTool {
+    fn name(&self) -> String {
+        self.tool_name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn parameters(&self) -> Value {
+        if self.input_schema.is_object() {
+            self.input_schema.clone()
+        } else {
+            json!({
+                "type": "object",
+                "properties": {
+                    "input": {
+                        "type": "string",
+                        "description": self.description(),
+                    }
+                },
+                "required": ["input"],
+            })
+        }
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn std::error::Error>> {
+        let arguments = normalize_arguments(input, &self.input_schema);
+        let result = call_with_log(
+            self.call_log.as_ref(),
+            &self.plugin,
+            &self.tool_name,
+            arguments,
+            |arguments| {
+                self.registry
+                    .call_tool(&self.plugin, &self.tool_name, arguments)
+            },
+        )
+        .await;
+
+        if let Some(callback) = self.on_tool_used.as_ref().map(Arc::clone) {
+            callback();
+        }
+
+        let response = result.map_err(|e| anyhow!(e))?;
+
+        Ok(render_result(response))
+    }
+}
@@ -2,27 +2,52 @@ pub mod chat_controller;
 mod conversation_service;
 
 pub mod console_log;
+pub mod hot_reload;
 pub mod langchain_tools;
 pub mod mcp_client;
 pub mod mcp_manager;
 pub mod message_handler;
+pub mod notes;
 pub mod plugins;
 pub mod prompt_builders;
+pub mod slash_commands;
+pub mod tool_calls;
+pub mod turn_trace;
 
 pub use chat_controller::{
     ChatCommand, ChatController, ChatControllerConfig, ChatEvent, ChatState, ControllerError,
     ControllerSubscription, McpServerMetadata, McpServerStatus, McpToolMetadata,
 };
-pub use console_log::{ConsoleLogKind, ConsoleLogRecord};
+pub use console_log::{
+    default_console_log_path, ConsoleLogBuffer, ConsoleLogKind, ConsoleLogLevel, ConsoleLogRecord,
+    ConsoleLogSource, DEFAULT_CONSOLE_LOG_CAPACITY,
+};
 pub use conversation_service::ConversationService;
+pub use hot_reload::{HotReloadWatcher, PluginChanges};
 pub use mcp_client::{
-    create_sample_config, load_mcp_config, save_mcp_config, McpClient, McpServerConfig, McpTool,
+    create_sample_config, load_mcp_config, save_mcp_config, McpClient, McpPrompt,
+    McpPromptArgument, McpServerConfig, McpTool,
 };
-pub use mcp_manager::McpManager;
+pub use mcp_manager::{McpManager, McpReloadSummary};
 pub use message_handler::MessageHandler;
+pub use notes::{ContextNote, NoteStore, DEFAULT_NOTE};
 pub use plugins::{
+    adapter::{AdapterRegistry, AdapterSource},
+    approval::{approval_status, approve_plugin, revoke_approval, ApprovalStatus},
     disable_plugin, discover_plugins, enable_plugin,
+    install::{archive_sha256, installed_plugins, InstallOptions, InstalledPlugin},
+    install_plugin,
     metadata::PluginEntry,
-    prompt_builder::{HostPromptBuilderFactory, PromptBuilderRegistry, PromptBuilderSource},
+    prompt_builder::{
+        BuilderPreview, HostPromptBuilderFactory, PromptBuilderRegistry, PromptBuilderSource,
+    },
+    tool::{ToolProviderSource, ToolRegistry},
+    uninstall_plugin, update_plugin,
 };
 pub use prompt_builders::register_builtin_prompt_builders;
+pub use slash_commands::{
+    default_export_path, ExportFormat, SlashCommand, SlashCommandRegistry, SlashCommandSource,
+    SlashCommandSpec,
+};
+pub use tool_calls::ToolCallLog;
+pub use turn_trace::TurnTrace;
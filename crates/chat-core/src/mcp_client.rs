@@ -46,6 +46,25 @@ pub struct McpTool {
     pub input_schema: serde_json::Value,
 }
 
+/// サーバーが `prompts/list` で公開するプロンプト
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpPrompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
 impl McpClient {
     /// 新しいMCPクライアントを作成し、サーバープロセスを起動
     pub async fn new(
@@ -191,6 +210,57 @@ impl McpClient {
         response.result.ok_or("No result in response".to_string())
     }
 
+    /// プロンプトの一覧（`prompts` に対応しないサーバーはエラーを返す）
+    pub async fn list_prompts(&mut self) -> Result<Vec<McpPrompt>, String> {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: self.next_request_id(),
+            method: "prompts/list".to_string(),
+            params: None,
+        };
+
+        self.send_request(&request).await?;
+        let response = self.receive_response().await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Error: {} - {}", error.code, error.message));
+        }
+
+        let prompts_value = response
+            .result
+            .and_then(|r| r.get("prompts").cloned())
+            .ok_or("No prompts in response")?;
+
+        serde_json::from_value(prompts_value).map_err(|e| format!("Failed to parse prompts: {}", e))
+    }
+
+    /// プロンプトを引数で展開し、メッセージの本文をつないで返す
+    pub async fn get_prompt(
+        &mut self,
+        prompt_name: &str,
+        arguments: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String, String> {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: self.next_request_id(),
+            method: "prompts/get".to_string(),
+            params: Some(serde_json::json!({
+                "name": prompt_name,
+                "arguments": arguments
+            })),
+        };
+
+        self.send_request(&request).await?;
+        let response = self.receive_response().await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Prompt error: {} - {}", error.code, error.message));
+        }
+
+        let result = response.result.ok_or("No result in response")?;
+        Ok(prompt_text(&result))
+    }
+
     async fn send_request(&mut self, request: &JsonRpcRequest) -> Result<(), String> {
         if let Some(stdin) = &mut self.stdin {
             let json = serde_json::to_string(request)
@@ -267,7 +337,24 @@ impl Drop for McpClient {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `prompts/get` の結果からテキストのメッセージを取り出す
+fn prompt_text(result: &serde_json::Value) -> String {
+    result
+        .get("messages")
+        .and_then(|messages| messages.as_array())
+        .map(|messages| {
+            messages
+                .iter()
+                .filter_map(|message| message.get("content"))
+                .filter(|content| content.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|content| content.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct McpServerConfig {
     pub name: String,
     pub command: String,
@@ -299,6 +386,11 @@ pub fn save_mcp_config(configs: &[McpServerConfig]) -> Result<(), String> {
     Ok(())
 }
 
+/// `mcp_servers.json` のパス（実行ファイルと同じディレクトリ）
+pub fn mcp_config_path() -> Result<PathBuf, String> {
+    ensure_mcp_config_path()
+}
+
 fn ensure_mcp_config_path() -> Result<PathBuf, String> {
     let exe_dir = std::env::current_exe()
         .map_err(|e| format!("Failed to get current executable path: {}", e))?
@@ -371,3 +463,22 @@ pub fn create_sample_config() -> Result<(), String> {
     println!("Sample MCP config created at: {}", config_path.display());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_text_messages_of_a_prompt() {
+        let result = serde_json::json!({
+            "description": "Review code",
+            "messages": [
+                {"role": "user", "content": {"type": "text", "text": "Review this:"}},
+                {"role": "user", "content": {"type": "image", "data": "...", "mimeType": "image/png"}},
+                {"role": "user", "content": {"type": "text", "text": "fn main() {}"}}
+            ]
+        });
+        assert_eq!(prompt_text(&result), "Review this:\n\nfn main() {}");
+        assert_eq!(prompt_text(&serde_json::json!({})), "");
+    }
+}
@@ -1,44 +1,119 @@
-use crate::mcp_client::{McpClient, McpServerConfig, McpTool};
+use crate::hot_reload::write_changes;
+use crate::mcp_client::{McpClient, McpPrompt, McpServerConfig, McpTool};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 /// 複数のMCPサーバーを管理
 pub struct McpManager {
     clients: Arc<Mutex<HashMap<String, McpClient>>>,
-    configs: Vec<McpServerConfig>,
+    configs: Mutex<Vec<McpServerConfig>>,
+}
+
+/// `McpManager::reload` で変わったサーバー名
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct McpReloadSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// コマンド・引数・環境変数が変わり再起動したサーバー
+    pub restarted: Vec<String>,
+}
+
+impl McpReloadSummary {
+    pub fn diff(old: &[McpServerConfig], new: &[McpServerConfig]) -> Self {
+        let find = |list: &[McpServerConfig], name: &str| {
+            list.iter().find(|cfg| cfg.name == name).cloned()
+        };
+        let mut summary = Self::default();
+        for cfg in new {
+            match find(old, &cfg.name) {
+                None => summary.added.push(cfg.name.clone()),
+                Some(previous) if previous != *cfg => summary.restarted.push(cfg.name.clone()),
+                Some(_) => {}
+            }
+        }
+        for cfg in old {
+            if find(new, &cfg.name).is_none() {
+                summary.removed.push(cfg.name.clone());
+            }
+        }
+        summary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.restarted.is_empty()
+    }
+}
+
+impl fmt::Display for McpReloadSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_changes(
+            f,
+            [
+                ("added", &self.added),
+                ("restarted", &self.restarted),
+                ("removed", &self.removed),
+            ],
+        )
+    }
 }
 
 impl McpManager {
     pub fn new(configs: Vec<McpServerConfig>) -> Self {
         Self {
             clients: Arc::new(Mutex::new(HashMap::new())),
-            configs,
+            configs: Mutex::new(configs),
         }
     }
 
+    pub async fn configs(&self) -> Vec<McpServerConfig> {
+        self.configs.lock().await.clone()
+    }
+
     pub async fn initialize_all(&self) -> Result<(), String> {
+        let configs = self.configs().await;
         let mut clients = self.clients.lock().await;
-
-        for config in &self.configs {
-            match McpClient::new(&config.command, &config.args, config.env.clone()).await {
-                Ok(mut client) => {
-                    if let Err(e) = client.initialize().await {
-                        eprintln!("Failed to initialize MCP server '{}': {}", config.name, e);
-                        continue;
-                    }
-                    clients.insert(config.name.clone(), client);
-                    eprintln!("MCP server '{}' initialized successfully", config.name);
-                }
-                Err(e) => {
-                    eprintln!("Failed to start MCP server '{}': {}", config.name, e);
-                }
+        for config in &configs {
+            if let Some(client) = start_client(config).await {
+                clients.insert(config.name.clone(), client);
             }
         }
 
         Ok(())
     }
 
+    /// 設定を差し替え、追加・変更されたサーバーだけを起動し直す。
+    /// まだ一度も起動していなければ、次の利用時にまとめて起動する
+    pub async fn reload(&self, configs: Vec<McpServerConfig>) -> McpReloadSummary {
+        let summary = {
+            let mut current = self.configs.lock().await;
+            let summary = McpReloadSummary::diff(&current, &configs);
+            *current = configs.clone();
+            summary
+        };
+        if summary.is_empty() {
+            return summary;
+        }
+
+        let mut clients = self.clients.lock().await;
+        if clients.is_empty() {
+            return summary;
+        }
+        // 削除した McpClient はプロセスを終了させる
+        for name in summary.removed.iter().chain(&summary.restarted) {
+            clients.remove(name);
+        }
+        for config in configs.iter().filter(|cfg| {
+            summary.added.contains(&cfg.name) || summary.restarted.contains(&cfg.name)
+        }) {
+            if let Some(client) = start_client(config).await {
+                clients.insert(config.name.clone(), client);
+            }
+        }
+        summary
+    }
+
     async fn ensure_initialized(&self) -> Result<(), String> {
         let needs_init = {
             let clients = self.clients.lock().await;
@@ -89,6 +164,40 @@ impl McpManager {
         client.call_tool(tool_name, arguments).await
     }
 
+    /// 全サーバーのプロンプト。`prompts` に対応しないサーバーは読み飛ばす
+    pub async fn get_all_prompts(&self) -> Result<Vec<(String, McpPrompt)>, String> {
+        self.ensure_initialized().await?;
+        let mut clients = self.clients.lock().await;
+        let mut all_prompts = Vec::new();
+
+        for (server_name, client) in clients.iter_mut() {
+            if let Ok(prompts) = client.list_prompts().await {
+                for prompt in prompts {
+                    all_prompts.push((server_name.clone(), prompt));
+                }
+            }
+        }
+
+        all_prompts.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+        Ok(all_prompts)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        server_name: &str,
+        prompt_name: &str,
+        arguments: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String, String> {
+        self.ensure_initialized().await?;
+        let mut clients = self.clients.lock().await;
+
+        let client = clients
+            .get_mut(server_name)
+            .ok_or_else(|| format!("MCP server '{}' not found", server_name))?;
+
+        client.get_prompt(prompt_name, arguments).await
+    }
+
     #[allow(dead_code)]
     pub async fn find_server_for_tool(&self, tool_name: &str) -> Result<String, String> {
         self.ensure_initialized().await?;
@@ -135,3 +244,49 @@ impl McpManager {
         description
     }
 }
+
+async fn start_client(config: &McpServerConfig) -> Option<McpClient> {
+    match McpClient::new(&config.command, &config.args, config.env.clone()).await {
+        Ok(mut client) => {
+            if let Err(e) = client.initialize().await {
+                eprintln!("Failed to initialize MCP server '{}': {}", config.name, e);
+                return None;
+            }
+            eprintln!("MCP server '{}' initialized successfully", config.name);
+            Some(client)
+        }
+        Err(e) => {
+            eprintln!("Failed to start MCP server '{}': {}", config.name, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str, command: &str) -> McpServerConfig {
+        McpServerConfig {
+            name: name.into(),
+            command: command.into(),
+            args: Vec::new(),
+            env: None,
+        }
+    }
+
+    #[test]
+    fn diffs_server_configs_by_name() {
+        let old = [config("weather", "weather"), config("files", "files")];
+        let new = [config("weather", "weather-v2"), config("search", "search")];
+        let summary = McpReloadSummary::diff(&old, &new);
+        assert_eq!(summary.added, vec!["search".to_string()]);
+        assert_eq!(summary.removed, vec!["files".to_string()]);
+        assert_eq!(summary.restarted, vec!["weather".to_string()]);
+        assert!(McpReloadSummary::diff(&new, &new).is_empty());
+        assert_eq!(
+            summary.to_string(),
+            "added search; restarted weather; removed files"
+        );
+    }
+}
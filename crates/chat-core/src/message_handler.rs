@@ -1,23 +1,34 @@
-use crate::console_log::{ConsoleLogKind, ConsoleLogRecord};
-use crate::langchain_tools::build_mcp_tools;
+use crate::console_log::{
+    ConsoleLogKind, ConsoleLogLevel, ConsoleLogRecord, ConsoleLogSource, ConsoleLogger,
+    RequestLogger,
+};
+use crate::langchain_tools::build_mcp_tools_filtered;
 use crate::mcp_manager::McpManager;
-use crate::plugins::{PromptBuilderRegistry, PromptBuilderSource};
-use chat_history::{Message, MessageRole};
-use langchain_bridge::{LangChainEngine, LangChainToolAgent};
-use model_provider::{ollama_impl::OllamaProvider, ModelProvider};
+use crate::notes::{ContextNote, NoteStore};
+use crate::plugins::{
+    AdapterRegistry, AdapterSource, BuilderPreview, PromptBuilderRegistry, PromptBuilderSource,
+    ToolRegistry,
+};
+use crate::tool_calls::{call_with_log, ToolCallLog};
+use crate::turn_trace::TurnTrace;
+use chat_history::{ConversationSettings, GenerationOptions, Message, MessageRole, ToolCallRecord};
+use langchain_bridge::{LangChainEngine, LangChainToolAgent, OllamaGenerationOptions};
+use model_provider::ollama_impl::{GenerateOptions, OllamaProvider};
+use model_provider::{GenerateResult, ModelProvider, ProviderError};
 use prompt_spi::{
     ConversationRole as SpiConversationRole, ConversationTurn as SpiConversationTurn,
     DirectiveSource as SpiDirectiveSource, PromptAgentMode, PromptContext as SpiPromptContext,
     PromptPayload, SystemDirective as SpiSystemDirective, ToolInvocation, ToolSpec as SpiToolSpec,
 };
-use serde_json::{self, json};
-use std::sync::{Arc, Mutex};
+use serde_json::{self, json, Map, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use tokio::sync::{mpsc, Mutex as AsyncMutex};
 
 use crate::ConversationService;
 
 type RefreshCallback = Arc<dyn Fn() + Send + Sync>;
-type ConsoleLogger = Arc<dyn Fn(ConsoleLogRecord) + Send + Sync>;
+type AgentSlot = Arc<AsyncMutex<Option<CachedAgent>>>;
 
 const DEFAULT_LOCALE: &str = "ja-JP";
 const HOST_DIRECTIVE: &str =
@@ -33,13 +44,23 @@ pub struct MessageHandler {
     ollama_url: String,
     model_name: Arc<Mutex<String>>,
     mcp_manager: Option<Arc<McpManager>>,
-    langchain_agent: Arc<AsyncMutex<Option<LangChainToolAgent>>>,
-    prompt_registry: Option<Arc<PromptBuilderRegistry>>,
+    langchain_agent: AgentSlot,
+    /// ホットリロードで差し替える。実行中のセッションは取得済みの `Arc` を使い続ける
+    prompt_registry: RwLock<Option<Arc<PromptBuilderRegistry>>>,
+    adapter_registry: RwLock<Option<Arc<AdapterRegistry>>>,
+    tool_registry: RwLock<Option<Arc<ToolRegistry>>>,
     mcp_refresh_callback: Mutex<Option<RefreshCallback>>,
     console_logger: Mutex<Option<ConsoleLogger>>,
+    /// 会話の設定 `context_notes` で選んだノートの読み込み元
+    note_store: Mutex<Option<NoteStore>>,
+    /// ツール呼び出しの記録。応答を保存するときに取り出して会話に残す
+    tool_calls: ToolCallLog,
+    /// 会話に保存し終えていないバックグラウンドの応答の数
+    pending_turns: Arc<AtomicUsize>,
 }
 
 impl MessageHandler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         conversation_service: ConversationService,
         ui_update_tx: mpsc::UnboundedSender<()>,
@@ -48,6 +69,8 @@ impl MessageHandler {
         model_name: String,
         mcp_manager: Option<Arc<McpManager>>,
         prompt_registry: Option<Arc<PromptBuilderRegistry>>,
+        adapter_registry: Option<Arc<AdapterRegistry>>,
+        tool_registry: Option<Arc<ToolRegistry>>,
     ) -> Self {
         let langchain_agent = Arc::new(AsyncMutex::new(None));
         let model_state = Arc::new(Mutex::new(model_name));
@@ -60,24 +83,31 @@ impl MessageHandler {
             model_name: model_state,
             mcp_manager,
             langchain_agent,
-            prompt_registry,
+            prompt_registry: RwLock::new(prompt_registry),
+            adapter_registry: RwLock::new(adapter_registry),
+            tool_registry: RwLock::new(tool_registry),
             mcp_refresh_callback: Mutex::new(None),
             console_logger: Mutex::new(None),
+            note_store: Mutex::new(None),
+            tool_calls: ToolCallLog::default(),
+            pending_turns: Arc::new(AtomicUsize::new(0)),
         };
 
         if handler.use_langchain {
-            if let Some(manager) = handler.mcp_manager.clone() {
+            let tools = handler.tool_sources();
+            if !tools.is_empty() {
                 let agent_slot = handler.langchain_agent.clone();
-                let model_snapshot = handler.current_model();
+                let profile = handler.current_profile();
                 let console_logger = handler.console_logger();
                 tokio::spawn(async move {
-                    if let Err(e) =
-                        ensure_tool_agent(agent_slot, manager, model_snapshot, None).await
-                    {
+                    if let Err(e) = ensure_tool_agent(agent_slot, tools, profile, None).await {
                         emit_console_log(
                             &console_logger,
-                            ConsoleLogKind::Error,
-                            format!("Failed to initialize MCP tools: {}", e),
+                            ConsoleLogRecord::new(
+                                ConsoleLogKind::Error,
+                                format!("Failed to initialize MCP tools: {}", e),
+                            )
+                            .with_source(ConsoleLogSource::LangChain),
                         );
                         eprintln!("Failed to initialize MCP tools: {}", e);
                     }
@@ -88,6 +118,34 @@ impl MessageHandler {
         handler
     }
 
+    /// 同じ接続先・MCP・プラグインのレジストリで、別の会話を扱うハンドラーを作る
+    pub fn for_conversation(
+        &self,
+        conversation_service: ConversationService,
+        ui_update_tx: mpsc::UnboundedSender<()>,
+        model_name: String,
+    ) -> Self {
+        let handler = Self::new(
+            conversation_service,
+            ui_update_tx,
+            self.use_langchain,
+            self.ollama_url.clone(),
+            model_name,
+            self.mcp_manager.clone(),
+            self.prompt_registry(),
+            self.adapter_registry
+                .read()
+                .ok()
+                .and_then(|guard| guard.clone()),
+            self.tool_registry
+                .read()
+                .ok()
+                .and_then(|guard| guard.clone()),
+        );
+        handler.set_note_store(self.note_store());
+        handler
+    }
+
     /// ユーザーメッセージを処理し、AI応答を生成
     pub fn handle_user_message(&self, user_input: String) {
         if let Err(err) = self.record_user_message(&user_input) {
@@ -95,13 +153,28 @@ impl MessageHandler {
             return;
         }
         let _ = self.ui_update_tx.send(()); // UI更新通知
+        self.respond_to(user_input);
+    }
 
-        let active_model = self.current_model();
-        let prompt_builder = self.select_prompt_builder(&active_model);
-        let needs_async = prompt_builder.is_some() || self.use_langchain;
+    /// 記録済みのユーザーメッセージに対する応答を生成する（再生成にも使う）
+    pub fn respond_to(&self, user_input: String) {
+        let profile = self.current_profile();
+        let prompt_builder = self.select_prompt_builder(&profile);
+        // プロンプトビルダーが優先。無ければモデル用のアダプタを使う
+        let adapter = prompt_builder
+            .is_none()
+            .then(|| self.select_adapter(&profile.model))
+            .flatten();
+        let needs_async = prompt_builder.is_some() || adapter.is_some() || self.use_langchain;
         let refresh_hook = self.tool_refresh_callback();
-        let console_logger = self.console_logger();
-
+        let log = RequestLogger::new(
+            self.console_logger(),
+            self.conversation_service.current_conversation_id(),
+        );
+        self.tool_calls.set_console(Some(log.clone()));
+        let notes = self.context_notes(&profile);
+
+        let turn = self.begin_turn();
         if needs_async {
             if let Err(err) = self.conversation_service.append_message_with_metadata(
                 MessageRole::System,
@@ -117,37 +190,46 @@ impl MessageHandler {
             let service_bg = self.conversation_service.clone();
             let ui_tx_bg = self.ui_update_tx.clone();
             let ollama_url = self.ollama_url.clone();
-            let model_name = active_model.clone();
-            let manager = self.mcp_manager.clone();
+            let profile = profile.clone();
+            let tools = self.tool_sources();
             let agent_slot = self.langchain_agent.clone();
             let refresh_hook_clone = refresh_hook.clone();
-            let console_logger_clone = console_logger.clone();
+            let log = log.clone();
+
+            let builder_metadata = prompt_builder_metadata(&builder_source);
+            let tool_log = self.tool_calls.clone();
 
             tokio::spawn(async move {
+                let _turn = turn;
                 let session_config = PromptBuilderSessionConfig {
                     ollama_url: ollama_url.clone(),
-                    manager,
+                    tools,
                     agent_slot,
                     refresh_callback: refresh_hook_clone,
-                    console_logger: console_logger_clone,
+                    log,
+                    notes,
                 };
+                let mut trace = TurnTrace::default();
                 match run_prompt_builder_session(
                     builder_source,
                     service_bg.clone(),
-                    model_name,
+                    profile,
                     session_config,
+                    &mut trace,
                 )
                 .await
                 {
                     Ok(result) => {
-                        let metadata = result
-                            .used_mcp
-                            .then(|| mcp_response_metadata("prompt_builder"));
+                        let mut metadata = builder_metadata;
+                        if result.used_mcp {
+                            merge_metadata(&mut metadata, mcp_response_metadata("prompt_builder"));
+                        }
                         if let Err(err) = finalize_response(
                             &service_bg,
                             MessageRole::Assistant,
                             result.response,
-                            metadata,
+                            trace.attach_to(Some(metadata)),
+                            tool_log.take(),
                         ) {
                             eprintln!("Failed to record assistant response: {}", err);
                         }
@@ -157,7 +239,8 @@ impl MessageHandler {
                             &service_bg,
                             MessageRole::Error,
                             format!("Error: {}", e),
-                            None,
+                            trace.attach_to(Some(builder_metadata)),
+                            tool_log.take(),
                         ) {
                             eprintln!("Failed to record error response: {}", err);
                         }
@@ -170,30 +253,90 @@ impl MessageHandler {
             return;
         }
 
+        if let Some(adapter_source) = adapter {
+            let service_bg = self.conversation_service.clone();
+            let ui_tx_bg = self.ui_update_tx.clone();
+            let user_text = user_input.clone();
+            let tool_log = self.tool_calls.clone();
+            let session_config = AdapterSessionConfig {
+                ollama_url: self.ollama_url.clone(),
+                tools: self.tool_sources(),
+                refresh_callback: refresh_hook.clone(),
+                log,
+                notes,
+            };
+
+            tokio::spawn(async move {
+                let _turn = turn;
+                let mut trace = TurnTrace::default();
+                let result = run_adapter_session(
+                    adapter_source,
+                    &user_text,
+                    &profile,
+                    session_config,
+                    &mut trace,
+                )
+                .await;
+                let outcome = match result {
+                    Ok(response) => {
+                        let metadata = response.used_mcp.then(|| mcp_response_metadata("adapter"));
+                        finalize_response(
+                            &service_bg,
+                            MessageRole::Assistant,
+                            response.text,
+                            trace.attach_to(metadata),
+                            tool_log.take(),
+                        )
+                    }
+                    Err(e) => finalize_response(
+                        &service_bg,
+                        MessageRole::Error,
+                        format!("Error: {}", e),
+                        trace.attach_to(None),
+                        tool_log.take(),
+                    ),
+                };
+                if let Err(err) = outcome {
+                    eprintln!("Failed to record adapter response: {}", err);
+                }
+
+                let _ = ui_tx_bg.send(());
+            });
+
+            return;
+        }
+
         if self.use_langchain {
             let service_bg = self.conversation_service.clone();
             let ui_tx_bg = self.ui_update_tx.clone();
             let ollama_url = self.ollama_url.clone();
-            let model_name = active_model.clone();
-            let user_text = user_input.clone();
+            let user_text = prepend_context_notes(&notes, &user_input);
             let agent_slot = self.langchain_agent.clone();
-            let manager = self.mcp_manager.clone();
+            let tools = self.tool_sources();
             let refresh_hook_clone = refresh_hook.clone();
-            let console_logger_clone = console_logger.clone();
+            let tool_log = self.tool_calls.clone();
 
             tokio::spawn(async move {
-                let console_logger_clone = console_logger_clone;
-                let tool_agent = if let Some(manager) = manager {
+                let _turn = turn;
+                let tool_agent = if !tools.is_empty() {
                     match ensure_tool_agent(
                         agent_slot.clone(),
-                        manager,
-                        model_name.clone(),
+                        tools,
+                        profile.clone(),
                         refresh_hook_clone.clone(),
                     )
                     .await
                     {
                         Ok(agent) => Some(agent),
                         Err(e) => {
+                            log.emit(
+                                log.record(
+                                    ConsoleLogKind::Error,
+                                    ConsoleLogSource::LangChain,
+                                    format!("Failed to prepare MCP tools: {}", e),
+                                )
+                                .with_level(ConsoleLogLevel::Warn),
+                            );
                             eprintln!("Failed to prepare MCP tools: {}", e);
                             None
                         }
@@ -202,78 +345,74 @@ impl MessageHandler {
                     None
                 };
 
+                // LangChain にはユーザーの入力（選んだノートを前に置く）をそのまま渡す
+                let mut trace = TurnTrace {
+                    prompt: Some(user_text.clone()),
+                    ..Default::default()
+                };
                 if let Some(agent) = tool_agent {
-                    emit_console_log(
-                        &console_logger_clone,
-                        ConsoleLogKind::Input,
+                    log.input(
+                        ConsoleLogSource::LangChain,
                         format!("LangChain Agent Prompt:\n{}", user_text),
                     );
                     match agent.invoke(&user_text).await {
                         Ok(response) => {
-                            emit_console_log(
-                                &console_logger_clone,
-                                ConsoleLogKind::Output,
-                                response.clone(),
-                            );
+                            log.output(ConsoleLogSource::LangChain, response.clone());
+                            trace.raw_output = Some(response.clone());
                             if let Err(err) = finalize_response(
                                 &service_bg,
                                 MessageRole::Assistant,
                                 response,
-                                Some(mcp_response_metadata("langchain_chat")),
+                                trace.attach_to(Some(mcp_response_metadata("langchain_chat"))),
+                                tool_log.take(),
                             ) {
                                 eprintln!("Failed to record assistant response: {}", err);
                             }
                         }
                         Err(e) => {
-                            emit_console_log(
-                                &console_logger_clone,
-                                ConsoleLogKind::Error,
+                            log.error(
+                                ConsoleLogSource::LangChain,
                                 format!("LangChain agent error: {}", e),
                             );
                             if let Err(err) = finalize_response(
                                 &service_bg,
                                 MessageRole::Error,
                                 format!("Error: {}", e),
-                                None,
+                                trace.attach_to(None),
+                                tool_log.take(),
                             ) {
                                 eprintln!("Failed to record error response: {}", err);
                             }
                         }
                     }
                 } else {
-                    emit_console_log(
-                        &console_logger_clone,
-                        ConsoleLogKind::Input,
+                    log.input(
+                        ConsoleLogSource::Ollama,
                         format!("Ollama Prompt:\n{}", user_text),
                     );
-                    let mut engine = LangChainEngine::new(&ollama_url, &model_name);
+                    let mut engine = profile.engine(&ollama_url);
                     match engine.send_message_simple(&user_text).await {
                         Ok(response) => {
-                            emit_console_log(
-                                &console_logger_clone,
-                                ConsoleLogKind::Output,
-                                response.clone(),
-                            );
+                            log.output(ConsoleLogSource::Ollama, response.clone());
+                            trace.raw_output = Some(response.clone());
                             if let Err(err) = finalize_response(
                                 &service_bg,
                                 MessageRole::Assistant,
                                 response,
-                                None,
+                                trace.attach_to(None),
+                                tool_log.take(),
                             ) {
                                 eprintln!("Failed to record assistant response: {}", err);
                             }
                         }
                         Err(e) => {
-                            emit_console_log(
-                                &console_logger_clone,
-                                ConsoleLogKind::Error,
-                                format!("Ollama error: {}", e),
-                            );
+                            log.error(ConsoleLogSource::Ollama, format!("Ollama error: {}", e));
                             if let Err(err) = finalize_response(
                                 &service_bg,
                                 MessageRole::Error,
                                 format!("Error: {}", e),
-                                None,
+                                trace.attach_to(None),
+                                tool_log.take(),
                             ) {
                                 eprintln!("Failed to record error response: {}", err);
                             }
@@ -295,6 +434,17 @@ impl MessageHandler {
         }
     }
 
+    /// バックグラウンドで生成中の応答（ツールの再実行を含む）があるかどうか。
+    /// 応答は生成を始めたときの会話に保存される
+    pub fn has_pending_turn(&self) -> bool {
+        self.pending_turns.load(Ordering::SeqCst) > 0
+    }
+
+    fn begin_turn(&self) -> PendingTurn {
+        self.pending_turns.fetch_add(1, Ordering::SeqCst);
+        PendingTurn(self.pending_turns.clone())
+    }
+
     pub fn set_model(&self, new_model: String) -> Result<(), String> {
         {
             let mut guard = self
@@ -308,18 +458,17 @@ impl MessageHandler {
         }
 
         if self.use_langchain {
-            if let Some(manager) = self.mcp_manager.clone() {
+            let tools = self.tool_sources();
+            if !tools.is_empty() {
                 let agent_slot = self.langchain_agent.clone();
-                let agent_slot_for_init = agent_slot.clone();
+                let profile = AgentProfile {
+                    model: new_model,
+                    settings: self.conversation_service.current_settings(),
+                };
                 let refresh_callback = self.tool_refresh_callback();
                 tokio::spawn(async move {
-                    {
-                        let mut guard = agent_slot.lock().await;
-                        *guard = None;
-                    }
                     if let Err(e) =
-                        ensure_tool_agent(agent_slot_for_init, manager, new_model, refresh_callback)
-                            .await
+                        ensure_tool_agent(agent_slot, tools, profile, refresh_callback).await
                     {
                         eprintln!("Failed to reinitialize MCP tools: {}", e);
                     }
@@ -348,6 +497,24 @@ impl MessageHandler {
         }
     }
 
+    pub fn set_note_store(&self, store: Option<NoteStore>) {
+        if let Ok(mut guard) = self.note_store.lock() {
+            *guard = store;
+        }
+    }
+
+    fn note_store(&self) -> Option<NoteStore> {
+        self.note_store.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    /// 現在の会話でプロンプトに含めるノート
+    fn context_notes(&self, profile: &AgentProfile) -> Vec<ContextNote> {
+        match self.note_store() {
+            Some(store) => store.context_notes(&profile.settings.context_notes),
+            None => Vec::new(),
+        }
+    }
+
     fn console_logger(&self) -> Option<ConsoleLogger> {
         self.console_logger
             .lock()
@@ -362,35 +529,145 @@ impl MessageHandler {
             .and_then(|guard| guard.as_ref().map(Arc::clone))
     }
 
+    /// プラグインのレジストリを差し替える。古いレジストリ（とライブラリ）は
+    /// 実行中のセッションが終わった時点で破棄される
+    pub fn set_plugin_registries(
+        &self,
+        prompt_registry: Option<Arc<PromptBuilderRegistry>>,
+        adapter_registry: Option<Arc<AdapterRegistry>>,
+        tool_registry: Option<Arc<ToolRegistry>>,
+    ) {
+        if let Ok(mut guard) = self.prompt_registry.write() {
+            *guard = prompt_registry;
+        }
+        if let Ok(mut guard) = self.adapter_registry.write() {
+            *guard = adapter_registry;
+        }
+        if let Ok(mut guard) = self.tool_registry.write() {
+            *guard = tool_registry;
+        }
+        self.reinitialize_langchain_agent();
+    }
+
+    /// 現在のプロンプトビルダーレジストリ
+    pub fn prompt_registry(&self) -> Option<Arc<PromptBuilderRegistry>> {
+        self.prompt_registry.read().ok()?.clone()
+    }
+
+    /// MCP サーバーやツールプラグインのツール一覧が変わったときに LangChain エージェントを作り直す
+    pub fn reload_tools(&self) {
+        self.reinitialize_langchain_agent();
+    }
+
     fn reinitialize_langchain_agent(&self) {
         if !self.use_langchain {
             return;
         }
 
-        let Some(manager) = self.mcp_manager.clone() else {
-            return;
-        };
-
+        let tools = self.tool_sources();
         let agent_slot = self.langchain_agent.clone();
         let refresh_callback = self.tool_refresh_callback();
-        let model_snapshot = self.current_model();
+        let profile = self.current_profile();
         tokio::spawn(async move {
             {
                 let mut guard = agent_slot.lock().await;
                 *guard = None;
             }
-            if let Err(e) =
-                ensure_tool_agent(agent_slot, manager, model_snapshot, refresh_callback).await
-            {
+            if tools.is_empty() {
+                return;
+            }
+            if let Err(e) = ensure_tool_agent(agent_slot, tools, profile, refresh_callback).await {
                 eprintln!("Failed to initialize MCP tools: {}", e);
             }
         });
     }
 
+    /// 記録済みのツール呼び出しを同じ引数でもう一度実行し、結果を会話の末尾に追加する
+    pub fn rerun_tool_call(&self, record: ToolCallRecord) {
+        self.run_tool_call(record.server, record.tool, record.arguments);
+    }
+
+    /// ツールを直接呼び出し、結果を会話の末尾に追加する
+    pub fn run_tool_call(&self, server: String, tool: String, arguments: Value) {
+        let service_bg = self.conversation_service.clone();
+        let ui_tx_bg = self.ui_update_tx.clone();
+        let refresh_hook = self.tool_refresh_callback();
+        let tools = ToolSources {
+            // 応答待ちのターンの記録に混ざらないよう別の記録先を使う
+            log: ToolCallLog::default(),
+            ..self.tool_sources()
+        };
+        tools.log.set_console(Some(RequestLogger::new(
+            self.console_logger(),
+            self.conversation_service.current_conversation_id(),
+        )));
+        let turn = self.begin_turn();
+        tokio::spawn(async move {
+            let _turn = turn;
+            let _ = call_with_log(Some(&tools.log), &server, &tool, arguments, |arguments| {
+                call_tool(&tools, &server, &tool, arguments)
+            })
+            .await;
+            if let Some(callback) = refresh_hook {
+                callback();
+            }
+            let records = tools.log.take();
+            if let Err(err) = service_bg.mutate_and_save(|conversation| {
+                for record in &records {
+                    conversation.add_message(Message::tool_call(record));
+                }
+            }) {
+                eprintln!("Failed to record tool call: {}", err);
+            }
+            let _ = ui_tx_bg.send(());
+        });
+    }
+
+    /// ツールプラグインのうち、指定した名前のツールを持つもの
+    pub fn plugin_tool_servers(&self, tool: &str) -> Vec<String> {
+        let registry = self
+            .tool_registry
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone());
+        registry
+            .map(|registry| {
+                registry
+                    .tools()
+                    .into_iter()
+                    .filter(|(_, spec)| spec.name == tool)
+                    .map(|(plugin, _)| plugin)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn tool_sources(&self) -> ToolSources {
+        ToolSources {
+            mcp: self.mcp_manager.clone(),
+            plugins: self
+                .tool_registry
+                .read()
+                .ok()
+                .and_then(|guard| guard.clone()),
+            log: self.tool_calls.clone(),
+        }
+    }
+
     fn current_model(&self) -> String {
         snapshot_model(&self.model_name)
     }
 
+    /// 現在の会話設定に基づく実行条件（会話にモデル指定が無ければ選択中のモデル）
+    fn current_profile(&self) -> AgentProfile {
+        let settings = self.conversation_service.current_settings();
+        let model = settings
+            .model
+            .clone()
+            .unwrap_or_else(|| self.current_model());
+        AgentProfile { model, settings }
+    }
+
     fn record_user_message(&self, user_input: &str) -> chat_history::Result<()> {
         let message_text = user_input.to_string();
         let title_candidate = derive_title(user_input);
@@ -402,19 +679,222 @@ impl MessageHandler {
         })
     }
 
-    fn select_prompt_builder(&self, model: &str) -> Option<PromptBuilderSource> {
+    /// 会話で指定されたビルダーがモデルに一致すればそれを、無ければ既定のビルダーを使う
+    fn select_prompt_builder(&self, profile: &AgentProfile) -> Option<PromptBuilderSource> {
         self.prompt_registry
+            .read()
+            .ok()?
+            .as_ref()
+            .and_then(|registry| {
+                registry
+                    .resolve_preferred(&profile.model, profile.settings.prompt_builder.as_deref())
+            })
+    }
+
+    /// 現在の会話の文脈を、モデルに一致するすべてのプロンプトビルダーで組み立てる（比較用）
+    pub async fn preview_prompt_builders(&self) -> Result<Vec<BuilderPreview>, String> {
+        let registry = self
+            .prompt_registry()
+            .ok_or_else(|| "No prompt builders are loaded".to_string())?;
+        let profile = self.current_profile();
+        let notes = self.context_notes(&profile);
+        let inputs = PromptInputs::collect(
+            &self.conversation_service,
+            &profile,
+            &self.tool_sources(),
+            &notes,
+        )
+        .await?;
+        // 設定値は `preview_all` がビルダーごとに差し替える
+        Ok(inputs.with_context(&profile, &Map::new(), |ctx| {
+            registry.preview_all(&ctx, profile.settings.prompt_builder.as_deref())
+        }))
+    }
+
+    fn select_adapter(&self, model: &str) -> Option<AdapterSource> {
+        self.adapter_registry
+            .read()
+            .ok()?
             .as_ref()
             .and_then(|registry| registry.resolve(model))
     }
 }
 
+/// 生成中の応答。タスクが終わる（途中で失敗しても）と破棄され、数が戻る
+struct PendingTurn(Arc<AtomicUsize>);
+
+impl Drop for PendingTurn {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// LLM 呼び出しの条件。LangChain エージェントはこれが変わったら作り直す
+#[derive(Clone, PartialEq)]
+struct AgentProfile {
+    model: String,
+    settings: ConversationSettings,
+}
+
+impl AgentProfile {
+    fn instruction(&self) -> Option<&str> {
+        self.settings.system_prompt.as_deref()
+    }
+
+    fn ollama_options(&self) -> Option<OllamaGenerationOptions> {
+        let generation = &self.settings.generation;
+        if generation.is_default() {
+            return None;
+        }
+        let mut options = OllamaGenerationOptions::default();
+        if let Some(temperature) = generation.temperature {
+            options = options.temperature(temperature);
+        }
+        if let Some(top_p) = generation.top_p {
+            options = options.top_p(top_p);
+        }
+        if let Some(max_tokens) = generation.max_tokens {
+            options = options.num_predict(clamp_tokens(max_tokens));
+        }
+        if let Some(seed) = generation.seed {
+            options = options.seed(seed);
+        }
+        Some(options)
+    }
+
+    fn provider_options(&self) -> GenerateOptions {
+        let GenerationOptions {
+            temperature,
+            top_p,
+            max_tokens,
+            seed,
+        } = self.settings.generation;
+        GenerateOptions {
+            temperature,
+            top_p,
+            num_predict: max_tokens.map(clamp_tokens),
+            seed,
+        }
+    }
+
+    /// ツール無しで使う LangChain エンジン
+    fn engine(&self, ollama_url: &str) -> LangChainEngine {
+        let mut engine = LangChainEngine::new(ollama_url, &self.model);
+        if let Some(instruction) = self.instruction() {
+            engine = engine.with_instruction(instruction);
+        }
+        if let Some(options) = self.ollama_options() {
+            engine = engine.with_options(options);
+        }
+        engine
+    }
+}
+
+fn clamp_tokens(max_tokens: u32) -> i32 {
+    i32::try_from(max_tokens).unwrap_or(i32::MAX)
+}
+
+/// 構築済みのエージェントと、その構築条件
+struct CachedAgent {
+    profile: AgentProfile,
+    agent: LangChainToolAgent,
+}
+
+/// モデルに渡すツールの提供元（MCP サーバーとツールプラグイン）と呼び出しの記録先
+#[derive(Clone, Default)]
+struct ToolSources {
+    mcp: Option<Arc<McpManager>>,
+    plugins: Option<Arc<ToolRegistry>>,
+    log: ToolCallLog,
+}
+
+impl ToolSources {
+    fn is_empty(&self) -> bool {
+        self.mcp.is_none() && self.plugins.as_ref().is_none_or(|r| r.is_empty())
+    }
+}
+
 struct PromptBuilderSessionConfig {
     ollama_url: String,
-    manager: Option<Arc<McpManager>>,
-    agent_slot: Arc<AsyncMutex<Option<LangChainToolAgent>>>,
+    tools: ToolSources,
+    agent_slot: AgentSlot,
     refresh_callback: Option<RefreshCallback>,
-    console_logger: Option<ConsoleLogger>,
+    log: RequestLogger,
+    notes: Vec<ContextNote>,
+}
+
+/// プロンプトの組み立てに使う会話・ツール・ノート
+struct PromptInputs {
+    turns: Vec<(SpiConversationRole, String)>,
+    tool_specs: Vec<SpiToolSpec>,
+    /// ユーザーの指示として渡すノート（見出し付きの本文）
+    notes: Vec<String>,
+}
+
+impl PromptInputs {
+    async fn collect(
+        service: &ConversationService,
+        profile: &AgentProfile,
+        tools: &ToolSources,
+        notes: &[ContextNote],
+    ) -> Result<Self, String> {
+        let tool_specs = collect_tool_specs(tools, &profile.settings).await?;
+        let conversation = service.snapshot().map_err(|e| e.to_string())?;
+        let turns = conversation
+            .messages
+            .into_iter()
+            .filter(|message| !should_skip_placeholder(message))
+            .filter_map(|message| {
+                map_message_role(message.role).map(|role| (role, message.content))
+            })
+            .collect();
+        let notes = notes.iter().map(ContextNote::directive).collect();
+        Ok(Self {
+            turns,
+            tool_specs,
+            notes,
+        })
+    }
+
+    fn with_context<R>(
+        &self,
+        profile: &AgentProfile,
+        settings: &Map<String, Value>,
+        f: impl FnOnce(SpiPromptContext<'_>) -> R,
+    ) -> R {
+        let conversation: Vec<SpiConversationTurn> = self
+            .turns
+            .iter()
+            .map(|(role, content)| SpiConversationTurn {
+                role: *role,
+                content,
+            })
+            .collect();
+        let instruction = match profile.instruction() {
+            Some(prompt) => SpiSystemDirective {
+                source: SpiDirectiveSource::User,
+                content: prompt,
+            },
+            None => SpiSystemDirective {
+                source: SpiDirectiveSource::Host,
+                content: HOST_DIRECTIVE,
+            },
+        };
+        let system_directives: Vec<SpiSystemDirective> = std::iter::once(instruction)
+            .chain(self.notes.iter().map(|note| SpiSystemDirective {
+                source: SpiDirectiveSource::User,
+                content: note,
+            }))
+            .collect();
+        f(SpiPromptContext {
+            model: &profile.model,
+            locale: DEFAULT_LOCALE,
+            conversation: &conversation,
+            tools: &self.tool_specs,
+            system_directives: &system_directives,
+            settings,
+        })
+    }
 }
 
 struct PromptSessionResult {
@@ -430,59 +910,44 @@ struct GeneratedResponse {
 async fn run_prompt_builder_session(
     source: PromptBuilderSource,
     service: ConversationService,
-    model_name: String,
+    profile: AgentProfile,
     config: PromptBuilderSessionConfig,
+    trace: &mut TurnTrace,
 ) -> Result<PromptSessionResult, String> {
     let PromptBuilderSessionConfig {
         ollama_url,
-        manager,
+        tools,
         agent_slot,
         refresh_callback,
-        console_logger,
+        log,
+        notes,
     } = config;
     let builder = source.create_builder();
 
-    let tool_specs = collect_tool_specs(manager.clone()).await?;
-
-    let conversation = service.snapshot().map_err(|e| e.to_string())?;
-    let mut roles: Vec<SpiConversationRole> = Vec::with_capacity(conversation.messages.len());
-    let mut message_storage: Vec<String> = Vec::with_capacity(conversation.messages.len());
-
-    for message in conversation.messages.into_iter() {
-        if should_skip_placeholder(&message) {
-            continue;
-        }
-
-        if let Some(role) = map_message_role(message.role) {
-            roles.push(role);
-            message_storage.push(message.content);
-        }
-    }
-
-    let mut conversation_turns: Vec<SpiConversationTurn> = Vec::with_capacity(roles.len());
-    for (idx, role) in roles.iter().enumerate() {
-        conversation_turns.push(SpiConversationTurn {
-            role: *role,
-            content: &message_storage[idx],
-        });
-    }
-
-    let system_directives = vec![SpiSystemDirective {
-        source: SpiDirectiveSource::Host,
-        content: HOST_DIRECTIVE,
-    }];
-
-    let context = SpiPromptContext {
-        model: &model_name,
-        locale: DEFAULT_LOCALE,
-        conversation: &conversation_turns,
-        tools: &tool_specs,
-        system_directives: &system_directives,
-    };
-
-    let payload = builder
-        .build(context)
-        .map_err(|e| format!("Prompt build error: {}", e))?;
+    let inputs = PromptInputs::collect(&service, &profile, &tools, &notes).await?;
+    let plugin_settings = source.settings();
+    let payload = inputs
+        .with_context(&profile, &plugin_settings, |context| builder.build(context))
+        .map_err(|e| {
+            let message = format!("Prompt build error: {}", e);
+            log.error(ConsoleLogSource::PromptBuilder, message.clone());
+            message
+        })?;
+    trace.prompt = payload.prompt.clone();
+    log.emit(
+        log.record(
+            ConsoleLogKind::Input,
+            ConsoleLogSource::PromptBuilder,
+            format!(
+                "Built prompt with {} ({} turns, {} tools, {} notes)",
+                source.id(),
+                inputs.turns.len(),
+                inputs.tool_specs.len(),
+                inputs.notes.len()
+            ),
+        )
+        .with_level(ConsoleLogLevel::Debug),
+    );
 
     let mut used_mcp = false;
 
@@ -491,33 +956,52 @@ async fn run_prompt_builder_session(
             let response = execute_with_langchain(
                 &payload,
                 agent_slot.clone(),
-                manager.clone(),
-                model_name.clone(),
+                tools.clone(),
+                profile.clone(),
                 ollama_url.clone(),
                 refresh_callback.clone(),
-                console_logger.clone(),
+                log.clone(),
             )
             .await?;
             used_mcp |= response.used_mcp;
             response.text
         }
         PromptAgentMode::DirectProvider => {
-            let response =
-                execute_direct_provider(&payload, &ollama_url, &model_name, console_logger.clone())
-                    .await?;
+            let response = execute_direct_provider(&payload, &ollama_url, &profile, &log).await?;
             used_mcp |= response.used_mcp;
             response.text
         }
     };
 
-    let parsed = builder
-        .parse(&raw_output)
-        .map_err(|e| format!("Prompt parse error: {}", e))?;
+    trace.raw_output = Some(raw_output.clone());
+
+    let parsed = builder.parse(&raw_output).map_err(|e| {
+        let message = format!("Prompt parse error: {}", e);
+        log.error(ConsoleLogSource::PromptBuilder, message.clone());
+        message
+    })?;
+    trace.parsed = Some(parsed.clone());
+    log.emit(
+        log.record(
+            ConsoleLogKind::Output,
+            ConsoleLogSource::PromptBuilder,
+            format!(
+                "Parsed output: {} tool requests, final answer {}",
+                parsed.tool_requests.len(),
+                if parsed.final_answer.is_some() {
+                    "present"
+                } else {
+                    "missing"
+                }
+            ),
+        )
+        .with_level(ConsoleLogLevel::Debug),
+    );
 
     if !parsed.tool_requests.is_empty() {
         let tool_text = fulfill_prompt_builder_tools(
             parsed.tool_requests,
-            manager.clone(),
+            tools.clone(),
             refresh_callback.clone(),
         )
         .await?;
@@ -556,30 +1040,31 @@ async fn run_prompt_builder_session(
 async fn execute_direct_provider(
     payload: &PromptPayload,
     ollama_url: &str,
-    model_name: &str,
-    console_logger: Option<ConsoleLogger>,
+    profile: &AgentProfile,
+    log: &RequestLogger,
 ) -> Result<GeneratedResponse, String> {
     let prompt_text = extract_prompt(payload)?;
-    emit_console_log(
-        &console_logger,
-        ConsoleLogKind::Input,
+    log.input(
+        ConsoleLogSource::DirectProvider,
         format!("Direct Provider Prompt:\n{}", prompt_text),
     );
     let provider = OllamaProvider::new(ollama_url)
         .map_err(|e| format!("Invalid Ollama URL '{}': {}", ollama_url, e))?;
 
-    match provider.generate(model_name, &prompt_text).await {
+    match provider
+        .generate_with_options(&profile.model, &prompt_text, &profile.provider_options())
+        .await
+    {
         Ok(result) => {
-            emit_console_log(&console_logger, ConsoleLogKind::Output, result.text.clone());
+            log.output(ConsoleLogSource::DirectProvider, result.text.clone());
             Ok(GeneratedResponse {
                 text: result.text,
                 used_mcp: false,
             })
         }
         Err(e) => {
-            emit_console_log(
-                &console_logger,
-                ConsoleLogKind::Error,
+            log.error(
+                ConsoleLogSource::DirectProvider,
                 format!("Direct provider error: {}", e),
             );
             Err(format!("Direct provider error: {}", e))
@@ -589,65 +1074,70 @@ async fn execute_direct_provider(
 
 async fn execute_with_langchain(
     payload: &PromptPayload,
-    agent_slot: Arc<AsyncMutex<Option<LangChainToolAgent>>>,
-    manager: Option<Arc<McpManager>>,
-    model_name: String,
+    agent_slot: AgentSlot,
+    tools: ToolSources,
+    profile: AgentProfile,
     ollama_url: String,
     refresh_callback: Option<RefreshCallback>,
-    console_logger: Option<ConsoleLogger>,
+    log: RequestLogger,
 ) -> Result<GeneratedResponse, String> {
     let prompt_text = extract_prompt(payload)?;
-    emit_console_log(
-        &console_logger,
-        ConsoleLogKind::Input,
+    log.input(
+        ConsoleLogSource::LangChain,
         format!("LangChain Prompt:\n{}", prompt_text),
     );
 
-    if let Some(manager) = manager {
+    if !tools.is_empty() {
         match ensure_tool_agent(
             agent_slot.clone(),
-            manager.clone(),
-            model_name.clone(),
+            tools.clone(),
+            profile.clone(),
             refresh_callback.clone(),
         )
         .await
         {
             Ok(agent) => match agent.invoke(&prompt_text).await {
                 Ok(response) => {
-                    emit_console_log(&console_logger, ConsoleLogKind::Output, response.clone());
+                    log.output(ConsoleLogSource::LangChain, response.clone());
                     return Ok(GeneratedResponse {
                         text: response,
                         used_mcp: true,
                     });
                 }
                 Err(e) => {
-                    emit_console_log(
-                        &console_logger,
-                        ConsoleLogKind::Error,
+                    log.error(
+                        ConsoleLogSource::LangChain,
                         format!("LangChain agent error: {}", e),
                     );
                     return Err(format!("LangChain agent error: {}", e));
                 }
             },
             Err(err) => {
+                log.emit(
+                    log.record(
+                        ConsoleLogKind::Error,
+                        ConsoleLogSource::LangChain,
+                        format!("Falling back to plain LLM: {}", err),
+                    )
+                    .with_level(ConsoleLogLevel::Warn),
+                );
                 eprintln!("Falling back to plain LLM: {}", err);
             }
         }
     }
 
-    let mut engine = LangChainEngine::new(&ollama_url, &model_name);
+    let mut engine = profile.engine(&ollama_url);
     match engine.send_message_simple(&prompt_text).await {
         Ok(response) => {
-            emit_console_log(&console_logger, ConsoleLogKind::Output, response.clone());
+            log.output(ConsoleLogSource::LangChain, response.clone());
             Ok(GeneratedResponse {
                 text: response,
                 used_mcp: false,
             })
         }
         Err(e) => {
-            emit_console_log(
-                &console_logger,
-                ConsoleLogKind::Error,
+            log.error(
+                ConsoleLogSource::LangChain,
                 format!("LangChain engine error: {}", e),
             );
             Err(format!("LangChain engine error: {}", e))
@@ -655,31 +1145,237 @@ async fn execute_with_langchain(
     }
 }
 
-async fn collect_tool_specs(manager: Option<Arc<McpManager>>) -> Result<Vec<SpiToolSpec>, String> {
-    let mut specs = Vec::new();
-    let Some(manager) = manager else {
-        return Ok(specs);
+struct AdapterSessionConfig {
+    ollama_url: String,
+    tools: ToolSources,
+    refresh_callback: Option<RefreshCallback>,
+    log: RequestLogger,
+    notes: Vec<ContextNote>,
+}
+
+/// アダプタプラグイン経由で 1 ターン分の応答を生成する
+///
+/// 構造化出力が提示したツールの呼び出しなら、ツールを実行してその結果を応答にする。
+async fn run_adapter_session(
+    source: AdapterSource,
+    user_text: &str,
+    profile: &AgentProfile,
+    config: AdapterSessionConfig,
+    trace: &mut TurnTrace,
+) -> Result<GeneratedResponse, String> {
+    let AdapterSessionConfig {
+        ollama_url,
+        tools: tool_sources,
+        refresh_callback,
+        log,
+        notes,
+    } = config;
+    let adapter = source.adapter();
+    let tools: Vec<model_adapter::ToolSpec> = collect_tool_specs(&tool_sources, &profile.settings)
+        .await?
+        .into_iter()
+        .map(|spec| model_adapter::ToolSpec {
+            name: spec.name,
+            description: spec.description,
+            schema: Some(spec.input_schema),
+        })
+        .collect();
+    let tools = (!tools.is_empty()).then_some(tools);
+
+    let instruction = profile.instruction().unwrap_or(HOST_DIRECTIVE);
+    let prompt = format!(
+        "{}\n\n{}",
+        instruction,
+        prepend_context_notes(&notes, user_text)
+    );
+    let adapter_prompt = adapter.build_prompt(&profile.model, &prompt, tools.as_deref());
+    let log_source = ConsoleLogSource::Adapter(adapter.adapter_name().to_string());
+    log.input(
+        log_source.clone(),
+        format!(
+            "Adapter Prompt ({}):\n{}",
+            adapter.adapter_name(),
+            adapter_prompt
+        ),
+    );
+    trace.prompt = Some(adapter_prompt);
+
+    let provider = ConfiguredProvider {
+        inner: OllamaProvider::new(&ollama_url)
+            .map_err(|e| format!("Invalid Ollama URL '{}': {}", ollama_url, e))?,
+        options: profile.provider_options(),
     };
 
-    for (server, tool) in manager.get_all_tools().await? {
-        specs.push(SpiToolSpec {
-            name: format!("{}@{}", tool.name, server),
-            description: Some(format!("{} (server: {})", tool.description, server)),
-            input_schema: tool.input_schema,
+    let result = match adapter
+        .invoke(&provider, &profile.model, &prompt, tools.as_deref())
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            log.error(
+                log_source,
+                format!("Adapter '{}' error: {}", adapter.adapter_name(), e),
+            );
+            return Err(format!("Adapter '{}' error: {}", adapter.adapter_name(), e));
+        }
+    };
+    log.output(log_source, result.text.clone());
+    trace.raw_output = Some(result.text.clone());
+
+    let requests = match (result.structured.as_ref(), tools.as_deref()) {
+        (Some(structured), Some(offered)) => adapter_tool_invocations(structured, offered),
+        _ => Vec::new(),
+    };
+    if requests.is_empty() {
+        return Ok(GeneratedResponse {
+            text: result.text,
+            used_mcp: false,
         });
     }
 
+    let text = fulfill_prompt_builder_tools(requests, tool_sources, refresh_callback).await?;
+    Ok(GeneratedResponse {
+        text,
+        used_mcp: true,
+    })
+}
+
+/// プロンプトビルダーを通さない経路（アダプタ・LangChain・Ollama）で、
+/// 選んだノートをユーザーの入力の前に置く
+fn prepend_context_notes(notes: &[ContextNote], user_text: &str) -> String {
+    notes
+        .iter()
+        .map(ContextNote::directive)
+        .chain(std::iter::once(user_text.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// アダプタの構造化出力から、提示したツールへの呼び出しを取り出す
+///
+/// `{"name", "arguments" | "parameters"}`、その配列、`{"tool_calls": [...]}`
+/// （要素が `function` で包まれた形も可）を受け付ける。`@サーバー名` を省いた名前は
+/// 提示したツールと一意に対応する場合だけ補う。
+fn adapter_tool_invocations(
+    structured: &Value,
+    offered: &[model_adapter::ToolSpec],
+) -> Vec<ToolInvocation> {
+    let calls: Vec<&Value> = match structured {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => match map.get("tool_calls") {
+            Some(Value::Array(items)) => items.iter().collect(),
+            _ => vec![structured],
+        },
+        _ => Vec::new(),
+    };
+
+    calls
+        .into_iter()
+        .filter_map(|call| {
+            let call = call.get("function").unwrap_or(call);
+            let name = resolve_offered_tool(call.get("name")?.as_str()?, offered)?;
+            let arguments = match call.get("arguments").or_else(|| call.get("parameters")) {
+                // 文字列で渡された引数は JSON として解釈する
+                Some(Value::String(text)) => {
+                    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.clone()))
+                }
+                Some(value) => value.clone(),
+                None => json!({}),
+            };
+            Some(ToolInvocation { name, arguments })
+        })
+        .collect()
+}
+
+fn resolve_offered_tool(name: &str, offered: &[model_adapter::ToolSpec]) -> Option<String> {
+    if offered.iter().any(|spec| spec.name == name) {
+        return Some(name.to_string());
+    }
+    if name.contains('@') {
+        return None;
+    }
+    let prefix = format!("{}@", name);
+    let mut matches = offered.iter().filter(|spec| spec.name.starts_with(&prefix));
+    match (matches.next(), matches.next()) {
+        (Some(spec), None) => Some(spec.name.clone()),
+        _ => None,
+    }
+}
+
+/// 会話の生成オプションを付けて Ollama を呼ぶ `ModelProvider`
+struct ConfiguredProvider {
+    inner: OllamaProvider,
+    options: GenerateOptions,
+}
+
+#[async_trait::async_trait]
+impl ModelProvider for ConfiguredProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn health(&self) -> Result<bool, ProviderError> {
+        self.inner.health().await
+    }
+
+    async fn generate(&self, model: &str, prompt: &str) -> Result<GenerateResult, ProviderError> {
+        self.inner
+            .generate_with_options(model, prompt, &self.options)
+            .await
+    }
+}
+
+/// MCP ツールとツールプラグインのツールを `ツール名@サーバー名`（プラグインはディレクトリ名）で並べる
+async fn collect_tool_specs(
+    tools: &ToolSources,
+    settings: &ConversationSettings,
+) -> Result<Vec<SpiToolSpec>, String> {
+    let mut specs = Vec::new();
+
+    if let Some(manager) = tools.mcp.as_ref() {
+        for (server, tool) in manager.get_all_tools().await? {
+            if !settings.is_tool_enabled(&server, &tool.name) {
+                continue;
+            }
+            specs.push(SpiToolSpec {
+                name: format!("{}@{}", tool.name, server),
+                description: Some(format!("{} (server: {})", tool.description, server)),
+                input_schema: tool.input_schema,
+            });
+        }
+    }
+
+    if let Some(registry) = tools.plugins.as_ref() {
+        for (plugin, tool) in registry.tools() {
+            if !settings.is_tool_enabled(&plugin, &tool.name) {
+                continue;
+            }
+            specs.push(SpiToolSpec {
+                name: format!("{}@{}", tool.name, plugin),
+                description: Some(format!(
+                    "{} (plugin: {})",
+                    tool.description.unwrap_or_default(),
+                    plugin
+                )),
+                input_schema: tool.input_schema,
+            });
+        }
+    }
+
     Ok(specs)
 }
 
 async fn fulfill_prompt_builder_tools(
     requests: Vec<ToolInvocation>,
-    manager: Option<Arc<McpManager>>,
+    tools: ToolSources,
     refresh_callback: Option<RefreshCallback>,
 ) -> Result<String, String> {
-    let manager = manager.ok_or_else(|| {
-        "Prompt builder requested tool calls, but no MCP servers are configured".to_string()
-    })?;
+    if tools.is_empty() {
+        return Err(
+            "Prompt builder requested tool calls, but no MCP servers or tool plugins are configured"
+                .to_string(),
+        );
+    }
 
     if requests.is_empty() {
         return Err("No tool requests provided".to_string());
@@ -688,8 +1384,15 @@ async fn fulfill_prompt_builder_tools(
     let mut outputs = Vec::new();
     for invocation in requests {
         let (tool_name, server_name) = split_tool_identifier(&invocation.name)?;
-        let arguments = invocation.arguments;
-        match manager.call_tool(&server_name, &tool_name, arguments).await {
+        let result = call_with_log(
+            Some(&tools.log),
+            &server_name,
+            &tool_name,
+            invocation.arguments,
+            |arguments| call_tool(&tools, &server_name, &tool_name, arguments),
+        )
+        .await;
+        match result {
             Ok(result) => {
                 let pretty =
                     serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string());
@@ -710,11 +1413,31 @@ async fn fulfill_prompt_builder_tools(
     }
 
     Ok(format!(
-        "以下の MCP ツールを実行しました:\n\n{}",
+        "以下のツールを実行しました:\n\n{}",
         outputs.join("\n\n")
     ))
 }
 
+/// ツールを呼び出す。同名の MCP サーバーよりツールプラグインを優先する
+async fn call_tool(
+    tools: &ToolSources,
+    server: &str,
+    tool: &str,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    match tools
+        .plugins
+        .as_ref()
+        .filter(|registry| registry.contains(server, tool))
+    {
+        Some(registry) => registry.call_tool(server, tool, arguments).await,
+        None => match tools.mcp.as_ref() {
+            Some(manager) => manager.call_tool(server, tool, arguments).await,
+            None => Err(format!("Tool not found: {}@{}", tool, server)),
+        },
+    }
+}
+
 fn split_tool_identifier(identifier: &str) -> Result<(String, String), String> {
     let mut parts = identifier.rsplitn(2, '@');
     let server = parts
@@ -743,21 +1466,29 @@ fn map_message_role(role: MessageRole) -> Option<SpiConversationRole> {
         MessageRole::Assistant => Some(SpiConversationRole::Assistant),
         MessageRole::System => Some(SpiConversationRole::System),
         MessageRole::Error => Some(SpiConversationRole::Assistant),
+        MessageRole::Tool => Some(SpiConversationRole::Tool),
     }
 }
 
+/// 考え中のメッセージを応答に置き換える。ターン中のツール呼び出しは応答の直前に残す
 fn finalize_response(
     service: &ConversationService,
     role: MessageRole,
     content: String,
     metadata: Option<serde_json::Value>,
+    tool_calls: Vec<ToolCallRecord>,
 ) -> chat_history::Result<()> {
-    service.pop_last_if(|msg| is_thinking_message(msg))?;
-    if let Some(metadata) = metadata {
-        service.append_message_with_metadata(role, content, metadata)
-    } else {
-        service.append_message(role, content)
-    }
+    service.pop_last_if(is_thinking_message)?;
+    let response = match metadata {
+        Some(metadata) => Message::with_metadata(role, content, metadata),
+        None => Message::new(role, content),
+    };
+    service.mutate_and_save(|conversation| {
+        for record in &tool_calls {
+            conversation.add_message(Message::tool_call(record));
+        }
+        conversation.add_message(response);
+    })
 }
 
 fn thinking_metadata() -> serde_json::Value {
@@ -775,6 +1506,23 @@ fn is_thinking_message(message: &Message) -> bool {
     }
 }
 
+/// 応答を組み立てたプロンプトビルダー（`origin_label` は `plugin` / `builtin` など）
+fn prompt_builder_metadata(source: &PromptBuilderSource) -> serde_json::Value {
+    json!({
+        "prompt_builder": {
+            "id": source.id(),
+            "name": source.metadata().name,
+            "origin_label": source.origin_label(),
+        }
+    })
+}
+
+fn merge_metadata(target: &mut serde_json::Value, extra: serde_json::Value) {
+    if let (Some(target), serde_json::Value::Object(extra)) = (target.as_object_mut(), extra) {
+        target.extend(extra);
+    }
+}
+
 fn mcp_response_metadata(origin: &str) -> serde_json::Value {
     json!({
         "source": "mcp",
@@ -797,26 +1545,42 @@ fn derive_title(source: &str) -> String {
 }
 
 async fn ensure_tool_agent(
-    slot: Arc<AsyncMutex<Option<LangChainToolAgent>>>,
-    manager: Arc<McpManager>,
-    model: String,
+    slot: AgentSlot,
+    tools: ToolSources,
+    profile: AgentProfile,
     refresh_callback: Option<RefreshCallback>,
 ) -> Result<LangChainToolAgent, String> {
     {
         let guard = slot.lock().await;
-        if let Some(agent) = guard.as_ref() {
-            return Ok(agent.clone());
+        if let Some(cached) = guard.as_ref().filter(|cached| cached.profile == profile) {
+            return Ok(cached.agent.clone());
         }
     }
 
-    let tools = build_mcp_tools(manager.clone(), refresh_callback.clone()).await?;
+    let tools = build_mcp_tools_filtered(
+        tools.mcp,
+        tools.plugins,
+        refresh_callback.clone(),
+        &profile.settings,
+        Some(tools.log.clone()),
+    )
+    .await?;
     if tools.is_empty() {
-        return Err("No MCP tools available".to_string());
+        return Err("No MCP or plugin tools available".to_string());
     }
 
-    let agent = LangChainToolAgent::new(&model, tools).map_err(|e| e.to_string())?;
+    let agent = LangChainToolAgent::with_config(
+        &profile.model,
+        tools,
+        profile.instruction(),
+        profile.ollama_options(),
+    )
+    .map_err(|e| e.to_string())?;
     let mut guard = slot.lock().await;
-    *guard = Some(agent.clone());
+    *guard = Some(CachedAgent {
+        profile,
+        agent: agent.clone(),
+    });
     Ok(agent)
 }
 
@@ -827,12 +1591,57 @@ fn snapshot_model(state: &Arc<Mutex<String>>) -> String {
     }
 }
 
-fn emit_console_log(
-    logger: &Option<ConsoleLogger>,
-    kind: ConsoleLogKind,
-    content: impl Into<String>,
-) {
+fn emit_console_log(logger: &Option<ConsoleLogger>, record: ConsoleLogRecord) {
     if let Some(callback) = logger {
-        callback(ConsoleLogRecord::new(kind, content));
+        callback(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offered() -> Vec<model_adapter::ToolSpec> {
+        vec![
+            model_adapter::ToolSpec::new("search@web", "Search"),
+            model_adapter::ToolSpec::new("read@fs", "Read"),
+        ]
+    }
+
+    #[test]
+    fn adapter_tool_invocations_accepts_common_shapes() {
+        let single = json!({"name": "search@web", "arguments": {"q": "neko"}});
+        let calls = adapter_tool_invocations(&single, &offered());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "search@web");
+        assert_eq!(calls[0].arguments, json!({"q": "neko"}));
+
+        let wrapped = json!({"tool_calls": [
+            {"function": {"name": "read", "arguments": "{\"path\": \"a.txt\"}"}}
+        ]});
+        let calls = adapter_tool_invocations(&wrapped, &offered());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "read@fs");
+        assert_eq!(calls[0].arguments, json!({"path": "a.txt"}));
+    }
+
+    #[test]
+    fn adapter_tool_invocations_ignores_unknown_tools_and_plain_json() {
+        let unknown = json!([{"name": "delete@fs", "parameters": {}}]);
+        assert!(adapter_tool_invocations(&unknown, &offered()).is_empty());
+        assert!(adapter_tool_invocations(&json!({"answer": 42}), &offered()).is_empty());
+    }
+
+    #[test]
+    fn prepend_context_notes_puts_notes_before_the_input() {
+        let notes = vec![ContextNote {
+            name: "glossary".into(),
+            content: "neko = cat\n".into(),
+        }];
+        assert_eq!(
+            prepend_context_notes(&notes, "what is neko?"),
+            "Note \"glossary\":\nneko = cat\n\nwhat is neko?"
+        );
+        assert_eq!(prepend_context_notes(&[], "hello"), "hello");
     }
 }
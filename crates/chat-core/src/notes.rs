@@ -0,0 +1,205 @@
+//! スクラッチパッドの名前付きノート（データディレクトリの `notes/<名前>.md`）
+//!
+//! 会話の設定 `context_notes` で選んだノートは、プロンプトにユーザーの指示
+//! （`DirectiveSource::User`）として含まれる。プロンプトビルダーを使わない経路では
+//! ユーザーの入力の前に置く。
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// 最初から用意するノートの名前
+pub const DEFAULT_NOTE: &str = "scratchpad";
+
+const NOTE_EXTENSION: &str = "md";
+
+/// プロンプトに含めるノートの内容
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextNote {
+    pub name: String,
+    pub content: String,
+}
+
+impl ContextNote {
+    /// システム指示として渡す本文（見出しにノート名を付ける）
+    pub fn directive(&self) -> String {
+        format!("Note \"{}\":\n{}", self.name, self.content.trim_end())
+    }
+}
+
+/// ノートの保存先。ディレクトリは最初の保存で作る。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteStore {
+    dir: PathBuf,
+}
+
+impl NoteStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 既定の保存先（データディレクトリの `notes/`）
+    pub fn default_dir() -> PathBuf {
+        app_config::get_default_data_dir().join("notes")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// ノート名の一覧（名前順）
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == NOTE_EXTENSION))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// ノートを読む（まだ無ければ空）
+    pub fn load(&self, name: &str) -> io::Result<String> {
+        match fs::read_to_string(self.path(name)?) {
+            Ok(content) => Ok(content),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, name: &str, content: &str) -> io::Result<()> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, content)
+    }
+
+    /// ノートの末尾に段落として書き足す
+    pub fn append(&self, name: &str, text: &str) -> io::Result<String> {
+        let mut content = self.load(name)?;
+        let trimmed = content.trim_end().len();
+        content.truncate(trimmed);
+        if !content.is_empty() {
+            content.push_str("\n\n");
+        }
+        content.push_str(text.trim());
+        content.push('\n');
+        self.save(name, &content)?;
+        Ok(content)
+    }
+
+    pub fn delete(&self, name: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(name)?) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// `note`, `note-2`, `note-3` … のうちまだ使われていない名前
+    pub fn unused_name(&self, base: &str) -> io::Result<String> {
+        let existing = self.list()?;
+        let taken = |name: &str| existing.iter().any(|n| n == name);
+        if !taken(base) {
+            return Ok(base.to_string());
+        }
+        Ok((2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|name| !taken(name))
+            .expect("an unused note name exists"))
+    }
+
+    /// 指定のノートを選んだ順に読み込む（無いノートと空のノートは含めない）
+    pub fn context_notes(&self, names: &[String]) -> Vec<ContextNote> {
+        names
+            .iter()
+            .filter_map(|name| match self.load(name) {
+                Ok(content) if !content.trim().is_empty() => Some(ContextNote {
+                    name: name.clone(),
+                    content,
+                }),
+                Ok(_) => None,
+                Err(err) => {
+                    eprintln!("Failed to read note {}: {}", name, err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn path(&self, name: &str) -> io::Result<PathBuf> {
+        validate_note_name(name)?;
+        Ok(self.dir.join(format!("{}.{}", name, NOTE_EXTENSION)))
+    }
+}
+
+/// ノート名はファイル名にそのまま使うので、区切り文字や先頭の `.` を許さない
+pub fn validate_note_name(name: &str) -> io::Result<()> {
+    let valid = !name.trim().is_empty()
+        && name.trim() == name
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':']);
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid note name: {:?}", name),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn notes_are_saved_listed_and_appended() {
+        let dir = tempdir().unwrap();
+        let store = NoteStore::new(dir.path().join("notes"));
+        assert!(store.list().unwrap().is_empty());
+        assert_eq!(store.load("todo").unwrap(), "");
+
+        store.save("todo", "- write tests\n\n").unwrap();
+        store.save("api", "GET /items").unwrap();
+        assert_eq!(store.list().unwrap(), ["api", "todo"]);
+
+        let content = store.append("todo", "  - ship it  ").unwrap();
+        assert_eq!(content, "- write tests\n\n- ship it\n");
+        assert_eq!(store.load("todo").unwrap(), content);
+        assert_eq!(store.unused_name("todo").unwrap(), "todo-2");
+
+        store.delete("api").unwrap();
+        store.delete("api").unwrap();
+        assert_eq!(store.list().unwrap(), ["todo"]);
+    }
+
+    #[test]
+    fn context_notes_skip_missing_and_empty_notes() {
+        let dir = tempdir().unwrap();
+        let store = NoteStore::new(dir.path());
+        store.save("b", "second").unwrap();
+        store.save("a", "first").unwrap();
+        store.save("empty", " \n").unwrap();
+
+        let names = ["b", "missing", "empty", "a"].map(String::from);
+        let notes = store.context_notes(&names);
+        let names: Vec<&str> = notes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["b", "a"]);
+        assert_eq!(notes[0].directive(), "Note \"b\":\nsecond");
+    }
+
+    #[test]
+    fn note_names_cannot_leave_the_directory() {
+        let store = NoteStore::new("notes");
+        for name in ["", " x", "../x", "a/b", "a\\b", ".hidden", "c:x"] {
+            assert!(store.save(name, "x").is_err(), "{:?}", name);
+        }
+        assert!(validate_note_name("ideas 2").is_ok());
+    }
+}
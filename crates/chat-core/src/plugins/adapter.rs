@@ -0,0 +1,218 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use model_adapter::ffi::{AdapterEntrypoint, ForeignAdapter, DEFAULT_ADAPTER_ENTRYPOINT};
+use model_adapter::{ModelAdapter, ToolSpec};
+use model_provider::{GenerateResult, ModelProvider, ProviderError};
+
+use super::approval::{ensure_approved, PluginBinary};
+use super::library::{load_shared_library, PluginLibrary};
+use super::metadata::{PluginEntry, PluginKind, PluginMetadata};
+
+/// モデル名から `ModelAdapter` を引くレジストリ。
+/// `kind = "adapter"` で `library` を持つプラグインを C ABI 経由で読み込む。
+#[derive(Default)]
+pub struct AdapterRegistry {
+    by_model: HashMap<String, Vec<AdapterSource>>,
+}
+
+#[derive(Clone)]
+pub struct AdapterSource {
+    adapter: Arc<dyn ModelAdapter>,
+    priority: i32,
+    origin_label: &'static str,
+    manifest: Option<PluginMetadata>,
+    plugin_dir: Option<PathBuf>,
+}
+
+impl AdapterRegistry {
+    pub fn from_plugins(entries: &[PluginEntry]) -> Self {
+        let mut registry = Self::default();
+
+        for entry in entries {
+            if !entry.enabled {
+                continue;
+            }
+            let manifest = match &entry.metadata {
+                Some(m) => m.clone(),
+                None => continue,
+            };
+            // library を持たないアダプタはマニフェストだけの宣言（従来どおり）
+            if manifest.kind != PluginKind::Adapter || manifest.library.is_none() {
+                continue;
+            }
+
+            match ensure_approved(entry).and_then(|binary| load_adapter(&manifest, &binary)) {
+                Ok(adapter) => {
+                    let models = if manifest.models.is_empty() {
+                        adapter.supported_models()
+                    } else {
+                        manifest.models.clone()
+                    };
+                    let source = AdapterSource {
+                        adapter,
+                        priority: manifest.priority.unwrap_or(0),
+                        origin_label: "plugin",
+                        manifest: Some(manifest),
+                        plugin_dir: Some(entry.path.clone()),
+                    };
+                    for model in models {
+                        registry.insert(model, source.clone());
+                    }
+                }
+                Err(err) => {
+                    eprintln!(
+                        "failed to load adapter plugin '{}': {}",
+                        entry.dir_name, err
+                    );
+                }
+            }
+        }
+
+        registry
+    }
+
+    /// ホスト側で実装したアダプタを登録する（`supported_models` のモデルに対応）
+    pub fn register_host_adapter(
+        &mut self,
+        adapter: Arc<dyn ModelAdapter>,
+        priority: i32,
+        origin_label: &'static str,
+    ) {
+        let source = AdapterSource {
+            adapter: adapter.clone(),
+            priority,
+            origin_label,
+            manifest: None,
+            plugin_dir: None,
+        };
+        for model in adapter.supported_models() {
+            self.insert(model, source.clone());
+        }
+    }
+
+    pub fn resolve(&self, model: &str) -> Option<AdapterSource> {
+        self.by_model
+            .get(model)
+            .and_then(|list| list.first().cloned())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_model.is_empty()
+    }
+
+    fn insert(&mut self, model: String, source: AdapterSource) {
+        let list = self.by_model.entry(model).or_default();
+        list.push(source);
+        list.sort_by_key(|source| Reverse(source.priority));
+    }
+}
+
+impl AdapterSource {
+    pub fn adapter(&self) -> Arc<dyn ModelAdapter> {
+        self.adapter.clone()
+    }
+
+    pub fn name(&self) -> &str {
+        self.adapter.adapter_name()
+    }
+
+    pub fn manifest(&self) -> Option<&PluginMetadata> {
+        self.manifest.as_ref()
+    }
+
+    pub fn plugin_dir(&self) -> Option<PathBuf> {
+        self.plugin_dir.clone()
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn origin_label(&self) -> &'static str {
+        self.origin_label
+    }
+}
+
+/// `binary` の承認は呼び出し側で確かめる
+pub(crate) fn load_adapter(
+    manifest: &PluginMetadata,
+    binary: &PluginBinary,
+) -> Result<Arc<dyn ModelAdapter>> {
+    let entrypoint = manifest
+        .entrypoint
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_ADAPTER_ENTRYPOINT.to_string());
+
+    let library = load_shared_library(binary)?;
+    unsafe {
+        let symbol_name = CString::new(entrypoint.clone())
+            .map_err(|_| anyhow!("invalid entrypoint '{}': contains null byte", entrypoint))?;
+        let constructor: libloading::Symbol<AdapterEntrypoint> = library
+            .get(symbol_name.as_bytes_with_nul())
+            .with_context(|| format!("resolving symbol '{}'", entrypoint))?;
+        let adapter = ForeignAdapter::from_vtable(constructor())
+            .with_context(|| format!("binding adapter from '{}'", entrypoint))?;
+
+        Ok(Arc::new(LibraryBoundAdapter {
+            adapter,
+            _library: library.clone(),
+        }))
+    }
+}
+
+/// vtable はライブラリ内の static を指すため、アダプタが破棄されるまでライブラリを保持する
+struct LibraryBoundAdapter {
+    adapter: ForeignAdapter,
+    _library: Arc<PluginLibrary>,
+}
+
+#[async_trait]
+impl ModelAdapter for LibraryBoundAdapter {
+    fn adapter_name(&self) -> &str {
+        self.adapter.adapter_name()
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.adapter.supported_models()
+    }
+
+    fn build_prompt(&self, model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
+        self.adapter.build_prompt(model, prompt, tools)
+    }
+
+    async fn invoke(
+        &self,
+        provider: &dyn ModelProvider,
+        model: &str,
+        prompt: &str,
+        tools: Option<&[ToolSpec]>,
+    ) -> Result<GenerateResult, ProviderError> {
+        self.adapter.invoke(provider, model, prompt, tools).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model_adapter::Phi4MiniAdapter;
+
+    #[test]
+    fn resolves_host_adapter_by_priority() {
+        let mut registry = AdapterRegistry::default();
+        assert!(registry.is_empty());
+
+        registry.register_host_adapter(Arc::new(Phi4MiniAdapter::new()), 0, "host");
+        let source = registry.resolve("phi4-mini:3.8b").expect("adapter");
+        assert_eq!(source.name(), "phi4-mini-adapter");
+        assert_eq!(source.origin_label(), "host");
+        assert!(source.plugin_dir().is_none());
+        assert!(registry.resolve("unknown:latest").is_none());
+    }
+}
@@ -0,0 +1,322 @@
+//! プラグインの capability 承認
+//!
+//! ネイティブライブラリや WASM を読み込むプラグインは、初回の読み込み前にユーザーの承認が必要。
+//! 承認は `plugin.toml` と読み込むバイナリのハッシュとともに `plugins/approvals.json` に記録し、
+//! 承認後にハッシュが変わったプラグインは再承認されるまで読み込まない。
+//! 読み込みにはハッシュを確かめたのと同じバイト列を使い、確認後の差し替えを読まない。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::library::resolve_library_path;
+use super::metadata::{PluginEntry, PluginMetadata};
+use super::validation::{extract_capabilities, read_manifest};
+
+const APPROVALS_FILE: &str = "approvals.json";
+const MANIFEST_FILE: &str = "plugin.toml";
+
+/// `plugins/approvals.json` に保存する承認記録
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginApproval {
+    /// 承認時の `plugin.toml` とバイナリのハッシュ
+    pub sha256: String,
+    /// 承認時に宣言されていた capability
+    pub capabilities: Vec<String>,
+    /// Unix 時刻（秒）
+    pub approved_at: u64,
+}
+
+/// プラグインの承認状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApprovalStatus {
+    /// コードを読み込まないプラグイン（マニフェストだけの宣言など）
+    NotRequired,
+    Approved,
+    /// まだ承認されていない
+    Pending,
+    /// 承認後に `plugin.toml` かバイナリが変わった
+    Changed,
+}
+
+impl ApprovalStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            ApprovalStatus::NotRequired => "no approval needed",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Pending => "pending approval",
+            ApprovalStatus::Changed => "changed since approval",
+        }
+    }
+}
+
+/// ライブラリか WASM モジュールを読み込むプラグインかどうか
+pub fn requires_approval(entry: &PluginEntry) -> bool {
+    entry
+        .metadata
+        .as_ref()
+        .is_some_and(|m| binary_name(m).is_some())
+}
+
+/// `plugin.toml` の `[capabilities]` で有効にされているもの（名前順）
+pub fn declared_capabilities(entry: &PluginEntry) -> Vec<String> {
+    let mut capabilities: Vec<String> = read_manifest(&entry.path.join(MANIFEST_FILE))
+        .map(|manifest| extract_capabilities(&manifest).into_iter().collect())
+        .unwrap_or_default();
+    capabilities.sort();
+    capabilities
+}
+
+/// `plugin.toml` と読み込むバイナリの sha256
+pub fn plugin_hash(entry: &PluginEntry) -> Result<String> {
+    Ok(PluginBinary::read(entry)?.sha256())
+}
+
+/// 一度だけ読み込んだ `plugin.toml` とバイナリの内容
+pub(crate) struct PluginBinary {
+    /// 読み込むバイナリのパス
+    pub path: PathBuf,
+    pub bytes: Vec<u8>,
+    manifest: Vec<u8>,
+}
+
+impl PluginBinary {
+    pub(crate) fn read(entry: &PluginEntry) -> Result<Self> {
+        Self::read_with_manifest(entry, &entry.path.join(MANIFEST_FILE))
+    }
+
+    /// マニフェストがバイナリと別のディレクトリにある場合（開発中のビルド成果物など）
+    pub(crate) fn read_with_manifest(entry: &PluginEntry, manifest_path: &Path) -> Result<Self> {
+        let metadata = entry
+            .metadata
+            .as_ref()
+            .ok_or_else(|| anyhow!("plugin '{}' has no valid manifest", entry.dir_name))?;
+        let path = binary_path(entry, metadata)?;
+        let manifest = fs::read(manifest_path)
+            .with_context(|| format!("reading {}", manifest_path.display()))?;
+        let bytes = fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+        Ok(Self {
+            path,
+            bytes,
+            manifest,
+        })
+    }
+
+    fn sha256(&self) -> String {
+        let mut hasher = Sha256::new();
+        for bytes in [&self.manifest, &self.bytes] {
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// ハッシュを確かめた `plugin.toml` の `[capabilities]`
+    pub(crate) fn capabilities(&self) -> Result<HashSet<String>> {
+        let manifest = toml::from_str::<toml::Value>(&String::from_utf8_lossy(&self.manifest))
+            .context("parsing toml manifest")?;
+        Ok(extract_capabilities(&manifest))
+    }
+}
+
+pub fn approval_status(entry: &PluginEntry) -> ApprovalStatus {
+    if !requires_approval(entry) {
+        return ApprovalStatus::NotRequired;
+    }
+    let Some(approval) = load_approvals(&approvals_dir(entry))
+        .ok()
+        .and_then(|mut approvals| approvals.remove(&entry.dir_name))
+    else {
+        return ApprovalStatus::Pending;
+    };
+    match plugin_hash(entry) {
+        Ok(hash) if hash == approval.sha256 => ApprovalStatus::Approved,
+        _ => ApprovalStatus::Changed,
+    }
+}
+
+/// 現在の内容と capability で承認を記録する
+pub fn approve_plugin(entry: &PluginEntry) -> Result<PluginApproval> {
+    let approval = PluginApproval {
+        sha256: plugin_hash(entry)?,
+        capabilities: declared_capabilities(entry),
+        approved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    };
+    let dir = approvals_dir(entry);
+    let mut approvals = load_approvals(&dir)?;
+    approvals.insert(entry.dir_name.clone(), approval.clone());
+    save_approvals(&dir, &approvals)?;
+    Ok(approval)
+}
+
+/// 承認を取り消す（次回から読み込まれない）
+pub fn revoke_approval(entry: &PluginEntry) -> Result<()> {
+    let dir = approvals_dir(entry);
+    let mut approvals = load_approvals(&dir)?;
+    if approvals.remove(&entry.dir_name).is_some() {
+        save_approvals(&dir, &approvals)?;
+    }
+    Ok(())
+}
+
+/// 承認済みでなければ読み込みを拒否する。
+/// 承認済みなら、ハッシュを確かめた内容をそのまま返す（読み込みはこのバイト列から行う）。
+pub(crate) fn ensure_approved(entry: &PluginEntry) -> Result<PluginBinary> {
+    let binary = PluginBinary::read(entry)?;
+    let approval = load_approvals(&approvals_dir(entry))
+        .ok()
+        .and_then(|mut approvals| approvals.remove(&entry.dir_name));
+    match approval {
+        Some(approval) if approval.sha256 == binary.sha256() => Ok(binary),
+        Some(_) => bail!(
+            "plugin '{}' changed since it was approved; approve it again to load it",
+            entry.dir_name
+        ),
+        None => bail!(
+            "plugin '{}' has not been approved (capabilities: {})",
+            entry.dir_name,
+            describe_capabilities(&declared_capabilities(entry))
+        ),
+    }
+}
+
+/// 承認ダイアログなどに出す capability の一覧
+pub fn describe_capabilities(capabilities: &[String]) -> String {
+    if capabilities.is_empty() {
+        "none declared".to_string()
+    } else {
+        capabilities.join(", ")
+    }
+}
+
+fn binary_name(metadata: &PluginMetadata) -> Option<&str> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .is_some()
+    };
+    if non_empty(&metadata.wasm) {
+        metadata.wasm.as_deref()
+    } else if non_empty(&metadata.library) {
+        metadata.library.as_deref()
+    } else {
+        None
+    }
+}
+
+fn binary_path(entry: &PluginEntry, metadata: &PluginMetadata) -> Result<PathBuf> {
+    match metadata.wasm.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(wasm) => Ok(entry.path.join(wasm)),
+        None => resolve_library_path(&entry.path, metadata),
+    }
+}
+
+/// 承認記録はプラグインディレクトリの親（`plugins/`）に置く
+fn approvals_dir(entry: &PluginEntry) -> PathBuf {
+    entry
+        .path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| entry.path.clone())
+}
+
+fn load_approvals(dir: &Path) -> Result<BTreeMap<String, PluginApproval>> {
+    let path = dir.join(APPROVALS_FILE);
+    match fs::read_to_string(&path) {
+        Ok(text) => {
+            serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+fn save_approvals(dir: &Path, approvals: &BTreeMap<String, PluginApproval>) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(APPROVALS_FILE);
+    let tmp = dir.join(format!("{}.tmp", APPROVALS_FILE));
+    fs::write(&tmp, serde_json::to_string_pretty(approvals)?)?;
+    fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::validation::validate_manifest;
+
+    fn write_plugin(root: &Path, wasm: &[u8]) -> PluginEntry {
+        let dir = root.join("sample");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            "name = \"sample\"\nkind = \"tool\"\nwasm = \"tool.wasm\"\n\n[capabilities]\nnetwork = true\nfilesystem_read = false\n",
+        )
+        .unwrap();
+        fs::write(dir.join("tool.wasm"), wasm).unwrap();
+        let (metadata, _) = validate_manifest(&dir.join(MANIFEST_FILE)).unwrap();
+        PluginEntry {
+            dir_name: "sample".into(),
+            path: dir,
+            enabled: true,
+            metadata: Some(metadata),
+        }
+    }
+
+    #[test]
+    fn approval_is_tied_to_the_plugin_hash() {
+        let root = tempfile::tempdir().unwrap();
+        let entry = write_plugin(root.path(), b"\0asm v1");
+
+        assert_eq!(approval_status(&entry), ApprovalStatus::Pending);
+        let err = ensure_approved(&entry).err().unwrap().to_string();
+        assert!(err.contains("capabilities: network"), "{}", err);
+
+        let approval = approve_plugin(&entry).unwrap();
+        assert_eq!(approval.capabilities, vec!["network".to_string()]);
+        assert_eq!(approval_status(&entry), ApprovalStatus::Approved);
+        let binary = ensure_approved(&entry).unwrap();
+
+        // バイナリが差し替えられたら読み込まない
+        write_plugin(root.path(), b"\0asm v2");
+        assert_eq!(approval_status(&entry), ApprovalStatus::Changed);
+        assert!(ensure_approved(&entry).is_err());
+
+        // 確認済みの内容は差し替え後も承認時のまま
+        assert_eq!(binary.bytes, b"\0asm v1");
+        assert_eq!(binary.sha256(), approval.sha256);
+        assert!(binary.capabilities().unwrap().contains("network"));
+
+        approve_plugin(&entry).unwrap();
+        assert_eq!(approval_status(&entry), ApprovalStatus::Approved);
+        revoke_approval(&entry).unwrap();
+        assert_eq!(approval_status(&entry), ApprovalStatus::Pending);
+    }
+
+    #[test]
+    fn manifest_only_plugins_need_no_approval() {
+        let entry = PluginEntry {
+            dir_name: "declared".into(),
+            path: PathBuf::from("plugins/declared"),
+            enabled: true,
+            metadata: None,
+        };
+        assert_eq!(approval_status(&entry), ApprovalStatus::NotRequired);
+        assert!(!requires_approval(&entry));
+    }
+}
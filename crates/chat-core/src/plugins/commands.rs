@@ -0,0 +1,107 @@
+//! プラグインのスラッシュコマンド（`plugin.toml` の `[commands]`）
+//!
+//! ```toml
+//! [commands.summarize]
+//! description = "Summarize the given text"
+//! prompt = "Summarize the following text:\n\n{input}"
+//! ```
+//!
+//! `/summarize ...` と入力すると `{input}` を引数で置き換えた本文をユーザーメッセージとして送る。
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// `prompt` の中で引数に置き換える文字列
+pub const INPUT_PLACEHOLDER: &str = "{input}";
+
+/// `[commands.<name>]` の 1 項目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub name: String,
+    pub description: Option<String>,
+    pub prompt: String,
+}
+
+impl PluginCommand {
+    /// 引数を埋め込んだ本文（`{input}` が無ければ末尾に付ける）
+    pub fn expand(&self, input: &str) -> String {
+        let input = input.trim();
+        if self.prompt.contains(INPUT_PLACEHOLDER) {
+            self.prompt.replace(INPUT_PLACEHOLDER, input)
+        } else if input.is_empty() {
+            self.prompt.clone()
+        } else {
+            format!("{}\n\n{}", self.prompt, input)
+        }
+    }
+}
+
+/// マニフェストの `[commands]` を読む（名前順）
+pub fn parse_commands(manifest: &toml::Value) -> Result<Vec<PluginCommand>> {
+    let Some(table) = manifest.get("commands") else {
+        return Ok(Vec::new());
+    };
+    let table = table
+        .as_table()
+        .ok_or_else(|| anyhow!("'commands' must be a table"))?;
+
+    let mut commands = Vec::new();
+    for (name, field) in table {
+        if name.is_empty() || name.chars().any(|c| c.is_whitespace() || c == '/') {
+            bail!("command name '{}' must not contain spaces or '/'", name);
+        }
+        let prompt = field
+            .get("prompt")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("command '{}' requires 'prompt'", name))?;
+        commands.push(PluginCommand {
+            name: name.clone(),
+            description: field
+                .get("description")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            prompt: prompt.to_string(),
+        });
+    }
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_expands_the_input() {
+        let manifest: toml::Value = toml::from_str(
+            r#"
+            [commands.summarize]
+            description = "Summarize"
+            prompt = "Summarize:\n{input}"
+
+            [commands.haiku]
+            prompt = "Write a haiku"
+            "#,
+        )
+        .unwrap();
+        let commands = parse_commands(&manifest).unwrap();
+        assert_eq!(commands[0].name, "haiku");
+        assert_eq!(commands[1].description.as_deref(), Some("Summarize"));
+
+        assert_eq!(commands[1].expand(" cats "), "Summarize:\ncats");
+        assert_eq!(commands[0].expand(""), "Write a haiku");
+        assert_eq!(
+            commands[0].expand("about rain"),
+            "Write a haiku\n\nabout rain"
+        );
+    }
+
+    #[test]
+    fn rejects_commands_without_a_prompt() {
+        let manifest: toml::Value =
+            toml::from_str("[commands.empty]\ndescription = \"nothing\"\n").unwrap();
+        assert!(parse_commands(&manifest).is_err());
+        let manifest: toml::Value = toml::from_str("[commands.\"a b\"]\nprompt = \"x\"\n").unwrap();
+        assert!(parse_commands(&manifest).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! プラグイン開発用のツール（`neko-assistant plugin new|validate|test`）
+//!
+//! 開発者が明示的に指定したディレクトリを対象にするため、ここでの読み込みは承認を求めない。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::adapter::load_adapter;
+use super::approval::PluginBinary;
+use super::library::resolve_library_path;
+use super::metadata::{PluginEntry, PluginKind, PluginMetadata};
+use super::prompt_builder::{PromptBuilderHandle, PromptBuilderRegistry};
+use super::tool::load_tool_provider;
+use super::validation::validate_manifest;
+
+const MANIFEST_FILE: &str = "plugin.toml";
+
+/// `(生成するファイル, テンプレート)`
+const PROMPT_BUILDER_TEMPLATE: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("../../templates/plugin/prompt_builder/Cargo.toml.tmpl"),
+    ),
+    (
+        "plugin.toml",
+        include_str!("../../templates/plugin/prompt_builder/plugin.toml.tmpl"),
+    ),
+    (
+        "README.md",
+        include_str!("../../templates/plugin/prompt_builder/README.md.tmpl"),
+    ),
+    (
+        "src/lib.rs",
+        include_str!("../../templates/plugin/prompt_builder/lib.rs.tmpl"),
+    ),
+    (
+        "tests/smoke.json",
+        include_str!("../../templates/plugin/prompt_builder/smoke.json.tmpl"),
+    ),
+];
+
+const TOOL_TEMPLATE: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("../../templates/plugin/tool/Cargo.toml.tmpl"),
+    ),
+    (
+        "plugin.toml",
+        include_str!("../../templates/plugin/tool/plugin.toml.tmpl"),
+    ),
+    (
+        "README.md",
+        include_str!("../../templates/plugin/tool/README.md.tmpl"),
+    ),
+    (
+        "src/lib.rs",
+        include_str!("../../templates/plugin/tool/lib.rs.tmpl"),
+    ),
+];
+
+/// `plugin new` のオプション
+#[derive(Debug, Clone)]
+pub struct ScaffoldOptions {
+    pub kind: PluginKind,
+    /// 生成する `Cargo.toml` の `prompt-spi = ...` の右辺（例: `{ path = '../crates/prompt-spi' }`）
+    pub prompt_spi: String,
+}
+
+/// `validate_plugin` の結果
+#[derive(Debug, Clone)]
+pub struct PluginCheck {
+    pub metadata: PluginMetadata,
+    pub capabilities: Vec<String>,
+    /// 読み込んだライブラリ・WASM モジュール
+    pub binary: Option<PathBuf>,
+    /// 読み込んで確認できた内容（ABI・エントリポイント・ツール名など）
+    pub details: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// `parent/<name>` にテンプレートからプラグインのクレートを生成する
+pub fn scaffold_plugin(parent: &Path, name: &str, options: &ScaffoldOptions) -> Result<PathBuf> {
+    let valid_name = !name.is_empty()
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_name {
+        bail!(
+            "invalid plugin name '{}': use lowercase letters, digits, '-' and '_'",
+            name
+        );
+    }
+    let template = match options.kind {
+        PluginKind::PromptBuilder => PROMPT_BUILDER_TEMPLATE,
+        PluginKind::Tool => TOOL_TEMPLATE,
+        other => bail!("no template for plugin kind {:?}", other),
+    };
+
+    let dest = parent.join(name);
+    if dest.exists() {
+        bail!("{} already exists", dest.display());
+    }
+    let crate_name = name.replace('-', "_");
+    for (file, text) in template {
+        let path = dest.join(file);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = text
+            .replace("{{name}}", name)
+            .replace("{{crate_name}}", &crate_name)
+            .replace("{{prompt_spi}}", &options.prompt_spi);
+        fs::write(&path, text).with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(dest)
+}
+
+/// マニフェストを検証し、ライブラリ（WASM）を実際に読み込んで ABI とエントリポイントを確かめる。
+/// ライブラリがディレクトリに無ければ `target/debug`・`target/release` も探す。
+pub fn validate_plugin(dir: &Path) -> Result<PluginCheck> {
+    let (metadata, capabilities) = validate_manifest(&dir.join(MANIFEST_FILE))?;
+    let mut capabilities: Vec<String> = capabilities.into_iter().collect();
+    capabilities.sort();
+    let mut check = PluginCheck {
+        metadata: metadata.clone(),
+        capabilities,
+        binary: None,
+        details: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let has_wasm = metadata
+        .wasm
+        .as_deref()
+        .is_some_and(|s| !s.trim().is_empty());
+    let has_library = metadata
+        .library
+        .as_deref()
+        .is_some_and(|s| !s.trim().is_empty());
+    if !has_wasm && !has_library {
+        check
+            .warnings
+            .push("no 'library' or 'wasm': the manifest is only a declaration".into());
+        return Ok(check);
+    }
+
+    let entry = dev_entry(dir, &metadata)?;
+    check.binary = Some(if has_wasm {
+        entry
+            .path
+            .join(metadata.wasm.as_deref().unwrap_or_default())
+    } else {
+        resolve_library_path(&entry.path, &metadata)?
+    });
+    let binary = PluginBinary::read_with_manifest(&entry, &dir.join(MANIFEST_FILE))?;
+
+    match metadata.kind {
+        PluginKind::PromptBuilder => {
+            let handle = PromptBuilderHandle::load(&entry, metadata.clone(), &binary)?;
+            let runtime = handle.metadata();
+            check.details.push(format!(
+                "prompt builder '{}' {} ({:?})",
+                runtime.name, runtime.version, runtime.preferred_agent
+            ));
+            for model in &metadata.models {
+                if !runtime.supported_models.contains(model) {
+                    check.warnings.push(format!(
+                        "model '{}' is in plugin.toml but not in the builder's supported_models",
+                        model
+                    ));
+                }
+            }
+        }
+        PluginKind::Tool => {
+            let provider = load_tool_provider(&entry, &metadata, &binary)?;
+            let names: Vec<String> = provider.tools().into_iter().map(|t| t.name).collect();
+            if names.is_empty() {
+                check.warnings.push("the provider exposes no tools".into());
+            }
+            check.details.push(format!("tools: {}", names.join(", ")));
+        }
+        PluginKind::Adapter => {
+            let adapter = load_adapter(&metadata, &binary)?;
+            check.details.push(format!(
+                "adapter '{}' for {}",
+                adapter.adapter_name(),
+                adapter.supported_models().join(", ")
+            ));
+        }
+        PluginKind::Other => {
+            check
+                .warnings
+                .push("unknown kind: the host will not load this plugin".into());
+        }
+    }
+    Ok(check)
+}
+
+/// `plugin test --plugin <dir>` 用に、そのプラグインだけを登録したレジストリを作る
+pub fn dev_prompt_registry(dir: &Path) -> Result<PromptBuilderRegistry> {
+    let (metadata, _) = validate_manifest(&dir.join(MANIFEST_FILE))?;
+    if metadata.kind != PluginKind::PromptBuilder {
+        bail!("{} is not a prompt_builder plugin", dir.display());
+    }
+    let entry = dev_entry(dir, &metadata)?;
+    let binary = PluginBinary::read_with_manifest(&entry, &dir.join(MANIFEST_FILE))?;
+    let mut registry = PromptBuilderRegistry::default();
+    registry.register_plugin(Arc::new(PromptBuilderHandle::load(
+        &entry, metadata, &binary,
+    )?));
+    Ok(registry)
+}
+
+/// ビルド成果物のあるディレクトリを `PluginEntry` にする
+fn dev_entry(dir: &Path, metadata: &PluginMetadata) -> Result<PluginEntry> {
+    let dir_name = dir
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| dir.display().to_string());
+    let wasm = metadata.wasm.as_deref().filter(|s| !s.trim().is_empty());
+    let candidates = [
+        dir.to_path_buf(),
+        dir.join("target").join("debug"),
+        dir.join("target").join("release"),
+    ];
+    // WASM は capability をマニフェストから読むので、プラグインディレクトリに置いたものだけ扱う
+    let path = match wasm {
+        Some(wasm) if dir.join(wasm).exists() => dir.to_path_buf(),
+        Some(wasm) => bail!("wasm module not found: {}", dir.join(wasm).display()),
+        None => candidates
+            .into_iter()
+            .find(|candidate| resolve_library_path(candidate, metadata).is_ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "library '{}' not found in {} or its target/ directory; run cargo build first",
+                    metadata.library.as_deref().unwrap_or_default(),
+                    dir.display()
+                )
+            })?,
+    };
+    Ok(PluginEntry {
+        dir_name,
+        path,
+        enabled: true,
+        metadata: Some(metadata.clone()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaffolds_a_valid_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let options = ScaffoldOptions {
+            kind: PluginKind::PromptBuilder,
+            prompt_spi: "{ path = '../prompt-spi' }".into(),
+        };
+        let dest = scaffold_plugin(root.path(), "my-builder", &options).unwrap();
+
+        let cargo = fs::read_to_string(dest.join("Cargo.toml")).unwrap();
+        assert!(cargo.contains("name = \"my-builder\""));
+        assert!(cargo.contains("prompt-spi = { path = '../prompt-spi' }"));
+        assert!(dest.join("tests/smoke.json").exists());
+
+        let (metadata, _) = validate_manifest(&dest.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(metadata.kind, PluginKind::PromptBuilder);
+        assert_eq!(metadata.library.as_deref(), Some("my_builder.dll"));
+
+        // 未ビルドのライブラリは検出できない
+        let err = validate_plugin(&dest).unwrap_err().to_string();
+        assert!(err.contains("cargo build"), "{}", err);
+        assert!(scaffold_plugin(root.path(), "my-builder", &options).is_err());
+        assert!(scaffold_plugin(root.path(), "My Builder", &options).is_err());
+    }
+}
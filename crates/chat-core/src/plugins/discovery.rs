@@ -1,28 +1,33 @@
+use crate::plugins::enabled::load_enabled_list;
 use crate::plugins::metadata::PluginEntry;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Discover plugin directories under the repository `plugins/` directory.
-pub fn discover_plugins(repo_root: &Path) -> io::Result<Vec<PluginEntry>> {
+/// Plugin directory in use: `plugins/` next to the executable if it exists,
+/// otherwise the repository `plugins/` directory.
+pub fn plugins_dir(repo_root: &Path) -> PathBuf {
     let exe_plugins_dir = std::env::current_exe()
         .ok()
         .and_then(|p| p.parent().map(|parent| parent.join("plugins")));
 
-    let plugins_dir = if let Some(ref dir) = exe_plugins_dir {
-        if dir.exists() {
-            dir.clone()
-        } else {
-            repo_root.join("plugins")
-        }
-    } else {
-        repo_root.join("plugins")
-    };
+    match exe_plugins_dir {
+        Some(dir) if dir.exists() => dir,
+        _ => repo_root.join("plugins"),
+    }
+}
+
+/// Discover plugin directories under the repository `plugins/` directory.
+pub fn discover_plugins(repo_root: &Path) -> io::Result<Vec<PluginEntry>> {
+    let plugins_dir = plugins_dir(repo_root);
 
     let mut entries = vec![];
     if !plugins_dir.exists() {
         return Ok(entries);
     }
+    // Only plugins listed in `enabled.json` are loaded. `plugin install`
+    // adds new plugins to it and the `enable` / `disable` commands edit it.
+    let enabled_list = load_enabled_list(repo_root)?;
 
     for entry in fs::read_dir(&plugins_dir)? {
         let ent = entry?;
@@ -33,12 +38,10 @@ pub fn discover_plugins(repo_root: &Path) -> io::Result<Vec<PluginEntry>> {
                 .and_then(|s| s.to_str())
                 .unwrap_or("")
                 .to_string();
-            // Determine whether the plugin should be treated as enabled.
-            // Historically we used an `enabled.json` file, but that
-            // management step is unnecessary: load a plugin when a
-            // platform shared library exists in the plugin directory.
-            let enabled = plugin_has_shared_library(&path);
-
+            // Skip installer staging/backup directories.
+            if dir_name.starts_with('.') {
+                continue;
+            }
             let plugin_toml = path.join("plugin.toml");
             let metadata = if plugin_toml.exists() {
                 match crate::plugins::validation::validate_manifest(&plugin_toml) {
@@ -55,6 +58,14 @@ pub fn discover_plugins(repo_root: &Path) -> io::Result<Vec<PluginEntry>> {
                 None
             };
 
+            let enabled = enabled_list.iter().any(|name| {
+                *name == dir_name
+                    || metadata
+                        .as_ref()
+                        .and_then(|m| m.name.as_deref())
+                        .is_some_and(|n| n == name)
+            });
+
             entries.push(PluginEntry {
                 dir_name,
                 path,
@@ -66,21 +77,3 @@ pub fn discover_plugins(repo_root: &Path) -> io::Result<Vec<PluginEntry>> {
 
     Ok(entries)
 }
-
-/// Check plugin directory for a platform dynamic library (dll/so/dylib).
-fn plugin_has_shared_library(path: &Path) -> bool {
-    if let Ok(rd) = fs::read_dir(path) {
-        for ent in rd.flatten() {
-            let p = ent.path();
-            if p.is_file() {
-                if let Some(ext) = p.extension().and_then(|s| s.to_str()) {
-                    match ext.to_lowercase().as_str() {
-                        "dll" | "so" | "dylib" => return true,
-                        _ => {}
-                    }
-                }
-            }
-        }
-    }
-    false
-}
@@ -1,27 +1,28 @@
 use std::fs;
 use std::io;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use super::discovery::plugins_dir;
 
 const ENABLED_FILE: &str = "enabled.json";
 
-/// Load enabled plugins list from repo_root or exe directory
-pub fn load_enabled_list(repo_root: &Path) -> io::Result<Vec<String>> {
-    // Try exe directory first (for deployed binaries)
+/// `enabled.json` in use: next to the executable if it exists, otherwise in
+/// the plugin directory (`plugins_dir`).
+fn enabled_file(repo_root: &Path) -> PathBuf {
     let exe_enabled = std::env::current_exe()
         .ok()
         .and_then(|p| p.parent().map(|parent| parent.join(ENABLED_FILE)));
 
-    let f = if let Some(ref path) = exe_enabled {
-        if path.exists() {
-            path.clone()
-        } else {
-            repo_root.join("plugins").join(ENABLED_FILE)
-        }
-    } else {
-        repo_root.join("plugins").join(ENABLED_FILE)
-    };
+    match exe_enabled {
+        Some(path) if path.exists() => path,
+        _ => plugins_dir(repo_root).join(ENABLED_FILE),
+    }
+}
 
+/// Load enabled plugins list from repo_root or exe directory
+pub fn load_enabled_list(repo_root: &Path) -> io::Result<Vec<String>> {
+    let f = enabled_file(repo_root);
     if !f.exists() {
         return Ok(vec![]);
     }
@@ -30,28 +31,9 @@ pub fn load_enabled_list(repo_root: &Path) -> io::Result<Vec<String>> {
     Ok(v)
 }
 
-/// Save enabled plugins list to repo_root or exe directory
+/// Save enabled plugins list to the same file `load_enabled_list` reads
 pub fn save_enabled_list(repo_root: &Path, list: &[String]) -> io::Result<()> {
-    // Try exe directory first (for deployed binaries)
-    let exe_enabled = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.parent().map(|parent| parent.join(ENABLED_FILE)));
-
-    let f = if let Some(ref path) = exe_enabled {
-        // If exe directory is writable, use it
-        if let Ok(metadata) = fs::metadata(path.parent().unwrap_or_else(|| Path::new("."))) {
-            if !metadata.permissions().readonly() {
-                path.clone()
-            } else {
-                repo_root.join("plugins").join(ENABLED_FILE)
-            }
-        } else {
-            path.clone()
-        }
-    } else {
-        repo_root.join("plugins").join(ENABLED_FILE)
-    };
-
+    let f = enabled_file(repo_root);
     if let Some(parent) = f.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -0,0 +1,606 @@
+//! `.zip` / `.tar.gz` アーカイブからのプラグインインストール
+//!
+//! インストール元はアーカイブ（ローカルパスまたは URL）か、`[targets]` に
+//! プラットフォームごとのアーカイブと sha256 を書いたマニフェスト。
+//! インストール結果は `plugins/installed.json` に記録し、更新・削除に使う。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::discovery::plugins_dir;
+use super::enabled::{disable_plugin, enable_plugin};
+use super::validation::validate_manifest;
+
+const INSTALLED_FILE: &str = "installed.json";
+const MANIFEST_FILE: &str = "plugin.toml";
+
+/// `plugins/installed.json` に保存するインストール記録
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledPlugin {
+    /// `plugins/` 配下のディレクトリ名
+    pub dir_name: String,
+    pub name: String,
+    pub version: Option<String>,
+    /// インストールに使ったアーカイブまたはマニフェストのパス/URL
+    pub source: String,
+    /// インストールしたアーカイブの sha256
+    pub sha256: String,
+    /// Unix 時刻（秒）
+    pub installed_at: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// アーカイブの期待 sha256（マニフェスト経由でない場合は必須）
+    pub sha256: Option<String>,
+    /// 同名のプラグインがあれば置き換える
+    pub replace: bool,
+}
+
+/// 実行中プラットフォームの `[targets]` キー（例: `windows_x86_64`）
+pub fn current_target() -> String {
+    format!("{}_{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// アーカイブまたは `[targets]` 付きマニフェストからインストールする
+///
+/// 新しく入れたプラグインは `enabled.json` に追加する（`replace` の更新では有効・無効を変えない）。
+pub async fn install_plugin(
+    repo_root: &Path,
+    source: &str,
+    options: &InstallOptions,
+) -> Result<InstalledPlugin> {
+    install_from(repo_root, source, options, None).await
+}
+
+/// `dir_name` を指定すると、そのディレクトリ名のプラグインを含むアーカイブだけを入れる
+async fn install_from(
+    repo_root: &Path,
+    source: &str,
+    options: &InstallOptions,
+    dir_name: Option<&str>,
+) -> Result<InstalledPlugin> {
+    let (archive_source, expected) = resolve_archive(source, options).await?;
+    let bytes = fetch(&archive_source).await?;
+    let actual = sha256_hex(&bytes);
+    match expected {
+        Some(expected) if !expected.eq_ignore_ascii_case(&actual) => bail!(
+            "checksum mismatch for {}: expected {}, got {}",
+            archive_source,
+            expected,
+            actual
+        ),
+        _ => {}
+    }
+
+    let dir = plugins_dir(repo_root);
+    let record = install_archive(&dir, &archive_source, &bytes, options.replace, dir_name)?;
+    let record = InstalledPlugin {
+        source: source.to_string(),
+        sha256: actual,
+        ..record
+    };
+
+    let mut records = load_records(&dir)?;
+    records.insert(record.dir_name.clone(), record.clone());
+    save_records(&dir, &records)?;
+    if !options.replace {
+        enable_plugin(repo_root, &record.dir_name)
+            .with_context(|| format!("enabling {}", record.dir_name))?;
+    }
+    Ok(record)
+}
+
+/// アーカイブを取得して sha256 を計算する（確認用。インストールはしない）
+pub async fn archive_sha256(source: &str) -> Result<String> {
+    Ok(sha256_hex(&fetch(source).await?))
+}
+
+/// `source`（省略時は記録済みのインストール元）から入れ直す。
+/// アーカイブのプラグインが `name` と別のものなら何も変えずにエラーにする。
+pub async fn update_plugin(
+    repo_root: &Path,
+    name: &str,
+    source: Option<&str>,
+    options: &InstallOptions,
+) -> Result<InstalledPlugin> {
+    let dir = plugins_dir(repo_root);
+    let records = load_records(&dir)?;
+    let recorded = find_record(&records, name);
+    let dir_name = recorded
+        .map(|record| record.dir_name.clone())
+        .unwrap_or_else(|| name.to_string());
+    let source = match (source, recorded) {
+        (Some(source), _) => source.to_string(),
+        (None, Some(record)) => record.source.clone(),
+        (None, None) => bail!("plugin '{}' was not installed from an archive", name),
+    };
+
+    let options = InstallOptions {
+        replace: true,
+        ..options.clone()
+    };
+    install_from(repo_root, &source, &options, Some(&dir_name)).await
+}
+
+/// プラグインのディレクトリとインストール記録を削除する
+pub fn uninstall_plugin(repo_root: &Path, name: &str) -> Result<()> {
+    let dir = plugins_dir(repo_root);
+    let mut records = load_records(&dir)?;
+    let dir_name = find_record(&records, name)
+        .map(|record| record.dir_name.clone())
+        .unwrap_or_else(|| name.to_string());
+    if !is_plain_name(&dir_name) {
+        bail!("invalid plugin name: {}", name);
+    }
+
+    let path = dir.join(&dir_name);
+    if !path.is_dir() {
+        bail!("plugin not found: {}", path.display());
+    }
+    fs::remove_dir_all(&path).with_context(|| format!("removing {}", path.display()))?;
+
+    if records.remove(&dir_name).is_some() {
+        save_records(&dir, &records)?;
+    }
+    disable_plugin(repo_root, &dir_name).with_context(|| format!("disabling {}", dir_name))?;
+    Ok(())
+}
+
+/// `plugins/installed.json` に記録されたプラグイン
+pub fn installed_plugins(repo_root: &Path) -> Result<Vec<InstalledPlugin>> {
+    Ok(load_records(&plugins_dir(repo_root))?
+        .into_values()
+        .collect())
+}
+
+fn find_record<'a>(
+    records: &'a BTreeMap<String, InstalledPlugin>,
+    name: &str,
+) -> Option<&'a InstalledPlugin> {
+    records
+        .get(name)
+        .or_else(|| records.values().find(|record| record.name == name))
+}
+
+/// インストール元からアーカイブの場所と期待 sha256 を決める
+async fn resolve_archive(
+    source: &str,
+    options: &InstallOptions,
+) -> Result<(String, Option<String>)> {
+    if !source.ends_with(".toml") {
+        let sha256 = options.sha256.clone().ok_or_else(|| {
+            anyhow!(
+                "an expected sha256 is required to install {} (or use a manifest with [targets])",
+                source
+            )
+        })?;
+        return Ok((source.to_string(), Some(sha256)));
+    }
+
+    let text = String::from_utf8(fetch(source).await?).context("manifest is not UTF-8")?;
+    let manifest: toml::Value = toml::from_str(&text).context("parsing manifest")?;
+    let target = current_target();
+    let entry = manifest
+        .get("targets")
+        .and_then(|targets| targets.get(&target))
+        .ok_or_else(|| anyhow!("manifest has no [targets].{} entry", target))?;
+    let archive = entry
+        .get("archive")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("[targets].{} is missing 'archive'", target))?;
+    let sha256 = entry
+        .get("sha256")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| options.sha256.clone())
+        .ok_or_else(|| anyhow!("[targets].{} is missing 'sha256'", target))?;
+
+    Ok((resolve_relative(source, archive), Some(sha256)))
+}
+
+/// 相対パスの `archive` はマニフェストの場所を基準に解決する
+fn resolve_relative(manifest: &str, archive: &str) -> String {
+    if is_url(archive) || Path::new(archive).is_absolute() {
+        return archive.to_string();
+    }
+    match manifest.rfind('/').or_else(|| manifest.rfind('\\')) {
+        Some(idx) if is_url(manifest) => format!("{}/{}", &manifest[..idx], archive),
+        _ => Path::new(manifest)
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(archive)
+            .to_string_lossy()
+            .into_owned(),
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+async fn fetch(source: &str) -> Result<Vec<u8>> {
+    if is_url(source) {
+        let response = reqwest::get(source)
+            .await
+            .with_context(|| format!("downloading {}", source))?
+            .error_for_status()
+            .with_context(|| format!("downloading {}", source))?;
+        Ok(response.bytes().await?.to_vec())
+    } else {
+        fs::read(source).with_context(|| format!("reading {}", source))
+    }
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// ステージングに展開し、マニフェストを検証してから所定の場所へ移す。
+/// `expected` があれば、マニフェストの `name` がそれと一致しない限り何も変えない。
+fn install_archive(
+    plugins_dir: &Path,
+    archive_name: &str,
+    bytes: &[u8],
+    replace: bool,
+    expected: Option<&str>,
+) -> Result<InstalledPlugin> {
+    fs::create_dir_all(plugins_dir)?;
+    let stamp = unix_time_nanos();
+    let staging = plugins_dir.join(format!(".staging-{}", stamp));
+    let result = (|| {
+        extract_archive(archive_name, bytes, &staging)?;
+        let root = find_plugin_root(&staging)?;
+        let (metadata, _caps) = validate_manifest(&root.join(MANIFEST_FILE))?;
+
+        let name = metadata
+            .name
+            .clone()
+            .ok_or_else(|| anyhow!("manifest 'name' is required for installation"))?;
+        if !is_plain_name(&name) {
+            bail!(
+                "manifest 'name' cannot be used as a directory name: {}",
+                name
+            );
+        }
+        if let Some(expected) = expected.filter(|expected| *expected != name) {
+            bail!(
+                "archive contains plugin '{}', not '{}'; nothing was replaced",
+                name,
+                expected
+            );
+        }
+
+        let target = plugins_dir.join(&name);
+        if target.exists() {
+            if !replace {
+                bail!(
+                    "plugin '{}' is already installed (use update to replace it)",
+                    name
+                );
+            }
+            let backup = plugins_dir.join(format!(".backup-{}-{}", name, stamp));
+            fs::rename(&target, &backup)?;
+            if let Err(err) = fs::rename(&root, &target) {
+                let _ = fs::rename(&backup, &target);
+                return Err(err).context("replacing plugin directory");
+            }
+            let _ = fs::remove_dir_all(&backup);
+        } else {
+            fs::rename(&root, &target)?;
+        }
+
+        Ok(InstalledPlugin {
+            dir_name: name.clone(),
+            name,
+            version: metadata.version,
+            source: archive_name.to_string(),
+            sha256: sha256_hex(bytes),
+            installed_at: stamp as u64 / 1_000_000_000,
+        })
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn extract_archive(name: &str, bytes: &[u8], dest: &Path) -> Result<()> {
+    let lower = name.to_ascii_lowercase();
+    if lower.ends_with(".zip") {
+        extract_zip(bytes, dest)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        extract_tar_gz(bytes, dest)
+    } else {
+        bail!(
+            "unsupported archive format (expected .zip or .tar.gz): {}",
+            name
+        )
+    }
+}
+
+fn extract_zip(bytes: &[u8], dest: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).context("opening zip archive")?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let relative = file
+            .enclosed_name()
+            .ok_or_else(|| anyhow!("unsafe path in archive: {}", file.name()))?;
+        let out = dest.join(relative);
+        if file.is_dir() {
+            fs::create_dir_all(&out)?;
+            continue;
+        }
+        if let Some(parent) = out.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        fs::write(&out, data)?;
+    }
+    Ok(())
+}
+
+fn extract_tar_gz(bytes: &[u8], dest: &Path) -> Result<()> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(Cursor::new(bytes)));
+    fs::create_dir_all(dest)?;
+    for entry in archive.entries().context("reading tar archive")? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            bail!("unsafe path in archive: {}", path.display());
+        }
+        let kind = entry.header().entry_type();
+        if !(kind.is_file() || kind.is_dir()) {
+            bail!("unsupported entry type in archive: {}", path.display());
+        }
+        entry.unpack_in(dest)?;
+    }
+    Ok(())
+}
+
+/// `plugin.toml` はアーカイブ直下か、唯一のトップレベルディレクトリ内に置く
+fn find_plugin_root(staging: &Path) -> Result<PathBuf> {
+    if staging.join(MANIFEST_FILE).is_file() {
+        return Ok(staging.to_path_buf());
+    }
+    let dirs: Vec<PathBuf> = fs::read_dir(staging)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    match dirs.as_slice() {
+        [single] if single.join(MANIFEST_FILE).is_file() => Ok(single.clone()),
+        _ => bail!("archive does not contain {}", MANIFEST_FILE),
+    }
+}
+
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && Path::new(name).components().count() == 1
+        && matches!(
+            Path::new(name).components().next(),
+            Some(Component::Normal(_))
+        )
+}
+
+fn unix_time_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default()
+}
+
+fn load_records(plugins_dir: &Path) -> Result<BTreeMap<String, InstalledPlugin>> {
+    let path = plugins_dir.join(INSTALLED_FILE);
+    match fs::read_to_string(&path) {
+        Ok(text) => {
+            serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+fn save_records(plugins_dir: &Path, records: &BTreeMap<String, InstalledPlugin>) -> Result<()> {
+    fs::create_dir_all(plugins_dir)?;
+    let path = plugins_dir.join(INSTALLED_FILE);
+    let tmp = plugins_dir.join(format!("{}.tmp", INSTALLED_FILE));
+    fs::write(&tmp, serde_json::to_string_pretty(records)?)?;
+    fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::enabled::load_enabled_list;
+    use std::io::Write;
+
+    const MANIFEST: &str = r#"
+name = "sample-builder"
+version = "0.2.0"
+kind = "prompt_builder"
+wasm = "builder.wasm"
+models = ["sample:latest"]
+"#;
+
+    fn zip_bytes(prefix: &str) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::SimpleFileOptions::default();
+            writer
+                .start_file(format!("{}plugin.toml", prefix), options)
+                .unwrap();
+            writer.write_all(MANIFEST.as_bytes()).unwrap();
+            writer
+                .start_file(format!("{}builder.wasm", prefix), options)
+                .unwrap();
+            writer.write_all(b"\0asm").unwrap();
+            writer.finish().unwrap();
+        }
+        buffer.into_inner()
+    }
+
+    fn tar_gz_bytes() -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in [
+            ("sample/plugin.toml", MANIFEST.as_bytes()),
+            ("sample/builder.wasm", b"\0asm".as_slice()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn installs_updates_and_uninstalls_from_local_archives() {
+        let repo = tempfile::tempdir().unwrap();
+        let archive = repo.path().join("sample.zip");
+        fs::write(&archive, zip_bytes("")).unwrap();
+        let source = archive.to_string_lossy().into_owned();
+
+        // アーカイブを直接指定するときは sha256 が必須
+        let err = install_plugin(repo.path(), &source, &InstallOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sha256 is required"));
+
+        let wrong = InstallOptions {
+            sha256: Some("00".repeat(32)),
+            replace: false,
+        };
+        let err = install_plugin(repo.path(), &source, &wrong)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        let options = InstallOptions {
+            sha256: Some(sha256_hex(&fs::read(&archive).unwrap())),
+            replace: false,
+        };
+        let record = install_plugin(repo.path(), &source, &options)
+            .await
+            .unwrap();
+        assert_eq!(record.dir_name, "sample-builder");
+        assert_eq!(record.version.as_deref(), Some("0.2.0"));
+        let installed = repo.path().join("plugins/sample-builder");
+        assert!(installed.join("builder.wasm").is_file());
+        assert_eq!(installed_plugins(repo.path()).unwrap(), vec![record]);
+        assert_eq!(
+            load_enabled_list(repo.path()).unwrap(),
+            vec!["sample-builder".to_string()]
+        );
+
+        // 同名は update でのみ置き換える
+        assert!(install_plugin(repo.path(), &source, &options)
+            .await
+            .is_err());
+        let tarball = repo.path().join("sample.tar.gz");
+        let tarball_bytes = tar_gz_bytes();
+        fs::write(&tarball, &tarball_bytes).unwrap();
+        let updated = update_plugin(
+            repo.path(),
+            "sample-builder",
+            Some(&tarball.to_string_lossy()),
+            &InstallOptions {
+                sha256: Some(sha256_hex(&tarball_bytes)),
+                replace: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(updated.source.ends_with("sample.tar.gz"));
+        assert!(installed.join("plugin.toml").is_file());
+
+        // 別のプラグインのアーカイブでは置き換えない
+        let other = repo.path().join("plugins/other");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("plugin.toml"), "name = \"other\"\n").unwrap();
+        let err = update_plugin(
+            repo.path(),
+            "other",
+            Some(&tarball.to_string_lossy()),
+            &InstallOptions {
+                sha256: Some(sha256_hex(&tarball_bytes)),
+                replace: false,
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not 'other'"), "{}", err);
+        assert_eq!(
+            fs::read_to_string(other.join("plugin.toml")).unwrap(),
+            "name = \"other\"\n"
+        );
+        assert_eq!(installed_plugins(repo.path()).unwrap(), vec![updated]);
+
+        uninstall_plugin(repo.path(), "sample-builder").unwrap();
+        assert!(!installed.exists());
+        assert!(installed_plugins(repo.path()).unwrap().is_empty());
+        assert!(load_enabled_list(repo.path()).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn installs_from_manifest_targets() {
+        let repo = tempfile::tempdir().unwrap();
+        let dist = repo.path().join("dist");
+        fs::create_dir_all(&dist).unwrap();
+        let bytes = zip_bytes("sample/");
+        fs::write(dist.join("sample.zip"), &bytes).unwrap();
+        fs::write(
+            dist.join("release.toml"),
+            format!(
+                "name = \"sample-builder\"\n[targets]\n{} = {{ archive = \"sample.zip\", sha256 = \"{}\" }}\n",
+                current_target(),
+                sha256_hex(&bytes)
+            ),
+        )
+        .unwrap();
+
+        let source = dist.join("release.toml").to_string_lossy().into_owned();
+        let record = install_plugin(repo.path(), &source, &InstallOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(record.name, "sample-builder");
+        assert!(repo
+            .path()
+            .join("plugins/sample-builder/plugin.toml")
+            .is_file());
+    }
+
+    #[test]
+    fn rejects_path_traversal() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut buffer);
+            writer
+                .start_file("../evil.txt", zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(b"x").unwrap();
+            writer.finish().unwrap();
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let err =
+            install_archive(dir.path(), "evil.zip", buffer.get_ref(), false, None).unwrap_err();
+        assert!(err.to_string().contains("unsafe path"));
+        assert!(!dir.path().join("evil.txt").exists());
+    }
+}
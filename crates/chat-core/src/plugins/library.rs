@@ -0,0 +1,163 @@
+use std::fs;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use libloading::Library;
+
+use super::approval::PluginBinary;
+use super::install::sha256_hex;
+use super::metadata::PluginMetadata;
+
+/// マニフェストの `library` から読み込む共有ライブラリのパスを決める。
+/// 記載どおりのファイルが無ければ、同じ名前のプラットフォーム向けファイル名
+/// （`foo.dll` → `libfoo.so` など）も探す。
+pub(crate) fn resolve_library_path(
+    plugin_dir: &Path,
+    manifest: &PluginMetadata,
+) -> Result<PathBuf> {
+    let library_name = manifest
+        .library
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| anyhow!("plugin missing library field"))?;
+
+    let declared = plugin_dir.join(library_name);
+    if declared.exists() {
+        return Ok(declared);
+    }
+
+    let platform = plugin_dir.join(libloading::library_filename(library_stem(library_name)));
+    if platform.exists() {
+        return Ok(platform);
+    }
+
+    Err(anyhow!("library not found: {}", declared.display()))
+}
+
+/// 一時ディレクトリへ複製して読み込んだ共有ライブラリ。
+/// 破棄するとライブラリを閉じてから複製を削除する。
+pub(crate) struct PluginLibrary {
+    library: ManuallyDrop<Library>,
+    shadow_dir: PathBuf,
+}
+
+impl Deref for PluginLibrary {
+    type Target = Library;
+
+    fn deref(&self) -> &Library {
+        &self.library
+    }
+}
+
+impl Drop for PluginLibrary {
+    fn drop(&mut self) {
+        // Windows では読み込み中のファイルを消せないため、先に閉じる
+        let library = unsafe { ManuallyDrop::take(&mut self.library) };
+        let _ = library.close();
+        let _ = fs::remove_dir_all(&self.shadow_dir);
+    }
+}
+
+/// 承認を確かめた共有ライブラリのバイト列を一時ディレクトリへ書き出してから読み込む。
+/// 元のファイルを読み直さず、ロックもしないので、読み込んだまま再ビルドできる（ホットリロード用）。
+/// 複製先は読み込みごとに分けるため、再ビルド後は別のライブラリとして読み込まれる。
+/// 返した `Arc` がすべて破棄された時点でアンロードし、複製も削除する。
+pub(crate) fn load_shared_library(binary: &PluginBinary) -> Result<Arc<PluginLibrary>> {
+    load_into(&shadow_root(), &binary.path, &binary.bytes)
+}
+
+fn shadow_root() -> PathBuf {
+    std::env::temp_dir()
+        .join("neko-assistant")
+        .join("plugin-libs")
+}
+
+fn load_into(shadow_root: &Path, path: &Path, bytes: &[u8]) -> Result<Arc<PluginLibrary>> {
+    static NEXT_SHADOW: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid library path: {}", path.display()))?;
+    let digest = sha256_hex(bytes);
+    let shadow_dir = shadow_root.join(format!(
+        "{}-{}-{}",
+        &digest[..16],
+        std::process::id(),
+        NEXT_SHADOW.fetch_add(1, Ordering::Relaxed)
+    ));
+    let shadow = shadow_dir.join(file_name);
+
+    let loaded = (|| {
+        fs::create_dir_all(&shadow_dir)?;
+        fs::write(&shadow, bytes)?;
+        unsafe { Library::new(&shadow) }
+            .with_context(|| format!("loading library {}", path.display()))
+    })();
+    match loaded {
+        Ok(library) => Ok(Arc::new(PluginLibrary {
+            library: ManuallyDrop::new(library),
+            shadow_dir,
+        })),
+        Err(err) => {
+            let _ = fs::remove_dir_all(&shadow_dir);
+            Err(err)
+        }
+    }
+}
+
+fn library_stem(library_name: &str) -> &str {
+    let stem = Path::new(library_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(library_name);
+    stem.strip_prefix("lib").unwrap_or(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_platform_library_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = PluginMetadata {
+            name: Some("sample".into()),
+            description: None,
+            version: None,
+            author: None,
+            kind: Default::default(),
+            entrypoint: None,
+            library: Some("sample_adapter.dll".into()),
+            wasm: None,
+            models: Vec::new(),
+            priority: None,
+            settings: Vec::new(),
+            commands: Vec::new(),
+        };
+
+        assert!(resolve_library_path(dir.path(), &manifest).is_err());
+
+        let platform = dir
+            .path()
+            .join(libloading::library_filename("sample_adapter"));
+        std::fs::write(&platform, b"").unwrap();
+        assert_eq!(
+            resolve_library_path(dir.path(), &manifest).unwrap(),
+            platform
+        );
+    }
+
+    #[test]
+    fn removes_shadow_copy_when_loading_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = dir.path().join("broken.so");
+        let shadow_root = dir.path().join("shadow");
+
+        assert!(load_into(&shadow_root, &library, b"not a library").is_err());
+        assert_eq!(fs::read_dir(&shadow_root).unwrap().count(), 0);
+    }
+}
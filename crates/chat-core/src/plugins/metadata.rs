@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use super::commands::PluginCommand;
+use super::settings::PluginSetting;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PluginMetadata {
     pub name: Option<String>,
@@ -10,8 +13,17 @@ pub struct PluginMetadata {
     pub kind: PluginKind,
     pub entrypoint: Option<String>,
     pub library: Option<String>,
+    /// WASM モジュール（指定時は `library` の代わりにサンドボックスで実行）
+    #[serde(default)]
+    pub wasm: Option<String>,
     pub models: Vec<String>,
     pub priority: Option<i32>,
+    /// `[settings]` で宣言されたユーザー設定
+    #[serde(default)]
+    pub settings: Vec<PluginSetting>,
+    /// `[commands]` で宣言されたスラッシュコマンド
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
@@ -20,6 +32,8 @@ pub enum PluginKind {
     #[default]
     Adapter,
     PromptBuilder,
+    /// モデルに渡すツールをプロセス内で提供する（`prompt_spi::ToolProvider`）
+    Tool,
     #[serde(other)]
     Other,
 }
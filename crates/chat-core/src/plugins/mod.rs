@@ -1,11 +1,28 @@
+pub mod adapter;
+pub mod approval;
+pub mod commands;
+pub mod dev;
 pub mod discovery;
 pub mod enabled;
 pub mod guard;
+pub mod install;
+mod library;
 pub mod metadata;
+pub mod model_pattern;
 pub mod prompt_builder;
+pub mod settings;
+pub mod test_runner;
+pub mod tool;
 pub mod validation;
 
+pub use adapter::{AdapterRegistry, AdapterSource};
+pub use approval::{approval_status, approve_plugin, revoke_approval, ApprovalStatus};
 pub use discovery::discover_plugins;
 pub use enabled::{disable_plugin, enable_plugin};
+pub use install::{
+    archive_sha256, install_plugin, uninstall_plugin, update_plugin, InstallOptions,
+    InstalledPlugin,
+};
 pub use metadata::PluginEntry;
-pub use prompt_builder::{PromptBuilderRegistry, PromptBuilderSource};
+pub use prompt_builder::{BuilderPreview, PromptBuilderRegistry, PromptBuilderSource};
+pub use tool::{ToolProviderSource, ToolRegistry};
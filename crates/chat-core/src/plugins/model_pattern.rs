@@ -0,0 +1,86 @@
+//! マニフェストの `models` に書くモデル名のパターン（プロンプトビルダー用）
+//!
+//! - `qwen3:*` のようなグロブ（`*` は任意の文字列、`?` は任意の 1 文字）
+//! - `re:^llama3(\.\d)?:` のような正規表現（`re:` 接頭辞、部分一致）
+//! - それ以外は完全一致
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub enum ModelPattern {
+    Exact(String),
+    Matcher { source: String, regex: Regex },
+}
+
+impl ModelPattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        if let Some(expr) = pattern.strip_prefix("re:") {
+            let regex =
+                Regex::new(expr).with_context(|| format!("invalid model regex '{}'", expr))?;
+            return Ok(Self::Matcher {
+                source: pattern.to_string(),
+                regex,
+            });
+        }
+        if !pattern.contains(['*', '?']) {
+            return Ok(Self::Exact(pattern.to_string()));
+        }
+
+        let mut expr = String::from("^");
+        for ch in pattern.chars() {
+            match ch {
+                '*' => expr.push_str(".*"),
+                '?' => expr.push('.'),
+                other => expr.push_str(&regex::escape(other.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        expr.push('$');
+        let regex =
+            Regex::new(&expr).with_context(|| format!("invalid model glob '{}'", pattern))?;
+        Ok(Self::Matcher {
+            source: pattern.to_string(),
+            regex,
+        })
+    }
+
+    pub fn matches(&self, model: &str) -> bool {
+        match self {
+            ModelPattern::Exact(name) => name == model,
+            ModelPattern::Matcher { regex, .. } => regex.is_match(model),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ModelPattern::Exact(name) => name,
+            ModelPattern::Matcher { source, .. } => source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs_regexes_and_exact_names() {
+        let glob = ModelPattern::parse("qwen3:*").unwrap();
+        assert!(glob.matches("qwen3:4b-instruct"));
+        assert!(!glob.matches("qwen2.5:7b"));
+
+        let single = ModelPattern::parse("phi4-mini:?.?b").unwrap();
+        assert!(single.matches("phi4-mini:3.8b"));
+        assert!(!single.matches("phi4-mini:14b"));
+
+        let regex = ModelPattern::parse(r"re:^llama3(\.\d)?:").unwrap();
+        assert!(regex.matches("llama3.2:3b"));
+        assert!(!regex.matches("elyza-llama3:8b"));
+
+        let exact = ModelPattern::parse("gemma3:4b").unwrap();
+        assert!(matches!(exact, ModelPattern::Exact(_)));
+        assert!(!exact.matches("gemma3:4b-it"));
+
+        assert!(ModelPattern::parse("re:(").is_err());
+    }
+}
@@ -5,18 +5,28 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
-use libloading::Library;
+use prompt_spi::ffi::{
+    check_abi_version, AbiVersionFn, ForeignPromptBuilderFactory, PromptBuilderEntrypoint,
+    PromptSpiLoadError, ABI_VERSION_SYMBOL, DEFAULT_PROMPT_BUILDER_ENTRYPOINT,
+};
 use prompt_spi::{
-    factory_from_raw, CreatePromptBuilderFactory, PromptAgentMode, PromptBuilder,
-    PromptBuilderFactory, PromptMetadata,
+    PromptAgentMode, PromptBuilder, PromptBuilderFactory, PromptContext, PromptMetadata,
+    PromptParseOutput, PromptPayload, PromptSpiResult,
 };
+use serde_json::{Map, Value};
+use wasm_host::{WasmModule, WasmPermissions, WasmPluginConfig, WasmPromptBuilderFactory};
 
+use super::approval::{ensure_approved, PluginBinary};
+use super::library::{load_shared_library, PluginLibrary};
 use super::metadata::{PluginEntry, PluginKind, PluginMetadata};
+use super::model_pattern::ModelPattern;
+use super::settings::load_plugin_settings;
 
-const DEFAULT_ENTRYPOINT: &str = "create_prompt_builder";
-
+#[derive(Default)]
 pub struct PromptBuilderRegistry {
     by_model: HashMap<String, Vec<PromptBuilderSource>>,
+    /// グロブ・正規表現で登録したビルダー
+    patterns: Vec<(ModelPattern, PromptBuilderSource)>,
 }
 
 #[derive(Clone)]
@@ -27,7 +37,7 @@ pub enum PromptBuilderSource {
 
 impl PromptBuilderRegistry {
     pub fn from_plugins(entries: &[PluginEntry]) -> Self {
-        let mut by_model: HashMap<String, Vec<PromptBuilderSource>> = HashMap::new();
+        let mut registry = Self::default();
 
         for entry in entries {
             if !entry.enabled {
@@ -41,16 +51,10 @@ impl PromptBuilderRegistry {
                 continue;
             }
 
-            match PromptBuilderHandle::load(entry, manifest) {
-                Ok(handle) => {
-                    let handle = Arc::new(handle);
-                    for model in &handle.manifest_models {
-                        by_model
-                            .entry(model.clone())
-                            .or_default()
-                            .push(PromptBuilderSource::Plugin(handle.clone()));
-                    }
-                }
+            match ensure_approved(entry)
+                .and_then(|binary| PromptBuilderHandle::load(entry, manifest, &binary))
+            {
+                Ok(handle) => registry.register_plugin(Arc::new(handle)),
                 Err(err) => {
                     eprintln!(
                         "failed to load prompt builder plugin '{}': {}",
@@ -60,35 +64,123 @@ impl PromptBuilderRegistry {
             }
         }
 
-        for handles in by_model.values_mut() {
-            handles.sort_by_key(|source| Reverse(source.priority()));
-        }
+        registry
+    }
 
-        Self { by_model }
+    /// 読み込み済みのプラグインをマニフェストのモデルに登録する
+    pub(crate) fn register_plugin(&mut self, handle: Arc<PromptBuilderHandle>) {
+        for model in &handle.manifest_models {
+            self.insert(model, PromptBuilderSource::Plugin(handle.clone()));
+        }
     }
 
+    /// `model` にはグロブ・正規表現（`re:`）も書ける
     pub fn register_host_builder(
         &mut self,
         model: impl Into<String>,
         factory: HostPromptBuilderFactory,
     ) {
-        let entry = PromptBuilderSource::Host(Arc::new(factory));
-        let models = self.by_model.entry(model.into()).or_default();
-        models.push(entry);
-        models.sort_by_key(|source| Reverse(source.priority()));
+        let model = model.into();
+        self.insert(&model, PromptBuilderSource::Host(Arc::new(factory)));
+    }
+
+    fn insert(&mut self, model: &str, source: PromptBuilderSource) {
+        match ModelPattern::parse(model) {
+            Ok(ModelPattern::Exact(name)) => {
+                let models = self.by_model.entry(name).or_default();
+                models.push(source);
+                models.sort_by_key(|source| Reverse(source.priority()));
+            }
+            Ok(pattern) => self.patterns.push((pattern, source)),
+            Err(err) => eprintln!(
+                "skipping model pattern '{}' of prompt builder '{}': {:#}",
+                model,
+                source.id(),
+                err
+            ),
+        }
+    }
+
+    /// モデルに一致するビルダーを優先度の高い順に返す（同じ優先度なら完全一致が先）
+    pub fn candidates(&self, model: &str) -> Vec<PromptBuilderSource> {
+        let mut list = self.by_model.get(model).cloned().unwrap_or_default();
+        for (pattern, source) in &self.patterns {
+            if pattern.matches(model) && !list.iter().any(|s| s.id() == source.id()) {
+                list.push(source.clone());
+            }
+        }
+        list.sort_by_key(|source| Reverse(source.priority()));
+        list
     }
 
     pub fn resolve(&self, model: &str) -> Option<PromptBuilderSource> {
-        self.by_model
-            .get(model)
-            .and_then(|list| list.first().cloned())
+        self.candidates(model).into_iter().next()
+    }
+
+    /// `preferred`（`PromptBuilderSource::id`）がモデルに一致すればそれを、無ければ既定のビルダーを返す
+    pub fn resolve_preferred(
+        &self,
+        model: &str,
+        preferred: Option<&str>,
+    ) -> Option<PromptBuilderSource> {
+        let candidates = self.candidates(model);
+        preferred
+            .and_then(|id| candidates.iter().find(|source| source.id() == id).cloned())
+            .or_else(|| candidates.into_iter().next())
+    }
+
+    /// 同じ文脈をモデルに一致するすべてのビルダーで組み立てる（設定値はビルダーごと）
+    pub fn preview_all(
+        &self,
+        ctx: &PromptContext<'_>,
+        preferred: Option<&str>,
+    ) -> Vec<BuilderPreview> {
+        let selected = self
+            .resolve_preferred(ctx.model, preferred)
+            .map(|source| source.id());
+        self.candidates(ctx.model)
+            .into_iter()
+            .map(|source| {
+                let settings = source.settings();
+                let result = source
+                    .create_builder()
+                    .build(PromptContext {
+                        model: ctx.model,
+                        locale: ctx.locale,
+                        conversation: ctx.conversation,
+                        tools: ctx.tools,
+                        system_directives: ctx.system_directives,
+                        settings: &settings,
+                    })
+                    .map_err(|err| err.to_string());
+                let id = source.id();
+                BuilderPreview {
+                    selected: selected.as_deref() == Some(id.as_str()),
+                    label: source.label(),
+                    priority: source.priority(),
+                    id,
+                    result,
+                }
+            })
+            .collect()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.by_model.is_empty()
+        self.by_model.is_empty() && self.patterns.is_empty()
     }
 }
 
+/// ビルダー比較の 1 列
+#[derive(Debug, Clone)]
+pub struct BuilderPreview {
+    pub id: String,
+    pub label: String,
+    pub priority: i32,
+    /// 会話で実際に使われるビルダーか
+    pub selected: bool,
+    pub result: Result<PromptPayload, String>,
+}
+
 impl PromptBuilderSource {
     pub fn create_builder(&self) -> Box<dyn PromptBuilder> {
         match self {
@@ -118,6 +210,14 @@ impl PromptBuilderSource {
         }
     }
 
+    /// ビルダーに渡す設定値（組み込みビルダーは空）
+    pub fn settings(&self) -> Map<String, Value> {
+        match self {
+            PromptBuilderSource::Plugin(handle) => handle.settings(),
+            PromptBuilderSource::Host(_) => Map::new(),
+        }
+    }
+
     pub fn plugin_dir(&self) -> Option<PathBuf> {
         match self {
             PromptBuilderSource::Plugin(handle) => Some(handle.plugin_dir.clone()),
@@ -138,6 +238,21 @@ impl PromptBuilderSource {
             PromptBuilderSource::Host(factory) => factory.origin_label,
         }
     }
+
+    /// 会話ごとの指定に使う識別子（`plugin:<ディレクトリ名>` / `builtin:<ビルダー名>`）
+    pub fn id(&self) -> String {
+        match self {
+            PromptBuilderSource::Plugin(handle) => format!("plugin:{}", handle.dir_name),
+            PromptBuilderSource::Host(factory) => {
+                format!("{}:{}", factory.origin_label, factory.metadata.name)
+            }
+        }
+    }
+
+    /// 表示名（`<ビルダー名> (<由来>)`）
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.metadata().name, self.origin_label())
+    }
 }
 
 pub struct HostPromptBuilderFactory {
@@ -183,51 +298,31 @@ pub struct PromptBuilderHandle {
     runtime_metadata: PromptMetadata,
     manifest_models: Vec<String>,
     factory: Arc<dyn PromptBuilderFactory>,
+    /// `plugins/` 内のディレクトリ名（設定値と会話ごとの指定のキー）
+    dir_name: String,
     pub plugin_dir: PathBuf,
 }
 
 impl PromptBuilderHandle {
-    fn load(entry: &PluginEntry, manifest: PluginMetadata) -> Result<Self> {
-        let library_name = manifest
-            .library
-            .clone()
-            .ok_or_else(|| anyhow!("plugin missing library field"))?;
-        let library_path = entry.path.join(library_name);
-        if !library_path.exists() {
-            return Err(anyhow!("library not found: {}", library_path.display()));
-        }
-
-        let entrypoint = manifest
-            .entrypoint
-            .clone()
-            .filter(|s| !s.trim().is_empty())
-            .unwrap_or_else(|| DEFAULT_ENTRYPOINT.to_string());
-
-        unsafe {
-            let library = Library::new(&library_path)
-                .with_context(|| format!("loading library {}", library_path.display()))?;
-            let symbol_name = CString::new(entrypoint.clone())
-                .map_err(|_| anyhow!("invalid entrypoint '{}': contains null byte", entrypoint))?;
-            let constructor: libloading::Symbol<CreatePromptBuilderFactory> = library
-                .get(symbol_name.as_bytes_with_nul())
-                .with_context(|| format!("resolving symbol '{}'", entrypoint))?;
-            let factory_ptr = constructor();
-            if factory_ptr.is_null() {
-                return Err(anyhow!("entrypoint '{}' returned null", entrypoint));
-            }
-            let factory_box = factory_from_raw(factory_ptr);
-            let runtime_metadata = factory_box.metadata();
-            let factory_arc: Arc<dyn PromptBuilderFactory> = factory_box.into();
-            std::mem::forget(library);
-
-            Ok(Self {
-                manifest_models: manifest.models.clone(),
-                manifest,
-                runtime_metadata,
-                factory: factory_arc,
-                plugin_dir: entry.path.clone(),
-            })
-        }
+    /// `binary` の承認は呼び出し側で確かめる
+    pub(crate) fn load(
+        entry: &PluginEntry,
+        manifest: PluginMetadata,
+        binary: &PluginBinary,
+    ) -> Result<Self> {
+        let factory = match manifest.wasm.as_deref().filter(|s| !s.trim().is_empty()) {
+            Some(_) => load_wasm_factory(entry, binary)?,
+            None => load_native_factory(&manifest, binary)?,
+        };
+
+        Ok(Self {
+            manifest_models: manifest.models.clone(),
+            manifest,
+            runtime_metadata: factory.metadata(),
+            factory,
+            dir_name: entry.dir_name.clone(),
+            plugin_dir: entry.path.clone(),
+        })
     }
 
     pub fn create_builder(&self) -> Box<dyn PromptBuilder> {
@@ -246,7 +341,192 @@ impl PromptBuilderHandle {
         self.manifest.priority.unwrap_or(0)
     }
 
+    /// `[settings]` の既定値に保存値を重ねたもの（保存値はディレクトリ名で引く）
+    pub fn settings(&self) -> Map<String, Value> {
+        load_plugin_settings(&self.dir_name, &self.manifest.settings)
+    }
+
     pub fn preferred_agent(&self) -> PromptAgentMode {
         self.runtime_metadata.preferred_agent
     }
 }
+
+/// `cdylib` のプラグインを C ABI 経由で読み込む
+fn load_native_factory(
+    manifest: &PluginMetadata,
+    binary: &PluginBinary,
+) -> Result<Arc<dyn PromptBuilderFactory>> {
+    let entrypoint = manifest
+        .entrypoint
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PROMPT_BUILDER_ENTRYPOINT.to_string());
+
+    let library = load_shared_library(binary)?;
+    unsafe {
+        // エントリポイントに触れる前に ABI バージョンを確認する
+        let version: libloading::Symbol<AbiVersionFn> = library
+            .get(format!("{}\0", ABI_VERSION_SYMBOL).as_bytes())
+            .map_err(|_| PromptSpiLoadError::MissingVersionSymbol(ABI_VERSION_SYMBOL))?;
+        check_abi_version(version())?;
+
+        let symbol_name = CString::new(entrypoint.clone())
+            .map_err(|_| anyhow!("invalid entrypoint '{}': contains null byte", entrypoint))?;
+        let constructor: libloading::Symbol<PromptBuilderEntrypoint> = library
+            .get(symbol_name.as_bytes_with_nul())
+            .with_context(|| format!("resolving symbol '{}'", entrypoint))?;
+        let factory = ForeignPromptBuilderFactory::from_vtable(constructor())
+            .with_context(|| format!("binding prompt builder from '{}'", entrypoint))?;
+
+        Ok(Arc::new(LibraryBoundFactory {
+            factory,
+            library: library.clone(),
+        }))
+    }
+}
+
+/// vtable はライブラリ内の static を指すため、ファクトリと生成したビルダーが
+/// すべて破棄されるまでライブラリを保持する（フィールドは宣言順に破棄される）
+struct LibraryBoundFactory {
+    factory: ForeignPromptBuilderFactory,
+    library: Arc<PluginLibrary>,
+}
+
+impl PromptBuilderFactory for LibraryBoundFactory {
+    fn metadata(&self) -> PromptMetadata {
+        self.factory.metadata()
+    }
+
+    fn create(&self) -> Box<dyn PromptBuilder> {
+        Box::new(LibraryBoundBuilder {
+            builder: self.factory.create(),
+            _library: self.library.clone(),
+        })
+    }
+}
+
+struct LibraryBoundBuilder {
+    builder: Box<dyn PromptBuilder>,
+    _library: Arc<PluginLibrary>,
+}
+
+impl PromptBuilder for LibraryBoundBuilder {
+    fn metadata(&self) -> PromptMetadata {
+        self.builder.metadata()
+    }
+
+    fn build(&self, ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+        self.builder.build(ctx)
+    }
+
+    fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+        self.builder.parse(raw_output)
+    }
+}
+
+/// `.wasm` のプラグインをサンドボックスで読み込む。WASI 権限は manifest の capability に従う
+fn load_wasm_factory(
+    entry: &PluginEntry,
+    binary: &PluginBinary,
+) -> Result<Arc<dyn PromptBuilderFactory>> {
+    let permissions = WasmPermissions::from_capabilities(&binary.capabilities()?);
+    let config = WasmPluginConfig::new(&entry.path, permissions);
+    let factory = WasmModule::from_bytes(&binary.bytes, config)
+        .and_then(WasmPromptBuilderFactory::from_module)
+        .with_context(|| format!("loading wasm module {}", binary.path.display()))?;
+    Ok(Arc::new(factory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NamedBuilder(&'static str);
+
+    impl PromptBuilder for NamedBuilder {
+        fn metadata(&self) -> PromptMetadata {
+            metadata(self.0)
+        }
+
+        fn build(&self, ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+            Ok(PromptPayload::with_prompt(
+                format!("{}:{}", self.0, ctx.model),
+                PromptAgentMode::DirectProvider,
+            ))
+        }
+
+        fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+            Ok(PromptParseOutput {
+                final_answer: Some(raw_output.to_string()),
+                tool_requests: Vec::new(),
+            })
+        }
+    }
+
+    fn metadata(name: &str) -> PromptMetadata {
+        PromptMetadata {
+            name: name.into(),
+            version: "0.1.0".into(),
+            description: None,
+            supported_models: Vec::new(),
+            homepage: None,
+            preferred_agent: PromptAgentMode::DirectProvider,
+        }
+    }
+
+    fn host(name: &'static str, priority: i32) -> HostPromptBuilderFactory {
+        HostPromptBuilderFactory::new(
+            metadata(name),
+            PromptAgentMode::DirectProvider,
+            priority,
+            "builtin",
+            move || Box::new(NamedBuilder(name)),
+        )
+    }
+
+    #[test]
+    fn resolves_patterns_by_priority_and_honours_preference() {
+        let mut registry = PromptBuilderRegistry::default();
+        registry.register_host_builder("qwen3:4b-instruct", host("exact", 10));
+        registry.register_host_builder("qwen3:*", host("glob", 10));
+        registry.register_host_builder("re:^qwen", host("regex", 20));
+
+        let ids: Vec<String> = registry
+            .candidates("qwen3:4b-instruct")
+            .iter()
+            .map(|source| source.id())
+            .collect();
+        assert_eq!(ids, ["builtin:regex", "builtin:exact", "builtin:glob"]);
+        assert_eq!(registry.candidates("qwen2.5:7b").len(), 1);
+        assert!(registry.resolve("llama3:8b").is_none());
+
+        let preferred = registry
+            .resolve_preferred("qwen3:4b-instruct", Some("builtin:glob"))
+            .unwrap();
+        assert_eq!(preferred.label(), "glob (builtin)");
+        // モデルに一致しない指定は無視する
+        let fallback = registry
+            .resolve_preferred("qwen2.5:7b", Some("builtin:glob"))
+            .unwrap();
+        assert_eq!(fallback.id(), "builtin:regex");
+
+        let ctx = PromptContext {
+            model: "qwen3:4b-instruct",
+            locale: "en-US",
+            conversation: &[],
+            tools: &[],
+            system_directives: &[],
+            settings: &Map::new(),
+        };
+        let previews = registry.preview_all(&ctx, Some("builtin:exact"));
+        assert_eq!(previews.len(), 3);
+        let selected: Vec<&str> = previews
+            .iter()
+            .filter(|p| p.selected)
+            .map(|p| p.id.as_str())
+            .collect();
+        assert_eq!(selected, ["builtin:exact"]);
+        let prompt = previews[2].result.as_ref().unwrap().prompt.as_deref();
+        assert_eq!(prompt, Some("glob:qwen3:4b-instruct"));
+    }
+}
@@ -0,0 +1,202 @@
+//! プラグインの設定（`plugin.toml` の `[settings]`）
+//!
+//! ```toml
+//! [settings.system_prompt]
+//! type = "string"
+//! default = "You are a helpful assistant."
+//! description = "System prompt used when no directives are given"
+//! ```
+//!
+//! ユーザーが変更した値は `app-config` にプラグインのディレクトリ名ごとに保存し、
+//! 既定値に重ねて `PromptContext::settings` としてビルダーへ渡す。
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+}
+
+/// `[settings.<key>]` の 1 項目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginSetting {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub setting_type: SettingType,
+    pub default: Value,
+    pub description: Option<String>,
+}
+
+impl SettingType {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            SettingType::String => value.is_string(),
+            SettingType::Integer => value.is_i64() || value.is_u64(),
+            SettingType::Float => value.is_number(),
+            SettingType::Boolean => value.is_boolean(),
+        }
+    }
+}
+
+impl PluginSetting {
+    /// フォームに入力された文字列を型に合わせて変換する
+    pub fn parse_input(&self, text: &str) -> Result<Value> {
+        let text = text.trim();
+        let value = match self.setting_type {
+            SettingType::String => Value::String(text.to_string()),
+            SettingType::Integer => Value::from(
+                text.parse::<i64>()
+                    .with_context(|| format!("'{}' must be an integer", self.key))?,
+            ),
+            SettingType::Float => Value::from(
+                text.parse::<f64>()
+                    .with_context(|| format!("'{}' must be a number", self.key))?,
+            ),
+            SettingType::Boolean => Value::Bool(
+                text.parse::<bool>()
+                    .with_context(|| format!("'{}' must be true or false", self.key))?,
+            ),
+        };
+        Ok(value)
+    }
+
+    /// フォームに表示する文字列
+    pub fn display_value(value: &Value) -> String {
+        match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// マニフェストの `[settings]` を読む（キー順）
+pub fn parse_settings_schema(manifest: &toml::Value) -> Result<Vec<PluginSetting>> {
+    let Some(table) = manifest.get("settings") else {
+        return Ok(Vec::new());
+    };
+    let table = table
+        .as_table()
+        .ok_or_else(|| anyhow!("'settings' must be a table"))?;
+
+    let mut settings = Vec::new();
+    for (key, field) in table {
+        let setting_type = match field.get("type").and_then(|v| v.as_str()) {
+            Some("string") => SettingType::String,
+            Some("integer") => SettingType::Integer,
+            Some("float") => SettingType::Float,
+            Some("boolean") => SettingType::Boolean,
+            Some(other) => bail!("setting '{}' has unknown type '{}'", key, other),
+            None => bail!("setting '{}' requires 'type'", key),
+        };
+        let default = field
+            .get("default")
+            .ok_or_else(|| anyhow!("setting '{}' requires 'default'", key))
+            .and_then(|v| serde_json::to_value(v).map_err(Into::into))?;
+        if !setting_type.accepts(&default) {
+            bail!("default of setting '{}' is not a {:?}", key, setting_type);
+        }
+        settings.push(PluginSetting {
+            key: key.clone(),
+            setting_type,
+            default,
+            description: field
+                .get("description")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        });
+    }
+    settings.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(settings)
+}
+
+/// 既定値に保存値（JSON 文字列）を重ねる。型の合わない保存値は無視する。
+pub fn effective_settings(
+    schema: &[PluginSetting],
+    stored: &BTreeMap<String, String>,
+) -> Map<String, Value> {
+    schema
+        .iter()
+        .map(|setting| {
+            let value = stored
+                .get(&setting.key)
+                .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+                .filter(|value| setting.setting_type.accepts(value))
+                .unwrap_or_else(|| setting.default.clone());
+            (setting.key.clone(), value)
+        })
+        .collect()
+}
+
+/// `app-config` に保存された値を読み込んで既定値に重ねる
+pub fn load_plugin_settings(plugin: &str, schema: &[PluginSetting]) -> Map<String, Value> {
+    if schema.is_empty() {
+        return Map::new();
+    }
+    let stored = app_config::load_plugin_settings(plugin).unwrap_or_else(|err| {
+        eprintln!("failed to load settings of plugin '{}': {:#}", plugin, err);
+        BTreeMap::new()
+    });
+    effective_settings(schema, &stored)
+}
+
+pub fn save_plugin_settings(plugin: &str, values: &Map<String, Value>) -> Result<()> {
+    let stored = values
+        .iter()
+        .map(|(key, value)| (key.clone(), value.to_string()))
+        .collect();
+    app_config::save_plugin_settings(plugin, &stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Vec<PluginSetting> {
+        let manifest: toml::Value = toml::from_str(
+            r#"
+            [settings.system_prompt]
+            type = "string"
+            default = "You are a helpful assistant."
+            description = "System prompt"
+
+            [settings.temperature]
+            type = "float"
+            default = 0.7
+            "#,
+        )
+        .unwrap();
+        parse_settings_schema(&manifest).unwrap()
+    }
+
+    #[test]
+    fn stored_values_override_defaults_when_types_match() {
+        let schema = schema();
+        assert_eq!(schema[0].key, "system_prompt");
+        assert_eq!(schema[1].setting_type, SettingType::Float);
+
+        let mut stored = BTreeMap::new();
+        stored.insert("system_prompt".into(), "\"Be brief.\"".into());
+        stored.insert("temperature".into(), "\"hot\"".into());
+        let values = effective_settings(&schema, &stored);
+        assert_eq!(values["system_prompt"], "Be brief.");
+        assert_eq!(values["temperature"], 0.7);
+
+        assert_eq!(schema[1].parse_input(" 0.2 ").unwrap(), 0.2);
+        assert!(schema[1].parse_input("warm").is_err());
+    }
+
+    #[test]
+    fn rejects_defaults_of_the_wrong_type() {
+        let manifest: toml::Value =
+            toml::from_str("[settings.count]\ntype = \"integer\"\ndefault = \"three\"\n").unwrap();
+        assert!(parse_settings_schema(&manifest).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! プロンプトビルダーのテストスクリプト（`neko-assistant plugin test`）
+//!
+//! `{"tests": [...]}` の各ケースでプロンプトを組み立て、期待値と照合する。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use prompt_spi::{
+    ConversationRole, ConversationTurn, PromptAgentMode, PromptContext, PromptPayload, ToolSpec,
+};
+use regex::RegexBuilder;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::prompt_builder::PromptBuilderRegistry;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestScript {
+    pub tests: Vec<TestCase>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestCase {
+    /// `phi4-mini:3.8b (builtin)` のような表示名も受け付ける
+    pub model: String,
+    #[serde(default)]
+    pub conversation: Vec<ScriptTurn>,
+    pub locale: Option<String>,
+    /// ビルダーに渡すツール
+    #[serde(default)]
+    pub tools: Vec<ToolSpec>,
+    pub expected_prompt_contains: Option<String>,
+    /// `expected_prompt_contains` と両方あれば両方を満たす必要がある
+    pub expected_prompt_regex: Option<String>,
+    /// `i`（大文字小文字を区別しない）・`m`（複数行）・`s`（`.` が改行にも一致）の組み合わせ
+    pub expected_prompt_regex_flags: Option<String>,
+    /// `LangChain` / `DirectProvider`
+    pub expected_agent_mode: Option<String>,
+    /// 文字列は部分一致、それ以外は完全一致
+    pub expected_prompt_variables: Option<HashMap<String, Value>>,
+    /// プロンプト本文に現れるべきツール名
+    pub expected_tool_names: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptTurn {
+    pub role: String,
+    pub content: String,
+}
+
+/// 1 ケースの結果
+#[derive(Debug, Clone)]
+pub struct TestOutcome {
+    pub model: String,
+    /// 解決したビルダー名と由来（`plugin` / `builtin`）
+    pub builder: Option<String>,
+    pub prompt: Option<String>,
+    pub failures: Vec<String>,
+}
+
+impl TestOutcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+pub fn load_test_script(path: &Path) -> Result<TestScript> {
+    let raw = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_slice(&raw).with_context(|| format!("parsing {}", path.display()))
+}
+
+pub fn run_test_script(registry: &PromptBuilderRegistry, script: &TestScript) -> Vec<TestOutcome> {
+    script
+        .tests
+        .iter()
+        .map(|case| run_test_case(registry, case))
+        .collect()
+}
+
+fn run_test_case(registry: &PromptBuilderRegistry, case: &TestCase) -> TestOutcome {
+    let model = normalize_label(&case.model);
+    let mut outcome = TestOutcome {
+        model: model.clone(),
+        builder: None,
+        prompt: None,
+        failures: Vec::new(),
+    };
+    let Some(source) = registry.resolve(&model) else {
+        outcome
+            .failures
+            .push(format!("no prompt builder resolved for {}", model));
+        return outcome;
+    };
+    outcome.builder = Some(source.label());
+
+    let conversation: Vec<ConversationTurn> = case
+        .conversation
+        .iter()
+        .map(|turn| ConversationTurn {
+            role: match turn.role.as_str() {
+                "system" => ConversationRole::System,
+                "assistant" => ConversationRole::Assistant,
+                "tool" => ConversationRole::Tool,
+                _ => ConversationRole::User,
+            },
+            content: turn.content.as_str(),
+        })
+        .collect();
+    let settings = source.settings();
+    let ctx = PromptContext {
+        model: &model,
+        locale: case.locale.as_deref().unwrap_or("en-US"),
+        conversation: &conversation,
+        tools: &case.tools,
+        system_directives: &[],
+        settings: &settings,
+    };
+    match source.create_builder().build(ctx) {
+        Ok(payload) => {
+            let prompt = payload.prompt.clone().unwrap_or_default();
+            outcome.failures = check_expectations(&payload, &prompt, case);
+            outcome.prompt = Some(prompt);
+        }
+        Err(err) => outcome.failures.push(format!("builder error: {}", err)),
+    }
+    outcome
+}
+
+fn check_expectations(payload: &PromptPayload, prompt: &str, case: &TestCase) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    if let Some(expected) = &case.expected_prompt_contains {
+        if !prompt.contains(expected.as_str()) {
+            failures.push(format!("expected substring not found: {}", expected));
+        }
+    }
+
+    if let Some(pattern) = &case.expected_prompt_regex {
+        let mut builder = RegexBuilder::new(pattern);
+        if let Some(flags) = &case.expected_prompt_regex_flags {
+            builder
+                .case_insensitive(flags.contains('i'))
+                .multi_line(flags.contains('m'))
+                .dot_matches_new_line(flags.contains('s'));
+        }
+        match builder.build() {
+            Ok(re) if re.is_match(prompt) => {}
+            Ok(_) => failures.push(format!("regex did not match: {}", pattern)),
+            Err(err) => failures.push(format!("invalid regex '{}': {}", pattern, err)),
+        }
+    }
+
+    if let Some(expected) = &case.expected_agent_mode {
+        let actual = match payload.agent_mode {
+            PromptAgentMode::LangChain => "langchain",
+            PromptAgentMode::DirectProvider => "directprovider",
+        };
+        if expected.to_lowercase().replace(['-', '_'], "") != actual {
+            failures.push(format!(
+                "agent_mode mismatch: expected '{}', actual '{}'",
+                expected, actual
+            ));
+        }
+    }
+
+    if let Some(variables) = &case.expected_prompt_variables {
+        for (key, expected) in variables {
+            match (expected, payload.prompt_variables.get(key)) {
+                (_, None) => failures.push(format!("prompt variable '{}' missing", key)),
+                (Value::String(expected), Some(Value::String(actual))) => {
+                    if !actual.contains(expected.as_str()) {
+                        failures.push(format!(
+                            "prompt variable '{}' does not contain '{}'",
+                            key, expected
+                        ));
+                    }
+                }
+                (expected, Some(actual)) if expected != actual => failures.push(format!(
+                    "prompt variable '{}': expected {} actual {}",
+                    key, expected, actual
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    for name in case.expected_tool_names.iter().flatten() {
+        if !prompt.contains(name.as_str()) {
+            failures.push(format!("expected tool name '{}' not found in prompt", name));
+        }
+    }
+
+    failures
+}
+
+/// モデル選択の表示名（`name (origin)`）からモデル名だけを取り出す
+fn normalize_label(label: &str) -> String {
+    let label = label.trim();
+    match label.find('(') {
+        Some(idx) => label[..idx].trim().to_string(),
+        None => label.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::register_builtin_prompt_builders;
+
+    #[test]
+    fn reports_each_unmet_expectation() {
+        let mut registry = PromptBuilderRegistry::default();
+        register_builtin_prompt_builders(&mut registry);
+        let script: TestScript = serde_json::from_value(serde_json::json!({
+            "tests": [
+                {
+                    "model": "qwen3:4b-instruct (builtin)",
+                    "conversation": [{ "role": "user", "content": "hello" }],
+                    "tools": [{ "name": "search", "description": null, "input_schema": {} }],
+                    "expected_prompt_contains": "User: hello",
+                    "expected_prompt_regex": "^# available tools$",
+                    "expected_prompt_regex_flags": "im",
+                    "expected_agent_mode": "direct-provider",
+                    "expected_tool_names": ["search"]
+                },
+                {
+                    "model": "phi4-mini:3.8b",
+                    "expected_prompt_contains": "missing text",
+                    "expected_agent_mode": "LangChain"
+                },
+                { "model": "unknown:latest" }
+            ]
+        }))
+        .unwrap();
+
+        let outcomes = run_test_script(&registry, &script);
+        assert!(outcomes[0].passed(), "{:?}", outcomes[0].failures);
+        assert_eq!(outcomes[0].model, "qwen3:4b-instruct");
+        assert_eq!(outcomes[1].failures.len(), 2);
+        assert!(outcomes[2].failures[0].contains("no prompt builder"));
+    }
+}
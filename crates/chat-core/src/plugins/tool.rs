@@ -0,0 +1,232 @@
+use std::ffi::CString;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use prompt_spi::ffi::{
+    check_abi_version, AbiVersionFn, ForeignToolProvider, PromptSpiLoadError,
+    ToolProviderEntrypoint, DEFAULT_TOOL_PROVIDER_ENTRYPOINT, TOOL_ABI_VERSION_SYMBOL,
+};
+use prompt_spi::{PromptSpiResult, ToolProvider, ToolSpec};
+use serde_json::Value;
+use wasm_host::{WasmModule, WasmPermissions, WasmPluginConfig, WasmToolProvider};
+
+use super::approval::{ensure_approved, PluginBinary};
+use super::library::{load_shared_library, PluginLibrary};
+use super::metadata::{PluginEntry, PluginKind, PluginMetadata};
+
+/// ツールプラグイン（`kind = "tool"`）の `ToolProvider` をまとめたレジストリ。
+/// MCP サーバーと同じく、ツールは「プラグイン名（ディレクトリ名）＋ツール名」で識別する。
+#[derive(Default)]
+pub struct ToolRegistry {
+    providers: Vec<ToolProviderSource>,
+}
+
+#[derive(Clone)]
+pub struct ToolProviderSource {
+    plugin: String,
+    provider: Arc<dyn ToolProvider>,
+    tools: Vec<ToolSpec>,
+}
+
+impl ToolRegistry {
+    pub fn from_plugins(entries: &[PluginEntry]) -> Self {
+        let mut registry = Self::default();
+
+        for entry in entries {
+            if !entry.enabled {
+                continue;
+            }
+            let manifest = match &entry.metadata {
+                Some(m) => m.clone(),
+                None => continue,
+            };
+            if manifest.kind != PluginKind::Tool {
+                continue;
+            }
+
+            match ensure_approved(entry)
+                .and_then(|binary| load_tool_provider(entry, &manifest, &binary))
+            {
+                Ok(provider) => registry.register_provider(entry.dir_name.clone(), provider),
+                Err(err) => {
+                    eprintln!("failed to load tool plugin '{}': {}", entry.dir_name, err);
+                }
+            }
+        }
+
+        registry
+    }
+
+    /// ホスト側で実装したツールを登録する
+    pub fn register_provider(
+        &mut self,
+        plugin: impl Into<String>,
+        provider: Arc<dyn ToolProvider>,
+    ) {
+        let tools = provider.tools();
+        self.providers.push(ToolProviderSource {
+            plugin: plugin.into(),
+            provider,
+            tools,
+        });
+    }
+
+    /// `(プラグイン名, ツール)` の一覧
+    pub fn tools(&self) -> Vec<(String, ToolSpec)> {
+        self.providers
+            .iter()
+            .flat_map(|source| {
+                source
+                    .tools
+                    .iter()
+                    .map(|tool| (source.plugin.clone(), tool.clone()))
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, plugin: &str, tool: &str) -> bool {
+        self.find(plugin, tool).is_some()
+    }
+
+    /// ツールを実行する。プラグインの処理はブロッキングスレッドで行う
+    pub async fn call_tool(
+        &self,
+        plugin: &str,
+        tool: &str,
+        arguments: Value,
+    ) -> Result<Value, String> {
+        let provider = self
+            .find(plugin, tool)
+            .ok_or_else(|| format!("Tool '{}' not found in plugin '{}'", tool, plugin))?;
+        let name = tool.to_string();
+        tokio::task::spawn_blocking(move || provider.invoke(&name, arguments))
+            .await
+            .map_err(|e| format!("Tool '{}' panicked: {}", tool, e))?
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.iter().all(|source| source.tools.is_empty())
+    }
+
+    fn find(&self, plugin: &str, tool: &str) -> Option<Arc<dyn ToolProvider>> {
+        self.providers
+            .iter()
+            .find(|source| {
+                source.plugin == plugin && source.tools.iter().any(|spec| spec.name == tool)
+            })
+            .map(|source| source.provider.clone())
+    }
+}
+
+/// `library`（C ABI）または `wasm`（サンドボックス）からツールプロバイダーを読み込む。
+/// `binary` の承認は呼び出し側で確かめる。
+pub(crate) fn load_tool_provider(
+    entry: &PluginEntry,
+    manifest: &PluginMetadata,
+    binary: &PluginBinary,
+) -> Result<Arc<dyn ToolProvider>> {
+    if manifest
+        .wasm
+        .as_deref()
+        .is_some_and(|s| !s.trim().is_empty())
+    {
+        let permissions = WasmPermissions::from_capabilities(&binary.capabilities()?);
+        let provider = WasmModule::from_bytes(
+            &binary.bytes,
+            WasmPluginConfig::new(&entry.path, permissions),
+        )
+        .and_then(WasmToolProvider::from_module)
+        .with_context(|| format!("loading wasm module {}", binary.path.display()))?;
+        return Ok(Arc::new(provider));
+    }
+
+    let entrypoint = manifest
+        .entrypoint
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TOOL_PROVIDER_ENTRYPOINT.to_string());
+
+    let library = load_shared_library(binary)?;
+    unsafe {
+        let version: libloading::Symbol<AbiVersionFn> = library
+            .get(format!("{}\0", TOOL_ABI_VERSION_SYMBOL).as_bytes())
+            .map_err(|_| PromptSpiLoadError::MissingVersionSymbol(TOOL_ABI_VERSION_SYMBOL))?;
+        check_abi_version(version())?;
+
+        let symbol_name = CString::new(entrypoint.clone())
+            .map_err(|_| anyhow!("invalid entrypoint '{}': contains null byte", entrypoint))?;
+        let constructor: libloading::Symbol<ToolProviderEntrypoint> = library
+            .get(symbol_name.as_bytes_with_nul())
+            .with_context(|| format!("resolving symbol '{}'", entrypoint))?;
+        let provider = ForeignToolProvider::from_vtable(constructor())
+            .with_context(|| format!("binding tool provider from '{}'", entrypoint))?;
+
+        Ok(Arc::new(LibraryBoundProvider {
+            provider,
+            _library: library.clone(),
+        }))
+    }
+}
+
+/// vtable はライブラリ内の static を指すため、プロバイダーが破棄されるまでライブラリを保持する
+struct LibraryBoundProvider {
+    provider: ForeignToolProvider,
+    _library: Arc<PluginLibrary>,
+}
+
+impl ToolProvider for LibraryBoundProvider {
+    fn tools(&self) -> Vec<ToolSpec> {
+        self.provider.tools()
+    }
+
+    fn invoke(&self, name: &str, arguments: Value) -> PromptSpiResult<Value> {
+        self.provider.invoke(name, arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prompt_spi::PromptSpiError;
+    use serde_json::json;
+
+    struct Clock;
+
+    impl ToolProvider for Clock {
+        fn tools(&self) -> Vec<ToolSpec> {
+            vec![ToolSpec {
+                name: "now".into(),
+                description: Some("Current time".into()),
+                input_schema: json!({ "type": "object" }),
+            }]
+        }
+
+        fn invoke(&self, name: &str, _arguments: Value) -> PromptSpiResult<Value> {
+            match name {
+                "now" => Ok(json!("2024-01-01T00:00:00Z")),
+                other => Err(PromptSpiError::Tool(format!("unknown tool: {}", other))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_calls_to_the_owning_plugin() {
+        let mut registry = ToolRegistry::default();
+        assert!(registry.is_empty());
+        registry.register_provider("clock", Arc::new(Clock));
+
+        let tools = registry.tools();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].0, "clock");
+        assert!(registry.contains("clock", "now"));
+        assert!(!registry.contains("other", "now"));
+
+        let result = registry.call_tool("clock", "now", json!({})).await;
+        assert_eq!(result.unwrap(), json!("2024-01-01T00:00:00Z"));
+        assert!(registry
+            .call_tool("clock", "later", json!({}))
+            .await
+            .is_err());
+    }
+}
@@ -1,4 +1,7 @@
+use crate::plugins::commands::parse_commands;
 use crate::plugins::metadata::{PluginKind, PluginMetadata};
+use crate::plugins::model_pattern::ModelPattern;
+use crate::plugins::settings::parse_settings_schema;
 use anyhow::{Context, Result};
 use std::collections::HashSet;
 use std::fs;
@@ -33,6 +36,7 @@ pub fn extract_metadata(value: &toml::Value) -> PluginMetadata {
         .map(|s| match s {
             "prompt_builder" => PluginKind::PromptBuilder,
             "adapter" => PluginKind::Adapter,
+            "tool" => PluginKind::Tool,
             _ => PluginKind::Adapter,
         })
         .unwrap_or_default();
@@ -47,6 +51,11 @@ pub fn extract_metadata(value: &toml::Value) -> PluginMetadata {
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
+    let wasm = value
+        .get("wasm")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
     let models = value
         .get("models")
         .and_then(|v| v.as_array())
@@ -70,8 +79,11 @@ pub fn extract_metadata(value: &toml::Value) -> PluginMetadata {
         kind,
         entrypoint,
         library,
+        wasm,
         models,
         priority,
+        settings: parse_settings_schema(value).unwrap_or_default(),
+        commands: parse_commands(value).unwrap_or_default(),
     }
 }
 
@@ -99,8 +111,20 @@ pub fn validate_manifest(path: &Path) -> Result<(PluginMetadata, HashSet<String>
     }
 
     let metadata = extract_metadata(&v);
+    parse_settings_schema(&v)?;
+    parse_commands(&v)?;
+    if metadata.kind == PluginKind::PromptBuilder {
+        for model in &metadata.models {
+            ModelPattern::parse(model)?;
+        }
+    }
 
-    if matches!(metadata.kind, PluginKind::PromptBuilder) {
+    let has_wasm = !metadata.wasm.as_deref().unwrap_or("").trim().is_empty();
+    if matches!(metadata.kind, PluginKind::PromptBuilder) && has_wasm {
+        if metadata.models.is_empty() {
+            anyhow::bail!("prompt_builder requires at least one model in 'models'");
+        }
+    } else if matches!(metadata.kind, PluginKind::PromptBuilder) {
         if metadata
             .entrypoint
             .as_deref()
@@ -117,6 +141,18 @@ pub fn validate_manifest(path: &Path) -> Result<(PluginMetadata, HashSet<String>
             anyhow::bail!("prompt_builder requires at least one model in 'models'");
         }
     }
+    if matches!(metadata.kind, PluginKind::Adapter)
+        && !metadata.library.as_deref().unwrap_or("").trim().is_empty()
+        && metadata.models.is_empty()
+    {
+        anyhow::bail!("adapter with 'library' requires at least one model in 'models'");
+    }
+    if matches!(metadata.kind, PluginKind::Tool)
+        && !has_wasm
+        && metadata.library.as_deref().unwrap_or("").trim().is_empty()
+    {
+        anyhow::bail!("tool requires non-empty 'library' or 'wasm'");
+    }
     let caps = extract_capabilities(&v);
     Ok((metadata, caps))
 }
@@ -0,0 +1,593 @@
+//! チャット入力のスラッシュコマンド（`/model qwen3:4b` など）
+//!
+//! 組み込みのコマンドに、プラグインの `[commands]` と MCP サーバーのプロンプトを足して一覧を作る。
+//! GUI の入力欄（`ChatCommand::SendUserMessage`）と CLI の `chat` が同じ解析を使う。
+
+use std::path::PathBuf;
+
+use chat_history::Conversation;
+use serde_json::{Map, Value};
+
+use crate::mcp_client::McpPrompt;
+use crate::plugins::commands::PluginCommand;
+use crate::plugins::metadata::PluginEntry;
+
+/// コマンドの提供元
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlashCommandSource {
+    Builtin,
+    /// プラグイン（ディレクトリ名）の `[commands]`
+    Plugin {
+        plugin: String,
+        command: PluginCommand,
+    },
+    /// MCP サーバーのプロンプト
+    McpPrompt {
+        server: String,
+        prompt: McpPrompt,
+    },
+}
+
+/// 補完や一覧に出すコマンド
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlashCommandSpec {
+    /// `/` を除いた名前
+    pub name: String,
+    /// 引数の書き方（引数を取らなければ空）
+    pub usage: String,
+    pub description: String,
+    pub source: SlashCommandSource,
+}
+
+impl SlashCommandSpec {
+    /// 入力欄に補完する文字列（引数を取るコマンドは末尾に空白を付ける）
+    pub fn completion(&self) -> String {
+        if self.usage.is_empty() {
+            format!("/{}", self.name)
+        } else {
+            format!("/{} ", self.name)
+        }
+    }
+}
+
+/// 会話の書き出し形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    /// 会話をこの形式の文字列にする
+    pub fn render(self, conversation: &Conversation) -> String {
+        match self {
+            ExportFormat::Markdown => conversation.to_markdown(),
+        }
+    }
+}
+
+/// 解析したスラッシュコマンド
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlashCommand {
+    SwitchModel(String),
+    NewConversation,
+    /// 現在の会話のメッセージをすべて消す
+    ClearConversation,
+    /// None なら既定の指示文に戻す
+    SetSystemPrompt(Option<String>),
+    /// ツールを直接呼ぶ（server が None ならツール名から探す）
+    CallTool {
+        tool: String,
+        server: Option<String>,
+        arguments: Value,
+    },
+    /// path が None なら [`default_export_path`]
+    Export {
+        format: ExportFormat,
+        path: Option<PathBuf>,
+    },
+    /// 最後の応答を生成し直す
+    Retry,
+    /// 本文をユーザーメッセージとして送る（プラグインのコマンドと `//` で始まる入力）
+    SendMessage(String),
+    /// MCP サーバーのプロンプトを展開して送る
+    McpPrompt {
+        server: String,
+        prompt: String,
+        arguments: Map<String, Value>,
+    },
+}
+
+const BUILTIN_COMMANDS: &[(&str, &str, &str)] = &[
+    ("model", "<model>", "Switch the model of this conversation"),
+    ("new", "", "Start a new conversation"),
+    ("clear", "", "Delete all messages in this conversation"),
+    (
+        "system",
+        "[prompt]",
+        "Set the system prompt (no prompt resets it)",
+    ),
+    ("tool", "<tool>[@server] [json]", "Call a tool directly"),
+    (
+        "export",
+        "md [path]",
+        "Export this conversation as Markdown",
+    ),
+    ("retry", "", "Regenerate the last response"),
+];
+
+/// 使えるスラッシュコマンドの一覧
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlashCommandRegistry {
+    commands: Vec<SlashCommandSpec>,
+}
+
+impl Default for SlashCommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlashCommandRegistry {
+    /// 組み込みのコマンドだけを持つ一覧
+    pub fn new() -> Self {
+        let commands = BUILTIN_COMMANDS
+            .iter()
+            .map(|(name, usage, description)| SlashCommandSpec {
+                name: name.to_string(),
+                usage: usage.to_string(),
+                description: description.to_string(),
+                source: SlashCommandSource::Builtin,
+            })
+            .collect();
+        Self { commands }
+    }
+
+    pub fn commands(&self) -> &[SlashCommandSpec] {
+        &self.commands
+    }
+
+    pub fn find(&self, name: &str) -> Option<&SlashCommandSpec> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// コマンドを追加する。同名のコマンドがあれば追加せず false（先に登録した方が優先）
+    pub fn register(&mut self, spec: SlashCommandSpec) -> bool {
+        if self.find(&spec.name).is_some() {
+            return false;
+        }
+        self.commands.push(spec);
+        true
+    }
+
+    /// プラグインのコマンドを、有効なプラグインの `[commands]` で置き換える
+    pub fn set_plugin_commands(&mut self, plugins: &[PluginEntry]) {
+        self.commands
+            .retain(|command| !matches!(command.source, SlashCommandSource::Plugin { .. }));
+        for plugin in plugins.iter().filter(|plugin| plugin.enabled) {
+            let Some(metadata) = &plugin.metadata else {
+                continue;
+            };
+            for command in &metadata.commands {
+                let spec = SlashCommandSpec {
+                    name: command.name.clone(),
+                    usage: "[input]".to_string(),
+                    description: command.description.clone().unwrap_or_default(),
+                    source: SlashCommandSource::Plugin {
+                        plugin: plugin.dir_name.clone(),
+                        command: command.clone(),
+                    },
+                };
+                if !self.register(spec) {
+                    eprintln!(
+                        "Warning: Command /{} of plugin '{}' is already defined",
+                        command.name, plugin.dir_name
+                    );
+                }
+            }
+        }
+    }
+
+    /// MCP のプロンプトを、サーバーごとのプロンプト一覧で置き換える
+    pub fn set_mcp_prompts(&mut self, prompts: &[(String, McpPrompt)]) {
+        self.commands
+            .retain(|command| !matches!(command.source, SlashCommandSource::McpPrompt { .. }));
+        for (server, prompt) in prompts {
+            let usage = prompt
+                .arguments
+                .iter()
+                .map(|argument| {
+                    if argument.required {
+                        format!("<{}>", argument.name)
+                    } else {
+                        format!("[{}]", argument.name)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let spec = SlashCommandSpec {
+                name: prompt.name.clone(),
+                usage,
+                description: prompt
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("Prompt from {}", server)),
+                source: SlashCommandSource::McpPrompt {
+                    server: server.clone(),
+                    prompt: prompt.clone(),
+                },
+            };
+            if !self.register(spec) {
+                eprintln!(
+                    "Warning: Prompt /{} of MCP server '{}' is already defined",
+                    prompt.name, server
+                );
+            }
+        }
+    }
+
+    /// 入力中のコマンド名に前方一致するコマンド（引数を入力し始めたら空）
+    pub fn completions(&self, input: &str) -> Vec<&SlashCommandSpec> {
+        let Some(prefix) = input.strip_prefix('/') else {
+            return Vec::new();
+        };
+        if prefix.starts_with('/') || prefix.contains(char::is_whitespace) {
+            return Vec::new();
+        }
+        self.commands
+            .iter()
+            .filter(|command| command.name.starts_with(prefix))
+            .collect()
+    }
+
+    /// 入力を解析する。`/` で始まらなければ None（`//` で始まれば `/` を 1 つ外して送る）
+    pub fn parse(&self, input: &str) -> Option<Result<SlashCommand, String>> {
+        let body = input.trim().strip_prefix('/')?;
+        if body.starts_with('/') {
+            return Some(Ok(SlashCommand::SendMessage(body.to_string())));
+        }
+        let (name, args) = split_first_word(body);
+        let Some(spec) = self.find(name) else {
+            return Some(Err(format!("Unknown command: /{}", name)));
+        };
+        Some(match &spec.source {
+            SlashCommandSource::Builtin => parse_builtin(name, args),
+            SlashCommandSource::Plugin { command, .. } => {
+                Ok(SlashCommand::SendMessage(command.expand(args)))
+            }
+            SlashCommandSource::McpPrompt { server, prompt } => {
+                prompt_arguments(prompt, args).map(|arguments| SlashCommand::McpPrompt {
+                    server: server.clone(),
+                    prompt: prompt.name.clone(),
+                    arguments,
+                })
+            }
+        })
+    }
+}
+
+/// 既定の書き出し先（データディレクトリの `exports/<タイトル>-<ID の先頭>.<拡張子>`）
+pub fn default_export_path(conversation: &Conversation, format: ExportFormat) -> PathBuf {
+    let title: String = conversation
+        .title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let title = title.trim_matches('_');
+    let title = if title.is_empty() {
+        "conversation"
+    } else {
+        title
+    };
+    let id: String = conversation.id.chars().take(8).collect();
+    app_config::get_default_data_dir()
+        .join("exports")
+        .join(format!("{}-{}.{}", title, id, format.extension()))
+}
+
+fn split_first_word(text: &str) -> (&str, &str) {
+    match text.split_once(char::is_whitespace) {
+        Some((first, rest)) => (first, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+fn parse_builtin(name: &str, args: &str) -> Result<SlashCommand, String> {
+    let without_args = |command: SlashCommand| {
+        if args.is_empty() {
+            Ok(command)
+        } else {
+            Err(format!("/{} takes no arguments", name))
+        }
+    };
+    match name {
+        "model" if args.is_empty() => Err("Usage: /model <model>".to_string()),
+        "model" => Ok(SlashCommand::SwitchModel(args.to_string())),
+        "new" => without_args(SlashCommand::NewConversation),
+        "clear" => without_args(SlashCommand::ClearConversation),
+        "system" => Ok(SlashCommand::SetSystemPrompt(
+            Some(args.to_string()).filter(|prompt| !prompt.is_empty()),
+        )),
+        "tool" => parse_tool(args),
+        "export" => parse_export(args),
+        "retry" => without_args(SlashCommand::Retry),
+        _ => Err(format!("Unknown command: /{}", name)),
+    }
+}
+
+fn parse_tool(args: &str) -> Result<SlashCommand, String> {
+    let (identifier, json) = split_first_word(args);
+    if identifier.is_empty() {
+        return Err("Usage: /tool <tool>[@server] [json]".to_string());
+    }
+    let (tool, server) = match identifier.rsplit_once('@') {
+        Some((tool, server)) if !tool.is_empty() && !server.is_empty() => {
+            (tool, Some(server.to_string()))
+        }
+        Some(_) => return Err(format!("Invalid tool identifier: {}", identifier)),
+        None => (identifier, None),
+    };
+    let arguments = if json.is_empty() {
+        Value::Object(Map::new())
+    } else {
+        serde_json::from_str::<Value>(json).map_err(|e| format!("Invalid tool arguments: {}", e))?
+    };
+    if !arguments.is_object() {
+        return Err("Tool arguments must be a JSON object".to_string());
+    }
+    Ok(SlashCommand::CallTool {
+        tool: tool.to_string(),
+        server,
+        arguments,
+    })
+}
+
+fn parse_export(args: &str) -> Result<SlashCommand, String> {
+    let (format, path) = split_first_word(args);
+    if format.is_empty() {
+        return Err("Usage: /export md [path]".to_string());
+    }
+    let format = ExportFormat::parse(format)
+        .ok_or_else(|| format!("Unsupported export format: {} (use md)", format))?;
+    Ok(SlashCommand::Export {
+        format,
+        path: Some(path)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from),
+    })
+}
+
+/// MCP プロンプトの引数。JSON オブジェクトか、引数が 1 つならその値として扱う
+fn prompt_arguments(prompt: &McpPrompt, args: &str) -> Result<Map<String, Value>, String> {
+    let arguments = if args.is_empty() {
+        Map::new()
+    } else if args.starts_with('{') {
+        match serde_json::from_str::<Value>(args) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => return Err("Prompt arguments must be a JSON object".to_string()),
+            Err(e) => return Err(format!("Invalid prompt arguments: {}", e)),
+        }
+    } else {
+        match prompt.arguments.as_slice() {
+            [only] => Map::from_iter([(only.name.clone(), Value::String(args.to_string()))]),
+            [] => return Err(format!("/{} takes no arguments", prompt.name)),
+            _ => {
+                return Err(format!(
+                    "Pass the arguments of /{} as a JSON object",
+                    prompt.name
+                ))
+            }
+        }
+    };
+    if let Some(missing) = prompt
+        .arguments
+        .iter()
+        .find(|argument| argument.required && !arguments.contains_key(&argument.name))
+    {
+        return Err(format!(
+            "/{} requires the argument '{}'",
+            prompt.name, missing.name
+        ));
+    }
+    Ok(arguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp_client::McpPromptArgument;
+    use crate::plugins::metadata::PluginMetadata;
+    use serde_json::json;
+
+    fn parse(input: &str) -> Result<SlashCommand, String> {
+        SlashCommandRegistry::new().parse(input).unwrap()
+    }
+
+    fn plugin(commands: Vec<PluginCommand>, enabled: bool) -> PluginEntry {
+        let manifest = toml::Value::Table(Default::default());
+        let mut metadata: PluginMetadata = crate::plugins::validation::extract_metadata(&manifest);
+        metadata.commands = commands;
+        PluginEntry {
+            dir_name: "snippets".into(),
+            path: PathBuf::from("plugins/snippets"),
+            enabled,
+            metadata: Some(metadata),
+        }
+    }
+
+    #[test]
+    fn parses_builtin_commands() {
+        let registry = SlashCommandRegistry::new();
+        assert!(registry.parse("hello /model").is_none());
+        assert_eq!(
+            parse("/model qwen3:4b"),
+            Ok(SlashCommand::SwitchModel("qwen3:4b".into()))
+        );
+        assert!(parse("/model").is_err());
+        assert_eq!(parse(" /new "), Ok(SlashCommand::NewConversation));
+        assert!(parse("/clear everything").is_err());
+        assert_eq!(
+            parse("/system Be brief.\nUse English."),
+            Ok(SlashCommand::SetSystemPrompt(Some(
+                "Be brief.\nUse English.".into()
+            )))
+        );
+        assert_eq!(parse("/system"), Ok(SlashCommand::SetSystemPrompt(None)));
+        assert_eq!(parse("/retry"), Ok(SlashCommand::Retry));
+        assert_eq!(
+            parse("/unknown"),
+            Err("Unknown command: /unknown".to_string())
+        );
+        assert_eq!(
+            parse("//model is literal"),
+            Ok(SlashCommand::SendMessage("/model is literal".into()))
+        );
+    }
+
+    #[test]
+    fn parses_tool_calls_and_exports() {
+        assert_eq!(
+            parse(r#"/tool get_weather@weather {"city": "Tokyo"}"#),
+            Ok(SlashCommand::CallTool {
+                tool: "get_weather".into(),
+                server: Some("weather".into()),
+                arguments: json!({"city": "Tokyo"}),
+            })
+        );
+        assert_eq!(
+            parse("/tool list_files"),
+            Ok(SlashCommand::CallTool {
+                tool: "list_files".into(),
+                server: None,
+                arguments: json!({}),
+            })
+        );
+        assert!(parse("/tool get_weather@ {}").is_err());
+        assert!(parse("/tool get_weather [1, 2]").is_err());
+        assert!(parse("/tool get_weather {oops").is_err());
+
+        assert_eq!(
+            parse("/export md"),
+            Ok(SlashCommand::Export {
+                format: ExportFormat::Markdown,
+                path: None,
+            })
+        );
+        assert_eq!(
+            parse("/export markdown notes/chat.md"),
+            Ok(SlashCommand::Export {
+                format: ExportFormat::Markdown,
+                path: Some(PathBuf::from("notes/chat.md")),
+            })
+        );
+        assert!(parse("/export pdf").is_err());
+    }
+
+    #[test]
+    fn completes_command_names_until_arguments_start() {
+        let registry = SlashCommandRegistry::new();
+        let names = |input: &str| -> Vec<String> {
+            registry
+                .completions(input)
+                .into_iter()
+                .map(|spec| spec.completion())
+                .collect()
+        };
+        assert_eq!(names("/").len(), registry.commands().len());
+        assert_eq!(names("/re"), ["/retry"]);
+        assert_eq!(names("/mo"), ["/model "]);
+        assert!(names("/model q").is_empty());
+        assert!(names("model").is_empty());
+        assert!(names("//").is_empty());
+    }
+
+    #[test]
+    fn plugin_commands_expand_into_messages() {
+        let summarize = PluginCommand {
+            name: "summarize".into(),
+            description: Some("Summarize text".into()),
+            prompt: "Summarize:\n{input}".into(),
+        };
+        let shadowing = PluginCommand {
+            name: "model".into(),
+            description: None,
+            prompt: "ignored".into(),
+        };
+        let mut registry = SlashCommandRegistry::new();
+        registry.set_plugin_commands(&[plugin(vec![summarize.clone(), shadowing], true)]);
+        assert_eq!(
+            registry.parse("/summarize the meeting notes"),
+            Some(Ok(SlashCommand::SendMessage(
+                "Summarize:\nthe meeting notes".into()
+            )))
+        );
+        // 組み込みのコマンドは上書きできない
+        assert_eq!(
+            registry.parse("/model phi4"),
+            Some(Ok(SlashCommand::SwitchModel("phi4".into())))
+        );
+
+        registry.set_plugin_commands(&[plugin(vec![summarize], false)]);
+        assert!(registry.find("summarize").is_none());
+    }
+
+    #[test]
+    fn mcp_prompts_take_a_single_value_or_json() {
+        let prompt = |name: &str, arguments: &[(&str, bool)]| McpPrompt {
+            name: name.into(),
+            description: None,
+            arguments: arguments
+                .iter()
+                .map(|(name, required)| McpPromptArgument {
+                    name: name.to_string(),
+                    description: None,
+                    required: *required,
+                })
+                .collect(),
+        };
+        let mut registry = SlashCommandRegistry::new();
+        registry.set_mcp_prompts(&[
+            ("git".into(), prompt("review", &[("diff", true)])),
+            (
+                "git".into(),
+                prompt("commit", &[("message", true), ("scope", false)]),
+            ),
+        ]);
+        assert_eq!(registry.find("commit").unwrap().usage, "<message> [scope]");
+        assert_eq!(
+            registry.parse("/review fn main() {}"),
+            Some(Ok(SlashCommand::McpPrompt {
+                server: "git".into(),
+                prompt: "review".into(),
+                arguments: Map::from_iter([("diff".to_string(), json!("fn main() {}"))]),
+            }))
+        );
+        assert!(registry.parse("/review").unwrap().is_err());
+        assert!(registry.parse("/commit fix typo").unwrap().is_err());
+        assert!(registry
+            .parse(r#"/commit {"message": "fix typo"}"#)
+            .unwrap()
+            .is_ok());
+
+        registry.set_mcp_prompts(&[]);
+        assert!(registry.find("review").is_none());
+    }
+}
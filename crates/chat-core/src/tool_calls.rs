@@ -0,0 +1,115 @@
+//! ターン中のツール呼び出しを集め、会話に `ToolCallRecord` として残す
+
+use crate::console_log::{ConsoleLogKind, ConsoleLogSource, RequestLogger};
+use chat_history::ToolCallRecord;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// ツール呼び出しの記録先。LangChain のツールと共有し、ターンの終わりに取り出す
+#[derive(Clone, Default)]
+pub struct ToolCallLog {
+    records: Arc<Mutex<Vec<ToolCallRecord>>>,
+    /// 呼び出しをコンソールにも出す（今のターンのリクエスト ID を付ける）
+    console: Arc<Mutex<Option<RequestLogger>>>,
+}
+
+impl ToolCallLog {
+    pub fn push(&self, record: ToolCallRecord) {
+        self.log_to_console(&record);
+        if let Ok(mut records) = self.records.lock() {
+            records.push(record);
+        }
+    }
+
+    pub(crate) fn set_console(&self, logger: Option<RequestLogger>) {
+        if let Ok(mut console) = self.console.lock() {
+            *console = logger;
+        }
+    }
+
+    fn log_to_console(&self, record: &ToolCallRecord) {
+        let Some(logger) = self.console.lock().ok().and_then(|guard| guard.clone()) else {
+            return;
+        };
+        let (kind, outcome) = match (&record.result, &record.error) {
+            (_, Some(error)) => (ConsoleLogKind::Error, error.clone()),
+            (Some(result), None) => (ConsoleLogKind::Output, result.to_string()),
+            (None, None) => (ConsoleLogKind::Output, String::new()),
+        };
+        let content = format!("{} {}\n{}", record.identifier(), record.arguments, outcome);
+        logger.emit(
+            logger
+                .record(kind, ConsoleLogSource::Mcp(record.server.clone()), content)
+                .with_duration(Duration::from_millis(record.duration_ms)),
+        );
+    }
+
+    /// 記録を取り出して空にする
+    pub fn take(&self) -> Vec<ToolCallRecord> {
+        self.records
+            .lock()
+            .map(|mut records| std::mem::take(&mut *records))
+            .unwrap_or_default()
+    }
+}
+
+/// ツールを呼び出し、引数・結果・所要時間を `log` に残す
+pub(crate) async fn call_with_log<F, Fut>(
+    log: Option<&ToolCallLog>,
+    server: &str,
+    tool: &str,
+    arguments: Value,
+    call: F,
+) -> Result<Value, String>
+where
+    F: FnOnce(Value) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let started = Instant::now();
+    let result = call(arguments.clone()).await;
+    if let Some(log) = log {
+        log.push(ToolCallRecord {
+            tool: tool.to_string(),
+            server: server.to_string(),
+            arguments,
+            result: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+            duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn records_results_and_errors() {
+        let log = ToolCallLog::default();
+        let ok = call_with_log(
+            Some(&log),
+            "weather",
+            "forecast",
+            json!({"city": "Osaka"}),
+            |args| async move { Ok(json!({"echo": args})) },
+        )
+        .await;
+        assert!(ok.is_ok());
+        let err = call_with_log(Some(&log), "weather", "broken", json!({}), |_| async {
+            Err::<Value, _>("boom".to_string())
+        })
+        .await;
+        assert_eq!(err.unwrap_err(), "boom");
+
+        let records = log.take();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].identifier(), "forecast@weather");
+        assert_eq!(records[0].result, Some(json!({"echo": {"city": "Osaka"}})));
+        assert_eq!(records[1].error.as_deref(), Some("boom"));
+        assert!(log.take().is_empty());
+    }
+}
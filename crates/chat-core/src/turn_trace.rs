@@ -0,0 +1,81 @@
+//! 1 ターン分の実行記録（モデルに渡したプロンプト・生の出力・ビルダーの解析結果）
+//!
+//! 応答メッセージのメタデータ `trace` に保存し、UI の「Inspect」で表示する。
+
+use chat_history::Message;
+use prompt_spi::PromptParseOutput;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const METADATA_KEY: &str = "trace";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnTrace {
+    /// モデルに渡したプロンプト（ビルダーやアダプタが組み立てたもの）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// モデルの生の出力
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_output: Option<String>,
+    /// プロンプトビルダーの `parse` の結果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed: Option<PromptParseOutput>,
+}
+
+impl TurnTrace {
+    /// メッセージに保存された記録（無ければ None）
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let value = message.metadata.as_ref()?.get(METADATA_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prompt.is_none() && self.raw_output.is_none() && self.parsed.is_none()
+    }
+
+    /// 応答のメタデータに記録を加える。空なら何もしない
+    pub(crate) fn attach_to(&self, metadata: Option<Value>) -> Option<Value> {
+        if self.is_empty() {
+            return metadata;
+        }
+        let mut metadata = metadata.unwrap_or_else(|| Value::Object(Default::default()));
+        if let (Some(object), Ok(trace)) = (metadata.as_object_mut(), serde_json::to_value(self)) {
+            object.insert(METADATA_KEY.into(), trace);
+        }
+        Some(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chat_history::MessageRole;
+    use prompt_spi::ToolInvocation;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_through_message_metadata() {
+        let trace = TurnTrace {
+            prompt: Some("<|user|>hi".into()),
+            raw_output: Some("<|tool-invoke|>{}".into()),
+            parsed: Some(PromptParseOutput {
+                final_answer: None,
+                tool_requests: vec![ToolInvocation {
+                    name: "search@web".into(),
+                    arguments: json!({"q": "cats"}),
+                }],
+            }),
+        };
+        let metadata = trace.attach_to(Some(json!({"source": "mcp"}))).unwrap();
+        assert_eq!(metadata["source"], "mcp");
+
+        let message = Message::with_metadata(MessageRole::Assistant, "answer", metadata);
+        let restored = TurnTrace::from_message(&message).unwrap();
+        assert_eq!(restored.prompt, trace.prompt);
+        assert_eq!(restored.raw_output, trace.raw_output);
+        assert_eq!(restored.parsed.unwrap().tool_requests[0].name, "search@web");
+
+        assert!(TurnTrace::default().attach_to(None).is_none());
+        assert!(TurnTrace::from_message(&Message::assistant("plain")).is_none());
+    }
+}
@@ -1,8 +1,17 @@
 use chat_core::{
-    ChatCommand, ChatController, ChatControllerConfig, ChatEvent, ChatState,
-    ControllerSubscription, ConversationService,
+    register_builtin_prompt_builders, ChatCommand, ChatController, ChatControllerConfig, ChatEvent,
+    ChatState, ConsoleLogKind, ConsoleLogRecord, ConsoleLogSource, ControllerSubscription,
+    ConversationService, HostPromptBuilderFactory, NoteStore, PromptBuilderRegistry,
+    DEFAULT_CONSOLE_LOG_CAPACITY,
+};
+use chat_history::{
+    Conversation, ConversationFilter, ConversationManager, GenerationOptions, Message, MessageRole,
+    ToolCallRecord,
+};
+use prompt_spi::{
+    PromptAgentMode, PromptBuilder, PromptContext, PromptMetadata, PromptParseOutput,
+    PromptPayload, PromptSpiError, PromptSpiResult,
 };
-use chat_history::{Conversation, ConversationManager, Message, MessageRole};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tempfile::{tempdir, TempDir};
@@ -64,8 +73,65 @@ fn conversation_service_pop_last_if_removes_matching_message() {
     assert_eq!(messages.last().unwrap().content, "Keep this");
 }
 
+#[test]
+fn conversation_service_updates_stored_conversation_organisation() {
+    let temp_dir = tempdir().unwrap();
+    let service = conversation_service_with_temp_storage(&temp_dir);
+    service.save_current().unwrap();
+
+    let other = Conversation::new("Other");
+    let other_id = other.id.clone();
+    ConversationManager::new(temp_dir.path())
+        .unwrap()
+        .save(&other)
+        .unwrap();
+
+    service
+        .update_conversation(&other_id, |conv| {
+            conv.set_folder(Some("project-a".to_string()));
+            conv.add_tag("rust");
+            conv.archived = true;
+        })
+        .unwrap();
+
+    let visible = service
+        .list_filtered(&ConversationFilter::default())
+        .unwrap();
+    assert_eq!(visible.len(), 1);
+    assert_ne!(visible[0].id, other_id);
+
+    let archive = service
+        .list_filtered(&ConversationFilter {
+            archived: true,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(archive.len(), 1);
+    assert_eq!(archive[0].folder.as_deref(), Some("project-a"));
+    assert_eq!(archive[0].tags, vec!["rust"]);
+}
+
+#[test]
+fn conversation_service_restores_current_conversation_from_backup() {
+    let temp_dir = tempdir().unwrap();
+    let service = conversation_service_with_temp_storage(&temp_dir);
+
+    service.append_message(MessageRole::User, "first").unwrap();
+    service
+        .append_message(MessageRole::User, "accidental")
+        .unwrap();
+
+    let id = service.current_conversation_id().unwrap();
+    let restored = service.restore_backup(&id, None).unwrap();
+
+    assert_eq!(restored.messages.last().unwrap().content, "first");
+    assert_eq!(service.current_messages().last().unwrap().content, "first");
+    assert!(!service.list_backups(&id).unwrap().is_empty());
+}
+
 struct ControllerHarness {
     controller: ChatController,
+    service: ConversationService,
     events_rx: UnboundedReceiver<ChatEvent>,
     _subscription: ControllerSubscription,
     _temp_dir: TempDir,
@@ -73,16 +139,24 @@ struct ControllerHarness {
 
 impl ControllerHarness {
     fn new() -> Self {
+        Self::with_prompt_registry(None)
+    }
+
+    fn with_prompt_registry(prompt_registry: Option<Arc<PromptBuilderRegistry>>) -> Self {
         let temp_dir = tempdir().unwrap();
         let service = conversation_service_with_temp_storage(&temp_dir);
         let controller = ChatController::new(ChatControllerConfig {
-            conversation_service: service,
+            conversation_service: service.clone(),
             active_model: "phi4-mini:3.8b".to_string(),
             use_langchain: false,
             ollama_url: "http://localhost:11434".to_string(),
             mcp_manager: None,
             mcp_configs: Vec::new(),
-            prompt_registry: None,
+            prompt_registry,
+            adapter_registry: None,
+            tool_registry: None,
+            note_store: Some(NoteStore::new(temp_dir.path().join("notes"))),
+            console_log_capacity: DEFAULT_CONSOLE_LOG_CAPACITY,
             welcome_message: "Welcome to Neko Assistant".to_string(),
         });
 
@@ -93,6 +167,7 @@ impl ControllerHarness {
 
         Self {
             controller,
+            service,
             events_rx: rx,
             _subscription: subscription,
             _temp_dir: temp_dir,
@@ -114,6 +189,15 @@ impl ControllerHarness {
             }
         }
     }
+
+    async fn state_matching(&mut self, predicate: impl Fn(&ChatState) -> bool) -> ChatState {
+        loop {
+            let state = self.next_state().await;
+            if predicate(&state) {
+                return state;
+            }
+        }
+    }
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -183,3 +267,767 @@ async fn chat_controller_create_conversation_resets_history() {
     assert_eq!(state.messages[0].role, MessageRole::System);
     assert_eq!(state.messages[0].content, "Welcome to Neko Assistant");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_pins_and_filters_conversations() {
+    let harness = ControllerHarness::new();
+
+    harness
+        .controller
+        .handle_command(ChatCommand::CreateConversation)
+        .unwrap();
+    let current_id = harness.controller.state_snapshot().conversation_id.unwrap();
+    harness
+        .controller
+        .handle_command(ChatCommand::CreateConversation)
+        .unwrap();
+    harness
+        .controller
+        .handle_command(ChatCommand::SetConversationPinned {
+            id: current_id.clone(),
+            pinned: true,
+        })
+        .unwrap();
+
+    let state = harness.controller.state_snapshot();
+    assert_eq!(state.conversations.len(), 2);
+    assert_eq!(state.conversations[0].id, current_id);
+    assert!(state.conversations[0].pinned);
+
+    harness
+        .controller
+        .handle_command(ChatCommand::AddConversationTag {
+            id: current_id.clone(),
+            tag: "work".to_string(),
+        })
+        .unwrap();
+    harness
+        .controller
+        .handle_command(ChatCommand::SetConversationFilter(ConversationFilter {
+            tag: Some("work".to_string()),
+            ..Default::default()
+        }))
+        .unwrap();
+
+    let state = harness.controller.state_snapshot();
+    assert_eq!(state.conversation_tags, vec!["work"]);
+    assert_eq!(state.conversations.len(), 1);
+    assert_eq!(state.conversations[0].id, current_id);
+
+    harness
+        .controller
+        .handle_command(ChatCommand::SetConversationArchived {
+            id: current_id,
+            archived: true,
+        })
+        .unwrap();
+    assert!(harness.controller.state_snapshot().conversations.is_empty());
+}
+
+#[tokio::test]
+async fn chat_controller_reports_unreadable_conversations_once() {
+    let harness = ControllerHarness::new();
+    // 読み込むと I/O エラーになる会話（隔離の対象ではない）
+    std::fs::create_dir(harness._temp_dir.path().join("unreadable.json")).unwrap();
+
+    for _ in 0..2 {
+        harness
+            .controller
+            .handle_command(ChatCommand::RefreshConversations)
+            .unwrap();
+    }
+
+    let state = harness.controller.state_snapshot();
+    assert_eq!(state.unreadable_conversations.len(), 1);
+    assert!(state.unreadable_conversations[0]
+        .path
+        .ends_with("unreadable.json"));
+    let reports = state
+        .console_logs
+        .iter()
+        .filter(|record| record.content.starts_with("Could not read conversation"))
+        .count();
+    assert_eq!(reports, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_restores_settings_when_switching_conversations() {
+    let harness = ControllerHarness::new();
+    let controller = &harness.controller;
+
+    controller
+        .handle_command(ChatCommand::CreateConversation)
+        .unwrap();
+    let first_id = controller.state_snapshot().conversation_id.unwrap();
+    controller
+        .handle_command(ChatCommand::SwitchModel("qwen3:4b-instruct".to_string()))
+        .unwrap();
+    controller
+        .handle_command(ChatCommand::SetSystemPrompt(Some(
+            "Answer in English.".to_string(),
+        )))
+        .unwrap();
+    controller
+        .handle_command(ChatCommand::SetGenerationOptions(GenerationOptions {
+            temperature: Some(0.2),
+            ..Default::default()
+        }))
+        .unwrap();
+
+    // 新しい会話は直前の設定を引き継ぐ
+    controller
+        .handle_command(ChatCommand::CreateConversation)
+        .unwrap();
+    let second_id = controller.state_snapshot().conversation_id.unwrap();
+    assert_eq!(
+        controller
+            .state_snapshot()
+            .conversation_settings
+            .system_prompt
+            .as_deref(),
+        Some("Answer in English.")
+    );
+    controller
+        .handle_command(ChatCommand::SwitchModel("phi4-mini:3.8b".to_string()))
+        .unwrap();
+    controller
+        .handle_command(ChatCommand::SetSystemPrompt(None))
+        .unwrap();
+    controller
+        .handle_command(ChatCommand::SetEnabledTools(Some(vec![
+            "forecast@weather".to_string()
+        ])))
+        .unwrap();
+
+    controller
+        .handle_command(ChatCommand::SwitchConversation(first_id))
+        .unwrap();
+    let state = controller.state_snapshot();
+    assert_eq!(state.active_model, "qwen3:4b-instruct");
+    assert_eq!(
+        state.conversation_settings.system_prompt.as_deref(),
+        Some("Answer in English.")
+    );
+    assert_eq!(
+        state.conversation_settings.generation.temperature,
+        Some(0.2)
+    );
+    assert!(state.conversation_settings.enabled_tools.is_none());
+
+    controller
+        .handle_command(ChatCommand::SwitchConversation(second_id))
+        .unwrap();
+    let state = controller.state_snapshot();
+    assert_eq!(state.active_model, "phi4-mini:3.8b");
+    assert!(state.conversation_settings.system_prompt.is_none());
+    assert_eq!(
+        state.conversation_settings.enabled_tools,
+        Some(vec!["forecast@weather".to_string()])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_reloads_changed_plugins() {
+    let mut harness = ControllerHarness::new();
+    let repo = tempdir().unwrap();
+    let plugin_dir = repo.path().join("plugins").join("sample");
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    std::fs::write(
+        plugin_dir.join("plugin.toml"),
+        "name = \"sample\"\nkind = \"prompt_builder\"\nmodels = [\"sample:latest\"]\n",
+    )
+    .unwrap();
+
+    let changes = harness.controller.reload_plugins(repo.path()).unwrap();
+    assert_eq!(changes.added, vec!["sample".to_string()]);
+    assert_eq!(harness.controller.plugins().len(), 1);
+    assert!(harness.controller.prompt_registry().is_some());
+
+    let mut observed_event = false;
+    for _ in 0..3 {
+        if let ChatEvent::PluginsReloaded = harness.next_event().await {
+            observed_event = true;
+            break;
+        }
+    }
+    assert!(observed_event, "plugin reload event not observed");
+    assert!(harness
+        .controller
+        .state_snapshot()
+        .console_logs
+        .iter()
+        .any(|log| log.content == "Plugins reloaded: added sample"));
+
+    // 変更が無ければ何もしない
+    assert!(harness
+        .controller
+        .reload_plugins(repo.path())
+        .unwrap()
+        .is_empty());
+}
+
+struct TerseBuilder;
+
+impl PromptBuilder for TerseBuilder {
+    fn metadata(&self) -> PromptMetadata {
+        terse_metadata()
+    }
+
+    fn build(&self, ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+        let last = ctx.conversation.last().map(|turn| turn.content);
+        Ok(PromptPayload::with_prompt(
+            format!("terse: {}", last.unwrap_or_default()),
+            PromptAgentMode::DirectProvider,
+        ))
+    }
+
+    fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+        Ok(PromptParseOutput {
+            final_answer: Some(raw_output.to_string()),
+            tool_requests: Vec::new(),
+        })
+    }
+}
+
+fn terse_metadata() -> PromptMetadata {
+    PromptMetadata {
+        name: "Terse".into(),
+        version: "0.1.0".into(),
+        description: None,
+        supported_models: vec!["phi4-mini:*".into()],
+        homepage: None,
+        preferred_agent: PromptAgentMode::DirectProvider,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_overrides_and_compares_prompt_builders() {
+    let mut registry = PromptBuilderRegistry::default();
+    register_builtin_prompt_builders(&mut registry);
+    registry.register_host_builder(
+        "phi4-mini:*",
+        HostPromptBuilderFactory::new(
+            terse_metadata(),
+            PromptAgentMode::DirectProvider,
+            0,
+            "test",
+            || Box::new(TerseBuilder),
+        ),
+    );
+    let harness = ControllerHarness::with_prompt_registry(Some(Arc::new(registry)));
+    let controller = &harness.controller;
+
+    let previews = controller.preview_prompt_builders().await.unwrap().unwrap();
+    let ids: Vec<&str> = previews.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, ["builtin:Builtin Phi4 Prompt", "test:Terse"]);
+    assert!(previews[0].selected);
+
+    controller
+        .handle_command(ChatCommand::SetPromptBuilder(Some(
+            "test:Terse".to_string(),
+        )))
+        .unwrap();
+    assert_eq!(
+        controller
+            .state_snapshot()
+            .conversation_settings
+            .prompt_builder
+            .as_deref(),
+        Some("test:Terse")
+    );
+    let previews = controller.preview_prompt_builders().await.unwrap().unwrap();
+    assert!(previews[1].selected);
+    let prompt = previews[1].result.as_ref().unwrap().prompt.clone();
+    assert_eq!(prompt.as_deref(), Some("terse: Welcome to Neko Assistant"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_includes_selected_notes_in_the_prompt() {
+    let mut registry = PromptBuilderRegistry::default();
+    register_builtin_prompt_builders(&mut registry);
+    let harness = ControllerHarness::with_prompt_registry(Some(Arc::new(registry)));
+    let controller = &harness.controller;
+    let notes = controller.note_store().unwrap();
+    notes.save("glossary", "neko = cat").unwrap();
+    notes.save("unrelated", "do not include").unwrap();
+
+    controller
+        .handle_command(ChatCommand::SetContextNotes(vec![
+            "glossary".to_string(),
+            "missing".to_string(),
+        ]))
+        .unwrap();
+    assert_eq!(
+        controller
+            .state_snapshot()
+            .conversation_settings
+            .context_notes,
+        ["glossary", "missing"]
+    );
+
+    let previews = controller.preview_prompt_builders().await.unwrap().unwrap();
+    let prompt = previews[0].result.as_ref().unwrap().prompt.clone().unwrap();
+    assert!(
+        prompt.contains("Note \"glossary\":\nneko = cat"),
+        "{}",
+        prompt
+    );
+    assert!(!prompt.contains("do not include"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_deletes_pins_and_retries_messages() {
+    let mut harness = ControllerHarness::new();
+    for text in ["first", "second"] {
+        harness
+            .controller
+            .handle_command(ChatCommand::SendUserMessage(text.to_string()))
+            .unwrap();
+    }
+    let state = harness.state_matching(|s| s.messages.len() == 5).await;
+    let contents: Vec<&str> = state.messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        [
+            "Welcome to Neko Assistant",
+            "first",
+            "(echo) first",
+            "second",
+            "(echo) second"
+        ]
+    );
+
+    let first_answer = state.messages[2].id.clone();
+    harness
+        .controller
+        .handle_command(ChatCommand::SetMessagePinned {
+            id: first_answer.clone(),
+            pinned: true,
+        })
+        .unwrap();
+    assert!(harness.controller.state_snapshot().messages[2].is_pinned());
+
+    harness
+        .controller
+        .handle_command(ChatCommand::DeleteMessage(state.messages[4].id.clone()))
+        .unwrap();
+    assert_eq!(harness.controller.state_snapshot().messages.len(), 4);
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::DeleteMessage(state.messages[4].id.clone()))
+        .is_err());
+
+    // 最初の応答からやり直すと、それ以降が消えて応答が作り直される
+    harness
+        .controller
+        .handle_command(ChatCommand::RetryFromMessage(first_answer))
+        .unwrap();
+    let state = harness
+        .state_matching(|s| {
+            s.messages
+                .last()
+                .is_some_and(|m| m.role == MessageRole::Assistant)
+        })
+        .await;
+    let contents: Vec<&str> = state.messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        ["Welcome to Neko Assistant", "first", "(echo) first"]
+    );
+    assert!(!state.messages[2].is_pinned());
+
+    let welcome = state.messages[0].id.clone();
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::RetryFromMessage(welcome))
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_reruns_recorded_tool_calls() {
+    let mut harness = ControllerHarness::new();
+    let record = ToolCallRecord {
+        tool: "search".into(),
+        server: "web".into(),
+        arguments: serde_json::json!({"q": "cats"}),
+        result: Some(serde_json::json!({"hits": 3})),
+        error: None,
+        duration_ms: 12,
+    };
+    harness
+        .service
+        .mutate_and_save(|conv| conv.add_message(Message::tool_call(&record)))
+        .unwrap();
+    let messages = harness.service.current_messages();
+    let tool_message = messages.last().unwrap().clone();
+    assert_eq!(tool_message.role, MessageRole::Tool);
+
+    // ツールの提供元が無いのでエラーとして記録される
+    harness
+        .controller
+        .handle_command(ChatCommand::RerunToolCall(tool_message.id.clone()))
+        .unwrap();
+    let state = harness
+        .state_matching(|s| s.messages.len() == messages.len() + 1)
+        .await;
+    let rerun = state.messages.last().unwrap().tool_call_record().unwrap();
+    assert_eq!(rerun.identifier(), "search@web");
+    assert_eq!(rerun.arguments, record.arguments);
+    assert!(rerun.result.is_none());
+    assert!(rerun.error.unwrap().contains("Tool not found"));
+
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::RerunToolCall(messages[0].id.clone()))
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_exports_structured_console_logs() {
+    let mut harness = ControllerHarness::new();
+    let conversation_id = harness.service.current_conversation_id();
+    harness
+        .controller
+        .handle_command(ChatCommand::CallTool {
+            tool: "search".into(),
+            server: "web".into(),
+            arguments: serde_json::json!({"q": "cats"}),
+        })
+        .unwrap();
+    let state = harness
+        .state_matching(|s| s.console_logs.iter().any(|l| l.request_id.is_some()))
+        .await;
+    let tool_log = state
+        .console_logs
+        .iter()
+        .find(|l| l.request_id.is_some())
+        .unwrap();
+    assert_eq!(tool_log.source, ConsoleLogSource::Mcp("web".into()));
+    assert_eq!(tool_log.kind, ConsoleLogKind::Error);
+    assert_eq!(tool_log.conversation_id, conversation_id);
+    assert!(tool_log.duration_ms.is_some());
+    assert!(tool_log.content.starts_with("search@web"));
+
+    let export_dir = tempdir().unwrap();
+    let path = export_dir.path().join("logs").join("console.jsonl");
+    harness
+        .controller
+        .handle_command(ChatCommand::ExportConsoleLog(path.clone()))
+        .unwrap();
+    let exported = std::fs::read_to_string(&path).unwrap();
+    let records: Vec<ConsoleLogRecord> = exported
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), state.console_logs.len());
+    assert!(records.contains(tool_log));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_runs_slash_commands() {
+    let mut harness = ControllerHarness::new();
+    let send = |harness: &ControllerHarness, text: &str| {
+        harness
+            .controller
+            .handle_command(ChatCommand::SendUserMessage(text.to_string()))
+    };
+
+    send(&harness, "/system Be brief.").unwrap();
+    let state = harness
+        .state_matching(|s| s.conversation_settings.system_prompt.is_some())
+        .await;
+    assert_eq!(
+        state.conversation_settings.system_prompt.as_deref(),
+        Some("Be brief.")
+    );
+
+    let before = harness.service.current_messages().len();
+    send(&harness, r#"/tool search@web {"q": "dogs"}"#).unwrap();
+    let state = harness
+        .state_matching(|s| s.messages.len() == before + 1)
+        .await;
+    let record = state.messages.last().unwrap().tool_call_record().unwrap();
+    assert_eq!(record.identifier(), "search@web");
+    assert_eq!(record.arguments, serde_json::json!({"q": "dogs"}));
+
+    let export_dir = tempdir().unwrap();
+    let path = export_dir.path().join("out").join("chat.md");
+    send(&harness, &format!("/export md {}", path.display())).unwrap();
+    let markdown = std::fs::read_to_string(&path).unwrap();
+    assert!(markdown.contains("## Tool: search@web"));
+
+    // 不明なコマンドや提供元の無いツールはエラーになり、コンソールにも残る
+    assert!(send(&harness, "/nope").is_err());
+    assert!(send(&harness, "/tool search").is_err());
+    assert!(harness
+        .controller
+        .state_snapshot()
+        .console_logs
+        .iter()
+        .any(|log| log.content.contains("Unknown command: /nope")));
+
+    send(&harness, "/clear").unwrap();
+    harness.state_matching(|s| s.messages.is_empty()).await;
+    assert!(harness.service.current_messages().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chat_controller_sessions_keep_their_own_conversation_and_model() {
+    let mut harness = ControllerHarness::new();
+    harness
+        .controller
+        .handle_command(ChatCommand::CreateConversation)
+        .unwrap();
+    let first_id = harness.controller.state_snapshot().conversation_id.unwrap();
+
+    let second = harness.controller.open_session(None).unwrap();
+    let (tx, mut second_events) = unbounded_channel();
+    let _second_subscription = second.subscribe(move |event| {
+        let _ = tx.send(event);
+    });
+    let second_state = second.state_snapshot();
+    let second_id = second_state.conversation_id.unwrap();
+    assert_ne!(first_id, second_id);
+    assert_eq!(second_state.conversation_title, "New Chat");
+    assert_eq!(harness.controller.state_snapshot().conversations.len(), 2);
+
+    // モデルと会話はセッションごと
+    second
+        .handle_command(ChatCommand::SwitchModel("qwen3:4b-instruct".to_string()))
+        .unwrap();
+    second
+        .handle_command(ChatCommand::SendUserMessage(
+            "from the second tab".to_string(),
+        ))
+        .unwrap();
+    loop {
+        let event = timeout(Duration::from_secs(1), second_events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if matches!(event, ChatEvent::StateChanged)
+            && second
+                .state_snapshot()
+                .messages
+                .iter()
+                .any(|m| m.content == "(echo) from the second tab")
+        {
+            break;
+        }
+    }
+    let first = harness.controller.state_snapshot();
+    assert_eq!(first.active_model, "phi4-mini:3.8b");
+    assert!(first.messages.iter().all(|m| m.role == MessageRole::System));
+    assert_eq!(second.state_snapshot().active_model, "qwen3:4b-instruct");
+
+    // 共有の一覧とコンソールは両方のセッションに届く
+    second.append_console_log(chat_core::ConsoleLogKind::Output, "shared log");
+    harness
+        .state_matching(|s| s.console_logs.iter().any(|l| l.content == "shared log"))
+        .await;
+
+    // 別のセッションで開いている会話は開けず、削除もできない
+    assert!(harness.controller.open_session(Some(&second_id)).is_err());
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::SwitchConversation(second_id.clone()))
+        .is_err());
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::DeleteConversation(second_id.clone()))
+        .is_err());
+
+    second.close_session().unwrap();
+    let reopened = harness.controller.open_session(Some(&second_id)).unwrap();
+    let state = reopened.state_snapshot();
+    assert_eq!(state.active_model, "qwen3:4b-instruct");
+    assert!(state
+        .messages
+        .iter()
+        .any(|m| m.content == "(echo) from the second tab"));
+}
+
+/// 通知されるまで build で止まり、その後はエラーを返すビルダー（生成中の応答の代わり）
+struct GatedBuilder(Arc<Mutex<std::sync::mpsc::Receiver<()>>>);
+
+impl PromptBuilder for GatedBuilder {
+    fn metadata(&self) -> PromptMetadata {
+        PromptMetadata {
+            name: "Gated".into(),
+            ..terse_metadata()
+        }
+    }
+
+    fn build(&self, _ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+        let _ = self.0.lock().unwrap().recv();
+        Err(PromptSpiError::Build("released".into()))
+    }
+
+    fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+        Ok(PromptParseOutput {
+            final_answer: Some(raw_output.to_string()),
+            tool_requests: Vec::new(),
+        })
+    }
+}
+
+/// `GatedBuilder` を使うハーネスと、止めた build を進める送信側
+fn gated_harness() -> (ControllerHarness, std::sync::mpsc::Sender<()>) {
+    let (release, gate) = std::sync::mpsc::channel();
+    let gate = Arc::new(Mutex::new(gate));
+    let mut registry = PromptBuilderRegistry::default();
+    registry.register_host_builder(
+        "phi4-mini:*",
+        HostPromptBuilderFactory::new(
+            GatedBuilder(gate.clone()).metadata(),
+            PromptAgentMode::DirectProvider,
+            0,
+            "test",
+            move || Box::new(GatedBuilder(gate.clone())),
+        ),
+    );
+    let harness = ControllerHarness::with_prompt_registry(Some(Arc::new(registry)));
+    (harness, release)
+}
+
+async fn wait_for_pending_turn(controller: &ChatController) {
+    timeout(Duration::from_secs(5), async {
+        while controller.has_pending_turn() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the response was never saved");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn chat_controller_keeps_the_conversation_while_a_response_is_pending() {
+    let (harness, release) = gated_harness();
+    let controller = &harness.controller;
+    let first_id = controller.state_snapshot().conversation_id.unwrap();
+    let other = controller.open_session(None).unwrap();
+    let other_id = other.state_snapshot().conversation_id.unwrap();
+    other.close_session().unwrap();
+
+    controller
+        .handle_command(ChatCommand::SendUserMessage("hold on".to_string()))
+        .unwrap();
+    assert!(controller.has_pending_turn());
+
+    // 応答は開始時の会話に保存されるため、それまで会話を入れ替えない
+    for command in [
+        ChatCommand::SwitchConversation(other_id.clone()),
+        ChatCommand::CreateConversation,
+        ChatCommand::ClearConversation,
+    ] {
+        assert!(controller.handle_command(command).is_err());
+    }
+    assert_eq!(
+        controller.state_snapshot().conversation_id.as_deref(),
+        Some(first_id.as_str())
+    );
+
+    release.send(()).unwrap();
+    wait_for_pending_turn(controller).await;
+    let messages = controller.state_snapshot().messages;
+    assert_eq!(messages.last().unwrap().role, MessageRole::Error);
+    assert!(messages.iter().all(|m| m.content != "Thinking..."));
+
+    controller
+        .handle_command(ChatCommand::SwitchConversation(other_id.clone()))
+        .unwrap();
+    let state = controller.state_snapshot();
+    assert_eq!(state.conversation_id.as_deref(), Some(other_id.as_str()));
+    assert!(state.messages.iter().all(|m| m.content != "hold on"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn chat_controller_refuses_to_edit_messages_while_a_response_is_pending() {
+    let (harness, release) = gated_harness();
+    let controller = &harness.controller;
+    controller
+        .handle_command(ChatCommand::SendUserMessage("hold on".to_string()))
+        .unwrap();
+    let messages = controller.state_snapshot().messages;
+    let question = messages[messages.len() - 2].id.clone();
+    let placeholder = messages.last().unwrap().id.clone();
+
+    // 考え中の行（応答の置き場所）を消したり巻き戻したりしない
+    for command in [
+        ChatCommand::RetryFromMessage(question.clone()),
+        ChatCommand::DeleteMessage(question.clone()),
+        ChatCommand::DeleteMessage(placeholder),
+        ChatCommand::SendUserMessage("/retry".to_string()),
+    ] {
+        assert!(controller.handle_command(command).is_err());
+    }
+
+    release.send(()).unwrap();
+    wait_for_pending_turn(controller).await;
+    let contents: Vec<String> = controller
+        .state_snapshot()
+        .messages
+        .iter()
+        .map(|m| m.content.clone())
+        .collect();
+    assert_eq!(
+        contents.iter().filter(|c| *c == "hold on").count(),
+        1,
+        "{:?}",
+        contents
+    );
+    controller
+        .handle_command(ChatCommand::DeleteMessage(question))
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn chat_controller_reopens_a_closed_session_after_its_response_is_saved() {
+    let (harness, release) = gated_harness();
+
+    let second = harness.controller.open_session(None).unwrap();
+    let second_id = second.state_snapshot().conversation_id.unwrap();
+    second
+        .handle_command(ChatCommand::SendUserMessage("still thinking".to_string()))
+        .unwrap();
+    second.close_session().unwrap();
+
+    // 応答を保存し終えるまでは開き直せず、削除もできない
+    let err = harness
+        .controller
+        .open_session(Some(&second_id))
+        .err()
+        .unwrap();
+    assert!(err.message().contains("still saving"), "{}", err.message());
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::SwitchConversation(second_id.clone()))
+        .is_err());
+    assert!(harness
+        .controller
+        .handle_command(ChatCommand::DeleteConversation(second_id.clone()))
+        .is_err());
+
+    release.send(()).unwrap();
+    let reopened = timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(session) = harness.controller.open_session(Some(&second_id)) {
+                return session;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("closed session never finished its response");
+
+    let messages = reopened.state_snapshot().messages;
+    let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(
+        contents.iter().filter(|c| **c == "still thinking").count(),
+        1,
+        "{:?}",
+        contents
+    );
+    assert_eq!(messages.last().unwrap().role, MessageRole::Error);
+    assert!(!contents.contains(&"Thinking..."));
+}
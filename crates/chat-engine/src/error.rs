@@ -1,3 +1,4 @@
+use chat_history::HistoryError;
 use model_provider::ProviderError;
 use thiserror::Error;
 
@@ -20,4 +21,7 @@ pub enum ChatError {
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    #[error("History error: {0}")]
+    History(#[from] HistoryError),
 }
@@ -2,61 +2,14 @@
 //!
 //! ModelProvider と ModelAdapter を使用して会話を管理します。
 
-use chrono::{DateTime, Utc};
+use chat_history::{Conversation, ConversationManager, HistoryError};
 use model_adapter::{ModelAdapter, ModelProvider, ToolSpec};
-use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use uuid::Uuid;
 
 pub mod error;
-pub mod session;
 
+pub use chat_history::{Message, MessageRole as Role};
 pub use error::ChatError;
-pub use session::{ChatSession, SessionInfo};
-
-/// メッセージのロール
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum Role {
-    System,
-    User,
-    Assistant,
-}
-
-/// 単一のメッセージ
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Message {
-    pub id: String,
-    pub role: Role,
-    pub content: String,
-    pub timestamp: DateTime<Utc>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<serde_json::Value>,
-}
-
-impl Message {
-    pub fn new(role: Role, content: String) -> Self {
-        Self {
-            id: Uuid::new_v4().to_string(),
-            role,
-            content,
-            timestamp: Utc::now(),
-            metadata: None,
-        }
-    }
-
-    pub fn user(content: String) -> Self {
-        Self::new(Role::User, content)
-    }
-
-    pub fn assistant(content: String) -> Self {
-        Self::new(Role::Assistant, content)
-    }
-
-    pub fn system(content: String) -> Self {
-        Self::new(Role::System, content)
-    }
-}
 
 /// チャットエンジン
 pub struct ChatEngine {
@@ -104,37 +57,47 @@ impl ChatEngine {
         self.history.clear();
     }
 
-    /// セッションを保存
+    /// 履歴を会話として保存（ConversationService と同じ保存先・形式）
+    ///
+    /// `conversation_id` が既存の会話を指す場合はメッセージを置き換え、
+    /// タイトルやフォルダなどの属性は保持する。会話が存在しない場合だけ新しく作り、
+    /// 読み込みに失敗した場合（復号や解析のエラーなど）は上書きせずにエラーを返す。
     pub fn save_session(
         &self,
-        session_dir: &std::path::Path,
-        session_id: Option<&str>,
+        manager: &ConversationManager,
+        conversation_id: Option<&str>,
     ) -> Result<String, ChatError> {
-        let mut session = ChatSession::new();
-
-        if let Some(id) = session_id {
-            session.id = id.to_string();
-        }
-
-        // メッセージをセッションにコピー
+        let mut conversation = match conversation_id {
+            Some(id) => match manager.load(id) {
+                Ok(conversation) => conversation,
+                Err(HistoryError::NotFound(_)) => {
+                    let mut conversation = Conversation::new("New Chat");
+                    conversation.id = id.to_string();
+                    conversation
+                }
+                Err(err) => return Err(err.into()),
+            },
+            None => Conversation::new("New Chat"),
+        };
+
+        conversation.messages.clear();
         for msg in &self.history {
-            session.add_message(msg.clone());
+            conversation.add_message(msg.clone());
         }
 
-        // ファイルパスを生成
-        let file_name = format!("{}.json", session.id);
-        let file_path = session_dir.join(file_name);
-
-        // 保存
-        session.save_to_file(&file_path)?;
+        manager.save(&conversation)?;
 
-        Ok(session.id)
+        Ok(conversation.id)
     }
 
-    /// セッションから履歴を読み込み
-    pub fn load_session(&mut self, session_path: &std::path::Path) -> Result<(), ChatError> {
-        let session = ChatSession::load_from_file(session_path)?;
-        self.history = session.messages;
+    /// 保存済みの会話から履歴を読み込み
+    pub fn load_session(
+        &mut self,
+        manager: &ConversationManager,
+        conversation_id: &str,
+    ) -> Result<(), ChatError> {
+        let conversation = manager.load(conversation_id)?;
+        self.history = conversation.messages;
         Ok(())
     }
 
@@ -260,4 +223,43 @@ mod tests {
         // 履歴が max_history 以下に保たれている
         assert!(engine.get_history().len() <= 4);
     }
+
+    #[tokio::test]
+    async fn test_session_roundtrip_uses_conversation_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path()).unwrap();
+        let provider = Arc::new(MockProvider);
+        let adapter = Arc::new(Phi4MiniAdapter::new());
+        let mut engine = ChatEngine::new(provider.clone(), adapter.clone(), "test".to_string());
+        engine.send_message("Hello").await.unwrap();
+
+        let id = engine.save_session(&manager, None).unwrap();
+        let stored = manager.load(&id).unwrap();
+        assert_eq!(stored.messages.len(), 2);
+
+        let mut restored = ChatEngine::new(provider, adapter, "test".to_string());
+        restored.load_session(&manager, &id).unwrap();
+        assert_eq!(restored.get_history()[0].role, Role::User);
+        assert_eq!(restored.get_history()[0].content, "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_save_session_keeps_unreadable_conversation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path()).unwrap();
+        let provider = Arc::new(MockProvider);
+        let adapter = Arc::new(Phi4MiniAdapter::new());
+        let mut engine = ChatEngine::new(provider, adapter, "test".to_string());
+        engine.send_message("Hello").await.unwrap();
+
+        // 存在しない ID なら新しい会話として保存する
+        let id = engine.save_session(&manager, Some("fresh")).unwrap();
+        assert_eq!(id, "fresh");
+
+        // 読めない会話は空の会話で上書きしない
+        let path = temp_dir.path().join("broken.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(engine.save_session(&manager, Some("broken")).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ not json");
+    }
 }
@@ -1,17 +1,37 @@
 //! 会話型定義
 
-use crate::message::Message;
+use crate::message::{Message, MessageRole};
+use crate::schema::CURRENT_SCHEMA_VERSION;
+use crate::settings::ConversationSettings;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// 会話
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
+    /// 保存形式のバージョン（旧形式のファイルは 0）
+    #[serde(default)]
+    pub schema_version: u32,
     pub id: String,
     pub title: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub messages: Vec<Message>,
+    /// 所属フォルダ（プロジェクト）。未分類なら None
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// 自由入力のタグ
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 一覧の先頭に固定するか
+    #[serde(default)]
+    pub pinned: bool,
+    /// アーカイブ済み（既定の一覧では非表示）
+    #[serde(default)]
+    pub archived: bool,
+    /// 会話ごとのモデル・プロンプト・ツール・生成オプション
+    #[serde(default)]
+    pub settings: ConversationSettings,
 }
 
 impl Conversation {
@@ -19,11 +39,17 @@ impl Conversation {
     pub fn new(title: impl Into<String>) -> Self {
         let now = Utc::now();
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             id: uuid::Uuid::new_v4().to_string(),
             title: title.into(),
             created_at: now,
             updated_at: now,
             messages: Vec::new(),
+            folder: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            settings: ConversationSettings::default(),
         }
     }
 
@@ -38,6 +64,99 @@ impl Conversation {
         self.messages.last()
     }
 
+    /// メッセージを削除。削除した場合は true
+    pub fn remove_message(&mut self, message_id: &str) -> bool {
+        let before = self.messages.len();
+        self.messages.retain(|message| message.id != message_id);
+        let removed = self.messages.len() != before;
+        if removed {
+            self.updated_at = Utc::now();
+        }
+        removed
+    }
+
+    /// メッセージのピン留めを切り替える。見つかれば true
+    pub fn set_message_pinned(&mut self, message_id: &str, pinned: bool) -> bool {
+        match self.messages.iter_mut().find(|m| m.id == message_id) {
+            Some(message) => {
+                message.set_pinned(pinned);
+                self.updated_at = Utc::now();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 指定メッセージから再生成できるよう、それ以前で最後のユーザーメッセージより後ろを削除する。
+    /// 応答し直すユーザーメッセージの本文を返す（見つからなければ何もしない）
+    pub fn rewind_to_user_message(&mut self, message_id: &str) -> Option<String> {
+        let index = self.messages.iter().position(|m| m.id == message_id)?;
+        let user_index = self.messages[..=index]
+            .iter()
+            .rposition(|m| m.role == MessageRole::User)?;
+        self.messages.truncate(user_index + 1);
+        self.updated_at = Utc::now();
+        Some(self.messages[user_index].content.clone())
+    }
+
+    /// メッセージをすべて削除し、削除した件数を返す
+    pub fn clear_messages(&mut self) -> usize {
+        let count = self.messages.len();
+        if count > 0 {
+            self.messages.clear();
+            self.updated_at = Utc::now();
+        }
+        count
+    }
+
+    /// Markdown に書き出す（タイトルと、役割ごとの見出しを付けたメッセージ）
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        for message in &self.messages {
+            let heading = match message.role {
+                MessageRole::User => "User".to_string(),
+                MessageRole::Assistant => "Assistant".to_string(),
+                MessageRole::System => "System".to_string(),
+                MessageRole::Error => "Error".to_string(),
+                MessageRole::Tool => match message.tool_call_record() {
+                    Some(record) => format!("Tool: {}", record.identifier()),
+                    None => "Tool".to_string(),
+                },
+            };
+            markdown.push_str(&format!(
+                "\n## {} ({})\n\n{}\n",
+                heading,
+                message.timestamp.format("%Y-%m-%d %H:%M"),
+                message.content.trim_end()
+            ));
+        }
+        markdown
+    }
+
+    /// フォルダを設定（空文字は未分類扱い）
+    pub fn set_folder(&mut self, folder: Option<String>) {
+        self.folder = folder
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+    }
+
+    /// タグを追加（重複・空文字は無視）。追加した場合は true
+    pub fn add_tag(&mut self, tag: impl AsRef<str>) -> bool {
+        let tag = tag.as_ref().trim();
+        if tag.is_empty() || self.tags.iter().any(|existing| existing == tag) {
+            return false;
+        }
+        self.tags.push(tag.to_string());
+        true
+    }
+
+    /// タグを削除。削除した場合は true
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|existing| existing != tag.trim());
+        self.tags.len() != before
+    }
+
     /// メタデータに変換（一覧表示用）
     pub fn to_metadata(&self) -> ConversationMetadata {
         ConversationMetadata {
@@ -46,6 +165,10 @@ impl Conversation {
             created_at: self.created_at,
             updated_at: self.updated_at,
             message_count: self.messages.len(),
+            folder: self.folder.clone(),
+            tags: self.tags.clone(),
+            pinned: self.pinned,
+            archived: self.archived,
         }
     }
 }
@@ -58,4 +181,82 @@ pub struct ConversationMetadata {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub message_count: usize,
+    #[serde(default)]
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Conversation {
+        let mut conv = Conversation::new("test");
+        conv.add_message(Message::system("welcome"));
+        conv.add_message(Message::user("first"));
+        conv.add_message(Message::assistant("answer 1"));
+        conv.add_message(Message::user("second"));
+        conv.add_message(Message::assistant("answer 2"));
+        conv
+    }
+
+    fn contents(conv: &Conversation) -> Vec<&str> {
+        conv.messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[test]
+    fn rewinds_to_the_preceding_user_message() {
+        let mut conv = conversation();
+        let answer = conv.messages[2].id.clone();
+        assert_eq!(
+            conv.rewind_to_user_message(&answer).as_deref(),
+            Some("first")
+        );
+        assert_eq!(contents(&conv), ["welcome", "first"]);
+
+        // ユーザーメッセージ自身を指定したらそれ以降を消して応答し直す
+        let mut conv = conversation();
+        let second = conv.messages[3].id.clone();
+        assert_eq!(
+            conv.rewind_to_user_message(&second).as_deref(),
+            Some("second")
+        );
+        assert_eq!(contents(&conv), ["welcome", "first", "answer 1", "second"]);
+
+        let welcome = conv.messages[0].id.clone();
+        assert_eq!(conv.rewind_to_user_message(&welcome), None);
+        assert_eq!(conv.messages.len(), 4);
+    }
+
+    #[test]
+    fn exports_messages_as_markdown() {
+        let mut conv = conversation();
+        conv.messages.truncate(3);
+        let markdown = conv.to_markdown();
+        assert!(markdown.starts_with("# test\n\n## System ("));
+        assert!(markdown.contains(")\n\nfirst\n\n## Assistant ("));
+        assert!(markdown.ends_with("answer 1\n"));
+
+        assert_eq!(conv.clear_messages(), 3);
+        assert_eq!(conv.to_markdown(), "# test\n");
+    }
+
+    #[test]
+    fn removes_and_pins_messages() {
+        let mut conv = conversation();
+        let id = conv.messages[2].id.clone();
+        assert!(conv.set_message_pinned(&id, true));
+        assert!(conv.messages[2].is_pinned());
+        assert!(conv.set_message_pinned(&id, false));
+        assert_eq!(conv.messages[2].metadata, None);
+
+        assert!(conv.remove_message(&id));
+        assert!(!conv.remove_message(&id));
+        assert_eq!(contents(&conv), ["welcome", "first", "second", "answer 2"]);
+    }
 }
@@ -0,0 +1,163 @@
+//! 会話一覧のフィルタと並び順
+
+use crate::ConversationMetadata;
+use std::collections::BTreeSet;
+
+/// 会話一覧の絞り込み条件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversationFilter {
+    /// 指定フォルダの会話のみ表示
+    pub folder: Option<String>,
+    /// 指定タグを持つ会話のみ表示
+    pub tag: Option<String>,
+    /// タイトルの部分一致（大文字小文字を区別しない）
+    pub query: Option<String>,
+    /// true ならアーカイブ済みのみ、false ならアーカイブ済みを除外
+    pub archived: bool,
+}
+
+impl ConversationFilter {
+    /// 条件に一致するか
+    pub fn matches(&self, meta: &ConversationMetadata) -> bool {
+        if meta.archived != self.archived {
+            return false;
+        }
+
+        if let Some(folder) = &self.folder {
+            if meta.folder.as_deref() != Some(folder.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(tag) = &self.tag {
+            if !meta.tags.iter().any(|t| t == tag) {
+                return false;
+            }
+        }
+
+        if let Some(query) = self.query.as_deref().map(str::trim) {
+            if !query.is_empty() && !meta.title.to_lowercase().contains(&query.to_lowercase()) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// 一覧に条件を適用
+    pub fn apply(&self, list: &[ConversationMetadata]) -> Vec<ConversationMetadata> {
+        list.iter()
+            .filter(|meta| self.matches(meta))
+            .cloned()
+            .collect()
+    }
+}
+
+/// ピン留めを先頭に、その後は更新日時の新しい順に並べる
+pub fn sort_conversations(list: &mut [ConversationMetadata]) {
+    list.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| b.updated_at.cmp(&a.updated_at))
+    });
+}
+
+/// 一覧に現れるフォルダ名（重複なし・昇順）
+pub fn collect_folders(list: &[ConversationMetadata]) -> Vec<String> {
+    list.iter()
+        .filter_map(|meta| meta.folder.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// 一覧に現れるタグ（重複なし・昇順）
+pub fn collect_tags(list: &[ConversationMetadata]) -> Vec<String> {
+    list.iter()
+        .flat_map(|meta| meta.tags.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Conversation;
+
+    fn meta(
+        title: &str,
+        folder: Option<&str>,
+        tags: &[&str],
+        archived: bool,
+    ) -> ConversationMetadata {
+        let mut conversation = Conversation::new(title);
+        conversation.set_folder(folder.map(str::to_string));
+        for tag in tags {
+            conversation.add_tag(tag);
+        }
+        conversation.archived = archived;
+        conversation.to_metadata()
+    }
+
+    #[test]
+    fn test_default_filter_hides_archived() {
+        let list = vec![
+            meta("active", None, &[], false),
+            meta("old", None, &[], true),
+        ];
+
+        let visible = ConversationFilter::default().apply(&list);
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].title, "active");
+
+        let archive = ConversationFilter {
+            archived: true,
+            ..Default::default()
+        }
+        .apply(&list);
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive[0].title, "old");
+    }
+
+    #[test]
+    fn test_folder_tag_and_query() {
+        let list = vec![
+            meta("Rust build", Some("neko"), &["rust"], false),
+            meta("Shopping", Some("home"), &["todo"], false),
+            meta("Rust docs", None, &["rust", "docs"], false),
+        ];
+
+        let filter = ConversationFilter {
+            tag: Some("rust".into()),
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(&list).len(), 2);
+
+        let filter = ConversationFilter {
+            folder: Some("neko".into()),
+            tag: Some("rust".into()),
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(&list).len(), 1);
+
+        let filter = ConversationFilter {
+            query: Some("DOCS".into()),
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(&list)[0].title, "Rust docs");
+
+        assert_eq!(collect_folders(&list), vec!["home", "neko"]);
+        assert_eq!(collect_tags(&list), vec!["docs", "rust", "todo"]);
+    }
+
+    #[test]
+    fn test_sort_puts_pinned_first() {
+        let mut pinned = meta("pinned", None, &[], false);
+        pinned.pinned = true;
+        let mut list = vec![pinned, meta("newer", None, &[], false)];
+
+        sort_conversations(&mut list);
+        assert_eq!(list[0].title, "pinned");
+    }
+}
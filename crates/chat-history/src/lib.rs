@@ -3,12 +3,24 @@
 //! 会話のメッセージを永続化・読み込みする機能を提供します。
 
 mod conversation;
+mod filter;
 mod manager;
 mod message;
+mod schema;
+mod settings;
+mod storage;
+mod tool_call;
 
 pub use conversation::{Conversation, ConversationMetadata};
-pub use manager::ConversationManager;
+pub use filter::{collect_folders, collect_tags, sort_conversations, ConversationFilter};
+pub use manager::{
+    BackupInfo, ConversationManager, ImportReport, QuarantinedFile, SkippedConversation,
+    DEFAULT_MAX_BACKUPS,
+};
 pub use message::{Message, MessageRole};
+pub use schema::{parse_conversation, CURRENT_SCHEMA_VERSION};
+pub use settings::{tool_key, ConversationSettings, GenerationOptions};
+pub use tool_call::ToolCallRecord;
 
 #[derive(Debug, thiserror::Error)]
 pub enum HistoryError {
@@ -26,6 +38,13 @@ pub enum HistoryError {
 
     #[error("Invalid conversation data: {0}")]
     InvalidData(String),
+
+    #[error("Unsupported schema version: {0}")]
+    UnsupportedSchema(u32),
+
+    /// 鍵が無い・違うなど。ファイル自体は壊れていないので隔離しない
+    #[error("Encryption error: {0}")]
+    Encryption(String),
 }
 
 pub type Result<T> = std::result::Result<T, HistoryError>;
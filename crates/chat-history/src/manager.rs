@@ -1,12 +1,55 @@
 //! 会話管理マネージャー
 
+use crate::filter::{sort_conversations, ConversationFilter};
+use crate::schema::parse_conversation;
+use crate::storage::{atomic_write, parse_stamped_name, unique_stamped_path};
 use crate::{Conversation, ConversationMetadata, HistoryError};
+use chrono::{DateTime, Utc};
+use secret_store::EncryptionKey;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// 旧セッション取り込み結果
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// 既定で保持するバックアップ世代数
+pub const DEFAULT_MAX_BACKUPS: usize = 5;
+
+const BACKUP_DIR: &str = "backups";
+const QUARANTINE_DIR: &str = "quarantine";
+
+/// 会話のバックアップ 1 世代分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub conversation_id: String,
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 読み込めずに隔離された会話ファイル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedFile {
+    pub path: PathBuf,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// 読み込めずに一覧から外した会話ファイル（鍵違い・I/O エラーなど。隔離はしない）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedConversation {
+    pub path: PathBuf,
+    pub error: String,
+}
+
 /// 会話マネージャー
 pub struct ConversationManager {
     storage_dir: PathBuf,
+    max_backups: usize,
+    encryption: Option<EncryptionKey>,
 }
 
 impl ConversationManager {
@@ -22,7 +65,30 @@ impl ConversationManager {
             fs::create_dir_all(&storage_dir).map_err(HistoryError::Io)?;
         }
 
-        Ok(Self { storage_dir })
+        Ok(Self {
+            storage_dir,
+            max_backups: DEFAULT_MAX_BACKUPS,
+            encryption: None,
+        })
+    }
+
+    /// 保持するバックアップ世代数を設定（0 でバックアップ無効）
+    pub fn with_max_backups(mut self, max_backups: usize) -> Self {
+        self.max_backups = max_backups;
+        self
+    }
+
+    /// 保存時に使う暗号鍵を設定（None で平文保存）
+    ///
+    /// 平文で保存済みのファイルは鍵を設定しても読み込める。
+    pub fn with_encryption(mut self, key: Option<EncryptionKey>) -> Self {
+        self.encryption = key;
+        self
+    }
+
+    /// 保存時に暗号化するかどうか
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
     }
 
     /// デフォルトの保存ディレクトリを取得
@@ -38,14 +104,18 @@ impl ConversationManager {
     }
 
     /// 会話を保存
+    ///
+    /// 既存ファイルはバックアップしてから、一時ファイル経由で置き換える。
     pub fn save(&self, conversation: &Conversation) -> Result<(), HistoryError> {
         let path = self.conversation_path(&conversation.id);
         let json = serde_json::to_string_pretty(conversation)
             .map_err(|e| HistoryError::Serialization(e.to_string()))?;
 
-        fs::write(path, json).map_err(HistoryError::Io)?;
+        if path.exists() {
+            self.backup_file(&conversation.id, &path)?;
+        }
 
-        Ok(())
+        atomic_write(&path, &self.encode(&json)?)
     }
 
     /// 会話を読み込み
@@ -56,12 +126,7 @@ impl ConversationManager {
             return Err(HistoryError::NotFound(id.to_string()));
         }
 
-        let json = fs::read_to_string(path).map_err(HistoryError::Io)?;
-
-        let conversation = serde_json::from_str(&json)
-            .map_err(|e| HistoryError::Deserialization(e.to_string()))?;
-
-        Ok(conversation)
+        parse_conversation(&self.read_json(&path)?)
     }
 
     /// 会話を削除
@@ -77,9 +142,19 @@ impl ConversationManager {
         Ok(())
     }
 
-    /// すべての会話のメタデータを取得
+    /// すべての会話のメタデータを取得（読めなかった会話は [`Self::list_metadata_with_skipped`] で確認する）
     pub fn list_metadata(&self) -> Result<Vec<ConversationMetadata>, HistoryError> {
+        Ok(self.list_metadata_with_skipped()?.0)
+    }
+
+    /// 会話のメタデータ一覧と、読み込めずに一覧から外した会話
+    ///
+    /// 壊れたファイルは隔離し、それ以外の理由で読めない会話は隔離せずに `skipped` として返す。
+    pub fn list_metadata_with_skipped(
+        &self,
+    ) -> Result<(Vec<ConversationMetadata>, Vec<SkippedConversation>), HistoryError> {
         let mut metadata_list = Vec::new();
+        let mut skipped = Vec::new();
 
         let entries = fs::read_dir(&self.storage_dir).map_err(HistoryError::Io)?;
 
@@ -93,29 +168,314 @@ impl ConversationManager {
             }
 
             // ファイル名から会話IDを取得
-            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
-                if let Ok(conversation) = self.load(id) {
-                    metadata_list.push(conversation.to_metadata());
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match self.load(id) {
+                Ok(conversation) => metadata_list.push(conversation.to_metadata()),
+                // 壊れたファイルは黙って無視せず隔離する
+                Err(HistoryError::Deserialization(_)) | Err(HistoryError::InvalidData(_)) => {
+                    self.quarantine(&path)?;
                 }
+                Err(err) => skipped.push(SkippedConversation {
+                    path: path.clone(),
+                    error: err.to_string(),
+                }),
             }
         }
 
-        // 更新日時でソート（新しい順）
-        metadata_list.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        // ピン留めを先頭に、更新日時でソート（新しい順）
+        sort_conversations(&mut metadata_list);
 
-        Ok(metadata_list)
+        Ok((metadata_list, skipped))
+    }
+
+    /// 条件に一致する会話のメタデータを取得
+    pub fn list_filtered(
+        &self,
+        filter: &ConversationFilter,
+    ) -> Result<Vec<ConversationMetadata>, HistoryError> {
+        Ok(filter.apply(&self.list_metadata()?))
+    }
+
+    /// 指定会話のバックアップ一覧（新しい順）
+    pub fn list_backups(&self, id: &str) -> Result<Vec<BackupInfo>, HistoryError> {
+        let dir = self.backup_dir(id);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if let Some((created_at, seq)) = parse_stamped_name(name) {
+                backups.push((
+                    seq,
+                    BackupInfo {
+                        conversation_id: id.to_string(),
+                        path,
+                        created_at,
+                    },
+                ));
+            }
+        }
+
+        // 同じミリ秒内の保存は連番で順序を決める
+        backups.sort_by(|(a_seq, a), (b_seq, b)| (b.created_at, b_seq).cmp(&(a.created_at, a_seq)));
+        let backups = backups.into_iter().map(|(_, backup)| backup).collect();
+        Ok(backups)
+    }
+
+    /// バックアップから会話を復元（現在の内容もバックアップされる）
+    ///
+    /// `backup` が None の場合は最新のバックアップを使う。
+    pub fn restore_backup(
+        &self,
+        id: &str,
+        backup: Option<&Path>,
+    ) -> Result<Conversation, HistoryError> {
+        let backups = self.list_backups(id)?;
+        let chosen = match backup {
+            Some(path) => backups.iter().find(|b| b.path == path),
+            None => backups.first(),
+        }
+        .ok_or_else(|| HistoryError::NotFound(format!("backup for {}", id)))?;
+
+        let conversation = parse_conversation(&self.read_json(&chosen.path)?)?;
+        if conversation.id != id {
+            return Err(HistoryError::InvalidData(format!(
+                "backup {} belongs to {}",
+                chosen.path.display(),
+                conversation.id
+            )));
+        }
+
+        self.save(&conversation)?;
+        Ok(conversation)
+    }
+
+    /// 隔離された会話ファイルの一覧（新しい順）
+    pub fn list_quarantined(&self) -> Result<Vec<QuarantinedFile>, HistoryError> {
+        let dir = self.storage_dir.join(QUARANTINE_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let stamp = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .and_then(parse_stamped_name);
+            if let Some((quarantined_at, seq)) = stamp {
+                files.push((
+                    seq,
+                    QuarantinedFile {
+                        path,
+                        quarantined_at,
+                    },
+                ));
+            }
+        }
+
+        files.sort_by(|(a_seq, a), (b_seq, b)| {
+            (b.quarantined_at, b_seq).cmp(&(a.quarantined_at, a_seq))
+        });
+        let files = files.into_iter().map(|(_, file)| file).collect();
+        Ok(files)
+    }
+
+    /// 旧形式のセッションファイル（`chat_engine::ChatSession`）を取り込む
+    ///
+    /// 同じ ID の会話が既にあればスキップするため、何度呼んでも安全。
+    /// 元ファイルは削除しない。
+    pub fn import_legacy_sessions(
+        &self,
+        session_dir: impl AsRef<Path>,
+    ) -> Result<ImportReport, HistoryError> {
+        let session_dir = session_dir.as_ref();
+        let mut report = ImportReport::default();
+
+        if !session_dir.is_dir() || session_dir == self.storage_dir {
+            return Ok(report);
+        }
+
+        for entry in fs::read_dir(session_dir).map_err(HistoryError::Io)? {
+            let path = entry.map_err(HistoryError::Io)?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+
+            let parsed = fs::read_to_string(&path)
+                .map_err(HistoryError::Io)
+                .and_then(|json| parse_conversation(&json));
+            let conversation = match parsed {
+                Ok(conversation) => conversation,
+                Err(err) => {
+                    report.failed.push((path, err.to_string()));
+                    continue;
+                }
+            };
+
+            if self.conversation_path(&conversation.id).exists() {
+                report.skipped += 1;
+                continue;
+            }
+
+            self.save(&conversation)?;
+            report.imported += 1;
+        }
+
+        Ok(report)
+    }
+
+    /// すべての会話とバックアップを新しい鍵で書き直す（None で平文に戻す）
+    ///
+    /// 先に全ファイルを復号し、1 つでも失敗したら何も書き換えない。
+    /// 新しい鍵で暗号化した写し（`*.json.rekey`）をすべて書き出してから元のファイルと入れ替える。
+    /// 現在の鍵で読めないファイルは新しい鍵でも試すので、途中で止まった書き直しは
+    /// 同じ鍵でもう一度呼べば再開でき、現在の鍵と新しい鍵を入れ替えて呼べば元に戻せる。
+    /// 戻り値は書き直したファイル数。
+    pub fn reencrypt_all(&mut self, new_key: Option<EncryptionKey>) -> Result<usize, HistoryError> {
+        let mut files = json_files(&self.storage_dir)?;
+        let backup_root = self.storage_dir.join(BACKUP_DIR);
+        if backup_root.is_dir() {
+            for entry in fs::read_dir(&backup_root)? {
+                let dir = entry?.path();
+                if dir.is_dir() {
+                    files.extend(json_files(&dir)?);
+                }
+            }
+        }
+
+        let decoded = files
+            .into_iter()
+            .map(|path| {
+                self.read_json_or(&path, new_key.as_ref())
+                    .map(|json| (path, json))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (path, json) in &decoded {
+            atomic_write(&rekey_path(path), &encode(new_key.as_ref(), json)?)?;
+        }
+        for (path, _) in &decoded {
+            fs::rename(rekey_path(path), path)?;
+        }
+
+        self.encryption = new_key;
+        Ok(decoded.len())
+    }
+
+    /// ファイルを読み込み、暗号化されていれば復号して JSON 文字列を返す
+    fn read_json(&self, path: &Path) -> Result<String, HistoryError> {
+        let bytes = fs::read(path)?;
+        if !secret_store::is_encrypted(&bytes) {
+            return String::from_utf8(bytes)
+                .map_err(|e| HistoryError::Deserialization(e.to_string()));
+        }
+
+        let key = self.encryption.as_ref().ok_or_else(|| {
+            HistoryError::Encryption(format!("{} is encrypted but no key is set", path.display()))
+        })?;
+        decrypt(key, path, &bytes)
+    }
+
+    /// 現在の鍵で復号できなければ `fallback` の鍵でも試す
+    fn read_json_or(
+        &self,
+        path: &Path,
+        fallback: Option<&EncryptionKey>,
+    ) -> Result<String, HistoryError> {
+        match (self.read_json(path), fallback) {
+            (Err(HistoryError::Encryption(_)), Some(key)) => decrypt(key, path, &fs::read(path)?),
+            (result, _) => result,
+        }
+    }
+
+    /// 保存用のバイト列に変換（鍵があれば暗号化）
+    fn encode(&self, json: &str) -> Result<Vec<u8>, HistoryError> {
+        encode(self.encryption.as_ref(), json)
     }
 
     /// 会話のファイルパスを取得
     fn conversation_path(&self, id: &str) -> PathBuf {
         self.storage_dir.join(format!("{}.json", id))
     }
+
+    fn backup_dir(&self, id: &str) -> PathBuf {
+        self.storage_dir.join(BACKUP_DIR).join(id)
+    }
+
+    /// 既存ファイルをバックアップし、古い世代を削除
+    fn backup_file(&self, id: &str, path: &Path) -> Result<(), HistoryError> {
+        if self.max_backups == 0 {
+            return Ok(());
+        }
+
+        let dir = self.backup_dir(id);
+        fs::create_dir_all(&dir)?;
+        fs::copy(path, unique_stamped_path(&dir, ".json"))?;
+
+        for stale in self.list_backups(id)?.into_iter().skip(self.max_backups) {
+            fs::remove_file(stale.path)?;
+        }
+        Ok(())
+    }
+
+    /// 読み込めないファイルを隔離ディレクトリへ移動
+    fn quarantine(&self, path: &Path) -> Result<(), HistoryError> {
+        let dir = self.storage_dir.join(QUARANTINE_DIR);
+        fs::create_dir_all(&dir)?;
+        let file_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("conversation.json");
+        fs::rename(path, unique_stamped_path(&dir, &format!("_{}", file_name)))?;
+        Ok(())
+    }
+}
+
+/// ディレクトリ直下の JSON ファイル
+fn encode(key: Option<&EncryptionKey>, json: &str) -> Result<Vec<u8>, HistoryError> {
+    match key {
+        Some(key) => secret_store::encrypt(key, json.as_bytes())
+            .map_err(|e| HistoryError::Encryption(e.to_string())),
+        None => Ok(json.as_bytes().to_vec()),
+    }
+}
+
+fn decrypt(key: &EncryptionKey, path: &Path, bytes: &[u8]) -> Result<String, HistoryError> {
+    let plaintext = secret_store::decrypt(key, bytes)
+        .map_err(|e| HistoryError::Encryption(format!("{}: {}", path.display(), e)))?;
+    String::from_utf8(plaintext).map_err(|e| HistoryError::Deserialization(e.to_string()))
+}
+
+/// 鍵の書き直し中に置く写しのパス
+fn rekey_path(path: &Path) -> PathBuf {
+    path.with_extension("json.rekey")
+}
+
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>, HistoryError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
+            files.push(path);
+        }
+    }
+    Ok(files)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::message::{Message, MessageRole};
+    use crate::ConversationSettings;
     use tempfile::tempdir;
 
     #[test]
@@ -166,4 +526,247 @@ mod tests {
         let metadata_list = manager.list_metadata().unwrap();
         assert_eq!(metadata_list.len(), 3);
     }
+
+    #[test]
+    fn test_organisation_fields_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path()).unwrap();
+
+        let mut conversation = Conversation::new("Organised");
+        conversation.set_folder(Some("neko".to_string()));
+        conversation.add_tag("rust");
+        conversation.pinned = true;
+        manager.save(&conversation).unwrap();
+
+        let mut archived = Conversation::new("Archived");
+        archived.archived = true;
+        manager.save(&archived).unwrap();
+
+        let loaded = manager.load(&conversation.id).unwrap();
+        assert_eq!(loaded.folder.as_deref(), Some("neko"));
+        assert_eq!(loaded.tags, vec!["rust"]);
+        assert!(loaded.pinned);
+
+        let visible = manager
+            .list_filtered(&ConversationFilter::default())
+            .unwrap();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].id, conversation.id);
+    }
+
+    #[test]
+    fn test_load_legacy_file_without_organisation_fields() {
+        let temp_dir = tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path()).unwrap();
+
+        let legacy = r#"{
+            "id": "legacy",
+            "title": "Old",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "messages": []
+        }"#;
+        fs::write(temp_dir.path().join("legacy.json"), legacy).unwrap();
+
+        let loaded = manager.load("legacy").unwrap();
+        assert!(loaded.folder.is_none());
+        assert!(loaded.tags.is_empty());
+        assert!(!loaded.pinned && !loaded.archived);
+        assert_eq!(loaded.settings, ConversationSettings::default());
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path()).unwrap();
+
+        let mut conversation = Conversation::new("Configured");
+        conversation.settings.model = Some("llama3.2".to_string());
+        conversation
+            .settings
+            .set_system_prompt(Some("Answer in English.".to_string()));
+        conversation.settings.enabled_tools = Some(vec!["forecast@weather".to_string()]);
+        conversation.settings.generation.temperature = Some(0.2);
+        manager.save(&conversation).unwrap();
+
+        let loaded = manager.load(&conversation.id).unwrap();
+        assert_eq!(loaded.settings, conversation.settings);
+    }
+
+    #[test]
+    fn test_save_rotates_backups_and_restores() {
+        let temp_dir = tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path())
+            .unwrap()
+            .with_max_backups(2);
+
+        let mut conversation = Conversation::new("Versions");
+        manager.save(&conversation).unwrap();
+        for i in 0..3 {
+            conversation.add_message(Message::new(MessageRole::User, format!("v{}", i)));
+            manager.save(&conversation).unwrap();
+        }
+
+        let backups = manager.list_backups(&conversation.id).unwrap();
+        assert_eq!(backups.len(), 2);
+
+        // 最新のバックアップは 1 つ前の版（メッセージ 2 件）
+        let restored = manager.restore_backup(&conversation.id, None).unwrap();
+        assert_eq!(restored.messages.len(), 2);
+        assert_eq!(manager.load(&conversation.id).unwrap().messages.len(), 2);
+
+        // 一時ファイルは残らない
+        let leftovers = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_list_metadata_quarantines_corrupted_files() {
+        let temp_dir = tempdir().unwrap();
+        let manager = ConversationManager::new(temp_dir.path()).unwrap();
+
+        manager.save(&Conversation::new("Healthy")).unwrap();
+        fs::write(temp_dir.path().join("broken.json"), "{\"id\": ").unwrap();
+
+        let metadata_list = manager.list_metadata().unwrap();
+        assert_eq!(metadata_list.len(), 1);
+        assert!(!temp_dir.path().join("broken.json").exists());
+
+        let quarantined = manager.list_quarantined().unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0]
+            .path
+            .to_string_lossy()
+            .ends_with("_broken.json"));
+    }
+
+    #[test]
+    fn test_import_legacy_sessions_is_idempotent() {
+        let storage = tempdir().unwrap();
+        let sessions = tempdir().unwrap();
+        let manager = ConversationManager::new(storage.path()).unwrap();
+
+        let legacy = r#"{
+            "id": "legacy-session",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "title": "From engine",
+            "messages": [
+                {"id": "m1", "role": "system", "content": "hi", "timestamp": "2025-01-01T00:00:00Z"}
+            ]
+        }"#;
+        fs::write(sessions.path().join("legacy-session.json"), legacy).unwrap();
+        fs::write(sessions.path().join("broken.json"), "{").unwrap();
+
+        let report = manager.import_legacy_sessions(sessions.path()).unwrap();
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.failed.len(), 1);
+
+        let loaded = manager.load("legacy-session").unwrap();
+        assert_eq!(loaded.title, "From engine");
+        assert_eq!(loaded.schema_version, crate::CURRENT_SCHEMA_VERSION);
+
+        let again = manager.import_legacy_sessions(sessions.path()).unwrap();
+        assert_eq!(again.imported, 0);
+        assert_eq!(again.skipped, 1);
+    }
+
+    #[test]
+    fn test_encrypted_storage_and_rotation() {
+        let temp_dir = tempdir().unwrap();
+        let old_key = EncryptionKey::generate();
+        let mut manager = ConversationManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption(Some(old_key.clone()));
+
+        let mut conversation = Conversation::new("Secret");
+        conversation.add_message(Message::new(MessageRole::User, "合言葉は猫".to_string()));
+        manager.save(&conversation).unwrap();
+        manager.save(&conversation).unwrap();
+
+        let raw = fs::read(temp_dir.path().join(format!("{}.json", conversation.id))).unwrap();
+        assert!(secret_store::is_encrypted(&raw));
+        assert_eq!(manager.load(&conversation.id).unwrap().title, "Secret");
+
+        // 鍵が無い・違う場合は読めないが、隔離はしない
+        let plain = ConversationManager::new(temp_dir.path()).unwrap();
+        assert!(matches!(
+            plain.load(&conversation.id),
+            Err(HistoryError::Encryption(_))
+        ));
+        assert!(plain.list_metadata().unwrap().is_empty());
+        assert!(plain.list_quarantined().unwrap().is_empty());
+        let (listed, skipped) = plain.list_metadata_with_skipped().unwrap();
+        assert!(listed.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0]
+            .path
+            .ends_with(format!("{}.json", conversation.id)));
+
+        let new_key = EncryptionKey::generate();
+        let rewritten = manager.reencrypt_all(Some(new_key.clone())).unwrap();
+        assert_eq!(rewritten, 2);
+
+        let rotated = ConversationManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption(Some(new_key));
+        assert_eq!(rotated.load(&conversation.id).unwrap().messages.len(), 1);
+        assert!(rotated.restore_backup(&conversation.id, None).is_ok());
+
+        let stale = ConversationManager::new(temp_dir.path())
+            .unwrap()
+            .with_encryption(Some(old_key));
+        assert!(stale.load(&conversation.id).is_err());
+
+        // 平文に戻す
+        manager.reencrypt_all(None).unwrap();
+        assert_eq!(plain.load(&conversation.id).unwrap().title, "Secret");
+    }
+
+    #[test]
+    fn test_interrupted_rotation_can_resume_or_roll_back() {
+        let temp_dir = tempdir().unwrap();
+        let old_key = EncryptionKey::generate();
+        let new_key = EncryptionKey::generate();
+        let manager_with = |key: &EncryptionKey| {
+            ConversationManager::new(temp_dir.path())
+                .unwrap()
+                .with_encryption(Some(key.clone()))
+        };
+
+        let first = Conversation::new("First");
+        let second = Conversation::new("Second");
+        manager_with(&old_key).save(&first).unwrap();
+        manager_with(&old_key).save(&second).unwrap();
+
+        // 入れ替えの途中で止まった状態（片方だけ新しい鍵、写しが残っている）
+        manager_with(&new_key).save(&first).unwrap();
+        let second_path = temp_dir.path().join(format!("{}.json", second.id));
+        fs::write(rekey_path(&second_path), b"partial").unwrap();
+
+        // 元の鍵へ巻き戻す（First のバックアップを含めて 3 ファイル）
+        let rewritten = manager_with(&new_key)
+            .reencrypt_all(Some(old_key.clone()))
+            .unwrap();
+        assert_eq!(rewritten, 3);
+        assert!(!rekey_path(&second_path).exists());
+        assert_eq!(
+            manager_with(&old_key).load(&first.id).unwrap().title,
+            "First"
+        );
+
+        // 同じ状態から再開する
+        manager_with(&new_key).save(&first).unwrap();
+        manager_with(&old_key)
+            .reencrypt_all(Some(new_key.clone()))
+            .unwrap();
+        let rotated = manager_with(&new_key);
+        assert_eq!(rotated.load(&first.id).unwrap().title, "First");
+        assert_eq!(rotated.load(&second.id).unwrap().title, "Second");
+        assert!(manager_with(&old_key).load(&second.id).is_err());
+    }
 }
@@ -11,6 +11,8 @@ pub enum MessageRole {
     Assistant,
     System,
     Error,
+    /// ツール呼び出しの記録（メタデータ `tool_call`）
+    Tool,
 }
 
 /// チャットメッセージ
@@ -37,6 +39,21 @@ impl Message {
         }
     }
 
+    /// ユーザーメッセージを作成
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::User, content)
+    }
+
+    /// アシスタントメッセージを作成
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::Assistant, content)
+    }
+
+    /// システムメッセージを作成
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::System, content)
+    }
+
     /// メタデータ付きメッセージを作成
     pub fn with_metadata(
         role: MessageRole,
@@ -51,4 +68,35 @@ impl Message {
             metadata: Some(metadata),
         }
     }
+
+    /// メッセージがピン留めされているか（メタデータの `pinned`）
+    pub fn is_pinned(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("pinned"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// ピン留めを切り替える（外すときはキーごと消す）
+    pub fn set_pinned(&mut self, pinned: bool) {
+        let metadata = self
+            .metadata
+            .get_or_insert_with(|| serde_json::Value::Object(Default::default()));
+        if let Some(object) = metadata.as_object_mut() {
+            if pinned {
+                object.insert("pinned".into(), serde_json::Value::Bool(true));
+            } else {
+                object.remove("pinned");
+            }
+        }
+        if self
+            .metadata
+            .as_ref()
+            .and_then(serde_json::Value::as_object)
+            .is_some_and(|object| object.is_empty())
+        {
+            self.metadata = None;
+        }
+    }
 }
@@ -0,0 +1,162 @@
+//! 会話ファイルのスキーマバージョン管理
+//!
+//! 保存形式を変更するときは `CURRENT_SCHEMA_VERSION` を上げ、
+//! 1 つ前のバージョンから変換する関数を `UPGRADERS` に追加する。
+
+use crate::{Conversation, HistoryError};
+use serde_json::{Map, Value};
+
+/// 現在の保存形式のバージョン
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// 既定タイトル（旧 ChatSession はタイトルが無い場合がある）
+const UNTITLED: &str = "Imported Chat";
+
+type Upgrader = fn(&mut Map<String, Value>) -> Result<(), HistoryError>;
+
+/// `UPGRADERS[n]` はバージョン n の JSON を n + 1 に変換する
+const UPGRADERS: &[Upgrader] = &[upgrade_v0_to_v1, upgrade_v1_to_v2];
+
+/// JSON 文字列を読み込み、必要なら最新スキーマへ変換して会話を返す
+pub fn parse_conversation(json: &str) -> Result<Conversation, HistoryError> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| HistoryError::Deserialization(e.to_string()))?;
+    let value = upgrade(value)?;
+    serde_json::from_value(value).map_err(|e| HistoryError::Deserialization(e.to_string()))
+}
+
+/// JSON 値を最新スキーマへ変換
+pub fn upgrade(value: Value) -> Result<Value, HistoryError> {
+    let Value::Object(mut map) = value else {
+        return Err(HistoryError::InvalidData(
+            "conversation file is not a JSON object".into(),
+        ));
+    };
+
+    let mut version = schema_version_of(&map)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(HistoryError::UnsupportedSchema(version));
+    }
+
+    while version < CURRENT_SCHEMA_VERSION {
+        UPGRADERS[version as usize](&mut map)?;
+        version += 1;
+        map.insert("schema_version".into(), Value::from(version));
+    }
+
+    Ok(Value::Object(map))
+}
+
+fn schema_version_of(map: &Map<String, Value>) -> Result<u32, HistoryError> {
+    match map.get("schema_version") {
+        None | Some(Value::Null) => Ok(0),
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| HistoryError::InvalidData(format!("invalid schema_version: {}", value))),
+    }
+}
+
+/// v0: バージョン番号の無い形式。
+/// `chat_history::Conversation` と `chat_engine::ChatSession`（title が Option）の両方を含む。
+fn upgrade_v0_to_v1(map: &mut Map<String, Value>) -> Result<(), HistoryError> {
+    let has_title = map
+        .get("title")
+        .and_then(Value::as_str)
+        .map(|title| !title.trim().is_empty())
+        .unwrap_or(false);
+    if !has_title {
+        let title = first_user_line(map).unwrap_or_else(|| UNTITLED.to_string());
+        map.insert("title".into(), Value::from(title));
+    }
+
+    if !map.contains_key("messages") {
+        map.insert("messages".into(), Value::Array(Vec::new()));
+    }
+
+    Ok(())
+}
+
+/// v2: `MessageRole::Tool` のメッセージ（メタデータ `tool_call`）を追加。
+/// 既存のメッセージは変わらないので、バージョン番号だけを上げる。
+/// ロールを知らない古いビルドは `UnsupportedSchema` で読み込みを止め、ファイルを書き換えない。
+fn upgrade_v1_to_v2(_map: &mut Map<String, Value>) -> Result<(), HistoryError> {
+    Ok(())
+}
+
+/// タイトル代わりに最初のユーザー発言の 1 行目を使う
+fn first_user_line(map: &Map<String, Value>) -> Option<String> {
+    map.get("messages")?
+        .as_array()?
+        .iter()
+        .find(|msg| msg.get("role").and_then(Value::as_str) == Some("user"))
+        .and_then(|msg| msg.get("content")?.as_str())
+        .and_then(|content| content.lines().next())
+        .map(|line| line.chars().take(40).collect::<String>())
+        .filter(|line| !line.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_legacy_chat_session() {
+        let legacy = r#"{
+            "id": "session-1",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-02T00:00:00Z",
+            "title": null,
+            "messages": [
+                {"id": "m1", "role": "user", "content": "Rust の質問\n詳細", "timestamp": "2025-01-01T00:00:00Z"},
+                {"id": "m2", "role": "assistant", "content": "はい", "timestamp": "2025-01-01T00:00:01Z"}
+            ]
+        }"#;
+
+        let conversation = parse_conversation(legacy).unwrap();
+        assert_eq!(conversation.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(conversation.title, "Rust の質問");
+        assert_eq!(conversation.messages.len(), 2);
+    }
+
+    #[test]
+    fn test_current_version_is_untouched() {
+        let conversation = Conversation::new("Current");
+        let json = serde_json::to_string(&conversation).unwrap();
+
+        let parsed = parse_conversation(&json).unwrap();
+        assert_eq!(parsed.title, "Current");
+        assert_eq!(parsed.schema_version, CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_upgrade_v1_keeps_messages() {
+        let v1 = r#"{
+            "schema_version": 1,
+            "id": "c1",
+            "title": "Saved",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "messages": [
+                {"id": "m1", "role": "user", "content": "hi", "timestamp": "2025-01-01T00:00:00Z"}
+            ]
+        }"#;
+
+        let conversation = parse_conversation(v1).unwrap();
+        assert_eq!(conversation.schema_version, 2);
+        assert_eq!(conversation.title, "Saved");
+        assert_eq!(conversation.messages[0].content, "hi");
+    }
+
+    #[test]
+    fn test_rejects_newer_schema() {
+        let json = format!(
+            r#"{{"schema_version": {}, "id": "x", "title": "t"}}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        assert!(matches!(
+            parse_conversation(&json),
+            Err(HistoryError::UnsupportedSchema(_))
+        ));
+    }
+}
@@ -0,0 +1,118 @@
+//! 会話ごとの設定（モデル・システムプロンプト・ツール・生成オプション）
+
+use serde::{Deserialize, Serialize};
+
+/// 会話ごとの設定。未設定の項目はアプリ全体の既定値を使う
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationSettings {
+    /// 使用するモデル名。None なら既定モデル
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// カスタムシステムプロンプト。None なら組み込みの指示文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// 有効な MCP ツール（`tool@server` 形式）。None なら全ツール
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_tools: Option<Vec<String>>,
+    /// 生成オプション
+    #[serde(default)]
+    pub generation: GenerationOptions,
+    /// 使うプロンプトビルダーの識別子（`plugin:<名前>` など）。None ならモデルの既定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_builder: Option<String>,
+    /// プロンプトに含めるスクラッチパッドのノート名（選んだ順）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_notes: Vec<String>,
+}
+
+impl ConversationSettings {
+    /// ツールが有効か（`tool@server` または `tool` 単体で指定可）
+    pub fn is_tool_enabled(&self, server: &str, tool: &str) -> bool {
+        match &self.enabled_tools {
+            None => true,
+            Some(list) => list
+                .iter()
+                .any(|entry| entry == tool || *entry == tool_key(server, tool)),
+        }
+    }
+
+    /// システムプロンプトを設定（空文字は未設定扱い）
+    pub fn set_system_prompt(&mut self, prompt: Option<String>) {
+        self.system_prompt = prompt.filter(|p| !p.trim().is_empty());
+    }
+}
+
+/// `enabled_tools` で使うツールキー
+pub fn tool_key(server: &str, tool: &str) -> String {
+    format!("{}@{}", tool, server)
+}
+
+/// LLM の生成オプション。None の項目はモデル既定値
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// 生成する最大トークン数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i32>,
+}
+
+impl GenerationOptions {
+    /// すべてモデル既定値か
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// ツールバー表示用の短い要約（例: `temp 0.7 · max 512`）
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(t) = self.temperature {
+            parts.push(format!("temp {}", t));
+        }
+        if let Some(p) = self.top_p {
+            parts.push(format!("top_p {}", p));
+        }
+        if let Some(m) = self.max_tokens {
+            parts.push(format!("max {}", m));
+        }
+        if let Some(s) = self.seed {
+            parts.push(format!("seed {}", s));
+        }
+        if parts.is_empty() {
+            "default".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_filter() {
+        let mut settings = ConversationSettings::default();
+        assert!(settings.is_tool_enabled("weather", "forecast"));
+
+        settings.enabled_tools = Some(vec![tool_key("weather", "forecast"), "search".into()]);
+        assert!(settings.is_tool_enabled("weather", "forecast"));
+        assert!(settings.is_tool_enabled("web", "search"));
+        assert!(!settings.is_tool_enabled("weather", "alerts"));
+    }
+
+    #[test]
+    fn test_generation_summary() {
+        assert_eq!(GenerationOptions::default().summary(), "default");
+        let options = GenerationOptions {
+            temperature: Some(0.5),
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+        assert_eq!(options.summary(), "temp 0.5 · max 256");
+    }
+}
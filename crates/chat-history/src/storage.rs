@@ -0,0 +1,87 @@
+//! クラッシュに強いファイル書き込みとバックアップ・隔離の補助関数
+
+use crate::HistoryError;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// バックアップ・隔離ファイル名に使う時刻書式（辞書順 = 時系列順）
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// 一時ファイルへ書き込み fsync した後、rename で置き換える。
+/// 途中でクラッシュしても元のファイルは壊れない。
+pub(crate) fn atomic_write(path: &Path, contents: &[u8]) -> Result<(), HistoryError> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| HistoryError::InvalidData(format!("no parent dir: {}", path.display())))?;
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| HistoryError::InvalidData(format!("invalid path: {}", path.display())))?;
+    let tmp_path = dir.join(format!(".{}.tmp", file_name));
+
+    {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+
+    if let Err(err) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err.into());
+    }
+
+    sync_dir(dir);
+    Ok(())
+}
+
+/// rename をディスクへ反映させる（対応していない OS では何もしない）
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(handle) = File::open(dir) {
+        let _ = handle.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// 現在時刻のスタンプ
+pub(crate) fn timestamp_now() -> String {
+    Utc::now().format(STAMP_FORMAT).to_string()
+}
+
+/// スタンプ文字列を時刻に戻す
+pub(crate) fn parse_timestamp(stamp: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// `<stamp>[-<n>]...` 形式のファイル名から時刻と連番を取り出す（新旧の並べ替え用）
+pub(crate) fn parse_stamped_name(name: &str) -> Option<(DateTime<Utc>, u32)> {
+    let stamp_len = "20250101T000000.000Z".len();
+    let stamp = name.get(..stamp_len)?;
+    let created_at = parse_timestamp(stamp)?;
+    let rest = &name[stamp_len..];
+    let seq = match rest.strip_prefix('-') {
+        Some(rest) => {
+            let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()?
+        }
+        None => 0,
+    };
+    Some((created_at, seq))
+}
+
+/// `dir/<stamp><suffix>` のうち未使用のパスを返す
+pub(crate) fn unique_stamped_path(dir: &Path, suffix: &str) -> PathBuf {
+    let stamp = timestamp_now();
+    let mut candidate = dir.join(format!("{}{}", stamp, suffix));
+    let mut counter = 1;
+    while candidate.exists() {
+        candidate = dir.join(format!("{}-{}{}", stamp, counter, suffix));
+        counter += 1;
+    }
+    candidate
+}
@@ -0,0 +1,86 @@
+//! ツール呼び出しの記録（会話には `MessageRole::Tool` のメッセージとして保存する）
+
+use crate::message::{Message, MessageRole};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const METADATA_KEY: &str = "tool_call";
+
+/// 1 回のツール呼び出し
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub tool: String,
+    /// MCP サーバー名またはツールプラグインのディレクトリ名
+    pub server: String,
+    pub arguments: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl ToolCallRecord {
+    /// `tool@server` 形式の識別子
+    pub fn identifier(&self) -> String {
+        format!("{}@{}", self.tool, self.server)
+    }
+
+    /// 会話の文脈としてモデルに渡す本文
+    pub fn summary(&self) -> String {
+        let outcome = match (&self.result, &self.error) {
+            (_, Some(error)) => format!("error: {}", error),
+            (Some(Value::String(text)), None) => text.clone(),
+            (Some(result), None) => result.to_string(),
+            (None, None) => "null".to_string(),
+        };
+        format!("{} {} -> {}", self.identifier(), self.arguments, outcome)
+    }
+}
+
+impl Message {
+    /// ツール呼び出しのメッセージを作成
+    pub fn tool_call(record: &ToolCallRecord) -> Self {
+        let metadata = serde_json::json!({ METADATA_KEY: record });
+        Self::with_metadata(MessageRole::Tool, record.summary(), metadata)
+    }
+
+    /// ツール呼び出しの記録（ツールのメッセージでなければ None）
+    pub fn tool_call_record(&self) -> Option<ToolCallRecord> {
+        if self.role != MessageRole::Tool {
+            return None;
+        }
+        let value = self.metadata.as_ref()?.get(METADATA_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stores_tool_calls_as_tool_messages() {
+        let record = ToolCallRecord {
+            tool: "get_weather".into(),
+            server: "weather".into(),
+            arguments: json!({"city": "Osaka"}),
+            result: Some(json!("晴れ")),
+            error: None,
+            duration_ms: 120,
+        };
+        let message = Message::tool_call(&record);
+        assert_eq!(message.role, MessageRole::Tool);
+        assert_eq!(
+            message.content,
+            r#"get_weather@weather {"city":"Osaka"} -> 晴れ"#
+        );
+
+        let json = serde_json::to_string(&message).unwrap();
+        let restored: Message = serde_json::from_str(&json).unwrap();
+        assert!(json.contains(r#""role":"tool""#));
+        assert_eq!(restored.tool_call_record(), Some(record));
+        assert_eq!(Message::assistant("hi").tool_call_record(), None);
+    }
+}
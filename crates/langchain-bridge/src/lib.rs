@@ -16,11 +16,16 @@ use langchain_rust::{
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-const JAPANESE_INSTRUCTION: &str = r"あなたは日本語で回答するAIアシスタントです。ツール呼び出し結果や引用した数値があれば、それらを尊重しつつ自然な日本語で簡潔にまとめてください。";
+/// Ollama の生成オプション（temperature / top_p / num_predict / seed など）
+pub use langchain_rust::llm::ollama::client::GenerationOptions as OllamaGenerationOptions;
+
+/// 既定のシステム指示文
+pub const JAPANESE_INSTRUCTION: &str = r"あなたは日本語で回答するAIアシスタントです。ツール呼び出し結果や引用した数値があれば、それらを尊重しつつ自然な日本語で簡潔にまとめてください。";
 
 /// LangChain ベースのチャットエンジン
 pub struct LangChainEngine {
     ollama: Ollama,
+    instruction: String,
     _base_url: String,
     _model: String,
 }
@@ -32,11 +37,24 @@ impl LangChainEngine {
 
         Self {
             ollama,
+            instruction: JAPANESE_INSTRUCTION.to_string(),
             _base_url: base_url.to_string(),
             _model: model.to_string(),
         }
     }
 
+    /// システム指示文を差し替える
+    pub fn with_instruction(mut self, instruction: impl Into<String>) -> Self {
+        self.instruction = instruction.into();
+        self
+    }
+
+    /// 生成オプションを指定
+    pub fn with_options(mut self, options: OllamaGenerationOptions) -> Self {
+        self.ollama = self.ollama.with_options(options);
+        self
+    }
+
     /// 会話履歴を含むメッセージ送信
     pub async fn send_message(&mut self, message: &str) -> Result<String> {
         let memory = Arc::new(Mutex::new(SimpleMemory::new()));
@@ -47,7 +65,7 @@ impl LangChainEngine {
 
         let response = chain
             .invoke(prompt_args! {
-                "input" => format!("{}\n\nユーザー入力:\n{}", JAPANESE_INSTRUCTION, message),
+                "input" => format!("{}\n\nユーザー入力:\n{}", self.instruction, message),
             })
             .await?;
 
@@ -56,7 +74,7 @@ impl LangChainEngine {
 
     /// ストリーミング応答（簡易版）
     pub async fn send_message_simple(&mut self, message: &str) -> Result<String> {
-        let prompt = format!("{}\n\nユーザー入力:\n{}", JAPANESE_INSTRUCTION, message);
+        let prompt = format!("{}\n\nユーザー入力:\n{}", self.instruction, message);
         let response = self.ollama.invoke(&prompt).await?;
         Ok(response)
     }
@@ -73,11 +91,25 @@ type AgentExecutorInner = AgentExecutor<ConversationalAgent>;
 #[derive(Clone)]
 pub struct LangChainToolAgent {
     executor: Arc<tokio::sync::Mutex<AgentExecutorInner>>,
+    instruction: Arc<str>,
 }
 
 impl LangChainToolAgent {
     pub fn new(model: &str, tools: Vec<Arc<dyn Tool>>) -> Result<Self> {
-        let llm = Ollama::default().with_model(model);
+        Self::with_config(model, tools, None, None)
+    }
+
+    /// システム指示文と生成オプションを指定して作成（None は既定値）
+    pub fn with_config(
+        model: &str,
+        tools: Vec<Arc<dyn Tool>>,
+        instruction: Option<&str>,
+        options: Option<OllamaGenerationOptions>,
+    ) -> Result<Self> {
+        let mut llm = Ollama::default().with_model(model);
+        if let Some(options) = options {
+            llm = llm.with_options(options);
+        }
         let memory = SimpleMemory::new();
 
         let mut builder = ConversationalAgentBuilder::new();
@@ -90,12 +122,13 @@ impl LangChainToolAgent {
 
         Ok(Self {
             executor: Arc::new(tokio::sync::Mutex::new(executor)),
+            instruction: Arc::from(instruction.unwrap_or(JAPANESE_INSTRUCTION)),
         })
     }
 
     pub async fn invoke(&self, input: &str) -> Result<String> {
         let vars = prompt_args! {
-            "input" => format!("{}\n\nユーザー入力:\n{}", JAPANESE_INSTRUCTION, input),
+            "input" => format!("{}\n\nユーザー入力:\n{}", self.instruction, input),
         };
 
         let executor = self.executor.lock().await;
@@ -106,14 +139,14 @@ impl LangChainToolAgent {
     /// デバッグモードで実行し、LLMとの生の対話を stderr に出力
     pub async fn invoke_with_debug(&self, input: &str) -> Result<String> {
         let vars = prompt_args! {
-            "input" => format!("{}\n\nユーザー入力:\n{}", JAPANESE_INSTRUCTION, input),
+            "input" => format!("{}\n\nユーザー入力:\n{}", self.instruction, input),
         };
 
         eprintln!("\n=== LangChain Debug Output ===");
         eprintln!("[DEBUG] User Input:");
         eprintln!("{}", input);
         eprintln!("\n[DEBUG] Prompt sent to LLM:");
-        eprintln!("{}\n\nユーザー入力:\n{}", JAPANESE_INSTRUCTION, input);
+        eprintln!("{}\n\nユーザー入力:\n{}", self.instruction, input);
 
         let executor = self.executor.lock().await;
 
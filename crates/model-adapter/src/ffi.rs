@@ -0,0 +1,372 @@
+//! Stable C ABI used to load adapters from `cdylib` plugins.
+//!
+//! Only `#[repr(C)]` data and NUL-terminated UTF-8 strings (JSON) cross the
+//! boundary, so the host and a plugin may be built with different compilers
+//! or crate versions. Strings returned by the plugin must be released with
+//! the plugin's own `free_string`. A panic inside the plugin is caught before
+//! it reaches the host and reported as a null return.
+//!
+//! Plugin side:
+//!
+//! ```ignore
+//! model_adapter::export_model_adapter!(MyAdapter::new);
+//! ```
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{GenerateResult, ModelAdapter, ModelProvider, ProviderError, ToolSpec};
+
+/// Version of the adapter ABI. Bump when `AdapterVTable` or the JSON shapes change.
+pub const ADAPTER_ABI_VERSION: u32 = 1;
+
+/// Default exported symbol name (`entrypoint` in `plugin.toml`).
+pub const DEFAULT_ADAPTER_ENTRYPOINT: &str = "neko_model_adapter";
+
+/// Function table exported by an adapter plugin.
+#[repr(C)]
+pub struct AdapterVTable {
+    /// Must equal the host's `ADAPTER_ABI_VERSION`.
+    pub abi_version: u32,
+    /// Returns `AdapterDescription` as JSON.
+    pub describe: extern "C" fn() -> *mut c_char,
+    /// Takes `BuildPromptRequest` as JSON and returns the formatted prompt,
+    /// or null on failure.
+    pub build_prompt: extern "C" fn(*const c_char) -> *mut c_char,
+    /// Releases a string returned by `describe` / `build_prompt`.
+    pub free_string: unsafe extern "C" fn(*mut c_char),
+}
+
+/// Signature of the exported entry point.
+pub type AdapterEntrypoint = extern "C" fn() -> *const AdapterVTable;
+
+/// Adapter identity reported by `describe`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterDescription {
+    pub name: String,
+    pub models: Vec<String>,
+}
+
+/// Payload passed to `build_prompt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildPromptRequest {
+    pub model: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub tools: Option<Vec<ToolSpec>>,
+}
+
+/// Errors raised while binding a plugin's vtable.
+#[derive(Debug, thiserror::Error)]
+pub enum AdapterLoadError {
+    #[error("entry point returned null")]
+    NullVTable,
+
+    #[error("adapter ABI version mismatch: plugin {plugin}, host {host}")]
+    AbiMismatch { plugin: u32, host: u32 },
+
+    #[error("invalid adapter description: {0}")]
+    InvalidDescription(String),
+}
+
+// ---- plugin side -------------------------------------------------------
+
+/// Run the body of an exported function, returning null if it panics
+/// (unwinding across `extern "C"` would abort the host).
+#[doc(hidden)]
+pub fn null_on_panic(f: impl FnOnce() -> *mut c_char) -> *mut c_char {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(std::ptr::null_mut())
+}
+
+/// Plugin-side helper for `describe`.
+pub fn describe_adapter(adapter: &dyn ModelAdapter) -> *mut c_char {
+    let description = AdapterDescription {
+        name: adapter.adapter_name().to_string(),
+        models: adapter.supported_models(),
+    };
+    serde_json::to_string(&description)
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Plugin-side helper for `build_prompt`.
+///
+/// # Safety
+/// `request` must be null or a valid NUL-terminated string.
+pub unsafe fn build_prompt_for(adapter: &dyn ModelAdapter, request: *const c_char) -> *mut c_char {
+    if request.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(json) = CStr::from_ptr(request).to_str() else {
+        return std::ptr::null_mut();
+    };
+    let Ok(request) = serde_json::from_str::<BuildPromptRequest>(json) else {
+        return std::ptr::null_mut();
+    };
+    let prompt = adapter.build_prompt(&request.model, &request.prompt, request.tools.as_deref());
+    CString::new(prompt).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Plugin-side `free_string`.
+///
+/// # Safety
+/// `ptr` must be null or a string returned by `describe_adapter` /
+/// `build_prompt_for` in this library.
+pub unsafe extern "C" fn free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+/// Export an adapter from a plugin crate as `neko_model_adapter`.
+///
+/// `$constructor` is called once, on first use.
+#[macro_export]
+macro_rules! export_model_adapter {
+    ($constructor:expr) => {
+        fn __neko_adapter_instance() -> &'static dyn $crate::ModelAdapter {
+            static INSTANCE: ::std::sync::OnceLock<::std::boxed::Box<dyn $crate::ModelAdapter>> =
+                ::std::sync::OnceLock::new();
+            INSTANCE
+                .get_or_init(|| ::std::boxed::Box::new($constructor()))
+                .as_ref()
+        }
+
+        extern "C" fn __neko_adapter_describe() -> *mut ::std::os::raw::c_char {
+            $crate::ffi::null_on_panic(|| $crate::ffi::describe_adapter(__neko_adapter_instance()))
+        }
+
+        extern "C" fn __neko_adapter_build_prompt(
+            request: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::ffi::null_on_panic(|| {
+                // SAFETY: the host passes a valid NUL-terminated string.
+                unsafe { $crate::ffi::build_prompt_for(__neko_adapter_instance(), request) }
+            })
+        }
+
+        static __NEKO_ADAPTER_VTABLE: $crate::ffi::AdapterVTable = $crate::ffi::AdapterVTable {
+            abi_version: $crate::ffi::ADAPTER_ABI_VERSION,
+            describe: __neko_adapter_describe,
+            build_prompt: __neko_adapter_build_prompt,
+            free_string: $crate::ffi::free_string,
+        };
+
+        #[no_mangle]
+        pub extern "C" fn neko_model_adapter() -> *const $crate::ffi::AdapterVTable {
+            &__NEKO_ADAPTER_VTABLE
+        }
+    };
+}
+
+// ---- host side ---------------------------------------------------------
+
+/// Host-side `ModelAdapter` backed by a plugin's vtable.
+pub struct ForeignAdapter {
+    vtable: &'static AdapterVTable,
+    name: String,
+    models: Vec<String>,
+}
+
+impl ForeignAdapter {
+    /// Bind a vtable returned by the plugin's entry point.
+    ///
+    /// # Safety
+    /// `vtable` must be null or point to an `AdapterVTable` that stays valid
+    /// while the returned adapter is alive. The caller must keep the plugin's
+    /// `Library` loaded until the adapter is dropped (the host keeps an
+    /// `Arc` to it next to the adapter in `LibraryBoundAdapter`).
+    pub unsafe fn from_vtable(vtable: *const AdapterVTable) -> Result<Self, AdapterLoadError> {
+        let vtable = vtable.as_ref().ok_or(AdapterLoadError::NullVTable)?;
+        if vtable.abi_version != ADAPTER_ABI_VERSION {
+            return Err(AdapterLoadError::AbiMismatch {
+                plugin: vtable.abi_version,
+                host: ADAPTER_ABI_VERSION,
+            });
+        }
+
+        let json = take_string(vtable, (vtable.describe)())
+            .ok_or_else(|| AdapterLoadError::InvalidDescription("describe returned null".into()))?;
+        let description: AdapterDescription = serde_json::from_str(&json)
+            .map_err(|e| AdapterLoadError::InvalidDescription(e.to_string()))?;
+
+        Ok(Self {
+            vtable,
+            name: description.name,
+            models: description.models,
+        })
+    }
+
+    fn try_build_prompt(
+        &self,
+        model: &str,
+        prompt: &str,
+        tools: Option<&[ToolSpec]>,
+    ) -> Result<String, ProviderError> {
+        let request = BuildPromptRequest {
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            tools: tools.map(<[ToolSpec]>::to_vec),
+        };
+        let json = serde_json::to_string(&request)
+            .ok()
+            .and_then(|json| CString::new(json).ok())
+            .ok_or_else(|| ProviderError::Other("failed to encode adapter request".into()))?;
+
+        let raw = (self.vtable.build_prompt)(json.as_ptr());
+        // SAFETY: `raw` comes from this vtable and is released by its `free_string`.
+        unsafe { take_string(self.vtable, raw) }.ok_or_else(|| {
+            ProviderError::Other(format!("adapter '{}' failed to build prompt", self.name))
+        })
+    }
+}
+
+/// Copy a plugin-owned string and release it.
+///
+/// # Safety
+/// `raw` must be null or a string returned by a function of `vtable`.
+unsafe fn take_string(vtable: &AdapterVTable, raw: *mut c_char) -> Option<String> {
+    if raw.is_null() {
+        return None;
+    }
+    let value = CStr::from_ptr(raw).to_string_lossy().into_owned();
+    (vtable.free_string)(raw);
+    Some(value)
+}
+
+#[async_trait]
+impl ModelAdapter for ForeignAdapter {
+    fn adapter_name(&self) -> &str {
+        &self.name
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.models.clone()
+    }
+
+    fn build_prompt(&self, model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
+        self.try_build_prompt(model, prompt, tools)
+            .unwrap_or_else(|_| prompt.to_string())
+    }
+
+    async fn invoke(
+        &self,
+        provider: &dyn ModelProvider,
+        model: &str,
+        prompt: &str,
+        tools: Option<&[ToolSpec]>,
+    ) -> Result<GenerateResult, ProviderError> {
+        let formatted = self.try_build_prompt(model, prompt, tools)?;
+        provider.generate(model, &formatted).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Phi4MiniAdapter;
+
+    crate::export_model_adapter!(Phi4MiniAdapter::new);
+
+    extern "C" fn describe_stub() -> *mut c_char {
+        std::ptr::null_mut()
+    }
+
+    extern "C" fn build_stub(_: *const c_char) -> *mut c_char {
+        std::ptr::null_mut()
+    }
+
+    #[test]
+    fn exported_adapter_roundtrips_through_vtable() {
+        let adapter = unsafe { ForeignAdapter::from_vtable(neko_model_adapter()) }.unwrap();
+        assert_eq!(adapter.adapter_name(), "phi4-mini-adapter");
+        assert!(adapter
+            .supported_models()
+            .contains(&"phi4-mini:3.8b".to_string()));
+
+        let tool = ToolSpec::new("forecast", "Weather forecast");
+        let prompt = adapter.build_prompt("phi4-mini:3.8b", "hello", Some(&[tool]));
+        assert!(prompt.starts_with("<|system|>"));
+        assert!(prompt.contains("forecast"));
+        assert!(prompt.contains("hello"));
+    }
+
+    struct PanickingAdapter;
+
+    #[async_trait]
+    impl ModelAdapter for PanickingAdapter {
+        fn adapter_name(&self) -> &str {
+            "panicking"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn build_prompt(&self, _model: &str, _prompt: &str, _tools: Option<&[ToolSpec]>) -> String {
+            panic!("build_prompt exploded")
+        }
+    }
+
+    extern "C" fn panicking_describe() -> *mut c_char {
+        null_on_panic(|| describe_adapter(&PanickingAdapter))
+    }
+
+    extern "C" fn panicking_build(request: *const c_char) -> *mut c_char {
+        null_on_panic(|| unsafe { build_prompt_for(&PanickingAdapter, request) })
+    }
+
+    extern "C" fn exploding_describe() -> *mut c_char {
+        null_on_panic(|| panic!("describe exploded"))
+    }
+
+    #[test]
+    fn plugin_panics_become_null_returns() {
+        static VTABLE: AdapterVTable = AdapterVTable {
+            abi_version: ADAPTER_ABI_VERSION,
+            describe: panicking_describe,
+            build_prompt: panicking_build,
+            free_string,
+        };
+        let adapter = unsafe { ForeignAdapter::from_vtable(&VTABLE) }.unwrap();
+        assert!(matches!(
+            adapter.try_build_prompt("any", "hello", None),
+            Err(ProviderError::Other(message)) if message.contains("panicking")
+        ));
+        assert_eq!(adapter.build_prompt("any", "hello", None), "hello");
+
+        static BROKEN: AdapterVTable = AdapterVTable {
+            abi_version: ADAPTER_ABI_VERSION,
+            describe: exploding_describe,
+            build_prompt: build_stub,
+            free_string,
+        };
+        assert!(matches!(
+            unsafe { ForeignAdapter::from_vtable(&BROKEN) },
+            Err(AdapterLoadError::InvalidDescription(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_abi_version() {
+        static VTABLE: AdapterVTable = AdapterVTable {
+            abi_version: ADAPTER_ABI_VERSION + 1,
+            describe: describe_stub,
+            build_prompt: build_stub,
+            free_string,
+        };
+        let err = unsafe { ForeignAdapter::from_vtable(&VTABLE) }
+            .err()
+            .unwrap();
+        assert!(matches!(err, AdapterLoadError::AbiMismatch { .. }));
+        assert!(matches!(
+            unsafe { ForeignAdapter::from_vtable(std::ptr::null()) },
+            Err(AdapterLoadError::NullVTable)
+        ));
+    }
+}
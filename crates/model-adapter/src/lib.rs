@@ -8,6 +8,8 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+pub mod ffi;
+
 pub use model_provider::{GenerateResult, ModelProvider, ProviderError};
 
 /// Representation of a tool/function the model can call.
@@ -47,18 +49,31 @@ pub trait ModelAdapter: Send + Sync + 'static {
     /// List of model names this adapter supports (e.g. ["llama3.1:8b"]).
     fn supported_models(&self) -> Vec<String>;
 
+    /// Format `prompt` (and the tool descriptions) for the given model.
+    ///
+    /// This is the part of an adapter that crosses the plugin boundary (see
+    /// [`ffi`]). The default returns the prompt unchanged.
+    fn build_prompt(&self, model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
+        let _ = (model, tools);
+        prompt.to_string()
+    }
+
     /// Invoke the provider for the given model using adapter-specific formatting.
     ///
     /// `tools` describes available functions the model may call. Adapters are
     /// responsible for serializing these into the provider-specific payload
-    /// (if the provider/model supports function calling).
+    /// (if the provider/model supports function calling). The default sends
+    /// the result of `build_prompt` to the provider.
     async fn invoke(
         &self,
         provider: &dyn ModelProvider,
         model: &str,
         prompt: &str,
         tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError>;
+    ) -> Result<GenerateResult, ProviderError> {
+        let formatted = self.build_prompt(model, prompt, tools);
+        provider.generate(model, &formatted).await
+    }
 }
 
 /// Default adapter for `llama3.1:8b`.
@@ -86,13 +101,7 @@ impl ModelAdapter for Llama3DefaultAdapter {
         vec!["llama3.1:8b".to_string()]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
         // Llama3 default behavior: if tools are provided, append a short
         // serialized description to the prompt so the model can call them.
         let mut actual_prompt = prompt.to_string();
@@ -114,9 +123,7 @@ impl ModelAdapter for Llama3DefaultAdapter {
             }
         }
 
-        // Delegate to provider. Here we assume the provider expects model
-        // name exactly as given (e.g. "llama3.1:8b").
-        provider.generate(model, &actual_prompt).await
+        actual_prompt
     }
 }
 
@@ -204,17 +211,9 @@ impl ModelAdapter for Phi4MiniAdapter {
         ]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
         // Format prompt in Phi-4-mini-instruct format
-        let formatted = Self::format_prompt(None, prompt, tools);
-
-        provider.generate(model, &formatted).await
+        Self::format_prompt(None, prompt, tools)
     }
 }
 
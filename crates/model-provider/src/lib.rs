@@ -40,6 +40,7 @@ pub mod ollama_impl {
     use super::*;
     use crate::ProviderError;
 
+    pub use ollama_client::GenerateOptions;
     use ollama_client::OllamaClient;
 
     pub struct OllamaProvider {
@@ -76,6 +77,22 @@ pub mod ollama_impl {
 
             Ok(GenerateResult { text, structured })
         }
+
+        /// 生成オプション付きで生成
+        pub async fn generate_with_options(
+            &self,
+            model: &str,
+            prompt: &str,
+            options: &GenerateOptions,
+        ) -> Result<GenerateResult, ProviderError> {
+            let text = self
+                .client
+                .generate_with_options(model, prompt, options)
+                .await
+                .map_err(|e| ProviderError::Http(e.to_string()))?;
+            let structured = serde_json::from_str::<serde_json::Value>(&text).ok();
+            Ok(GenerateResult { text, structured })
+        }
     }
 
     #[async_trait]
@@ -96,14 +113,8 @@ pub mod ollama_impl {
             model: &str,
             prompt: &str,
         ) -> Result<GenerateResult, ProviderError> {
-            let text = self
-                .client
-                .generate(model, prompt)
+            self.generate_with_options(model, prompt, &GenerateOptions::default())
                 .await
-                .map_err(|e| ProviderError::Http(e.to_string()))?;
-            // Try to parse structured JSON if possible, otherwise leave None.
-            let structured = serde_json::from_str::<serde_json::Value>(&text).ok();
-            Ok(GenerateResult { text, structured })
         }
     }
 }
@@ -1,5 +1,7 @@
 //! チャットバブルコンポーネント
 
+use crate::markdown_view::markdown_view;
+use crate::theme::{current_theme, BubbleColor};
 use gpui::*;
 use gpui_component::skeleton::Skeleton;
 use gpui_component::StyledExt;
@@ -11,6 +13,7 @@ pub enum MessageType {
     Assistant,
     System,
     Error,
+    Tool,
 }
 
 /// チャットバブルコンポーネント
@@ -29,31 +32,33 @@ impl ChatBubble {
         }
     }
 
-    /// メッセージタイプに応じた背景色を取得
-    fn background_color(&self) -> Rgba {
+    /// メッセージタイプに応じた色を現在のテーマから取得
+    fn colors(&self) -> BubbleColor {
+        let bubble = &current_theme().colors.bubble;
         match self.msg_type {
-            MessageType::User => rgb(0x3b82f6),      // 青
-            MessageType::Assistant => rgb(0x10b981), // 緑
-            MessageType::System => rgb(0x6b7280),    // グレー
-            MessageType::Error => rgb(0xef4444),     // 赤
+            MessageType::User => bubble.user,
+            MessageType::Assistant => bubble.assistant,
+            MessageType::System => bubble.system,
+            MessageType::Error => bubble.error,
+            MessageType::Tool => bubble.tool,
         }
     }
 
-    /// メッセージタイプに応じたテキスト色を取得
-    fn text_color(&self) -> Rgba {
-        rgb(0xffffff) // 白
-    }
-
-    /// レンダリング
+    /// レンダリング（アシスタントの返答は Markdown として描画する）
     pub fn render(&self) -> impl IntoElement {
-        div()
+        let colors = self.colors();
+        let bubble = div()
             .w_full()
             .p_2()
             .rounded(px(12.0))
-            .bg(self.background_color())
-            .text_color(self.text_color())
-            .text_sm()
-            .child(self.content.clone())
+            .bg(colors.bg)
+            .text_color(colors.text)
+            .text_sm();
+        if self.msg_type == MessageType::Assistant {
+            bubble.child(markdown_view(&self.content))
+        } else {
+            bubble.child(self.content.clone())
+        }
     }
 
     /// スケルトンアニメーション付きの Thinking バブルを生成
@@ -66,7 +71,7 @@ impl ChatBubble {
             .w_full()
             .p_3()
             .rounded(px(12.0))
-            .bg(rgb(0x4b5563))
+            .bg(current_theme().colors.bubble.thinking)
             .opacity(0.85)
             .child(div().v_flex().gap_1().child(line(160.0)).child(line(100.0)))
     }
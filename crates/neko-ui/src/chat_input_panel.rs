@@ -2,13 +2,26 @@ use gpui::*;
 use gpui_component::input::{Input, InputState};
 use gpui_component::StyledExt;
 
+use crate::theme::current_theme;
+
+/// 入力欄の上に出すスラッシュコマンドの候補
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlashSuggestion {
+    /// `/model` のようなコマンド名
+    pub command: String,
+    /// 引数の書き方（無ければ空）
+    pub usage: String,
+    pub description: String,
+}
+
 /// 入力ヒント付きのチャット入力パネル
 pub fn chat_input_panel(input_state: &Entity<InputState>, hint_text: &str) -> Div {
+    let colors = current_theme().colors;
     div()
         .w_full()
         .p_4()
         .border_t_1()
-        .border_color(rgb(0x333333))
+        .border_color(colors.border)
         .child(
             div()
                 .w_full()
@@ -17,9 +30,79 @@ pub fn chat_input_panel(input_state: &Entity<InputState>, hint_text: &str) -> Di
                 .child(
                     div()
                         .text_sm()
-                        .text_color(rgb(0x888888))
+                        .text_color(colors.text_subtle)
                         .child(hint_text.to_string()),
                 )
                 .child(Input::new(input_state).w_full()),
         )
 }
+
+/// スラッシュコマンドの候補付きの入力パネル。先頭の候補を強調し、クリックで `on_select` を呼ぶ
+pub fn chat_input_panel_with_suggestions<V: Render>(
+    input_state: &Entity<InputState>,
+    hint_text: &str,
+    suggestions: &[SlashSuggestion],
+    on_select: impl Fn(&mut V, usize, &mut Window, &mut Context<V>) + 'static + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    let panel = chat_input_panel(input_state, hint_text);
+    if suggestions.is_empty() {
+        return panel;
+    }
+
+    let colors = current_theme().colors;
+    let rows = suggestions.iter().enumerate().map(|(index, suggestion)| {
+        let on_select = on_select.clone();
+        let row = div()
+            .h_flex()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .rounded_sm()
+            .cursor_pointer()
+            .hover(|style| style.bg(colors.hover))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(colors.accent_text)
+                    .child(suggestion.command.clone()),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(colors.text_muted)
+                    .child(suggestion.usage.clone()),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(colors.text_subtle)
+                    .child(suggestion.description.clone()),
+            )
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, window, cx| on_select(this, index, window, cx)),
+            );
+        if index == 0 {
+            row.bg(colors.selection)
+        } else {
+            row
+        }
+    });
+
+    let list = div()
+        .id("slash-command-suggestions")
+        .v_flex()
+        .mx_4()
+        .mt_2()
+        .p_1()
+        .max_h(px(200.0))
+        .overflow_y_scroll()
+        .rounded_md()
+        .border_1()
+        .border_color(colors.border)
+        .bg(colors.panel)
+        .children(rows);
+
+    div().w_full().v_flex().child(list).child(panel)
+}
@@ -1,50 +1,169 @@
+use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::StyledExt;
 
 use crate::chat_bubble::{ChatBubble, MessageType};
+use crate::theme::current_theme;
+use crate::tool_call_card::{tool_call_card, ToolCallCard};
 
 /// 表示用のチャットメッセージ行
 #[derive(Clone, Debug, PartialEq)]
 pub struct ChatMessageRow {
+    /// 履歴上のメッセージ ID（アクションの対象）
+    pub id: String,
     pub content: String,
     pub message_type: MessageType,
     pub align_end: bool,
     pub is_thinking: bool,
     pub source_label: Option<String>,
+    pub pinned: bool,
+    /// プロンプトや生の出力の記録があり Inspect できるか
+    pub inspectable: bool,
+    /// ツール呼び出しの記録（あればバブルの代わりにカードで表示する）
+    pub tool_call: Option<ToolCallCard>,
+    /// Retry / Delete を出すか（応答の生成中は出さない）
+    pub editable: bool,
 }
 
-/// チャットメッセージリスト
-pub fn chat_message_list(rows: &[ChatMessageRow]) -> Div {
-    div()
-        .v_flex()
-        .h_full()
-        .p_4()
-        .gap_3()
-        .children(rows.iter().map(|row| {
-            let bubble = if row.is_thinking {
-                ChatBubble::thinking_placeholder().into_any_element()
-            } else {
-                ChatBubble::new(row.content.clone(), row.message_type.clone())
-                    .render()
-                    .into_any_element()
-            };
-
-            // make bubble container fill the available width so bubble matches chat window width
-            let mut bubble_container = div().max_w_4_5().v_flex().gap_1();
-            if let Some(label) = &row.source_label {
-                bubble_container = bubble_container.child(
-                    div()
-                        .text_xs()
-                        .text_color(rgb(0xa5b4fc))
-                        .child(label.clone()),
-                );
-            }
-            let bubble_container = bubble_container.child(bubble);
-
-            if row.align_end {
-                div().flex().justify_end().child(bubble_container)
-            } else {
-                div().flex().justify_start().child(bubble_container)
-            }
-        }))
+/// メッセージにマウスを重ねたときのアクション（Copy はこのコンポーネント内で処理する）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageAction {
+    Delete,
+    Retry,
+    TogglePin,
+    Inspect,
+    /// ツール呼び出しのカードを開閉する
+    ToggleToolCall,
+    /// ツールを同じ引数でもう一度実行する
+    RerunTool,
+    /// アシスタントの返答をスクラッチパッドのノートに書き足す
+    AppendToNote,
+}
+
+/// チャットメッセージリスト（全行を描画する。長い会話は `chat_messages_panel` の仮想化リストを使う）
+pub fn chat_message_list<V: Render>(
+    rows: &[ChatMessageRow],
+    on_action: impl Fn(&mut V, &ChatMessageRow, MessageAction, &mut Window, &mut Context<V>)
+        + 'static
+        + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    div().v_flex().h_full().p_4().gap_3().children(
+        rows.iter()
+            .map(|row| chat_message_row(row, on_action.clone(), cx)),
+    )
+}
+
+/// メッセージ 1 行（ラベル・バブル・アクションバー）
+pub fn chat_message_row<V: Render>(
+    row: &ChatMessageRow,
+    on_action: impl Fn(&mut V, &ChatMessageRow, MessageAction, &mut Window, &mut Context<V>)
+        + 'static
+        + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    let label_color = current_theme().colors.accent_text;
+    let bubble = if row.is_thinking {
+        ChatBubble::thinking_placeholder().into_any_element()
+    } else if let Some(card) = &row.tool_call {
+        tool_call_card(row, card, on_action.clone(), cx).into_any_element()
+    } else {
+        ChatBubble::new(row.content.clone(), row.message_type.clone())
+            .render()
+            .into_any_element()
+    };
+
+    let group = SharedString::from(format!("message-{}", row.id));
+    // make bubble container fill the available width so bubble matches chat window width
+    let mut bubble_container = div().group(group.clone()).max_w_4_5().v_flex().gap_1();
+    let label = match (&row.source_label, row.pinned) {
+        (Some(label), true) => Some(format!("📌 {}", label)),
+        (Some(label), false) => Some(label.clone()),
+        (None, true) => Some("📌 Pinned".to_string()),
+        (None, false) => None,
+    };
+    if let Some(label) = label {
+        bubble_container =
+            bubble_container.child(div().text_xs().text_color(label_color).child(label));
+    }
+    let bubble_container = bubble_container
+        .child(bubble)
+        .when(!row.is_thinking, |container| {
+            container.child(action_bar(row, group, on_action, cx))
+        });
+
+    if row.align_end {
+        div().flex().justify_end().child(bubble_container)
+    } else {
+        div().flex().justify_start().child(bubble_container)
+    }
+}
+
+/// ホバー中だけ表示するアクションバー
+fn action_bar<V: Render>(
+    row: &ChatMessageRow,
+    group: SharedString,
+    on_action: impl Fn(&mut V, &ChatMessageRow, MessageAction, &mut Window, &mut Context<V>)
+        + 'static
+        + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    let colors = current_theme().colors;
+    let action = |label: &'static str| {
+        div()
+            .px_1()
+            .rounded_sm()
+            .text_xs()
+            .text_color(colors.text_subtle)
+            .hover(|style| style.text_color(colors.text).bg(colors.hover))
+            .cursor_pointer()
+            .child(label)
+    };
+
+    let content = row.content.clone();
+    let mut actions =
+        vec![
+            action("Copy").on_mouse_down(MouseButton::Left, move |_, _window, cx: &mut App| {
+                cx.write_to_clipboard(ClipboardItem::new_string(content.clone()));
+            }),
+        ];
+
+    let mut entries = Vec::new();
+    // ウェルカムメッセージなどの前にはやり直すユーザーメッセージが無い
+    if row.editable && row.message_type != MessageType::System {
+        entries.push(("Retry", MessageAction::Retry));
+    }
+    entries.push((
+        if row.pinned { "Unpin" } else { "Pin" },
+        MessageAction::TogglePin,
+    ));
+    if row.editable {
+        entries.push(("Delete", MessageAction::Delete));
+    }
+    if row.message_type == MessageType::Assistant && row.tool_call.is_none() {
+        entries.push(("→ Note", MessageAction::AppendToNote));
+    }
+    if row.inspectable {
+        entries.push(("Inspect", MessageAction::Inspect));
+    }
+    for (label, kind) in entries {
+        let on_action = on_action.clone();
+        let row = row.clone();
+        actions.push(action(label).on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, _, window, cx| on_action(this, &row, kind, window, cx)),
+        ));
+    }
+
+    let bar = div()
+        .h_flex()
+        .gap_1()
+        .invisible()
+        .group_hover(group, |style| style.visible())
+        .children(actions);
+    if row.align_end {
+        bar.justify_end()
+    } else {
+        bar
+    }
 }
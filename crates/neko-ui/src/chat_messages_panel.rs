@@ -1,30 +1,75 @@
+use std::rc::Rc;
+
 use gpui::*;
-use gpui::prelude::FluentBuilder;
-use gpui_component::scroll::ScrollableElement;
+use gpui_component::StyledExt;
 
-use crate::chat_message_list::{chat_message_list, ChatMessageRow};
+use crate::chat_message_list::{chat_message_row, ChatMessageRow, MessageAction};
+use crate::theme::current_theme;
 
-/// スクロール付きのチャットメッセージパネル（ScrollHandle不使用版）
-pub fn chat_messages_panel(
-    rows: &[ChatMessageRow],
-    scroll_handle: Option<&gpui::ScrollHandle>,
+/// チャットメッセージパネル（仮想化リスト）
+///
+/// `list_state` の行数は `rows` と揃えておく（`ui_utils::VirtualListState` を使う）。
+/// 表示中の行だけを描画し、`show_jump_to_latest` のときは右下に「最新へ」ボタンを出す。
+pub fn chat_messages_panel<V: Render>(
+    rows: Rc<Vec<ChatMessageRow>>,
+    list_state: &ListState,
+    show_jump_to_latest: bool,
+    on_action: impl Fn(&mut V, &ChatMessageRow, MessageAction, &mut Window, &mut Context<V>)
+        + 'static
+        + Clone,
+    on_jump_to_latest: impl Fn(&mut V, &mut Window, &mut Context<V>) + 'static,
+    cx: &mut Context<V>,
 ) -> Div {
-    div()
+    let last = rows.len().saturating_sub(1);
+    let messages = list(
+        list_state.clone(),
+        cx.processor(move |_this: &mut V, ix: usize, _window, cx| {
+            let Some(row) = rows.get(ix) else {
+                return div().into_any_element();
+            };
+            // gap の代わりに行ごとの余白で間隔を空ける
+            div()
+                .px_4()
+                .pt(if ix == 0 { px(16.0) } else { px(0.0) })
+                .pb(if ix == last { px(16.0) } else { px(12.0) })
+                .child(chat_message_row(row, on_action.clone(), cx))
+                .into_any_element()
+        }),
+    )
+    .size_full();
+
+    let panel = div()
+        .relative()
         .flex_1()
         .h_full()
-        // .max_h(px(400.0))
         .overflow_hidden()
-        .child(
-            if let Some(handle) = scroll_handle {
-                div()
-                    .id("chat-messages-panel")
-                    .track_scroll(handle)
-                    .map(|this| this.overflow_y_scrollbar().child(chat_message_list(rows)))
-            } else {
-                div()
-                    .id("chat-messages-panel")
-                    .overflow_y_scrollbar()
-                    .child(chat_message_list(rows))
-            },
-        )
+        .child(messages);
+    if !show_jump_to_latest {
+        return panel;
+    }
+
+    let colors = current_theme().colors;
+    panel.child(
+        div()
+            .absolute()
+            .bottom_3()
+            .right_4()
+            .h_flex()
+            .px_3()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(colors.border)
+            .bg(colors.control)
+            .text_xs()
+            .text_color(colors.control_text)
+            .shadow_md()
+            .cursor_pointer()
+            .hover(|style| style.bg(colors.hover))
+            .child("↓ Jump to latest")
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, window, cx| on_jump_to_latest(this, window, cx)),
+            ),
+    )
 }
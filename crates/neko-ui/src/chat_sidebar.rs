@@ -1,8 +1,11 @@
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::button::Button;
+use gpui_component::input::{Input, InputState};
 use gpui_component::StyledExt;
 
+use crate::theme::current_theme;
+
 /// サイドバーで表示する会話エントリ情報
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ChatSidebarItem {
@@ -10,30 +13,212 @@ pub struct ChatSidebarItem {
     pub title: String,
     pub message_count: usize,
     pub active: bool,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    pub pinned: bool,
+    pub archived: bool,
+}
+
+impl ChatSidebarItem {
+    /// フォルダ・タグ・件数をまとめた補足行
+    pub fn subtitle(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(folder) = &self.folder {
+            parts.push(format!("📁 {}", folder));
+        }
+        if !self.tags.is_empty() {
+            parts.push(
+                self.tags
+                    .iter()
+                    .map(|tag| format!("#{}", tag))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+        parts.push(format!("{} messages", self.message_count));
+        parts.join(" · ")
+    }
+}
+
+/// サイドバー上部のフィルタ表示用データ
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChatSidebarFilter {
+    pub folders: Vec<String>,
+    pub tags: Vec<String>,
+    pub active_folder: Option<String>,
+    pub active_tag: Option<String>,
+    pub show_archived: bool,
+}
+
+/// タイトル検索・フォルダ・タグ・アーカイブ切り替えのフィルタバー
+pub fn chat_sidebar_filter_bar<V: Render>(
+    filter: &ChatSidebarFilter,
+    search_input: &Entity<InputState>,
+    on_folder_select: impl Fn(&mut V, Option<String>, &mut Context<V>) + 'static + Clone,
+    on_tag_select: impl Fn(&mut V, Option<String>, &mut Context<V>) + 'static + Clone,
+    on_toggle_archived: impl Fn(&mut V, &mut Context<V>) + 'static,
+    cx: &mut Context<V>,
+) -> Div {
+    fn chip(label: String, selected: bool) -> Div {
+        let colors = current_theme().colors;
+        div()
+            .px_2()
+            .rounded_sm()
+            .text_xs()
+            .cursor_pointer()
+            .when(selected, |d| d.bg(colors.selection).text_color(colors.text))
+            .when(!selected, |d| {
+                d.text_color(colors.text_muted)
+                    .hover(|style| style.bg(colors.hover))
+            })
+            .child(label)
+    }
+
+    let folder_row = div().h_flex().flex_wrap().gap_1().children(
+        std::iter::once(None)
+            .chain(filter.folders.iter().cloned().map(Some))
+            .map(|folder| {
+                let selected = filter.active_folder == folder;
+                let label = folder.clone().unwrap_or_else(|| "All".to_string());
+                let on_select = on_folder_select.clone();
+                chip(label, selected).on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, _, _window, cx| {
+                        on_select(this, folder.clone(), cx);
+                    }),
+                )
+            }),
+    );
+
+    let tag_row = div()
+        .h_flex()
+        .flex_wrap()
+        .gap_1()
+        .children(filter.tags.iter().map(|tag| {
+            let selected = filter.active_tag.as_deref() == Some(tag.as_str());
+            let next = if selected { None } else { Some(tag.clone()) };
+            let on_select = on_tag_select.clone();
+            chip(format!("#{}", tag), selected).on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, _window, cx| {
+                    on_select(this, next.clone(), cx);
+                }),
+            )
+        }));
+
+    let archive_label = if filter.show_archived {
+        "← Back to chats"
+    } else {
+        "Archived"
+    };
+    let archive_toggle = chip(archive_label.to_string(), filter.show_archived).on_mouse_down(
+        MouseButton::Left,
+        cx.listener(move |this, _, _window, cx| {
+            on_toggle_archived(this, cx);
+        }),
+    );
+
+    div()
+        .v_flex()
+        .gap_1()
+        .p_2()
+        .border_b_1()
+        .border_color(current_theme().colors.border)
+        .child(Input::new(search_input).text_xs())
+        .child(folder_row)
+        .when(!filter.tags.is_empty(), |d| d.child(tag_row))
+        .child(archive_toggle)
+}
+
+/// 開いている会話のフォルダとタグを編集する欄
+///
+/// 入力欄で Enter を押したときの処理（フォルダの移動・タグの追加）は呼び出し側で購読する。
+pub fn chat_sidebar_labels_editor<V: Render>(
+    item: &ChatSidebarItem,
+    folder_input: &Entity<InputState>,
+    tag_input: &Entity<InputState>,
+    on_remove_tag: impl Fn(&mut V, &str, &str, &mut Context<V>) + 'static + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    let colors = current_theme().colors;
+    let folder_label = match &item.folder {
+        Some(folder) => format!("Folder: {}", folder),
+        None => "No folder".to_string(),
+    };
+    let tags = item.tags.iter().map(|tag| {
+        let on_remove = on_remove_tag.clone();
+        let id = item.id.clone();
+        let tag_for_click = tag.clone();
+        div()
+            .h_flex()
+            .px_1()
+            .rounded_sm()
+            .text_xs()
+            .bg(colors.panel)
+            .text_color(colors.text_muted)
+            .child(format!("#{}", tag))
+            .child(
+                div()
+                    .px_1()
+                    .cursor_pointer()
+                    .hover(|style| style.text_color(colors.error_text))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, _window, cx| {
+                            on_remove(this, &id, &tag_for_click, cx);
+                        }),
+                    )
+                    .child("×"),
+            )
+    });
+
+    div()
+        .v_flex()
+        .gap_1()
+        .p_2()
+        .border_b_1()
+        .border_color(colors.border)
+        .child(
+            div()
+                .text_xs()
+                .text_color(colors.text_subtle)
+                .child(folder_label),
+        )
+        .child(Input::new(folder_input).text_xs())
+        .when(!item.tags.is_empty(), |d| {
+            d.child(div().h_flex().flex_wrap().gap_1().children(tags))
+        })
+        .child(Input::new(tag_input).text_xs())
 }
 
 /// チャットサイドバーコンポーネント
+#[allow(clippy::too_many_arguments)]
 pub fn chat_sidebar<V: Render>(
     items: &[ChatSidebarItem],
+    filter_bar: Option<Div>,
     on_new_chat: impl Fn(&mut V, &ClickEvent, &mut Window, &mut Context<V>) + 'static,
     on_conversation_click: impl Fn(&mut V, &str, &mut Window, &mut Context<V>) + 'static + Clone,
     on_delete_click: impl Fn(&mut V, &str, &mut Context<V>) + 'static + Clone,
+    on_pin_click: impl Fn(&mut V, &str, bool, &mut Context<V>) + 'static + Clone,
+    on_archive_click: impl Fn(&mut V, &str, bool, &mut Context<V>) + 'static + Clone,
     cx: &mut Context<V>,
 ) -> Div {
+    let colors = current_theme().colors;
     div()
-        .w(px(200.0))
+        .w(px(240.0))
         .h_full()
         .border_r_1()
-        .border_color(rgb(0x333333))
+        .border_color(colors.border)
         .v_flex()
         .child(
-            div().p_2().border_b_1().border_color(rgb(0x333333)).child(
+            div().p_2().border_b_1().border_color(colors.border).child(
                 Button::new(SharedString::from("new_chat"))
                     .label(SharedString::from("+ New Chat"))
                     .w_full()
                     .on_click(cx.listener(on_new_chat)),
             ),
         )
+        .when_some(filter_bar, |d, bar| d.child(bar))
         .child(
             div()
                 .flex_1()
@@ -41,15 +226,38 @@ pub fn chat_sidebar<V: Render>(
                 .child(div().v_flex().p_2().gap_2().children(items.iter().map({
                     let on_click = on_conversation_click.clone();
                     let on_delete = on_delete_click.clone();
+                    let on_pin = on_pin_click.clone();
+                    let on_archive = on_archive_click.clone();
                     move |item| {
                         let conv_id = item.id.clone();
                         let click_id = conv_id.clone();
                         let delete_id = conv_id.clone();
+                        let pin_id = conv_id.clone();
+                        let archive_id = conv_id.clone();
                         let on_click = on_click.clone();
                         let on_delete = on_delete.clone();
+                        let on_pin = on_pin.clone();
+                        let on_archive = on_archive.clone();
                         let is_active = item.active;
-                        let title = SharedString::from(item.title.clone());
-                        let message_count = item.message_count;
+                        let pinned = item.pinned;
+                        let archived = item.archived;
+                        let title = if pinned {
+                            SharedString::from(format!("📌 {}", item.title))
+                        } else {
+                            SharedString::from(item.title.clone())
+                        };
+                        let subtitle = item.subtitle();
+
+                        let action = |label: &'static str| {
+                            div()
+                                .p_1()
+                                .rounded_sm()
+                                .text_xs()
+                                .text_color(colors.text_subtle)
+                                .hover(|style| style.text_color(colors.text).bg(colors.hover))
+                                .cursor_pointer()
+                                .child(label)
+                        };
 
                         div()
                             .flex()
@@ -57,8 +265,8 @@ pub fn chat_sidebar<V: Render>(
                             .gap_2()
                             .p_2()
                             .rounded_md()
-                            .when(is_active, |div| div.bg(rgb(0x2a5a8a)))
-                            .when(!is_active, |div| div.hover(|style| style.bg(rgb(0x3a3a3a))))
+                            .when(is_active, |div| div.bg(colors.selection))
+                            .when(!is_active, |div| div.hover(|style| style.bg(colors.hover)))
                             .child(
                                 div()
                                     .flex_1()
@@ -84,8 +292,30 @@ pub fn chat_sidebar<V: Render>(
                                             .child(
                                                 div()
                                                     .text_xs()
-                                                    .text_color(rgb(0x888888))
-                                                    .child(format!("{} messages", message_count)),
+                                                    .text_color(colors.text_subtle)
+                                                    .child(subtitle),
+                                            ),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .flex_shrink_0()
+                                    .h_flex()
+                                    .child(
+                                        action(if pinned { "Unpin" } else { "Pin" }).on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(move |this, _, _window, cx| {
+                                                on_pin(this, &pin_id, !pinned, cx);
+                                            }),
+                                        ),
+                                    )
+                                    .child(
+                                        action(if archived { "Restore" } else { "Archive" })
+                                            .on_mouse_down(
+                                                MouseButton::Left,
+                                                cx.listener(move |this, _, _window, cx| {
+                                                    on_archive(this, &archive_id, !archived, cx);
+                                                }),
                                             ),
                                     ),
                             )
@@ -96,9 +326,9 @@ pub fn chat_sidebar<V: Render>(
                                         d.p_1()
                                             .rounded_sm()
                                             .text_sm()
-                                            .text_color(rgb(0x888888))
+                                            .text_color(colors.text_subtle)
                                             .hover(|style| {
-                                                style.text_color(rgb(0xff4444)).bg(rgb(0x444444))
+                                                style.text_color(colors.error_text).bg(colors.hover)
                                             })
                                             .cursor_pointer()
                                             .on_mouse_down(
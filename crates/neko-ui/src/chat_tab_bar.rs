@@ -0,0 +1,101 @@
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::StyledExt;
+
+use crate::theme::current_theme;
+
+/// タブバーに並べる会話タブ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatTabItem {
+    pub title: String,
+    pub active: bool,
+    /// 返答を生成中（タイトルの前に印を付ける）
+    pub busy: bool,
+}
+
+/// 会話タブのバー（クリックで切り替え、× で閉じる、+ で新しいタブ）
+pub fn chat_tab_bar<V: Render>(
+    items: &[ChatTabItem],
+    on_select: impl Fn(&mut V, usize, &mut Window, &mut Context<V>) + 'static + Clone,
+    on_close: impl Fn(&mut V, usize, &mut Window, &mut Context<V>) + 'static + Clone,
+    on_new: impl Fn(&mut V, &mut Window, &mut Context<V>) + 'static,
+    cx: &mut Context<V>,
+) -> Div {
+    let colors = current_theme().colors;
+    // 最後のタブは閉じられないので × を出さない
+    let closable = items.len() > 1;
+
+    let tabs = items.iter().enumerate().map(|(ix, item)| {
+        let on_select = on_select.clone();
+        let on_close = on_close.clone();
+        let title = if item.busy {
+            format!("● {}", item.title)
+        } else {
+            item.title.clone()
+        };
+
+        div()
+            .h_flex()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .max_w(px(220.0))
+            .border_r_1()
+            .border_color(colors.border)
+            .text_sm()
+            .cursor_pointer()
+            .when(item.active, |d| {
+                d.bg(colors.background).text_color(colors.text)
+            })
+            .when(!item.active, |d| {
+                d.text_color(colors.text_muted)
+                    .hover(|style| style.bg(colors.hover))
+            })
+            .child(div().flex_1().truncate().child(title))
+            .when(closable, |d| {
+                d.child(
+                    div()
+                        .px_1()
+                        .rounded_sm()
+                        .text_xs()
+                        .text_color(colors.text_subtle)
+                        .hover(|style| style.bg(colors.hover).text_color(colors.text))
+                        .child("×")
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, window, cx| {
+                                cx.stop_propagation();
+                                on_close(this, ix, window, cx)
+                            }),
+                        ),
+                )
+            })
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, window, cx| on_select(this, ix, window, cx)),
+            )
+    });
+
+    div()
+        .h_flex()
+        .w_full()
+        .flex_shrink_0()
+        .bg(colors.panel)
+        .border_b_1()
+        .border_color(colors.border)
+        .children(tabs)
+        .child(
+            div()
+                .px_3()
+                .py_1()
+                .text_sm()
+                .text_color(colors.text_muted)
+                .cursor_pointer()
+                .hover(|style| style.bg(colors.hover).text_color(colors.text))
+                .child("+")
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, _, window, cx| on_new(this, window, cx)),
+                ),
+        )
+}
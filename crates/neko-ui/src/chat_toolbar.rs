@@ -2,6 +2,8 @@ use gpui::*;
 use gpui_component::button::Button;
 use gpui_component::StyledExt;
 
+use crate::theme::current_theme;
+
 /// チャットビュー上部のツールバー
 pub fn chat_toolbar(
     plugins_button: Button,
@@ -21,7 +23,26 @@ pub fn chat_toolbar(
                 .flex_1()
                 .justify_end()
                 .text_sm()
-                .text_color(rgb(0x888888))
+                .text_color(current_theme().colors.text_subtle)
                 .child(builder_status.into()),
         )
 }
+
+/// 現在の会話の設定（モデル・プロンプト・ツール・生成オプション）をバッジで表示する行
+pub fn conversation_settings_row(items: &[String]) -> Div {
+    let colors = current_theme().colors;
+    items.iter().fold(
+        div().h_flex().flex_wrap().gap_2().items_center(),
+        |row, item| {
+            row.child(
+                div()
+                    .text_xs()
+                    .rounded(px(6.0))
+                    .p_1()
+                    .bg(colors.control)
+                    .text_color(colors.control_text)
+                    .child(item.clone()),
+            )
+        },
+    )
+}
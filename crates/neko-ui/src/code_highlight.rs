@@ -0,0 +1,148 @@
+//! コードブロックのシンタックスハイライト（syntect。テーマは配色テーマの `syntax_theme`）
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// 指定のテーマが syntect に無いときに使うテーマ
+const FALLBACK_THEME: &str = "base16-ocean.dark";
+
+/// 色付けされた文字列（色は 0xRRGGBB）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightedSpan {
+    pub text: String,
+    pub color: u32,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// コードブロックの背景色
+pub fn code_background(theme_name: &str) -> u32 {
+    theme(theme_name)
+        .settings
+        .background
+        .map(|c| to_rgb(c.r, c.g, c.b))
+        .unwrap_or(0x2b303b)
+}
+
+/// コードを行ごとに色付けする。言語が分からなければ素のテキストとして扱う
+pub fn highlight_code(
+    code: &str,
+    language: Option<&str>,
+    theme_name: &str,
+) -> Vec<Vec<HighlightedSpan>> {
+    let syntaxes = syntax_set();
+    let syntax = language
+        .and_then(|lang| {
+            syntaxes
+                .find_syntax_by_token(lang)
+                .or_else(|| syntaxes.find_syntax_by_token(&lang.to_ascii_lowercase()))
+        })
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, theme(theme_name));
+
+    let mut lines = Vec::new();
+    for line in LinesWithEndings::from(code) {
+        let ranges = match highlighter.highlight_line(line, syntaxes) {
+            Ok(ranges) => ranges,
+            Err(_) => {
+                lines.push(vec![plain_span(
+                    line.trim_end_matches(['\r', '\n']),
+                    theme_name,
+                )]);
+                continue;
+            }
+        };
+        let spans = ranges
+            .into_iter()
+            .filter_map(|(style, text)| {
+                let text = text.trim_end_matches(['\r', '\n']);
+                (!text.is_empty()).then(|| HighlightedSpan {
+                    text: text.to_string(),
+                    color: to_rgb(style.foreground.r, style.foreground.g, style.foreground.b),
+                    bold: style.font_style.contains(FontStyle::BOLD),
+                    italic: style.font_style.contains(FontStyle::ITALIC),
+                })
+            })
+            .collect();
+        lines.push(spans);
+    }
+    lines
+}
+
+fn plain_span(text: &str, theme_name: &str) -> HighlightedSpan {
+    HighlightedSpan {
+        text: text.to_string(),
+        color: theme(theme_name)
+            .settings
+            .foreground
+            .map(|c| to_rgb(c.r, c.g, c.b))
+            .unwrap_or(0xc0c5ce),
+        bold: false,
+        italic: false,
+    }
+}
+
+fn to_rgb(r: u8, g: u8, b: u8) -> u32 {
+    (u32::from(r) << 16) | (u32::from(g) << 8) | u32::from(b)
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme(name: &str) -> &'static Theme {
+    static THEMES: OnceLock<BTreeMap<String, Theme>> = OnceLock::new();
+    static DEFAULT: OnceLock<Theme> = OnceLock::new();
+    let themes = THEMES.get_or_init(|| ThemeSet::load_defaults().themes);
+    themes
+        .get(name)
+        .or_else(|| themes.get(FALLBACK_THEME))
+        .unwrap_or_else(|| DEFAULT.get_or_init(Theme::default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_text(line: &[HighlightedSpan]) -> String {
+        line.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn highlights_known_languages_per_line() {
+        let code = "fn main() {\n    let x = \"hi\";\n}";
+        let lines = highlight_code(code, Some("rust"), FALLBACK_THEME);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(line_text(&lines[1]), "    let x = \"hi\";");
+
+        // キーワードと文字列リテラルは別の色になる
+        let keyword = lines[0].iter().find(|s| s.text == "fn").unwrap();
+        let string = lines[1].iter().find(|s| s.text.contains("hi")).unwrap();
+        assert_ne!(keyword.color, string.color);
+    }
+
+    #[test]
+    fn falls_back_to_plain_text() {
+        let lines = highlight_code("a <b> c\n", Some("no-such-language"), FALLBACK_THEME);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(line_text(&lines[0]), "a <b> c");
+        assert_eq!(highlight_code("x", None, FALLBACK_THEME).len(), 1);
+    }
+
+    #[test]
+    fn uses_the_requested_theme() {
+        assert_ne!(
+            code_background("InspiredGitHub"),
+            code_background(FALLBACK_THEME)
+        );
+        assert_eq!(
+            code_background("no-such-theme"),
+            code_background(FALLBACK_THEME)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! コマンドパレット（あいまい検索で項目を絞り込み、クリックか Enter で実行する）
+
+use crate::theme::current_theme;
+use gpui::*;
+use gpui_component::input::{Input, InputState};
+use gpui_component::StyledExt;
+
+/// パレットの 1 項目
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteItem {
+    /// 表示名（検索対象）
+    pub label: String,
+    /// 分類（`Chat` / `View` / `Conversation` など）
+    pub category: String,
+    /// 割り当てられたキー（`ctrl-n` など）
+    pub shortcut: Option<String>,
+}
+
+/// クエリに一致する項目の位置を、よく一致する順に返す（空のクエリなら全件をそのままの順で）
+pub fn filter_palette_items(items: &[PaletteItem], query: &str) -> Vec<usize> {
+    let query = query.trim();
+    let mut scored: Vec<(i32, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let text = format!("{} {}", item.category, item.label);
+            fuzzy_score(query, &text).map(|score| (score, index))
+        })
+        .collect();
+    // 同点なら元の順序を保つ
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, index)| index).collect()
+}
+
+/// クエリの文字が順番どおりに含まれていれば点数を返す（大文字小文字は区別しない）。
+/// 連続した一致と単語の先頭での一致を高く評価する
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        if wanted.is_whitespace() {
+            continue;
+        }
+        let found = position + text[position..].iter().position(|c| *c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// パレットを描画する。`matches` は `filter_palette_items` の結果、`selected` はその中の位置
+pub fn command_palette<V: Render>(
+    input: &Entity<InputState>,
+    items: &[PaletteItem],
+    matches: &[usize],
+    selected: usize,
+    on_select: impl Fn(&mut V, usize, &mut Window, &mut Context<V>) + 'static + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    let colors = current_theme().colors;
+    let rows = matches.iter().enumerate().map(|(position, &index)| {
+        let item = &items[index];
+        let on_select = on_select.clone();
+        let row = div()
+            .h_flex()
+            .justify_between()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .rounded_sm()
+            .cursor_pointer()
+            .hover(|style| style.bg(colors.hover))
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(colors.text_subtle)
+                            .child(item.category.clone()),
+                    )
+                    .child(div().text_sm().child(item.label.clone())),
+            )
+            .children(item.shortcut.clone().map(|shortcut| {
+                div()
+                    .px_1()
+                    .rounded_sm()
+                    .bg(colors.control)
+                    .text_xs()
+                    .text_color(colors.control_text)
+                    .child(shortcut)
+            }))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, window, cx| on_select(this, index, window, cx)),
+            );
+        if position == selected {
+            row.bg(colors.selection)
+        } else {
+            row
+        }
+    });
+
+    let list = div()
+        .id("command-palette-items")
+        .v_flex()
+        .max_h(px(360.0))
+        .overflow_y_scroll()
+        .children(rows);
+
+    div()
+        .v_flex()
+        .gap_2()
+        .p_2()
+        .w(px(560.0))
+        .rounded_md()
+        .border_1()
+        .border_color(colors.border)
+        .bg(colors.panel)
+        .text_color(colors.text)
+        .shadow_lg()
+        .child(Input::new(input))
+        .child(if matches.is_empty() {
+            div()
+                .px_3()
+                .py_1()
+                .text_sm()
+                .text_color(colors.text_subtle)
+                .child("No matching commands")
+        } else {
+            div().child(list)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(category: &str, label: &str) -> PaletteItem {
+        PaletteItem {
+            label: label.into(),
+            category: category.into(),
+            shortcut: None,
+        }
+    }
+
+    #[test]
+    fn matches_subsequences_case_insensitively() {
+        assert!(fuzzy_score("nwcht", "New Chat").is_some());
+        assert!(fuzzy_score("NEW", "new chat").is_some());
+        assert!(fuzzy_score("chat new", "New Chat").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn ranks_word_starts_and_runs_first() {
+        let items = vec![
+            item("View", "Toggle console"),
+            item("Chat", "New chat"),
+            item("Model", "Switch model: phi4-mini"),
+        ];
+        assert_eq!(filter_palette_items(&items, ""), [0, 1, 2]);
+        assert_eq!(filter_palette_items(&items, "nc")[0], 1);
+        assert_eq!(filter_palette_items(&items, "phi"), [2]);
+        assert!(filter_palette_items(&items, "zzz").is_empty());
+    }
+}
@@ -2,7 +2,14 @@
 //!
 //! このクレートはNeko Assistantで使用するカスタムUIコンポーネントを提供します：
 //! - ChatBubble: チャットメッセージの表示
+//! - ToolCallCard: ツール呼び出しの開閉できるカード
+//! - command_palette: あいまい検索付きのコマンドパレット
+//! - markdown / markdown_view: アシスタントの返答の Markdown 解析と描画（コードはハイライト）
+//! - theme: 配色テーマ（組み込みの dark / light とテーマファイル）
+//! - chat_tab_bar: 会話タブのバー
+//! - scratchpad_console: スクラッチパッド（名前付きノート）とコンソール
 //! - ChatInput: IME対応の複数行入力（gpui-component Input のラッパー）
+//! - chat_input_panel: チャットの入力欄（スラッシュコマンドの候補付き）
 //! - その他の共通UIコンポーネント
 
 pub mod chat_bubble;
@@ -12,23 +19,45 @@ pub mod chat_main_panel;
 pub mod chat_message_list;
 pub mod chat_messages_panel;
 pub mod chat_sidebar;
+pub mod chat_tab_bar;
 pub mod chat_toolbar;
 pub mod chat_workspace;
+pub mod code_highlight;
+pub mod command_palette;
+pub mod markdown;
+pub mod markdown_view;
 pub mod mcp_status_panel;
 pub mod model_selector;
 pub mod model_selector_row;
 pub mod scratchpad_console;
+pub mod theme;
+pub mod tool_call_card;
 
 pub use chat_bubble::{ChatBubble, MessageType};
 pub use chat_input::{ChatInput, SendKeyConfig};
-pub use chat_input_panel::chat_input_panel;
+pub use chat_input_panel::{chat_input_panel, chat_input_panel_with_suggestions, SlashSuggestion};
 pub use chat_main_panel::chat_main_panel;
-pub use chat_message_list::{chat_message_list, ChatMessageRow};
+pub use chat_message_list::{chat_message_list, chat_message_row, ChatMessageRow, MessageAction};
 pub use chat_messages_panel::chat_messages_panel;
-pub use chat_sidebar::{chat_sidebar, ChatSidebarItem};
-pub use chat_toolbar::chat_toolbar;
+pub use chat_sidebar::{
+    chat_sidebar, chat_sidebar_filter_bar, chat_sidebar_labels_editor, ChatSidebarFilter,
+    ChatSidebarItem,
+};
+pub use chat_tab_bar::{chat_tab_bar, ChatTabItem};
+pub use chat_toolbar::{chat_toolbar, conversation_settings_row};
 pub use chat_workspace::chat_workspace;
+pub use command_palette::{command_palette, filter_palette_items, PaletteItem};
+pub use markdown::{parse_markdown, MarkdownBlock};
+pub use markdown_view::markdown_view;
 pub use mcp_status_panel::{mcp_status_panel, McpServerItem, McpServerStatusBadge, McpToolItem};
 pub use model_selector::{model_selector, ModelPreset};
 pub use model_selector_row::model_selector_row;
-pub use scratchpad_console::{scratchpad_console, ConsoleLogEntry};
+pub use scratchpad_console::{
+    console_filter_bar, console_log_sources, scratchpad_console, scratchpad_notes_bar,
+    ConsoleLogEntry, ConsoleLogFilter, ScratchpadNoteItem, CONSOLE_LOG_LEVELS,
+};
+pub use theme::{
+    current_theme, load_themes_from_dir, resolve_theme, set_current_theme, Theme, ThemeAppearance,
+    ThemeColor, ThemeColors,
+};
+pub use tool_call_card::ToolCallCard;
@@ -0,0 +1,479 @@
+//! Markdown（CommonMark + 表・取り消し線・タスクリスト）を表示用の要素モデルに変換する
+//!
+//! gpui に依存しない純粋な変換で、描画は `markdown_view` が行う。
+
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+/// インライン要素の装飾
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    /// インラインコード
+    pub code: bool,
+}
+
+/// 同じ装飾・リンクが続く文字列
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineSpan {
+    pub text: String,
+    pub style: SpanStyle,
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListItem {
+    /// タスクリストのチェック状態（通常の項目は None）
+    pub checked: Option<bool>,
+    pub blocks: Vec<MarkdownBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// フェンスの情報文字列の先頭の語（`rust` など）
+    pub language: Option<String>,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub alignments: Vec<ColumnAlignment>,
+    pub header: Vec<Vec<InlineSpan>>,
+    pub rows: Vec<Vec<Vec<InlineSpan>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownBlock {
+    Heading {
+        level: u8,
+        spans: Vec<InlineSpan>,
+    },
+    Paragraph(Vec<InlineSpan>),
+    /// `start` は番号付きリストの開始番号
+    List {
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    Quote(Vec<MarkdownBlock>),
+    Code(CodeBlock),
+    Table(Table),
+    Rule,
+}
+
+/// Markdown を要素モデルに変換する
+pub fn parse_markdown(source: &str) -> Vec<MarkdownBlock> {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut builder = Builder::new();
+    for event in Parser::new_ext(source, options) {
+        builder.push(event);
+    }
+    builder.finish()
+}
+
+/// 装飾を除いた文字列
+pub fn plain_text(spans: &[InlineSpan]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+enum Frame {
+    Root(Vec<MarkdownBlock>),
+    Quote(Vec<MarkdownBlock>),
+    List {
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    Item(ListItem),
+}
+
+#[derive(Default)]
+struct TableState {
+    alignments: Vec<ColumnAlignment>,
+    header: Vec<Vec<InlineSpan>>,
+    rows: Vec<Vec<Vec<InlineSpan>>>,
+    row: Vec<Vec<InlineSpan>>,
+}
+
+struct Builder {
+    frames: Vec<Frame>,
+    /// 組み立て中の段落・見出し・セル
+    spans: Option<Vec<InlineSpan>>,
+    heading: Option<u8>,
+    bold: usize,
+    italic: usize,
+    strikethrough: usize,
+    /// 開けないスキームのリンクは None（ただの文字列として表示する）
+    links: Vec<Option<String>>,
+    code: Option<CodeBlock>,
+    table: Option<TableState>,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Self {
+            frames: vec![Frame::Root(Vec::new())],
+            spans: None,
+            heading: None,
+            bold: 0,
+            italic: 0,
+            strikethrough: 0,
+            links: Vec::new(),
+            code: None,
+            table: None,
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code {
+                Some(code) => code.code.push_str(&text),
+                None => self.add_text(&text, false),
+            },
+            Event::Code(text) => self.add_text(&text, true),
+            Event::Html(text) | Event::InlineHtml(text) => self.add_text(&text, false),
+            Event::SoftBreak => self.add_text(" ", false),
+            Event::HardBreak => self.add_text("\n", false),
+            Event::Rule => {
+                self.flush_paragraph();
+                self.push_block(MarkdownBlock::Rule);
+            }
+            Event::TaskListMarker(checked) => {
+                if let Some(Frame::Item(item)) = self.frames.last_mut() {
+                    item.checked = Some(checked);
+                }
+            }
+            Event::FootnoteReference(name) => self.add_text(&format!("[^{}]", name), false),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::HtmlBlock => {
+                self.flush_paragraph();
+                self.spans = Some(Vec::new());
+            }
+            Tag::Heading { level, .. } => {
+                self.flush_paragraph();
+                self.heading = Some(level as u8);
+                self.spans = Some(Vec::new());
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.frames.push(Frame::Quote(Vec::new()));
+            }
+            Tag::List(start) => {
+                self.flush_paragraph();
+                self.frames.push(Frame::List {
+                    start,
+                    items: Vec::new(),
+                });
+            }
+            Tag::Item => self.frames.push(Frame::Item(ListItem {
+                checked: None,
+                blocks: Vec::new(),
+            })),
+            Tag::CodeBlock(kind) => {
+                self.flush_paragraph();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(|lang| lang.trim_matches(['{', '}', '.']).to_string())
+                        .filter(|lang| !lang.is_empty()),
+                    CodeBlockKind::Indented => None,
+                };
+                self.code = Some(CodeBlock {
+                    language,
+                    code: String::new(),
+                });
+            }
+            Tag::Table(alignments) => {
+                self.flush_paragraph();
+                self.table = Some(TableState {
+                    alignments: alignments.into_iter().map(column_alignment).collect(),
+                    ..Default::default()
+                });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.row.clear();
+                }
+            }
+            Tag::TableCell => self.spans = Some(Vec::new()),
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strikethrough += 1,
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                let link = is_openable_url(&dest_url).then(|| dest_url.to_string());
+                self.links.push(link)
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::HtmlBlock => self.flush_paragraph(),
+            TagEnd::Heading(_) => {
+                let spans = self.spans.take().unwrap_or_default();
+                let level = self.heading.take().unwrap_or(1);
+                self.push_block(MarkdownBlock::Heading { level, spans });
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_paragraph();
+                if let Some(Frame::Quote(blocks)) = self.frames.pop() {
+                    self.push_block(MarkdownBlock::Quote(blocks));
+                }
+            }
+            TagEnd::List(_) => {
+                if let Some(Frame::List { start, items }) = self.frames.pop() {
+                    self.push_block(MarkdownBlock::List { start, items });
+                }
+            }
+            TagEnd::Item => {
+                // 詰まったリストの項目は段落の開始・終了が無い
+                self.flush_paragraph();
+                if let Some(Frame::Item(item)) = self.frames.pop() {
+                    if let Some(Frame::List { items, .. }) = self.frames.last_mut() {
+                        items.push(item);
+                    }
+                }
+            }
+            TagEnd::CodeBlock => {
+                if let Some(mut code) = self.code.take() {
+                    if code.code.ends_with('\n') {
+                        code.code.pop();
+                    }
+                    self.push_block(MarkdownBlock::Code(code));
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.push_block(MarkdownBlock::Table(Table {
+                        alignments: table.alignments,
+                        header: table.header,
+                        rows: table.rows,
+                    }));
+                }
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.header = std::mem::take(&mut table.row);
+                }
+            }
+            TagEnd::TableRow => {
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+            }
+            TagEnd::TableCell => {
+                let cell = self.spans.take().unwrap_or_default();
+                if let Some(table) = &mut self.table {
+                    table.row.push(cell);
+                }
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.strikethrough = self.strikethrough.saturating_sub(1),
+            TagEnd::Link | TagEnd::Image => {
+                self.links.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn add_text(&mut self, text: &str, code: bool) {
+        let style = SpanStyle {
+            bold: self.bold > 0,
+            italic: self.italic > 0,
+            strikethrough: self.strikethrough > 0,
+            code,
+        };
+        let link = self.links.last().cloned().flatten();
+        let spans = self.spans.get_or_insert_with(Vec::new);
+        match spans.last_mut() {
+            Some(last) if last.style == style && last.link == link => last.text.push_str(text),
+            _ => spans.push(InlineSpan {
+                text: text.to_string(),
+                style,
+                link,
+            }),
+        }
+    }
+
+    fn flush_paragraph(&mut self) {
+        // 表のセルと見出しはそれぞれの終了タグで取り出す
+        if self.table.is_some() || self.heading.is_some() {
+            return;
+        }
+        if let Some(spans) = self.spans.take() {
+            if !spans.is_empty() {
+                self.push_block(MarkdownBlock::Paragraph(spans));
+            }
+        }
+    }
+
+    fn push_block(&mut self, block: MarkdownBlock) {
+        for frame in self.frames.iter_mut().rev() {
+            match frame {
+                Frame::Root(blocks) | Frame::Quote(blocks) => return blocks.push(block),
+                Frame::Item(item) => return item.blocks.push(block),
+                Frame::List { .. } => continue,
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<MarkdownBlock> {
+        self.flush_paragraph();
+        match self.frames.into_iter().next() {
+            Some(Frame::Root(blocks)) => blocks,
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// クリックで開いてよいリンク先か（http / https / mailto のみ）
+pub fn is_openable_url(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        ["http", "https", "mailto"]
+            .iter()
+            .any(|allowed| scheme.eq_ignore_ascii_case(allowed))
+    })
+}
+
+fn column_alignment(alignment: Alignment) -> ColumnAlignment {
+    match alignment {
+        Alignment::None => ColumnAlignment::None,
+        Alignment::Left => ColumnAlignment::Left,
+        Alignment::Center => ColumnAlignment::Center,
+        Alignment::Right => ColumnAlignment::Right,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[InlineSpan]) -> String {
+        plain_text(spans)
+    }
+
+    #[test]
+    fn parses_headings_paragraphs_and_inline_styles() {
+        let blocks =
+            parse_markdown("# Title\n\nUse **bold**, `code` and [docs](https://example.com).\n");
+        assert_eq!(blocks.len(), 2);
+        let MarkdownBlock::Heading { level, spans } = &blocks[0] else {
+            panic!("expected heading: {:?}", blocks[0]);
+        };
+        assert_eq!((*level, text(spans).as_str()), (1, "Title"));
+
+        let MarkdownBlock::Paragraph(spans) = &blocks[1] else {
+            panic!("expected paragraph: {:?}", blocks[1]);
+        };
+        assert_eq!(text(spans), "Use bold, code and docs.");
+        assert!(spans.iter().any(|s| s.text == "bold" && s.style.bold));
+        assert!(spans.iter().any(|s| s.text == "code" && s.style.code));
+        let link = spans.iter().find(|s| s.text == "docs").unwrap();
+        assert_eq!(link.link.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn parses_nested_lists_and_task_items() {
+        let blocks = parse_markdown("3. first\n4. second\n   - [x] done\n   - [ ] todo\n");
+        let MarkdownBlock::List { start, items } = &blocks[0] else {
+            panic!("expected list: {:?}", blocks);
+        };
+        assert_eq!(*start, Some(3));
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0].blocks,
+            [MarkdownBlock::Paragraph(vec![InlineSpan {
+                text: "first".into(),
+                style: SpanStyle::default(),
+                link: None,
+            }])]
+        );
+        let MarkdownBlock::List {
+            start,
+            items: nested,
+        } = &items[1].blocks[1]
+        else {
+            panic!("expected nested list: {:?}", items[1]);
+        };
+        assert_eq!(*start, None);
+        assert_eq!(nested[0].checked, Some(true));
+        assert_eq!(nested[1].checked, Some(false));
+    }
+
+    #[test]
+    fn keeps_fenced_code_verbatim() {
+        let blocks = parse_markdown("```rust title=main.rs\nfn main() {\n    println!(\"**hi**\");\n}\n```\n\n    indented\n");
+        assert_eq!(
+            blocks,
+            [
+                MarkdownBlock::Code(CodeBlock {
+                    language: Some("rust".into()),
+                    code: "fn main() {\n    println!(\"**hi**\");\n}".into(),
+                }),
+                MarkdownBlock::Code(CodeBlock {
+                    language: None,
+                    code: "indented".into(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_tables_quotes_and_rules() {
+        let source = "| Name | Size |\n|:-----|-----:|\n| a | **1** |\n\n> quoted\n\n---\n";
+        let blocks = parse_markdown(source);
+        let MarkdownBlock::Table(table) = &blocks[0] else {
+            panic!("expected table: {:?}", blocks);
+        };
+        assert_eq!(
+            table.alignments,
+            [ColumnAlignment::Left, ColumnAlignment::Right]
+        );
+        assert_eq!(text(&table.header[1]), "Size");
+        assert_eq!(table.rows.len(), 1);
+        assert!(table.rows[0][1][0].style.bold);
+
+        let MarkdownBlock::Quote(inner) = &blocks[1] else {
+            panic!("expected quote: {:?}", blocks[1]);
+        };
+        assert!(matches!(&inner[0], MarkdownBlock::Paragraph(spans) if text(spans) == "quoted"));
+        assert_eq!(blocks[2], MarkdownBlock::Rule);
+    }
+
+    #[test]
+    fn drops_links_with_unsafe_schemes() {
+        let blocks = parse_markdown(
+            "[a](file:///etc/passwd) [b](smb://host/x) [c](mailto:me@example.com) [d](HTTPS://example.com)",
+        );
+        let MarkdownBlock::Paragraph(spans) = &blocks[0] else {
+            panic!("expected paragraph: {:?}", blocks);
+        };
+        let links: Vec<_> = spans
+            .iter()
+            .filter_map(|span| span.link.as_deref())
+            .collect();
+        assert_eq!(links, ["mailto:me@example.com", "HTTPS://example.com"]);
+        assert_eq!(text(spans), "a b c d");
+        assert!(!is_openable_url("javascript:alert(1)"));
+        assert!(!is_openable_url("relative/path"));
+    }
+}
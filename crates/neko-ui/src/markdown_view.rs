@@ -0,0 +1,296 @@
+//! Markdown の要素モデルを gpui の要素として描画する
+
+use crate::code_highlight::{code_background, highlight_code, HighlightedSpan};
+use crate::markdown::{
+    parse_markdown, CodeBlock, ColumnAlignment, InlineSpan, ListItem, MarkdownBlock, SpanStyle,
+    Table,
+};
+use crate::theme::current_theme;
+use gpui::*;
+use gpui_component::button::Button;
+use gpui_component::StyledExt;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// コードブロックのフォント（見つからなければ gpui の既定フォントで代替される）
+const CODE_FONT: &str = "Consolas";
+/// 解析・ハイライト結果のキャッシュ上限（超えたら捨てて作り直す）
+const CACHE_LIMIT: usize = 256;
+
+type HighlightedLines = Vec<Vec<HighlightedSpan>>;
+
+/// Markdown を描画する。再描画のたびに解析しないよう結果を内容のハッシュでキャッシュする
+pub fn markdown_view(source: &str) -> Div {
+    let key = hash_of(&source);
+    let blocks = cached(parse_cache(), key, || parse_markdown(source));
+    let mut ids = ElementIds { key, next: 0 };
+    div()
+        .v_flex()
+        .gap_2()
+        .children(blocks.iter().map(|block| render_block(block, &mut ids)))
+}
+
+/// 1 つのメッセージ内で一意な要素 ID を払い出す
+struct ElementIds {
+    key: u64,
+    next: usize,
+}
+
+impl ElementIds {
+    fn next(&mut self, name: &str) -> ElementId {
+        self.next += 1;
+        SharedString::from(format!("md-{}-{:x}-{}", name, self.key, self.next)).into()
+    }
+}
+
+fn render_block(block: &MarkdownBlock, ids: &mut ElementIds) -> AnyElement {
+    let colors = &current_theme().colors.markdown;
+    match block {
+        MarkdownBlock::Heading { level, spans } => {
+            let heading = div().font_weight(FontWeight::BOLD);
+            let heading = match level {
+                1 => heading.text_xl(),
+                2 => heading.text_lg(),
+                _ => heading.text_base(),
+            };
+            heading.child(inline_text(spans, ids)).into_any_element()
+        }
+        MarkdownBlock::Paragraph(spans) => div().child(inline_text(spans, ids)).into_any_element(),
+        MarkdownBlock::List { start, items } => render_list(*start, items, ids),
+        MarkdownBlock::Quote(blocks) => div()
+            .v_flex()
+            .gap_1()
+            .pl_2()
+            .border_l_2()
+            .border_color(colors.quote_border)
+            .opacity(0.9)
+            .children(blocks.iter().map(|block| render_block(block, ids)))
+            .into_any_element(),
+        MarkdownBlock::Code(code) => render_code_block(code, ids),
+        MarkdownBlock::Table(table) => render_table(table, ids),
+        MarkdownBlock::Rule => div().w_full().h(px(1.0)).bg(colors.rule).into_any_element(),
+    }
+}
+
+fn render_list(start: Option<u64>, items: &[ListItem], ids: &mut ElementIds) -> AnyElement {
+    div()
+        .v_flex()
+        .gap_1()
+        .children(items.iter().enumerate().map(|(index, item)| {
+            let marker = match (item.checked, start) {
+                (Some(true), _) => "☑".to_string(),
+                (Some(false), _) => "☐".to_string(),
+                (None, Some(start)) => format!("{}.", start + index as u64),
+                (None, None) => "•".to_string(),
+            };
+            div()
+                .h_flex()
+                .items_start()
+                .gap_1()
+                .child(div().min_w(px(18.0)).child(marker))
+                .child(
+                    div()
+                        .v_flex()
+                        .gap_1()
+                        .flex_1()
+                        .children(item.blocks.iter().map(|block| render_block(block, ids))),
+                )
+        }))
+        .into_any_element()
+}
+
+fn render_code_block(block: &CodeBlock, ids: &mut ElementIds) -> AnyElement {
+    let theme = current_theme();
+    let key = hash_of(&(&block.language, &block.code, &theme.syntax_theme));
+    let lines = cached(highlight_cache(), key, || {
+        highlight_code(&block.code, block.language.as_deref(), &theme.syntax_theme)
+    });
+
+    let mut text = String::new();
+    let mut highlights = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            text.push('\n');
+        }
+        for span in line {
+            let start = text.len();
+            text.push_str(&span.text);
+            highlights.push((
+                start..text.len(),
+                HighlightStyle {
+                    color: Some(rgb(span.color).into()),
+                    font_weight: span.bold.then_some(FontWeight::BOLD),
+                    font_style: span.italic.then_some(FontStyle::Italic),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+
+    let code = block.code.clone();
+    let header = div()
+        .h_flex()
+        .justify_between()
+        .items_center()
+        .px_2()
+        .pt_1()
+        .child(
+            div()
+                .text_xs()
+                .text_color(theme.colors.markdown.code_label)
+                .child(block.language.clone().unwrap_or_else(|| "text".into())),
+        )
+        .child(
+            Button::new(ids.next("copy"))
+                .label("Copy")
+                .on_click(move |_, _window, cx| {
+                    cx.write_to_clipboard(ClipboardItem::new_string(code.clone()));
+                }),
+        );
+
+    div()
+        .v_flex()
+        .w_full()
+        .rounded_md()
+        .bg(rgb(code_background(&theme.syntax_theme)))
+        .child(header)
+        .child(
+            div()
+                .p_2()
+                .font_family(CODE_FONT)
+                .text_xs()
+                .overflow_hidden()
+                .child(StyledText::new(text).with_highlights(highlights)),
+        )
+        .into_any_element()
+}
+
+fn render_table(table: &Table, ids: &mut ElementIds) -> AnyElement {
+    let border = current_theme().colors.markdown.table_border;
+    let row = |cells: &[Vec<InlineSpan>], header: bool, ids: &mut ElementIds| {
+        let row = div().h_flex().w_full();
+        let row = if header {
+            row.font_weight(FontWeight::BOLD)
+        } else {
+            row
+        };
+        row.children(cells.iter().enumerate().map(|(column, cell)| {
+            let alignment = table
+                .alignments
+                .get(column)
+                .copied()
+                .unwrap_or(ColumnAlignment::None);
+            let cell_div = div()
+                .flex()
+                .flex_1()
+                .min_w(px(0.0))
+                .px_2()
+                .py_1()
+                .border_1()
+                .border_color(border);
+            let cell_div = match alignment {
+                ColumnAlignment::Center => cell_div.justify_center(),
+                ColumnAlignment::Right => cell_div.justify_end(),
+                ColumnAlignment::None | ColumnAlignment::Left => cell_div.justify_start(),
+            };
+            cell_div.child(inline_text(cell, ids))
+        }))
+    };
+
+    let mut rows = vec![row(&table.header, true, ids)];
+    for cells in &table.rows {
+        rows.push(row(cells, false, ids));
+    }
+    div().v_flex().w_full().children(rows).into_any_element()
+}
+
+/// 装飾付きの文字列を 1 つの StyledText にまとめ、リンクはクリックで開けるようにする
+fn inline_text(spans: &[InlineSpan], ids: &mut ElementIds) -> AnyElement {
+    let mut text = String::new();
+    let mut highlights = Vec::new();
+    let mut links: Vec<(Range<usize>, String)> = Vec::new();
+    for span in spans {
+        let start = text.len();
+        text.push_str(&span.text);
+        let range = start..text.len();
+        if span.style != SpanStyle::default() || span.link.is_some() {
+            highlights.push((range.clone(), span_highlight(span)));
+        }
+        if let Some(url) = &span.link {
+            links.push((range, url.clone()));
+        }
+    }
+
+    let styled = StyledText::new(text).with_highlights(highlights);
+    if links.is_empty() {
+        return styled.into_any_element();
+    }
+    let (ranges, urls): (Vec<_>, Vec<_>) = links.into_iter().unzip();
+    InteractiveText::new(ids.next("link"), styled)
+        .on_click(ranges, move |index, _window, cx| {
+            if let Some(url) = urls.get(index) {
+                cx.open_url(url);
+            }
+        })
+        .into_any_element()
+}
+
+fn span_highlight(span: &InlineSpan) -> HighlightStyle {
+    let colors = &current_theme().colors.markdown;
+    let mut style = HighlightStyle::default();
+    if span.style.bold {
+        style.font_weight = Some(FontWeight::BOLD);
+    }
+    if span.style.italic {
+        style.font_style = Some(FontStyle::Italic);
+    }
+    if span.style.strikethrough {
+        style.strikethrough = Some(StrikethroughStyle {
+            thickness: px(1.0),
+            ..Default::default()
+        });
+    }
+    if span.style.code {
+        style.background_color = Some(colors.inline_code_bg.into());
+    }
+    if span.link.is_some() {
+        style.color = Some(colors.link.into());
+        style.underline = Some(UnderlineStyle {
+            thickness: px(1.0),
+            ..Default::default()
+        });
+    }
+    style
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cached<T>(cache: &Mutex<HashMap<u64, Arc<T>>>, key: u64, compute: impl FnOnce() -> T) -> Arc<T> {
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(value) = cache.get(&key) {
+        return value.clone();
+    }
+    if cache.len() >= CACHE_LIMIT {
+        cache.clear();
+    }
+    let value = Arc::new(compute());
+    cache.insert(key, value.clone());
+    value
+}
+
+fn parse_cache() -> &'static Mutex<HashMap<u64, Arc<Vec<MarkdownBlock>>>> {
+    static CACHE: OnceLock<Mutex<HashMap<u64, Arc<Vec<MarkdownBlock>>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+fn highlight_cache() -> &'static Mutex<HashMap<u64, Arc<HighlightedLines>>> {
+    static CACHE: OnceLock<Mutex<HashMap<u64, Arc<HighlightedLines>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
@@ -3,6 +3,8 @@ use gpui_component::accordion::Accordion;
 use gpui_component::button::Button;
 use gpui_component::StyledExt;
 
+use crate::theme::current_theme;
+
 trait OverflowScrollExt: Sized {
     fn overflow_y_scroll(self) -> Self;
 }
@@ -42,6 +44,7 @@ pub fn mcp_status_panel(
     refresh_button: Button,
     manage_button: Button,
 ) -> Div {
+    let colors = current_theme().colors;
     fn scroll_container(content: Div) -> Div {
         div()
             .max_h(px(200.0))
@@ -53,12 +56,7 @@ pub fn mcp_status_panel(
         .h_flex()
         .items_center()
         .justify_between()
-        .child(
-            div()
-                .text_sm()
-                .text_color(rgb(0xffffff))
-                .child("MCP Status"),
-        )
+        .child(div().text_sm().text_color(colors.text).child("MCP Status"))
         .child(
             div()
                 .h_flex()
@@ -70,7 +68,7 @@ pub fn mcp_status_panel(
     let server_section = if servers.is_empty() {
         div()
             .text_sm()
-            .text_color(rgb(0x999999))
+            .text_color(colors.text_subtle)
             .child("No MCP servers configured.")
     } else {
         let rows = servers.iter().map(|item| {
@@ -84,14 +82,14 @@ pub fn mcp_status_panel(
                         .flex_1()
                         .min_w(px(0.0))
                         .text_sm()
-                        .text_color(rgb(0xffffff))
+                        .text_color(colors.text)
                         .child(item.name.clone()),
                 )
                 .child(status_badge)
                 .child(
                     div()
                         .text_xs()
-                        .text_color(rgb(0xaaaaaa))
+                        .text_color(colors.text_muted)
                         .child(format!("{} tools", item.tool_count)),
                 )
         });
@@ -101,7 +99,7 @@ pub fn mcp_status_panel(
     let tools_section: AnyElement = if tools.is_empty() {
         div()
             .text_sm()
-            .text_color(rgb(0x999999))
+            .text_color(colors.text_subtle)
             .child("No tools reported yet.")
             .into_any_element()
     } else {
@@ -112,19 +110,19 @@ pub fn mcp_status_panel(
                 .child(
                     div()
                         .text_sm()
-                        .text_color(rgb(0xffffff))
+                        .text_color(colors.text)
                         .child(tool.tool_name.clone()),
                 )
                 .child(
                     div()
                         .text_xs()
-                        .text_color(rgb(0xaaaaaa))
+                        .text_color(colors.text_muted)
                         .child(format!("@{}", tool.server_name)),
                 )
                 .child(
                     div()
                         .text_xs()
-                        .text_color(rgb(0xcccccc))
+                        .text_color(colors.text_muted)
                         .child(tool.description.clone()),
                 )
         });
@@ -149,25 +147,27 @@ pub fn mcp_status_panel(
 }
 
 fn section_title(label: &str, count: usize) -> Div {
+    let colors = current_theme().colors;
     let label_text = label.to_string();
     div()
         .h_flex()
         .items_center()
         .justify_between()
-        .child(div().text_sm().text_color(rgb(0xffffff)).child(label_text))
+        .child(div().text_sm().text_color(colors.text).child(label_text))
         .child(
             div()
                 .text_xs()
-                .text_color(rgb(0x888888))
+                .text_color(colors.text_subtle)
                 .child(format!("{}", count)),
         )
 }
 
 fn render_status_badge(status: &McpServerStatusBadge, message: Option<&str>) -> Div {
+    let colors = current_theme().colors;
     let (label, bg_color, text_color) = match status {
-        McpServerStatusBadge::Unknown => ("Unknown", rgb(0x444444), rgb(0xcccccc)),
-        McpServerStatusBadge::Ready => ("Ready", rgb(0x1d8348), rgb(0xffffff)),
-        McpServerStatusBadge::Error => ("Error", rgb(0x922b21), rgb(0xffffff)),
+        McpServerStatusBadge::Unknown => ("Unknown", colors.control, colors.control_text),
+        McpServerStatusBadge::Ready => ("Ready", colors.success, colors.on_accent),
+        McpServerStatusBadge::Error => ("Error", colors.error, colors.on_accent),
     };
 
     let mut row = div().h_flex().gap_1().items_center().child(
@@ -185,7 +185,7 @@ fn render_status_badge(status: &McpServerStatusBadge, message: Option<&str>) ->
         row = row.child(
             div()
                 .text_xs()
-                .text_color(rgb(0xf1948a))
+                .text_color(colors.error_text)
                 .child(msg.to_string()),
         );
     }
@@ -3,6 +3,7 @@ use gpui_component::select::SelectState;
 use gpui_component::StyledExt;
 
 use crate::model_selector::{model_selector, ModelPreset};
+use crate::theme::current_theme;
 
 /// モデルセレクタのラベルと入力行をまとめた行コンポーネント
 pub fn model_selector_row(
@@ -10,14 +11,29 @@ pub fn model_selector_row(
     has_prompt_builder: bool,
     has_adapter: bool,
 ) -> Div {
+    let colors = current_theme().colors;
+    let badge_bg = |active: bool| {
+        if active {
+            colors.success
+        } else {
+            colors.control
+        }
+    };
+    let badge_text = |active: bool| {
+        if active {
+            colors.on_accent
+        } else {
+            colors.control_text
+        }
+    };
     div()
         .border_t_1()
-        .border_color(rgb(0x333333))
-        .bg(rgb(0x101010))
+        .border_color(colors.border)
+        .bg(colors.surface)
         .p_2()
         .v_flex()
         .gap_1()
-        .child(div().text_sm().text_color(rgb(0xaaaaaa)).child("Model"))
+        .child(div().text_sm().text_color(colors.text_muted).child("Model"))
         .child(
             div()
                 .h_flex()
@@ -31,8 +47,8 @@ pub fn model_selector_row(
                             .text_xs()
                             .rounded(px(6.0))
                             .p_1()
-                            .bg(if has_prompt_builder { rgb(0x153f00) } else { rgb(0x2b2b2b) })
-                            .text_color(rgb(0xffffff))
+                            .bg(badge_bg(has_prompt_builder))
+                            .text_color(badge_text(has_prompt_builder))
                             .child(if has_prompt_builder { "専用プロンプトあり" } else { "専用プロンプト無し" })
                         )
                         // Adapter/plugin badge (new)
@@ -40,8 +56,8 @@ pub fn model_selector_row(
                             .text_xs()
                             .rounded(px(6.0))
                             .p_1()
-                            .bg(if has_adapter { rgb(0x153f00) } else { rgb(0x2b2b2b) })
-                            .text_color(rgb(0xffffff))
+                            .bg(badge_bg(has_adapter))
+                            .text_color(badge_text(has_adapter))
                             .child(if has_adapter { "アダプタあり" } else { "アダプタ無し" })
                         ),
                 ),
@@ -1,37 +1,327 @@
+use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::input::{Input, InputState};
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::StyledExt;
 
+use crate::theme::current_theme;
+
 /// ログ表示用のエントリ
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConsoleLogEntry {
     pub role_label: String,
     pub content: String,
+    /// 表示用の時刻（例: `12:34:56`）
+    pub time_label: String,
+    /// [`CONSOLE_LOG_LEVELS`] のいずれか
+    pub level: String,
+    /// 発生元（`prompt_builder` / `langchain` / `mcp:<サーバー>` など）
+    pub source: String,
+    pub request_id: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+impl ConsoleLogEntry {
+    /// 本文の上に出す 1 行（時刻・レベル・発生元・リクエスト ID・所要時間）
+    pub fn header(&self) -> String {
+        let mut parts = vec![
+            self.time_label.clone(),
+            self.level.to_uppercase(),
+            self.source.clone(),
+            self.role_label.clone(),
+        ];
+        if let Some(request_id) = &self.request_id {
+            parts.push(format!("#{}", request_id));
+        }
+        if let Some(ms) = self.duration_ms {
+            parts.push(format_duration(ms));
+        }
+        parts.retain(|part| !part.is_empty());
+        parts.join(" ")
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+/// 重要度の低い順に並べたログのレベル
+pub const CONSOLE_LOG_LEVELS: [&str; 4] = ["debug", "info", "warn", "error"];
+
+/// コンソールの絞り込み条件
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsoleLogFilter {
+    /// 本文・発生元・リクエスト ID に含まれる文字列（大文字小文字を区別しない）
+    pub query: String,
+    /// このレベル以上だけを表示する（None ならすべて）
+    pub min_level: Option<String>,
+    /// この発生元だけを表示する（None ならすべて）
+    pub source: Option<String>,
+}
+
+impl ConsoleLogFilter {
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty() || self.min_level.is_some() || self.source.is_some()
+    }
+
+    pub fn matches(&self, entry: &ConsoleLogEntry) -> bool {
+        let level_rank = |level: &str| CONSOLE_LOG_LEVELS.iter().position(|l| *l == level);
+        if let Some(min_level) = &self.min_level {
+            if level_rank(&entry.level) < level_rank(min_level) {
+                return false;
+            }
+        }
+        if self
+            .source
+            .as_ref()
+            .is_some_and(|source| *source != entry.source)
+        {
+            return false;
+        }
+        let query = self.query.trim().to_lowercase();
+        query.is_empty()
+            || [
+                entry.content.as_str(),
+                entry.source.as_str(),
+                entry.request_id.as_deref().unwrap_or_default(),
+            ]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&query))
+    }
+}
+
+/// ログに現れる発生元（名前順、重複なし）
+pub fn console_log_sources(logs: &[ConsoleLogEntry]) -> Vec<String> {
+    let mut sources: Vec<String> = logs.iter().map(|entry| entry.source.clone()).collect();
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
+/// コンソールの検索欄・レベルと発生元の絞り込み・書き出しボタン
+pub fn console_filter_bar<V: Render>(
+    filter: &ConsoleLogFilter,
+    sources: &[String],
+    search_input: &Entity<InputState>,
+    on_level: impl Fn(&mut V, Option<String>, &mut Context<V>) + 'static + Clone,
+    on_source: impl Fn(&mut V, Option<String>, &mut Context<V>) + 'static + Clone,
+    on_export: impl Fn(&mut V, &mut Context<V>) + 'static,
+    cx: &mut Context<V>,
+) -> Div {
+    fn chip(label: String, selected: bool) -> Div {
+        let colors = current_theme().colors;
+        div()
+            .px_2()
+            .rounded_sm()
+            .text_xs()
+            .cursor_pointer()
+            .when(selected, |d| d.bg(colors.selection).text_color(colors.text))
+            .when(!selected, |d| {
+                d.text_color(colors.text_muted)
+                    .hover(|style| style.bg(colors.hover))
+            })
+            .child(label)
+    }
+
+    // debug はすべてと同じなので「All」にまとめる
+    let levels = std::iter::once(None)
+        .chain(
+            CONSOLE_LOG_LEVELS[1..]
+                .iter()
+                .map(|level| Some(level.to_string())),
+        )
+        .map(|level| {
+            let selected = filter.min_level == level;
+            let label = match &level {
+                Some(level) => format!("{}+", level),
+                None => "All".to_string(),
+            };
+            let on_level = on_level.clone();
+            chip(label, selected).on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, _window, cx| on_level(this, level.clone(), cx)),
+            )
+        });
+
+    let source_chips = sources.iter().map(|source| {
+        let selected = filter.source.as_deref() == Some(source.as_str());
+        let next = if selected { None } else { Some(source.clone()) };
+        let on_source = on_source.clone();
+        chip(source.clone(), selected).on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, _, _window, cx| on_source(this, next.clone(), cx)),
+        )
+    });
+
+    div()
+        .v_flex()
+        .gap_1()
+        .child(
+            div()
+                .h_flex()
+                .items_center()
+                .gap_1()
+                .child(div().flex_1().child(Input::new(search_input).text_xs()))
+                .children(levels)
+                .child(chip("Export".to_string(), false).on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, _, _window, cx| on_export(this, cx)),
+                )),
+        )
+        .when(!sources.is_empty(), |d| {
+            d.child(div().h_flex().flex_wrap().gap_1().children(source_chips))
+        })
+}
+
+/// スクラッチパッドのノート一覧に並べるノート
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScratchpadNoteItem {
+    pub name: String,
+    /// 編集中のノート
+    pub active: bool,
+    /// 会話のプロンプトに含めている
+    pub in_context: bool,
+}
+
+/// ノートの切り替え・コンテキストへの追加・新規作成と、選択範囲をチャットへ送るボタン
+pub fn scratchpad_notes_bar<V: Render>(
+    notes: &[ScratchpadNoteItem],
+    on_open: impl Fn(&mut V, &str, &mut Window, &mut Context<V>) + 'static + Clone,
+    on_toggle_context: impl Fn(&mut V, &str, &mut Window, &mut Context<V>) + 'static + Clone,
+    on_new: impl Fn(&mut V, &mut Window, &mut Context<V>) + 'static,
+    on_send_selection: impl Fn(&mut V, &mut Window, &mut Context<V>) + 'static,
+    cx: &mut Context<V>,
+) -> Div {
+    let colors = current_theme().colors;
+
+    let chips = notes.iter().map(|note| {
+        let on_open = on_open.clone();
+        let on_toggle_context = on_toggle_context.clone();
+        let open_name = note.name.clone();
+        let toggle_name = note.name.clone();
+
+        div()
+            .h_flex()
+            .gap_1()
+            .px_2()
+            .rounded_sm()
+            .border_1()
+            .border_color(colors.border)
+            .text_xs()
+            .cursor_pointer()
+            .when(note.active, |d| {
+                d.bg(colors.selection).text_color(colors.text)
+            })
+            .when(!note.active, |d| {
+                d.text_color(colors.text_muted)
+                    .hover(|style| style.bg(colors.hover))
+            })
+            // 左の印でプロンプトに含めるかを切り替える
+            .child(
+                div()
+                    .text_color(if note.in_context {
+                        colors.accent
+                    } else {
+                        colors.text_subtle
+                    })
+                    .child(if note.in_context { "◆" } else { "◇" })
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, window, cx| {
+                            cx.stop_propagation();
+                            on_toggle_context(this, &toggle_name, window, cx)
+                        }),
+                    ),
+            )
+            .child(note.name.clone())
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, window, cx| on_open(this, &open_name, window, cx)),
+            )
+    });
+
+    let button = |label: &'static str| {
+        div()
+            .px_2()
+            .rounded_sm()
+            .text_xs()
+            .text_color(colors.text_muted)
+            .cursor_pointer()
+            .hover(|style| style.bg(colors.hover).text_color(colors.text))
+            .child(label)
+    };
+
+    div()
+        .h_flex()
+        .flex_wrap()
+        .items_center()
+        .gap_1()
+        .children(chips)
+        .child(button("+").on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, _, window, cx| on_new(this, window, cx)),
+        ))
+        .child(button("Send selection").on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, _, window, cx| on_send_selection(this, window, cx)),
+        ))
 }
 
 /// スクラッチパッドと簡易コンソールを並べて表示するコンポーネント
+///
+/// `notes_bar` は [`scratchpad_notes_bar`] で作ったノートの一覧（見出しの横に置く）。
+/// コンソールには `filter` に合うログだけを表示し、`filter_bar`（[`console_filter_bar`]）を見出しの下に置く。
 pub fn scratchpad_console(
     editor_input: &Entity<InputState>,
+    notes_bar: Div,
     logs: &[ConsoleLogEntry],
+    filter: &ConsoleLogFilter,
+    filter_bar: Div,
     show_scratchpad: bool,
     show_console: bool,
 ) -> Div {
-    let console_items: Vec<_> = if logs.is_empty() {
+    let colors = current_theme().colors;
+    let visible: Vec<&ConsoleLogEntry> = logs.iter().filter(|e| filter.matches(e)).collect();
+    let console_items: Vec<_> = if visible.is_empty() {
+        let message = if logs.is_empty() {
+            "No messages yet"
+        } else {
+            "No matching logs"
+        };
         vec![div()
             .text_xs()
-            .text_color(rgb(0x777777))
-            .child("No messages yet")]
+            .text_color(colors.text_subtle)
+            .child(message)]
     } else {
-        logs.iter()
+        visible
+            .iter()
             .map(|entry| {
+                let header_color = match entry.level.as_str() {
+                    "error" => colors.error,
+                    "warn" => colors.accent,
+                    _ => colors.text_subtle,
+                };
                 div()
+                    .v_flex()
                     .text_xs()
-                    .text_color(rgb(0xcccccc))
-                    .child(format!("[{}] {}", entry.role_label, entry.content))
+                    .child(div().text_color(header_color).child(entry.header()))
+                    .child(
+                        div()
+                            .text_color(colors.text_muted)
+                            .child(entry.content.clone()),
+                    )
             })
             .collect()
     };
+    let title = if filter.is_active() {
+        format!("Console ({} / {})", visible.len(), logs.len())
+    } else {
+        "Console".to_string()
+    };
 
     let mut root = div().h_full().v_flex();
 
@@ -40,7 +330,7 @@ pub fn scratchpad_console(
                 // make scratchpad larger than default by allowing it to grow
                 .flex_1()
                 .border_b_1()
-                .border_color(rgb(0x333333))
+                .border_color(colors.border)
                 .v_flex()
                 .gap_2()
                 .child(
@@ -48,12 +338,15 @@ pub fn scratchpad_console(
                         .h_flex()
                         .items_center()
                         .justify_between()
+                        .gap_2()
                         .child(
                             div()
+                                .flex_shrink_0()
                                 .text_sm()
-                                .text_color(rgb(0xaaaaaa))
+                                .text_color(colors.text_muted)
                                 .child("Scratchpad"),
-                        ),
+                        )
+                        .child(notes_bar),
                 )
                 .child(Input::new(editor_input).w_full().h_full().text_sm()),
         );
@@ -63,7 +356,7 @@ pub fn scratchpad_console(
             div()
                 // keep console relatively small and fixed height
                 .flex_shrink_0()
-                .h(px(160.0))
+                .h(px(220.0))
             .v_flex()
             .p_1()
                 .gap_1()
@@ -71,9 +364,10 @@ pub fn scratchpad_console(
                     div()
                         .flex_shrink_0()
                         .text_sm()
-                        .text_color(rgb(0xaaaaaa))
-                        .child("Console"),
+                        .text_color(colors.text_muted)
+                        .child(title),
                 )
+                .child(div().flex_shrink_0().child(filter_bar))
                 .child(
                     div()
                         .overflow_hidden()
@@ -92,3 +386,74 @@ pub fn scratchpad_console(
     root
 }
 // inline actions were removed: menu bar now toggles visibility and opens sheets
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: &str, source: &str, content: &str) -> ConsoleLogEntry {
+        ConsoleLogEntry {
+            role_label: "Output".into(),
+            content: content.into(),
+            time_label: "12:00:00".into(),
+            level: level.into(),
+            source: source.into(),
+            request_id: Some("abcd1234".into()),
+            duration_ms: Some(1250),
+        }
+    }
+
+    #[test]
+    fn filters_by_level_source_and_text() {
+        let logs = [
+            entry("debug", "prompt_builder", "Built prompt"),
+            entry("info", "langchain", "Answer about cats"),
+            entry("error", "mcp:weather", "Timeout"),
+        ];
+        let visible = |filter: &ConsoleLogFilter| -> Vec<&str> {
+            logs.iter()
+                .filter(|e| filter.matches(e))
+                .map(|e| e.content.as_str())
+                .collect()
+        };
+
+        let mut filter = ConsoleLogFilter::default();
+        assert!(!filter.is_active());
+        assert_eq!(visible(&filter).len(), 3);
+
+        filter.min_level = Some("info".into());
+        assert_eq!(visible(&filter), ["Answer about cats", "Timeout"]);
+
+        filter.query = "CATS".into();
+        assert_eq!(visible(&filter), ["Answer about cats"]);
+
+        filter = ConsoleLogFilter {
+            source: Some("mcp:weather".into()),
+            ..Default::default()
+        };
+        assert_eq!(visible(&filter), ["Timeout"]);
+
+        // リクエスト ID でも探せる
+        filter = ConsoleLogFilter {
+            query: "abcd".into(),
+            ..Default::default()
+        };
+        assert_eq!(visible(&filter).len(), 3);
+        assert_eq!(
+            console_log_sources(&logs),
+            ["langchain", "mcp:weather", "prompt_builder"]
+        );
+    }
+
+    #[test]
+    fn header_lists_the_structured_fields() {
+        assert_eq!(
+            entry("warn", "langchain", "x").header(),
+            "12:00:00 WARN langchain Output #abcd1234 1.2s"
+        );
+        let mut plain = entry("info", "app", "x");
+        plain.request_id = None;
+        plain.duration_ms = Some(40);
+        assert_eq!(plain.header(), "12:00:00 INFO app Output 40ms");
+    }
+}
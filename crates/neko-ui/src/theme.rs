@@ -0,0 +1,453 @@
+//! 配色テーマ（意味ごとの色トークン）
+//!
+//! 組み込みの dark / light に加え、データディレクトリの `themes/*.toml` を読み込める。
+//! ファイルには変えたいトークンだけを書けばよく、残りは `appearance` の組み込みテーマから引き継ぐ。
+//!
+//! ```toml
+//! name = "Solarized Light"
+//! appearance = "light"
+//! syntax_theme = "Solarized (light)"
+//!
+//! [colors]
+//! surface = "#fdf6e3"
+//! accent = "#268bd2"
+//!
+//! [colors.bubble.user]
+//! bg = "#268bd2"
+//! ```
+
+use gpui::{rgba, Fill, Hsla};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// 色（0xRRGGBBAA）。テーマファイルでは `#RRGGBB` か `#RRGGBBAA` で書く
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ThemeColor(pub u32);
+
+impl ThemeColor {
+    /// 不透明な色
+    pub const fn rgb(hex: u32) -> Self {
+        Self((hex << 8) | 0xff)
+    }
+
+    pub const fn rgba(hex: u32) -> Self {
+        Self(hex)
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let hex = text.trim().strip_prefix('#')?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        match hex.len() {
+            6 => Some(Self::rgb(value)),
+            8 => Some(Self::rgba(value)),
+            _ => None,
+        }
+    }
+
+    pub fn to_hex(self) -> String {
+        if self.0 & 0xff == 0xff {
+            format!("#{:06x}", self.0 >> 8)
+        } else {
+            format!("#{:08x}", self.0)
+        }
+    }
+}
+
+impl Serialize for ThemeColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Self::parse(&text).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid color {:?} (expected #RRGGBB or #RRGGBBAA)",
+                text
+            ))
+        })
+    }
+}
+
+impl From<ThemeColor> for Hsla {
+    fn from(color: ThemeColor) -> Self {
+        rgba(color.0).into()
+    }
+}
+
+impl From<ThemeColor> for Fill {
+    fn from(color: ThemeColor) -> Self {
+        rgba(color.0).into()
+    }
+}
+
+/// テーマが明るいか暗いか（OS の設定に合わせるときにも使う）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeAppearance {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// 吹き出しの背景と文字の色
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BubbleColor {
+    pub bg: ThemeColor,
+    pub text: ThemeColor,
+}
+
+/// メッセージの種類ごとの吹き出しの色
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BubbleColors {
+    pub user: BubbleColor,
+    pub assistant: BubbleColor,
+    pub system: BubbleColor,
+    pub error: BubbleColor,
+    pub tool: BubbleColor,
+    /// 応答待ちのスケルトン
+    pub thinking: ThemeColor,
+}
+
+/// アシスタントの返答（Markdown）の装飾の色
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarkdownColors {
+    pub link: ThemeColor,
+    pub quote_border: ThemeColor,
+    pub rule: ThemeColor,
+    pub table_border: ThemeColor,
+    pub inline_code_bg: ThemeColor,
+    /// コードブロックの言語名
+    pub code_label: ThemeColor,
+}
+
+/// 色トークン
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeColors {
+    /// チャット画面の背景
+    pub background: ThemeColor,
+    /// ツールバー・メニュー・サブウィンドウの背景
+    pub surface: ThemeColor,
+    /// 浮き上がったパネル（コマンドパレットなど）の背景
+    pub panel: ThemeColor,
+    pub border: ThemeColor,
+    pub text: ThemeColor,
+    pub text_muted: ThemeColor,
+    /// 補足や無効な項目の文字
+    pub text_subtle: ThemeColor,
+    pub hover: ThemeColor,
+    /// 選択中の項目
+    pub selection: ThemeColor,
+    pub accent: ThemeColor,
+    /// 強調した文字（ラベル・キー表示など）
+    pub accent_text: ThemeColor,
+    /// accent / success / error で塗った上の文字
+    pub on_accent: ThemeColor,
+    /// 小さなボタンやタグの背景
+    pub control: ThemeColor,
+    pub control_text: ThemeColor,
+    pub success: ThemeColor,
+    pub error: ThemeColor,
+    pub error_text: ThemeColor,
+    pub warning: ThemeColor,
+    pub warning_text: ThemeColor,
+    /// ダイアログの後ろを暗くする幕
+    pub overlay: ThemeColor,
+    pub bubble: BubbleColors,
+    pub markdown: MarkdownColors,
+}
+
+/// 配色テーマ
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub appearance: ThemeAppearance,
+    /// コードのハイライトに使う syntect のテーマ名
+    pub syntax_theme: String,
+    pub colors: ThemeColors,
+}
+
+/// テーマファイルの中身（colors は組み込みテーマに重ねる差分）
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: String,
+    #[serde(default)]
+    appearance: ThemeAppearance,
+    syntax_theme: Option<String>,
+    #[serde(default)]
+    colors: toml::Table,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        let white = ThemeColor::rgb(0xffffff);
+        let bubble = |bg: u32| BubbleColor {
+            bg: ThemeColor::rgb(bg),
+            text: white,
+        };
+        Self {
+            name: "Dark".to_string(),
+            appearance: ThemeAppearance::Dark,
+            syntax_theme: "base16-ocean.dark".to_string(),
+            colors: ThemeColors {
+                background: ThemeColor::rgb(0x111111),
+                surface: ThemeColor::rgb(0x161616),
+                panel: ThemeColor::rgb(0x111827),
+                border: ThemeColor::rgb(0x333333),
+                text: white,
+                text_muted: ThemeColor::rgb(0xaaaaaa),
+                text_subtle: ThemeColor::rgb(0x888888),
+                hover: ThemeColor::rgb(0x3a3a3a),
+                selection: ThemeColor::rgb(0x2a5a8a),
+                accent: ThemeColor::rgb(0x2563eb),
+                accent_text: ThemeColor::rgb(0xa5b4fc),
+                on_accent: white,
+                control: ThemeColor::rgb(0x2b2b2b),
+                control_text: ThemeColor::rgb(0xdddddd),
+                success: ThemeColor::rgb(0x1d8348),
+                error: ThemeColor::rgb(0x922b21),
+                error_text: ThemeColor::rgb(0xf1948a),
+                warning: ThemeColor::rgb(0x92400e),
+                warning_text: ThemeColor::rgb(0xfde68a),
+                overlay: ThemeColor::rgba(0x00000066),
+                bubble: BubbleColors {
+                    user: bubble(0x3b82f6),
+                    assistant: bubble(0x10b981),
+                    system: bubble(0x6b7280),
+                    error: bubble(0xef4444),
+                    tool: bubble(0x1f2937),
+                    thinking: ThemeColor::rgb(0x4b5563),
+                },
+                markdown: MarkdownColors {
+                    link: ThemeColor::rgb(0xbfdbfe),
+                    quote_border: ThemeColor::rgb(0xd1fae5),
+                    rule: ThemeColor::rgba(0xffffff66),
+                    table_border: ThemeColor::rgba(0xffffff4d),
+                    inline_code_bg: ThemeColor::rgba(0x00000040),
+                    code_label: ThemeColor::rgb(0x9ca3af),
+                },
+            },
+        }
+    }
+
+    pub fn light() -> Self {
+        let bubble = |bg: u32, text: u32| BubbleColor {
+            bg: ThemeColor::rgb(bg),
+            text: ThemeColor::rgb(text),
+        };
+        Self {
+            name: "Light".to_string(),
+            appearance: ThemeAppearance::Light,
+            syntax_theme: "InspiredGitHub".to_string(),
+            colors: ThemeColors {
+                background: ThemeColor::rgb(0xffffff),
+                surface: ThemeColor::rgb(0xf5f5f5),
+                panel: ThemeColor::rgb(0xffffff),
+                border: ThemeColor::rgb(0xd4d4d4),
+                text: ThemeColor::rgb(0x1f2937),
+                text_muted: ThemeColor::rgb(0x4b5563),
+                text_subtle: ThemeColor::rgb(0x6b7280),
+                hover: ThemeColor::rgb(0xe5e7eb),
+                selection: ThemeColor::rgb(0xbfdbfe),
+                accent: ThemeColor::rgb(0x2563eb),
+                accent_text: ThemeColor::rgb(0x1d4ed8),
+                on_accent: ThemeColor::rgb(0xffffff),
+                control: ThemeColor::rgb(0xe5e7eb),
+                control_text: ThemeColor::rgb(0x1f2937),
+                success: ThemeColor::rgb(0x15803d),
+                error: ThemeColor::rgb(0xdc2626),
+                error_text: ThemeColor::rgb(0xb91c1c),
+                warning: ThemeColor::rgb(0xd97706),
+                warning_text: ThemeColor::rgb(0x92400e),
+                overlay: ThemeColor::rgba(0x00000033),
+                bubble: BubbleColors {
+                    user: bubble(0x2563eb, 0xffffff),
+                    assistant: bubble(0xecfdf5, 0x064e3b),
+                    system: bubble(0xe5e7eb, 0x1f2937),
+                    error: bubble(0xfee2e2, 0x991b1b),
+                    tool: bubble(0xf3f4f6, 0x1f2937),
+                    thinking: ThemeColor::rgb(0xd1d5db),
+                },
+                markdown: MarkdownColors {
+                    link: ThemeColor::rgb(0x1d4ed8),
+                    quote_border: ThemeColor::rgb(0x10b981),
+                    rule: ThemeColor::rgba(0x0000004d),
+                    table_border: ThemeColor::rgba(0x00000033),
+                    inline_code_bg: ThemeColor::rgba(0x0000001a),
+                    code_label: ThemeColor::rgb(0x6b7280),
+                },
+            },
+        }
+    }
+
+    /// 見た目に合った組み込みテーマ
+    pub fn builtin(appearance: ThemeAppearance) -> Self {
+        match appearance {
+            ThemeAppearance::Dark => Self::dark(),
+            ThemeAppearance::Light => Self::light(),
+        }
+    }
+
+    /// テーマファイルを解釈する。書かれていないトークンは組み込みテーマの色になる
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        let file: ThemeFile = toml::from_str(content)?;
+        let base = Self::builtin(file.appearance);
+        let mut colors =
+            toml::Value::try_from(base.colors).expect("built-in theme colors serialize to TOML");
+        merge_toml(&mut colors, file.colors);
+        Ok(Self {
+            name: file.name,
+            appearance: file.appearance,
+            syntax_theme: file.syntax_theme.unwrap_or(base.syntax_theme),
+            colors: ThemeColors::deserialize(colors)?,
+        })
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read theme file {}: {}", path.display(), e))?;
+        Self::from_toml(&content)
+            .map_err(|e| format!("Failed to parse theme file {}: {}", path.display(), e))
+    }
+}
+
+/// `overrides` の値で `base` を上書きする（テーブルは中身ごとに重ねる）
+fn merge_toml(base: &mut toml::Value, overrides: toml::Table) {
+    let toml::Value::Table(base) = base else {
+        return;
+    };
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(existing @ toml::Value::Table(_)), toml::Value::Table(table)) => {
+                merge_toml(existing, table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// ディレクトリ内の `*.toml` をテーマとして読み込む（読めないファイルは警告して飛ばす）
+pub fn load_themes_from_dir(dir: &Path) -> Vec<Theme> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            Theme::load_from_file(path)
+                .map_err(|e| eprintln!("Warning: {}", e))
+                .ok()
+        })
+        .collect()
+}
+
+/// 設定値（"system" / "dark" / "light" / テーマ名）から使うテーマを決める。
+/// 見つからない名前なら OS の設定に合わせる
+pub fn resolve_theme(preference: &str, system: ThemeAppearance, custom: &[Theme]) -> Theme {
+    match preference.trim().to_ascii_lowercase().as_str() {
+        "dark" => return Theme::dark(),
+        "light" => return Theme::light(),
+        _ => {}
+    }
+    custom
+        .iter()
+        .find(|theme| theme.name.eq_ignore_ascii_case(preference.trim()))
+        .cloned()
+        .unwrap_or_else(|| Theme::builtin(system))
+}
+
+fn current_slot() -> &'static RwLock<Arc<Theme>> {
+    static CURRENT: OnceLock<RwLock<Arc<Theme>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(Theme::dark())))
+}
+
+/// 描画に使う現在のテーマ
+pub fn current_theme() -> Arc<Theme> {
+    current_slot()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// 現在のテーマを切り替える（反映にはウィンドウの再描画が必要）
+pub fn set_current_theme(theme: Theme) {
+    *current_slot().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(theme);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_colors() {
+        assert_eq!(ThemeColor::parse("#3b82f6"), Some(ThemeColor(0x3b82f6ff)));
+        assert_eq!(ThemeColor::parse("#00000066"), Some(ThemeColor(0x00000066)));
+        assert_eq!(ThemeColor::parse("3b82f6"), None);
+        assert_eq!(ThemeColor::parse("#3b82f"), None);
+        assert_eq!(ThemeColor::parse("#+b82f6"), None);
+        assert_eq!(ThemeColor::rgb(0x3b82f6).to_hex(), "#3b82f6");
+        assert_eq!(ThemeColor::rgba(0x00000066).to_hex(), "#00000066");
+    }
+
+    #[test]
+    fn theme_files_override_only_listed_tokens() {
+        let theme = Theme::from_toml(
+            r##"
+name = "Paper"
+appearance = "light"
+
+[colors]
+accent = "#ff0000"
+
+[colors.bubble.user]
+bg = "#00ff00"
+"##,
+        )
+        .unwrap();
+
+        let light = Theme::light();
+        assert_eq!(theme.name, "Paper");
+        assert_eq!(theme.appearance, ThemeAppearance::Light);
+        assert_eq!(theme.syntax_theme, light.syntax_theme);
+        assert_eq!(theme.colors.accent, ThemeColor::rgb(0xff0000));
+        assert_eq!(theme.colors.bubble.user.bg, ThemeColor::rgb(0x00ff00));
+        assert_eq!(theme.colors.bubble.user.text, light.colors.bubble.user.text);
+        assert_eq!(theme.colors.surface, light.colors.surface);
+
+        assert!(Theme::from_toml("name = \"Bad\"\n[colors]\nacent = \"#ff0000\"\n").is_err());
+        assert!(Theme::from_toml("name = \"Bad\"\n[colors]\naccent = \"red\"\n").is_err());
+    }
+
+    #[test]
+    fn resolves_preference_against_system_and_custom_themes() {
+        let custom = vec![Theme {
+            name: "Paper".to_string(),
+            ..Theme::light()
+        }];
+        let resolve = |preference, system| resolve_theme(preference, system, &custom).name;
+        assert_eq!(resolve("system", ThemeAppearance::Light), "Light");
+        assert_eq!(resolve("system", ThemeAppearance::Dark), "Dark");
+        assert_eq!(resolve("Dark", ThemeAppearance::Light), "Dark");
+        assert_eq!(resolve("paper", ThemeAppearance::Dark), "Paper");
+        assert_eq!(resolve("missing", ThemeAppearance::Light), "Light");
+    }
+}
@@ -0,0 +1,141 @@
+//! ツール呼び出しのカード（見出しをクリックすると引数と結果を開閉する）
+
+use gpui::*;
+use gpui_component::StyledExt;
+
+use crate::chat_message_list::{ChatMessageRow, MessageAction};
+use crate::theme::current_theme;
+
+const CODE_FONT: &str = "Consolas";
+
+/// 表示用のツール呼び出し（引数と結果は整形済みの JSON）
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToolCallCard {
+    pub tool: String,
+    pub server: String,
+    pub arguments: String,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub expanded: bool,
+}
+
+impl ToolCallCard {
+    /// 見出しの文字列（`🔧 tool@server · 12 ms · ok`）
+    pub fn header(&self) -> String {
+        format!(
+            "{} 🔧 {}@{} · {} ms · {}",
+            if self.expanded { "▾" } else { "▸" },
+            self.tool,
+            self.server,
+            self.duration_ms,
+            if self.error.is_some() { "error" } else { "ok" }
+        )
+    }
+}
+
+pub(crate) fn tool_call_card<V: Render>(
+    row: &ChatMessageRow,
+    card: &ToolCallCard,
+    on_action: impl Fn(&mut V, &ChatMessageRow, MessageAction, &mut Window, &mut Context<V>)
+        + 'static
+        + Clone,
+    cx: &mut Context<V>,
+) -> Div {
+    let listener = |kind: MessageAction| {
+        let on_action = on_action.clone();
+        let row = row.clone();
+        cx.listener(move |this, _, window, cx| on_action(this, &row, kind, window, cx))
+    };
+
+    let colors = current_theme().colors;
+    let header = div()
+        .px_2()
+        .py_1()
+        .text_xs()
+        .font_weight(FontWeight::BOLD)
+        .text_color(if card.error.is_some() {
+            colors.error_text
+        } else {
+            colors.warning_text
+        })
+        .cursor_pointer()
+        .child(card.header())
+        .on_mouse_down(MouseButton::Left, listener(MessageAction::ToggleToolCall));
+
+    let container = div()
+        .w_full()
+        .v_flex()
+        .rounded(px(8.0))
+        .border_1()
+        .border_color(colors.warning)
+        .bg(colors.bubble.tool.bg)
+        .text_color(colors.bubble.tool.text)
+        .child(header);
+    if !card.expanded {
+        return container;
+    }
+
+    let section = |title: &'static str, body: &str| {
+        div()
+            .v_flex()
+            .gap_1()
+            .child(div().text_xs().text_color(colors.text_subtle).child(title))
+            .child(
+                div()
+                    .p_2()
+                    .rounded_md()
+                    .bg(colors.panel)
+                    .font_family(CODE_FONT)
+                    .text_xs()
+                    .child(body.to_string()),
+            )
+    };
+    let mut body = div()
+        .v_flex()
+        .gap_2()
+        .px_2()
+        .pb_2()
+        .child(section("Arguments", &card.arguments));
+    if let Some(result) = &card.result {
+        body = body.child(section("Result", result));
+    }
+    if let Some(error) = &card.error {
+        body = body.child(section("Error", error));
+    }
+    let rerun = div()
+        .px_2()
+        .py_1()
+        .rounded_sm()
+        .text_xs()
+        .bg(colors.control)
+        .text_color(colors.control_text)
+        .hover(|style| style.bg(colors.hover))
+        .cursor_pointer()
+        .child("Re-run tool")
+        .on_mouse_down(MouseButton::Left, listener(MessageAction::RerunTool));
+    container.child(body.child(div().h_flex().child(rerun)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_shows_identifier_duration_and_outcome() {
+        let mut card = ToolCallCard {
+            tool: "search".into(),
+            server: "web".into(),
+            arguments: "{}".into(),
+            result: Some("[]".into()),
+            error: None,
+            duration_ms: 42,
+            expanded: false,
+        };
+        assert_eq!(card.header(), "▸ 🔧 search@web · 42 ms · ok");
+
+        card.error = Some("timeout".into());
+        card.expanded = true;
+        assert_eq!(card.header(), "▾ 🔧 search@web · 42 ms · error");
+    }
+}
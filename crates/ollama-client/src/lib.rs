@@ -6,7 +6,7 @@
 //!   local Ollama uses a different path, configure the base URL accordingly.
 
 use reqwest::Url;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
 pub struct OllamaClient {
@@ -39,6 +39,17 @@ impl OllamaClient {
     /// response body is returned as a string for maximum flexibility — callers
     /// can deserialize to a concrete shape if desired.
     pub async fn generate(&self, model: &str, prompt: &str) -> Result<String, reqwest::Error> {
+        self.generate_with_options(model, prompt, &GenerateOptions::default())
+            .await
+    }
+
+    /// `generate` with sampling options (sent as the `options` object).
+    pub async fn generate_with_options(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, reqwest::Error> {
         let mut url = self.base.clone();
         // join with a relative path; if base already contains a path this will work
         url.set_path(&format!(
@@ -46,11 +57,14 @@ impl OllamaClient {
             url.path().trim_end_matches('/')
         ));
 
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "model": model,
             "prompt": prompt,
             "stream": false,
         });
+        if !options.is_empty() {
+            payload["options"] = serde_json::to_value(options).unwrap_or_default();
+        }
 
         let res = self.client.post(url).json(&payload).send().await?;
 
@@ -132,6 +146,27 @@ impl OllamaClient {
     }
 }
 
+/// Sampling options for `api/generate`. `None` fields use the model defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct GenerateOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Maximum number of tokens to generate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i32>,
+}
+
+impl GenerateOptions {
+    /// True when no option is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 // Small helper types for callers who want to deserialize standard responses.
 #[derive(Debug, Deserialize)]
 pub struct GenerateResponse {
@@ -183,4 +218,19 @@ mod tests {
         let c = OllamaClient::new("http://localhost:11434/").unwrap();
         let _ = c.health().await; // may fail if server not present; we ignore
     }
+
+    #[test]
+    fn generate_options_skip_unset_fields() {
+        let options = GenerateOptions {
+            temperature: Some(0.5),
+            num_predict: Some(128),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(options).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"temperature": 0.5, "num_predict": 128})
+        );
+        assert!(GenerateOptions::default().is_empty());
+    }
 }
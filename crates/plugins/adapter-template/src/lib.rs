@@ -7,7 +7,6 @@
 
 use async_trait::async_trait;
 use model_adapter::{ModelAdapter, ToolSpec};
-use model_provider::{GenerateResult, ModelProvider, ProviderError};
 
 /// Example adapter struct — rename to your adapter name.
 pub struct MyAdapter;
@@ -35,14 +34,10 @@ impl ModelAdapter for MyAdapter {
         vec!["my-model:latest".to_string()]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
-        // TODO: Implement model-specific payload formatting.
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
+        // TODO: Implement model-specific prompt formatting.
+        // `invoke` sends the returned prompt to the provider; override `invoke`
+        // too if the provider needs a different call.
         // Example strategies:
         // - Some models expect `messages` (chat format) instead of raw `prompt`.
         // - Function-calling schemas might require embedding `tools` as JSON
@@ -58,11 +53,13 @@ impl ModelAdapter for MyAdapter {
             }
         }
 
-        // Delegate to provider. Customize if provider requires specific API.
-        provider.generate(model, &actual_prompt).await
+        actual_prompt
     }
 }
 
+// Entry point loaded by the host (`entrypoint = "neko_model_adapter"`).
+model_adapter::export_model_adapter!(MyAdapter::new);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -8,7 +8,6 @@
 
 use async_trait::async_trait;
 use model_adapter::{ModelAdapter, ToolSpec};
-use model_provider::{GenerateResult, ModelProvider, ProviderError};
 
 /// ModelAdapter for Google Gemma 3n (4B Instruct)
 pub struct Gemma3Adapter;
@@ -41,13 +40,7 @@ impl ModelAdapter for Gemma3Adapter {
         ]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
         // Gemma 3 の関数呼び出しフォーマットに従ってプロンプトを構築
         // システムロールはサポートされないため、ユーザープロンプト内に関数定義を含める
         let mut actual_prompt = prompt.to_string();
@@ -83,6 +76,9 @@ impl ModelAdapter for Gemma3Adapter {
             }
         }
 
-        provider.generate(model, &actual_prompt).await
+        actual_prompt
     }
 }
+
+// Entry point loaded by the host (`entrypoint = "neko_model_adapter"`).
+model_adapter::export_model_adapter!(Gemma3Adapter::new);
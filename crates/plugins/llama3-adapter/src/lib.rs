@@ -3,7 +3,6 @@
 
 use async_trait::async_trait;
 use model_adapter::{ModelAdapter, ToolSpec};
-use model_provider::{GenerateResult, ModelProvider, ProviderError};
 
 /// ModelAdapter for Meta Llama 3.1 (8B Instruct)
 pub struct Llama3Adapter;
@@ -37,13 +36,7 @@ impl ModelAdapter for Llama3Adapter {
         ]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
         // Llama 3.1 は Ollama 側でチャット形式を処理するため、
         // tools をプロンプトに注入する
         let mut actual_prompt = prompt.to_string();
@@ -73,6 +66,9 @@ impl ModelAdapter for Llama3Adapter {
             }
         }
 
-        provider.generate(model, &actual_prompt).await
+        actual_prompt
     }
 }
+
+// Entry point loaded by the host (`entrypoint = "neko_model_adapter"`).
+model_adapter::export_model_adapter!(Llama3Adapter::new);
@@ -8,7 +8,6 @@
 
 use async_trait::async_trait;
 use model_adapter::{ModelAdapter, ToolSpec};
-use model_provider::{GenerateResult, ModelProvider, ProviderError};
 use serde_json::json;
 
 const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful assistant.";
@@ -90,18 +89,14 @@ impl ModelAdapter for Phi4MiniAdapter {
         ]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
-        let formatted = Self::format_prompt(None, prompt, tools);
-        provider.generate(model, &formatted).await
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
+        Self::format_prompt(None, prompt, tools)
     }
 }
 
+// Entry point loaded by the host (`entrypoint = "neko_model_adapter"`).
+model_adapter::export_model_adapter!(Phi4MiniAdapter::new);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -11,6 +11,9 @@ use serde_json::Value;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 
+/// Used when neither directives nor the `system_prompt` setting are provided.
+const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful assistant.";
+
 /// The plugin's runtime PromptBuilder implementation.
 struct Phi4PromptBuilder;
 
@@ -63,7 +66,14 @@ impl PromptBuilder for Phi4PromptBuilder {
         }
 
         if system.trim().is_empty() {
-            system.push_str("You are a helpful assistant.");
+            // `system_prompt` comes from the plugin's [settings] in plugin.toml.
+            let default_prompt = ctx
+                .settings
+                .get("system_prompt")
+                .and_then(Value::as_str)
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or(DEFAULT_SYSTEM_PROMPT);
+            system.push_str(default_prompt);
         }
 
         if !ctx.tools.is_empty() {
@@ -171,16 +181,16 @@ impl PromptBuilderFactory for Phi4Factory {
     }
 }
 
-#[no_mangle]
-pub unsafe extern "C" fn create_prompt_builder() -> *mut dyn PromptBuilderFactory {
-    // Leak the factory as an FFI pointer the host will take ownership of.
-    leak_factory(Box::new(Phi4Factory::new()))
-}
+// Exports `create_prompt_builder` and `neko_prompt_spi_abi_version` (see `prompt_spi::ffi`).
+prompt_spi::export_prompt_builder!(Phi4Factory::new);
 
 /// JSON-based FFI helper for external runners. Accepts a JSON-serialized
 /// representation of a minimal PromptContext and returns a JSON-serialized
 /// PromptPayload (owned string). The returned pointer must be freed by the
 /// caller using the standard C free (we allocate via CString::into_raw).
+///
+/// # Safety
+/// `input` must be null or a valid NUL-terminated string.
 #[no_mangle]
 pub unsafe extern "C" fn build_prompt_json(input: *const c_char) -> *mut c_char {
     if input.is_null() {
@@ -203,6 +213,8 @@ pub unsafe extern "C" fn build_prompt_json(input: *const c_char) -> *mut c_char
         model: String,
         locale: Option<String>,
         conversation: Option<Vec<OwnedTurn>>,
+        #[serde(default)]
+        settings: serde_json::Map<String, Value>,
         // not supporting tools/system_directives in this thin FFI
     }
 
@@ -239,6 +251,7 @@ pub unsafe extern "C" fn build_prompt_json(input: *const c_char) -> *mut c_char
         conversation: &conv_turns,
         tools: &[],
         system_directives: &[],
+        settings: &ctx.settings,
     };
 
     let builder = Phi4PromptBuilder;
@@ -269,15 +282,34 @@ mod tests {
             conversation: &conv,
             tools: &[],
             system_directives: &[],
+            settings: &serde_json::Map::new(),
         };
 
         let payload = b.build(ctx).expect("build ok");
         let p = payload.prompt.expect("prompt present");
-        assert!(p.contains("<|system|>"));
+        assert!(p.contains("<|system|>\nYou are a helpful assistant."));
         assert!(p.contains("<|user|>"));
         assert!(p.contains("<|assistant|>"));
     }
 
+    #[test]
+    fn uses_system_prompt_setting() {
+        let b = Phi4PromptBuilder;
+        let mut settings = serde_json::Map::new();
+        settings.insert("system_prompt".into(), json!("You are a cat."));
+        let ctx = PromptContext {
+            model: "phi4-mini:3.8b",
+            locale: "en-US",
+            conversation: &[],
+            tools: &[],
+            system_directives: &[],
+            settings: &settings,
+        };
+
+        let p = b.build(ctx).unwrap().prompt.unwrap();
+        assert!(p.contains("<|system|>\nYou are a cat.<|end|>"));
+    }
+
     #[test]
     fn parse_returns_final_answer() {
         let b = Phi4PromptBuilder;
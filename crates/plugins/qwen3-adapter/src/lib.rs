@@ -3,7 +3,6 @@
 
 use async_trait::async_trait;
 use model_adapter::{ModelAdapter, ToolSpec};
-use model_provider::{GenerateResult, ModelProvider, ProviderError};
 
 /// ModelAdapter for Qwen 3 (4B Instruct)
 pub struct Qwen3Adapter;
@@ -35,13 +34,7 @@ impl ModelAdapter for Qwen3Adapter {
         ]
     }
 
-    async fn invoke(
-        &self,
-        provider: &dyn ModelProvider,
-        model: &str,
-        prompt: &str,
-        tools: Option<&[ToolSpec]>,
-    ) -> Result<GenerateResult, ProviderError> {
+    fn build_prompt(&self, _model: &str, prompt: &str, tools: Option<&[ToolSpec]>) -> String {
         // Qwen 3 も Ollama 側でチャット形式を処理するため、
         // tools をプロンプトに注入する
         let mut actual_prompt = prompt.to_string();
@@ -71,6 +64,9 @@ impl ModelAdapter for Qwen3Adapter {
             }
         }
 
-        provider.generate(model, &actual_prompt).await
+        actual_prompt
     }
 }
+
+// Entry point loaded by the host (`entrypoint = "neko_model_adapter"`).
+model_adapter::export_model_adapter!(Qwen3Adapter::new);
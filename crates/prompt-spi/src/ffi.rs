@@ -0,0 +1,888 @@
+//! Stable C ABI between the host and prompt builder plugins.
+//!
+//! Only `#[repr(C)]` data, opaque handles and NUL-terminated UTF-8 JSON
+//! strings cross the boundary, so a plugin built with a different rustc or
+//! `prompt-spi` version is rejected at load time instead of causing UB.
+//!
+//! Plugin side:
+//!
+//! ```ignore
+//! prompt_spi::export_prompt_builder!(MyFactory::new);
+//! ```
+//!
+//! Host side: read `neko_prompt_spi_abi_version` first, then call the
+//! entry point and wrap the vtable with `ForeignPromptBuilderFactory::from_vtable`.
+//!
+//! Tool providers (`kind = "tool"`) work the same way with
+//! `export_tool_provider!`, `neko_tool_spi_abi_version` and `ForeignToolProvider`.
+//!
+//! A panic inside a plugin callback must not unwind into the host (it would
+//! abort the process), so every exported function catches it and returns the
+//! ABI's error value instead: null, or an `Err` JSON for `build` / `parse` /
+//! `invoke`.
+
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    ConversationRole, ConversationTurn, DirectiveSource, PromptBuilder, PromptBuilderFactory,
+    PromptContext, PromptMetadata, PromptParseOutput, PromptPayload, PromptSpiError,
+    PromptSpiResult, SystemDirective, ToolInvocation, ToolProvider, ToolSpec,
+};
+
+/// Version of the prompt builder ABI. Bump when the vtable or JSON shapes change.
+pub const PROMPT_SPI_ABI_VERSION: u32 = 1;
+
+/// Symbol exporting the plugin's ABI version (`extern "C" fn() -> u32`).
+pub const ABI_VERSION_SYMBOL: &str = "neko_prompt_spi_abi_version";
+
+/// Default entry point name (`entrypoint` in `plugin.toml`).
+pub const DEFAULT_PROMPT_BUILDER_ENTRYPOINT: &str = "create_prompt_builder";
+
+/// Symbol exporting a tool provider's ABI version (`extern "C" fn() -> u32`).
+/// Separate from `ABI_VERSION_SYMBOL` so both kinds can live in one library.
+pub const TOOL_ABI_VERSION_SYMBOL: &str = "neko_tool_spi_abi_version";
+
+/// Default tool provider entry point name.
+pub const DEFAULT_TOOL_PROVIDER_ENTRYPOINT: &str = "create_tool_provider";
+
+/// Signature of `neko_prompt_spi_abi_version`.
+pub type AbiVersionFn = extern "C" fn() -> u32;
+
+/// Signature of the exported entry point.
+pub type PromptBuilderEntrypoint = extern "C" fn() -> *const PromptBuilderVTable;
+
+/// Signature of the exported tool provider entry point.
+pub type ToolProviderEntrypoint = extern "C" fn() -> *const ToolProviderVTable;
+
+/// Function table exported by a prompt builder plugin.
+#[repr(C)]
+pub struct PromptBuilderVTable {
+    /// Must equal the host's `PROMPT_SPI_ABI_VERSION`.
+    pub abi_version: u32,
+    /// Returns `PromptMetadata` as JSON.
+    pub metadata: extern "C" fn() -> *mut c_char,
+    /// Creates a builder instance and returns an opaque handle.
+    pub create_builder: extern "C" fn() -> *mut c_void,
+    /// Releases a handle returned by `create_builder`.
+    pub destroy_builder: unsafe extern "C" fn(*mut c_void),
+    /// Takes `OwnedPromptContext` as JSON and returns
+    /// `Result<PromptPayload, PromptSpiError>` as JSON.
+    pub build: unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_char,
+    /// Takes the raw model output and returns
+    /// `Result<PromptParseOutput, PromptSpiError>` as JSON.
+    pub parse: unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_char,
+    /// Releases a string returned by this vtable.
+    pub free_string: unsafe extern "C" fn(*mut c_char),
+}
+
+/// Function table exported by a tool provider plugin.
+#[repr(C)]
+pub struct ToolProviderVTable {
+    /// Must equal the host's `PROMPT_SPI_ABI_VERSION`.
+    pub abi_version: u32,
+    /// Returns `Vec<ToolSpec>` as JSON.
+    pub tools: extern "C" fn() -> *mut c_char,
+    /// Takes `ToolInvocation` as JSON and returns
+    /// `Result<serde_json::Value, PromptSpiError>` as JSON.
+    pub invoke: unsafe extern "C" fn(*const c_char) -> *mut c_char,
+    /// Releases a string returned by this vtable.
+    pub free_string: unsafe extern "C" fn(*mut c_char),
+}
+
+/// Errors raised while binding a plugin.
+#[derive(Debug, thiserror::Error)]
+pub enum PromptSpiLoadError {
+    #[error("plugin does not export '{0}' (built against an older prompt-spi?)")]
+    MissingVersionSymbol(&'static str),
+
+    #[error("prompt-spi ABI version mismatch: plugin {plugin}, host {host}")]
+    AbiMismatch { plugin: u32, host: u32 },
+
+    #[error("entry point returned null")]
+    NullVTable,
+
+    #[error("invalid prompt builder metadata: {0}")]
+    InvalidMetadata(String),
+
+    #[error("invalid tool list: {0}")]
+    InvalidTools(String),
+}
+
+/// Check the value returned by `neko_prompt_spi_abi_version`.
+pub fn check_abi_version(plugin: u32) -> Result<(), PromptSpiLoadError> {
+    if plugin == PROMPT_SPI_ABI_VERSION {
+        Ok(())
+    } else {
+        Err(PromptSpiLoadError::AbiMismatch {
+            plugin,
+            host: PROMPT_SPI_ABI_VERSION,
+        })
+    }
+}
+
+// ---- wire format -------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedTurn {
+    pub role: ConversationRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedDirective {
+    pub source: DirectiveSource,
+    pub content: String,
+}
+
+/// `PromptContext` with owned data, used on the wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedPromptContext {
+    pub model: String,
+    pub locale: String,
+    pub conversation: Vec<OwnedTurn>,
+    pub tools: Vec<ToolSpec>,
+    pub system_directives: Vec<OwnedDirective>,
+    /// Absent in contexts serialized by older hosts.
+    #[serde(default)]
+    pub settings: Map<String, Value>,
+}
+
+impl OwnedPromptContext {
+    pub fn from_context(ctx: &PromptContext<'_>) -> Self {
+        Self {
+            model: ctx.model.to_string(),
+            locale: ctx.locale.to_string(),
+            conversation: ctx
+                .conversation
+                .iter()
+                .map(|turn| OwnedTurn {
+                    role: turn.role,
+                    content: turn.content.to_string(),
+                })
+                .collect(),
+            tools: ctx.tools.to_vec(),
+            system_directives: ctx
+                .system_directives
+                .iter()
+                .map(|directive| OwnedDirective {
+                    source: directive.source,
+                    content: directive.content.to_string(),
+                })
+                .collect(),
+            settings: ctx.settings.clone(),
+        }
+    }
+
+    /// Borrow as a `PromptContext` and pass it to `f`.
+    pub fn with_context<R>(&self, f: impl FnOnce(PromptContext<'_>) -> R) -> R {
+        let conversation: Vec<ConversationTurn<'_>> = self
+            .conversation
+            .iter()
+            .map(|turn| ConversationTurn {
+                role: turn.role,
+                content: &turn.content,
+            })
+            .collect();
+        let system_directives: Vec<SystemDirective<'_>> = self
+            .system_directives
+            .iter()
+            .map(|directive| SystemDirective {
+                source: directive.source,
+                content: &directive.content,
+            })
+            .collect();
+        f(PromptContext {
+            model: &self.model,
+            locale: &self.locale,
+            conversation: &conversation,
+            tools: &self.tools,
+            system_directives: &system_directives,
+            settings: &self.settings,
+        })
+    }
+}
+
+// ---- plugin side -------------------------------------------------------
+
+fn into_c_string(value: impl Serialize) -> *mut c_char {
+    serde_json::to_string(&value)
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".into())
+}
+
+/// Run the body of an exported function, returning null if it panics.
+#[doc(hidden)]
+pub fn null_on_panic<T>(f: impl FnOnce() -> *mut T) -> *mut T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(std::ptr::null_mut())
+}
+
+/// Run the body of an exported function, returning `Err(map_err(..))` as
+/// JSON if it panics.
+#[doc(hidden)]
+pub fn error_on_panic(
+    f: impl FnOnce() -> *mut c_char,
+    map_err: fn(String) -> PromptSpiError,
+) -> *mut c_char {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = format!("plugin panicked: {}", panic_message(payload.as_ref()));
+        into_c_string(Err::<(), _>(map_err(message)))
+    })
+}
+
+/// Plugin-side helper for `metadata`.
+pub fn metadata_for(factory: &dyn PromptBuilderFactory) -> *mut c_char {
+    into_c_string(factory.metadata())
+}
+
+/// Plugin-side helper for `create_builder`.
+pub fn create_builder_for(factory: &dyn PromptBuilderFactory) -> *mut c_void {
+    Box::into_raw(Box::new(factory.create())).cast()
+}
+
+/// Plugin-side `destroy_builder`.
+///
+/// # Safety
+/// `handle` must be null or a handle returned by `create_builder_for`.
+pub unsafe extern "C" fn destroy_builder(handle: *mut c_void) {
+    if !handle.is_null() {
+        let builder = Box::from_raw(handle.cast::<Box<dyn PromptBuilder>>());
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(builder)));
+    }
+}
+
+/// Plugin-side `build`.
+///
+/// # Safety
+/// `handle` must come from `create_builder_for` and `request` must be null or
+/// a valid NUL-terminated string.
+pub unsafe extern "C" fn build_with_handle(
+    handle: *mut c_void,
+    request: *const c_char,
+) -> *mut c_char {
+    error_on_panic(
+        || {
+            into_c_string(with_builder(handle, request, |builder, json| {
+                let ctx: OwnedPromptContext = serde_json::from_str(json)
+                    .map_err(|e| PromptSpiError::Context(e.to_string()))?;
+                ctx.with_context(|ctx| builder.build(ctx))
+            }))
+        },
+        PromptSpiError::Build,
+    )
+}
+
+/// Plugin-side `parse`.
+///
+/// # Safety
+/// Same as `build_with_handle`.
+pub unsafe extern "C" fn parse_with_handle(
+    handle: *mut c_void,
+    raw_output: *const c_char,
+) -> *mut c_char {
+    error_on_panic(
+        || {
+            into_c_string(with_builder(handle, raw_output, |builder, raw| {
+                builder.parse(raw)
+            }))
+        },
+        PromptSpiError::Parse,
+    )
+}
+
+unsafe fn with_builder<T>(
+    handle: *mut c_void,
+    input: *const c_char,
+    f: impl FnOnce(&dyn PromptBuilder, &str) -> PromptSpiResult<T>,
+) -> PromptSpiResult<T> {
+    let builder = handle
+        .cast::<Box<dyn PromptBuilder>>()
+        .as_ref()
+        .ok_or_else(|| PromptSpiError::Context("null builder handle".into()))?;
+    if input.is_null() {
+        return Err(PromptSpiError::Context("null input".into()));
+    }
+    let input = CStr::from_ptr(input)
+        .to_str()
+        .map_err(|e| PromptSpiError::Context(e.to_string()))?;
+    f(builder.as_ref(), input)
+}
+
+/// Plugin-side `free_string`.
+///
+/// # Safety
+/// `ptr` must be null or a string returned by this module.
+pub unsafe extern "C" fn free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+/// Export a prompt builder factory as `create_prompt_builder`, together with
+/// the `neko_prompt_spi_abi_version` symbol.
+///
+/// `$constructor` is called once, on first use.
+#[macro_export]
+macro_rules! export_prompt_builder {
+    ($constructor:expr) => {
+        fn __neko_prompt_factory() -> &'static dyn $crate::PromptBuilderFactory {
+            static INSTANCE: ::std::sync::OnceLock<
+                ::std::boxed::Box<dyn $crate::PromptBuilderFactory>,
+            > = ::std::sync::OnceLock::new();
+            INSTANCE
+                .get_or_init(|| ::std::boxed::Box::new($constructor()))
+                .as_ref()
+        }
+
+        extern "C" fn __neko_prompt_metadata() -> *mut ::std::os::raw::c_char {
+            $crate::ffi::null_on_panic(|| $crate::ffi::metadata_for(__neko_prompt_factory()))
+        }
+
+        extern "C" fn __neko_prompt_create() -> *mut ::std::os::raw::c_void {
+            $crate::ffi::null_on_panic(|| $crate::ffi::create_builder_for(__neko_prompt_factory()))
+        }
+
+        static __NEKO_PROMPT_VTABLE: $crate::ffi::PromptBuilderVTable =
+            $crate::ffi::PromptBuilderVTable {
+                abi_version: $crate::ffi::PROMPT_SPI_ABI_VERSION,
+                metadata: __neko_prompt_metadata,
+                create_builder: __neko_prompt_create,
+                destroy_builder: $crate::ffi::destroy_builder,
+                build: $crate::ffi::build_with_handle,
+                parse: $crate::ffi::parse_with_handle,
+                free_string: $crate::ffi::free_string,
+            };
+
+        #[no_mangle]
+        pub extern "C" fn neko_prompt_spi_abi_version() -> u32 {
+            $crate::ffi::PROMPT_SPI_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn create_prompt_builder() -> *const $crate::ffi::PromptBuilderVTable {
+            &__NEKO_PROMPT_VTABLE
+        }
+    };
+}
+
+// ---- host side ---------------------------------------------------------
+
+/// Host-side `PromptBuilderFactory` backed by a plugin's vtable.
+pub struct ForeignPromptBuilderFactory {
+    vtable: &'static PromptBuilderVTable,
+    metadata: PromptMetadata,
+}
+
+impl ForeignPromptBuilderFactory {
+    /// Bind a vtable returned by the plugin's entry point.
+    ///
+    /// # Safety
+    /// `vtable` must be null or point to a `PromptBuilderVTable` that stays
+    /// valid while the returned factory and every builder it creates are
+    /// alive. The caller must keep the plugin's `Library` loaded until then
+    /// (the host keeps an `Arc` to it next to them in `LibraryBoundFactory` /
+    /// `LibraryBoundBuilder`).
+    pub unsafe fn from_vtable(
+        vtable: *const PromptBuilderVTable,
+    ) -> Result<Self, PromptSpiLoadError> {
+        let vtable = vtable.as_ref().ok_or(PromptSpiLoadError::NullVTable)?;
+        check_abi_version(vtable.abi_version)?;
+
+        let json = take_string(vtable.free_string, (vtable.metadata)())
+            .ok_or_else(|| PromptSpiLoadError::InvalidMetadata("metadata returned null".into()))?;
+        let metadata = serde_json::from_str(&json)
+            .map_err(|e| PromptSpiLoadError::InvalidMetadata(e.to_string()))?;
+
+        Ok(Self { vtable, metadata })
+    }
+}
+
+impl PromptBuilderFactory for ForeignPromptBuilderFactory {
+    fn metadata(&self) -> PromptMetadata {
+        self.metadata.clone()
+    }
+
+    fn create(&self) -> Box<dyn PromptBuilder> {
+        Box::new(ForeignPromptBuilder {
+            vtable: self.vtable,
+            handle: (self.vtable.create_builder)(),
+            metadata: self.metadata.clone(),
+        })
+    }
+}
+
+/// Builder instance living inside the plugin.
+struct ForeignPromptBuilder {
+    vtable: &'static PromptBuilderVTable,
+    handle: *mut c_void,
+    metadata: PromptMetadata,
+}
+
+// SAFETY: the handle wraps a plugin-side `Box<dyn PromptBuilder>`, which is
+// `Send + Sync` by the trait bound.
+unsafe impl Send for ForeignPromptBuilder {}
+unsafe impl Sync for ForeignPromptBuilder {}
+
+impl ForeignPromptBuilder {
+    fn call<T: DeserializeOwned>(
+        &self,
+        function: unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_char,
+        input: &str,
+        map_err: fn(String) -> PromptSpiError,
+    ) -> PromptSpiResult<T> {
+        if self.handle.is_null() {
+            return Err(map_err("plugin failed to create a builder".into()));
+        }
+        let input = CString::new(input).map_err(|e| map_err(e.to_string()))?;
+        // SAFETY: `handle` came from this vtable; the result is released by its `free_string`.
+        let json = unsafe {
+            take_string(
+                self.vtable.free_string,
+                function(self.handle, input.as_ptr()),
+            )
+        }
+        .ok_or_else(|| map_err("plugin returned null".into()))?;
+        serde_json::from_str::<Result<T, PromptSpiError>>(&json)
+            .map_err(|e| map_err(e.to_string()))?
+    }
+}
+
+impl PromptBuilder for ForeignPromptBuilder {
+    fn metadata(&self) -> PromptMetadata {
+        self.metadata.clone()
+    }
+
+    fn build(&self, ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+        let request = serde_json::to_string(&OwnedPromptContext::from_context(&ctx))
+            .map_err(|e| PromptSpiError::Context(e.to_string()))?;
+        self.call(self.vtable.build, &request, PromptSpiError::Build)
+    }
+
+    fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+        self.call(self.vtable.parse, raw_output, PromptSpiError::Parse)
+    }
+}
+
+impl Drop for ForeignPromptBuilder {
+    fn drop(&mut self) {
+        // SAFETY: the handle was created by this vtable and is released once.
+        unsafe { (self.vtable.destroy_builder)(self.handle) };
+    }
+}
+
+/// Copy a plugin-owned string and release it.
+///
+/// # Safety
+/// `raw` must be null or a string returned by the vtable owning `free_string`.
+unsafe fn take_string(
+    free_string: unsafe extern "C" fn(*mut c_char),
+    raw: *mut c_char,
+) -> Option<String> {
+    if raw.is_null() {
+        return None;
+    }
+    let value = CStr::from_ptr(raw).to_string_lossy().into_owned();
+    free_string(raw);
+    Some(value)
+}
+
+// ---- tool providers ----------------------------------------------------
+
+/// Plugin-side helper for `tools`.
+pub fn tools_for(provider: &dyn ToolProvider) -> *mut c_char {
+    into_c_string(provider.tools())
+}
+
+/// Plugin-side helper for `invoke`.
+///
+/// # Safety
+/// `request` must be null or a valid NUL-terminated string.
+pub unsafe fn invoke_for(provider: &dyn ToolProvider, request: *const c_char) -> *mut c_char {
+    let result = (|| {
+        if request.is_null() {
+            return Err(PromptSpiError::Tool("null input".into()));
+        }
+        let json = CStr::from_ptr(request)
+            .to_str()
+            .map_err(|e| PromptSpiError::Tool(e.to_string()))?;
+        let invocation: ToolInvocation =
+            serde_json::from_str(json).map_err(|e| PromptSpiError::Tool(e.to_string()))?;
+        provider.invoke(&invocation.name, invocation.arguments)
+    })();
+    into_c_string(result)
+}
+
+/// Export a tool provider as `create_tool_provider`, together with the
+/// `neko_tool_spi_abi_version` symbol.
+///
+/// `$constructor` is called once, on first use.
+#[macro_export]
+macro_rules! export_tool_provider {
+    ($constructor:expr) => {
+        fn __neko_tool_provider() -> &'static dyn $crate::ToolProvider {
+            static INSTANCE: ::std::sync::OnceLock<::std::boxed::Box<dyn $crate::ToolProvider>> =
+                ::std::sync::OnceLock::new();
+            INSTANCE
+                .get_or_init(|| ::std::boxed::Box::new($constructor()))
+                .as_ref()
+        }
+
+        extern "C" fn __neko_tool_list() -> *mut ::std::os::raw::c_char {
+            $crate::ffi::null_on_panic(|| $crate::ffi::tools_for(__neko_tool_provider()))
+        }
+
+        unsafe extern "C" fn __neko_tool_invoke(
+            request: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::ffi::error_on_panic(
+                || $crate::ffi::invoke_for(__neko_tool_provider(), request),
+                $crate::PromptSpiError::Tool,
+            )
+        }
+
+        static __NEKO_TOOL_VTABLE: $crate::ffi::ToolProviderVTable =
+            $crate::ffi::ToolProviderVTable {
+                abi_version: $crate::ffi::PROMPT_SPI_ABI_VERSION,
+                tools: __neko_tool_list,
+                invoke: __neko_tool_invoke,
+                free_string: $crate::ffi::free_string,
+            };
+
+        #[no_mangle]
+        pub extern "C" fn neko_tool_spi_abi_version() -> u32 {
+            $crate::ffi::PROMPT_SPI_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn create_tool_provider() -> *const $crate::ffi::ToolProviderVTable {
+            &__NEKO_TOOL_VTABLE
+        }
+    };
+}
+
+/// Host-side `ToolProvider` backed by a plugin's vtable.
+/// The tool list is read once when binding.
+pub struct ForeignToolProvider {
+    vtable: &'static ToolProviderVTable,
+    tools: Vec<ToolSpec>,
+}
+
+impl ForeignToolProvider {
+    /// Bind a vtable returned by the plugin's tool provider entry point.
+    ///
+    /// # Safety
+    /// `vtable` must be null or point to a `ToolProviderVTable` that stays
+    /// valid while the returned provider is alive. The caller must keep the
+    /// plugin's `Library` loaded until the provider is dropped (the host keeps
+    /// an `Arc` to it next to the provider in `LibraryBoundProvider`).
+    pub unsafe fn from_vtable(
+        vtable: *const ToolProviderVTable,
+    ) -> Result<Self, PromptSpiLoadError> {
+        let vtable = vtable.as_ref().ok_or(PromptSpiLoadError::NullVTable)?;
+        check_abi_version(vtable.abi_version)?;
+
+        let json = take_string(vtable.free_string, (vtable.tools)())
+            .ok_or_else(|| PromptSpiLoadError::InvalidTools("tools returned null".into()))?;
+        let tools = serde_json::from_str(&json)
+            .map_err(|e| PromptSpiLoadError::InvalidTools(e.to_string()))?;
+
+        Ok(Self { vtable, tools })
+    }
+}
+
+impl ToolProvider for ForeignToolProvider {
+    fn tools(&self) -> Vec<ToolSpec> {
+        self.tools.clone()
+    }
+
+    fn invoke(&self, name: &str, arguments: Value) -> PromptSpiResult<Value> {
+        let request = serde_json::to_string(&ToolInvocation {
+            name: name.to_string(),
+            arguments,
+        })
+        .map_err(|e| PromptSpiError::Tool(e.to_string()))?;
+        let request = CString::new(request).map_err(|e| PromptSpiError::Tool(e.to_string()))?;
+        // SAFETY: the result is released by the same vtable's `free_string`.
+        let json = unsafe {
+            take_string(
+                self.vtable.free_string,
+                (self.vtable.invoke)(request.as_ptr()),
+            )
+        }
+        .ok_or_else(|| PromptSpiError::Tool("plugin returned null".into()))?;
+        serde_json::from_str::<Result<Value, PromptSpiError>>(&json)
+            .map_err(|e| PromptSpiError::Tool(e.to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PromptAgentMode;
+
+    struct EchoBuilder;
+
+    impl PromptBuilder for EchoBuilder {
+        fn metadata(&self) -> PromptMetadata {
+            PromptMetadata {
+                name: "echo".into(),
+                version: "0.1.0".into(),
+                description: None,
+                supported_models: vec!["echo:latest".into()],
+                homepage: None,
+                preferred_agent: PromptAgentMode::DirectProvider,
+            }
+        }
+
+        fn build(&self, ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+            let text = ctx
+                .conversation
+                .iter()
+                .map(|turn| turn.content)
+                .collect::<Vec<_>>()
+                .join("|");
+            let suffix = ctx
+                .settings
+                .get("suffix")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            Ok(PromptPayload::with_prompt(
+                format!("{}:{}{}", ctx.model, text, suffix),
+                PromptAgentMode::DirectProvider,
+            ))
+        }
+
+        fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+            if raw_output.is_empty() {
+                return Err(PromptSpiError::Parse("empty".into()));
+            }
+            Ok(PromptParseOutput {
+                final_answer: Some(raw_output.to_string()),
+                tool_requests: Vec::new(),
+            })
+        }
+    }
+
+    struct EchoFactory;
+
+    impl PromptBuilderFactory for EchoFactory {
+        fn metadata(&self) -> PromptMetadata {
+            EchoBuilder.metadata()
+        }
+
+        fn create(&self) -> Box<dyn PromptBuilder> {
+            Box::new(EchoBuilder)
+        }
+    }
+
+    crate::export_prompt_builder!(|| EchoFactory);
+
+    struct Calculator;
+
+    impl ToolProvider for Calculator {
+        fn tools(&self) -> Vec<ToolSpec> {
+            vec![ToolSpec {
+                name: "add".into(),
+                description: Some("Add two numbers".into()),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+                    "required": ["a", "b"],
+                }),
+            }]
+        }
+
+        fn invoke(&self, name: &str, arguments: Value) -> PromptSpiResult<Value> {
+            match name {
+                "add" => {
+                    let arg = |key: &str| arguments.get(key).and_then(Value::as_f64);
+                    match (arg("a"), arg("b")) {
+                        (Some(a), Some(b)) => Ok(Value::from(a + b)),
+                        _ => Err(PromptSpiError::Tool("a and b are required".into())),
+                    }
+                }
+                other => Err(PromptSpiError::Tool(format!("unknown tool: {}", other))),
+            }
+        }
+    }
+
+    crate::export_tool_provider!(|| Calculator);
+
+    extern "C" fn null_metadata() -> *mut c_char {
+        std::ptr::null_mut()
+    }
+
+    extern "C" fn null_create() -> *mut c_void {
+        std::ptr::null_mut()
+    }
+
+    #[test]
+    fn exported_builder_roundtrips_through_vtable() {
+        assert_eq!(neko_prompt_spi_abi_version(), PROMPT_SPI_ABI_VERSION);
+        let factory = unsafe { ForeignPromptBuilderFactory::from_vtable(create_prompt_builder()) }
+            .expect("bind vtable");
+        assert_eq!(factory.metadata().name, "echo");
+
+        let builder = factory.create();
+        let turns = [ConversationTurn {
+            role: ConversationRole::User,
+            content: "こんにちは",
+        }];
+        let mut settings = Map::new();
+        settings.insert("suffix".into(), Value::from("!"));
+        let ctx = PromptContext {
+            model: "echo:latest",
+            locale: "ja-JP",
+            conversation: &turns,
+            tools: &[],
+            system_directives: &[],
+            settings: &settings,
+        };
+        let payload = builder.build(ctx).expect("build");
+        assert_eq!(payload.prompt.as_deref(), Some("echo:latest:こんにちは!"));
+
+        let parsed = builder.parse("answer").expect("parse");
+        assert_eq!(parsed.final_answer.as_deref(), Some("answer"));
+        assert!(matches!(builder.parse(""), Err(PromptSpiError::Parse(_))));
+    }
+
+    #[test]
+    fn exported_tool_provider_roundtrips_through_vtable() {
+        assert_eq!(neko_tool_spi_abi_version(), PROMPT_SPI_ABI_VERSION);
+        let provider =
+            unsafe { ForeignToolProvider::from_vtable(create_tool_provider()) }.expect("bind");
+        let tools = provider.tools();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "add");
+
+        let result = provider
+            .invoke("add", serde_json::json!({ "a": 1, "b": 2.5 }))
+            .expect("invoke");
+        assert_eq!(result, serde_json::json!(3.5));
+        assert!(matches!(
+            provider.invoke("sub", Value::Null),
+            Err(PromptSpiError::Tool(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_abi_version() {
+        static VTABLE: PromptBuilderVTable = PromptBuilderVTable {
+            abi_version: PROMPT_SPI_ABI_VERSION + 1,
+            metadata: null_metadata,
+            create_builder: null_create,
+            destroy_builder,
+            build: build_with_handle,
+            parse: parse_with_handle,
+            free_string,
+        };
+        assert!(matches!(
+            unsafe { ForeignPromptBuilderFactory::from_vtable(&VTABLE) },
+            Err(PromptSpiLoadError::AbiMismatch { .. })
+        ));
+        assert!(matches!(
+            unsafe { ForeignPromptBuilderFactory::from_vtable(std::ptr::null()) },
+            Err(PromptSpiLoadError::NullVTable)
+        ));
+        assert!(check_abi_version(PROMPT_SPI_ABI_VERSION).is_ok());
+    }
+
+    struct PanickingBuilder;
+
+    impl PromptBuilder for PanickingBuilder {
+        fn metadata(&self) -> PromptMetadata {
+            EchoBuilder.metadata()
+        }
+
+        fn build(&self, _ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+            panic!("build exploded")
+        }
+
+        fn parse(&self, _raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+            panic!("parse exploded")
+        }
+    }
+
+    extern "C" fn echo_metadata() -> *mut c_char {
+        metadata_for(&EchoFactory)
+    }
+
+    extern "C" fn panicking_create() -> *mut c_void {
+        null_on_panic(|| {
+            Box::into_raw(Box::new(
+                Box::new(PanickingBuilder) as Box<dyn PromptBuilder>
+            ))
+            .cast()
+        })
+    }
+
+    extern "C" fn panicking_metadata() -> *mut c_char {
+        null_on_panic(|| panic!("metadata exploded"))
+    }
+
+    #[test]
+    fn plugin_panics_become_errors() {
+        static VTABLE: PromptBuilderVTable = PromptBuilderVTable {
+            abi_version: PROMPT_SPI_ABI_VERSION,
+            metadata: echo_metadata,
+            create_builder: panicking_create,
+            destroy_builder,
+            build: build_with_handle,
+            parse: parse_with_handle,
+            free_string,
+        };
+        let factory = unsafe { ForeignPromptBuilderFactory::from_vtable(&VTABLE) }.unwrap();
+        let builder = factory.create();
+        let ctx = PromptContext {
+            model: "echo:latest",
+            locale: "ja-JP",
+            conversation: &[],
+            tools: &[],
+            system_directives: &[],
+            settings: &Map::new(),
+        };
+        assert!(matches!(
+            builder.build(ctx),
+            Err(PromptSpiError::Build(message)) if message.contains("build exploded")
+        ));
+        assert!(matches!(
+            builder.parse("answer"),
+            Err(PromptSpiError::Parse(message)) if message.contains("parse exploded")
+        ));
+
+        static BROKEN: PromptBuilderVTable = PromptBuilderVTable {
+            abi_version: PROMPT_SPI_ABI_VERSION,
+            metadata: panicking_metadata,
+            create_builder: null_create,
+            destroy_builder,
+            build: build_with_handle,
+            parse: parse_with_handle,
+            free_string,
+        };
+        assert!(matches!(
+            unsafe { ForeignPromptBuilderFactory::from_vtable(&BROKEN) },
+            Err(PromptSpiLoadError::InvalidMetadata(_))
+        ));
+    }
+
+    #[test]
+    fn missing_version_symbol_names_the_symbol() {
+        let err = PromptSpiLoadError::MissingVersionSymbol(TOOL_ABI_VERSION_SYMBOL);
+        assert!(err.to_string().contains("neko_tool_spi_abi_version"));
+    }
+}
@@ -4,6 +4,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
+pub mod ffi;
+
 /// コンテキスト内のメッセージロール
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConversationRole {
@@ -50,6 +52,8 @@ pub struct PromptContext<'a> {
     pub conversation: &'a [ConversationTurn<'a>],
     pub tools: &'a [ToolSpec],
     pub system_directives: &'a [SystemDirective<'a>],
+    /// プラグインの設定値（`plugin.toml` の `[settings]` の既定値にユーザーの保存値を重ねたもの）
+    pub settings: &'a Map<String, Value>,
 }
 
 /// PromptBuilder が返す推論ヒント
@@ -112,7 +116,7 @@ pub struct PromptMetadata {
 }
 
 /// SPI 全体で共通のエラー
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
 pub enum PromptSpiError {
     #[error("context error: {0}")]
     Context(String),
@@ -120,6 +124,8 @@ pub enum PromptSpiError {
     Build(String),
     #[error("parse error: {0}")]
     Parse(String),
+    #[error("tool error: {0}")]
+    Tool(String),
 }
 
 pub type PromptSpiResult<T> = Result<T, PromptSpiError>;
@@ -137,20 +143,10 @@ pub trait PromptBuilderFactory: Send + Sync {
     fn create(&self) -> Box<dyn PromptBuilder>;
 }
 
-/// `extern "C" fn` エントリーポイントの型
-#[allow(improper_ctypes_definitions)]
-pub type CreatePromptBuilderFactory = unsafe extern "C" fn() -> *mut dyn PromptBuilderFactory;
-
-/// ホスト側ヘルパー：FFI ポインタを Box に戻す
-/// # Safety
-/// The caller must ensure that `ptr` is a valid pointer to a `dyn PromptBuilderFactory`.
-pub unsafe fn factory_from_raw(
-    ptr: *mut dyn PromptBuilderFactory,
-) -> Box<dyn PromptBuilderFactory> {
-    Box::from_raw(ptr)
-}
-
-/// プラグイン側ヘルパー：Box を FFI ポインタに変換
-pub fn leak_factory(factory: Box<dyn PromptBuilderFactory>) -> *mut dyn PromptBuilderFactory {
-    Box::into_raw(factory)
+/// プロセス内で実行するツールの提供元（`kind = "tool"` のプラグインが実装する）
+pub trait ToolProvider: Send + Sync {
+    /// 提供するツールの一覧（`input_schema` は JSON Schema）
+    fn tools(&self) -> Vec<ToolSpec>;
+    /// ツールを実行し、結果を JSON で返す
+    fn invoke(&self, name: &str, arguments: Value) -> PromptSpiResult<Value>;
 }
@@ -0,0 +1,110 @@
+//! XChaCha20-Poly1305 によるエンベロープ暗号化
+//!
+//! 形式: `ENVELOPE_MAGIC` (8 bytes) + nonce (24 bytes) + 暗号文（認証タグ込み）
+
+use crate::{EncryptionKey, Result, SecretError};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+/// 暗号化済みデータの先頭に付くマジックバイト
+pub const ENVELOPE_MAGIC: &[u8; 8] = b"NEKOENC1";
+
+const NONCE_LEN: usize = 24;
+
+/// 文字列として保存する場合の接頭辞（DB の TEXT 列など）
+pub const STRING_PREFIX: &str = "enc:v1:";
+
+/// 平文を暗号化してエンベロープを返す
+pub fn encrypt(key: &EncryptionKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_bytes()));
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| SecretError::Encryption)?;
+
+    let mut out = Vec::with_capacity(ENVELOPE_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(ENVELOPE_MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// エンベロープを復号
+pub fn decrypt(key: &EncryptionKey, envelope: &[u8]) -> Result<Vec<u8>> {
+    if !is_encrypted(envelope) || envelope.len() < ENVELOPE_MAGIC.len() + NONCE_LEN {
+        return Err(SecretError::Decryption);
+    }
+
+    let (nonce, ciphertext) = envelope[ENVELOPE_MAGIC.len()..].split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_bytes()));
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| SecretError::Decryption)
+}
+
+/// 暗号化済みエンベロープかどうか（平文との判別用）
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENVELOPE_MAGIC)
+}
+
+/// 文字列を暗号化し `enc:v1:<base64>` 形式で返す
+pub fn encrypt_string(key: &EncryptionKey, plaintext: &str) -> Result<String> {
+    let envelope = encrypt(key, plaintext.as_bytes())?;
+    Ok(format!("{}{}", STRING_PREFIX, STANDARD.encode(envelope)))
+}
+
+/// `encrypt_string` の結果を復号
+pub fn decrypt_string(key: &EncryptionKey, encoded: &str) -> Result<String> {
+    let body = encoded
+        .strip_prefix(STRING_PREFIX)
+        .ok_or(SecretError::Decryption)?;
+    let envelope = STANDARD.decode(body).map_err(|_| SecretError::Decryption)?;
+    String::from_utf8(decrypt(key, &envelope)?).map_err(|_| SecretError::Decryption)
+}
+
+/// `encrypt_string` で暗号化された文字列かどうか
+pub fn is_encrypted_string(value: &str) -> bool {
+    value.starts_with(STRING_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let key = EncryptionKey::generate();
+        let envelope = encrypt(&key, b"{\"title\":\"secret\"}").unwrap();
+
+        assert!(is_encrypted(&envelope));
+        assert!(!is_encrypted(b"{\"title\":\"secret\"}"));
+        assert_eq!(decrypt(&key, &envelope).unwrap(), b"{\"title\":\"secret\"}");
+    }
+
+    #[test]
+    fn test_string_roundtrip() {
+        let key = EncryptionKey::generate();
+        let encoded = encrypt_string(&key, "sk-secret").unwrap();
+
+        assert!(is_encrypted_string(&encoded));
+        assert!(!is_encrypted_string("sk-secret"));
+        assert_eq!(decrypt_string(&key, &encoded).unwrap(), "sk-secret");
+        assert!(decrypt_string(&key, "sk-secret").is_err());
+    }
+
+    #[test]
+    fn test_wrong_key_and_tampering_are_rejected() {
+        let key = EncryptionKey::generate();
+        let mut envelope = encrypt(&key, b"payload").unwrap();
+
+        assert!(matches!(
+            decrypt(&EncryptionKey::generate(), &envelope),
+            Err(SecretError::Decryption)
+        ));
+
+        let last = envelope.len() - 1;
+        envelope[last] ^= 0xff;
+        assert!(decrypt(&key, &envelope).is_err());
+    }
+}
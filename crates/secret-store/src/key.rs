@@ -0,0 +1,97 @@
+//! 暗号鍵
+
+use crate::{Result, SecretError};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{KeyInit, OsRng};
+use chacha20poly1305::XChaCha20Poly1305;
+
+/// 鍵長（バイト）
+pub const KEY_LEN: usize = 32;
+/// パスフレーズ導出用ソルト長（バイト）
+pub const SALT_LEN: usize = 16;
+
+/// 256bit の対称鍵
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    /// 乱数から新しい鍵を生成
+    pub fn generate() -> Self {
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let mut bytes = [0u8; KEY_LEN];
+        bytes.copy_from_slice(&key);
+        Self(bytes)
+    }
+
+    /// パスフレーズとソルトから Argon2id で鍵を導出
+    pub fn from_passphrase(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        let mut bytes = [0u8; KEY_LEN];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut bytes)
+            .map_err(|e| SecretError::KeyDerivation(e.to_string()))?;
+        Ok(Self(bytes))
+    }
+
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+
+    /// Base64 文字列へ変換（キーリング・ファイル保存用）
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.0)
+    }
+
+    /// Base64 文字列から復元
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let decoded = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| SecretError::InvalidKey(e.to_string()))?;
+        let bytes: [u8; KEY_LEN] = decoded
+            .try_into()
+            .map_err(|_| SecretError::InvalidKey(format!("expected {} bytes", KEY_LEN)))?;
+        Ok(Self(bytes))
+    }
+}
+
+/// 鍵の中身をログへ出さない
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// パスフレーズ導出用のランダムなソルトを生成
+pub fn generate_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_derivation_is_deterministic() {
+        let salt = generate_salt();
+        let a = EncryptionKey::from_passphrase("correct horse", &salt).unwrap();
+        let b = EncryptionKey::from_passphrase("correct horse", &salt).unwrap();
+        let c = EncryptionKey::from_passphrase("wrong horse", &salt).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let key = EncryptionKey::generate();
+        let restored = EncryptionKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(key, restored);
+        assert!(EncryptionKey::from_base64("c2hvcnQ=").is_err());
+    }
+}
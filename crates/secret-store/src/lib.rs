@@ -0,0 +1,42 @@
+//! 保存データの暗号化と鍵の保管
+//!
+//! - `EncryptionKey`: 256bit の対称鍵（乱数生成 / パスフレーズから導出）
+//! - `encrypt` / `decrypt`: XChaCha20-Poly1305 によるエンベロープ形式の暗号化
+//! - `KeyStore`: 鍵の保管先（OS キーリング / ファイル）
+
+mod cipher;
+mod key;
+mod store;
+
+pub use cipher::{
+    decrypt, decrypt_string, encrypt, encrypt_string, is_encrypted, is_encrypted_string,
+    ENVELOPE_MAGIC, STRING_PREFIX,
+};
+pub use key::{generate_salt, EncryptionKey, KEY_LEN, SALT_LEN};
+#[cfg(feature = "os-keyring")]
+pub use store::KeyringKeyStore;
+pub use store::{FileKeyStore, KeyStore};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(String),
+
+    #[error("Encryption failed")]
+    Encryption,
+
+    /// 鍵が違う、またはデータが改ざん・破損している
+    #[error("Decryption failed (wrong key or corrupted data)")]
+    Decryption,
+
+    #[error("Keyring error: {0}")]
+    Keyring(String),
+}
+
+pub type Result<T> = std::result::Result<T, SecretError>;
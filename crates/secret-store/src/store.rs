@@ -0,0 +1,150 @@
+//! 鍵の保管先
+
+use crate::{EncryptionKey, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 鍵の保管先を抽象化する
+pub trait KeyStore: Send + Sync {
+    /// 保存済みの鍵を読み込み（未保存なら None）
+    fn load(&self) -> Result<Option<EncryptionKey>>;
+    /// 鍵を保存（既存の鍵は置き換える）
+    fn store(&self, key: &EncryptionKey) -> Result<()>;
+    /// 鍵を削除（未保存でもエラーにしない）
+    fn delete(&self) -> Result<()>;
+
+    /// 鍵を読み込み、無ければ生成して保存する
+    fn load_or_create(&self) -> Result<EncryptionKey> {
+        if let Some(key) = self.load()? {
+            return Ok(key);
+        }
+        let key = EncryptionKey::generate();
+        self.store(&key)?;
+        Ok(key)
+    }
+}
+
+/// ファイルに Base64 で鍵を保存する（キーリングが使えない環境・テスト用）
+pub struct FileKeyStore {
+    path: PathBuf,
+}
+
+impl FileKeyStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl KeyStore for FileKeyStore {
+    fn load(&self) -> Result<Option<EncryptionKey>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let encoded = fs::read_to_string(&self.path)?;
+        EncryptionKey::from_base64(&encoded).map(Some)
+    }
+
+    fn store(&self, key: &EncryptionKey) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let mut file = options.open(&tmp_path)?;
+            file.write_all(key.to_base64().as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// OS のキーリングに鍵を保存する
+#[cfg(feature = "os-keyring")]
+pub struct KeyringKeyStore {
+    service: String,
+    user: String,
+}
+
+#[cfg(feature = "os-keyring")]
+impl KeyringKeyStore {
+    pub fn new(service: impl Into<String>, user: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            user: user.into(),
+        }
+    }
+
+    fn entry(&self) -> Result<keyring::Entry> {
+        keyring::Entry::new(&self.service, &self.user)
+            .map_err(|e| crate::SecretError::Keyring(e.to_string()))
+    }
+}
+
+#[cfg(feature = "os-keyring")]
+impl KeyStore for KeyringKeyStore {
+    fn load(&self) -> Result<Option<EncryptionKey>> {
+        match self.entry()?.get_password() {
+            Ok(encoded) => EncryptionKey::from_base64(&encoded).map(Some),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(crate::SecretError::Keyring(e.to_string())),
+        }
+    }
+
+    fn store(&self, key: &EncryptionKey) -> Result<()> {
+        self.entry()?
+            .set_password(&key.to_base64())
+            .map_err(|e| crate::SecretError::Keyring(e.to_string()))
+    }
+
+    fn delete(&self) -> Result<()> {
+        match self.entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(crate::SecretError::Keyring(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_file_key_store_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let store = FileKeyStore::new(temp_dir.path().join("keys").join("history.key"));
+
+        assert!(store.load().unwrap().is_none());
+        let key = store.load_or_create().unwrap();
+        assert_eq!(store.load().unwrap(), Some(key.clone()));
+        assert_eq!(store.load_or_create().unwrap(), key);
+
+        store.delete().unwrap();
+        assert!(store.load().unwrap().is_none());
+        store.delete().unwrap();
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! このクレートは複数のビューで共有されるUI機能を提供します：
 //! - テキスト入力とIME対応
-//! - スクロール機能
+//! - スクロール機能と表示中の行だけを描画する仮想化リスト
 //! - 共通のUIコンポーネント
 
 pub mod scroll_utils;
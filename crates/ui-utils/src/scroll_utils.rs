@@ -1,5 +1,9 @@
 //! スクロール関連のユーティリティ
 
+use std::cell::Cell;
+use std::ops::Range;
+use std::rc::Rc;
+
 use gpui::*;
 
 /// スクロールハンドルのラッパー
@@ -41,3 +45,118 @@ impl Default for ScrollManager {
         Self::new()
     }
 }
+
+/// 仮想化リストで一度に余分に描画する高さ（スクロール時のちらつき防止）
+const LIST_OVERDRAW: f32 = 600.0;
+
+/// 表示中の行だけを描画するリストの状態（gpui の `ListState` のラッパー）
+///
+/// 行の高さは描画時に測って `ListState` に残る。`sync` では変わった範囲だけを差し替えるので、
+/// 変わらなかった行は測り直さない。
+pub struct VirtualListState<T> {
+    list: ListState,
+    items: Rc<Vec<T>>,
+    /// 最後のスクロールで見えていた範囲の終わり
+    visible_end: Rc<Cell<usize>>,
+}
+
+impl<T: PartialEq> VirtualListState<T> {
+    pub fn new() -> Self {
+        let list = ListState::new(0, ListAlignment::Bottom, px(LIST_OVERDRAW));
+        let visible_end = Rc::new(Cell::new(usize::MAX));
+        let end = visible_end.clone();
+        list.set_scroll_handler(move |event: &ListScrollEvent, _window, _cx| {
+            end.set(event.visible_range.end);
+        });
+        Self {
+            list,
+            items: Rc::new(Vec::new()),
+            visible_end,
+        }
+    }
+
+    /// `list()` に渡す状態
+    pub fn list_state(&self) -> &ListState {
+        &self.list
+    }
+
+    /// 現在の行（描画のコールバックから添字で参照する）
+    pub fn items(&self) -> Rc<Vec<T>> {
+        self.items.clone()
+    }
+
+    /// 最後の行まで表示しているか
+    pub fn is_at_latest(&self) -> bool {
+        let count = self.list.item_count();
+        self.list.logical_scroll_top().item_ix >= count || self.visible_end.get() >= count
+    }
+
+    /// 最後の行が見える位置までスクロールする
+    pub fn scroll_to_latest(&self) {
+        self.list.scroll_to(ListOffset {
+            item_ix: self.list.item_count(),
+            offset_in_item: px(0.0),
+        });
+    }
+
+    /// 行を差し替える。挿入や更新があっても表示位置は保ち、最新を表示していたときだけ追従する。
+    /// 残る行が無いとき（会話の切り替えなど）は最新を表示する
+    pub fn sync(&mut self, items: Vec<T>) {
+        let Some((range, count)) = changed_range(&self.items, &items) else {
+            return;
+        };
+        let follow = self.is_at_latest() || range.len() == self.items.len();
+        self.list.splice(range, count);
+        self.items = Rc::new(items);
+        if follow {
+            self.scroll_to_latest();
+        }
+    }
+}
+
+impl<T: PartialEq> Default for VirtualListState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `old` を `new` にするための連続した 1 か所の置き換え（置き換える範囲, 新しい行数）。同じなら None
+pub fn changed_range<T: PartialEq>(old: &[T], new: &[T]) -> Option<(Range<usize>, usize)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    if prefix == old.len() && prefix == new.len() {
+        return None;
+    }
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    Some((prefix..old.len() - suffix, new.len() - prefix - suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_range_covers_only_the_edited_rows() {
+        assert_eq!(changed_range(&[1, 2, 3], &[1, 2, 3]), None);
+        // 追加
+        assert_eq!(changed_range(&[1, 2], &[1, 2, 3, 4]), Some((2..2, 2)));
+        // ストリーミング中の最後の行の更新
+        assert_eq!(changed_range(&[1, 2, 3], &[1, 2, 4]), Some((2..3, 1)));
+        // 途中の削除と先頭への挿入
+        assert_eq!(changed_range(&[1, 2, 3], &[1, 3]), Some((1..2, 0)));
+        assert_eq!(changed_range(&[2, 3], &[1, 2, 3]), Some((0..0, 1)));
+        // 全部入れ替わる
+        assert_eq!(changed_range(&[1, 2], &[3]), Some((0..2, 1)));
+        assert_eq!(changed_range(&[], &[1]), Some((0..0, 1)));
+    }
+
+    #[test]
+    fn changed_range_handles_repeated_rows() {
+        assert_eq!(changed_range(&[1, 1], &[1, 1, 1]), Some((2..2, 1)));
+        assert_eq!(changed_range(&[1, 1, 1], &[1]), Some((1..3, 0)));
+    }
+}
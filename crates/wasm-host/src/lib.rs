@@ -0,0 +1,451 @@
+//! WASM プラグインの実行ホスト（wasmtime + WASI preview 2 / component model）
+//!
+//! ネイティブの `cdylib` と違い、プラグインはサンドボックス内で動く。
+//! - WASI の権限は manifest の `[capabilities]` から決める（既定は何も許可しない）
+//! - 呼び出しごとに fuel を補充し、使い切ったらトラップで中断する
+//! - 線形メモリの上限を `WasmLimits::memory_bytes` で制限する
+//!
+//! ゲスト側の ABI は `wit/neko-plugin.wit` の component:
+//! - `neko:plugin/abi` の `version()` は `WASM_PLUGIN_ABI_VERSION` を返す
+//! - 各インターフェイスの関数は UTF-8 の JSON 文字列を受け取り、JSON 文字列を返す
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use wasmtime::component::{Component, Instance, Linker, ResourceTable, TypedFunc};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+use wasmtime_wasi::{DirPerms, FilePerms, IoView, WasiCtx, WasiCtxBuilder, WasiView};
+
+pub mod prompt_builder;
+pub mod tool_provider;
+
+pub use prompt_builder::WasmPromptBuilderFactory;
+pub use tool_provider::WasmToolProvider;
+
+/// ゲスト ABI のバージョン。WIT の形や JSON の形を変えたら上げる
+pub const WASM_PLUGIN_ABI_VERSION: u32 = 2;
+
+const ABI_INTERFACE: &str = "neko:plugin/abi@2.0.0";
+
+/// 実行時の資源制限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmLimits {
+    /// 1 回の呼び出しで使える fuel（おおよそ命令数）
+    pub fuel: u64,
+    /// 線形メモリの上限（バイト）
+    pub memory_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 500_000_000,
+            memory_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// manifest の capability から決まる WASI 権限
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WasmPermissions {
+    /// プラグインディレクトリを読み取り専用で `.` にマウント
+    pub filesystem_read: bool,
+    /// プラグインディレクトリを読み書き可能でマウント
+    pub filesystem_write: bool,
+    /// `wasi:sockets` の TCP / UDP と名前解決
+    pub network: bool,
+}
+
+impl WasmPermissions {
+    /// `extract_capabilities` の結果から権限を作る（`filesystem_read` / `filesystem.read` のどちらも可）
+    pub fn from_capabilities(capabilities: &HashSet<String>) -> Self {
+        let has = |names: &[&str]| names.iter().any(|name| capabilities.contains(*name));
+        let filesystem_write = has(&["filesystem_write", "filesystem.write"]);
+        Self {
+            filesystem_read: filesystem_write || has(&["filesystem_read", "filesystem.read"]),
+            filesystem_write,
+            network: has(&["network"]),
+        }
+    }
+
+    fn wasi_ctx(&self, plugin_dir: &Path) -> Result<WasiCtx> {
+        let mut builder = WasiCtxBuilder::new();
+        builder.inherit_stderr();
+        if self.filesystem_read {
+            let (dir_perms, file_perms) = if self.filesystem_write {
+                (DirPerms::all(), FilePerms::all())
+            } else {
+                (DirPerms::READ, FilePerms::READ)
+            };
+            builder
+                .preopened_dir(plugin_dir, ".", dir_perms, file_perms)
+                .with_context(|| format!("preopening {}", plugin_dir.display()))?;
+        }
+        if self.network {
+            builder.inherit_network().allow_ip_name_lookup(true);
+        } else {
+            // 既定でもアドレスの検査で全て拒否されるが、ソケット自体も作らせない
+            builder.allow_tcp(false).allow_udp(false);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// モジュールの読み込み設定
+#[derive(Debug, Clone)]
+pub struct WasmPluginConfig {
+    /// プラグインディレクトリ（ファイルシステム権限のルート）
+    pub plugin_dir: PathBuf,
+    pub permissions: WasmPermissions,
+    pub limits: WasmLimits,
+}
+
+impl WasmPluginConfig {
+    pub fn new(plugin_dir: impl Into<PathBuf>, permissions: WasmPermissions) -> Self {
+        Self {
+            plugin_dir: plugin_dir.into(),
+            permissions,
+            limits: WasmLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: WasmLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+/// コンパイル済み component。`instantiate` ごとに独立したサンドボックスを作る
+pub struct WasmModule {
+    engine: Engine,
+    component: Component,
+    linker: Linker<PluginState>,
+    config: WasmPluginConfig,
+}
+
+struct PluginState {
+    wasi: WasiCtx,
+    table: ResourceTable,
+    limits: StoreLimits,
+}
+
+impl IoView for PluginState {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for PluginState {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl WasmModule {
+    /// `.wasm` ファイル（component）を読み込む
+    pub fn load(path: &Path, config: WasmPluginConfig) -> Result<Self> {
+        let engine = Self::engine()?;
+        let component = Component::from_file(&engine, path)
+            .with_context(|| format!("compiling {}", path.display()))?;
+        Self::with_component(engine, component, config)
+    }
+
+    /// バイト列（component のバイナリ）から読み込む
+    pub fn from_bytes(bytes: &[u8], config: WasmPluginConfig) -> Result<Self> {
+        let engine = Self::engine()?;
+        let component = Component::new(&engine, bytes)?;
+        Self::with_component(engine, component, config)
+    }
+
+    fn engine() -> Result<Engine> {
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        config.consume_fuel(true);
+        Engine::new(&config)
+    }
+
+    fn with_component(
+        engine: Engine,
+        component: Component,
+        config: WasmPluginConfig,
+    ) -> Result<Self> {
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::add_to_linker_sync(&mut linker)?;
+        Ok(Self {
+            engine,
+            component,
+            linker,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &WasmPluginConfig {
+        &self.config
+    }
+
+    /// 新しいインスタンスを作り、ABI バージョンを確認する
+    pub fn instantiate(&self) -> Result<WasmInstance> {
+        let state = PluginState {
+            wasi: self.config.permissions.wasi_ctx(&self.config.plugin_dir)?,
+            table: ResourceTable::new(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.config.limits.memory_bytes)
+                .build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.config.limits.fuel)?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.component)
+            .context("instantiating wasm component")?;
+
+        let mut instance = WasmInstance {
+            store,
+            instance,
+            fuel: self.config.limits.fuel,
+        };
+        let version = instance
+            .func::<(), (u32,)>(ABI_INTERFACE, "version")
+            .map_err(|_| anyhow!("component does not export '{}#version'", ABI_INTERFACE))?;
+        let (version,) = instance.invoke(version, (), ABI_INTERFACE, "version")?;
+        if version != WASM_PLUGIN_ABI_VERSION {
+            bail!(
+                "wasm plugin ABI version mismatch: plugin {}, host {}",
+                version,
+                WASM_PLUGIN_ABI_VERSION
+            );
+        }
+
+        Ok(instance)
+    }
+}
+
+/// 1 つのサンドボックス（Store + Instance）
+pub struct WasmInstance {
+    store: Store<PluginState>,
+    instance: Instance,
+    fuel: u64,
+}
+
+impl WasmInstance {
+    /// 引数なしの関数を呼び、返された文字列を受け取る
+    pub fn call(&mut self, interface: &str, name: &str) -> Result<String> {
+        let func = self.func::<(), (String,)>(interface, name)?;
+        let (output,) = self.invoke(func, (), interface, name)?;
+        Ok(output)
+    }
+
+    /// 文字列を渡して関数を呼び、返された文字列を受け取る
+    pub fn call_with(&mut self, interface: &str, name: &str, input: &str) -> Result<String> {
+        let func = self.func::<(&str,), (String,)>(interface, name)?;
+        let (output,) = self.invoke(func, (input,), interface, name)?;
+        Ok(output)
+    }
+
+    /// `interface` がエクスポートする関数 `name` を型付きで取り出す
+    fn func<Params, Results>(
+        &mut self,
+        interface: &str,
+        name: &str,
+    ) -> Result<TypedFunc<Params, Results>>
+    where
+        Params: wasmtime::component::ComponentNamedList + wasmtime::component::Lower,
+        Results: wasmtime::component::ComponentNamedList + wasmtime::component::Lift,
+    {
+        let exported = self
+            .instance
+            .get_export(&mut self.store, None, interface)
+            .ok_or_else(|| anyhow!("component does not export '{}'", interface))?;
+        let index = self
+            .instance
+            .get_export(&mut self.store, Some(&exported), name)
+            .ok_or_else(|| anyhow!("'{}' does not export '{}'", interface, name))?;
+        self.instance
+            .get_typed_func(&mut self.store, index)
+            .with_context(|| format!("resolving '{}#{}'", interface, name))
+    }
+
+    /// fuel を補充して呼び出し、後処理（`post-return`）まで行う
+    fn invoke<Params, Results>(
+        &mut self,
+        func: TypedFunc<Params, Results>,
+        params: Params,
+        interface: &str,
+        name: &str,
+    ) -> Result<Results>
+    where
+        Params: wasmtime::component::ComponentNamedList + wasmtime::component::Lower,
+        Results: wasmtime::component::ComponentNamedList + wasmtime::component::Lift,
+    {
+        self.store.set_fuel(self.fuel)?;
+        let results = func
+            .call(&mut self.store, params)
+            .with_context(|| format!("calling '{}#{}'", interface, name))?;
+        func.post_return(&mut self.store)?;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_modules {
+    /// コアモジュールの共通部分（メモリ・`cabi_realloc`・戻り値の詰め替え・`version`）
+    const CORE_PRELUDE: &str = r#"
+    (memory (export "memory") 1)
+    (global $next (mut i32) (i32.const 4096))
+    (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (global.get $next))
+      (global.set $next (i32.add (global.get $next) (local.get 3)))
+      (local.get $ptr))
+    (func $ret (param $ptr i32) (param $len i32) (result i32)
+      (i32.store (i32.const 3072) (local.get $ptr))
+      (i32.store (i32.const 3076) (local.get $len))
+      (i32.const 3072))
+    (func (export "version") (result i32) (i32.const 2))
+"#;
+
+    const OPTIONS: &str = r#"(memory $i "memory") (realloc (func $i "cabi_realloc"))"#;
+
+    /// `core` を持つコアモジュールを `neko:plugin/abi` と `interface` として公開する component
+    fn component(core: &str, interface: &str, funcs: &[(&str, &str)]) -> Vec<u8> {
+        let lifts: String = funcs
+            .iter()
+            .map(|(name, ty)| {
+                format!(r#"(func ${name} {ty} (canon lift (core func $i "{name}") {OPTIONS}))"#)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let exports: String = funcs
+            .iter()
+            .map(|(name, _)| format!(r#"(export "{name}" (func ${name}))"#))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let wat = format!(
+            r#"
+(component
+  (core module $m {CORE_PRELUDE} {core})
+  (core instance $i (instantiate $m))
+  (func $version (result u32) (canon lift (core func $i "version")))
+  {lifts}
+  (instance $abi (export "version" (func $version)))
+  (instance $plugin {exports})
+  (export "neko:plugin/abi@2.0.0" (instance $abi))
+  (export "neko:plugin/{interface}@2.0.0" (instance $plugin))
+)
+"#
+        );
+        wat::parse_str(wat).expect("valid wat")
+    }
+
+    /// テスト用の最小 component。`build` / `parse` は固定の JSON を返す。
+    /// `spin` は無限ループ（fuel 切れの確認用）
+    pub fn echo_bytes() -> Vec<u8> {
+        component(
+            r#"
+    (data (i32.const 0) "{\"name\":\"wasm-echo\",\"version\":\"0.1.0\",\"description\":null,\"supported_models\":[\"wasm:latest\"],\"homepage\":null,\"preferred_agent\":\"DirectProvider\"}")
+    (data (i32.const 1024) "{\"Ok\":{\"agent_mode\":\"DirectProvider\",\"prompt\":\"from wasm\",\"prompt_variables\":{},\"execution_hints\":{}}}")
+    (data (i32.const 2048) "{\"Ok\":{\"final_answer\":\"parsed\",\"tool_requests\":[]}}")
+    (func (export "metadata") (result i32) (call $ret (i32.const 0) (i32.const 143)))
+    (func (export "build") (param i32 i32) (result i32) (call $ret (i32.const 1024) (i32.const 102)))
+    (func (export "parse") (param i32 i32) (result i32) (call $ret (i32.const 2048) (i32.const 51)))
+    (func (export "spin") (result i32) (loop $l (br $l)) (i32.const 0))
+"#,
+            "prompt-builder",
+            &[
+                ("metadata", "(result string)"),
+                ("build", r#"(param "context" string) (result string)"#),
+                ("parse", r#"(param "raw-output" string) (result string)"#),
+                ("spin", "(result string)"),
+            ],
+        )
+    }
+
+    /// `answer` ツールだけを持つツールプロバイダー（常に 42 を返す）
+    pub fn tools_bytes() -> Vec<u8> {
+        component(
+            r#"
+    (data (i32.const 0) "[{\"name\":\"answer\",\"description\":\"Returns 42\",\"input_schema\":{\"type\":\"object\"}}]")
+    (data (i32.const 1024) "{\"Ok\":42}")
+    (func (export "list") (result i32) (call $ret (i32.const 0) (i32.const 79)))
+    (func (export "invoke") (param i32 i32) (result i32) (call $ret (i32.const 1024) (i32.const 9)))
+"#,
+            "tool-provider",
+            &[
+                ("list", "(result string)"),
+                ("invoke", r#"(param "request" string) (result string)"#),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILDER: &str = "neko:plugin/prompt-builder@2.0.0";
+
+    fn config() -> WasmPluginConfig {
+        WasmPluginConfig::new(std::env::temp_dir(), WasmPermissions::default())
+    }
+
+    #[test]
+    fn permissions_follow_capabilities() {
+        let none = WasmPermissions::from_capabilities(&HashSet::new());
+        assert_eq!(none, WasmPermissions::default());
+
+        let caps: HashSet<String> = ["filesystem.write".to_string()].into_iter().collect();
+        let perms = WasmPermissions::from_capabilities(&caps);
+        assert!(perms.filesystem_read && perms.filesystem_write);
+        assert!(!perms.network);
+    }
+
+    #[test]
+    fn fuel_limit_stops_runaway_calls() {
+        let module = WasmModule::from_bytes(
+            &test_modules::echo_bytes(),
+            config().with_limits(WasmLimits {
+                fuel: 10_000,
+                ..WasmLimits::default()
+            }),
+        )
+        .unwrap();
+        let mut instance = module.instantiate().unwrap();
+        let err = instance.call(BUILDER, "spin").unwrap_err();
+        assert!(format!("{:?}", err).contains("fuel"), "{:?}", err);
+
+        // fuel はインスタンス・呼び出しごとに補充される
+        let mut instance = module.instantiate().unwrap();
+        assert!(instance.call(BUILDER, "metadata").is_ok());
+        assert!(instance.call(BUILDER, "metadata").is_ok());
+    }
+
+    #[test]
+    fn memory_limit_rejects_large_modules() {
+        let module = WasmModule::from_bytes(
+            &test_modules::echo_bytes(),
+            config().with_limits(WasmLimits {
+                memory_bytes: 1024,
+                ..WasmLimits::default()
+            }),
+        )
+        .unwrap();
+        assert!(module.instantiate().is_err());
+    }
+
+    #[test]
+    fn rejects_components_without_abi_version() {
+        let bytes = wat::parse_str("(component)").unwrap();
+        let module = WasmModule::from_bytes(&bytes, config()).unwrap();
+        let err = module.instantiate().err().unwrap();
+        assert!(err.to_string().contains("neko:plugin/abi"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_core_modules() {
+        let bytes = wat::parse_str(r#"(module (memory (export "memory") 1))"#).unwrap();
+        assert!(WasmModule::from_bytes(&bytes, config()).is_err());
+    }
+}
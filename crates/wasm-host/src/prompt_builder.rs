@@ -0,0 +1,143 @@
+//! WASM で実装された `PromptBuilder`
+//!
+//! ゲストは `neko:plugin/prompt-builder` の `metadata` / `build` / `parse` をエクスポートする。
+//! JSON の形は `prompt_spi::ffi` と同じ（`OwnedPromptContext` を受け取り `Result<_, PromptSpiError>` を返す）。
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use prompt_spi::ffi::OwnedPromptContext;
+use prompt_spi::{
+    PromptBuilder, PromptBuilderFactory, PromptContext, PromptMetadata, PromptParseOutput,
+    PromptPayload, PromptSpiError, PromptSpiResult,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{WasmInstance, WasmModule, WasmPluginConfig};
+
+const INTERFACE: &str = "neko:plugin/prompt-builder@2.0.0";
+const METADATA_EXPORT: &str = "metadata";
+const BUILD_EXPORT: &str = "build";
+const PARSE_EXPORT: &str = "parse";
+
+/// `.wasm` から作る `PromptBuilderFactory`。`create` ごとに新しいサンドボックスを作る
+pub struct WasmPromptBuilderFactory {
+    module: Arc<WasmModule>,
+    metadata: PromptMetadata,
+}
+
+impl WasmPromptBuilderFactory {
+    pub fn load(path: &Path, config: WasmPluginConfig) -> Result<Self> {
+        Self::from_module(WasmModule::load(path, config)?)
+    }
+
+    pub fn from_module(module: WasmModule) -> Result<Self> {
+        let mut instance = module.instantiate()?;
+        let json = instance.call(INTERFACE, METADATA_EXPORT)?;
+        let metadata = serde_json::from_str(&json).context("parsing prompt builder metadata")?;
+        Ok(Self {
+            module: Arc::new(module),
+            metadata,
+        })
+    }
+}
+
+impl PromptBuilderFactory for WasmPromptBuilderFactory {
+    fn metadata(&self) -> PromptMetadata {
+        self.metadata.clone()
+    }
+
+    fn create(&self) -> Box<dyn PromptBuilder> {
+        Box::new(WasmPromptBuilder {
+            instance: Mutex::new(self.module.instantiate().map_err(|e| format!("{:#}", e))),
+            module: Arc::clone(&self.module),
+            metadata: self.metadata.clone(),
+        })
+    }
+}
+
+struct WasmPromptBuilder {
+    instance: Mutex<Result<WasmInstance, String>>,
+    module: Arc<WasmModule>,
+    metadata: PromptMetadata,
+}
+
+impl WasmPromptBuilder {
+    fn call<T: DeserializeOwned>(
+        &self,
+        export: &str,
+        input: &str,
+        map_err: fn(String) -> PromptSpiError,
+    ) -> PromptSpiResult<T> {
+        let mut guard = self
+            .instance
+            .lock()
+            .map_err(|_| map_err("wasm instance lock poisoned".into()))?;
+        let instance = guard.as_mut().map_err(|e| map_err(e.clone()))?;
+        let json = match instance.call_with(INTERFACE, export, input) {
+            Ok(json) => json,
+            Err(err) => {
+                // トラップしたインスタンスには再入できないため作り直す
+                *guard = self.module.instantiate().map_err(|e| format!("{:#}", e));
+                return Err(map_err(format!("{:#}", err)));
+            }
+        };
+        serde_json::from_str::<Result<T, PromptSpiError>>(&json)
+            .map_err(|e| map_err(e.to_string()))?
+    }
+}
+
+impl PromptBuilder for WasmPromptBuilder {
+    fn metadata(&self) -> PromptMetadata {
+        self.metadata.clone()
+    }
+
+    fn build(&self, ctx: PromptContext) -> PromptSpiResult<PromptPayload> {
+        let request = serde_json::to_string(&OwnedPromptContext::from_context(&ctx))
+            .map_err(|e| PromptSpiError::Context(e.to_string()))?;
+        self.call(BUILD_EXPORT, &request, PromptSpiError::Build)
+    }
+
+    fn parse(&self, raw_output: &str) -> PromptSpiResult<PromptParseOutput> {
+        self.call(PARSE_EXPORT, raw_output, PromptSpiError::Parse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_modules, WasmPermissions};
+    use prompt_spi::{ConversationRole, ConversationTurn};
+
+    #[test]
+    fn runs_prompt_builder_in_sandbox() {
+        let module = WasmModule::from_bytes(
+            &test_modules::echo_bytes(),
+            WasmPluginConfig::new(std::env::temp_dir(), WasmPermissions::default()),
+        )
+        .unwrap();
+        let factory = WasmPromptBuilderFactory::from_module(module).unwrap();
+        assert_eq!(factory.metadata().name, "wasm-echo");
+
+        let builder = factory.create();
+        let turns = [ConversationTurn {
+            role: ConversationRole::User,
+            content: "こんにちは",
+        }];
+        let payload = builder
+            .build(PromptContext {
+                model: "wasm:latest",
+                locale: "ja-JP",
+                conversation: &turns,
+                tools: &[],
+                system_directives: &[],
+                settings: &Default::default(),
+            })
+            .unwrap();
+        assert_eq!(payload.prompt.as_deref(), Some("from wasm"));
+
+        let parsed = builder.parse("raw").unwrap();
+        assert_eq!(parsed.final_answer.as_deref(), Some("parsed"));
+    }
+}
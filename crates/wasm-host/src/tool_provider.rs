@@ -0,0 +1,94 @@
+//! WASM で実装された `ToolProvider`
+//!
+//! ゲストは `neko:plugin/tool-provider` の `list` と `invoke` をエクスポートする。
+//! JSON の形は `prompt_spi::ffi` と同じ（`Vec<ToolSpec>` を返し、`ToolInvocation` を受け取って
+//! `Result<Value, PromptSpiError>` を返す）。
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use prompt_spi::{PromptSpiError, PromptSpiResult, ToolInvocation, ToolProvider, ToolSpec};
+use serde_json::Value;
+
+use crate::{WasmInstance, WasmModule, WasmPluginConfig};
+
+const INTERFACE: &str = "neko:plugin/tool-provider@2.0.0";
+const LIST_EXPORT: &str = "list";
+const INVOKE_EXPORT: &str = "invoke";
+
+/// `.wasm` から作る `ToolProvider`。サンドボックスは 1 つで、呼び出しは直列に実行する
+pub struct WasmToolProvider {
+    module: WasmModule,
+    instance: Mutex<WasmInstance>,
+    tools: Vec<ToolSpec>,
+}
+
+impl WasmToolProvider {
+    pub fn load(path: &Path, config: WasmPluginConfig) -> Result<Self> {
+        Self::from_module(WasmModule::load(path, config)?)
+    }
+
+    pub fn from_module(module: WasmModule) -> Result<Self> {
+        let mut instance = module.instantiate()?;
+        let json = instance.call(INTERFACE, LIST_EXPORT)?;
+        let tools = serde_json::from_str(&json).context("parsing tool list")?;
+        Ok(Self {
+            module,
+            instance: Mutex::new(instance),
+            tools,
+        })
+    }
+}
+
+impl ToolProvider for WasmToolProvider {
+    fn tools(&self) -> Vec<ToolSpec> {
+        self.tools.clone()
+    }
+
+    fn invoke(&self, name: &str, arguments: Value) -> PromptSpiResult<Value> {
+        let request = serde_json::to_string(&ToolInvocation {
+            name: name.to_string(),
+            arguments,
+        })
+        .map_err(|e| PromptSpiError::Tool(e.to_string()))?;
+        let mut instance = self
+            .instance
+            .lock()
+            .map_err(|_| PromptSpiError::Tool("wasm instance lock poisoned".into()))?;
+        let json = match instance.call_with(INTERFACE, INVOKE_EXPORT, &request) {
+            Ok(json) => json,
+            Err(err) => {
+                // トラップしたインスタンスには再入できないため作り直す
+                if let Ok(fresh) = self.module.instantiate() {
+                    *instance = fresh;
+                }
+                return Err(PromptSpiError::Tool(format!("{:#}", err)));
+            }
+        };
+        serde_json::from_str::<Result<Value, PromptSpiError>>(&json)
+            .map_err(|e| PromptSpiError::Tool(e.to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_modules, WasmPermissions};
+
+    #[test]
+    fn runs_tool_provider_in_sandbox() {
+        let module = WasmModule::from_bytes(
+            &test_modules::tools_bytes(),
+            WasmPluginConfig::new(std::env::temp_dir(), WasmPermissions::default()),
+        )
+        .unwrap();
+        let provider = WasmToolProvider::from_module(module).unwrap();
+        let tools = provider.tools();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "answer");
+
+        let result = provider.invoke("answer", serde_json::json!({})).unwrap();
+        assert_eq!(result, serde_json::json!(42));
+    }
+}
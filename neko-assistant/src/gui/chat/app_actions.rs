@@ -0,0 +1,138 @@
+//! キーボードショートカットとコマンドパレットから呼ばれるアプリの操作
+
+use super::menu_actions::open_mcp_manager;
+use super::ChatView;
+use crate::gui::keymap::*;
+use chat_core::ChatCommand;
+use gpui::*;
+
+impl ChatView {
+    pub(super) fn run_app_action(
+        &mut self,
+        action: AppAction,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        match action {
+            AppAction::CommandPalette => self.toggle_command_palette(window, cx),
+            AppAction::NewChat => self.new_conversation(window, cx),
+            AppAction::NextConversation => self.step_conversation(1, window, cx),
+            AppAction::PreviousConversation => self.step_conversation(-1, window, cx),
+            AppAction::NewTab => self.new_tab(window, cx),
+            AppAction::CloseTab => self.close_tab(self.tabs.active_index(), window, cx),
+            AppAction::NextTab => self.step_tab(1, window, cx),
+            AppAction::PreviousTab => self.step_tab(-1, window, cx),
+            AppAction::NewWindow => self.open_new_window(cx),
+            AppAction::NextModel => self.step_model(window, cx),
+            AppAction::OpenSettings => crate::gui::settings::open_settings_window(cx),
+            AppAction::OpenMcpManager => open_mcp_manager(self.controller().controller(), cx),
+            AppAction::ToggleConsole => self.state.toggle_console(),
+            AppAction::ToggleScratchpad => self.state.toggle_scratchpad(),
+            AppAction::ToggleSidebar => self.state.toggle_sidebar(),
+            AppAction::ToggleMcpStatus => self.state.toggle_mcp_status(),
+        }
+        cx.notify();
+    }
+
+    pub(super) fn run_chat_command(&mut self, command: ChatCommand, cx: &mut Context<ChatView>) {
+        if let Err(err) = self.controller().handle_command(command) {
+            eprintln!("Command failed: {}", err.message());
+        }
+        cx.notify();
+    }
+
+    /// 会話一覧（サイドバーの並び）で前後の会話に切り替える
+    fn step_conversation(&mut self, step: isize, window: &mut Window, cx: &mut Context<ChatView>) {
+        let state = self.chat_state_snapshot();
+        let ids: Vec<&str> = state.conversations.iter().map(|c| c.id.as_str()).collect();
+        let Some(target) = neighbour(&ids, state.conversation_id.as_deref(), step) else {
+            return;
+        };
+        let target = target.to_string();
+        self.open_conversation(&target, window, cx);
+    }
+
+    /// 利用できるモデルを順に切り替える
+    fn step_model(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        let state = self.chat_state_snapshot();
+        let ids: Vec<&str> = state
+            .available_models
+            .iter()
+            .map(|model| model.id.as_str())
+            .collect();
+        let Some(target) = neighbour(&ids, Some(state.active_model.as_str()), 1) else {
+            return;
+        };
+        let target = target.to_string();
+        self.switch_model_to(&target, window, cx);
+    }
+
+    pub(super) fn switch_model_to(
+        &mut self,
+        model: &str,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        if let Err(err) =
+            self.state
+                .model_selector()
+                .switch_model(self.controller(), model, window, cx)
+        {
+            eprintln!("Failed to switch model: {}", err);
+        }
+    }
+}
+
+/// ショートカットのアクションを ChatView の操作につなぐ
+pub(super) fn on_app_actions(root: Div, cx: &mut Context<ChatView>) -> Div {
+    fn bind<A: Action>(root: Div, action: AppAction, cx: &mut Context<ChatView>) -> Div {
+        root.on_action(cx.listener(move |this: &mut ChatView, _: &A, window, cx| {
+            this.run_app_action(action, window, cx)
+        }))
+    }
+
+    let root = root.key_context(KEY_CONTEXT);
+    let root = bind::<CommandPalette>(root, AppAction::CommandPalette, cx);
+    let root = bind::<NewChat>(root, AppAction::NewChat, cx);
+    let root = bind::<NextConversation>(root, AppAction::NextConversation, cx);
+    let root = bind::<PreviousConversation>(root, AppAction::PreviousConversation, cx);
+    let root = bind::<NewTab>(root, AppAction::NewTab, cx);
+    let root = bind::<CloseTab>(root, AppAction::CloseTab, cx);
+    let root = bind::<NextTab>(root, AppAction::NextTab, cx);
+    let root = bind::<PreviousTab>(root, AppAction::PreviousTab, cx);
+    let root = bind::<NewWindow>(root, AppAction::NewWindow, cx);
+    let root = bind::<NextModel>(root, AppAction::NextModel, cx);
+    let root = bind::<OpenSettings>(root, AppAction::OpenSettings, cx);
+    let root = bind::<OpenMcpManager>(root, AppAction::OpenMcpManager, cx);
+    let root = bind::<ToggleConsole>(root, AppAction::ToggleConsole, cx);
+    let root = bind::<ToggleScratchpad>(root, AppAction::ToggleScratchpad, cx);
+    let root = bind::<ToggleSidebar>(root, AppAction::ToggleSidebar, cx);
+    bind::<ToggleMcpStatus>(root, AppAction::ToggleMcpStatus, cx)
+}
+
+/// `current` から `step` だけ進んだ要素（端では反対側に回る。current が無ければ先頭）
+fn neighbour<'a>(items: &[&'a str], current: Option<&str>, step: isize) -> Option<&'a str> {
+    if items.is_empty() {
+        return None;
+    }
+    let len = items.len() as isize;
+    let index = match current.and_then(|current| items.iter().position(|id| *id == current)) {
+        Some(index) => (index as isize + step).rem_euclid(len),
+        None => 0,
+    };
+    Some(items[index as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::neighbour;
+
+    #[test]
+    fn neighbour_wraps_around() {
+        let items = ["a", "b", "c"];
+        assert_eq!(neighbour(&items, Some("c"), 1), Some("a"));
+        assert_eq!(neighbour(&items, Some("a"), -1), Some("c"));
+        assert_eq!(neighbour(&items, Some("x"), 1), Some("a"));
+        assert_eq!(neighbour(&[], Some("a"), 1), None);
+    }
+}
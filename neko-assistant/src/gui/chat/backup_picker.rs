@@ -0,0 +1,99 @@
+//! 開いている会話をバックアップのどの世代から復元するかを選ぶウィンドウ
+
+use crate::gui::window_options_with_title;
+use chat_core::{ChatCommand, ChatController};
+use chat_history::BackupInfo;
+use gpui::*;
+use gpui_component::button::Button;
+use gpui_component::{Root, StyledExt};
+use neko_ui::current_theme;
+use std::sync::Arc;
+
+pub struct BackupPickerView {
+    controller: Arc<ChatController>,
+    conversation_id: String,
+    backups: Vec<BackupInfo>,
+    status: Option<String>,
+}
+
+impl BackupPickerView {
+    fn new(controller: Arc<ChatController>, conversation_id: String) -> Self {
+        let (backups, status) = match controller.conversation_backups(&conversation_id) {
+            Ok(backups) if backups.is_empty() => (backups, Some("No backups yet".to_string())),
+            Ok(backups) => (backups, None),
+            Err(err) => (Vec::new(), Some(err.message().to_string())),
+        };
+        Self {
+            controller,
+            conversation_id,
+            backups,
+            status,
+        }
+    }
+
+    fn restore(&mut self, backup: &BackupInfo, window: &mut Window, cx: &mut Context<Self>) {
+        let command = ChatCommand::RestoreConversationBackup {
+            id: self.conversation_id.clone(),
+            backup: Some(backup.path.clone()),
+        };
+        match self.controller.handle_command(command) {
+            Ok(()) => window.remove_window(),
+            Err(err) => {
+                self.status = Some(format!("Failed to restore: {}", err.message()));
+                cx.notify();
+            }
+        }
+    }
+}
+
+/// 開いている会話のバックアップ一覧を別ウィンドウで開く（会話が無ければ何もしない）
+pub fn open_backup_picker_window(controller: Arc<ChatController>, cx: &mut App) {
+    let Some(conversation_id) = controller.state_snapshot().conversation_id else {
+        return;
+    };
+    let _ = cx.open_window(
+        window_options_with_title("Restore previous version"),
+        move |window, cx| {
+            let view = cx.new(|_| BackupPickerView::new(controller, conversation_id));
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    );
+}
+
+impl Render for BackupPickerView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        crate::gui::theme::init_components(cx);
+        let colors = current_theme().colors;
+
+        let buttons = self.backups.iter().enumerate().map(|(idx, backup)| {
+            let local = backup.created_at.with_timezone(&chrono::Local);
+            let mut label = local.format("%Y-%m-%d %H:%M:%S").to_string();
+            if idx == 0 {
+                label.push_str(" (latest)");
+            }
+            let backup = backup.clone();
+            Button::new(SharedString::from(format!("backup_{}", idx)))
+                .label(label)
+                .on_click(cx.listener(move |this, _ev, window, cx| {
+                    this.restore(&backup, window, cx);
+                }))
+        });
+
+        div()
+            .v_flex()
+            .gap_2()
+            .p_3()
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(colors.text_muted)
+                    .child("Replace the conversation with the version saved at:"),
+            )
+            .children(buttons)
+            .children(
+                self.status
+                    .clone()
+                    .map(|status| div().text_sm().text_color(colors.text_subtle).child(status)),
+            )
+    }
+}
@@ -0,0 +1,165 @@
+//! 現在の会話の文脈を、モデルに一致するすべてのプロンプトビルダーで組み立てて並べる（デバッグ用）
+
+use super::chat_view::describe_agent_mode;
+use crate::gui::window_options_with_title;
+use chat_core::{BuilderPreview, ChatController};
+use gpui::*;
+use gpui_component::button::Button;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{Root, StyledExt};
+use neko_ui::current_theme;
+use std::sync::Arc;
+use ui_utils::TextStyleExt;
+
+pub struct BuilderCompareView {
+    controller: Arc<ChatController>,
+    model: String,
+    previews: Vec<BuilderPreview>,
+    status: Option<String>,
+}
+
+impl BuilderCompareView {
+    fn new(controller: Arc<ChatController>) -> Self {
+        Self {
+            controller,
+            model: String::new(),
+            previews: Vec::new(),
+            status: None,
+        }
+    }
+
+    /// 会話の最新の状態でプロンプトを組み立て直す
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.model = self.controller.state_snapshot().active_model;
+        self.status = Some("Building prompts…".into());
+        let receiver = self.controller.preview_prompt_builders();
+
+        cx.spawn(async move |this, cx| {
+            let result = receiver
+                .await
+                .unwrap_or_else(|_| Err("preview task was cancelled".into()));
+            let _ = this.update(cx, |view, cx| {
+                match result {
+                    Ok(previews) => {
+                        view.status = previews
+                            .is_empty()
+                            .then(|| format!("No prompt builder matches {}", view.model));
+                        view.previews = previews;
+                    }
+                    Err(err) => view.status = Some(err),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+        cx.notify();
+    }
+}
+
+pub fn open_builder_compare_window(controller: Arc<ChatController>, cx: &mut App) {
+    let _ = cx.open_window(
+        window_options_with_title("Compare prompt builders"),
+        move |window, cx| {
+            let view = cx.new(|cx| {
+                let mut view = BuilderCompareView::new(controller);
+                view.refresh(cx);
+                view
+            });
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    );
+}
+
+fn preview_column(preview: &BuilderPreview) -> Div {
+    let title = if preview.selected {
+        format!("{} ← used", preview.label)
+    } else {
+        preview.label.clone()
+    };
+    let colors = current_theme().colors;
+    let mut column = div()
+        .v_flex()
+        .gap_1()
+        .flex_1()
+        .min_w(px(0.0))
+        .p_2()
+        .rounded_md()
+        .bg(colors.surface)
+        .child(div().text_md().text_color(colors.text).child(title))
+        .child(
+            div()
+                .text_xs()
+                .text_color(colors.text_subtle)
+                .child(format!("{} · priority {}", preview.id, preview.priority)),
+        );
+
+    column = match &preview.result {
+        Ok(payload) => {
+            let mut details = vec![format!(
+                "Agent: {}",
+                describe_agent_mode(payload.agent_mode)
+            )];
+            if !payload.prompt_variables.is_empty() {
+                details.push(format!(
+                    "Variables: {}",
+                    serde_json::Value::Object(payload.prompt_variables.clone())
+                ));
+            }
+            column
+                .children(
+                    details
+                        .into_iter()
+                        .map(|line| div().text_xs().text_color(colors.text_muted).child(line)),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .text_xs()
+                        .text_color(colors.text_muted)
+                        .overflow_y_scrollbar()
+                        .child(payload.prompt.clone().unwrap_or_default()),
+                )
+        }
+        Err(err) => column.child(
+            div()
+                .text_xs()
+                .text_color(colors.error_text)
+                .child(format!("Build failed: {}", err)),
+        ),
+    };
+    column
+}
+
+impl Render for BuilderCompareView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        crate::gui::theme::init_components(cx);
+
+        let header = div()
+            .h_flex()
+            .items_center()
+            .gap_2()
+            .child(div().child(format!("Model: {}", self.model)))
+            .child(
+                Button::new("refresh_builder_previews")
+                    .label("Refresh")
+                    .on_click(cx.listener(|this, _ev, _window, cx| {
+                        this.refresh(cx);
+                    })),
+            )
+            .children(self.status.clone());
+
+        div()
+            .v_flex()
+            .gap_2()
+            .p_2()
+            .size_full()
+            .child(header)
+            .child(
+                div()
+                    .h_flex()
+                    .gap_2()
+                    .flex_1()
+                    .children(self.previews.iter().map(preview_column)),
+            )
+    }
+}
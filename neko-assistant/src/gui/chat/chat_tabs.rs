@@ -0,0 +1,142 @@
+//! ウィンドウ内の会話タブ（タブごとにコントローラーのセッションとスクロール位置を持つ）
+
+use super::controller_facade::ChatControllerFacade;
+use neko_ui::ChatMessageRow;
+use ui_utils::VirtualListState;
+
+/// 1 つの会話タブ。生成中の返答・モデル・スクロール位置はタブごとに独立する。
+pub struct ChatTab {
+    controller: ChatControllerFacade,
+    /// メッセージ一覧の仮想化リスト（測った行の高さとスクロール位置を保持する）
+    message_list: VirtualListState<ChatMessageRow>,
+}
+
+impl ChatTab {
+    pub fn new(controller: ChatControllerFacade) -> Self {
+        Self {
+            controller,
+            message_list: VirtualListState::new(),
+        }
+    }
+
+    pub fn controller(&self) -> &ChatControllerFacade {
+        &self.controller
+    }
+
+    pub fn message_list(&self) -> &VirtualListState<ChatMessageRow> {
+        &self.message_list
+    }
+
+    pub fn message_list_mut(&mut self) -> &mut VirtualListState<ChatMessageRow> {
+        &mut self.message_list
+    }
+
+    /// タブで開いている会話の ID
+    pub fn conversation_id(&self) -> Option<String> {
+        self.controller.state_snapshot().conversation_id
+    }
+}
+
+impl Drop for ChatTab {
+    /// タブを閉じたら（ウィンドウごと閉じた場合も）セッションを閉じて会話を保存する
+    fn drop(&mut self) {
+        if let Err(err) = self.controller.controller().close_session() {
+            eprintln!("Failed to close chat session: {}", err.message());
+        }
+    }
+}
+
+/// ウィンドウ内のタブ一覧と選択中のタブ。常に 1 つ以上のタブを持つ。
+pub struct ChatTabs {
+    tabs: Vec<ChatTab>,
+    active: usize,
+}
+
+impl ChatTabs {
+    pub fn new(first: ChatTab) -> Self {
+        Self {
+            tabs: vec![first],
+            active: 0,
+        }
+    }
+
+    pub fn active(&self) -> &ChatTab {
+        &self.tabs[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut ChatTab {
+        &mut self.tabs[self.active]
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ChatTab> {
+        self.tabs.iter()
+    }
+
+    /// タブを末尾に追加して選択する
+    pub fn push(&mut self, tab: ChatTab) {
+        self.tabs.push(tab);
+        self.active = self.tabs.len() - 1;
+    }
+
+    /// 指定位置のタブを選択する（範囲外なら false）
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() {
+            return false;
+        }
+        self.active = index;
+        true
+    }
+
+    /// 前後のタブへ移る（端では反対側に回る）
+    pub fn step(&mut self, step: isize) {
+        let len = self.tabs.len() as isize;
+        self.active = (self.active as isize + step).rem_euclid(len) as usize;
+    }
+
+    /// 指定の会話を開いているタブの位置
+    pub fn position_of(&self, conversation_id: &str) -> Option<usize> {
+        self.tabs
+            .iter()
+            .position(|tab| tab.conversation_id().as_deref() == Some(conversation_id))
+    }
+
+    /// タブを取り除く。最後の 1 つは閉じない（None を返す）。
+    pub fn remove(&mut self, index: usize) -> Option<ChatTab> {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        self.active = active_after_remove(self.active, index, self.tabs.len());
+        Some(tab)
+    }
+}
+
+/// `removed` のタブを閉じた後に選択するタブ（閉じたタブが選択中なら右隣、端なら左隣）
+fn active_after_remove(active: usize, removed: usize, remaining: usize) -> usize {
+    let active = if removed < active { active - 1 } else { active };
+    active.min(remaining.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::active_after_remove;
+
+    #[test]
+    fn active_tab_moves_to_a_neighbour_after_close() {
+        // 選択中より左を閉じると位置がずれる
+        assert_eq!(active_after_remove(2, 0, 3), 1);
+        // 選択中を閉じると右隣、末尾なら左隣
+        assert_eq!(active_after_remove(1, 1, 3), 1);
+        assert_eq!(active_after_remove(3, 3, 3), 2);
+        // 選択中より右を閉じても変わらない
+        assert_eq!(active_after_remove(0, 2, 3), 0);
+    }
+}
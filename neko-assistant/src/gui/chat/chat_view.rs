@@ -1,24 +1,33 @@
+use super::app_actions::on_app_actions;
+use super::chat_tabs::{ChatTab, ChatTabs};
 use super::chat_view_state::ChatViewState;
 use super::chat_window::chat_window;
+use super::console_widget::console_filter_widget;
 use super::controller_facade::ChatControllerFacade;
 use super::event_loop::ChatEventLoop;
 use super::initialization::{ChatViewBuilder, ChatViewParts};
 use super::menu_actions::manage_mcp_button;
 use super::menu_bar_widget::menu_bar_widget;
 use super::menu_context::MenuContext;
+use super::notes_widget::notes_bar_widget;
+use super::sidebar_widget::sidebar_widget;
+use super::tab_bar_widget::tab_bar_widget;
 use super::toolbar_view_model::ToolbarViewModel;
 use super::toolbar_widget::toolbar_widget;
 use super::ui_state::ChatUiSnapshot;
+use crate::gui::keymap::bind_keymap;
+use crate::gui::theme::{apply_configured_theme, init_components};
 use crate::gui::window_options_with_title;
+use app_config::Keymap;
 use chat_core::{
-    discover_plugins, register_builtin_prompt_builders, ChatCommand, ChatState, PluginEntry,
-    PromptBuilderRegistry,
+    discover_plugins, register_builtin_prompt_builders, ChatCommand, ChatController, ChatState,
+    HotReloadWatcher, PluginEntry, PromptBuilderRegistry,
 };
 use gpui::*;
 use gpui_component::button::Button;
 use gpui_component::{Root, StyledExt};
 use neko_ui::{
-    chat_input_panel, chat_messages_panel, chat_workspace, mcp_status_panel, model_selector_row,
+    chat_messages_panel, chat_workspace, current_theme, mcp_status_panel, model_selector_row,
     scratchpad_console,
 };
 use prompt_spi::PromptAgentMode;
@@ -29,9 +38,12 @@ pub struct ChatView {
     pub(super) repo_root: PathBuf,
     pub(super) plugins: Vec<PluginEntry>,
     pub(super) prompt_registry: Arc<PromptBuilderRegistry>,
-    pub(super) controller: ChatControllerFacade,
+    /// 会話タブ（タブごとにコントローラーのセッションを持つ）
+    pub(super) tabs: ChatTabs,
     pub(super) event_loop: ChatEventLoop,
     pub(super) state: ChatViewState,
+    /// プラグイン・MCP 設定の監視（最初のウィンドウだけが持つ）
+    _hot_reload: Option<HotReloadWatcher>,
 }
 
 impl ChatView {
@@ -42,10 +54,37 @@ impl ChatView {
         plugins: Vec<PluginEntry>,
         prompt_registry: Arc<PromptBuilderRegistry>,
     ) -> Self {
-        let parts = ChatViewBuilder::new(repo_root, plugins, prompt_registry).build(window, cx);
+        let builder = ChatViewBuilder::new(repo_root, plugins, prompt_registry);
+        Self::from_builder(builder, window, cx)
+    }
+
+    /// `ChatController::open_session` で開いたセッションを持つビュー（新しいウィンドウ用）
+    pub(super) fn for_session(
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+        repo_root: PathBuf,
+        plugins: Vec<PluginEntry>,
+        prompt_registry: Arc<PromptBuilderRegistry>,
+        session: ChatController,
+    ) -> Self {
+        let builder =
+            ChatViewBuilder::new(repo_root, plugins, prompt_registry).with_session(session);
+        Self::from_builder(builder, window, cx)
+    }
+
+    fn from_builder(
+        builder: ChatViewBuilder,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
+        let parts = builder.build(window, cx);
         let view = Self::from_parts(parts);
+        view.state.focus(window);
+        // テーマが "system" のとき OS の明暗の切り替えに追従する
+        cx.observe_window_appearance(window, |_this, _window, cx| apply_configured_theme(cx))
+            .detach();
 
-        if let Err(err) = view.controller.handle_command(ChatCommand::RefreshModels) {
+        if let Err(err) = view.controller().handle_command(ChatCommand::RefreshModels) {
             eprintln!("Failed to refresh model list: {}", err.message());
         }
 
@@ -57,14 +96,20 @@ impl ChatView {
             repo_root: parts.repo_root,
             plugins: parts.plugins,
             prompt_registry: parts.prompt_registry,
-            controller: parts.controller,
+            tabs: ChatTabs::new(ChatTab::new(parts.controller)),
             event_loop: parts.event_loop,
             state: parts.state,
+            _hot_reload: parts.hot_reload,
         }
     }
 
+    /// 選択中のタブのコントローラー
+    pub(super) fn controller(&self) -> &ChatControllerFacade {
+        self.tabs.active().controller()
+    }
+
     pub(super) fn chat_state_snapshot(&self) -> ChatState {
-        self.controller.state_snapshot()
+        self.controller().state_snapshot()
     }
 
     // NOTE: scratchpad/console sheet helpers were previously used by inline toolbar
@@ -78,7 +123,7 @@ impl Render for ChatView {
         window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl IntoElement {
-        gpui_component::init(cx);
+        init_components(cx);
         let event_loop = self.event_loop.clone();
         let batch = event_loop.poll();
         batch.apply(self, window, cx);
@@ -86,9 +131,31 @@ impl Render for ChatView {
         let view_entity = cx.entity();
         let menu_context = MenuContext::from_chat_view(self);
         let state = self.chat_state_snapshot();
-        let ui_snapshot = ChatUiSnapshot::from_state(&state);
-        // Attach scroll handle to the messages panel so ScrollManager can control it
-        let msgs_container = chat_messages_panel(&ui_snapshot.message_rows, Some(self.state.scroll_handle()));
+        let mut ui_snapshot = ChatUiSnapshot::from_state(&state);
+        for row in &mut ui_snapshot.message_rows {
+            if let Some(card) = row.tool_call.as_mut() {
+                card.expanded = self.state.is_tool_call_expanded(&row.id);
+            }
+        }
+        // 変わった行だけを仮想化リストに反映する（測った高さとスクロール位置は保たれる）
+        self.tabs
+            .active_mut()
+            .message_list_mut()
+            .sync(std::mem::take(&mut ui_snapshot.message_rows));
+        let message_list = self.tabs.active().message_list();
+        let msgs_container = chat_messages_panel(
+            message_list.items(),
+            message_list.list_state(),
+            !message_list.is_at_latest(),
+            |this: &mut ChatView, row, action, window, cx| {
+                this.handle_message_action(row, action, window, cx);
+            },
+            |this: &mut ChatView, _window, cx| {
+                this.tabs.active().message_list().scroll_to_latest();
+                cx.notify();
+            },
+            cx,
+        );
 
         let toolbar_model = ToolbarViewModel::from_chat_view(self);
         let toolbar = toolbar_widget(view_entity.clone(), toolbar_model, window);
@@ -101,10 +168,8 @@ impl Render for ChatView {
             .any(|p| p.metadata.as_ref().map(|m| m.models.iter().any(|mid| mid == &state.active_model)).unwrap_or(false));
 
         let model_controls = model_selector_row(selector.select_state(), has_prompt_builder, has_adapter);
-        let input_area = chat_input_panel(
-            self.state.input_state(),
-            "Enter: send, Shift+Enter: newline",
-        );
+        let input_area =
+            self.render_input_panel(&state, "Enter: send, Shift+Enter: newline, /: commands", cx);
 
         let server_items = &ui_snapshot.server_items;
         let tool_items = &ui_snapshot.tool_items;
@@ -150,11 +215,16 @@ impl Render for ChatView {
         let chat_panel = chat_window(chat_body, model_controls, input_area);
 
         let scratchpad_manager = self.state.scratchpad().clone();
+        let notes_bar = notes_bar_widget(self, cx);
+        let console_filter_bar = console_filter_widget(self, &ui_snapshot.console_logs, cx);
 
         // Inline action buttons removed; visibility and sheet toggles live in the top menu
         let scratchpad_panel = scratchpad_console(
             scratchpad_manager.editor_input(),
+            notes_bar,
             &ui_snapshot.console_logs,
+            self.state.console_filter(),
+            console_filter_bar,
             self.state.show_scratchpad(),
             self.state.show_console(),
         );
@@ -163,14 +233,24 @@ impl Render for ChatView {
         // Console/scratchpad: ~70% of workspace width, Chat main: ~30%.
         // This keeps the console comfortably wider while the layout scales with window resizing.
         // keep the console comfortably wide but do not allow it to shrink below 560px
+        let colors = current_theme().colors;
         let console_panel = div().w(relative(0.7)).min_w(px(560.0)).h_full()
             .h_full()
-            .bg(rgb(0x111111))
+            .bg(colors.background)
             .border_r_1()
-            .border_color(rgb(0x242424))
+            .border_color(colors.border)
             .child(scratchpad_panel);
 
-        let sidebar_placeholder = div().w(px(0.0)).h_full();
+        let sidebar = if self.state.show_sidebar() {
+            sidebar_widget(
+                self,
+                &ui_snapshot.sidebar_items,
+                &ui_snapshot.sidebar_filter,
+                cx,
+            )
+        } else {
+            div().w(px(0.0)).h_full()
+        };
         let main_panel = if self.state.show_chat_panel() {
             // allow the chat panel to take ~30% of workspace width and scale responsively
             // ensure chat remains usable when window is small
@@ -181,14 +261,18 @@ impl Render for ChatView {
                 .flex_1()
                 .h_full()
                 .overflow_hidden()
-                .child(div().p_4().text_sm().text_color(rgb(0x888888)).child("Chat panel hidden"))
+                .child(
+                    div()
+                        .p_4()
+                        .text_sm()
+                        .text_color(colors.text_subtle)
+                        .child("Chat panel hidden"),
+                )
         };
-        let workspace_content = chat_workspace(sidebar_placeholder, console_panel, main_panel);
-
-        // After the rendered tree has the scroll handle attached, flush pending scroll actions.
-        self.state.scroll_manager_mut().update();
+        let workspace_content = chat_workspace(sidebar, console_panel, main_panel);
 
         let menu_bar = menu_bar_widget(&menu_context, view_entity.clone());
+        let tab_bar = tab_bar_widget(self, cx);
 
         // Fill the window edge-to-edge (no outer padding / centering) so there are
         // no gaps between the window frame and panels.
@@ -197,7 +281,21 @@ impl Render for ChatView {
             .min_h(px(0.0))
             .child(div().h_full().w_full().child(workspace_content));
 
-        let mut root_layout = div().size_full().v_flex().child(menu_bar).child(workspace);
+        let root_layout = div()
+            .relative()
+            .size_full()
+            .v_flex()
+            .bg(colors.background)
+            .text_color(colors.text)
+            .track_focus(self.state.focus_handle())
+            .child(menu_bar)
+            .child(tab_bar)
+            .child(workspace);
+        let mut root_layout = on_app_actions(root_layout, cx);
+
+        if let Some(palette) = self.render_command_palette(cx) {
+            root_layout = root_layout.child(palette);
+        }
 
         if let Some(sheet_layer) = Root::render_sheet_layer(window, cx) {
             root_layout = root_layout.child(sheet_layer);
@@ -224,6 +322,8 @@ pub fn run_gui(repo_root: &Path) -> std::io::Result<()> {
 
     Application::new().run(move |cx: &mut App| {
         gpui_component::init(cx);
+        bind_keymap(cx, &Keymap::load_or_default());
+        apply_configured_theme(cx);
 
         let list_clone = list.clone();
         let repo_clone = repo_root_buf.clone();
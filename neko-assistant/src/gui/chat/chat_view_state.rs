@@ -1,10 +1,13 @@
+use std::collections::HashSet;
 use std::path::Path;
 
+use super::command_palette::CommandPaletteState;
 use super::model_selector::ModelSelector;
-use chat_core::ChatState;
-use gpui::{AppContext, Context, Entity, Subscription, Window};
+use app_config::Keymap;
+use chat_core::{ChatState, NoteStore};
+use gpui::{AppContext, Context, Entity, FocusHandle, Subscription, Window};
 use gpui_component::input::InputState;
-use ui_utils::ScrollManager;
+use neko_ui::ConsoleLogFilter;
 
 use super::{scratchpad::ScratchpadManager, ChatView};
 
@@ -13,11 +16,24 @@ pub struct ChatViewState {
     model_selector: ModelSelector,
     input_state: Entity<InputState>,
     scratchpad: ScratchpadManager,
-    scroll_manager: ScrollManager,
     show_scratchpad: bool,
     show_console: bool,
+    /// コンソールの検索欄と絞り込み条件
+    console_search: Entity<InputState>,
+    console_filter: ConsoleLogFilter,
     show_chat_panel: bool,
     show_mcp_status: bool,
+    show_sidebar: bool,
+    /// サイドバーのタイトル検索欄と、開いている会話のフォルダ・タグの入力欄
+    sidebar_search: Entity<InputState>,
+    folder_input: Entity<InputState>,
+    tag_input: Entity<InputState>,
+    /// 開いているツール呼び出しカード（メッセージ ID）
+    expanded_tool_calls: HashSet<String>,
+    /// ショートカットを受け取るためのフォーカス（入力欄などにフォーカスが無いとき）
+    focus_handle: FocusHandle,
+    keymap: Keymap,
+    command_palette: Option<CommandPaletteState>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -27,6 +43,7 @@ impl ChatViewState {
         cx: &mut Context<ChatView>,
         chat_state: &ChatState,
         repo_root: &Path,
+        note_store: NoteStore,
     ) -> Self {
         let input_state = cx.new(|cx| {
             InputState::new(window, cx)
@@ -47,20 +64,36 @@ impl ChatViewState {
                 .auto_grow(6, 16)
                 .placeholder("Notes / prompt scratchpad...")
         });
-        let scratchpad = ScratchpadManager::new(repo_root, editor_input.clone());
+        let scratchpad = ScratchpadManager::new(repo_root, note_store, editor_input.clone());
         if let Err(err) = scratchpad.load(window, cx) {
             eprintln!("Failed to load scratchpad on startup: {}", err);
         }
 
+        let console_search = cx.new(|cx| InputState::new(window, cx).placeholder("Search logs..."));
+        let sidebar_search =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Search conversations..."));
+        let folder_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Move to folder (Enter)"));
+        let tag_input = cx.new(|cx| InputState::new(window, cx).placeholder("Add tag (Enter)"));
+
         Self {
             model_selector,
             input_state,
             scratchpad,
-            scroll_manager: ScrollManager::new(),
             show_scratchpad: true,
             show_console: true,
+            console_search,
+            console_filter: ConsoleLogFilter::default(),
             show_chat_panel: true,
             show_mcp_status: false,
+            show_sidebar: false,
+            sidebar_search,
+            folder_input,
+            tag_input,
+            expanded_tool_calls: HashSet::new(),
+            focus_handle: cx.focus_handle(),
+            keymap: Keymap::load_or_default(),
+            command_palette: None,
             _subscriptions: Vec::new(),
         }
     }
@@ -77,19 +110,8 @@ impl ChatViewState {
         &self.scratchpad
     }
 
-    // scroll_manager getter removed; prefer explicit mutable access via scroll_manager_mut()
-
-    pub fn scroll_manager_mut(&mut self) -> &mut ScrollManager {
-        &mut self.scroll_manager
-    }
-
-    /// Access the internal scroll handle for read-only tracking (avoid mutable borrows).
-    pub fn scroll_handle(&self) -> &gpui::ScrollHandle {
-        self.scroll_manager.handle()
-    }
-
-    pub fn mark_scroll_to_bottom(&mut self) {
-        self.scroll_manager.mark_scroll_to_bottom();
+    pub fn scratchpad_mut(&mut self) -> &mut ScratchpadManager {
+        &mut self.scratchpad
     }
 
     pub fn show_mcp_status(&self) -> bool {
@@ -116,6 +138,18 @@ impl ChatViewState {
         self.show_console = !self.show_console;
     }
 
+    pub fn console_search(&self) -> &Entity<InputState> {
+        &self.console_search
+    }
+
+    pub fn console_filter(&self) -> &ConsoleLogFilter {
+        &self.console_filter
+    }
+
+    pub fn console_filter_mut(&mut self) -> &mut ConsoleLogFilter {
+        &mut self.console_filter
+    }
+
     pub fn show_chat_panel(&self) -> bool {
         self.show_chat_panel
     }
@@ -124,6 +158,65 @@ impl ChatViewState {
         self.show_chat_panel = !self.show_chat_panel;
     }
 
+    pub fn show_sidebar(&self) -> bool {
+        self.show_sidebar
+    }
+
+    pub fn toggle_sidebar(&mut self) {
+        self.show_sidebar = !self.show_sidebar;
+    }
+
+    pub fn sidebar_search(&self) -> &Entity<InputState> {
+        &self.sidebar_search
+    }
+
+    pub fn folder_input(&self) -> &Entity<InputState> {
+        &self.folder_input
+    }
+
+    pub fn tag_input(&self) -> &Entity<InputState> {
+        &self.tag_input
+    }
+
+    pub fn is_tool_call_expanded(&self, message_id: &str) -> bool {
+        self.expanded_tool_calls.contains(message_id)
+    }
+
+    pub fn toggle_tool_call(&mut self, message_id: &str) {
+        if !self.expanded_tool_calls.remove(message_id) {
+            self.expanded_tool_calls.insert(message_id.to_string());
+        }
+    }
+
+    pub fn focus_handle(&self) -> &FocusHandle {
+        &self.focus_handle
+    }
+
+    /// ChatView 自体にフォーカスを戻す
+    pub fn focus(&self, window: &mut Window) {
+        window.focus(&self.focus_handle);
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn command_palette(&self) -> Option<&CommandPaletteState> {
+        self.command_palette.as_ref()
+    }
+
+    pub fn command_palette_mut(&mut self) -> Option<&mut CommandPaletteState> {
+        self.command_palette.as_mut()
+    }
+
+    pub fn set_command_palette(&mut self, palette: CommandPaletteState) {
+        self.command_palette = Some(palette);
+    }
+
+    pub fn take_command_palette(&mut self) -> Option<CommandPaletteState> {
+        self.command_palette.take()
+    }
+
     pub fn set_subscriptions(&mut self, subs: Vec<Subscription>) {
         self._subscriptions = subs;
     }
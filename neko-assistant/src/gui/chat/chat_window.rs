@@ -1,24 +1,26 @@
 use gpui::*;
 use gpui_component::StyledExt;
+use neko_ui::current_theme;
 
 pub fn chat_window(
     chat_body: impl IntoElement,
     model_controls: impl IntoElement,
     input_area: impl IntoElement,
 ) -> Div {
+    let colors = current_theme().colors;
     div()
         .h_full()
         .v_flex()
         .gap_1()
         .p_1()
-        .bg(rgb(0x0d0d0d))
+        .bg(colors.background)
         .child(
             div()
                 .flex_shrink_0()
                 .h_flex()
                 .justify_between()
                 .items_center()
-                .child(div().text_lg().text_color(rgb(0xffffff)).child("Chat"))
+                .child(div().text_lg().text_color(colors.text).child("Chat"))
                 // session button removed; sessions handled via top menu
         )
         .child(
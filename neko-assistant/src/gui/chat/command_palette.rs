@@ -0,0 +1,328 @@
+//! コマンドパレット（アプリの操作と ChatCommand をあいまい検索して実行する）
+
+use super::data_mappers::is_thinking_message;
+use super::ChatView;
+use crate::gui::keymap::AppAction;
+use app_config::Keymap;
+use chat_core::{ChatCommand, ChatState};
+use chat_history::{ConversationFilter, GenerationOptions};
+use gpui::*;
+use gpui_component::input::{InputEvent, InputState};
+use neko_ui::{command_palette, current_theme, filter_palette_items, PaletteItem};
+
+/// パレットの項目が実行する内容
+#[derive(Clone, Debug)]
+pub(super) enum PaletteCommand {
+    App(AppAction),
+    Chat(ChatCommand),
+    /// モデルの切り替え（設定への保存とセレクタの同期も行う）
+    SwitchModel(String),
+}
+
+/// 開いているパレットの状態。項目は開いた時点の状態から作る
+pub(super) struct CommandPaletteState {
+    input: Entity<InputState>,
+    items: Vec<PaletteItem>,
+    commands: Vec<PaletteCommand>,
+    selected: usize,
+    _subscription: Subscription,
+}
+
+impl CommandPaletteState {
+    fn matches(&self, cx: &App) -> Vec<usize> {
+        filter_palette_items(&self.items, &self.input.read(cx).value())
+    }
+}
+
+impl ChatView {
+    pub(super) fn toggle_command_palette(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        if self.state.take_command_palette().is_some() {
+            return;
+        }
+
+        let input = cx.new(|cx| InputState::new(window, cx).placeholder("Type a command..."));
+        let subscription = cx.subscribe_in(
+            &input,
+            window,
+            |this: &mut ChatView, _input, event: &InputEvent, window, cx| match event {
+                InputEvent::PressEnter { .. } => this.run_selected_palette_item(window, cx),
+                InputEvent::Change => {
+                    if let Some(palette) = this.state.command_palette_mut() {
+                        palette.selected = 0;
+                    }
+                    cx.notify();
+                }
+                _ => {}
+            },
+        );
+        input.update(cx, |input, cx| input.focus(window, cx));
+
+        let (items, commands) = palette_entries(&self.chat_state_snapshot(), self.state.keymap())
+            .into_iter()
+            .unzip();
+        self.state.set_command_palette(CommandPaletteState {
+            input,
+            items,
+            commands,
+            selected: 0,
+            _subscription: subscription,
+        });
+    }
+
+    fn run_selected_palette_item(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        let Some(palette) = self.state.command_palette_mut() else {
+            return;
+        };
+        if let Some(&index) = palette.matches(cx).get(palette.selected) {
+            self.run_palette_item(index, window, cx);
+        }
+    }
+
+    fn run_palette_item(&mut self, index: usize, window: &mut Window, cx: &mut Context<ChatView>) {
+        let Some(palette) = self.state.take_command_palette() else {
+            return;
+        };
+        let Some(command) = palette.commands.get(index).cloned() else {
+            return;
+        };
+        match command {
+            PaletteCommand::App(action) => self.run_app_action(action, window, cx),
+            PaletteCommand::Chat(ChatCommand::SwitchConversation(id)) => {
+                self.open_conversation(&id, window, cx)
+            }
+            PaletteCommand::Chat(command) => self.run_chat_command(command, cx),
+            PaletteCommand::SwitchModel(model) => self.switch_model_to(&model, window, cx),
+        }
+        self.state.focus(window);
+        cx.notify();
+    }
+
+    /// ↑↓ で選択を動かし、Esc で閉じる（入力欄より先に受け取る）
+    fn handle_palette_key(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        let Some(palette) = self.state.command_palette_mut() else {
+            return;
+        };
+        let count = palette.matches(cx).len();
+        match event.keystroke.key.as_str() {
+            "down" if count > 0 => palette.selected = (palette.selected + 1) % count,
+            "up" if count > 0 => palette.selected = (palette.selected + count - 1) % count,
+            "escape" => {
+                self.state.take_command_palette();
+                self.state.focus(window);
+            }
+            _ => return,
+        }
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    /// 開いていればパレットを画面上部に重ねて表示する
+    pub(super) fn render_command_palette(&self, cx: &mut Context<ChatView>) -> Option<Div> {
+        let palette = self.state.command_palette()?;
+        let matches = palette.matches(cx);
+        let panel = command_palette(
+            &palette.input,
+            &palette.items,
+            &matches,
+            palette.selected,
+            |this: &mut ChatView, index, window, cx| this.run_palette_item(index, window, cx),
+            cx,
+        )
+        .capture_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+            this.handle_palette_key(event, window, cx)
+        }))
+        // パレットの中のクリックで背景の「閉じる」が動かないようにする
+        .on_mouse_down(MouseButton::Left, |_, _window, cx| cx.stop_propagation());
+
+        Some(
+            div()
+                .absolute()
+                .top_0()
+                .left_0()
+                .size_full()
+                .flex()
+                .justify_center()
+                .pt(px(80.0))
+                .bg(current_theme().colors.overlay)
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(|this, _, window, cx| {
+                        this.state.take_command_palette();
+                        this.state.focus(window);
+                        cx.notify();
+                    }),
+                )
+                .child(panel),
+        )
+    }
+}
+
+/// パレットに並べる項目（アプリの操作、会話・モデルの切り替え、現在の会話への ChatCommand）
+pub(super) fn palette_entries(
+    state: &ChatState,
+    keymap: &Keymap,
+) -> Vec<(PaletteItem, PaletteCommand)> {
+    let mut entries = Vec::new();
+    let mut push = |category: &str, label: String, command: PaletteCommand| {
+        let shortcut = match &command {
+            PaletteCommand::App(action) => action.shortcut(keymap),
+            _ => None,
+        };
+        entries.push((
+            PaletteItem {
+                label,
+                category: category.to_string(),
+                shortcut,
+            },
+            command,
+        ));
+    };
+
+    for action in AppAction::ALL {
+        push(
+            action.category(),
+            action.label().to_string(),
+            PaletteCommand::App(action),
+        );
+    }
+
+    for conversation in &state.conversations {
+        if state.conversation_id.as_deref() == Some(conversation.id.as_str()) {
+            continue;
+        }
+        push(
+            "Conversation",
+            format!("Switch to: {}", conversation.title),
+            PaletteCommand::Chat(ChatCommand::SwitchConversation(conversation.id.clone())),
+        );
+    }
+
+    for model in &state.available_models {
+        if model.id == state.active_model {
+            continue;
+        }
+        push(
+            "Model",
+            format!("Switch model: {}", model.label),
+            PaletteCommand::SwitchModel(model.id.clone()),
+        );
+    }
+
+    if let Some(id) = state.conversation_id.clone() {
+        let current = state.conversations.iter().find(|c| c.id == id);
+        let pinned = current.is_some_and(|c| c.pinned);
+        let archived = current.is_some_and(|c| c.archived);
+        push(
+            "Conversation",
+            if pinned {
+                "Unpin conversation"
+            } else {
+                "Pin conversation"
+            }
+            .to_string(),
+            PaletteCommand::Chat(ChatCommand::SetConversationPinned {
+                id: id.clone(),
+                pinned: !pinned,
+            }),
+        );
+        push(
+            "Conversation",
+            if archived {
+                "Unarchive conversation"
+            } else {
+                "Archive conversation"
+            }
+            .to_string(),
+            PaletteCommand::Chat(ChatCommand::SetConversationArchived {
+                id: id.clone(),
+                archived: !archived,
+            }),
+        );
+        push(
+            "Conversation",
+            "Restore latest backup".to_string(),
+            PaletteCommand::Chat(ChatCommand::RestoreConversationBackup {
+                id: id.clone(),
+                backup: None,
+            }),
+        );
+        push(
+            "Conversation",
+            "Delete conversation".to_string(),
+            PaletteCommand::Chat(ChatCommand::DeleteConversation(id)),
+        );
+    }
+
+    push(
+        "Conversation",
+        if state.conversation_filter.archived {
+            "Show active conversations"
+        } else {
+            "Show archived conversations"
+        }
+        .to_string(),
+        PaletteCommand::Chat(ChatCommand::SetConversationFilter(ConversationFilter {
+            archived: !state.conversation_filter.archived,
+            ..state.conversation_filter.clone()
+        })),
+    );
+    push(
+        "Conversation",
+        "Clear conversation filter".to_string(),
+        PaletteCommand::Chat(ChatCommand::SetConversationFilter(
+            ConversationFilter::default(),
+        )),
+    );
+
+    if let Some(last) = state
+        .messages
+        .last()
+        .filter(|last| !is_thinking_message(last))
+    {
+        push(
+            "Message",
+            "Retry last response".to_string(),
+            PaletteCommand::Chat(ChatCommand::RetryFromMessage(last.id.clone())),
+        );
+        push(
+            "Message",
+            "Delete last message".to_string(),
+            PaletteCommand::Chat(ChatCommand::DeleteMessage(last.id.clone())),
+        );
+    }
+
+    for (label, command) in [
+        ("Reset system prompt", ChatCommand::SetSystemPrompt(None)),
+        ("Enable all tools", ChatCommand::SetEnabledTools(None)),
+        (
+            "Use the model's default prompt builder",
+            ChatCommand::SetPromptBuilder(None),
+        ),
+        (
+            "Reset generation options",
+            ChatCommand::SetGenerationOptions(GenerationOptions::default()),
+        ),
+    ] {
+        push("Settings", label.to_string(), PaletteCommand::Chat(command));
+    }
+
+    for (label, command) in [
+        ("Refresh state", ChatCommand::RefreshState),
+        ("Refresh conversations", ChatCommand::RefreshConversations),
+        ("Refresh MCP metadata", ChatCommand::RefreshMcpMetadata),
+        ("Refresh models", ChatCommand::RefreshModels),
+    ] {
+        push("Refresh", label.to_string(), PaletteCommand::Chat(command));
+    }
+
+    entries
+}
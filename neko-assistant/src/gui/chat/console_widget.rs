@@ -0,0 +1,37 @@
+//! コンソールの絞り込みバーと JSONL への書き出し
+
+use super::ChatView;
+use chat_core::ChatCommand;
+use gpui::*;
+use neko_ui::{console_filter_bar, console_log_sources, ConsoleLogEntry};
+
+/// コンソールの見出しの下に置く絞り込みバーを構築する。
+pub(super) fn console_filter_widget(
+    view: &ChatView,
+    logs: &[ConsoleLogEntry],
+    cx: &mut Context<ChatView>,
+) -> Div {
+    console_filter_bar(
+        view.state.console_filter(),
+        &console_log_sources(logs),
+        view.state.console_search(),
+        |this: &mut ChatView, level, cx| {
+            this.state.console_filter_mut().min_level = level;
+            cx.notify();
+        },
+        |this: &mut ChatView, source, cx| {
+            this.state.console_filter_mut().source = source;
+            cx.notify();
+        },
+        |this: &mut ChatView, cx| this.export_console_log(cx),
+        cx,
+    )
+}
+
+impl ChatView {
+    /// コンソールのログ（絞り込み前のすべて）をデータフォルダの exports に書き出す
+    fn export_console_log(&mut self, cx: &mut Context<ChatView>) {
+        let path = chat_core::default_console_log_path();
+        self.run_chat_command(ChatCommand::ExportConsoleLog(path), cx);
+    }
+}
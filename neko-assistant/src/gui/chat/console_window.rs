@@ -1,30 +1,32 @@
 use gpui::*;
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::StyledExt;
-use neko_ui::ConsoleLogEntry;
+use neko_ui::{current_theme, ConsoleLogEntry};
 use ui_utils::TextStyleExt;
 
 #[allow(dead_code)]
 pub fn console_window(logs: &[ConsoleLogEntry]) -> Div {
+    let colors = current_theme().colors;
     let console_items: Vec<_> = if logs.is_empty() {
         vec![div()
             .text_xs()
-            .text_color(rgb(0x777777))
+            .text_color(colors.text_subtle)
             .child("No console activity yet")]
     } else {
         logs.iter()
             .map(|entry| {
-                div()
-                    .text_xs()
-                    .text_color(rgb(0xcccccc))
-                    .child(format!("[{}] {}", entry.role_label, entry.content))
+                div().text_xs().text_color(colors.text_muted).child(format!(
+                    "[{}] {}",
+                    entry.header(),
+                    entry.content
+                ))
             })
             .collect()
     };
 
     div().v_flex().gap_1().p_1()
-        .bg(rgb(0x161616))
-        .child(div().text_md().text_color(rgb(0xffffff)).child("Console"))
+        .bg(colors.surface)
+        .child(div().text_md().text_color(colors.text).child("Console"))
         .child(
             div()
                 .h(px(300.0))
@@ -1,27 +1,36 @@
-use chat_core::{ChatState, McpServerStatus};
-use chat_history::{Message, MessageRole};
+use chat_core::{ChatState, McpServerStatus, TurnTrace};
+use chat_history::{Message, MessageRole, ToolCallRecord};
 use neko_ui::{
     ChatMessageRow, ConsoleLogEntry, McpServerItem, McpServerStatusBadge, McpToolItem, MessageType,
+    ToolCallCard,
 };
 
 pub struct ChatStateMapper;
 
 impl ChatStateMapper {
     pub fn message_rows(state: &ChatState) -> Vec<ChatMessageRow> {
+        // 生成中は応答の置き場所（考え中の行）を消さないよう、Retry / Delete を出さない
+        let editable = !state.messages.last().is_some_and(is_thinking_message);
         state
             .messages
             .iter()
             .map(|msg| ChatMessageRow {
+                id: msg.id.clone(),
                 content: msg.content.clone(),
                 message_type: match msg.role {
                     MessageRole::User => MessageType::User,
                     MessageRole::Assistant => MessageType::Assistant,
                     MessageRole::System => MessageType::System,
                     MessageRole::Error => MessageType::Error,
+                    MessageRole::Tool => MessageType::Tool,
                 },
                 align_end: matches!(msg.role, MessageRole::User),
                 is_thinking: is_thinking_message(msg),
                 source_label: message_source_label(msg),
+                pinned: msg.is_pinned(),
+                inspectable: TurnTrace::from_message(msg).is_some(),
+                tool_call: msg.tool_call_record().map(tool_call_card),
+                editable,
             })
             .collect()
     }
@@ -63,18 +72,39 @@ impl ChatStateMapper {
             .console_logs
             .iter()
             .map(|record| ConsoleLogEntry {
-                role_label: match record.kind {
-                    chat_core::ConsoleLogKind::Input => "Input".to_string(),
-                    chat_core::ConsoleLogKind::Output => "Output".to_string(),
-                    chat_core::ConsoleLogKind::Error => "Error".to_string(),
-                },
+                role_label: record.kind.label().to_string(),
                 content: record.content.clone(),
+                time_label: record
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string(),
+                level: record.level.label().to_string(),
+                source: record.source.to_string(),
+                request_id: record.request_id.clone(),
+                duration_ms: record.duration_ms,
             })
             .collect()
     }
 }
 
-fn is_thinking_message(message: &Message) -> bool {
+/// カードは閉じた状態で作り、開閉は ChatView 側の状態で反映する
+fn tool_call_card(record: ToolCallRecord) -> ToolCallCard {
+    let pretty = |value: &serde_json::Value| {
+        serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+    };
+    ToolCallCard {
+        arguments: pretty(&record.arguments),
+        result: record.result.as_ref().map(pretty),
+        error: record.error,
+        duration_ms: record.duration_ms,
+        tool: record.tool,
+        server: record.server,
+        expanded: false,
+    }
+}
+
+pub(super) fn is_thinking_message(message: &Message) -> bool {
     message
         .metadata
         .as_ref()
@@ -1,12 +1,14 @@
 use super::ChatView;
-use chat_core::ChatEvent;
+use chat_core::{ChatController, ChatEvent, ControllerSubscription};
 use gpui::{Context, Window};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
 /// ChatController からのイベントをポーリングし、テストしやすいバッチ表現へ変換するループ。
+/// ウィンドウ内のタブはすべて同じループに購読する。
 #[derive(Clone)]
 pub struct ChatEventLoop {
+    tx: mpsc::UnboundedSender<ChatEvent>,
     rx: Arc<Mutex<mpsc::UnboundedReceiver<ChatEvent>>>,
 }
 
@@ -19,12 +21,27 @@ pub struct ChatEventBatch {
     pub models_updated: bool,
     pub mcp_metadata_updated: bool,
     pub console_log_updated: bool,
+    pub plugins_reloaded: bool,
     pub errors: Vec<String>,
 }
 
 impl ChatEventLoop {
-    pub fn new(rx: Arc<Mutex<mpsc::UnboundedReceiver<ChatEvent>>>) -> Self {
-        Self { rx }
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: Arc::new(Mutex::new(rx)),
+        }
+    }
+
+    /// コントローラー（タブのセッション）のイベントをこのループへ流す。
+    pub fn subscribe(&self, controller: &ChatController) -> ControllerSubscription {
+        let tx = self.tx.clone();
+        controller.subscribe(move |event| {
+            if tx.send(event).is_err() {
+                eprintln!("Failed to deliver chat event to UI");
+            }
+        })
     }
 
     /// 処理待ちイベントをまとめて取り出し、分類済みのバッチとして返す。
@@ -39,6 +56,12 @@ impl ChatEventLoop {
     }
 }
 
+impl Default for ChatEventLoop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ChatEventBatch {
     fn record(&mut self, event: ChatEvent) {
         match event {
@@ -48,6 +71,7 @@ impl ChatEventBatch {
             ChatEvent::ModelsUpdated => self.models_updated = true,
             ChatEvent::McpMetadataUpdated => self.mcp_metadata_updated = true,
             ChatEvent::ConsoleLogUpdated => self.console_log_updated = true,
+            ChatEvent::PluginsReloaded => self.plugins_reloaded = true,
             ChatEvent::Error(message) => self.errors.push(message),
         }
     }
@@ -59,6 +83,7 @@ impl ChatEventBatch {
             && !self.models_updated
             && !self.mcp_metadata_updated
             && !self.console_log_updated
+            && !self.plugins_reloaded
             && self.errors.is_empty()
     }
 
@@ -70,10 +95,6 @@ impl ChatEventBatch {
 
         let plan = self.into_dispatch_plan();
 
-        if plan.mark_scroll_to_bottom {
-            view.state.mark_scroll_to_bottom();
-        }
-
         if plan.sync_active_model {
             let state = view.chat_state_snapshot();
             view.state
@@ -89,6 +110,14 @@ impl ChatEventBatch {
                 .sync_selection(&state, window, cx);
         }
 
+        if plan.reload_plugins {
+            let controller = view.controller().controller();
+            view.plugins = controller.plugins();
+            if let Some(registry) = controller.prompt_registry() {
+                view.prompt_registry = registry;
+            }
+        }
+
         for message in plan.errors {
             eprintln!("Chat controller error: {}", message);
         }
@@ -105,7 +134,6 @@ impl ChatEventBatch {
         };
 
         if self.state_changed {
-            plan.mark_scroll_to_bottom = true;
             plan.request_notify = true;
         }
 
@@ -127,6 +155,11 @@ impl ChatEventBatch {
             plan.request_notify = true;
         }
 
+        if self.plugins_reloaded {
+            plan.reload_plugins = true;
+            plan.request_notify = true;
+        }
+
         plan
     }
 
@@ -138,9 +171,9 @@ impl ChatEventBatch {
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct DispatchPlan {
-    pub mark_scroll_to_bottom: bool,
     pub sync_active_model: bool,
     pub sync_model_list: bool,
+    pub reload_plugins: bool,
     pub request_notify: bool,
     pub errors: Vec<String>,
 }
@@ -151,16 +184,17 @@ mod tests {
 
     #[test]
     fn poll_collects_all_event_flags() {
-        let (tx, rx) = mpsc::unbounded_channel();
+        let loop_ = ChatEventLoop::new();
+        let tx = loop_.tx.clone();
         tx.send(ChatEvent::StateChanged).unwrap();
         tx.send(ChatEvent::ConversationsUpdated).unwrap();
         tx.send(ChatEvent::ModelChanged).unwrap();
         tx.send(ChatEvent::ModelsUpdated).unwrap();
         tx.send(ChatEvent::McpMetadataUpdated).unwrap();
         tx.send(ChatEvent::ConsoleLogUpdated).unwrap();
+        tx.send(ChatEvent::PluginsReloaded).unwrap();
         tx.send(ChatEvent::Error("boom".into())).unwrap();
 
-        let loop_ = ChatEventLoop::new(Arc::new(Mutex::new(rx)));
         let batch = loop_.poll();
 
         assert!(batch.state_changed);
@@ -169,6 +203,7 @@ mod tests {
         assert!(batch.models_updated);
         assert!(batch.mcp_metadata_updated);
         assert!(batch.console_log_updated);
+        assert!(batch.plugins_reloaded);
         assert_eq!(batch.errors, vec!["boom".to_string()]);
         assert!(!batch.is_empty());
     }
@@ -182,14 +217,15 @@ mod tests {
             models_updated: true,
             mcp_metadata_updated: true,
             console_log_updated: false,
+            plugins_reloaded: true,
             errors: vec!["first".into(), "second".into()],
         };
 
         let plan = batch.dispatch_plan_for_test();
 
-        assert!(plan.mark_scroll_to_bottom);
         assert!(plan.sync_active_model);
         assert!(plan.sync_model_list);
+        assert!(plan.reload_plugins);
         assert!(plan.request_notify);
         assert_eq!(plan.errors, vec!["first", "second"]);
     }
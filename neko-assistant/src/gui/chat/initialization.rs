@@ -2,9 +2,11 @@ use super::chat_view::ChatView;
 use super::chat_view_state::ChatViewState;
 use super::controller_facade::ChatControllerFacade;
 use super::event_loop::ChatEventLoop;
+use super::plugin_approval::request_plugin_approvals;
 use chat_core::{
-    load_mcp_config, ChatCommand, ChatController, ChatControllerConfig, ControllerSubscription,
-    ConversationService, McpManager, McpServerConfig, PluginEntry, PromptBuilderRegistry, ConsoleLogKind,
+    load_mcp_config, AdapterRegistry, ChatCommand, ChatController, ChatControllerConfig,
+    ConsoleLogKind, ConversationService, HotReloadWatcher, McpManager, McpServerConfig, NoteStore,
+    PluginEntry, PromptBuilderRegistry, ToolRegistry,
 };
 use chat_history::{Conversation, ConversationManager, Message, MessageRole};
 use gpui::{Context, Window};
@@ -12,9 +14,8 @@ use gpui_component::input::InputEvent;
 use gpui_component::select::SelectEvent;
 use neko_ui::ModelPreset;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
 
 type McpConfigLoader = dyn Fn() -> Result<Vec<McpServerConfig>, String> + Send + Sync;
 
@@ -23,6 +24,7 @@ pub struct ChatViewBuilder {
     plugins: Vec<PluginEntry>,
     prompt_registry: Arc<PromptBuilderRegistry>,
     config_loader: Arc<McpConfigLoader>,
+    session: Option<Arc<ChatController>>,
 }
 
 pub struct ChatViewParts {
@@ -30,6 +32,7 @@ pub struct ChatViewParts {
     pub plugins: Vec<PluginEntry>,
     pub prompt_registry: Arc<PromptBuilderRegistry>,
     pub controller: ChatControllerFacade,
+    pub hot_reload: Option<HotReloadWatcher>,
     pub event_loop: ChatEventLoop,
     pub state: ChatViewState,
 }
@@ -45,27 +48,89 @@ impl ChatViewBuilder {
             plugins,
             prompt_registry,
             config_loader: Arc::new(load_mcp_config),
+            session: None,
         }
     }
 
+    /// 既存のセッション（`ChatController::open_session` で開いたもの）で新しいウィンドウを作る。
+    /// 設定・MCP・ホットリロードの初期化は最初のウィンドウだけが行う。
+    pub fn with_session(mut self, controller: ChatController) -> Self {
+        self.session = Some(Arc::new(controller));
+        self
+    }
+
     pub fn build(self, window: &mut Window, cx: &mut Context<ChatView>) -> ChatViewParts {
         let ChatViewBuilder {
             repo_root,
             plugins,
             prompt_registry,
             config_loader,
+            session,
         } = self;
 
+        let (controller, startup) = match session {
+            Some(controller) => (controller, None),
+            None => {
+                let (controller, use_langchain, mcp_configs) =
+                    Self::initialize_main_controller(&config_loader, &plugins, &prompt_registry);
+                (controller, Some((use_langchain, mcp_configs)))
+            }
+        };
+
+        let event_loop = ChatEventLoop::new();
+        let subscription = event_loop.subscribe(&controller);
+
+        let note_store = controller
+            .note_store()
+            .unwrap_or_else(|| NoteStore::new(NoteStore::default_dir()));
+        let mut state = ChatViewState::new(
+            window,
+            cx,
+            &controller.state_snapshot(),
+            &repo_root,
+            note_store,
+        );
+        Self::bind_input_listeners(cx, window, &mut state);
+
+        let mut hot_reload = None;
+        if let Some((use_langchain, mcp_configs)) = startup {
+            match controller.watch_for_changes(&repo_root) {
+                Ok(watcher) => hot_reload = Some(watcher),
+                Err(err) => eprintln!("{}", err),
+            }
+            Self::run_initial_commands(&controller, use_langchain, &mcp_configs);
+            request_plugin_approvals(&plugins, window, cx);
+        }
+
+        ChatViewParts {
+            repo_root,
+            plugins,
+            prompt_registry,
+            controller: ChatControllerFacade::new(controller, subscription),
+            hot_reload,
+            event_loop,
+            state,
+        }
+    }
+
+    /// 設定・会話ストア・MCP を読み込んで最初のセッションのコントローラーを作る。
+    fn initialize_main_controller(
+        config_loader: &Arc<McpConfigLoader>,
+        plugins: &[PluginEntry],
+        prompt_registry: &Arc<PromptBuilderRegistry>,
+    ) -> (Arc<ChatController>, bool, Vec<McpServerConfig>) {
         let config = app_config::AppConfig::load_or_default();
         let use_langchain = config.use_langchain;
         let active_model = config.default_model.clone();
 
         let (conversation_service, welcome_message) =
-            Self::initialize_conversation_context(use_langchain);
+            Self::initialize_conversation_context(use_langchain, &config.session_dir);
         let (mcp_manager, mcp_configs) =
-            Self::initialize_mcp_context_inner(&config_loader, use_langchain);
+            Self::initialize_mcp_context_inner(config_loader, use_langchain);
+        let adapter_registry = Arc::new(AdapterRegistry::from_plugins(plugins));
+        let tool_registry = Arc::new(ToolRegistry::from_plugins(plugins));
 
-        let (controller, event_loop, subscription) = Self::initialize_controller(
+        let controller = Self::initialize_controller(
             &config,
             active_model,
             use_langchain,
@@ -73,32 +138,35 @@ impl ChatViewBuilder {
             mcp_configs.clone(),
             conversation_service,
             prompt_registry.clone(),
+            adapter_registry,
+            tool_registry,
             welcome_message,
         );
 
-        let mut state = ChatViewState::new(window, cx, &controller.state_snapshot(), &repo_root);
-        Self::bind_input_listeners(cx, window, &mut state, controller.clone());
-
-        let facade = ChatControllerFacade::new(controller.clone(), subscription);
-        Self::run_initial_commands(&facade, use_langchain, &mcp_configs);
-
-        ChatViewParts {
-            repo_root,
-            plugins,
-            prompt_registry,
-            controller: facade,
-            event_loop,
-            state,
-        }
+        (controller, use_langchain, mcp_configs)
     }
 
-    fn initialize_conversation_context(use_langchain: bool) -> (ConversationService, String) {
+    fn initialize_conversation_context(
+        use_langchain: bool,
+        legacy_session_dir: &Path,
+    ) -> (ConversationService, String) {
         let storage_dir = ConversationManager::default_storage_dir().unwrap_or_else(|err| {
             eprintln!("Failed to determine conversation storage dir: {}", err);
             env::temp_dir().join("neko-assistant").join("conversations")
         });
+        let (storage_dir, encryption_key) = match app_config::resolve_default_encryption_key() {
+            Ok(key) => (storage_dir, key),
+            Err(err) => {
+                // 鍵が無いまま暗号化済みの履歴へ平文を書き込まないよう、一時ディレクトリを使う
+                eprintln!("Failed to unlock encrypted conversation storage: {:#}", err);
+                let locked_dir = env::temp_dir()
+                    .join("neko-assistant")
+                    .join("locked_conversations");
+                (locked_dir, None)
+            }
+        };
         let conversation_manager = match ConversationManager::new(&storage_dir) {
-            Ok(manager) => Arc::new(Mutex::new(manager)),
+            Ok(manager) => Arc::new(Mutex::new(manager.with_encryption(encryption_key))),
             Err(err) => {
                 eprintln!(
                     "Failed to initialize ConversationManager at {:?}: {}",
@@ -117,6 +185,7 @@ impl ChatViewBuilder {
                 Arc::new(Mutex::new(fallback_manager))
             }
         };
+        Self::import_legacy_sessions(&conversation_manager, legacy_session_dir);
 
         let mut conversation = Conversation::new("New Chat");
         let welcome_message = if use_langchain {
@@ -132,6 +201,27 @@ impl ChatViewBuilder {
         (conversation_service, welcome_message)
     }
 
+    /// 旧 ChatSession 形式のファイルを会話ストアへ取り込む（既存 ID はスキップ）。
+    fn import_legacy_sessions(manager: &Arc<Mutex<ConversationManager>>, session_dir: &Path) {
+        let Ok(manager) = manager.lock() else {
+            return;
+        };
+        match manager.import_legacy_sessions(session_dir) {
+            Ok(report) => {
+                if report.imported > 0 {
+                    eprintln!(
+                        "Imported {} legacy session(s) from {:?}",
+                        report.imported, session_dir
+                    );
+                }
+                for (path, err) in report.failed {
+                    eprintln!("Failed to import legacy session {:?}: {}", path, err);
+                }
+            }
+            Err(err) => eprintln!("Failed to import legacy sessions: {}", err),
+        }
+    }
+
     #[cfg(test)]
     fn initialize_mcp_context(
         &self,
@@ -176,9 +266,11 @@ impl ChatViewBuilder {
         mcp_configs: Vec<McpServerConfig>,
         conversation_service: ConversationService,
         prompt_registry: Arc<PromptBuilderRegistry>,
+        adapter_registry: Arc<AdapterRegistry>,
+        tool_registry: Arc<ToolRegistry>,
         welcome_message: String,
-    ) -> (Arc<ChatController>, ChatEventLoop, ControllerSubscription) {
-        let controller = Arc::new(ChatController::new(ChatControllerConfig {
+    ) -> Arc<ChatController> {
+        Arc::new(ChatController::new(ChatControllerConfig {
             conversation_service,
             active_model,
             use_langchain,
@@ -186,35 +278,31 @@ impl ChatViewBuilder {
             mcp_manager,
             mcp_configs: mcp_configs.clone(),
             prompt_registry: Some(prompt_registry),
+            adapter_registry: Some(adapter_registry),
+            tool_registry: Some(tool_registry),
+            note_store: Some(NoteStore::new(NoteStore::default_dir())),
+            console_log_capacity: config.console_log_capacity,
             welcome_message,
-        }));
-
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
-        let event_loop = ChatEventLoop::new(Arc::new(Mutex::new(event_rx)));
-        let subscription = controller.subscribe(move |event| {
-            if event_tx.send(event).is_err() {
-                eprintln!("Failed to deliver chat event to UI");
-            }
-        });
-
-        (controller, event_loop, subscription)
+        }))
     }
 
     fn bind_input_listeners(
         cx: &mut Context<ChatView>,
         window: &mut Window,
         state: &mut ChatViewState,
-        controller: Arc<ChatController>,
     ) {
         let input_state = state.input_state().clone();
         // model selector no longer exposes a free-text input; selection-only.
         let model_select_state = state.model_selector().select_state().clone();
 
-        let handler_sub = controller.clone();
         let mut subs = vec![cx.subscribe_in(
             &input_state,
             window,
-            move |_this, field, ev: &InputEvent, window, cx| {
+            move |this, field, ev: &InputEvent, window, cx| {
+                // スラッシュコマンドの候補を入力に合わせて更新する
+                if let InputEvent::Change = ev {
+                    cx.notify();
+                }
                 if let InputEvent::PressEnter { secondary } = ev {
                     if !secondary {
                         let val = field.read(cx).value();
@@ -224,11 +312,14 @@ impl ChatViewBuilder {
                         }
 
                         let user_input = trimmed.to_string();
-                        if let Err(err) = handler_sub
+                        if let Err(err) = this
+                            .controller()
                             .handle_command(ChatCommand::SendUserMessage(user_input.clone()))
                         {
                             eprintln!("Failed to send message: {}", err.message());
                         }
+                        // 送信したら上に遡っていても最新の行に戻る
+                        this.tabs.active().message_list().scroll_to_latest();
 
                         field.update(cx, |view, cx| view.set_value("", window, cx));
                     }
@@ -238,6 +329,92 @@ impl ChatViewBuilder {
 
         // No text-input subscription for model selector — use the selection widget only.
 
+        // ノートは編集のたびに保存する（プロンプトに含めるときはファイルから読む）
+        let scratchpad_input = state.scratchpad().editor_input().clone();
+        subs.push(cx.subscribe_in(
+            &scratchpad_input,
+            window,
+            move |this, _field, ev: &InputEvent, _window, cx| {
+                if let InputEvent::Change = ev {
+                    if let Err(err) = this.state.scratchpad().save(cx) {
+                        eprintln!("{}", err);
+                    }
+                }
+            },
+        ));
+
+        // コンソールは検索欄の入力に合わせて絞り込む
+        let console_search = state.console_search().clone();
+        subs.push(cx.subscribe_in(
+            &console_search,
+            window,
+            move |this, field, ev: &InputEvent, _window, cx| {
+                if let InputEvent::Change = ev {
+                    this.state.console_filter_mut().query = field.read(cx).value().to_string();
+                    cx.notify();
+                }
+            },
+        ));
+
+        // 会話一覧はタイトルの検索欄に合わせて絞り込む
+        let sidebar_search = state.sidebar_search().clone();
+        subs.push(cx.subscribe_in(
+            &sidebar_search,
+            window,
+            move |this, field, ev: &InputEvent, _window, cx| {
+                if let InputEvent::Change = ev {
+                    let query = field.read(cx).value().trim().to_string();
+                    this.update_conversation_filter(
+                        |f| f.query = (!query.is_empty()).then_some(query),
+                        cx,
+                    );
+                }
+            },
+        ));
+
+        // 開いている会話のフォルダを移す（空なら外す）
+        let folder_input = state.folder_input().clone();
+        subs.push(cx.subscribe_in(
+            &folder_input,
+            window,
+            move |this, field, ev: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = ev {
+                    let Some(id) = this.chat_state_snapshot().conversation_id else {
+                        return;
+                    };
+                    let folder = field.read(cx).value().trim().to_string();
+                    this.dispatch_sidebar_command(
+                        ChatCommand::SetConversationFolder {
+                            id,
+                            folder: (!folder.is_empty()).then_some(folder),
+                        },
+                        cx,
+                    );
+                    field.update(cx, |view, cx| view.set_value("", window, cx));
+                }
+            },
+        ));
+
+        // 開いている会話にタグを付ける
+        let tag_input = state.tag_input().clone();
+        subs.push(cx.subscribe_in(
+            &tag_input,
+            window,
+            move |this, field, ev: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = ev {
+                    let Some(id) = this.chat_state_snapshot().conversation_id else {
+                        return;
+                    };
+                    let tag = field.read(cx).value().trim().to_string();
+                    if tag.is_empty() {
+                        return;
+                    }
+                    this.dispatch_sidebar_command(ChatCommand::AddConversationTag { id, tag }, cx);
+                    field.update(cx, |view, cx| view.set_value("", window, cx));
+                }
+            },
+        ));
+
         let select_state_for_events = model_select_state.clone();
         subs.push(cx.subscribe_in(
             &select_state_for_events,
@@ -259,28 +436,24 @@ impl ChatViewBuilder {
                     if !plugin_match {
                         // No adapter plugin explicitly lists this model — warn and do not switch.
                         // (This avoids selecting a UI label that doesn't map to an installed adapter.)
-                        this.controller
-                            .controller()
-                            .append_console_log(
-                                ConsoleLogKind::Error,
-                                format!(
-                                    "No installed adapter plugin matches requested model: {}",
-                                    normalized
-                                ),
-                            );
+                        this.controller().controller().append_console_log(
+                            ConsoleLogKind::Error,
+                            format!(
+                                "No installed adapter plugin matches requested model: {}",
+                                normalized
+                            ),
+                        );
                         return;
                     }
 
                     // Log that we found a matching installed adapter and are switching
-                    this.controller
-                        .controller()
-                        .append_console_log(
-                            ConsoleLogKind::Output,
-                            format!("Plugin match found for model '{}', switching..", normalized),
-                        );
+                    this.controller().controller().append_console_log(
+                        ConsoleLogKind::Output,
+                        format!("Plugin match found for model '{}', switching..", normalized),
+                    );
 
                     if let Err(err) = this.state.model_selector().switch_model(
-                        &this.controller,
+                        this.controller(),
                         &normalized,
                         window,
                         cx,
@@ -295,7 +468,7 @@ impl ChatViewBuilder {
     }
 
     fn run_initial_commands(
-        controller: &ChatControllerFacade,
+        controller: &ChatController,
         use_langchain: bool,
         mcp_configs: &[McpServerConfig],
     ) {
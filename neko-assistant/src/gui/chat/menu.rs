@@ -1,6 +1,11 @@
 use gpui::*;
 use gpui_component::StyledExt;
+use neko_ui::current_theme;
 
 pub fn main_menu() -> Div {
-    div().h_flex().gap_2().p_2().bg(rgb(0x121212))
+    div()
+        .h_flex()
+        .gap_2()
+        .p_2()
+        .bg(current_theme().colors.surface)
 }
@@ -1,7 +1,8 @@
+use super::backup_picker::open_backup_picker_window;
 use super::menu_context::MenuContext;
 use super::ChatView;
 use crate::gui::{mcp_manager, window_options_with_title, PluginListView};
-use chat_core::ChatCommand;
+use chat_core::{ChatCommand, ChatController};
 use gpui::*;
 use gpui_component::button::Button;
 use gpui_component::menu::{DropdownMenu, PopupMenu, PopupMenuItem};
@@ -24,11 +25,13 @@ pub fn app_menu_button(
                   window: &mut gpui::Window,
                   _popup_cx: &mut gpui::Context<PopupMenu>| {
                 let controller_for_manager = controller.clone();
+                let controller_for_restore = controller.clone();
                 let repo_for_plugins = repo_root.clone();
                 let plugins_for_plugins = plugins.clone();
                 let view_for_scratchpad = view_entity.clone();
                 let view_for_console = view_entity.clone();
                 let view_for_toggle = view_entity.clone();
+                let view_for_sidebar = view_entity.clone();
                 let toggle_label = toggle_label.clone();
 
                 menu = menu.item(
@@ -39,18 +42,7 @@ pub fn app_menu_button(
 
                 menu = menu.item(PopupMenuItem::new("MCP Manager").on_click(
                     move |_, _window, app_cx| {
-                        let controller_for_refresh = controller_for_manager.clone();
-                        let refresh_callback: Arc<dyn Fn() + Send + Sync> = Arc::new(move || {
-                            if let Err(err) = controller_for_refresh
-                                .handle_command(ChatCommand::RefreshMcpMetadata)
-                            {
-                                eprintln!(
-                                    "Failed to refresh MCP metadata from manager: {}",
-                                    err.message()
-                                );
-                            }
-                        });
-                        mcp_manager::open_mcp_manager_window(app_cx, Some(refresh_callback));
+                        open_mcp_manager(controller_for_manager.clone(), app_cx);
                     },
                 ));
 
@@ -73,6 +65,12 @@ pub fn app_menu_button(
                     },
                 ));
 
+                menu = menu.item(PopupMenuItem::new("Restore previous version…").on_click(
+                    move |_, _window, app_cx| {
+                        open_backup_picker_window(controller_for_restore.clone(), app_cx);
+                    },
+                ));
+
                 menu = menu.separator();
 
                 menu = menu.item(
@@ -99,6 +97,19 @@ pub fn app_menu_button(
                     },
                 )));
 
+                menu = menu.item(PopupMenuItem::new("Conversations").on_click(
+                    window.listener_for(
+                        &view_for_sidebar,
+                        |this: &mut ChatView,
+                         _event: &ClickEvent,
+                         _window,
+                         cx: &mut gpui::Context<ChatView>| {
+                            this.state.toggle_sidebar();
+                            cx.notify();
+                        },
+                    ),
+                ));
+
                 // allow toggling the main chat panel visibility
                 menu = menu.item(PopupMenuItem::new("Chat panel").on_click(window.listener_for(
                     &view_for_toggle,
@@ -134,21 +145,23 @@ pub fn manage_mcp_button(context: &MenuContext, id: &str, label: &str) -> Button
     Button::new(SharedString::from(id.to_string()))
         .label(SharedString::from(label.to_string()))
         .on_click(move |_, _window, app_cx| {
-            let controller_for_refresh = controller.clone();
-            let refresh_callback: Arc<dyn Fn() + Send + Sync> = Arc::new(move || {
-                if let Err(err) =
-                    controller_for_refresh.handle_command(ChatCommand::RefreshMcpMetadata)
-                {
-                    eprintln!(
-                        "Failed to refresh MCP metadata from manager: {}",
-                        err.message()
-                    );
-                }
-            });
-            mcp_manager::open_mcp_manager_window(app_cx, Some(refresh_callback));
+            open_mcp_manager(controller.clone(), app_cx);
         })
 }
 
+/// MCP マネージャーを開く（設定が変わったら MCP のメタデータを取り直す）
+pub fn open_mcp_manager(controller: Arc<ChatController>, cx: &mut App) {
+    let refresh_callback: Arc<dyn Fn() + Send + Sync> = Arc::new(move || {
+        if let Err(err) = controller.handle_command(ChatCommand::RefreshMcpMetadata) {
+            eprintln!(
+                "Failed to refresh MCP metadata from manager: {}",
+                err.message()
+            );
+        }
+    });
+    mcp_manager::open_mcp_manager_window(cx, Some(refresh_callback));
+}
+
 pub fn plugin_button(context: &MenuContext) -> Button {
     let repo_clone = context.repo_root();
     let plugins_clone = context.plugins();
@@ -15,7 +15,7 @@ pub(super) struct MenuContext {
 impl MenuContext {
     pub fn from_chat_view(view: &ChatView) -> Self {
         Self {
-            controller: view.controller.controller(),
+            controller: view.controller().controller(),
             repo_root: Arc::new(view.repo_root.clone()),
             plugins: Arc::new(view.plugins.clone()),
             show_mcp_status: view.state.show_mcp_status(),
@@ -67,7 +67,7 @@ mod tests {
     use super::MenuContext;
     use chat_core::{
         ChatController, ChatControllerConfig, ConversationService, PluginEntry,
-        PromptBuilderRegistry,
+        PromptBuilderRegistry, DEFAULT_CONSOLE_LOG_CAPACITY,
     };
     use chat_history::{Conversation, ConversationManager};
     use std::sync::{Arc, Mutex};
@@ -88,6 +88,10 @@ mod tests {
             mcp_manager: None,
             mcp_configs: Vec::new(),
             prompt_registry: Some(Arc::new(PromptBuilderRegistry::from_plugins(&[]))),
+            adapter_registry: None,
+            tool_registry: None,
+            note_store: None,
+            console_log_capacity: DEFAULT_CONSOLE_LOG_CAPACITY,
             welcome_message: "hi".into(),
         }))
     }
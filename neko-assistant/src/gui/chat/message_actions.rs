@@ -0,0 +1,46 @@
+//! メッセージのホバーアクション（Retry / Pin / Delete / Inspect / → Note）とツール呼び出しカードの操作をコマンドに変換する
+
+use super::message_inspect::open_message_inspect_window;
+use super::ChatView;
+use chat_core::ChatCommand;
+use gpui::*;
+use neko_ui::{ChatMessageRow, MessageAction};
+
+impl ChatView {
+    pub(super) fn handle_message_action(
+        &mut self,
+        row: &ChatMessageRow,
+        action: MessageAction,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        let command = match action {
+            MessageAction::Delete => ChatCommand::DeleteMessage(row.id.clone()),
+            MessageAction::Retry => ChatCommand::RetryFromMessage(row.id.clone()),
+            MessageAction::TogglePin => ChatCommand::SetMessagePinned {
+                id: row.id.clone(),
+                pinned: !row.pinned,
+            },
+            MessageAction::Inspect => {
+                open_message_inspect_window(self.controller().controller(), row.id.clone(), cx);
+                return;
+            }
+            MessageAction::ToggleToolCall => {
+                self.state.toggle_tool_call(&row.id);
+                cx.notify();
+                return;
+            }
+            MessageAction::RerunTool => ChatCommand::RerunToolCall(row.id.clone()),
+            MessageAction::AppendToNote => {
+                if let Err(err) = self.state.scratchpad().append(&row.content, window, cx) {
+                    eprintln!("{}", err);
+                }
+                return;
+            }
+        };
+        if let Err(err) = self.controller().handle_command(command) {
+            eprintln!("Message action failed: {}", err.message());
+        }
+        cx.notify();
+    }
+}
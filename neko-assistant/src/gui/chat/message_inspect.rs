@@ -0,0 +1,93 @@
+//! 1 ターン分の記録（モデルに渡したプロンプト・生の出力・解析結果）を表示するウィンドウ
+
+use crate::gui::window_options_with_title;
+use chat_core::{ChatController, TurnTrace};
+use gpui::*;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{Root, StyledExt};
+use neko_ui::current_theme;
+use std::sync::Arc;
+use ui_utils::TextStyleExt;
+
+pub struct MessageInspectView {
+    controller: Arc<ChatController>,
+    message_id: String,
+}
+
+impl MessageInspectView {
+    /// 最新の状態からメッセージの記録を取り出す（削除済みなら None）
+    fn trace(&self) -> Option<TurnTrace> {
+        self.controller
+            .state_snapshot()
+            .messages
+            .iter()
+            .find(|message| message.id == self.message_id)
+            .and_then(TurnTrace::from_message)
+    }
+}
+
+pub fn open_message_inspect_window(
+    controller: Arc<ChatController>,
+    message_id: String,
+    cx: &mut App,
+) {
+    let _ = cx.open_window(
+        window_options_with_title("Inspect message"),
+        move |window, cx| {
+            let view = cx.new(|_| MessageInspectView {
+                controller,
+                message_id,
+            });
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    );
+}
+
+fn section(title: &'static str, body: String) -> Div {
+    let colors = current_theme().colors;
+    div()
+        .v_flex()
+        .gap_1()
+        .flex_1()
+        .min_h(px(0.0))
+        .p_2()
+        .rounded_md()
+        .bg(colors.surface)
+        .child(div().text_md().text_color(colors.text).child(title))
+        .child(
+            div()
+                .flex_1()
+                .text_xs()
+                .text_color(colors.text_muted)
+                .overflow_y_scrollbar()
+                .child(body),
+        )
+}
+
+impl Render for MessageInspectView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        crate::gui::theme::init_components(cx);
+
+        let root = div().v_flex().gap_2().p_2().size_full();
+        let Some(trace) = self.trace() else {
+            return root.child(
+                div()
+                    .text_color(current_theme().colors.text_subtle)
+                    .child("No trace was recorded for this message."),
+            );
+        };
+
+        let missing = || "(not recorded)".to_string();
+        let parsed = trace
+            .parsed
+            .as_ref()
+            .map(|parsed| serde_json::to_string_pretty(parsed).unwrap_or_default())
+            .unwrap_or_else(missing);
+        root.child(section("Prompt", trace.prompt.unwrap_or_else(missing)))
+            .child(section(
+                "Raw model output",
+                trace.raw_output.unwrap_or_else(missing),
+            ))
+            .child(section("Parsed output", parsed))
+    }
+}
@@ -1,6 +1,12 @@
+mod app_actions;
+mod backup_picker;
+mod builder_compare;
+mod chat_tabs;
 mod chat_view;
 mod chat_view_state;
 mod chat_window;
+mod command_palette;
+mod console_widget;
 mod console_window;
 mod controller_facade;
 mod data_mappers;
@@ -10,10 +16,17 @@ mod menu;
 mod menu_actions;
 mod menu_bar_widget;
 mod menu_context;
+mod message_actions;
+mod message_inspect;
 mod model_selector;
+mod notes_widget;
+mod plugin_approval;
 mod scratchpad;
 mod scratchpad_window;
 mod session_popup;
+mod sidebar_widget;
+mod slash_commands;
+mod tab_bar_widget;
 mod toolbar_view_model;
 mod toolbar_widget;
 mod ui_state;
@@ -0,0 +1,74 @@
+//! スクラッチパッドのノート一覧と、ノートを会話に使う操作
+
+use super::ChatView;
+use chat_core::ChatCommand;
+use gpui::*;
+use neko_ui::{scratchpad_notes_bar, ScratchpadNoteItem};
+
+/// スクラッチパッドの見出しに置くノートの一覧を構築する。
+pub(super) fn notes_bar_widget(view: &ChatView, cx: &mut Context<ChatView>) -> Div {
+    let context_notes = view
+        .chat_state_snapshot()
+        .conversation_settings
+        .context_notes;
+    let scratchpad = view.state.scratchpad();
+    let items: Vec<ScratchpadNoteItem> = scratchpad
+        .notes()
+        .into_iter()
+        .map(|name| ScratchpadNoteItem {
+            active: name == scratchpad.active_note(),
+            in_context: context_notes.contains(&name),
+            name,
+        })
+        .collect();
+
+    scratchpad_notes_bar(
+        &items,
+        |this: &mut ChatView, name, window, cx| this.open_note(name, window, cx),
+        |this: &mut ChatView, name, _window, cx| this.toggle_context_note(name, cx),
+        |this: &mut ChatView, window, cx| this.create_note(window, cx),
+        |this: &mut ChatView, window, cx| this.send_scratchpad_selection(window, cx),
+        cx,
+    )
+}
+
+impl ChatView {
+    fn open_note(&mut self, name: &str, window: &mut Window, cx: &mut Context<ChatView>) {
+        if let Err(err) = self.state.scratchpad_mut().open(name, window, cx) {
+            eprintln!("{}", err);
+        }
+        cx.notify();
+    }
+
+    fn create_note(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        if let Err(err) = self.state.scratchpad_mut().create(window, cx) {
+            eprintln!("{}", err);
+        }
+        cx.notify();
+    }
+
+    /// ノートを今の会話のプロンプトに含めるかを切り替える（設定は会話ごとに保存される）
+    fn toggle_context_note(&mut self, name: &str, cx: &mut Context<ChatView>) {
+        let mut notes = self
+            .chat_state_snapshot()
+            .conversation_settings
+            .context_notes;
+        if let Some(index) = notes.iter().position(|n| n == name) {
+            notes.remove(index);
+        } else {
+            notes.push(name.to_string());
+        }
+        self.run_chat_command(ChatCommand::SetContextNotes(notes), cx);
+    }
+
+    /// 編集欄で選択している文字列（選択が無ければノート全体）をチャットに送る
+    fn send_scratchpad_selection(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        let text = self.state.scratchpad().selection(window, cx);
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        self.run_chat_command(ChatCommand::SendUserMessage(text.to_string()), cx);
+        self.tabs.active().message_list().scroll_to_latest();
+    }
+}
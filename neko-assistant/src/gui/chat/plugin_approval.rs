@@ -0,0 +1,64 @@
+//! 未承認・変更されたプラグインの読み込み許可ダイアログ
+
+use chat_core::plugins::approval::{declared_capabilities, describe_capabilities};
+use chat_core::{approval_status, approve_plugin, ApprovalStatus, PluginEntry};
+use gpui::{Context, PromptLevel, Window};
+
+use super::chat_view::ChatView;
+
+const ALLOW: usize = 0;
+
+/// 承認が必要なプラグインを一つずつ確認する。
+/// 許可した分は `approvals.json` に記録され、ホットリロードで読み込まれる。
+pub(super) fn request_plugin_approvals(
+    plugins: &[PluginEntry],
+    window: &mut Window,
+    cx: &mut Context<ChatView>,
+) {
+    let pending: Vec<(PluginEntry, ApprovalStatus)> = plugins
+        .iter()
+        .filter(|entry| entry.enabled)
+        .filter_map(|entry| match approval_status(entry) {
+            status @ (ApprovalStatus::Pending | ApprovalStatus::Changed) => {
+                Some((entry.clone(), status))
+            }
+            _ => None,
+        })
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+
+    cx.spawn_in(window, async move |_this, cx| {
+        for (entry, status) in pending {
+            let message = match status {
+                ApprovalStatus::Changed => {
+                    format!("Plugin '{}' changed since it was approved", entry.dir_name)
+                }
+                _ => format!("Allow plugin '{}' to load?", entry.dir_name),
+            };
+            let detail = format!(
+                "Capabilities: {}",
+                describe_capabilities(&declared_capabilities(&entry))
+            );
+            let Ok(answer) = cx.update(|window, cx| {
+                window.prompt(
+                    PromptLevel::Warning,
+                    &message,
+                    Some(&detail),
+                    &["Allow", "Deny"],
+                    cx,
+                )
+            }) else {
+                return;
+            };
+            if answer.await.ok() != Some(ALLOW) {
+                continue;
+            }
+            if let Err(err) = approve_plugin(&entry) {
+                eprintln!("failed to approve plugin '{}': {:#}", entry.dir_name, err);
+            }
+        }
+    })
+    .detach();
+}
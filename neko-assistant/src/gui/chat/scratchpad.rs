@@ -1,30 +1,50 @@
 use std::fs;
-use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
+use std::io::{self, ErrorKind};
+use std::path::Path;
 use std::sync::Arc;
 
+use chat_core::{NoteStore, DEFAULT_NOTE};
 use gpui::*;
-use gpui::{Context, Entity, Window};
+use gpui::{Context, Entity, EntityInputHandler, Window};
 use gpui_component::button::Button;
 use gpui_component::input::InputState;
 use gpui_component::WindowExt;
-use neko_ui::ConsoleLogEntry;
+use neko_ui::{current_theme, ConsoleLogEntry};
 
 use super::console_window::console_window;
 use super::scratchpad_window::scratchpad_window;
 
+/// スクラッチパッドの名前付きノートを編集欄に読み書きする。
+/// 編集欄には選択中のノートを 1 つだけ表示する。
 #[derive(Clone)]
 pub struct ScratchpadManager {
-    file_path: PathBuf,
+    store: NoteStore,
+    active_note: String,
     editor_input: Entity<InputState>,
 }
 
 impl ScratchpadManager {
-    pub fn new(repo_root: &Path, editor_input: Entity<InputState>) -> Self {
-        let file_path = repo_root.join("scratchpad.md");
-        Self {
-            file_path,
+    /// リポジトリ直下の旧 `scratchpad.md` は、既定のノートがまだ無ければ取り込む
+    pub fn new(repo_root: &Path, store: NoteStore, editor_input: Entity<InputState>) -> Self {
+        let manager = Self {
+            store,
+            active_note: DEFAULT_NOTE.to_string(),
             editor_input,
+        };
+        if let Err(err) = manager.import_legacy(&repo_root.join("scratchpad.md")) {
+            eprintln!("Failed to import legacy scratchpad: {}", err);
+        }
+        manager
+    }
+
+    fn import_legacy(&self, legacy_path: &Path) -> io::Result<()> {
+        if self.store.list()?.iter().any(|name| name == DEFAULT_NOTE) {
+            return Ok(());
+        }
+        match fs::read_to_string(legacy_path) {
+            Ok(content) => self.store.save(DEFAULT_NOTE, &content),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
         }
     }
 
@@ -32,44 +52,109 @@ impl ScratchpadManager {
         &self.editor_input
     }
 
+    pub fn active_note(&self) -> &str {
+        &self.active_note
+    }
+
+    /// ノート名の一覧（まだ保存していない選択中のノートも含む）
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes = self.store.list().unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to list notes in {}: {}",
+                self.store.dir().display(),
+                err
+            );
+            Vec::new()
+        });
+        if !notes.contains(&self.active_note) {
+            notes.push(self.active_note.clone());
+            notes.sort();
+        }
+        notes
+    }
+
+    /// 選択中のノートを編集欄に読み込む
     pub fn load(
         &self,
         window: &mut Window,
         cx: &mut Context<super::ChatView>,
     ) -> Result<(), String> {
-        match fs::read_to_string(&self.file_path) {
-            Ok(content) => {
-                self.editor_input.update(cx, |state, cx| {
-                    state.set_value(&content, window, cx);
-                });
-                cx.notify();
-                Ok(())
-            }
-            Err(err) => {
-                if err.kind() == ErrorKind::NotFound {
-                    return Ok(());
-                }
-                Err(format!(
-                    "Failed to load scratchpad {}: {}",
-                    self.file_path.display(),
-                    err
-                ))
-            }
-        }
+        let content = self
+            .store
+            .load(&self.active_note)
+            .map_err(|err| format!("Failed to load note {}: {}", self.active_note, err))?;
+        self.editor_input.update(cx, |state, cx| {
+            state.set_value(&content, window, cx);
+        });
+        cx.notify();
+        Ok(())
     }
 
-    #[allow(dead_code)]
+    /// 編集欄の内容を選択中のノートに保存する
     pub fn save(&self, cx: &mut Context<super::ChatView>) -> Result<(), String> {
         let contents = self.editor_input.read(cx).value();
-        fs::write(&self.file_path, contents.as_str()).map_err(|err| {
-            format!(
-                "Failed to save scratchpad {}: {}",
-                self.file_path.display(),
-                err
-            )
+        self.store
+            .save(&self.active_note, contents.as_str())
+            .map_err(|err| format!("Failed to save note {}: {}", self.active_note, err))
+    }
+
+    /// 今のノートを保存してから別のノートを開く
+    pub fn open(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<super::ChatView>,
+    ) -> Result<(), String> {
+        self.save(cx)?;
+        self.active_note = name.to_string();
+        self.load(window, cx)
+    }
+
+    /// 空の新しいノートを作って開く
+    pub fn create(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<super::ChatView>,
+    ) -> Result<(), String> {
+        let name = self
+            .store
+            .unused_name("note")
+            .and_then(|name| self.store.save(&name, "").map(|_| name))
+            .map_err(|err| format!("Failed to create note: {}", err))?;
+        self.open(&name, window, cx)
+    }
+
+    /// 編集欄で選択している文字列（選択が無ければノート全体）
+    pub fn selection(&self, window: &mut Window, cx: &mut Context<super::ChatView>) -> String {
+        self.editor_input.update(cx, |state, cx| {
+            let selected = state
+                .selected_text_range(false, window, cx)
+                .map(|selection| selection.range)
+                .filter(|range| !range.is_empty())
+                .and_then(|range| state.text_for_range(range, &mut None, window, cx));
+            selected.unwrap_or_else(|| state.value().to_string())
         })
     }
 
+    /// 選択中のノートの末尾に書き足し、編集欄も更新する
+    pub fn append(
+        &self,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<super::ChatView>,
+    ) -> Result<(), String> {
+        self.save(cx)?;
+        let content = self
+            .store
+            .append(&self.active_note, text)
+            .map_err(|err| format!("Failed to append to note {}: {}", self.active_note, err))?;
+        self.editor_input.update(cx, |state, cx| {
+            state.set_value(&content, window, cx);
+        });
+        cx.notify();
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub fn open_sheet(
         &self,
@@ -119,7 +204,7 @@ impl ScratchpadManager {
                 .title(
                     div()
                         .text_sm()
-                        .text_color(rgb(0xffffff))
+                        .text_color(current_theme().colors.text)
                         .child("Scratchpad"),
                 )
                 .size(px(400.0))
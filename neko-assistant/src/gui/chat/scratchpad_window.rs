@@ -2,6 +2,7 @@ use gpui::*;
 use gpui_component::button::Button;
 use gpui_component::input::{Input, InputState};
 use gpui_component::StyledExt;
+use neko_ui::current_theme;
 use ui_utils::TextStyleExt;
 
 #[allow(dead_code)]
@@ -10,14 +11,10 @@ pub fn scratchpad_window(
     load_button: Button,
     save_button: Button,
 ) -> Div {
+    let colors = current_theme().colors;
     div().v_flex().gap_1().p_1()
-        .bg(rgb(0x161616))
-        .child(
-            div()
-                .text_md()
-                .text_color(rgb(0xffffff))
-                .child("Scratchpad"),
-        )
+        .bg(colors.surface)
+        .child(div().text_md().text_color(colors.text).child("Scratchpad"))
         .child(div().h_flex().gap_2().child(load_button).child(save_button))
         .child(Input::new(editor_input).w_full().h(px(220.0)).text_sm())
 }
@@ -2,7 +2,7 @@ use chat_core::{ChatCommand, ChatController};
 use gpui::*;
 use gpui_component::button::Button;
 use gpui_component::StyledExt;
-use neko_ui::ChatSidebarItem;
+use neko_ui::{current_theme, ChatSidebarItem};
 use std::sync::Arc;
 
 #[allow(dead_code)]
@@ -23,7 +23,7 @@ impl SessionPopupView {
 
 impl Render for SessionPopupView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        gpui_component::init(cx);
+        crate::gui::theme::init_components(cx);
 
         let mut list = div().v_flex().gap_2();
 
@@ -31,7 +31,7 @@ impl Render for SessionPopupView {
             list = list.child(
                 div()
                     .text_sm()
-                    .text_color(rgb(0xaaaaaa))
+                    .text_color(current_theme().colors.text_muted)
                     .child("No sessions yet"),
             );
         } else {
@@ -74,7 +74,7 @@ impl Render for SessionPopupView {
             .child(
                 div()
                     .text_lg()
-                    .text_color(rgb(0xffffff))
+                    .text_color(current_theme().colors.text)
                     .child("Select Session"),
             )
             .child(list)
@@ -0,0 +1,104 @@
+use super::ChatView;
+use chat_core::ChatCommand;
+use chat_history::ConversationFilter;
+use gpui::*;
+use neko_ui::{
+    chat_sidebar, chat_sidebar_filter_bar, chat_sidebar_labels_editor, ChatSidebarFilter,
+    ChatSidebarItem,
+};
+
+/// 会話サイドバー（フィルタバーと、開いている会話のフォルダ・タグの編集欄付き）を構築する。
+pub(super) fn sidebar_widget(
+    view: &ChatView,
+    items: &[ChatSidebarItem],
+    filter: &ChatSidebarFilter,
+    cx: &mut Context<ChatView>,
+) -> Div {
+    let mut filter_bar = chat_sidebar_filter_bar(
+        filter,
+        view.state.sidebar_search(),
+        |this: &mut ChatView, folder, cx| {
+            this.update_conversation_filter(|f| f.folder = folder, cx);
+        },
+        |this: &mut ChatView, tag, cx| {
+            this.update_conversation_filter(|f| f.tag = tag, cx);
+        },
+        |this: &mut ChatView, cx| {
+            this.update_conversation_filter(|f| f.archived = !f.archived, cx);
+        },
+        cx,
+    );
+    if let Some(active) = items.iter().find(|item| item.active) {
+        filter_bar = filter_bar.child(chat_sidebar_labels_editor(
+            active,
+            view.state.folder_input(),
+            view.state.tag_input(),
+            |this: &mut ChatView, id, tag, cx| {
+                this.dispatch_sidebar_command(
+                    ChatCommand::RemoveConversationTag {
+                        id: id.to_string(),
+                        tag: tag.to_string(),
+                    },
+                    cx,
+                );
+            },
+            cx,
+        ));
+    }
+
+    chat_sidebar(
+        items,
+        Some(filter_bar),
+        |this: &mut ChatView, _event, window, cx| {
+            this.new_conversation(window, cx);
+        },
+        |this: &mut ChatView, id, window, cx| {
+            this.open_conversation(id, window, cx);
+        },
+        |this: &mut ChatView, id, cx| {
+            this.dispatch_sidebar_command(ChatCommand::DeleteConversation(id.to_string()), cx);
+        },
+        |this: &mut ChatView, id, pinned, cx| {
+            this.dispatch_sidebar_command(
+                ChatCommand::SetConversationPinned {
+                    id: id.to_string(),
+                    pinned,
+                },
+                cx,
+            );
+        },
+        |this: &mut ChatView, id, archived, cx| {
+            this.dispatch_sidebar_command(
+                ChatCommand::SetConversationArchived {
+                    id: id.to_string(),
+                    archived,
+                },
+                cx,
+            );
+        },
+        cx,
+    )
+}
+
+impl ChatView {
+    pub(super) fn dispatch_sidebar_command(
+        &mut self,
+        command: ChatCommand,
+        cx: &mut Context<ChatView>,
+    ) {
+        if let Err(err) = self.controller().handle_command(command) {
+            eprintln!("Sidebar command failed: {}", err.message());
+        }
+        cx.notify();
+    }
+
+    pub(super) fn update_conversation_filter(
+        &mut self,
+        update: impl FnOnce(&mut ConversationFilter),
+        cx: &mut Context<ChatView>,
+    ) {
+        let mut filter = self.chat_state_snapshot().conversation_filter;
+        update(&mut filter);
+        self.dispatch_sidebar_command(ChatCommand::SetConversationFilter(filter), cx);
+    }
+}
@@ -0,0 +1,72 @@
+//! 入力欄のスラッシュコマンドの補完（候補の表示と Tab / クリックでの補完）
+
+use super::ChatView;
+use chat_core::{ChatState, SlashCommandSpec};
+use gpui::*;
+use neko_ui::{chat_input_panel_with_suggestions, SlashSuggestion};
+
+impl ChatView {
+    /// 入力中のコマンド名に一致する候補を付けて入力パネルを描画する
+    pub(super) fn render_input_panel(
+        &self,
+        state: &ChatState,
+        hint_text: &str,
+        cx: &mut Context<ChatView>,
+    ) -> Div {
+        let input = self.state.input_state().read(cx).value();
+        let suggestions: Vec<SlashSuggestion> = state
+            .slash_commands
+            .completions(&input)
+            .into_iter()
+            .map(slash_suggestion)
+            .collect();
+        chat_input_panel_with_suggestions(
+            self.state.input_state(),
+            hint_text,
+            &suggestions,
+            |this: &mut ChatView, index, window, cx| {
+                this.complete_slash_command(index, window, cx);
+            },
+            cx,
+        )
+        .capture_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+            if event.keystroke.key == "tab" && this.complete_slash_command(0, window, cx) {
+                cx.stop_propagation();
+            }
+        }))
+    }
+
+    /// `index` 番目の候補で入力欄を置き換える。候補が無ければ false
+    fn complete_slash_command(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) -> bool {
+        let input = self.state.input_state().clone();
+        let value = input.read(cx).value();
+        let state = self.chat_state_snapshot();
+        let Some(completion) = state
+            .slash_commands
+            .completions(&value)
+            .get(index)
+            .map(|spec| spec.completion())
+        else {
+            return false;
+        };
+        input.update(cx, |input, cx| {
+            input.set_value(completion, window, cx);
+            input.focus(window, cx);
+        });
+        cx.notify();
+        true
+    }
+}
+
+fn slash_suggestion(spec: &SlashCommandSpec) -> SlashSuggestion {
+    SlashSuggestion {
+        command: format!("/{}", spec.name),
+        usage: spec.usage.clone(),
+        description: spec.description.clone(),
+    }
+}
@@ -0,0 +1,172 @@
+//! 会話タブのバーとタブ・ウィンドウの操作
+
+use super::chat_tabs::ChatTab;
+use super::controller_facade::ChatControllerFacade;
+use super::data_mappers::is_thinking_message;
+use super::ChatView;
+use crate::gui::window_options_with_title;
+use chat_core::ChatCommand;
+use gpui::*;
+use gpui_component::Root;
+use neko_ui::{chat_tab_bar, ChatTabItem};
+use std::sync::Arc;
+
+/// ウィンドウ上部の会話タブのバーを構築する。
+pub(super) fn tab_bar_widget(view: &ChatView, cx: &mut Context<ChatView>) -> Div {
+    let active = view.tabs.active_index();
+    let items: Vec<ChatTabItem> = view
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(ix, tab)| {
+            let state = tab.controller().state_snapshot();
+            let title = if state.conversation_title.trim().is_empty() {
+                "New Chat".to_string()
+            } else {
+                state.conversation_title
+            };
+            ChatTabItem {
+                title,
+                active: ix == active,
+                busy: state.messages.last().is_some_and(is_thinking_message),
+            }
+        })
+        .collect();
+
+    chat_tab_bar(
+        &items,
+        |this: &mut ChatView, ix, window, cx| this.select_tab(ix, window, cx),
+        |this: &mut ChatView, ix, window, cx| this.close_tab(ix, window, cx),
+        |this: &mut ChatView, window, cx| this.new_tab(window, cx),
+        cx,
+    )
+}
+
+impl ChatView {
+    /// 新しい会話を新しいタブで開く
+    pub(super) fn new_tab(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        self.open_tab(None, window, cx);
+    }
+
+    /// 会話（None なら新しい会話）を新しいタブで開く
+    fn open_tab(
+        &mut self,
+        conversation_id: Option<&str>,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        match self.controller().controller().open_session(conversation_id) {
+            Ok(session) => {
+                let controller = Arc::new(session);
+                let subscription = self.event_loop.subscribe(&controller);
+                self.tabs.push(ChatTab::new(ChatControllerFacade::new(
+                    controller,
+                    subscription,
+                )));
+                self.on_tab_changed(window, cx);
+            }
+            Err(err) => eprintln!("Failed to open a tab: {}", err.message()),
+        }
+    }
+
+    /// タブを閉じる（最後のタブは閉じない）。セッションはタブと一緒に閉じられる。
+    pub(super) fn close_tab(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        if self.tabs.remove(index).is_some() {
+            self.on_tab_changed(window, cx);
+        }
+    }
+
+    pub(super) fn select_tab(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        if self.tabs.select(index) {
+            self.on_tab_changed(window, cx);
+        }
+    }
+
+    /// 前後のタブへ移る（端では反対側に回る）
+    pub(super) fn step_tab(
+        &mut self,
+        step: isize,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        self.tabs.step(step);
+        self.on_tab_changed(window, cx);
+    }
+
+    /// 会話を開く。別のタブで開いていればそのタブへ移り、そうでなければ今のタブで開く。
+    /// 今のタブが応答を生成中なら、新しいタブで開く。
+    pub(super) fn open_conversation(
+        &mut self,
+        conversation_id: &str,
+        window: &mut Window,
+        cx: &mut Context<ChatView>,
+    ) {
+        if let Some(index) = self.tabs.position_of(conversation_id) {
+            self.select_tab(index, window, cx);
+            return;
+        }
+        if self.controller().controller().has_pending_turn() {
+            self.open_tab(Some(conversation_id), window, cx);
+            return;
+        }
+        self.run_chat_command(
+            ChatCommand::SwitchConversation(conversation_id.to_string()),
+            cx,
+        );
+        self.on_tab_changed(window, cx);
+    }
+
+    /// 新しい会話を開く。今のタブが応答を生成中なら新しいタブで開く
+    pub(super) fn new_conversation(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        if self.controller().controller().has_pending_turn() {
+            self.new_tab(window, cx);
+        } else {
+            self.run_chat_command(ChatCommand::CreateConversation, cx);
+        }
+    }
+
+    /// 新しい会話を新しいウィンドウで開く（会話ストア・プラグイン・MCP は共有する）
+    pub(super) fn open_new_window(&mut self, cx: &mut Context<ChatView>) {
+        let session = match self.controller().controller().open_session(None) {
+            Ok(session) => session,
+            Err(err) => {
+                eprintln!("Failed to open a new window: {}", err.message());
+                return;
+            }
+        };
+        let repo_root = self.repo_root.clone();
+        let plugins = self.plugins.clone();
+        let prompt_registry = self.prompt_registry.clone();
+        let opened = cx.open_window(
+            window_options_with_title("Neko Assistant"),
+            move |window, cx| {
+                let view = cx.new(|cx| {
+                    ChatView::for_session(window, cx, repo_root, plugins, prompt_registry, session)
+                });
+                cx.new(|cx| Root::new(view, window, cx))
+            },
+        );
+        if let Err(err) = opened {
+            eprintln!("Failed to open a new window: {}", err);
+        }
+    }
+
+    /// 選択中のタブが変わったらモデル選択を合わせる
+    fn on_tab_changed(&mut self, window: &mut Window, cx: &mut Context<ChatView>) {
+        let state = self.chat_state_snapshot();
+        self.state
+            .model_selector()
+            .sync_selection(&state, window, cx);
+        cx.notify();
+    }
+}
@@ -1,10 +1,23 @@
 use super::chat_view::{describe_agent_mode, ChatView};
 use chat_core::PromptBuilderRegistry;
+use chat_history::ConversationSettings;
 
 #[allow(dead_code)]
 pub(super) struct ToolbarViewModel {
     builder_status: String,
     show_mcp_status: bool,
+    settings_badges: Vec<String>,
+    builder_choices: Vec<BuilderChoice>,
+    /// 会話でビルダーを指定していない（モデルの既定を使う）
+    builder_auto: bool,
+}
+
+/// ツールバーのビルダー選択肢
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct BuilderChoice {
+    pub id: String,
+    pub label: String,
+    pub active: bool,
 }
 
 impl ToolbarViewModel {
@@ -15,6 +28,66 @@ impl ToolbarViewModel {
             &state.active_model,
             view.state.show_mcp_status(),
         )
+        .with_builder_override(
+            view.prompt_registry.as_ref(),
+            &state.active_model,
+            state.conversation_settings.prompt_builder.as_deref(),
+        )
+        .with_conversation_settings(
+            &state.active_model,
+            &state.conversation_settings,
+            state.mcp_tools.len(),
+        )
+    }
+
+    /// 現在の会話の設定をバッジ表示用の文字列にする
+    pub(super) fn with_conversation_settings(
+        mut self,
+        active_model: &str,
+        settings: &ConversationSettings,
+        total_tools: usize,
+    ) -> Self {
+        self.settings_badges = describe_conversation_settings(active_model, settings, total_tools);
+        self
+    }
+
+    /// 会話で指定したビルダーを反映し、モデルに一致するビルダーを選択肢にする
+    pub(super) fn with_builder_override(
+        mut self,
+        registry: &PromptBuilderRegistry,
+        active_model: &str,
+        preferred: Option<&str>,
+    ) -> Self {
+        let active = registry
+            .resolve_preferred(active_model, preferred)
+            .map(|source| source.id());
+        self.builder_auto = preferred.is_none();
+        self.builder_choices = registry
+            .candidates(active_model)
+            .into_iter()
+            .map(|source| BuilderChoice {
+                active: active.as_deref() == Some(source.id().as_str()),
+                id: source.id(),
+                label: source.label(),
+            })
+            .collect();
+        if preferred.is_some() {
+            self.builder_status =
+                describe_prompt_builder(registry, active_model, preferred) + " [会話で指定]";
+        }
+        self
+    }
+
+    pub fn settings_badges(&self) -> &[String] {
+        &self.settings_badges
+    }
+
+    pub fn builder_choices(&self) -> &[BuilderChoice] {
+        &self.builder_choices
+    }
+
+    pub fn builder_auto(&self) -> bool {
+        self.builder_auto
     }
 
     #[allow(dead_code)]
@@ -36,20 +109,59 @@ impl ToolbarViewModel {
         active_model: &str,
         show_mcp_status: bool,
     ) -> Self {
-        let builder_status = describe_prompt_builder(registry, active_model);
+        let builder_status = describe_prompt_builder(registry, active_model, None);
         Self {
             builder_status,
             show_mcp_status,
+            settings_badges: Vec::new(),
+            builder_choices: Vec::new(),
+            builder_auto: true,
         }
     }
 }
 
-fn describe_prompt_builder(registry: &PromptBuilderRegistry, active_model: &str) -> String {
+fn describe_conversation_settings(
+    active_model: &str,
+    settings: &ConversationSettings,
+    total_tools: usize,
+) -> Vec<String> {
+    let model = if settings.model.is_some() {
+        format!("Model: {}", active_model)
+    } else {
+        format!("Model: {} (既定)", active_model)
+    };
+    let prompt = match &settings.system_prompt {
+        Some(prompt) => format!("Prompt: {}", truncate(prompt, 24)),
+        None => "Prompt: 既定".to_string(),
+    };
+    let tools = match &settings.enabled_tools {
+        Some(enabled) => format!("Tools: {}/{}", enabled.len(), total_tools),
+        None => "Tools: すべて".to_string(),
+    };
+    let generation = format!("Options: {}", settings.generation.summary());
+    vec![model, prompt, tools, generation]
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() <= max && line.len() == text.len() {
+        return line.to_string();
+    }
+    let mut truncated: String = line.chars().take(max).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn describe_prompt_builder(
+    registry: &PromptBuilderRegistry,
+    active_model: &str,
+    preferred: Option<&str>,
+) -> String {
     if registry.is_empty() {
         return "Prompt Builder: 未検出".to_string();
     }
 
-    if let Some(source) = registry.resolve(active_model) {
+    if let Some(source) = registry.resolve_preferred(active_model, preferred) {
         let meta = source.metadata();
         let mode = describe_agent_mode(source.preferred_agent());
         if let Some(manifest) = source.manifest() {
@@ -83,6 +195,7 @@ fn describe_prompt_builder(registry: &PromptBuilderRegistry, active_model: &str)
 mod tests {
     use super::ToolbarViewModel;
     use chat_core::{register_builtin_prompt_builders, PromptBuilderRegistry};
+    use chat_history::{ConversationSettings, GenerationOptions};
 
     #[test]
     fn reports_available_prompt_builder() {
@@ -116,4 +229,66 @@ mod tests {
         );
         assert_eq!(model.mcp_toggle_label(), "Hide MCP");
     }
+
+    #[test]
+    fn lists_builder_choices_with_conversation_override() {
+        let mut registry = PromptBuilderRegistry::from_plugins(&[]);
+        register_builtin_prompt_builders(&mut registry);
+
+        let auto = ToolbarViewModel::from_prompt_state(&registry, "qwen3:4b-instruct", false)
+            .with_builder_override(&registry, "qwen3:4b-instruct", None);
+        assert!(auto.builder_auto());
+        assert_eq!(auto.builder_choices().len(), 1);
+        assert!(auto.builder_choices()[0].active);
+        assert_eq!(
+            auto.builder_choices()[0].label,
+            "Builtin Qwen Prompt (builtin)"
+        );
+
+        // モデルに一致しない指定は既定のビルダーで表示する
+        let stale = ToolbarViewModel::from_prompt_state(&registry, "qwen3:4b-instruct", false)
+            .with_builder_override(&registry, "qwen3:4b-instruct", Some("plugin:removed"));
+        assert!(!stale.builder_auto());
+        assert!(stale.builder_choices()[0].active);
+        assert!(stale.builder_status().ends_with("[会話で指定]"));
+    }
+
+    #[test]
+    fn describes_conversation_settings() {
+        let registry = PromptBuilderRegistry::from_plugins(&[]);
+        let defaults = ToolbarViewModel::from_prompt_state(&registry, "phi4-mini:3.8b", false)
+            .with_conversation_settings("phi4-mini:3.8b", &ConversationSettings::default(), 3);
+        assert_eq!(
+            defaults.settings_badges(),
+            [
+                "Model: phi4-mini:3.8b (既定)",
+                "Prompt: 既定",
+                "Tools: すべて",
+                "Options: default",
+            ]
+        );
+
+        let settings = ConversationSettings {
+            model: Some("qwen3:4b-instruct".to_string()),
+            system_prompt: Some("Answer briefly\nin English.".to_string()),
+            enabled_tools: Some(vec!["forecast@weather".to_string()]),
+            generation: GenerationOptions {
+                temperature: Some(0.7),
+                ..Default::default()
+            },
+            prompt_builder: None,
+            context_notes: Vec::new(),
+        };
+        let custom = ToolbarViewModel::from_prompt_state(&registry, "qwen3:4b-instruct", false)
+            .with_conversation_settings("qwen3:4b-instruct", &settings, 3);
+        assert_eq!(
+            custom.settings_badges(),
+            [
+                "Model: qwen3:4b-instruct",
+                "Prompt: Answer briefly…",
+                "Tools: 1/3",
+                "Options: temp 0.7",
+            ]
+        );
+    }
 }
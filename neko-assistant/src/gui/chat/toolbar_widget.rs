@@ -1,13 +1,107 @@
+use super::builder_compare::open_builder_compare_window;
 use super::toolbar_view_model::ToolbarViewModel;
 use super::ChatView;
+use chat_core::ChatCommand;
 use gpui::*;
+use gpui_component::button::Button;
 use gpui_component::StyledExt;
+use neko_ui::{conversation_settings_row, current_theme};
 
 pub(super) fn toolbar_widget(
-    _view_entity: gpui::Entity<ChatView>,
-    _view_model: ToolbarViewModel,
-    _window: &mut gpui::Window,
+    view_entity: gpui::Entity<ChatView>,
+    view_model: ToolbarViewModel,
+    window: &mut gpui::Window,
 ) -> impl IntoElement {
     // toolbar no longer contains scratchpad/console/session buttons — those are in the top menu
-    div().p_3().rounded_md().bg(rgb(0x101010)).child(div().h_flex().items_center())
+    div()
+        .p_3()
+        .rounded_md()
+        .bg(current_theme().colors.surface)
+        .v_flex()
+        .gap_2()
+        .child(
+            div()
+                .h_flex()
+                .items_center()
+                .child(conversation_settings_row(view_model.settings_badges())),
+        )
+        .children(builder_row(&view_entity, &view_model, window))
+}
+
+/// 会話で使うプロンプトビルダーの選択（Auto はモデルの既定）と比較ビュー
+fn builder_row(
+    view_entity: &gpui::Entity<ChatView>,
+    view_model: &ToolbarViewModel,
+    window: &mut gpui::Window,
+) -> Option<Div> {
+    if view_model.builder_choices().is_empty() {
+        return None;
+    }
+    let auto_label = if view_model.builder_auto() {
+        "[✓] Auto"
+    } else {
+        "[ ] Auto"
+    };
+    let mut row = div()
+        .h_flex()
+        .items_center()
+        .gap_2()
+        .child(
+            div()
+                .text_sm()
+                .text_color(current_theme().colors.text_muted)
+                .child("Builder"),
+        )
+        .child(
+            Button::new("builder_auto")
+                .label(auto_label)
+                .on_click(set_builder_listener(view_entity, None, window)),
+        );
+    for (idx, choice) in view_model.builder_choices().iter().enumerate() {
+        let label = if choice.active {
+            format!("[✓] {}", choice.label)
+        } else {
+            format!("[ ] {}", choice.label)
+        };
+        row = row.child(Button::new(("builder_choice", idx)).label(label).on_click(
+            set_builder_listener(view_entity, Some(choice.id.clone()), window),
+        ));
+    }
+    Some(
+        row.child(
+            Button::new("compare_builders")
+                .label("Compare…")
+                .on_click(window.listener_for(
+                    view_entity,
+                    |this: &mut ChatView,
+                     _event: &ClickEvent,
+                     _window,
+                     cx: &mut gpui::Context<ChatView>| {
+                        open_builder_compare_window(this.controller().controller(), cx);
+                    },
+                )),
+        ),
+    )
+}
+
+fn set_builder_listener(
+    view_entity: &gpui::Entity<ChatView>,
+    builder: Option<String>,
+    window: &mut gpui::Window,
+) -> impl Fn(&ClickEvent, &mut gpui::Window, &mut App) + 'static {
+    window.listener_for(
+        view_entity,
+        move |this: &mut ChatView,
+              _event: &ClickEvent,
+              _window,
+              cx: &mut gpui::Context<ChatView>| {
+            if let Err(err) = this
+                .controller()
+                .handle_command(ChatCommand::SetPromptBuilder(builder.clone()))
+            {
+                eprintln!("Failed to set prompt builder: {}", err.message());
+            }
+            cx.notify();
+        },
+    )
 }
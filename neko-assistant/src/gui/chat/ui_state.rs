@@ -1,11 +1,13 @@
 use super::data_mappers::ChatStateMapper;
 use chat_core::ChatState;
-use neko_ui::{ChatMessageRow, ChatSidebarItem, ConsoleLogEntry, McpServerItem, McpToolItem};
+use neko_ui::{
+    ChatMessageRow, ChatSidebarFilter, ChatSidebarItem, ConsoleLogEntry, McpServerItem, McpToolItem,
+};
 
 #[derive(Clone)]
 pub struct ChatUiSnapshot {
-    #[allow(dead_code)]
     pub sidebar_items: Vec<ChatSidebarItem>,
+    pub sidebar_filter: ChatSidebarFilter,
     pub server_items: Vec<McpServerItem>,
     pub tool_items: Vec<McpToolItem>,
     pub console_logs: Vec<ConsoleLogEntry>,
@@ -16,6 +18,7 @@ impl ChatUiSnapshot {
     pub fn from_state(state: &ChatState) -> Self {
         Self {
             sidebar_items: sidebar_items(state),
+            sidebar_filter: sidebar_filter(state),
             server_items: ChatStateMapper::mcp_server_items(state),
             tool_items: ChatStateMapper::mcp_tool_items(state),
             console_logs: ChatStateMapper::console_log_entries(state),
@@ -34,8 +37,22 @@ fn sidebar_items(state: &ChatState) -> Vec<ChatSidebarItem> {
             title: meta.title.clone(),
             message_count: meta.message_count,
             active: active_id == Some(meta.id.as_str()),
+            folder: meta.folder.clone(),
+            tags: meta.tags.clone(),
+            pinned: meta.pinned,
+            archived: meta.archived,
         })
         .collect()
 }
 
+fn sidebar_filter(state: &ChatState) -> ChatSidebarFilter {
+    ChatSidebarFilter {
+        folders: state.conversation_folders.clone(),
+        tags: state.conversation_tags.clone(),
+        active_folder: state.conversation_filter.folder.clone(),
+        active_tag: state.conversation_filter.tag.clone(),
+        show_archived: state.conversation_filter.archived,
+    }
+}
+
 // sidebar 生成は Phase 3 以降も UI ごとに必要となるためコマンドを保持
@@ -0,0 +1,191 @@
+//! キーボードショートカット（`keymap.toml` のアクション名と gpui のアクションの対応）
+
+use app_config::Keymap;
+use gpui::{actions, App, KeyBinding, Keystroke};
+
+/// ショートカットを有効にする要素のキーコンテキスト
+pub const KEY_CONTEXT: &str = "ChatView";
+
+actions!(
+    neko_assistant,
+    [
+        CommandPalette,
+        NewChat,
+        NextConversation,
+        PreviousConversation,
+        NewTab,
+        CloseTab,
+        NextTab,
+        PreviousTab,
+        NewWindow,
+        NextModel,
+        OpenSettings,
+        OpenMcpManager,
+        ToggleConsole,
+        ToggleScratchpad,
+        ToggleSidebar,
+        ToggleMcpStatus,
+    ]
+);
+
+/// キーやコマンドパレットから実行できるアプリの操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppAction {
+    CommandPalette,
+    NewChat,
+    NextConversation,
+    PreviousConversation,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    NewWindow,
+    NextModel,
+    OpenSettings,
+    OpenMcpManager,
+    ToggleConsole,
+    ToggleScratchpad,
+    ToggleSidebar,
+    ToggleMcpStatus,
+}
+
+impl AppAction {
+    pub const ALL: [AppAction; 16] = [
+        AppAction::CommandPalette,
+        AppAction::NewChat,
+        AppAction::NextConversation,
+        AppAction::PreviousConversation,
+        AppAction::NewTab,
+        AppAction::CloseTab,
+        AppAction::NextTab,
+        AppAction::PreviousTab,
+        AppAction::NewWindow,
+        AppAction::NextModel,
+        AppAction::OpenSettings,
+        AppAction::OpenMcpManager,
+        AppAction::ToggleConsole,
+        AppAction::ToggleScratchpad,
+        AppAction::ToggleSidebar,
+        AppAction::ToggleMcpStatus,
+    ];
+
+    /// `keymap.toml` でのアクション名
+    pub fn name(self) -> &'static str {
+        match self {
+            AppAction::CommandPalette => "command_palette",
+            AppAction::NewChat => "new_chat",
+            AppAction::NextConversation => "next_conversation",
+            AppAction::PreviousConversation => "previous_conversation",
+            AppAction::NewTab => "new_tab",
+            AppAction::CloseTab => "close_tab",
+            AppAction::NextTab => "next_tab",
+            AppAction::PreviousTab => "previous_tab",
+            AppAction::NewWindow => "new_window",
+            AppAction::NextModel => "next_model",
+            AppAction::OpenSettings => "open_settings",
+            AppAction::OpenMcpManager => "open_mcp_manager",
+            AppAction::ToggleConsole => "toggle_console",
+            AppAction::ToggleScratchpad => "toggle_scratchpad",
+            AppAction::ToggleSidebar => "toggle_sidebar",
+            AppAction::ToggleMcpStatus => "toggle_mcp_status",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AppAction::CommandPalette => "Command palette",
+            AppAction::NewChat => "New chat",
+            AppAction::NextConversation => "Next conversation",
+            AppAction::PreviousConversation => "Previous conversation",
+            AppAction::NewTab => "New tab",
+            AppAction::CloseTab => "Close tab",
+            AppAction::NextTab => "Next tab",
+            AppAction::PreviousTab => "Previous tab",
+            AppAction::NewWindow => "New window",
+            AppAction::NextModel => "Next model",
+            AppAction::OpenSettings => "Open settings",
+            AppAction::OpenMcpManager => "Open MCP manager",
+            AppAction::ToggleConsole => "Toggle console",
+            AppAction::ToggleScratchpad => "Toggle scratchpad",
+            AppAction::ToggleSidebar => "Toggle conversations",
+            AppAction::ToggleMcpStatus => "Toggle MCP status",
+        }
+    }
+
+    /// コマンドパレットでの分類
+    pub fn category(self) -> &'static str {
+        match self {
+            AppAction::NewChat | AppAction::NextConversation | AppAction::PreviousConversation => {
+                "Conversation"
+            }
+            AppAction::NewTab
+            | AppAction::CloseTab
+            | AppAction::NextTab
+            | AppAction::PreviousTab
+            | AppAction::NewWindow => "Tab",
+            AppAction::NextModel => "Model",
+            AppAction::CommandPalette | AppAction::OpenSettings | AppAction::OpenMcpManager => {
+                "App"
+            }
+            AppAction::ToggleConsole
+            | AppAction::ToggleScratchpad
+            | AppAction::ToggleSidebar
+            | AppAction::ToggleMcpStatus => "View",
+        }
+    }
+
+    /// パレットや設定画面に表示するキー（複数あれば先頭）
+    pub fn shortcut(self, keymap: &Keymap) -> Option<String> {
+        keymap.keys_for(self.name()).first().cloned()
+    }
+
+    fn key_binding(self, keystrokes: &str) -> KeyBinding {
+        let context = Some(KEY_CONTEXT);
+        match self {
+            AppAction::CommandPalette => KeyBinding::new(keystrokes, CommandPalette, context),
+            AppAction::NewChat => KeyBinding::new(keystrokes, NewChat, context),
+            AppAction::NextConversation => KeyBinding::new(keystrokes, NextConversation, context),
+            AppAction::PreviousConversation => {
+                KeyBinding::new(keystrokes, PreviousConversation, context)
+            }
+            AppAction::NewTab => KeyBinding::new(keystrokes, NewTab, context),
+            AppAction::CloseTab => KeyBinding::new(keystrokes, CloseTab, context),
+            AppAction::NextTab => KeyBinding::new(keystrokes, NextTab, context),
+            AppAction::PreviousTab => KeyBinding::new(keystrokes, PreviousTab, context),
+            AppAction::NewWindow => KeyBinding::new(keystrokes, NewWindow, context),
+            AppAction::NextModel => KeyBinding::new(keystrokes, NextModel, context),
+            AppAction::OpenSettings => KeyBinding::new(keystrokes, OpenSettings, context),
+            AppAction::OpenMcpManager => KeyBinding::new(keystrokes, OpenMcpManager, context),
+            AppAction::ToggleConsole => KeyBinding::new(keystrokes, ToggleConsole, context),
+            AppAction::ToggleScratchpad => KeyBinding::new(keystrokes, ToggleScratchpad, context),
+            AppAction::ToggleSidebar => KeyBinding::new(keystrokes, ToggleSidebar, context),
+            AppAction::ToggleMcpStatus => KeyBinding::new(keystrokes, ToggleMcpStatus, context),
+        }
+    }
+}
+
+/// キーマップを gpui に登録する。解釈できないキーやアクション名は警告して読み飛ばす
+pub fn bind_keymap(cx: &mut App, keymap: &Keymap) {
+    for action in keymap.unknown_actions() {
+        eprintln!("Warning: Unknown action in keymap: {}", action);
+    }
+
+    let mut bindings = Vec::new();
+    for action in AppAction::ALL {
+        for keystrokes in keymap.keys_for(action.name()) {
+            let valid = keystrokes
+                .split_whitespace()
+                .all(|keystroke| Keystroke::parse(keystroke).is_ok());
+            if !valid || keystrokes.trim().is_empty() {
+                eprintln!(
+                    "Warning: Invalid key binding for {}: {:?}",
+                    action.name(),
+                    keystrokes
+                );
+                continue;
+            }
+            bindings.push(action.key_binding(keystrokes));
+        }
+    }
+    cx.bind_keys(bindings);
+}
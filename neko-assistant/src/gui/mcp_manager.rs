@@ -4,6 +4,7 @@ use gpui::*;
 use gpui_component::button::Button;
 use gpui_component::input::{Input, InputState};
 use gpui_component::{Root, StyledExt};
+use neko_ui::current_theme;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -200,7 +201,7 @@ impl McpManagerView {
 
 impl Render for McpManagerView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        gpui_component::init(cx);
+        crate::gui::theme::init_components(cx);
 
         let mut root = div().v_flex().gap_4().p_4().size_full();
         root = root.child(div().child("MCP Server Manager").text_size(px(22.0)));
@@ -293,7 +294,11 @@ impl Render for McpManagerView {
         root = root.child(actions);
 
         if let Some(msg) = self.status.borrow().as_ref() {
-            root = root.child(div().text_color(rgb(0xcccccc)).child(msg.clone()));
+            root = root.child(
+                div()
+                    .text_color(current_theme().colors.text_muted)
+                    .child(msg.clone()),
+            );
         }
 
         root
@@ -1,8 +1,10 @@
 pub mod chat;
 pub mod console;
+pub mod keymap;
 pub mod mcp_manager;
 pub mod plugins;
 pub mod settings;
+pub mod theme;
 
 use gpui::{SharedString, TitlebarOptions, WindowOptions};
 
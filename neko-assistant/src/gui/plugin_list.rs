@@ -26,7 +26,7 @@ impl PluginListView {
 impl Render for PluginListView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         // Init gpui-component helpers (safe to call each frame)
-        gpui_component::init(cx);
+        crate::gui::theme::init_components(cx);
 
         // Left: list of plugins as buttons. Right: details for selected plugin.
         let list_col = {
@@ -1,39 +1,369 @@
-use chat_core::PluginEntry;
+use chat_core::plugins::approval::{declared_capabilities, describe_capabilities};
+use chat_core::plugins::settings::{
+    load_plugin_settings, save_plugin_settings, PluginSetting, SettingType,
+};
+use chat_core::{
+    approval_status, approve_plugin, archive_sha256, disable_plugin, discover_plugins,
+    enable_plugin, install_plugin, revoke_approval, ApprovalStatus, InstallOptions, PluginEntry,
+};
 use gpui::*;
 use gpui_component::button::Button;
+use gpui_component::input::{Input, InputState};
 use gpui_component::StyledExt;
 use std::path::{Path, PathBuf};
 
 pub struct PluginListView {
-    _repo_root: PathBuf,
+    repo_root: PathBuf,
     plugins: Vec<PluginEntry>,
     selected: Option<usize>,
+    status: Option<String>,
+    /// 選択中のプラグインの `[settings]` フォーム
+    settings_form: Option<SettingsForm>,
+    /// sha256 の確認待ちのアーカイブ
+    pending_install: Option<PendingInstall>,
+}
+
+struct PendingInstall {
+    source: String,
+    sha256: String,
+}
+
+struct SettingsForm {
+    plugin: String,
+    fields: Vec<(PluginSetting, FieldInput)>,
+}
+
+enum FieldInput {
+    Text(Entity<InputState>),
+    Toggle(bool),
 }
 
 impl PluginListView {
     pub fn new(repo_root: &Path, plugins: Vec<PluginEntry>) -> Self {
         let selected = if plugins.is_empty() { None } else { Some(0) };
         Self {
-            _repo_root: repo_root.to_path_buf(),
+            repo_root: repo_root.to_path_buf(),
             plugins,
             selected,
+            status: None,
+            settings_form: None,
+            pending_install: None,
+        }
+    }
+
+    /// 選択が変わったら保存値を読み込んでフォームを作り直す
+    fn ensure_settings_form(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.selected.and_then(|idx| self.plugins.get(idx)) else {
+            self.settings_form = None;
+            return;
+        };
+        if self
+            .settings_form
+            .as_ref()
+            .is_some_and(|form| form.plugin == entry.dir_name)
+        {
+            return;
         }
+        let schema = entry
+            .metadata
+            .as_ref()
+            .map(|m| m.settings.clone())
+            .unwrap_or_default();
+        let values = load_plugin_settings(&entry.dir_name, &schema);
+        let fields = schema
+            .into_iter()
+            .map(|setting| {
+                let value = values
+                    .get(&setting.key)
+                    .cloned()
+                    .unwrap_or_else(|| setting.default.clone());
+                let input = match setting.setting_type {
+                    SettingType::Boolean => FieldInput::Toggle(value.as_bool().unwrap_or(false)),
+                    _ => {
+                        let text = PluginSetting::display_value(&value);
+                        FieldInput::Text(cx.new(|cx| {
+                            let mut state = InputState::new(window, cx);
+                            state.set_value(&text, window, cx);
+                            state
+                        }))
+                    }
+                };
+                (setting, input)
+            })
+            .collect();
+        self.settings_form = Some(SettingsForm {
+            plugin: entry.dir_name.clone(),
+            fields,
+        });
+    }
+
+    /// フォームの値を型に合わせて変換し、`app-config` に保存する
+    fn save_settings(&mut self, cx: &mut Context<Self>) {
+        let Some(form) = &self.settings_form else {
+            return;
+        };
+        let mut values = serde_json::Map::new();
+        let mut errors = Vec::new();
+        for (setting, input) in &form.fields {
+            let parsed = match input {
+                FieldInput::Text(state) => setting.parse_input(&state.read(cx).value()),
+                FieldInput::Toggle(checked) => Ok(serde_json::Value::Bool(*checked)),
+            };
+            match parsed {
+                Ok(value) => {
+                    values.insert(setting.key.clone(), value);
+                }
+                Err(err) => errors.push(format!("{:#}", err)),
+            }
+        }
+        self.status = Some(if !errors.is_empty() {
+            format!("Invalid settings: {}", errors.join("; "))
+        } else {
+            match save_plugin_settings(&form.plugin, &values) {
+                Ok(()) => format!("Saved settings for {}", form.plugin),
+                Err(err) => format!("Failed to save settings: {:#}", err),
+            }
+        });
+        cx.notify();
+    }
+
+    fn settings_section(&self, cx: &mut Context<Self>) -> Option<Div> {
+        let form = self
+            .settings_form
+            .as_ref()
+            .filter(|f| !f.fields.is_empty())?;
+        let mut section = div().v_flex().gap_2().child(div().child("Settings"));
+        for (idx, (setting, input)) in form.fields.iter().enumerate() {
+            let field = match input {
+                FieldInput::Text(state) => div()
+                    .v_flex()
+                    .gap_1()
+                    .child(div().child(format!("{}:", setting.key)))
+                    .child(Input::new(state)),
+                FieldInput::Toggle(checked) => {
+                    let label = if *checked {
+                        format!("[✓] {}", setting.key)
+                    } else {
+                        format!("[ ] {}", setting.key)
+                    };
+                    div().child(Button::new(("toggle_setting", idx)).label(label).on_click(
+                        cx.listener(move |this, _ev, _window, cx| {
+                            if let Some(FieldInput::Toggle(checked)) = this
+                                .settings_form
+                                .as_mut()
+                                .and_then(|form| form.fields.get_mut(idx))
+                                .map(|(_, input)| input)
+                            {
+                                *checked = !*checked;
+                            }
+                            cx.notify();
+                        }),
+                    ))
+                }
+            };
+            section = section.child(field).children(
+                setting
+                    .description
+                    .clone()
+                    .map(|d| div().text_sm().child(d)),
+            );
+        }
+        Some(
+            section.child(
+                Button::new("save_plugin_settings")
+                    .label("Save settings")
+                    .on_click(cx.listener(|this, _ev, _window, cx| {
+                        this.save_settings(cx);
+                    })),
+            ),
+        )
+    }
+
+    /// アーカイブを選び、sha256 を表示して確認を求める（マニフェストはそのままインストール）
+    fn install_from_archive(&mut self, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Install".into()),
+        });
+
+        cx.spawn(async move |this, cx| {
+            let Some(path) = paths
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .flatten()
+                .and_then(|p| p.into_iter().next())
+            else {
+                return;
+            };
+            let source = path.to_string_lossy().into_owned();
+            if source.ends_with(".toml") {
+                // [targets] の sha256 で検証される
+                let _ = this.update(cx, |view, cx| {
+                    view.run_install(source, InstallOptions::default(), cx);
+                });
+                return;
+            }
+
+            let archive = source.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?;
+                    runtime.block_on(archive_sha256(&archive))
+                })
+                .await;
+            let _ = this.update(cx, |view, cx| {
+                match result {
+                    Ok(sha256) => {
+                        view.status = None;
+                        view.pending_install = Some(PendingInstall { source, sha256 });
+                    }
+                    Err(err) => view.status = Some(format!("Install failed: {:#}", err)),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// 確認済みの sha256 でインストールする
+    fn confirm_install(&mut self, cx: &mut Context<Self>) {
+        let Some(pending) = self.pending_install.take() else {
+            return;
+        };
+        let options = InstallOptions {
+            sha256: Some(pending.sha256),
+            replace: false,
+        };
+        self.run_install(pending.source, options, cx);
+    }
+
+    /// バックグラウンドでインストールし、一覧を読み直す
+    fn run_install(&mut self, source: String, options: InstallOptions, cx: &mut Context<Self>) {
+        let root = self.repo_root.clone();
+        self.status = Some(format!("Installing {}…", source));
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?;
+                    runtime.block_on(install_plugin(&root, &source, &options))
+                })
+                .await;
+
+            let _ = this.update(cx, |view, cx| {
+                view.status = Some(match result {
+                    Ok(record) => format!("Installed {}", record.name),
+                    Err(err) => format!("Install failed: {:#}", err),
+                });
+                view.reload_plugins();
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn reload_plugins(&mut self) {
+        if let Ok(plugins) = discover_plugins(&self.repo_root) {
+            self.selected = match self.selected {
+                Some(idx) if idx < plugins.len() => Some(idx),
+                _ if plugins.is_empty() => None,
+                _ => Some(0),
+            };
+            self.plugins = plugins;
+        }
+    }
+
+    /// 選択中のプラグインを `enabled.json` に追加する（取り除く）
+    fn set_enabled(&mut self, enable: bool, cx: &mut Context<Self>) {
+        let Some(entry) = self.selected.and_then(|idx| self.plugins.get(idx)) else {
+            return;
+        };
+        let name = entry.dir_name.clone();
+        let result = if enable {
+            enable_plugin(&self.repo_root, &name).map(|_| format!("Enabled {}", name))
+        } else {
+            disable_plugin(&self.repo_root, &name).map(|_| format!("Disabled {}", name))
+        };
+        self.status =
+            Some(result.unwrap_or_else(|err| format!("Failed to update {}: {}", name, err)));
+        self.reload_plugins();
+        cx.notify();
+    }
+
+    /// インストール前に表示する sha256 の確認欄
+    fn pending_install_section(&self, cx: &mut Context<Self>) -> Option<Div> {
+        let pending = self.pending_install.as_ref()?;
+        Some(
+            div()
+                .v_flex()
+                .gap_2()
+                .child(div().child(format!("Install {}?", pending.source)))
+                .child(div().child(format!("sha256: {}", pending.sha256)))
+                .child(
+                    div().text_sm().child(
+                        "Compare this checksum with the one published by the plugin author.",
+                    ),
+                )
+                .child(
+                    div()
+                        .h_flex()
+                        .gap_2()
+                        .child(Button::new("confirm_install").label("Install").on_click(
+                            cx.listener(|this, _ev, _window, cx| {
+                                this.confirm_install(cx);
+                            }),
+                        ))
+                        .child(Button::new("cancel_install").label("Cancel").on_click(
+                            cx.listener(|this, _ev, _window, cx| {
+                                this.pending_install = None;
+                                cx.notify();
+                            }),
+                        )),
+                ),
+        )
+    }
+
+    /// 選択中のプラグインの承認を記録する（取り消す）
+    fn set_approval(&mut self, approve: bool, cx: &mut Context<Self>) {
+        let Some(entry) = self.selected.and_then(|idx| self.plugins.get(idx)) else {
+            return;
+        };
+        let result = if approve {
+            approve_plugin(entry).map(|_| format!("Approved {}", entry.dir_name))
+        } else {
+            revoke_approval(entry).map(|_| format!("Revoked approval for {}", entry.dir_name))
+        };
+        self.status = Some(result.unwrap_or_else(|err| format!("Approval failed: {:#}", err)));
+        cx.notify();
     }
 }
 
 impl gpui::Render for PluginListView {
     fn render(
         &mut self,
-        _window: &mut gpui::Window,
+        window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl IntoElement {
         // Init gpui-component helpers (safe to call each frame)
-        gpui_component::init(cx);
+        crate::gui::theme::init_components(cx);
+        self.ensure_settings_form(window, cx);
+        let settings_section = self.settings_section(cx);
+        let pending_install = self.pending_install_section(cx);
 
         // Left: list of plugins as buttons. Right: details for selected plugin.
         let list_col = {
             let mut col = div().v_flex().gap_2().size_full();
-            for entry in self.plugins.iter() {
+            for (idx, entry) in self.plugins.iter().enumerate() {
                 let title = entry
                     .metadata
                     .as_ref()
@@ -43,8 +373,12 @@ impl gpui::Render for PluginListView {
                 // Convert to SharedString so ElementId can be built from it
                 let title_ss = gpui::SharedString::from(title.clone());
 
-                // Render as simple buttons (no click wiring yet).
-                let btn = Button::new(title_ss.clone()).label(title_ss.clone());
+                let btn = Button::new(title_ss.clone())
+                    .label(title_ss.clone())
+                    .on_click(cx.listener(move |this, _ev, _window, cx| {
+                        this.selected = Some(idx);
+                        cx.notify();
+                    }));
                 col = col.child(btn);
             }
             col
@@ -62,14 +396,52 @@ impl gpui::Render for PluginListView {
                     .as_ref()
                     .and_then(|m| m.description.clone())
                     .unwrap_or_default();
+                let status = approval_status(entry);
+                let capabilities = format!(
+                    "Capabilities: {}",
+                    describe_capabilities(&declared_capabilities(entry))
+                );
                 let name_ss = gpui::SharedString::from(name);
                 let desc_ss = gpui::SharedString::from(desc);
+                let approval_button = match status {
+                    ApprovalStatus::Pending | ApprovalStatus::Changed => Some(
+                        Button::new("approve_plugin")
+                            .label("Approve")
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.set_approval(true, cx);
+                            })),
+                    ),
+                    ApprovalStatus::Approved => Some(
+                        Button::new("revoke_plugin")
+                            .label("Revoke")
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.set_approval(false, cx);
+                            })),
+                    ),
+                    ApprovalStatus::NotRequired => None,
+                };
                 div()
                     .v_flex()
                     .gap_2()
                     .child(div().child(name_ss))
                     .child(div().child(desc_ss))
-                    .child(Button::new("enable").label("Enable"))
+                    .child(div().child(format!("Approval: {}", status.label())))
+                    .child(div().child(capabilities))
+                    .child(if entry.enabled {
+                        Button::new("disable_plugin")
+                            .label("Disable")
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.set_enabled(false, cx);
+                            }))
+                    } else {
+                        Button::new("enable_plugin")
+                            .label("Enable")
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.set_enabled(true, cx);
+                            }))
+                    })
+                    .children(approval_button)
+                    .children(settings_section)
             } else {
                 div().child("No plugin selected")
             }
@@ -77,12 +449,32 @@ impl gpui::Render for PluginListView {
             div().child("No plugin selected")
         };
 
-        // Root layout: horizontal split
-        div()
+        let toolbar = div()
             .h_flex()
-            .gap_4()
+            .gap_2()
+            .child(
+                Button::new("install_plugin")
+                    .label("Install…")
+                    .on_click(cx.listener(|this, _ev, _window, cx| {
+                        this.install_from_archive(cx);
+                    })),
+            )
+            .children(self.status.clone());
+
+        // Root layout: toolbar above a horizontal split
+        div()
+            .v_flex()
+            .gap_2()
             .size_full()
-            .child(list_col.flex_grow())
-            .child(detail_col.flex_grow())
+            .child(toolbar)
+            .children(pending_install)
+            .child(
+                div()
+                    .h_flex()
+                    .gap_4()
+                    .size_full()
+                    .child(list_col.flex_grow())
+                    .child(detail_col.flex_grow()),
+            )
     }
 }
@@ -1,8 +1,12 @@
+use crate::gui::keymap::AppAction;
+use crate::gui::theme::{apply_theme, custom_themes, themes_dir};
 use crate::gui::window_options_with_title;
+use app_config::{default_keymap_path, Keymap};
 use gpui::*;
 use gpui_component::button::*;
 use gpui_component::input::{Input, InputState};
 use gpui_component::{Root, StyledExt};
+use neko_ui::current_theme;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -11,8 +15,15 @@ pub struct SettingsView {
     ollama_url_input: gpui::Entity<InputState>,
     model_input: gpui::Entity<InputState>,
     max_history_input: gpui::Entity<InputState>,
+    /// コンソールに残すログの件数（再起動で反映）
+    console_log_limit_input: gpui::Entity<InputState>,
     use_langchain: Rc<RefCell<bool>>,
     status_message: Rc<RefCell<Option<String>>>,
+    keymap: Keymap,
+    /// 選択中のテーマ（"system" / "dark" / "light" / テーマファイルの名前）
+    theme: String,
+    /// テーマファイルから読み込んだテーマの名前
+    custom_theme_names: Vec<String>,
     _subscriptions: Vec<gpui::Subscription>,
 }
 
@@ -40,6 +51,13 @@ impl SettingsView {
             state
         });
 
+        let console_log_limit_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx);
+            let limit_value = config.console_log_capacity.to_string();
+            state.set_value(&limit_value, window, cx);
+            state
+        });
+
         let use_langchain = Rc::new(RefCell::new(config.use_langchain));
         let status_message = Rc::new(RefCell::new(None));
 
@@ -47,8 +65,15 @@ impl SettingsView {
             ollama_url_input,
             model_input,
             max_history_input,
+            console_log_limit_input,
             use_langchain,
             status_message,
+            keymap: Keymap::load_or_default(),
+            theme: config.theme.clone(),
+            custom_theme_names: custom_themes()
+                .into_iter()
+                .map(|theme| theme.name)
+                .collect(),
             _subscriptions: Vec::new(),
         }
     }
@@ -60,7 +85,7 @@ impl gpui::Render for SettingsView {
         _window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl IntoElement {
-        gpui_component::init(cx);
+        crate::gui::theme::init_components(cx);
 
         let mut content = div().v_flex().gap_4().p_4().size_full();
 
@@ -94,6 +119,15 @@ impl gpui::Render for SettingsView {
                 .child(Input::new(&self.max_history_input)),
         );
 
+        // コンソールのログ件数
+        content = content.child(
+            div()
+                .v_flex()
+                .gap_2()
+                .child(div().child("Console Log Limit (applied on restart):"))
+                .child(Input::new(&self.console_log_limit_input)),
+        );
+
         // LangChain 使用設定（ボタンで切り替え）
         let use_langchain_ref = self.use_langchain.clone();
         let is_checked = *use_langchain_ref.borrow();
@@ -121,6 +155,7 @@ impl gpui::Render for SettingsView {
         let ollama_input = self.ollama_url_input.clone();
         let model_input = self.model_input.clone();
         let max_input = self.max_history_input.clone();
+        let console_limit_input = self.console_log_limit_input.clone();
         let use_langchain = self.use_langchain.clone();
 
         content = content.child(
@@ -132,6 +167,7 @@ impl gpui::Render for SettingsView {
                         let ollama_url = ollama_input.read(cx).value().to_string();
                         let model = model_input.read(cx).value().to_string();
                         let max_history_str = max_input.read(cx).value().to_string();
+                        let console_limit_str = console_limit_input.read(cx).value().to_string();
 
                         // バリデーション
                         let max_history = match max_history_str.parse::<usize>() {
@@ -143,12 +179,23 @@ impl gpui::Render for SettingsView {
                                 return;
                             }
                         };
+                        let console_log_capacity = match console_limit_str.trim().parse::<usize>() {
+                            Ok(n) if n > 0 => n,
+                            _ => {
+                                *status_msg.borrow_mut() = Some(
+                                    "Error: Console log limit must be a positive number"
+                                        .to_string(),
+                                );
+                                return;
+                            }
+                        };
 
                         // 設定を作成して保存
                         let mut config = app_config::AppConfig::load_or_default();
                         config.ollama_base_url = ollama_url;
                         config.default_model = model;
                         config.max_history_messages = max_history;
+                        config.console_log_capacity = console_log_capacity;
                         config.use_langchain = *use_langchain.borrow();
 
                         match config.save() {
@@ -166,6 +213,12 @@ impl gpui::Render for SettingsView {
             ),
         );
 
+        // テーマ（選ぶとすぐに保存して全ウィンドウに反映）
+        content = content.child(self.render_theme_picker(cx));
+
+        // キーボードショートカット（変更は keymap.toml を編集し、再起動で反映）
+        content = content.child(self.render_key_bindings(cx));
+
         // ステータスメッセージ
         if let Some(msg) = self.status_message.borrow().as_ref() {
             content = content.child(div().child(msg.clone()));
@@ -175,6 +228,123 @@ impl gpui::Render for SettingsView {
     }
 }
 
+impl SettingsView {
+    fn render_theme_picker(&self, cx: &mut gpui::Context<Self>) -> Div {
+        let choices = [("system", "System"), ("dark", "Dark"), ("light", "Light")]
+            .into_iter()
+            .map(|(value, label)| (value.to_string(), label.to_string()))
+            .chain(
+                self.custom_theme_names
+                    .iter()
+                    .map(|name| (name.clone(), name.clone())),
+            );
+
+        let buttons = choices.enumerate().map(|(idx, (value, label))| {
+            let label = if self.theme.eq_ignore_ascii_case(&value) {
+                format!("[✓] {}", label)
+            } else {
+                format!("[ ] {}", label)
+            };
+            Button::new(SharedString::from(format!("theme_{}", idx)))
+                .label(label)
+                .on_click(cx.listener(move |this: &mut Self, _event, _window, cx| {
+                    this.select_theme(&value, cx);
+                }))
+        });
+
+        div()
+            .v_flex()
+            .gap_1()
+            .child(div().child("Theme:"))
+            .child(div().h_flex().flex_wrap().gap_2().children(buttons))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(current_theme().colors.text_subtle)
+                    .child(format!(
+                        "Put theme files (*.toml) in {} to add color schemes",
+                        themes_dir().display()
+                    )),
+            )
+    }
+
+    fn select_theme(&mut self, theme: &str, cx: &mut gpui::Context<Self>) {
+        self.theme = theme.to_string();
+        let mut config = app_config::AppConfig::load_or_default();
+        config.theme = self.theme.clone();
+        if let Err(e) = config.save() {
+            *self.status_message.borrow_mut() = Some(format!("Error saving theme: {}", e));
+        }
+        apply_theme(&self.theme, cx);
+        cx.notify();
+    }
+
+    fn render_key_bindings(&self, cx: &mut gpui::Context<Self>) -> Div {
+        let keymap_path = default_keymap_path();
+        let colors = current_theme().colors;
+        let rows = AppAction::ALL.map(|action| {
+            let keys = self.keymap.keys_for(action.name());
+            let keys = if keys.is_empty() {
+                "—".to_string()
+            } else {
+                keys.join(", ")
+            };
+            div()
+                .h_flex()
+                .gap_4()
+                .child(div().w(px(220.0)).child(action.label()))
+                .child(
+                    div()
+                        .w(px(180.0))
+                        .text_color(colors.accent_text)
+                        .child(keys),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(colors.text_subtle)
+                        .child(action.name()),
+                )
+        });
+
+        let mut section = div()
+            .v_flex()
+            .gap_1()
+            .child(div().child("Keyboard Shortcuts:"))
+            .children(rows)
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(colors.text_subtle)
+                    .child(format!(
+                        "Edit {} to change bindings (applied on restart)",
+                        keymap_path.display()
+                    )),
+            );
+
+        if !keymap_path.exists() {
+            let status_msg = self.status_message.clone();
+            let keymap = self.keymap.clone();
+            section = section.child(
+                div().h_flex().child(
+                    Button::new("write_keymap")
+                        .label("Create keymap.toml")
+                        .on_click(cx.listener(move |_this: &mut Self, _event, _window, cx| {
+                            let message = match keymap.save_to_file(&keymap_path) {
+                                Ok(()) => format!("Wrote {}", keymap_path.display()),
+                                Err(e) => format!("Error writing keymap: {}", e),
+                            };
+                            *status_msg.borrow_mut() = Some(message);
+                            cx.notify();
+                        })),
+                ),
+            );
+        }
+
+        section
+    }
+}
+
 /// 設定ウィンドウを開く
 pub fn open_settings_window(cx: &mut App) {
     let _ = cx.open_window(window_options_with_title("Settings"), move |window, cx| {
@@ -0,0 +1,55 @@
+//! 配色テーマの適用（設定値・OS の明暗・テーマファイルから決めて全ウィンドウに反映する）
+
+use app_config::{get_default_data_dir, AppConfig};
+use gpui::{App, WindowAppearance};
+use gpui_component::ThemeMode;
+use neko_ui::{
+    current_theme, load_themes_from_dir, resolve_theme, set_current_theme, Theme, ThemeAppearance,
+};
+use std::path::PathBuf;
+
+/// テーマファイル（`*.toml`）を置くディレクトリ
+pub fn themes_dir() -> PathBuf {
+    get_default_data_dir().join("themes")
+}
+
+/// テーマファイルから読み込んだテーマ
+pub fn custom_themes() -> Vec<Theme> {
+    load_themes_from_dir(&themes_dir())
+}
+
+/// OS の明暗設定
+pub fn system_appearance(cx: &App) -> ThemeAppearance {
+    match cx.window_appearance() {
+        WindowAppearance::Dark | WindowAppearance::VibrantDark => ThemeAppearance::Dark,
+        WindowAppearance::Light | WindowAppearance::VibrantLight => ThemeAppearance::Light,
+    }
+}
+
+/// 設定値（"system" / "dark" / "light" / テーマ名）のテーマに切り替えて再描画する
+pub fn apply_theme(preference: &str, cx: &mut App) {
+    let theme = resolve_theme(preference, system_appearance(cx), &custom_themes());
+    set_current_theme(theme);
+    sync_component_theme(cx);
+    cx.refresh_windows();
+}
+
+/// 保存済みの設定のテーマを適用する（起動時と OS の明暗が変わったとき）
+pub fn apply_configured_theme(cx: &mut App) {
+    apply_theme(&AppConfig::load_or_default().theme, cx);
+}
+
+/// gpui-component を初期化し、入力欄やボタンの明暗を現在のテーマに合わせる。
+/// `gpui_component::init` は OS の明暗に戻すため、各ビューの描画ではこちらを呼ぶ
+pub fn init_components(cx: &mut App) {
+    gpui_component::init(cx);
+    sync_component_theme(cx);
+}
+
+fn sync_component_theme(cx: &mut App) {
+    let mode = match current_theme().appearance {
+        ThemeAppearance::Dark => ThemeMode::Dark,
+        ThemeAppearance::Light => ThemeMode::Light,
+    };
+    gpui_component::Theme::change(mode, None, cx);
+}
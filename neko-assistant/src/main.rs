@@ -43,7 +43,7 @@ enum Commands {
     // },
     /// Execute a chat prompt with MCP tools and plugins
     Chat {
-        /// Prompt to send to the LLM
+        /// Prompt to send to the LLM (slash commands such as `/tool` are expanded first)
         #[arg(short, long)]
         prompt: String,
         /// Ollama model identifier
@@ -70,14 +70,15 @@ enum Commands {
         #[command(subcommand)]
         action: TokenAction,
     },
-    /// Run the external research CLI test runner (thin wrapper)
-    CliTest {
-        /// Path to test script JSON file
-        #[arg(short, long)]
-        script: std::path::PathBuf,
-        /// Optionally run discovery from the workspace target/debug/plugins directory
-        #[arg(long)]
-        use_target_plugins: bool,
+    /// Manage encryption at rest for conversation history and stored tokens
+    Encryption {
+        #[command(subcommand)]
+        action: EncryptionAction,
+    },
+    /// Install, approve and develop plugins
+    Plugin {
+        #[command(subcommand)]
+        action: PluginAction,
     },
 }
 
@@ -93,6 +94,365 @@ enum TokenAction {
     Remove { service: String, name: String },
 }
 
+#[derive(Subcommand)]
+enum EncryptionAction {
+    /// show the current encryption mode
+    Status,
+    /// enable encryption (mode: keyring, passphrase or file) and re-encrypt existing data
+    Enable {
+        mode: String,
+        /// key file path for the file mode (defaults to ~/.neko-assistant/keys/storage.key)
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
+    /// generate a new key (or passphrase) and re-encrypt conversations and tokens
+    Rotate,
+    /// decrypt everything and store it as plain text again
+    Disable,
+    /// finish an enable/rotate/disable that was interrupted
+    Resume,
+    /// roll an interrupted enable/rotate/disable back to the previous key
+    Abort,
+}
+
+#[derive(Subcommand)]
+enum PluginAction {
+    /// install from a .zip/.tar.gz (path or URL) or a manifest with [targets]
+    Install {
+        source: String,
+        /// expected sha256 of the archive (required unless installing from a manifest)
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+    /// reinstall from the recorded source, or from a new one
+    Update {
+        name: String,
+        source: Option<String>,
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+    /// remove a plugin directory and its install record
+    Uninstall { name: String },
+    /// allow a plugin to load its library/wasm with the declared capabilities
+    Approve { name: String },
+    /// withdraw an approval so the plugin is no longer loaded
+    Revoke { name: String },
+    /// generate a plugin crate from a template
+    New {
+        name: String,
+        /// prompt_builder or tool
+        #[arg(long, default_value = "prompt_builder")]
+        kind: String,
+        /// directory to create the crate in (default: current directory)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// check plugin.toml and load the built library (ABI, entrypoint, exports)
+    Validate {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+    },
+    /// run a prompt builder test script (expected_prompt_contains / _regex / expected_tool_names)
+    Test {
+        script: PathBuf,
+        /// test only this plugin directory instead of the installed plugins and builtins
+        #[arg(long)]
+        plugin: Option<PathBuf>,
+    },
+}
+
+async fn plugin_command(repo: &Path, action: PluginAction) -> anyhow::Result<()> {
+    use chat_core::plugins::approval::describe_capabilities;
+    use chat_core::plugins::dev::{
+        dev_prompt_registry, scaffold_plugin, validate_plugin, ScaffoldOptions,
+    };
+    use chat_core::plugins::metadata::PluginKind;
+    use chat_core::plugins::test_runner::{load_test_script, run_test_script};
+    use chat_core::{
+        approve_plugin, install_plugin, register_builtin_prompt_builders, revoke_approval,
+        uninstall_plugin, update_plugin, InstallOptions, PromptBuilderRegistry,
+    };
+
+    match action {
+        PluginAction::Install { source, sha256 } => {
+            let options = InstallOptions {
+                sha256,
+                replace: false,
+            };
+            let record = install_plugin(repo, &source, &options).await?;
+            println!(
+                "Installed plugin: {} {} (sha256 {})",
+                record.name,
+                record.version.unwrap_or_default(),
+                record.sha256
+            );
+        }
+        PluginAction::Update {
+            name,
+            source,
+            sha256,
+        } => {
+            let options = InstallOptions {
+                sha256,
+                replace: true,
+            };
+            let record = update_plugin(repo, &name, source.as_deref(), &options).await?;
+            println!(
+                "Updated plugin: {} {}",
+                record.name,
+                record.version.unwrap_or_default()
+            );
+        }
+        PluginAction::Uninstall { name } => {
+            uninstall_plugin(repo, &name)?;
+            println!("Uninstalled plugin: {}", name);
+        }
+        PluginAction::Approve { name } => {
+            let entry = find_plugin(repo, &name)?;
+            let approval = approve_plugin(&entry)?;
+            println!(
+                "Approved plugin: {} (capabilities: {}, sha256 {})",
+                name,
+                describe_capabilities(&approval.capabilities),
+                approval.sha256
+            );
+        }
+        PluginAction::Revoke { name } => {
+            let entry = find_plugin(repo, &name)?;
+            revoke_approval(&entry)?;
+            println!("Revoked approval: {}", name);
+        }
+        PluginAction::New { name, kind, dir } => {
+            let kind = match kind.as_str() {
+                "prompt_builder" => PluginKind::PromptBuilder,
+                "tool" => PluginKind::Tool,
+                other => anyhow::bail!("unsupported kind '{}': use prompt_builder or tool", other),
+            };
+            let spi = repo.join("crates").join("prompt-spi");
+            let prompt_spi = if spi.exists() {
+                let path = std::path::absolute(&spi)?;
+                format!(
+                    "{{ path = '{}' }}",
+                    path.display().to_string().replace('\\', "/")
+                )
+            } else {
+                "{ git = \"https://github.com/tyaro/neko_no_te\" }".to_string()
+            };
+            let parent = match dir {
+                Some(dir) => dir,
+                None => std::env::current_dir()?,
+            };
+            let dest = scaffold_plugin(&parent, &name, &ScaffoldOptions { kind, prompt_spi })?;
+            println!("Created plugin crate: {}", dest.display());
+        }
+        PluginAction::Validate { dir } => {
+            let check = validate_plugin(&dir)?;
+            println!(
+                "plugin.toml ok: {} ({:?})",
+                check.metadata.name.as_deref().unwrap_or("(unnamed)"),
+                check.metadata.kind
+            );
+            println!(
+                "  capabilities: {}",
+                describe_capabilities(&check.capabilities)
+            );
+            if let Some(binary) = &check.binary {
+                println!("  loaded: {}", binary.display());
+            }
+            for detail in &check.details {
+                println!("  {}", detail);
+            }
+            for warning in &check.warnings {
+                println!("  warning: {}", warning);
+            }
+        }
+        PluginAction::Test { script, plugin } => {
+            let registry = match plugin {
+                Some(dir) => dev_prompt_registry(&dir)?,
+                None => {
+                    let mut registry =
+                        PromptBuilderRegistry::from_plugins(&discover_plugins(repo)?);
+                    register_builtin_prompt_builders(&mut registry);
+                    registry
+                }
+            };
+            let outcomes = run_test_script(&registry, &load_test_script(&script)?);
+            for (index, outcome) in outcomes.iter().enumerate() {
+                println!(
+                    "\n== TEST #{} model={} builder={} ==",
+                    index + 1,
+                    outcome.model,
+                    outcome.builder.as_deref().unwrap_or("-")
+                );
+                if let Some(prompt) = &outcome.prompt {
+                    println!("{}", prompt);
+                }
+                for failure in &outcome.failures {
+                    println!("✗ {}", failure);
+                }
+                if outcome.passed() {
+                    println!("✓ ok");
+                }
+            }
+            let passed = outcomes.iter().filter(|o| o.passed()).count();
+            println!("\nSUMMARY: {}/{} passed", passed, outcomes.len());
+            if passed != outcomes.len() {
+                anyhow::bail!("{} test(s) failed", outcomes.len() - passed);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn find_plugin(repo: &Path, name: &str) -> anyhow::Result<chat_core::PluginEntry> {
+    discover_plugins(repo)?
+        .into_iter()
+        .find(|entry| entry.dir_name == name)
+        .ok_or_else(|| anyhow::anyhow!("plugin '{}' not found", name))
+}
+
+/// 新しいパスフレーズを渡す環境変数（未設定なら標準入力から読む）
+const NEW_PASSPHRASE_ENV: &str = "NEKO_ASSISTANT_NEW_PASSPHRASE";
+
+/// `confirm` が false なら確認の再入力を求めない（中断した切り替えの再開用）
+fn read_new_passphrase(confirm: bool) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var(NEW_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+
+    let read = |prompt: &str| -> anyhow::Result<String> {
+        use std::io::Write;
+        eprint!("{}", prompt);
+        std::io::stderr().flush()?;
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+    let passphrase = read("New passphrase: ")?;
+    if confirm && read("Confirm passphrase: ")? != passphrase {
+        anyhow::bail!("passphrases do not match");
+    }
+    Ok(passphrase)
+}
+
+/// 暗号化モード・鍵を切り替え、会話履歴とトークンを新しい鍵で書き直す
+///
+/// `mode` が None の場合は現在のモードのまま鍵だけを作り直す。
+/// 現在の鍵がパスフレーズ由来なら `NEKO_ASSISTANT_PASSPHRASE` が必要。
+fn change_encryption(
+    mode: Option<app_config::EncryptionMode>,
+    key_file: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    use app_config::EncryptionMode;
+
+    if app_config::load_pending_encryption()?.is_some() {
+        anyhow::bail!(
+            "a previous encryption change was interrupted; run `encryption resume` or `encryption abort` first"
+        );
+    }
+    let current = app_config::load_encryption_settings()?;
+    let old_key = app_config::resolve_encryption_key(&current, None)?;
+    let mode = mode.unwrap_or(current.mode);
+    if mode == EncryptionMode::Disabled && !current.is_enabled() {
+        println!("encryption is already disabled");
+        return Ok(());
+    }
+
+    let passphrase = if mode == EncryptionMode::Passphrase {
+        Some(read_new_passphrase(true)?)
+    } else {
+        None
+    };
+    let key_file = key_file.or_else(|| current.key_file.clone());
+    let (settings, new_key) =
+        app_config::prepare_encryption(mode, passphrase.as_deref(), key_file)?;
+
+    // 新しい鍵を先に保留として保存し、書き直しが途中で止まっても再開・巻き戻しできるようにする
+    app_config::stage_encryption(&settings, new_key.as_ref())?;
+    finish_encryption(&current, old_key, &settings, new_key)
+}
+
+/// 会話履歴とトークンを新しい鍵で書き直し、設定を確定する
+///
+/// 会話を先に書き直し、設定の保存に失敗したら元の鍵へ戻す。
+fn finish_encryption(
+    current: &app_config::EncryptionSettings,
+    old_key: Option<app_config::EncryptionKey>,
+    settings: &app_config::EncryptionSettings,
+    new_key: Option<app_config::EncryptionKey>,
+) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use chat_history::ConversationManager;
+
+    let storage_dir = ConversationManager::default_storage_dir()?;
+    let mut manager = ConversationManager::new(&storage_dir)?.with_encryption(old_key.clone());
+    let files = manager
+        .reencrypt_all(new_key.clone())
+        .context(INTERRUPTED_HINT)?;
+    let tokens = match app_config::apply_encryption(old_key.as_ref(), settings, new_key.as_ref()) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            manager.reencrypt_all(old_key).context(INTERRUPTED_HINT)?;
+            app_config::discard_pending_encryption()?;
+            return Err(err);
+        }
+    };
+
+    println!(
+        "encryption: {} -> {} ({} conversation file(s), {} token(s) rewritten)",
+        current.mode, settings.mode, files, tokens
+    );
+    Ok(())
+}
+
+const INTERRUPTED_HINT: &str =
+    "re-encryption did not finish; run `encryption resume` or `encryption abort`";
+
+/// 中断した暗号化の切り替えを再開する（`abort` なら元の鍵へ戻す）
+///
+/// 設定の確定まで済んでいた場合は巻き戻せないため、残りの鍵の保存だけを行う。
+fn resume_encryption(abort: bool) -> anyhow::Result<()> {
+    use app_config::EncryptionMode;
+    use chat_history::ConversationManager;
+
+    let Some(pending) = app_config::load_pending_encryption()? else {
+        println!("no interrupted encryption change");
+        return Ok(());
+    };
+    let passphrase = if pending.mode == EncryptionMode::Passphrase {
+        Some(read_new_passphrase(false)?)
+    } else {
+        None
+    };
+    let new_key = app_config::resolve_pending_encryption_key(&pending, passphrase.as_deref())?;
+
+    let current = app_config::load_encryption_settings()?;
+    if current == pending {
+        app_config::apply_encryption(new_key.as_ref(), &pending, new_key.as_ref())?;
+        println!(
+            "encryption: {} (the change had already been applied)",
+            pending.mode
+        );
+        return Ok(());
+    }
+
+    let old_key = app_config::resolve_encryption_key(&current, None)?;
+    if !abort {
+        return finish_encryption(&current, old_key, &pending, new_key);
+    }
+
+    let storage_dir = ConversationManager::default_storage_dir()?;
+    let files = ConversationManager::new(&storage_dir)?
+        .with_encryption(new_key)
+        .reencrypt_all(old_key)?;
+    app_config::discard_pending_encryption()?;
+    println!(
+        "encryption: rolled back to {} ({} conversation file(s) rewritten)",
+        current.mode, files
+    );
+    Ok(())
+}
+
 async fn test_mcp() -> anyhow::Result<()> {
     use chat_core::{load_mcp_config, McpManager};
     use std::sync::Arc;
@@ -155,7 +515,10 @@ async fn chat_cli(config: ChatCliConfig<'_>) -> anyhow::Result<()> {
         debug,
         repo,
     } = config;
-    use chat_core::{langchain_tools::build_mcp_tools, load_mcp_config, McpManager};
+    use chat_core::{
+        langchain_tools::build_mcp_tools, load_mcp_config, McpManager, SlashCommand,
+        SlashCommandRegistry, ToolRegistry,
+    };
     use std::sync::{
         atomic::{AtomicUsize, Ordering},
         Arc,
@@ -169,12 +532,15 @@ async fn chat_cli(config: ChatCliConfig<'_>) -> anyhow::Result<()> {
     }
 
     // 1. Load plugins if requested
+    let mut plugins = Vec::new();
     let mut plugin_info = Vec::new();
+    let mut plugin_tools = None;
     if enable_plugins {
         if verbose {
             eprintln!("[INFO] Discovering plugins...");
         }
-        let plugins = discover_plugins(repo)?;
+        plugins = discover_plugins(repo)?;
+        plugin_tools = Some(Arc::new(ToolRegistry::from_plugins(&plugins)));
         let enabled = plugins.iter().filter(|p| p.enabled).count();
         plugin_info = plugins
             .iter()
@@ -196,45 +562,85 @@ async fn chat_cli(config: ChatCliConfig<'_>) -> anyhow::Result<()> {
     }
 
     // 2. Load MCP tools if requested
-    let mut tools = Vec::new();
     let tool_counter = Arc::new(AtomicUsize::new(0));
+    let hook: Arc<dyn Fn() + Send + Sync> = {
+        let counter = tool_counter.clone();
+        Arc::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+    };
+    let mut manager = None;
     if enable_mcp {
         if verbose {
             eprintln!("[INFO] Loading MCP configuration...");
         }
         let configs = load_mcp_config().map_err(|e| anyhow::anyhow!(e))?;
         if !configs.is_empty() {
-            let manager = Arc::new(McpManager::new(configs));
+            let mcp = Arc::new(McpManager::new(configs));
             if verbose {
                 eprintln!("[INFO] Initializing MCP servers...");
             }
-            manager
-                .initialize_all()
-                .await
-                .map_err(|e| anyhow::anyhow!(e))?;
-
-            let hook: Arc<dyn Fn() + Send + Sync> = {
-                let counter = tool_counter.clone();
-                Arc::new(move || {
-                    counter.fetch_add(1, Ordering::SeqCst);
-                })
-            };
-
-            if verbose {
-                eprintln!("[INFO] Building LangChain tool descriptors...");
-            }
-            tools = build_mcp_tools(manager.clone(), Some(hook))
-                .await
-                .map_err(|e| anyhow::anyhow!(e))?;
-            if verbose {
-                eprintln!("[INFO] Loaded {} MCP tools", tools.len());
-            }
+            mcp.initialize_all().await.map_err(|e| anyhow::anyhow!(e))?;
+            manager = Some(mcp);
         } else if verbose {
             eprintln!("[WARN] No MCP servers configured");
         }
     }
 
-    // 3. Build LangChain agent
+    // 3. Expand slash commands (same parser as the GUI chat input)
+    let mut slash_commands = SlashCommandRegistry::new();
+    slash_commands.set_plugin_commands(&plugins);
+    if let Some(mcp) = &manager {
+        slash_commands.set_mcp_prompts(&mcp.get_all_prompts().await.unwrap_or_default());
+    }
+    let prompt = match slash_commands.parse(&prompt) {
+        None => prompt,
+        Some(Err(message)) => anyhow::bail!(message),
+        Some(Ok(SlashCommand::SendMessage(text))) => text,
+        Some(Ok(SlashCommand::McpPrompt {
+            server,
+            prompt,
+            arguments,
+        })) => {
+            let mcp = manager
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("MCP is disabled"))?;
+            mcp.get_prompt(&server, &prompt, arguments)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?
+        }
+        Some(Ok(SlashCommand::CallTool {
+            tool,
+            server,
+            arguments,
+        })) => {
+            return run_tool_command(
+                &tool,
+                server,
+                arguments,
+                manager.as_ref(),
+                plugin_tools.as_ref(),
+                &format,
+            )
+            .await;
+        }
+        Some(Ok(_)) => anyhow::bail!("This command is only available in the GUI chat input"),
+    };
+    if verbose {
+        eprintln!("[INFO] Prompt: {}", prompt);
+    }
+
+    if verbose {
+        eprintln!("[INFO] Building LangChain tool descriptors...");
+    }
+    let tools = build_mcp_tools(manager, plugin_tools, Some(hook))
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    if verbose {
+        eprintln!("[INFO] Loaded {} MCP/plugin tools", tools.len());
+    }
+
+    // 4. Build LangChain agent
     if verbose {
         eprintln!(
             "[INFO] Creating LangChain agent with {} tools...",
@@ -243,7 +649,7 @@ async fn chat_cli(config: ChatCliConfig<'_>) -> anyhow::Result<()> {
     }
     let agent = LangChainToolAgent::new(&model, tools).map_err(|e| anyhow::anyhow!(e))?;
 
-    // 4. Execute prompt
+    // 5. Execute prompt
     if verbose {
         eprintln!("[INFO] Sending prompt to LLM...");
     }
@@ -263,7 +669,7 @@ async fn chat_cli(config: ChatCliConfig<'_>) -> anyhow::Result<()> {
 
     let tool_calls = tool_counter.load(Ordering::SeqCst);
 
-    // 5. Output results
+    // 6. Output results
     match format.as_str() {
         "json" => {
             let output = serde_json::json!({
@@ -291,6 +697,56 @@ async fn chat_cli(config: ChatCliConfig<'_>) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// `/tool` をモデルを介さずに実行して結果を表示する
+async fn run_tool_command(
+    tool: &str,
+    server: Option<String>,
+    arguments: serde_json::Value,
+    manager: Option<&std::sync::Arc<chat_core::McpManager>>,
+    plugin_tools: Option<&std::sync::Arc<chat_core::ToolRegistry>>,
+    format: &str,
+) -> anyhow::Result<()> {
+    let plugin_server = plugin_tools.and_then(|registry| {
+        registry
+            .tools()
+            .into_iter()
+            .find(|(_, spec)| spec.name == tool)
+            .map(|(plugin, _)| plugin)
+    });
+    let server = match (server, plugin_server, manager) {
+        (Some(server), _, _) => server,
+        (None, Some(plugin), _) => plugin,
+        (None, None, Some(mcp)) => mcp
+            .find_server_for_tool(tool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?,
+        (None, None, None) => anyhow::bail!("Tool not found: {}", tool),
+    };
+
+    let start = std::time::Instant::now();
+    let result = match plugin_tools.filter(|registry| registry.contains(&server, tool)) {
+        Some(registry) => registry.call_tool(&server, tool, arguments).await,
+        None => match manager {
+            Some(mcp) => mcp.call_tool(&server, tool, arguments).await,
+            None => Err(format!("Tool not found: {}@{}", tool, server)),
+        },
+    }
+    .map_err(|e| anyhow::anyhow!(e))?;
+
+    match format {
+        "json" => {
+            let output = serde_json::json!({
+                "tool": format!("{}@{}", tool, server),
+                "result": result,
+                "elapsed_ms": start.elapsed().as_millis(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => println!("{}", serde_json::to_string_pretty(&result)?),
+    }
+    Ok(())
+}
+
 // async fn verify_weather(city: String, model: String) -> anyhow::Result<()> {
 //     use chat_core::{langchain_tools::build_mcp_tools, load_mcp_config, McpManager};
 //     use std::sync::{
@@ -393,9 +849,10 @@ async fn main() -> anyhow::Result<()> {
                             .unwrap_or_else(|| p.dir_name.clone());
                         let desc = meta.and_then(|m| m.description.clone()).unwrap_or_default();
                         println!(
-                            "- {}  [{}]",
+                            "- {}  [{}, {}]",
                             title,
-                            if p.enabled { "enabled" } else { "disabled" }
+                            if p.enabled { "enabled" } else { "disabled" },
+                            chat_core::approval_status(&p).label()
                         );
                         if !desc.is_empty() {
                             println!("    {}", desc);
@@ -454,6 +911,50 @@ async fn main() -> anyhow::Result<()> {
                     }
                 }
             }
+            Some(Commands::Encryption { action }) => {
+                let result = match action {
+                    EncryptionAction::Status => app_config::load_encryption_settings().and_then(|s| {
+                        println!("encryption mode: {}", s.mode);
+                        if let Some(path) = s.key_file {
+                            println!("key file: {}", path.display());
+                        }
+                        if let Some(pending) = app_config::load_pending_encryption()? {
+                            println!(
+                                "interrupted change to {} (run `encryption resume` or `encryption abort`)",
+                                pending.mode
+                            );
+                        }
+                        Ok(())
+                    }),
+                    EncryptionAction::Enable { mode, key_file } => mode
+                        .parse::<app_config::EncryptionMode>()
+                        .and_then(|mode| change_encryption(Some(mode), key_file)),
+                    EncryptionAction::Rotate => {
+                        match app_config::load_encryption_settings() {
+                            Ok(s) if !s.is_enabled() => {
+                                Err(anyhow::anyhow!("encryption is not enabled; use `encryption enable` first"))
+                            }
+                            Ok(_) => change_encryption(None, None),
+                            Err(e) => Err(e),
+                        }
+                    }
+                    EncryptionAction::Disable => {
+                        change_encryption(Some(app_config::EncryptionMode::Disabled), None)
+                    }
+                    EncryptionAction::Resume => resume_encryption(false),
+                    EncryptionAction::Abort => resume_encryption(true),
+                };
+                if let Err(e) = result {
+                    eprintln!("encryption command failed: {:#}", e);
+                    std::process::exit(1);
+                }
+            }
+            Some(Commands::Plugin { action }) => {
+                if let Err(e) = plugin_command(&repo, action).await {
+                    eprintln!("plugin command failed: {:#}", e);
+                    std::process::exit(1);
+                }
+            }
             // Some(Commands::VerifyWeather { city, model }) => {
             //     println!("Running phi4-mini weather verification...");
             //     verify_weather(city, model).await?;
@@ -479,44 +980,6 @@ async fn main() -> anyhow::Result<()> {
                 };
                 chat_cli(cli_config).await?;
             }
-            Some(Commands::CliTest { script, use_target_plugins }) => {
-                // Thin wrapper: try to run a built research/cli-test-runner binary first,
-                // otherwise fall back to `cargo run -p cli-test-runner`.
-                let exe_name = if cfg!(windows) { "cli-test-runner.exe" } else { "cli-test-runner" };
-                let bin_path = repo.join("research").join("cli-test-runner").join("target").join("debug").join(exe_name);
-
-                let status = if bin_path.exists() {
-                    std::process::Command::new(bin_path)
-                        .arg(&script)
-                        .args(if use_target_plugins { vec!["--use-target-plugins"] } else { vec![] })
-                        .status()
-                } else {
-                    let mut cmd = std::process::Command::new("cargo");
-                    cmd.current_dir(&repo)
-                        .arg("run")
-                        .arg("-p")
-                        .arg("cli-test-runner")
-                        .arg("--")
-                        .arg(&script);
-                    if use_target_plugins {
-                        cmd.arg("--use-target-plugins");
-                    }
-                    cmd.status()
-                };
-
-                match status {
-                    Ok(s) if s.success() => { /* success */ }
-                    Ok(s) => {
-                        eprintln!("cli-test-runner exited with status {}", s);
-                        std::process::exit(s.code().unwrap_or(2));
-                    }
-                    Err(e) => {
-                        eprintln!("failed to launch cli-test-runner: {}", e);
-                        std::process::exit(2);
-                    }
-                }
-            }
-
             None => {
                 // No subcommand and --cli provided: show help-ish message
                 println!("No command specified. Use --help for usage.");